@@ -0,0 +1,60 @@
+//poincare_sampling.rs
+// 庞加莱采样的耗时对比：逐个采样时刻线性查找（旧实现，O(N·M)）与单向游标（O(N + M)）
+// 运行：cargo bench --bench poincare_sampling
+use std::f64::consts::PI;
+use std::time::Instant;
+use chaos::solve_equation::{hermite_sample, sample_poincare_from_trajectory, solve, wrap_angle, State};
+use chaos::PendulumParams;
+
+// 旧实现：每个采样时刻都从头 position() 一次
+fn linear_scan(traj: &[(f64, State)], params: &PendulumParams, transient: usize, sample: usize) -> Vec<(f64, f64)> {
+    let period = 2.0 * PI / params.omega_d;
+    let mut samples = Vec::with_capacity(sample);
+    for n in transient + 1..=transient + sample {
+        let t_sample = n as f64 * period;
+        match traj.iter().position(|(t, _)| *t >= t_sample) {
+            Some(0) => samples.push((wrap_angle(traj[0].1.theta), traj[0].1.omega)),
+            Some(i) => {
+                let (t1, s1) = &traj[i - 1];
+                let (t2, s2) = &traj[i];
+                samples.push(hermite_sample(*t1, s1, *t2, s2, t_sample, params));
+            }
+            None => break,
+        }
+    }
+    samples
+}
+
+fn main() {
+    let mut params = PendulumParams::new();
+    params.l = params.g;
+    params.q = 0.5;
+    params.f_d = 1.2;
+    params.omega_d = 2.0 / 3.0;
+    let period = 2.0 * PI / params.omega_d;
+    params.dt = period / 400.0;
+
+    for sample_periods in [500, 2000, 4000] {
+        let transient_periods = 100;
+        params.t_end = period * (transient_periods + sample_periods) as f64 + params.dt;
+        let traj = solve(&params, 0.2, 0.0).expect("valid parameters");
+
+        let start = Instant::now();
+        let old = linear_scan(&traj, &params, transient_periods, sample_periods);
+        let t_old = start.elapsed();
+
+        let start = Instant::now();
+        let new = sample_poincare_from_trajectory(&traj, &params, transient_periods, sample_periods).samples;
+        let t_new = start.elapsed();
+
+        assert_eq!(old, new);
+        println!(
+            "sample_periods = {:5}, steps = {:8}: linear scan {:>10.3?}, cursor {:>10.3?}, speedup {:.0}x",
+            sample_periods,
+            traj.len(),
+            t_old,
+            t_new,
+            t_old.as_secs_f64() / t_new.as_secs_f64()
+        );
+    }
+}
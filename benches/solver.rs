@@ -0,0 +1,95 @@
+//solver.rs
+// 求解器与采样的 criterion 基准：单步 RK4、长时间 solve、三种庞加莱采样路径，以及轨迹的两种存储布局。
+// 运行：cargo bench --bench solver（结果与历史对比保存在 target/criterion/ 下）
+use std::f64::consts::PI;
+use std::hint::black_box;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use chaos::adaptive::poincare_adaptive;
+use chaos::model::StepControl;
+use chaos::solve_equation::{poincare_streaming, rk4_step, sample_poincare_from_trajectory, solve, State, TrajectoryIter};
+use chaos::{PendulumParams, Trajectory};
+
+// 经典混沌参数，每个驱动周期 200 步
+fn canonical_params() -> PendulumParams {
+    let mut params = PendulumParams::new();
+    params.l = params.g;
+    params.q = 0.5;
+    params.f_d = 1.2;
+    params.omega_d = 2.0 / 3.0;
+    params.dt = 2.0 * PI / params.omega_d / 200.0;
+    params
+}
+
+fn with_periods(params: &PendulumParams, periods: usize) -> PendulumParams {
+    let mut p = params.clone();
+    p.t_end = periods as f64 * 2.0 * PI / p.omega_d + p.dt;
+    p
+}
+
+fn bench_rk4_step(c: &mut Criterion) {
+    let params = canonical_params();
+    let state = State { theta: 0.2, omega: 0.0 };
+    c.bench_function("rk4_step", |b| b.iter(|| rk4_step(black_box(&state), black_box(1.0), &params)));
+}
+
+fn bench_solve(c: &mut Criterion) {
+    let mut group = c.benchmark_group("solve");
+    group.sample_size(10);
+    for periods in [100, 1000, 5000] {
+        let params = with_periods(&canonical_params(), periods);
+        group.throughput(Throughput::Elements((periods * 200) as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(periods), &params, |b, p| {
+            b.iter(|| solve(p, 0.2, 0.0).unwrap())
+        });
+    }
+    group.finish();
+}
+
+// 同样的采样窗口：完整轨迹后扫描、流式、自适应步长
+fn bench_poincare(c: &mut Criterion) {
+    let (transient, samples) = (100, 1000);
+    let params = with_periods(&canonical_params(), transient + samples);
+    let mut adaptive = params.clone();
+    adaptive.step_control = StepControl::Adaptive;
+
+    let mut group = c.benchmark_group("poincare");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(samples as u64));
+    group.bench_function("full_trajectory", |b| {
+        b.iter(|| {
+            let traj = solve(&params, 0.2, 0.0).unwrap();
+            sample_poincare_from_trajectory(&traj, &params, transient, samples)
+        })
+    });
+    group.bench_function("streaming", |b| b.iter(|| poincare_streaming(&params, 0.2, 0.0, transient, samples)));
+    group.bench_function("adaptive", |b| b.iter(|| poincare_adaptive(&adaptive, 0.2, 0.0, transient, samples)));
+    group.finish();
+}
+
+// 按列存放的 Trajectory 与 Vec<(f64, State)> 比较填充与逐列归约的开销
+fn bench_storage_layout(c: &mut Criterion) {
+    let params = with_periods(&canonical_params(), 2000);
+    let rows: Vec<(f64, State)> = TrajectoryIter::new(&params, 0.2, 0.0).collect();
+    let columns = Trajectory::from(rows.as_slice());
+
+    let mut group = c.benchmark_group("storage_layout");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(rows.len() as u64));
+    group.bench_function("fill/vec_of_structs", |b| {
+        b.iter(|| TrajectoryIter::new(&params, 0.2, 0.0).collect::<Vec<_>>())
+    });
+    group.bench_function("fill/struct_of_arrays", |b| {
+        b.iter(|| TrajectoryIter::new(&params, 0.2, 0.0).collect::<Trajectory>())
+    });
+    // 只读 ω 一列：平均动能
+    group.bench_function("kinetic/vec_of_structs", |b| {
+        b.iter(|| black_box(&rows).iter().map(|(_, s)| 0.5 * s.omega * s.omega).sum::<f64>())
+    });
+    group.bench_function("kinetic/struct_of_arrays", |b| {
+        b.iter(|| black_box(columns.omegas()).iter().map(|w| 0.5 * w * w).sum::<f64>())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_rk4_step, bench_solve, bench_poincare, bench_storage_layout);
+criterion_main!(benches);
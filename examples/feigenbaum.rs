@@ -0,0 +1,22 @@
+//feigenbaum.rs
+// 定位倍周期级联的阈值并估计 Feigenbaum δ：cargo run --release --example feigenbaum
+// 四个阈值需要格点间距小于周期 8 窗口的宽度（约 6e-4），因此扫描范围收窄到级联附近。
+use chaos::feigenbaum::{feigenbaum_deltas, period_doubling_thresholds};
+use chaos::PendulumParams;
+
+fn main() {
+    let mut params = PendulumParams::new();
+    params.l = params.g;
+    params.q = 0.5;
+    params.omega_d = 2.0 / 3.0;
+    params.dt = 2.0 * std::f64::consts::PI / params.omega_d / 200.0;
+
+    let doublings = period_doubling_thresholds(&params, (1.06, 1.084), 240, 4, 1000, 64, 1e-6, 1e-6);
+    for d in &doublings {
+        println!("周期 {} -> {}: f_d = {:.6}", d.period, 2 * d.period, d.f_d);
+    }
+    let thresholds: Vec<f64> = doublings.iter().map(|d| d.f_d).collect();
+    for (i, delta) in feigenbaum_deltas(&thresholds).iter().enumerate() {
+        println!("δ_{} = {:.3}", i + 1, delta);
+    }
+}
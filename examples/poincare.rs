@@ -0,0 +1,24 @@
+//poincare.rs
+// 作为库使用的最小示例：cargo run --example poincare > poincare.csv
+use chaos::{poincare_via_solve, ChaosError, PendulumParams};
+
+fn main() -> Result<(), ChaosError> {
+    let omega_d = 2.0 / 3.0;
+    let transient_periods = 100;
+    let sample_periods = 1000;
+    let period = 2.0 * std::f64::consts::PI / omega_d;
+    let params = PendulumParams::builder()
+        .l(9.8)
+        .q(0.5)
+        .f_d(1.2)
+        .omega_d(omega_d)
+        .dt(period / 200.0)
+        .t_end(period * (transient_periods + sample_periods) as f64 + period / 200.0)
+        .build()?;
+
+    println!("theta,omega");
+    for (theta, omega) in poincare_via_solve(&params, 0.2, 0.0, transient_periods, sample_periods)? {
+        println!("{:.12},{:.12}", theta, omega);
+    }
+    Ok(())
+}
@@ -0,0 +1,20 @@
+//build.rs
+// 用 cbindgen 从 src/lib.rs 生成头文件到 OUT_DIR，不改动源码树；测试会核对它与提交的 include/chaos.h 一致。
+// 导出函数改动后设置 CHAOS_FFI_UPDATE_HEADER=1 构建一次，把生成结果写回 include/chaos.h 再提交。
+fn main() {
+    let dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    let out = std::env::var("OUT_DIR").unwrap();
+    let config = cbindgen::Config::from_file(format!("{}/cbindgen.toml", dir)).expect("cbindgen.toml");
+    let bindings = cbindgen::Builder::new()
+        .with_crate(&dir)
+        .with_config(config)
+        .generate()
+        .expect("cannot generate C header");
+    bindings.write_to_file(format!("{}/chaos.h", out));
+    if std::env::var_os("CHAOS_FFI_UPDATE_HEADER").is_some() {
+        bindings.write_to_file(format!("{}/include/chaos.h", dir));
+    }
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-env-changed=CHAOS_FFI_UPDATE_HEADER");
+}
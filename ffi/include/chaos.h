@@ -0,0 +1,214 @@
+#ifndef CHAOS_H
+#define CHAOS_H
+
+/* 由 cbindgen 根据 ffi/src/lib.rs 生成，不要手工修改 */
+
+#include <stddef.h>
+
+/*
+ 返回码
+ */
+typedef enum ChaosStatus {
+  CHAOS_STATUS_OK = 0,
+  /*
+   传入了 NULL 指针
+   */
+  CHAOS_STATUS_NULL_POINTER = 1,
+  /*
+   参数名不存在或字符串不是合法的 UTF-8
+   */
+  CHAOS_STATUS_INVALID_ARGUMENT = 2,
+  /*
+   参数取值无效（dt <= 0、omega_d = 0 等）或积分时长不足以完成采样
+   */
+  CHAOS_STATUS_INVALID_PARAMS = 3,
+  /*
+   读文件失败
+   */
+  CHAOS_STATUS_IO = 4,
+} ChaosStatus;
+
+/*
+ 按行存放的结果矩阵（不透明）：rows 行、columns 列的 double
+ */
+typedef struct ChaosBuffer ChaosBuffer;
+
+/*
+ 单摆参数（不透明）
+ */
+typedef struct ChaosParams ChaosParams;
+
+/*
+ 逐步推进的求解器（不透明）：持有参数的副本与当前的 (t, θ, ω)
+ */
+typedef struct ChaosSolver ChaosSolver;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/*
+ 本线程最近一次错误的说明（UTF-8，以 NUL 结尾）；在本线程下一次调用失败之前有效，不要释放
+ */
+const char *chaos_last_error(void);
+
+/*
+ 默认参数（与 PendulumParams::new() 相同）
+ */
+struct ChaosParams *chaos_params_new(void);
+
+/*
+ 从参数文件读取（.toml 或 JSON）；失败时返回 NULL
+
+ # Safety
+ path 须为 NULL 或以 NUL 结尾的字符串。
+ */
+struct ChaosParams *chaos_params_from_file(const char *path);
+
+/*
+ # Safety
+ params 须为 NULL 或由 chaos_params_new / chaos_params_from_file 返回且尚未释放的指针。
+ */
+void chaos_params_free(struct ChaosParams *params);
+
+/*
+ 按名字设置参数：g、l、q、f_d、omega_d、dt、t_end
+
+ # Safety
+ params 须为有效的参数句柄，name 须为以 NUL 结尾的字符串。
+ */
+enum ChaosStatus chaos_params_set(struct ChaosParams *params, const char *name, double value);
+
+/*
+ 按名字读取参数，写入 *out
+
+ # Safety
+ params 须为有效的参数句柄，name 须为以 NUL 结尾的字符串，out 须指向可写的 double。
+ */
+enum ChaosStatus chaos_params_get(const struct ChaosParams *params,
+                                  const char *name,
+                                  double *out);
+
+/*
+ 检查参数（与 PendulumParams::validate 相同）
+
+ # Safety
+ params 须为有效的参数句柄。
+ */
+enum ChaosStatus chaos_params_validate(const struct ChaosParams *params);
+
+/*
+ 从 (theta0, omega0)、t = 0 出发的求解器；复制一份参数，之后修改 params 不影响求解器。参数无效时返回 NULL
+
+ # Safety
+ params 须为有效的参数句柄。
+ */
+struct ChaosSolver *chaos_solver_new(const struct ChaosParams *params,
+                                     double theta0,
+                                     double omega0);
+
+/*
+ # Safety
+ solver 须为 NULL 或由 chaos_solver_new 返回且尚未释放的指针。
+ */
+void chaos_solver_free(struct ChaosSolver *solver);
+
+/*
+ 用参数中的积分器推进 n 步，每步 dt（参数文件中 step_control 为 steps_per_period 时为 T / steps；θ 不折叠）
+
+ # Safety
+ solver 须为有效的求解器句柄。
+ */
+enum ChaosStatus chaos_solver_step(struct ChaosSolver *solver,
+                                   size_t n);
+
+/*
+ 读取当前的时间与状态；t、theta、omega 中为 NULL 的不写
+
+ # Safety
+ solver 须为有效的求解器句柄，非 NULL 的输出指针须指向可写的 double。
+ */
+enum ChaosStatus chaos_solver_state(const struct ChaosSolver *solver,
+                                    double *t,
+                                    double *theta,
+                                    double *omega);
+
+/*
+ 积分到 t_end 的完整轨迹，3 列：t, theta, omega；失败时返回 NULL
+
+ # Safety
+ params 须为有效的参数句柄。
+ */
+struct ChaosBuffer *chaos_solve(const struct ChaosParams *params, double theta0, double omega0);
+
+/*
+ 庞加莱截面，2 列：theta, omega（θ 折叠到 [-π, π)）；失败时返回 NULL
+
+ # Safety
+ params 须为有效的参数句柄。
+ */
+struct ChaosBuffer *chaos_poincare(const struct ChaosParams *params,
+                                   double theta0,
+                                   double omega0,
+                                   size_t transient_periods,
+                                   size_t sample_periods);
+
+/*
+ 驱动幅度分岔图，2 列：f_d, theta；f_d 在 [f_d_min, f_d_max] 上取 steps + 1 个值。参数无效或 omega_d = 0 时返回 NULL
+
+ # Safety
+ params 须为有效的参数句柄。
+ */
+struct ChaosBuffer *chaos_bifurcation(const struct ChaosParams *params,
+                                      double f_d_min,
+                                      double f_d_max,
+                                      size_t steps,
+                                      size_t transient_periods,
+                                      size_t sample_periods);
+
+/*
+ 最大 Lyapunov 指数：从 (theta0, omega0) 积分到 t_end，每个驱动周期重归一化一次，结果写入 *out
+
+ # Safety
+ params 须为有效的参数句柄，out 须指向可写的 double。
+ */
+enum ChaosStatus chaos_lyapunov(const struct ChaosParams *params,
+                                double theta0,
+                                double omega0,
+                                double *out);
+
+/*
+ 行数；buffer 为 NULL 时为 0
+
+ # Safety
+ buffer 须为 NULL 或有效的结果句柄。
+ */
+size_t chaos_buffer_rows(const struct ChaosBuffer *buffer);
+
+/*
+ 列数；buffer 为 NULL 时为 0
+
+ # Safety
+ buffer 须为 NULL 或有效的结果句柄。
+ */
+size_t chaos_buffer_columns(const struct ChaosBuffer *buffer);
+
+/*
+ 按行存放的数据，第 i 行第 j 列为 data[i * columns + j]；在 chaos_buffer_free 之前有效
+
+ # Safety
+ buffer 须为 NULL 或有效的结果句柄。
+ */
+const double *chaos_buffer_data(const struct ChaosBuffer *buffer);
+
+/*
+ # Safety
+ buffer 须为 NULL 或由本库返回且尚未释放的结果句柄。
+ */
+void chaos_buffer_free(struct ChaosBuffer *buffer);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* CHAOS_H */
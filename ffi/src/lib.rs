@@ -0,0 +1,497 @@
+//lib.rs
+//! chaos crate 的 C 接口：参数与求解器以不透明句柄传递，结果放在 ChaosBuffer 中由调用方取回。
+//! C、C++、Julia（ccall）都可以直接链接 libchaos_ffi，头文件为 include/chaos.h，用法见 examples/pendulum.c。
+//!
+//! 约定：
+//! - `*_new` / 返回指针的函数失败时返回 NULL，返回 ChaosStatus 的函数失败时返回非零值；
+//!   两种情况下 chaos_last_error() 都给出本线程最近一次错误的说明。
+//! - 每个 `*_new` 与返回 ChaosBuffer 的函数得到的指针都要用对应的 `*_free` 释放，`*_free(NULL)` 无操作。
+//! - 句柄不是线程安全的：同一个句柄不要在多个线程中同时使用。
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::ptr;
+use chaos::bifurcation::bifurcation_diagram;
+use chaos::lyapunov::largest_exponent;
+use chaos::solve_equation::{solve_trajectory, step, State};
+use chaos::{ChaosError, PendulumParams};
+
+/// 返回码
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChaosStatus {
+    Ok = 0,
+    /// 传入了 NULL 指针
+    NullPointer = 1,
+    /// 参数名不存在或字符串不是合法的 UTF-8
+    InvalidArgument = 2,
+    /// 参数取值无效（dt <= 0、omega_d = 0 等）或积分时长不足以完成采样
+    InvalidParams = 3,
+    /// 读文件失败
+    Io = 4,
+}
+
+/// 单摆参数（不透明）
+pub struct ChaosParams {
+    inner: PendulumParams,
+}
+
+/// 逐步推进的求解器（不透明）：持有参数的副本与当前的 (t, θ, ω)
+pub struct ChaosSolver {
+    params: PendulumParams,
+    state: State,
+    t: f64,
+}
+
+/// 按行存放的结果矩阵（不透明）：rows 行、columns 列的 double
+pub struct ChaosBuffer {
+    data: Vec<f64>,
+    columns: usize,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+fn set_error(msg: impl ToString) {
+    // 消息中的 NUL 字节替换掉，保证能转成 C 字符串
+    let msg = msg.to_string().replace('\0', " ");
+    LAST_ERROR.with(|e| *e.borrow_mut() = CString::new(msg).unwrap_or_default());
+}
+
+fn fail(e: ChaosError) -> ChaosStatus {
+    let status = match e {
+        ChaosError::Io(_) => ChaosStatus::Io,
+        _ => ChaosStatus::InvalidParams,
+    };
+    set_error(e);
+    status
+}
+
+fn null_pointer() -> ChaosStatus {
+    set_error("null pointer argument");
+    ChaosStatus::NullPointer
+}
+
+// 可按名字读写的参数
+fn field(params: &PendulumParams, name: &str) -> Option<f64> {
+    Some(match name {
+        "g" => params.g,
+        "l" => params.l,
+        "q" => params.q,
+        "f_d" => params.f_d,
+        "omega_d" => params.omega_d,
+        "dt" => params.dt,
+        "t_end" => params.t_end,
+        _ => return None,
+    })
+}
+
+fn field_mut<'a>(params: &'a mut PendulumParams, name: &str) -> Option<&'a mut f64> {
+    Some(match name {
+        "g" => &mut params.g,
+        "l" => &mut params.l,
+        "q" => &mut params.q,
+        "f_d" => &mut params.f_d,
+        "omega_d" => &mut params.omega_d,
+        "dt" => &mut params.dt,
+        "t_end" => &mut params.t_end,
+        _ => return None,
+    })
+}
+
+// C 字符串转 &str；NULL 或非 UTF-8 时记录错误
+unsafe fn to_str<'a>(s: *const c_char) -> Result<&'a str, ChaosStatus> {
+    if s.is_null() {
+        return Err(null_pointer());
+    }
+    unsafe { CStr::from_ptr(s) }.to_str().map_err(|_| {
+        set_error("string is not valid UTF-8");
+        ChaosStatus::InvalidArgument
+    })
+}
+
+fn into_buffer(points: Vec<(f64, f64)>) -> *mut ChaosBuffer {
+    let data = points.into_iter().flat_map(|(x, y)| [x, y]).collect();
+    Box::into_raw(Box::new(ChaosBuffer { data, columns: 2 }))
+}
+
+/// 本线程最近一次错误的说明（UTF-8，以 NUL 结尾）；在本线程下一次调用失败之前有效，不要释放
+#[unsafe(no_mangle)]
+pub extern "C" fn chaos_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ptr())
+}
+
+/// 默认参数（与 PendulumParams::new() 相同）
+#[unsafe(no_mangle)]
+pub extern "C" fn chaos_params_new() -> *mut ChaosParams {
+    Box::into_raw(Box::new(ChaosParams {
+        inner: PendulumParams::new(),
+    }))
+}
+
+/// 从参数文件读取（.toml 或 JSON）；失败时返回 NULL
+///
+/// # Safety
+/// path 须为 NULL 或以 NUL 结尾的字符串。
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn chaos_params_from_file(path: *const c_char) -> *mut ChaosParams {
+    let Ok(path) = (unsafe { to_str(path) }) else {
+        return ptr::null_mut();
+    };
+    match PendulumParams::from_file(path) {
+        Ok(inner) => Box::into_raw(Box::new(ChaosParams { inner })),
+        Err(e) => {
+            fail(e.into());
+            ptr::null_mut()
+        }
+    }
+}
+
+/// # Safety
+/// params 须为 NULL 或由 chaos_params_new / chaos_params_from_file 返回且尚未释放的指针。
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn chaos_params_free(params: *mut ChaosParams) {
+    if !params.is_null() {
+        drop(unsafe { Box::from_raw(params) });
+    }
+}
+
+/// 按名字设置参数：g、l、q、f_d、omega_d、dt、t_end
+///
+/// # Safety
+/// params 须为有效的参数句柄，name 须为以 NUL 结尾的字符串。
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn chaos_params_set(params: *mut ChaosParams, name: *const c_char, value: f64) -> ChaosStatus {
+    let Some(params) = (unsafe { params.as_mut() }) else {
+        return null_pointer();
+    };
+    let name = match unsafe { to_str(name) } {
+        Ok(name) => name,
+        Err(status) => return status,
+    };
+    match field_mut(&mut params.inner, name) {
+        Some(field) => {
+            *field = value;
+            ChaosStatus::Ok
+        }
+        None => {
+            set_error(format!("unknown parameter '{}'", name));
+            ChaosStatus::InvalidArgument
+        }
+    }
+}
+
+/// 按名字读取参数，写入 *out
+///
+/// # Safety
+/// params 须为有效的参数句柄，name 须为以 NUL 结尾的字符串，out 须指向可写的 double。
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn chaos_params_get(params: *const ChaosParams, name: *const c_char, out: *mut f64) -> ChaosStatus {
+    let (Some(params), false) = (unsafe { params.as_ref() }, out.is_null()) else {
+        return null_pointer();
+    };
+    let name = match unsafe { to_str(name) } {
+        Ok(name) => name,
+        Err(status) => return status,
+    };
+    match field(&params.inner, name) {
+        Some(value) => {
+            unsafe { *out = value };
+            ChaosStatus::Ok
+        }
+        None => {
+            set_error(format!("unknown parameter '{}'", name));
+            ChaosStatus::InvalidArgument
+        }
+    }
+}
+
+/// 检查参数（与 PendulumParams::validate 相同）
+///
+/// # Safety
+/// params 须为有效的参数句柄。
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn chaos_params_validate(params: *const ChaosParams) -> ChaosStatus {
+    match unsafe { params.as_ref() } {
+        Some(params) => params.inner.validate().map_or_else(fail, |_| ChaosStatus::Ok),
+        None => null_pointer(),
+    }
+}
+
+/// 从 (theta0, omega0)、t = 0 出发的求解器；复制一份参数，之后修改 params 不影响求解器。参数无效时返回 NULL
+///
+/// # Safety
+/// params 须为有效的参数句柄。
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn chaos_solver_new(params: *const ChaosParams, theta0: f64, omega0: f64) -> *mut ChaosSolver {
+    let Some(params) = (unsafe { params.as_ref() }) else {
+        null_pointer();
+        return ptr::null_mut();
+    };
+    if let Err(e) = params.inner.validate() {
+        fail(e);
+        return ptr::null_mut();
+    }
+    let mut copy = params.inner.clone();
+    copy.dt = copy.effective_dt();
+    Box::into_raw(Box::new(ChaosSolver {
+        params: copy,
+        state: State {
+            theta: theta0,
+            omega: omega0,
+        },
+        t: 0.0,
+    }))
+}
+
+/// # Safety
+/// solver 须为 NULL 或由 chaos_solver_new 返回且尚未释放的指针。
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn chaos_solver_free(solver: *mut ChaosSolver) {
+    if !solver.is_null() {
+        drop(unsafe { Box::from_raw(solver) });
+    }
+}
+
+/// 用参数中的积分器推进 n 步，每步 dt（参数文件中 step_control 为 steps_per_period 时为 T / steps；θ 不折叠）
+///
+/// # Safety
+/// solver 须为有效的求解器句柄。
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn chaos_solver_step(solver: *mut ChaosSolver, n: usize) -> ChaosStatus {
+    let Some(solver) = (unsafe { solver.as_mut() }) else {
+        return null_pointer();
+    };
+    for _ in 0..n {
+        (solver.state, solver.t) = step(&solver.state, solver.t, &solver.params);
+    }
+    ChaosStatus::Ok
+}
+
+/// 读取当前的时间与状态；t、theta、omega 中为 NULL 的不写
+///
+/// # Safety
+/// solver 须为有效的求解器句柄，非 NULL 的输出指针须指向可写的 double。
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn chaos_solver_state(
+    solver: *const ChaosSolver,
+    t: *mut f64,
+    theta: *mut f64,
+    omega: *mut f64,
+) -> ChaosStatus {
+    let Some(solver) = (unsafe { solver.as_ref() }) else {
+        return null_pointer();
+    };
+    for (out, value) in [(t, solver.t), (theta, solver.state.theta), (omega, solver.state.omega)] {
+        if let Some(out) = unsafe { out.as_mut() } {
+            *out = value;
+        }
+    }
+    ChaosStatus::Ok
+}
+
+/// 积分到 t_end 的完整轨迹，3 列：t, theta, omega；失败时返回 NULL
+///
+/// # Safety
+/// params 须为有效的参数句柄。
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn chaos_solve(params: *const ChaosParams, theta0: f64, omega0: f64) -> *mut ChaosBuffer {
+    let Some(params) = (unsafe { params.as_ref() }) else {
+        null_pointer();
+        return ptr::null_mut();
+    };
+    match solve_trajectory(&params.inner, theta0, omega0) {
+        Ok(traj) => {
+            let data = traj.iter().flat_map(|(t, s)| [t, s.theta, s.omega]).collect();
+            Box::into_raw(Box::new(ChaosBuffer { data, columns: 3 }))
+        }
+        Err(e) => {
+            fail(e);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// 庞加莱截面，2 列：theta, omega（θ 折叠到 [-π, π)）；失败时返回 NULL
+///
+/// # Safety
+/// params 须为有效的参数句柄。
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn chaos_poincare(
+    params: *const ChaosParams,
+    theta0: f64,
+    omega0: f64,
+    transient_periods: usize,
+    sample_periods: usize,
+) -> *mut ChaosBuffer {
+    let Some(params) = (unsafe { params.as_ref() }) else {
+        null_pointer();
+        return ptr::null_mut();
+    };
+    match chaos::poincare(&params.inner, theta0, omega0, transient_periods, sample_periods) {
+        Ok(samples) => into_buffer(samples),
+        Err(e) => {
+            fail(e);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// 驱动幅度分岔图，2 列：f_d, theta；f_d 在 [f_d_min, f_d_max] 上取 steps + 1 个值。参数无效或 omega_d = 0 时返回 NULL
+///
+/// # Safety
+/// params 须为有效的参数句柄。
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn chaos_bifurcation(
+    params: *const ChaosParams,
+    f_d_min: f64,
+    f_d_max: f64,
+    steps: usize,
+    transient_periods: usize,
+    sample_periods: usize,
+) -> *mut ChaosBuffer {
+    let Some(params) = (unsafe { params.as_ref() }) else {
+        null_pointer();
+        return ptr::null_mut();
+    };
+    if let Err(e) = params.inner.validate_periodic() {
+        fail(e);
+        return ptr::null_mut();
+    }
+    into_buffer(bifurcation_diagram(
+        &params.inner,
+        (f_d_min, f_d_max),
+        steps,
+        transient_periods,
+        sample_periods,
+    ))
+}
+
+/// 最大 Lyapunov 指数：从 (theta0, omega0) 积分到 t_end，每个驱动周期重归一化一次，结果写入 *out
+///
+/// # Safety
+/// params 须为有效的参数句柄，out 须指向可写的 double。
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn chaos_lyapunov(params: *const ChaosParams, theta0: f64, omega0: f64, out: *mut f64) -> ChaosStatus {
+    let (Some(params), false) = (unsafe { params.as_ref() }, out.is_null()) else {
+        return null_pointer();
+    };
+    if let Err(e) = params.inner.validate_periodic() {
+        return fail(e);
+    }
+    let mut copy = params.inner.clone();
+    copy.dt = copy.effective_dt();
+    let period = 2.0 * std::f64::consts::PI / copy.omega_d.abs();
+    let estimate = largest_exponent(&copy, State { theta: theta0, omega: omega0 }, period);
+    unsafe { *out = estimate.exponent };
+    ChaosStatus::Ok
+}
+
+/// 行数；buffer 为 NULL 时为 0
+///
+/// # Safety
+/// buffer 须为 NULL 或有效的结果句柄。
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn chaos_buffer_rows(buffer: *const ChaosBuffer) -> usize {
+    unsafe { buffer.as_ref() }.map_or(0, |b| b.data.len() / b.columns)
+}
+
+/// 列数；buffer 为 NULL 时为 0
+///
+/// # Safety
+/// buffer 须为 NULL 或有效的结果句柄。
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn chaos_buffer_columns(buffer: *const ChaosBuffer) -> usize {
+    unsafe { buffer.as_ref() }.map_or(0, |b| b.columns)
+}
+
+/// 按行存放的数据，第 i 行第 j 列为 data[i * columns + j]；在 chaos_buffer_free 之前有效
+///
+/// # Safety
+/// buffer 须为 NULL 或有效的结果句柄。
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn chaos_buffer_data(buffer: *const ChaosBuffer) -> *const f64 {
+    unsafe { buffer.as_ref() }.map_or(ptr::null(), |b| b.data.as_ptr())
+}
+
+/// # Safety
+/// buffer 须为 NULL 或由本库返回且尚未释放的结果句柄。
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn chaos_buffer_free(buffer: *mut ChaosBuffer) {
+    if !buffer.is_null() {
+        drop(unsafe { Box::from_raw(buffer) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn last_error() -> String {
+        unsafe { CStr::from_ptr(chaos_last_error()) }.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn test_c_api_round_trip() {
+        unsafe {
+            let params = chaos_params_new();
+            for (name, value) in [(c"l", 9.8), (c"q", 0.5), (c"f_d", 1.2), (c"omega_d", 2.0 / 3.0), (c"dt", 0.01), (c"t_end", 40.0)] {
+                assert_eq!(chaos_params_set(params, name.as_ptr(), value), ChaosStatus::Ok);
+            }
+            let mut q = 0.0;
+            assert_eq!(chaos_params_get(params, c"q".as_ptr(), &mut q), ChaosStatus::Ok);
+            assert_eq!(q, 0.5);
+            assert_eq!(chaos_params_set(params, c"mass".as_ptr(), 1.0), ChaosStatus::InvalidArgument);
+            assert!(last_error().contains("mass"));
+
+            // 逐步推进与一次积分到 t_end 的结果相同
+            let solver = chaos_solver_new(params, 0.2, 0.0);
+            assert_eq!(chaos_solver_step(solver, 4000), ChaosStatus::Ok);
+            let (mut t, mut theta, mut omega) = (0.0, 0.0, 0.0);
+            assert_eq!(chaos_solver_state(solver, &mut t, &mut theta, &mut omega), ChaosStatus::Ok);
+            let traj = chaos_solve(params, 0.2, 0.0);
+            assert_eq!(chaos_buffer_columns(traj), 3);
+            let rows = chaos_buffer_rows(traj);
+            let data = std::slice::from_raw_parts(chaos_buffer_data(traj), rows * 3);
+            assert_eq!(rows, 4001);
+            assert_eq!(&data[4000 * 3..], &[t, theta, omega]);
+            chaos_buffer_free(traj);
+            chaos_solver_free(solver);
+
+            let section = chaos_poincare(params, 0.2, 0.0, 1, 3);
+            assert_eq!((chaos_buffer_rows(section), chaos_buffer_columns(section)), (3, 2));
+            chaos_buffer_free(section);
+            // 积分时长不够采样
+            assert!(chaos_poincare(params, 0.2, 0.0, 100, 100).is_null());
+            assert!(last_error().contains("too short"));
+
+            let mut lambda = 0.0;
+            assert_eq!(chaos_lyapunov(params, 0.2, 0.0, &mut lambda), ChaosStatus::Ok);
+            assert!(lambda.is_finite());
+
+            // omega_d = 0 时没有驱动周期：报错而不是卡住或返回 0
+            assert_eq!(chaos_params_set(params, c"omega_d".as_ptr(), 0.0), ChaosStatus::Ok);
+            assert!(chaos_bifurcation(params, 1.0, 1.2, 2, 1, 1).is_null());
+            assert_eq!(chaos_lyapunov(params, 0.2, 0.0, &mut lambda), ChaosStatus::InvalidParams);
+            assert!(last_error().contains("omega_d"));
+            assert_eq!(chaos_params_set(params, c"omega_d".as_ptr(), 2.0 / 3.0), ChaosStatus::Ok);
+
+            assert_eq!(chaos_params_set(params, c"dt".as_ptr(), -1.0), ChaosStatus::Ok);
+            assert_eq!(chaos_params_validate(params), ChaosStatus::InvalidParams);
+            assert!(chaos_solver_new(params, 0.0, 0.0).is_null());
+            assert_eq!(chaos_solver_step(ptr::null_mut(), 1), ChaosStatus::NullPointer);
+            chaos_params_free(params);
+            chaos_buffer_free(ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn test_committed_header_is_up_to_date() {
+        let generated = include_str!(concat!(env!("OUT_DIR"), "/chaos.h"));
+        let committed = include_str!("../include/chaos.h");
+        assert!(
+            generated == committed,
+            "include/chaos.h is stale; rebuild with CHAOS_FFI_UPDATE_HEADER=1"
+        );
+    }
+}
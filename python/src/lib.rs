@@ -0,0 +1,339 @@
+//lib.rs
+//! chaos crate 的 Python 绑定（模块名 pychaos）：积分在 Rust 中完成，结果以 numpy 数组返回，
+//! 在 notebook 里直接作图与分析，不必经过 CSV。
+//!
+//! 构建：在 python/ 目录下 `maturin develop --release`，之后
+//! `import pychaos; t, theta, omega = pychaos.solve(pychaos.PendulumParams(f_d=1.2), 0.2, 0.0)`。
+//! 数组接口的测试在 tests/test_pychaos.py（`maturin develop` 之后 `pytest tests`），与 Rust API 的结果对照。
+use chaos::bifurcation::bifurcation_diagram;
+use chaos::chaos_map::lyapunov_chaos_map as chaos_map;
+use chaos::lyapunov::largest_exponent;
+use chaos::solve_equation::{solve_trajectory, State};
+use chaos::{ChaosError, PendulumParams};
+use numpy::ndarray::Array2;
+use numpy::{IntoPyArray, PyArray1, PyArray2};
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+
+type Array<'py> = Bound<'py, PyArray1<f64>>;
+
+// 文件读写错误映射为 IOError，其余（参数无效、采样不足、不收敛）为 ValueError
+fn to_py_err(e: ChaosError) -> PyErr {
+    match e {
+        ChaosError::Io(e) => PyIOError::new_err(e.to_string()),
+        other => PyValueError::new_err(other.to_string()),
+    }
+}
+
+fn array(py: Python<'_>, values: Vec<f64>) -> Array<'_> {
+    values.into_pyarray(py)
+}
+
+// (x, y) 点列拆成两个数组
+fn columns(py: Python<'_>, points: Vec<(f64, f64)>) -> (Array<'_>, Array<'_>) {
+    let (xs, ys) = points.into_iter().unzip();
+    (array(py, xs), array(py, ys))
+}
+
+/// 单摆参数；未给出的字段取 chaos::PendulumParams::new() 的默认值
+#[pyclass(name = "PendulumParams", module = "pychaos", from_py_object)]
+#[derive(Clone)]
+struct PyPendulumParams {
+    inner: PendulumParams,
+}
+
+#[pymethods]
+impl PyPendulumParams {
+    #[new]
+    #[pyo3(signature = (*, g=None, l=None, q=None, f_d=None, omega_d=None, dt=None, t_end=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        g: Option<f64>,
+        l: Option<f64>,
+        q: Option<f64>,
+        f_d: Option<f64>,
+        omega_d: Option<f64>,
+        dt: Option<f64>,
+        t_end: Option<f64>,
+    ) -> PyResult<Self> {
+        let mut inner = PendulumParams::new();
+        let fields = [
+            (&mut inner.g, g),
+            (&mut inner.l, l),
+            (&mut inner.q, q),
+            (&mut inner.f_d, f_d),
+            (&mut inner.omega_d, omega_d),
+            (&mut inner.dt, dt),
+            (&mut inner.t_end, t_end),
+        ];
+        for (field, value) in fields {
+            if let Some(v) = value {
+                *field = v;
+            }
+        }
+        inner.validate().map_err(to_py_err)?;
+        Ok(PyPendulumParams { inner })
+    }
+
+    /// 从参数文件读取（.toml 或 JSON，与命令行的 --config 相同）
+    #[staticmethod]
+    fn from_file(path: &str) -> PyResult<Self> {
+        let inner = PendulumParams::from_file(path).map_err(|e| to_py_err(e.into()))?;
+        Ok(PyPendulumParams { inner })
+    }
+
+    /// 全部参数的 JSON 文本
+    fn to_json(&self) -> PyResult<String> {
+        serde_json::to_string(&self.inner).map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    fn validate(&self) -> PyResult<()> {
+        self.inner.validate().map_err(to_py_err)
+    }
+
+    #[getter]
+    fn g(&self) -> f64 {
+        self.inner.g
+    }
+
+    #[setter]
+    fn set_g(&mut self, v: f64) {
+        self.inner.g = v;
+    }
+
+    #[getter]
+    fn l(&self) -> f64 {
+        self.inner.l
+    }
+
+    #[setter]
+    fn set_l(&mut self, v: f64) {
+        self.inner.l = v;
+    }
+
+    #[getter]
+    fn q(&self) -> f64 {
+        self.inner.q
+    }
+
+    #[setter]
+    fn set_q(&mut self, v: f64) {
+        self.inner.q = v;
+    }
+
+    #[getter]
+    fn f_d(&self) -> f64 {
+        self.inner.f_d
+    }
+
+    #[setter]
+    fn set_f_d(&mut self, v: f64) {
+        self.inner.f_d = v;
+    }
+
+    #[getter]
+    fn omega_d(&self) -> f64 {
+        self.inner.omega_d
+    }
+
+    #[setter]
+    fn set_omega_d(&mut self, v: f64) {
+        self.inner.omega_d = v;
+    }
+
+    #[getter]
+    fn dt(&self) -> f64 {
+        self.inner.dt
+    }
+
+    #[setter]
+    fn set_dt(&mut self, v: f64) {
+        self.inner.dt = v;
+    }
+
+    #[getter]
+    fn t_end(&self) -> f64 {
+        self.inner.t_end
+    }
+
+    #[setter]
+    fn set_t_end(&mut self, v: f64) {
+        self.inner.t_end = v;
+    }
+
+    fn __repr__(&self) -> String {
+        let p = &self.inner;
+        format!(
+            "PendulumParams(g={}, l={}, q={}, f_d={}, omega_d={}, dt={}, t_end={})",
+            p.g, p.l, p.q, p.f_d, p.omega_d, p.dt, p.t_end
+        )
+    }
+}
+
+impl PyPendulumParams {
+    // 按驱动周期采样或重归一化前的检查：omega_d = 0 时没有周期，否则会一直积分下去
+    fn validate_periodic(&self) -> PyResult<()> {
+        self.inner.validate_periodic().map_err(to_py_err)
+    }
+}
+
+/// 从 (theta0, omega0) 积分到 params.t_end，返回 (t, theta, omega) 三个数组
+#[pyfunction]
+fn solve<'py>(
+    py: Python<'py>,
+    params: &PyPendulumParams,
+    theta0: f64,
+    omega0: f64,
+) -> PyResult<(Array<'py>, Array<'py>, Array<'py>)> {
+    let traj = py
+        .detach(|| solve_trajectory(&params.inner, theta0, omega0))
+        .map_err(to_py_err)?;
+    Ok((
+        array(py, traj.times().to_vec()),
+        array(py, traj.thetas().to_vec()),
+        array(py, traj.omegas().to_vec()),
+    ))
+}
+
+/// 庞加莱截面：跳过 transient_periods 个驱动周期后采样 sample_periods 个点，返回 (theta, omega)
+#[pyfunction]
+fn poincare<'py>(
+    py: Python<'py>,
+    params: &PyPendulumParams,
+    theta0: f64,
+    omega0: f64,
+    transient_periods: usize,
+    sample_periods: usize,
+) -> PyResult<(Array<'py>, Array<'py>)> {
+    let samples = py
+        .detach(|| chaos::poincare(&params.inner, theta0, omega0, transient_periods, sample_periods))
+        .map_err(to_py_err)?;
+    Ok(columns(py, samples))
+}
+
+/// 驱动幅度分岔图：f_d 在 [f_d_min, f_d_max] 上取 steps + 1 个值，返回 (f_d, theta)
+#[pyfunction]
+fn bifurcation<'py>(
+    py: Python<'py>,
+    params: &PyPendulumParams,
+    f_d_min: f64,
+    f_d_max: f64,
+    steps: usize,
+    transient_periods: usize,
+    sample_periods: usize,
+) -> PyResult<(Array<'py>, Array<'py>)> {
+    params.validate_periodic()?;
+    let points = py.detach(|| {
+        bifurcation_diagram(&params.inner, (f_d_min, f_d_max), steps, transient_periods, sample_periods)
+    });
+    Ok(columns(py, points))
+}
+
+/// 最大 Lyapunov 指数：从 (theta0, omega0) 积分到 params.t_end，每 renormalization_interval（默认一个驱动周期）重归一化一次
+///
+/// 返回 (exponent, t, running)，后两个数组为每次重归一化后的 λ(t)，用来判断是否收敛。
+#[pyfunction]
+#[pyo3(signature = (params, theta0, omega0, renormalization_interval=None))]
+fn lyapunov<'py>(
+    py: Python<'py>,
+    params: &PyPendulumParams,
+    theta0: f64,
+    omega0: f64,
+    renormalization_interval: Option<f64>,
+) -> PyResult<(f64, Array<'py>, Array<'py>)> {
+    params.validate_periodic()?;
+    let mut inner = params.inner.clone();
+    inner.dt = inner.effective_dt();
+    let interval = renormalization_interval.unwrap_or(2.0 * std::f64::consts::PI / inner.omega_d.abs());
+    let estimate = py.detach(|| largest_exponent(&inner, State { theta: theta0, omega: omega0 }, interval));
+    let (t, running) = columns(py, estimate.history);
+    Ok((estimate.exponent, t, running))
+}
+
+/// (f_d, q) 网格上的最大 Lyapunov 指数，返回 (f_d, q, exponents)，exponents 的形状为 (len(q), len(f_d))
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+fn lyapunov_chaos_map<'py>(
+    py: Python<'py>,
+    params: &PyPendulumParams,
+    f_d_min: f64,
+    f_d_max: f64,
+    f_d_steps: usize,
+    q_min: f64,
+    q_max: f64,
+    q_steps: usize,
+    transient_periods: usize,
+    sample_periods: usize,
+) -> PyResult<(Array<'py>, Array<'py>, Bound<'py, PyArray2<f64>>)> {
+    params.validate_periodic()?;
+    let map = py.detach(|| {
+        chaos_map(
+            &params.inner,
+            (f_d_min, f_d_max),
+            f_d_steps,
+            (q_min, q_max),
+            q_steps,
+            transient_periods,
+            sample_periods,
+        )
+    });
+    let shape = (map.qs.len(), map.f_ds.len());
+    let exponents = Array2::from_shape_vec(shape, map.exponents).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    Ok((array(py, map.f_ds), array(py, map.qs), exponents.into_pyarray(py)))
+}
+
+#[pymodule]
+fn pychaos(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyPendulumParams>()?;
+    m.add_function(wrap_pyfunction!(solve, m)?)?;
+    m.add_function(wrap_pyfunction!(poincare, m)?)?;
+    m.add_function(wrap_pyfunction!(bifurcation, m)?)?;
+    m.add_function(wrap_pyfunction!(lyapunov, m)?)?;
+    m.add_function(wrap_pyfunction!(lyapunov_chaos_map, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::types::PyDict;
+
+    // cargo test 的解释器里不一定有 numpy，这里只检查参数类与错误映射；数组接口见 tests/test_pychaos.py
+    #[test]
+    fn test_params_class_and_errors() {
+        Python::attach(|py| {
+            let module = PyModule::new(py, "pychaos").unwrap();
+            pychaos(&module).unwrap();
+            let locals = PyDict::new(py);
+            locals.set_item("pychaos", &module).unwrap();
+
+            let params: PyPendulumParams = py
+                .eval(c"pychaos.PendulumParams(q=0.5, f_d=1.2)", None, Some(&locals))
+                .unwrap()
+                .extract()
+                .unwrap();
+            assert_eq!(params.inner.q, 0.5);
+            assert_eq!(params.inner.f_d, 1.2);
+            assert_eq!(params.inner.g, PendulumParams::new().g);
+
+            let err = py
+                .eval(c"pychaos.PendulumParams(dt=-1.0)", None, Some(&locals))
+                .unwrap_err();
+            assert!(err.is_instance_of::<PyValueError>(py));
+            let err = py
+                .eval(c"pychaos.PendulumParams.from_file('no/such/file.toml')", None, Some(&locals))
+                .unwrap_err();
+            assert!(err.is_instance_of::<PyIOError>(py));
+
+            // omega_d = 0 时没有驱动周期，在积分之前报错
+            for call in [
+                c"pychaos.bifurcation(pychaos.PendulumParams(omega_d=0.0), 1.0, 1.1, 2, 1, 1)",
+                c"pychaos.lyapunov(pychaos.PendulumParams(omega_d=0.0), 0.2, 0.0)",
+                c"pychaos.lyapunov_chaos_map(pychaos.PendulumParams(omega_d=0.0), 0.5, 1.2, 1, 0.5, 0.6, 1, 1, 1)",
+            ] {
+                let err = py.eval(call, None, Some(&locals)).unwrap_err();
+                assert!(err.is_instance_of::<PyValueError>(py));
+            }
+        });
+    }
+}
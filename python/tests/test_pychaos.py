@@ -0,0 +1,79 @@
+# test_pychaos.py
+# 数组接口的测试：在 python/ 目录下 `maturin develop --release && pytest tests`。
+# 参考值由 Rust API（chaos::poincare、bifurcation_diagram、largest_exponent、lyapunov_chaos_map）
+# 以相同参数直接计算得到，绑定只做数据搬运，两者应在舍入误差内一致。
+import math
+
+import numpy as np
+import pytest
+
+import pychaos
+
+
+def chaotic_params(t_end=200.0):
+    return pychaos.PendulumParams(l=9.8, q=0.5, f_d=1.2, omega_d=2.0 / 3.0, dt=0.01, t_end=t_end)
+
+
+def test_solve_shapes_and_small_angle_solution():
+    # 无阻尼无驱动的小角度解 θ(t) = θ0 cos(√(g/l) t)
+    params = pychaos.PendulumParams(q=0.0, f_d=0.0, dt=0.001, t_end=2.0)
+    t, theta, omega = pychaos.solve(params, 0.01, 0.0)
+    assert t.shape == theta.shape == omega.shape == (2001,)
+    assert t.dtype == np.float64
+    assert t[0] == 0.0 and t[-1] == pytest.approx(2.0)
+    expected = 0.01 * np.cos(math.sqrt(params.g / params.l) * t)
+    assert np.max(np.abs(theta - expected)) < 1e-5
+
+
+def test_poincare_matches_rust():
+    theta, omega = pychaos.poincare(chaotic_params(), 0.2, 0.0, 10, 5)
+    assert theta.shape == omega.shape == (5,)
+    expected = [
+        (-2.7992482191895762, -0.7924542342567125),
+        (3.040469120694312, -0.8218602668985165),
+        (2.2023532466574185, -1.2378582951906538),
+        (3.023965256627024, -0.6283485295086455),
+        (-0.8740934977343793, 0.3009836250701735),
+    ]
+    np.testing.assert_allclose(np.column_stack([theta, omega]), expected, rtol=1e-8, atol=1e-10)
+
+    # 轨迹不够长时 Rust 端的错误以 ValueError 抛出
+    with pytest.raises(ValueError):
+        pychaos.poincare(chaotic_params(t_end=20.0), 0.2, 0.0, 10, 5)
+
+
+def test_bifurcation_matches_rust():
+    f_d, theta = pychaos.bifurcation(chaotic_params(), 1.0, 1.1, 2, 10, 4)
+    assert f_d.shape == theta.shape == (12,)
+    np.testing.assert_allclose(f_d, np.repeat([1.0, 1.05, 1.1], 4))
+    expected = [
+        -2.5534961054356597, -2.5506949992606045, -2.5481120131985913, -2.5457246657654484,
+        -1.9392790844651067, -1.9392160862190961, -1.939242608097283, -1.939231440530964,
+        -2.5936776561694064, -2.718339330325506, -2.9221772282806313, -2.8136478466231036,
+    ]
+    np.testing.assert_allclose(theta, expected, rtol=1e-8)
+
+def test_lyapunov_matches_rust():
+    exponent, t, running = pychaos.lyapunov(chaotic_params(), 0.2, 0.0)
+    assert exponent == pytest.approx(0.14173417887855327, rel=1e-6)
+    assert t.shape == running.shape == (21,)
+    assert running[-1] == pytest.approx(exponent)
+    assert np.all(np.diff(t) > 0)
+
+
+def test_lyapunov_chaos_map_shape_and_values():
+    f_d, q, exponents = pychaos.lyapunov_chaos_map(chaotic_params(), 0.5, 1.2, 1, 0.5, 0.6, 2, 20, 30)
+    np.testing.assert_allclose(f_d, [0.5, 1.2])
+    np.testing.assert_allclose(q, [0.5, 0.55, 0.6])
+    # 行对应 q、列对应 f_d
+    assert exponents.shape == (3, 2)
+    np.testing.assert_allclose(exponents[0], [-0.24936871978230368, 0.16737681035363366], rtol=1e-6)
+
+
+def test_zero_drive_frequency_is_rejected():
+    # omega_d = 0 时没有驱动周期，应立即报错而不是一直积分
+    params = pychaos.PendulumParams(omega_d=0.0)
+    with pytest.raises(ValueError):
+        pychaos.bifurcation(params, 1.0, 1.1, 2, 1, 1)
+    with pytest.raises(ValueError):
+        pychaos.lyapunov(params, 0.2, 0.0)
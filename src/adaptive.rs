@@ -0,0 +1,185 @@
+//adaptive.rs
+use std::f64::consts::PI;
+use crate::model::PendulumParams;
+use crate::solve_equation::{rhs, wrap_angle, State};
+
+// Dormand–Prince 5(4) 系数
+const C: [f64; 7] = [0.0, 1.0 / 5.0, 3.0 / 10.0, 4.0 / 5.0, 8.0 / 9.0, 1.0, 1.0];
+const A: [[f64; 6]; 7] = [
+    [0.0; 6],
+    [1.0 / 5.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+    [3.0 / 40.0, 9.0 / 40.0, 0.0, 0.0, 0.0, 0.0],
+    [44.0 / 45.0, -56.0 / 15.0, 32.0 / 9.0, 0.0, 0.0, 0.0],
+    [19372.0 / 6561.0, -25360.0 / 2187.0, 64448.0 / 6561.0, -212.0 / 729.0, 0.0, 0.0],
+    [9017.0 / 3168.0, -355.0 / 33.0, 46732.0 / 5247.0, 49.0 / 176.0, -5103.0 / 18656.0, 0.0],
+    [35.0 / 384.0, 0.0, 500.0 / 1113.0, 125.0 / 192.0, -2187.0 / 6784.0, 11.0 / 84.0],
+];
+// 五阶解的权重（即 A 的最后一行）与五阶、四阶解之差
+const B5: [f64; 7] = [35.0 / 384.0, 0.0, 500.0 / 1113.0, 125.0 / 192.0, -2187.0 / 6784.0, 11.0 / 84.0, 0.0];
+const E: [f64; 7] = [
+    71.0 / 57600.0,
+    0.0,
+    -71.0 / 16695.0,
+    71.0 / 1920.0,
+    -17253.0 / 339200.0,
+    22.0 / 525.0,
+    -1.0 / 40.0,
+];
+
+// 步长调整的安全系数与单步缩放上下限
+const SAFETY: f64 = 0.9;
+const MIN_SCALE: f64 = 0.2;
+const MAX_SCALE: f64 = 5.0;
+
+/// Dormand–Prince 单步：返回 (五阶解, 按 atol/rtol 归一化的误差范数)
+///
+/// 误差范数 <= 1 表示该步可接受。
+pub fn dopri5_step(state: &State, t: f64, h: f64, params: &PendulumParams) -> (State, f64) {
+    let mut k = [(0.0, 0.0); 7];
+    for i in 0..7 {
+        let (mut theta, mut omega) = (state.theta, state.omega);
+        for (a, kj) in A[i].iter().zip(&k).take(i) {
+            theta += h * a * kj.0;
+            omega += h * a * kj.1;
+        }
+        k[i] = rhs(theta, omega, t + C[i] * h, params);
+    }
+
+    let combine = |w: &[f64; 7]| {
+        w.iter()
+            .zip(&k)
+            .fold((0.0, 0.0), |(a, b), (wi, ki)| (a + wi * ki.0, b + wi * ki.1))
+    };
+    let (d_theta, d_omega) = combine(&B5);
+    let next = State {
+        theta: state.theta + h * d_theta,
+        omega: state.omega + h * d_omega,
+    };
+    let (e_theta, e_omega) = combine(&E);
+    let scale = |y0: f64, y1: f64| params.atol + params.rtol * y0.abs().max(y1.abs());
+    let r_theta = h * e_theta / scale(state.theta, next.theta);
+    let r_omega = h * e_omega / scale(state.omega, next.omega);
+    (next, ((r_theta * r_theta + r_omega * r_omega) / 2.0).sqrt())
+}
+
+// 从 (t, state) 积分到 t_target，步长自适应，最后一步截短以恰好落在 t_target 上；
+// h 为初始试探步长，返回时更新为下一次建议步长。每个接受的步都交给 on_step。
+fn advance<F: FnMut(f64, &State)>(
+    params: &PendulumParams,
+    state: &mut State,
+    t: &mut f64,
+    t_target: f64,
+    h: &mut f64,
+    mut on_step: F,
+) {
+    let min_step = 1e-12 * t_target.abs().max(1.0);
+    while t_target - *t > min_step {
+        let step = h.min(t_target - *t);
+        let (next, err) = dopri5_step(state, *t, step, params);
+        let scale = if err == 0.0 {
+            MAX_SCALE
+        } else {
+            (SAFETY * err.powf(-0.2)).clamp(MIN_SCALE, MAX_SCALE)
+        };
+        if err <= 1.0 || step <= min_step {
+            // 截短到 t_target 的步直接取 t_target，使周期末等时刻精确出现在轨迹上
+            *t = if step == t_target - *t { t_target } else { *t + step };
+            *state = next;
+            on_step(*t, state);
+            // 为了落在 t_target 而截短的步不代表误差允许的步长，不据此缩小建议步长
+            if step == *h || scale > 1.0 {
+                *h = step * scale;
+            }
+        } else {
+            *h = step * scale;
+        }
+    }
+}
+
+/// 自适应步长积分到 params.t_end，返回接受的各步 (t, State)（不等距）
+///
+/// params.dt 只作为初始试探步长；误差由 params.atol / params.rtol 控制。
+/// 最后一步截短以恰好落在 t_end 上；params.exact_stroboscopic 时每个驱动周期末 t = nT 也恰好出现在轨迹上。
+pub fn solve_adaptive(params: &PendulumParams, initial_theta: f64, initial_omega: f64) -> Vec<(f64, State)> {
+    let mut state = State {
+        theta: initial_theta,
+        omega: initial_omega,
+    };
+    let mut t = 0.0;
+    let mut h = params.dt;
+    let mut trajectory = vec![(t, state)];
+    if params.exact_stroboscopic {
+        let period = 2.0 * PI / params.omega_d.abs();
+        let mut n = 1;
+        while (n as f64) * period < params.t_end {
+            advance(params, &mut state, &mut t, n as f64 * period, &mut h, |t, s| trajectory.push((t, *s)));
+            n += 1;
+        }
+    }
+    advance(params, &mut state, &mut t, params.t_end, &mut h, |t, s| trajectory.push((t, *s)));
+    trajectory
+}
+
+/// 自适应步长的庞加莱采样：步长在每个驱动周期末截短，使采样点恰好落在 t = nT 上，无需插值
+///
+/// 返回第 transient_periods + 1 到 transient_periods + sample_periods 个周期末的 (折叠后的 θ, ω)，
+/// 不保存轨迹。
+pub fn poincare_adaptive(
+    params: &PendulumParams,
+    initial_theta: f64,
+    initial_omega: f64,
+    transient_periods: usize,
+    sample_periods: usize,
+) -> Vec<(f64, f64)> {
+    let period = 2.0 * PI / params.omega_d;
+    let mut state = State {
+        theta: initial_theta,
+        omega: initial_omega,
+    };
+    let mut t = 0.0;
+    let mut h = params.dt;
+    let mut samples = Vec::with_capacity(sample_periods);
+    for n in 1..=transient_periods + sample_periods {
+        advance(params, &mut state, &mut t, n as f64 * period, &mut h, |_, _| {});
+        if n > transient_periods {
+            samples.push((wrap_angle(state.theta), state.omega));
+        }
+    }
+    samples
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_adaptive_matches_fixed_step_with_fewer_steps() {
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.q = 0.5;
+        params.f_d = 0.5;
+        params.omega_d = 2.0 / 3.0;
+        let period = 2.0 * PI / params.omega_d;
+        params.dt = period / 1000.0;
+        params.t_end = 10.0 * period;
+        params.atol = 1e-10;
+        params.rtol = 1e-10;
+
+        let fixed = crate::solve_equation::solve_periods(&params, 0.2, 0.0, 10, 1000).unwrap();
+        let adaptive = solve_adaptive(&params, 0.2, 0.0);
+        let (t_end, last) = adaptive[adaptive.len() - 1];
+        let (_, reference) = fixed[fixed.len() - 1];
+        assert_relative_eq!(t_end, params.t_end, epsilon = 1e-9);
+        assert_relative_eq!(last.theta, reference.theta, epsilon = 1e-6);
+        assert_relative_eq!(last.omega, reference.omega, epsilon = 1e-6);
+        assert!(adaptive.len() < fixed.len() / 2);
+
+        let sections = poincare_adaptive(&params, 0.2, 0.0, 5, 5);
+        let reference = crate::solve_equation::stroboscopic_samples(&fixed, 1000, 5, 5);
+        for (a, b) in sections.iter().zip(&reference) {
+            assert_relative_eq!(a.0, b.0, epsilon = 1e-6);
+            assert_relative_eq!(a.1, b.1, epsilon = 1e-6);
+        }
+    }
+}
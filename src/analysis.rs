@@ -0,0 +1,772 @@
+//analysis.rs
+use std::collections::{HashMap, HashSet};
+use std::f64::consts::PI;
+use std::fs::File;
+use std::io::Write;
+use rayon::prelude::*;
+use crate::error::ChaosError;
+use crate::model::PendulumParams;
+use crate::separatrix::pendulum_energy;
+use crate::solve_equation::{rhs, rk4_step, slice_by_periods, solve_periods, steps_per_period, wrap_angle, State, TrajectoryIter};
+use crate::spectrum::analytic_signal;
+use crate::stroboscopic::StroboscopicMap;
+
+/// 平均每个驱动周期转过的圈数（旋转数）
+///
+/// 沿轨迹累加相邻两点折叠后的角度增量得到展开的 θ，再以 Δθ / 2π 除以窗口内的驱动周期数，
+/// 因此 θ 已折叠到 [-π, π) 的轨迹（例如 resample 的输出）也能正确计数，只要相邻两点间转过不到半圈。
+/// 摆动解趋于 0，同步转动解为 ±1/n 之类的有理数。窗口少于两个点时返回 0。
+pub fn rotation_number(traj: &[(f64, State)], params: &PendulumParams) -> f64 {
+    if traj.len() < 2 {
+        return 0.0;
+    }
+    let (t0, _) = traj[0];
+    let (t1, _) = traj[traj.len() - 1];
+    let periods = (t1 - t0) * params.omega_d / (2.0 * PI);
+    unwrapped_angle_change(traj) / (2.0 * PI) / periods
+}
+
+// 展开后的 θ 总变化量：逐段累加 wrap_angle(θ_{k+1} - θ_k)
+fn unwrapped_angle_change(traj: &[(f64, State)]) -> f64 {
+    traj.windows(2).map(|w| wrap_angle(w[1].1.theta - w[0].1.theta)).sum()
+}
+
+/// 单个驱动周期内的统计量（能量按单位 m l² 计）
+#[derive(Debug, Clone, Copy)]
+pub struct PeriodSummary {
+    pub period_index: usize,
+    pub t_start: f64,
+    /// 周期内 max |θ|（θ 折叠到 [-π, π)）
+    pub max_abs_theta: f64,
+    /// 周期内净转过的圈数 Δθ / 2π
+    pub net_rotation: f64,
+    /// 阻尼耗散的能量 ∫ q ω² dt
+    pub dissipated_energy: f64,
+    /// 驱动力做的功 ∫ F(t) ω dt（F 为 params.drive）
+    pub drive_work: f64,
+    /// 周期首末的能量差，应约等于 drive_work - dissipated_energy
+    pub energy_change: f64,
+}
+
+/// 逐个驱动周期统计，每个完整周期一行
+///
+/// 周期边界由 slice_by_periods 插值得到，积分用梯形公式。
+pub fn per_period_summary(traj: &[(f64, State)], params: &PendulumParams) -> Vec<PeriodSummary> {
+    let Some((t_last, _)) = traj.last() else {
+        return Vec::new();
+    };
+    let period = 2.0 * PI / params.omega_d;
+    let n_periods = (t_last / period + 1e-9).floor() as usize;
+
+    (0..n_periods)
+        .filter_map(|n| {
+            let seg = slice_by_periods(traj, params, n, n + 1);
+            let (t_start, first) = *seg.first()?;
+            let (_, last) = *seg.last()?;
+
+            let mut dissipated = 0.0;
+            let mut work = 0.0;
+            for w in seg.windows(2) {
+                let (t1, s1) = w[0];
+                let (t2, s2) = w[1];
+                let h = t2 - t1;
+                dissipated += 0.5 * h * (params.q_at(t1) * s1.omega * s1.omega + params.q_at(t2) * s2.omega * s2.omega);
+                let drive1 = params.drive_torque(s1.theta, t1) * s1.omega;
+                let drive2 = params.drive_torque(s2.theta, t2) * s2.omega;
+                work += 0.5 * h * (drive1 + drive2);
+            }
+
+            Some(PeriodSummary {
+                period_index: n,
+                t_start,
+                max_abs_theta: seg.iter().map(|(_, s)| wrap_angle(s.theta).abs()).fold(0.0, f64::max),
+                net_rotation: unwrapped_angle_change(&seg) / (2.0 * PI),
+                dissipated_energy: dissipated,
+                drive_work: work,
+                energy_change: pendulum_energy(last.theta, last.omega, params)
+                    - pendulum_energy(first.theta, first.omega, params),
+            })
+        })
+        .collect()
+}
+
+/// 把逐周期统计写成 CSV
+pub fn write_period_summary_csv(path: &str, rows: &[PeriodSummary]) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(
+        f,
+        "period,t_start,max_abs_theta,net_rotation,dissipated_energy,drive_work,energy_change"
+    )?;
+    for r in rows {
+        writeln!(
+            f,
+            "{},{:.12},{:.12},{:.12},{:.12},{:.12},{:.12}",
+            r.period_index,
+            r.t_start,
+            r.max_abs_theta,
+            r.net_rotation,
+            r.dissipated_energy,
+            r.drive_work,
+            r.energy_change
+        )?;
+    }
+    Ok(())
+}
+
+/// 流的散度 ∂θ'/∂θ + ∂ω'/∂ω 的中心差分估计（步长 h），理论值为 -q
+pub fn flow_divergence(state: &State, t: f64, params: &PendulumParams, h: f64) -> f64 {
+    let (f_plus, _) = rhs(state.theta + h, state.omega, t, params);
+    let (f_minus, _) = rhs(state.theta - h, state.omega, t, params);
+    let (_, g_plus) = rhs(state.theta, state.omega + h, t, params);
+    let (_, g_minus) = rhs(state.theta, state.omega - h, t, params);
+    (f_plus - f_minus) / (2.0 * h) + (g_plus - g_minus) / (2.0 * h)
+}
+
+/// 一个驱动周期映射（频闪映射）在 (theta, omega) 处的雅可比矩阵，中心差分，扰动 h
+///
+/// 起点取驱动相位 0，即庞加莱截面上的点；返回 [[∂θ'/∂θ, ∂θ'/∂ω], [∂ω'/∂θ, ∂ω'/∂ω]]。
+pub fn stroboscopic_jacobian(params: &PendulumParams, theta: f64, omega: f64, h: f64) -> [[f64; 2]; 2] {
+    let strobe = StroboscopicMap::new(params);
+    let map = |theta: f64, omega: f64| strobe.iterate(State { theta, omega });
+    let tp = map(theta + h, omega);
+    let tm = map(theta - h, omega);
+    let wp = map(theta, omega + h);
+    let wm = map(theta, omega - h);
+    [
+        [(tp.theta - tm.theta) / (2.0 * h), (wp.theta - wm.theta) / (2.0 * h)],
+        [(tp.omega - tm.omega) / (2.0 * h), (wp.omega - wm.omega) / (2.0 * h)],
+    ]
+}
+
+/// 相体积收缩率的数值估计与理论值对比
+#[derive(Debug, Clone, Copy)]
+pub struct ContractionReport {
+    /// 沿轨迹平均的散度
+    pub mean_divergence: f64,
+    /// 理论散度 -q
+    pub expected_divergence: f64,
+    /// 庞加莱映射雅可比行列式（面积收缩因子）在截面点上的几何平均
+    pub mean_area_contraction: f64,
+    /// 理论面积收缩因子 exp(-qT)
+    pub expected_area_contraction: f64,
+}
+
+/// 计算沿轨迹的平均散度与每次庞加莱迭代的面积收缩，并与理论值对比
+///
+/// traj 用于散度平均，section 为庞加莱点（驱动相位 0 处），在每个点上求频闪映射的雅可比行列式。
+pub fn contraction_report(
+    params: &PendulumParams,
+    traj: &[(f64, State)],
+    section: &[(f64, f64)],
+) -> ContractionReport {
+    const H: f64 = 1e-6;
+    let mean_divergence = if traj.is_empty() {
+        0.0
+    } else {
+        traj.iter()
+            .map(|(t, s)| flow_divergence(s, *t, params, H))
+            .sum::<f64>()
+            / traj.len() as f64
+    };
+
+    let log_det_mean = if section.is_empty() {
+        0.0
+    } else {
+        section
+            .iter()
+            .map(|(theta, omega)| {
+                let j = stroboscopic_jacobian(params, *theta, *omega, H);
+                (j[0][0] * j[1][1] - j[0][1] * j[1][0]).abs().ln()
+            })
+            .sum::<f64>()
+            / section.len() as f64
+    };
+
+    let period = 2.0 * PI / params.omega_d;
+    ContractionReport {
+        mean_divergence,
+        expected_divergence: -params.q,
+        mean_area_contraction: log_det_mean.exp(),
+        expected_area_contraction: (-params.q * period).exp(),
+    }
+}
+
+/// 时间反演检验的结果
+#[derive(Debug, Clone, Copy)]
+pub struct ReversalReport {
+    pub duration: f64,
+    /// 正向积分到 duration 时的状态
+    pub forward_end: State,
+    /// 再反向积分回 t = 0 后与初值的相空间距离（θ 不折叠）
+    pub return_error: f64,
+}
+
+/// 时间反演检验：取 q = 0 的可逆子系统，正向积分 duration 后以 -dt 积分回 t = 0，报告回到初值的误差
+///
+/// 规则运动中误差停留在截断/舍入误差量级，可用来检查积分器实现；
+/// 混沌运动中误差随 duration 指数增长（约 e^{2λT}），直观体现混沌带来的实际不可逆性。
+/// 步长取 params.effective_dt()，步数取 round(duration / dt)，正反两段使用同一组时间点。
+pub fn time_reversal_test(
+    params: &PendulumParams,
+    initial_theta: f64,
+    initial_omega: f64,
+    duration: f64,
+) -> ReversalReport {
+    let mut p = params.clone();
+    p.q = 0.0;
+    p.dt = p.effective_dt();
+    let steps = (duration / p.dt).round() as usize;
+
+    let mut state = State {
+        theta: initial_theta,
+        omega: initial_omega,
+    };
+    for k in 0..steps {
+        state = rk4_step(&state, k as f64 * p.dt, &p).0;
+    }
+    let forward_end = state;
+
+    let mut back = p.clone();
+    back.dt = -p.dt;
+    for k in (1..=steps).rev() {
+        state = rk4_step(&state, k as f64 * p.dt, &back).0;
+    }
+    let d_theta = state.theta - initial_theta;
+    let d_omega = state.omega - initial_omega;
+    ReversalReport {
+        duration: steps as f64 * p.dt,
+        forward_end,
+        return_error: (d_theta * d_theta + d_omega * d_omega).sqrt(),
+    }
+}
+
+/// 对一组时长分别做时间反演检验，得到返回误差随时长的增长曲线
+pub fn time_reversal_curve(
+    params: &PendulumParams,
+    initial_theta: f64,
+    initial_omega: f64,
+    durations: &[f64],
+) -> Vec<ReversalReport> {
+    durations
+        .iter()
+        .map(|d| time_reversal_test(params, initial_theta, initial_omega, *d))
+        .collect()
+}
+
+/// 把时间反演检验结果写成 CSV（列：duration,return_error,theta_end,omega_end）
+pub fn write_time_reversal_csv(path: &str, reports: &[ReversalReport]) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(f, "duration,return_error,theta_end,omega_end")?;
+    for r in reports {
+        writeln!(
+            f,
+            "{:.12},{:.6e},{:.12},{:.12}",
+            r.duration, r.return_error, r.forward_end.theta, r.forward_end.omega
+        )?;
+    }
+    Ok(())
+}
+
+/// 用峰值检测提取 θ(t) 的上包络，返回各局部极大值的 (t, θ)
+///
+/// 对每个局部极大值用相邻三点做抛物线插值，修正离散步长带来的峰位与峰值误差。
+pub fn envelope_peaks(traj: &[(f64, State)]) -> Vec<(f64, f64)> {
+    traj.windows(3)
+        .filter(|w| w[1].1.theta > w[0].1.theta && w[1].1.theta >= w[2].1.theta)
+        .map(|w| {
+            let (y0, y1, y2) = (w[0].1.theta, w[1].1.theta, w[2].1.theta);
+            let h = w[1].0 - w[0].0;
+            let denom = y0 - 2.0 * y1 + y2;
+            if denom == 0.0 {
+                return (w[1].0, y1);
+            }
+            let offset = 0.5 * (y0 - y2) / denom;
+            (w[1].0 + offset * h, y1 - 0.25 * (y0 - y2) * offset)
+        })
+        .collect()
+}
+
+/// 用 Hilbert 解析信号提取 θ(t) 的瞬时包络，返回 (t, 包络)
+///
+/// 要求等步长轨迹；先减去均值，长度截断到 2 的幂。两端会有边缘效应。
+pub fn envelope_hilbert(traj: &[(f64, State)]) -> Vec<(f64, f64)> {
+    if traj.is_empty() {
+        return Vec::new();
+    }
+    let mean = traj.iter().map(|(_, s)| s.theta).sum::<f64>() / traj.len() as f64;
+    let signal: Vec<f64> = traj.iter().map(|(_, s)| s.theta - mean).collect();
+    let (re, im) = analytic_signal(&signal);
+    traj.iter()
+        .zip(re.iter().zip(&im))
+        .map(|((t, _), (a, b))| (*t, (a * a + b * b).sqrt()))
+        .collect()
+}
+
+/// 过渡期之后的长时间平均量（能量按单位 m l² 计）
+#[derive(Debug, Clone, Copy)]
+pub struct TimeAverages {
+    /// <E>，E = ω²/2 + (g/l)(1 - cos θ)
+    pub energy: f64,
+    /// <|ω|>
+    pub abs_omega: f64,
+    /// 驱动输入功率 <F(t) ω>
+    pub drive_power: f64,
+    /// 阻尼耗散功率 <q ω²>；稳态下应约等于 drive_power
+    pub dissipated_power: f64,
+}
+
+/// 沿轨迹做梯形时间平均；轨迹少于两个点时返回 None
+///
+/// 轨迹应已去掉过渡期，例如 slice_by_periods 的结果；不要求等步长。
+pub fn time_averages(traj: &[(f64, State)], params: &PendulumParams) -> Option<TimeAverages> {
+    let (t0, _) = *traj.first()?;
+    let (t1, _) = *traj.last()?;
+    let duration = t1 - t0;
+    if traj.len() < 2 || duration <= 0.0 {
+        return None;
+    }
+
+    let observe = |t: f64, s: &State| {
+        [
+            pendulum_energy(s.theta, s.omega, params),
+            s.omega.abs(),
+            params.drive_torque(s.theta, t) * s.omega,
+            params.q_at(t) * s.omega * s.omega,
+        ]
+    };
+    let mut sums = [0.0; 4];
+    for w in traj.windows(2) {
+        let (ta, sa) = w[0];
+        let (tb, sb) = w[1];
+        let (a, b) = (observe(ta, &sa), observe(tb, &sb));
+        for (sum, (x, y)) in sums.iter_mut().zip(a.iter().zip(&b)) {
+            *sum += 0.5 * (tb - ta) * (x + y);
+        }
+    }
+    Some(TimeAverages {
+        energy: sums[0] / duration,
+        abs_omega: sums[1] / duration,
+        drive_power: sums[2] / duration,
+        dissipated_power: sums[3] / duration,
+    })
+}
+
+/// 积分 transient_periods + sample_periods 个周期，只对后 sample_periods 个周期求时间平均
+///
+/// 适合作为参数扫描中的标量输出。参数无效或 omega_d = 0 时返回错误，样本窗口不足以求平均时为 Ok(None)。
+pub fn post_transient_averages(
+    params: &PendulumParams,
+    initial_theta: f64,
+    initial_omega: f64,
+    transient_periods: usize,
+    sample_periods: usize,
+) -> Result<Option<TimeAverages>, ChaosError> {
+    let spp = steps_per_period(params);
+    let traj = solve_periods(params, initial_theta, initial_omega, transient_periods + sample_periods, spp)?;
+    Ok(time_averages(&traj[(transient_periods * spp).min(traj.len())..], params))
+}
+
+/// 把包络写成 CSV（两列：t,envelope）
+pub fn write_envelope_csv(path: &str, envelope: &[(f64, f64)]) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(f, "t,envelope")?;
+    for (t, e) in envelope {
+        writeln!(f, "{:.12},{:.12}", t, e)?;
+    }
+    Ok(())
+}
+
+/// 初值敏感性实验：从 state_a、state_b 同时积分到 params.t_end，返回每一步的 (t, ln d(t))
+///
+/// d 为相空间距离 sqrt(Δθ² + Δω²)，Δθ 按 2π 折叠，转过整圈的两条轨道不会被误判为远离。
+/// 混沌运动中 ln d 先以最大 Lyapunov 指数为斜率线性增长，到吸引子尺度后饱和；
+/// 两点重合时该点为 -∞。两条轨道不做重归一化，与 lyapunov::benettin_trace 不同。
+pub fn divergence(params: &PendulumParams, state_a: State, state_b: State) -> Vec<(f64, f64)> {
+    let a = TrajectoryIter::new(params, state_a.theta, state_a.omega);
+    let b = TrajectoryIter::new(params, state_b.theta, state_b.omega);
+    a.zip(b)
+        .map(|((t, sa), (_, sb))| {
+            let d_theta = wrap_angle(sb.theta - sa.theta);
+            let d_omega = sb.omega - sa.omega;
+            (t, (d_theta * d_theta + d_omega * d_omega).sqrt().ln())
+        })
+        .collect()
+}
+
+/// 把 divergence 的结果写成 CSV（两列：t,log_separation）
+pub fn write_divergence_csv(path: &str, divergence: &[(f64, f64)]) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(f, "t,log_separation")?;
+    for (t, d) in divergence {
+        writeln!(f, "{:.12},{:.12}", t, d)?;
+    }
+    Ok(())
+}
+
+/// 关联维数估计中对数等距的半径个数
+const CORRELATION_RADII: usize = 24;
+
+/// Grassberger–Procaccia 关联维数的估计结果
+#[derive(Debug, Clone)]
+pub struct CorrelationDimension {
+    /// (ln r, ln C(r))，C(r) = 0 的半径不列出
+    pub curve: Vec<(f64, f64)>,
+    /// curve 的最小二乘斜率，即关联维数 D₂ 的估计
+    pub dimension: f64,
+    pub intercept: f64,
+}
+
+/// 庞加莱点 (θ, ω) 的关联和 C(r) = #{i < j : |x_i - x_j| < r} / (N(N-1)/2) 与关联维数
+///
+/// 在 r_range 上取 CORRELATION_RADII 个对数等距的半径，θ 方向按周期距离计算；
+/// 点先按边长不小于 r_max 的格子分桶，每个点只与相邻格子中的点比较，不需要 O(N²) 的全部点对。
+/// 斜率对全部半径拟合，r_range 应选在 ln C–ln r 曲线的线性段内（太小时统计不足，太大时接近吸引子尺度而饱和）。
+/// 有效半径少于两个时返回 None。
+pub fn correlation_dimension(points: &[(f64, f64)], r_range: (f64, f64)) -> Option<CorrelationDimension> {
+    let (r_min, r_max) = r_range;
+    let n = points.len();
+    if n < 2 || !(r_min > 0.0 && r_max > r_min) {
+        return None;
+    }
+    let radii: Vec<f64> = (0..CORRELATION_RADII)
+        .map(|k| r_min * (r_max / r_min).powf(k as f64 / (CORRELATION_RADII - 1) as f64))
+        .collect();
+
+    // θ 方向的格子数使格子宽度不小于 r_max；ω 方向格子宽度为 r_max
+    let theta_cells = ((2.0 * PI / r_max) as usize).max(1);
+    let omega_min = points.iter().map(|p| p.1).fold(f64::INFINITY, f64::min);
+    let cell_of = |p: &(f64, f64)| {
+        let u = (wrap_angle(p.0) + PI) / (2.0 * PI);
+        let i = ((u * theta_cells as f64) as usize).min(theta_cells - 1);
+        (i, ((p.1 - omega_min) / r_max) as i64)
+    };
+    let mut cells: HashMap<(usize, i64), Vec<usize>> = HashMap::new();
+    for (k, p) in points.iter().enumerate() {
+        cells.entry(cell_of(p)).or_default().push(k);
+    }
+
+    // counts[k]：距离落在 [radii[k-1], radii[k]) 的有序点对数，最后求前缀和
+    let counts = points
+        .par_iter()
+        .enumerate()
+        .map(|(a, p)| {
+            let mut counts = vec![0usize; CORRELATION_RADII];
+            let (ci, cj) = cell_of(p);
+            let mut columns = [(ci + theta_cells - 1) % theta_cells, ci, (ci + 1) % theta_cells];
+            columns.sort_unstable();
+            let mut last = None;
+            for &i in &columns {
+                if last == Some(i) {
+                    continue;
+                }
+                last = Some(i);
+                for j in cj - 1..=cj + 1 {
+                    for &b in cells.get(&(i, j)).into_iter().flatten() {
+                        if b == a {
+                            continue;
+                        }
+                        let q = points[b];
+                        let d = wrap_angle(q.0 - p.0).hypot(q.1 - p.1);
+                        let k = radii.partition_point(|&r| r <= d);
+                        if k < CORRELATION_RADII {
+                            counts[k] += 1;
+                        }
+                    }
+                }
+            }
+            counts
+        })
+        .reduce(
+            || vec![0usize; CORRELATION_RADII],
+            |mut x, y| {
+                x.iter_mut().zip(&y).for_each(|(a, b)| *a += b);
+                x
+            },
+        );
+
+    let pairs = (n * (n - 1)) as f64;
+    let mut cumulative = 0;
+    let curve: Vec<(f64, f64)> = radii
+        .iter()
+        .zip(&counts)
+        .filter_map(|(r, c)| {
+            cumulative += c;
+            (cumulative > 0).then(|| (r.ln(), (cumulative as f64 / pairs).ln()))
+        })
+        .collect();
+    let (dimension, intercept) = least_squares(&curve)?;
+    Some(CorrelationDimension {
+        curve,
+        dimension,
+        intercept,
+    })
+}
+
+/// 盒计数维数的估计结果
+#[derive(Debug, Clone)]
+pub struct BoxCountingDimension {
+    /// (ε, N(ε))：边长为 ε 的盒子中被点占据的个数
+    pub counts: Vec<(f64, usize)>,
+    /// ln N(ε) 对 ln(1/ε) 的最小二乘斜率，即盒计数维数的估计
+    pub dimension: f64,
+    pub intercept: f64,
+}
+
+/// 平面点集的盒计数维数：对每个盒子边长 ε 统计被占据的盒子数 N(ε)，拟合 N(ε) ∝ ε^(-D)
+///
+/// 盒子网格以点集的 (x, y) 最小值为原点；可用于庞加莱点（θ 已折叠），
+/// 也可用于 BasinMap::boundary_points 给出的吸引域边界格点——此时 ε 应明显大于格点间距，
+/// 否则每个格点各占一个盒子，N(ε) 饱和。ε 少于两个或没有点时返回 None。
+pub fn box_counting_dimension(points: &[(f64, f64)], box_sizes: &[f64]) -> Option<BoxCountingDimension> {
+    if points.is_empty() {
+        return None;
+    }
+    let x_min = points.iter().map(|p| p.0).fold(f64::INFINITY, f64::min);
+    let y_min = points.iter().map(|p| p.1).fold(f64::INFINITY, f64::min);
+    let counts: Vec<(f64, usize)> = box_sizes
+        .iter()
+        .filter(|eps| **eps > 0.0)
+        .map(|&eps| {
+            let boxes: HashSet<(i64, i64)> = points
+                .iter()
+                .map(|p| (((p.0 - x_min) / eps) as i64, ((p.1 - y_min) / eps) as i64))
+                .collect();
+            (eps, boxes.len())
+        })
+        .collect();
+    let log_log: Vec<(f64, f64)> = counts.iter().map(|(eps, n)| (-eps.ln(), (*n as f64).ln())).collect();
+    let (dimension, intercept) = least_squares(&log_log)?;
+    Some(BoxCountingDimension {
+        counts,
+        dimension,
+        intercept,
+    })
+}
+
+/// 把盒计数结果写成 CSV（两列：epsilon,count）
+pub fn write_box_counts_csv(path: &str, estimate: &BoxCountingDimension) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(f, "epsilon,count")?;
+    for (eps, n) in &estimate.counts {
+        writeln!(f, "{:.12},{}", eps, n)?;
+    }
+    Ok(())
+}
+
+// 直线 y = a x + b 的最小二乘拟合，返回 (a, b)；少于两个点或 x 全相同时为 None
+pub(crate) fn least_squares(points: &[(f64, f64)]) -> Option<(f64, f64)> {
+    if points.len() < 2 {
+        return None;
+    }
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+    let sxx: f64 = points.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+    let sxy: f64 = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+    if sxx == 0.0 {
+        return None;
+    }
+    let slope = sxy / sxx;
+    Some((slope, mean_y - slope * mean_x))
+}
+
+/// 把关联和写成 CSV（三列：log_r,log_c,fit），fit 为拟合直线在该处的值，斜率即 dimension
+pub fn write_correlation_sum_csv(path: &str, estimate: &CorrelationDimension) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(f, "log_r,log_c,fit")?;
+    for (log_r, log_c) in &estimate.curve {
+        let fit = estimate.intercept + estimate.dimension * log_r;
+        writeln!(f, "{:.12},{:.12},{:.12}", log_r, log_c, fit)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_rotation_number_of_uniform_rotation() {
+        let params = PendulumParams::new();
+        let period = 2.0 * PI / params.omega_d;
+        // 每两个驱动周期转一圈，叠加小幅摆动
+        let traj: Vec<(f64, State)> = (0..=4000)
+            .map(|i| {
+                let t = 20.0 * period * i as f64 / 4000.0;
+                let theta = PI * t / period + 0.1 * (params.omega_d * t).sin();
+                (t, State { theta, omega: 0.0 })
+            })
+            .collect();
+        assert_relative_eq!(rotation_number(&traj, &params), 0.5, epsilon = 1e-9);
+
+        // θ 折叠到 [-π, π) 后结果不变
+        let folded: Vec<(f64, State)> = traj
+            .iter()
+            .map(|&(t, s)| (t, State { theta: wrap_angle(s.theta), omega: s.omega }))
+            .collect();
+        assert_relative_eq!(rotation_number(&folded, &params), 0.5, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn test_per_period_energy_balance() {
+        let mut params = PendulumParams::new();
+        params.q = 0.5;
+        params.f_d = 1.2;
+        params.omega_d = 2.0 / 3.0;
+        let period = 2.0 * PI / params.omega_d;
+        params.dt = period / 400.0;
+        params.t_end = 10.0 * period + 0.5 * params.dt;
+
+        let traj = crate::solve_equation::solve(&params, 0.2, 0.0).unwrap();
+        let rows = per_period_summary(&traj, &params);
+        assert_eq!(rows.len(), 10);
+        for r in &rows {
+            assert_relative_eq!(r.drive_work - r.dissipated_energy, r.energy_change, epsilon = 1e-4);
+            assert!(r.dissipated_energy >= 0.0);
+        }
+    }
+
+    #[test]
+    fn test_envelope_of_decaying_oscillation() {
+        // θ = e^{-0.02 t} cos(3t)
+        let traj: Vec<(f64, State)> = (0..8192)
+            .map(|i| {
+                let t = i as f64 * 0.01;
+                (t, State { theta: (-0.02 * t).exp() * (3.0 * t).cos(), omega: 0.0 })
+            })
+            .collect();
+
+        let peaks = envelope_peaks(&traj);
+        assert!(peaks.len() > 30);
+        for (t, e) in &peaks {
+            assert_relative_eq!(*e, (-0.02 * t).exp(), epsilon = 1e-3);
+        }
+
+        let hilbert = envelope_hilbert(&traj);
+        assert_eq!(hilbert.len(), 8192);
+        for (t, e) in &hilbert[1000..7000] {
+            assert_relative_eq!(*e, (-0.02 * t).exp(), epsilon = 2e-2);
+        }
+    }
+
+    #[test]
+    fn test_contraction_matches_damping() {
+        let mut params = PendulumParams::new();
+        params.q = 0.5;
+        params.f_d = 1.2;
+        params.omega_d = 2.0 / 3.0;
+        let period = 2.0 * PI / params.omega_d;
+        params.dt = period / 200.0;
+
+        let traj = solve_periods(&params, 0.2, 0.0, 20, 200).unwrap();
+        let section = crate::solve_equation::stroboscopic_samples(&traj, 200, 10, 10);
+        let report = contraction_report(&params, &traj, &section);
+        assert_relative_eq!(report.mean_divergence, report.expected_divergence, epsilon = 1e-6);
+        assert_relative_eq!(
+            report.mean_area_contraction,
+            report.expected_area_contraction,
+            max_relative = 1e-4
+        );
+    }
+
+    #[test]
+    fn test_time_averages_power_balance() {
+        let mut params = PendulumParams::new();
+        params.q = 0.5;
+        params.f_d = 1.2;
+        params.omega_d = 2.0 / 3.0;
+        params.dt = 2.0 * PI / params.omega_d / 200.0;
+
+        let avg = post_transient_averages(&params, 0.2, 0.0, 50, 400).unwrap().unwrap();
+        // 长时间平均下能量变化可忽略，输入功率与耗散功率平衡
+        assert_relative_eq!(avg.drive_power, avg.dissipated_power, max_relative = 1e-2);
+        assert!(avg.energy > 0.0 && avg.abs_omega > 0.0);
+    }
+
+    #[test]
+    fn test_time_reversal_error_small_for_regular_and_grows_for_chaos() {
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.omega_d = 2.0 / 3.0;
+        params.dt = 2.0 * PI / params.omega_d / 200.0;
+
+        params.f_d = 0.0;
+        // 无驱动时只剩 RK4 截断误差的缓慢累积
+        let regular = time_reversal_test(&params, 0.3, 0.0, 200.0);
+        assert!(regular.return_error < 1e-6);
+
+        params.f_d = 1.2;
+        let curve = time_reversal_curve(&params, 0.3, 0.0, &[10.0, 400.0]);
+        assert!(curve[0].return_error < 1e-6);
+        assert!(curve[1].return_error > 1.0);
+
+        // 按每周期步数设定后改 omega_d：步长跟随 effective_dt()，与 solve 积分的时间点一致
+        params.set_steps_per_period(200);
+        params.omega_d = 0.7;
+        let dt = params.effective_dt();
+        let report = time_reversal_test(&params, 0.3, 0.0, 10.0);
+        assert!((report.duration - (10.0 / dt).round() * dt).abs() < 1e-12);
+        params.t_end = report.duration;
+        params.q = 0.0;
+        let (_, end) = *crate::solve_equation::solve(&params, 0.3, 0.0).unwrap().last().unwrap();
+        assert_eq!(end, report.forward_end);
+    }
+
+    #[test]
+    fn test_divergence_grows_for_chaos_and_decays_for_periodic_orbit() {
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.q = 0.5;
+        params.omega_d = 2.0 / 3.0;
+        let period = 2.0 * PI / params.omega_d;
+        params.set_steps_per_period(200);
+        params.t_end = 60.0 * period;
+        let a = State { theta: 0.2, omega: 0.0 };
+        let b = State { theta: 0.2 + 1e-8, omega: 0.0 };
+
+        params.f_d = 1.2;
+        let chaotic = divergence(&params, a, b);
+        assert_eq!(chaotic.len(), 60 * 200 + 1);
+        assert_relative_eq!(chaotic[0].1, 1e-8f64.ln(), epsilon = 1e-6);
+        assert!(chaotic.last().unwrap().1 > chaotic[0].1 + 10.0);
+
+        params.f_d = 0.5;
+        let periodic = divergence(&params, a, b);
+        assert!(periodic.last().unwrap().1 < periodic[0].1 - 5.0);
+    }
+
+    #[test]
+    fn test_correlation_dimension_of_line_and_plane() {
+        // 均匀的二维点云 D₂ ≈ 2，ω = 常数的一条线 D₂ ≈ 1
+        let plane: Vec<(f64, f64)> = (0..4000)
+            .map(|k| ((k as f64 * 0.754_877_7).fract() * 2.0 * PI - PI, (k as f64 * 0.569_840_3).fract()))
+            .collect();
+        let line: Vec<(f64, f64)> = (0..4000).map(|k| ((k as f64 * 0.618_034).fract() * 2.0 * PI - PI, 0.3)).collect();
+        let d_plane = correlation_dimension(&plane, (0.02, 0.2)).unwrap();
+        let d_line = correlation_dimension(&line, (0.01, 0.5)).unwrap();
+        assert!((d_plane.dimension - 2.0).abs() < 0.1, "{}", d_plane.dimension);
+        assert!((d_line.dimension - 1.0).abs() < 0.05, "{}", d_line.dimension);
+
+        // 分桶计数与全部点对的直接计数一致
+        let r = 0.2;
+        let brute = (0..plane.len())
+            .flat_map(|i| (i + 1..plane.len()).map(move |j| (i, j)))
+            .filter(|&(i, j)| wrap_angle(plane[j].0 - plane[i].0).hypot(plane[j].1 - plane[i].1) < r)
+            .count();
+        let expected = (2.0 * brute as f64 / (plane.len() * (plane.len() - 1)) as f64).ln();
+        assert_relative_eq!(d_plane.curve.last().unwrap().1, expected, max_relative = 1e-12);
+    }
+
+    #[test]
+    fn test_box_counting_dimension() {
+        let sizes: Vec<f64> = (0..6).map(|k| 0.5 / 2f64.powi(k)).collect();
+        let square: Vec<(f64, f64)> = (0..200).flat_map(|i| (0..200).map(move |j| (i as f64 / 200.0, j as f64 / 200.0))).collect();
+        let segment: Vec<(f64, f64)> = (0..20_000).map(|i| (i as f64 / 20_000.0, 0.5 * i as f64 / 20_000.0)).collect();
+        let d_square = box_counting_dimension(&square, &sizes).unwrap();
+        let d_segment = box_counting_dimension(&segment, &sizes).unwrap();
+        assert!((d_square.dimension - 2.0).abs() < 0.05, "{}", d_square.dimension);
+        assert!((d_segment.dimension - 1.0).abs() < 0.05, "{}", d_segment.dimension);
+        assert_eq!(d_square.counts[0], (0.5, 4));
+    }
+}
@@ -0,0 +1,199 @@
+//animation.rs
+use std::f64::consts::PI;
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use crate::solve_equation::{interpolate_state, State};
+
+// 动画导出：单摆本体的运动（杆 + 摆锤 + 摆锤轨迹拖尾），
+// 以及随驱动相位推移的庞加莱截面。既可以写成 GIF，也可以写成逐帧 PNG 序列
+// （再用 ffmpeg 等工具合成 MP4）。绘制逻辑对后端泛型，与 plot 模块相同。
+
+/// 动画选项
+#[derive(Debug, Clone)]
+pub struct AnimationOptions {
+    pub width: u32,
+    pub height: u32,
+    /// 每秒帧数
+    pub fps: f64,
+    /// 每一帧（实际时间 1/fps 秒）对应的模拟时长与 1/fps 之比；1 为实时播放
+    pub time_scale: f64,
+    /// 摆锤轨迹拖尾保留的帧数，0 表示不画拖尾
+    pub trail_length: usize,
+}
+
+impl Default for AnimationOptions {
+    fn default() -> Self {
+        AnimationOptions {
+            width: 480,
+            height: 480,
+            fps: 25.0,
+            time_scale: 1.0,
+            trail_length: 50,
+        }
+    }
+}
+
+impl AnimationOptions {
+    fn frame_delay_ms(&self) -> u32 {
+        (1000.0 / self.fps).round().max(1.0) as u32
+    }
+}
+
+fn animation_error<E: std::fmt::Display>(e: E) -> std::io::Error {
+    std::io::Error::other(e.to_string())
+}
+
+/// 按帧率在轨迹上等时间间隔取状态（线性插值），第 k 帧对应 t0 + k·time_scale/fps
+pub fn frame_states(traj: &[(f64, State)], opts: &AnimationOptions) -> Vec<State> {
+    let (Some((t0, _)), Some((t1, _))) = (traj.first(), traj.last()) else {
+        return Vec::new();
+    };
+    let step = opts.time_scale / opts.fps;
+    let frames = ((t1 - t0) / step).floor() as usize + 1;
+    let mut cursor = 0;
+    (0..frames)
+        .map(|k| {
+            let t = t0 + k as f64 * step;
+            while cursor + 2 < traj.len() && traj[cursor + 1].0 < t {
+                cursor += 1;
+            }
+            if cursor + 1 == traj.len() {
+                return traj[cursor].1;
+            }
+            let (ta, sa) = &traj[cursor];
+            let (tb, sb) = &traj[cursor + 1];
+            interpolate_state(*ta, sa, *tb, sb, t.min(*tb))
+        })
+        .collect()
+}
+
+// 摆锤位置（悬挂点为原点，θ 从竖直向下量起，杆长归一化为 1）
+fn bob_position(theta: f64) -> (f64, f64) {
+    (theta.sin(), -theta.cos())
+}
+
+fn draw_pendulum_frame<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    frames: &[State],
+    k: usize,
+    opts: &AnimationOptions,
+) -> std::io::Result<()> {
+    root.fill(&WHITE).map_err(animation_error)?;
+    let mut chart = ChartBuilder::on(root)
+        .margin(10)
+        .build_cartesian_2d(-1.2..1.2, -1.2..1.2)
+        .map_err(animation_error)?;
+    if opts.trail_length > 0 {
+        let start = k.saturating_sub(opts.trail_length);
+        let trail = frames[start..=k].iter().map(|s| bob_position(s.theta));
+        chart
+            .draw_series(LineSeries::new(trail, RGBColor(160, 190, 230).stroke_width(2)))
+            .map_err(animation_error)?;
+    }
+    let bob = bob_position(frames[k].theta);
+    chart
+        .draw_series(LineSeries::new([(0.0, 0.0), bob], BLACK.stroke_width(3)))
+        .map_err(animation_error)?;
+    chart
+        .draw_series([Circle::new(bob, 12, RED.filled()), Circle::new((0.0, 0.0), 4, BLACK.filled())])
+        .map_err(animation_error)?;
+    root.present().map_err(animation_error)
+}
+
+/// 把单摆的运动写成 GIF：每帧画出杆、摆锤与最近 trail_length 帧的摆锤轨迹
+pub fn pendulum_gif(path: &str, traj: &[(f64, State)], opts: &AnimationOptions) -> std::io::Result<()> {
+    let frames = frame_states(traj, opts);
+    let root = BitMapBackend::gif(path, (opts.width, opts.height), opts.frame_delay_ms())
+        .map_err(animation_error)?
+        .into_drawing_area();
+    for k in 0..frames.len() {
+        draw_pendulum_frame(&root, &frames, k, opts)?;
+    }
+    Ok(())
+}
+
+/// 把单摆的运动写成逐帧 PNG（dir/frame_00000.png, ...），返回帧数
+///
+/// 例如 ffmpeg -framerate 25 -i dir/frame_%05d.png out.mp4 可合成视频。
+pub fn pendulum_frames(dir: &str, traj: &[(f64, State)], opts: &AnimationOptions) -> std::io::Result<usize> {
+    std::fs::create_dir_all(dir)?;
+    let frames = frame_states(traj, opts);
+    for k in 0..frames.len() {
+        let path = std::path::Path::new(dir).join(format!("frame_{:05}.png", k));
+        let root = BitMapBackend::new(&path, (opts.width, opts.height)).into_drawing_area();
+        draw_pendulum_frame(&root, &frames, k, opts)?;
+    }
+    Ok(frames.len())
+}
+
+/// 把随驱动相位推移的庞加莱截面写成 GIF，sections 为 sample_poincare_phases_from_trajectory 的输出
+///
+/// 所有帧使用相同的坐标范围（θ ∈ [-π, π]，ω 取全部截面的范围），一帧对应一个相位；
+/// opts 中只用到尺寸与帧率。
+pub fn poincare_phase_gif(path: &str, sections: &[Vec<(f64, f64)>], opts: &AnimationOptions) -> std::io::Result<()> {
+    let (omega_min, omega_max) = sections
+        .iter()
+        .flatten()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| (lo.min(p.1), hi.max(p.1)));
+    let (omega_min, omega_max) = if omega_min < omega_max {
+        (omega_min, omega_max)
+    } else {
+        (-1.0, 1.0)
+    };
+    let root = BitMapBackend::gif(path, (opts.width, opts.height), opts.frame_delay_ms())
+        .map_err(animation_error)?
+        .into_drawing_area();
+    for (j, section) in sections.iter().enumerate() {
+        root.fill(&WHITE).map_err(animation_error)?;
+        let phase = 2.0 * PI * j as f64 / sections.len() as f64;
+        let mut chart = ChartBuilder::on(&root)
+            .margin(10)
+            .caption(format!("phase = {:.2}", phase), ("sans-serif", 18))
+            .x_label_area_size(30)
+            .y_label_area_size(40)
+            .build_cartesian_2d(-PI..PI, omega_min..omega_max)
+            .map_err(animation_error)?;
+        chart
+            .configure_mesh()
+            .disable_mesh()
+            .x_desc("theta")
+            .y_desc("omega")
+            .draw()
+            .map_err(animation_error)?;
+        chart
+            .draw_series(section.iter().map(|&p| Circle::new(p, 1, BLUE.filled())))
+            .map_err(animation_error)?;
+        root.present().map_err(animation_error)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_sampling_and_gif_output() {
+        let traj: Vec<(f64, State)> = (0..=100)
+            .map(|i| {
+                let t = i as f64 * 0.01;
+                (t, State { theta: t, omega: 1.0 })
+            })
+            .collect();
+        let opts = AnimationOptions {
+            width: 64,
+            height: 64,
+            fps: 10.0,
+            trail_length: 3,
+            ..AnimationOptions::default()
+        };
+        // 1 秒轨迹、每帧 0.1 秒：共 11 帧，θ 由插值得到
+        let frames = frame_states(&traj, &opts);
+        assert_eq!(frames.len(), 11);
+        assert!((frames[5].theta - 0.5).abs() < 1e-12);
+
+        let path = std::env::temp_dir().join("chaos_animation_test.gif");
+        pendulum_gif(path.to_str().unwrap(), &traj, &opts).unwrap();
+        assert_eq!(&std::fs::read(&path).unwrap()[..3], b"GIF");
+    }
+}
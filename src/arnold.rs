@@ -0,0 +1,190 @@
+//arnold.rs
+use std::fs::File;
+use std::io::Write;
+use rayon::prelude::*;
+use crate::basin::write_rgb_png;
+use crate::bifurcation::sweep_values;
+use crate::error::ChaosError;
+use crate::model::PendulumParams;
+use crate::solve_equation::{steps_per_period, State};
+use crate::stroboscopic::{orbit_rotation_number, StroboscopicMap};
+
+// 锁频图（Arnold 舌）：在 (Ω, f_d) 平面上逐点计算旋转数 ρ（平均每个驱动周期转过的圈数）。
+// 摆动解 ρ = 0，与驱动同步的转动解 ρ = p/q 为有理数，且在参数平面上占据一片有面积的区域——
+// 从 f_d = 0 附近的 Ω = ω/ρ 处张开的“舌头”。舌头之外 ρ 随参数连续变化（准周期）或没有极限（混沌），
+// 有限窗口内的估计值不会恰好落在低阶有理数上。
+
+/// (Ω, f_d) 平面上的旋转数图，rotations 按 f_d 为行（外层）、omega_d 为列（内层）排列
+#[derive(Debug, Clone)]
+pub struct ArnoldTongueMap {
+    pub omega_ds: Vec<f64>,
+    pub f_ds: Vec<f64>,
+    pub rotations: Vec<f64>,
+}
+
+impl ArnoldTongueMap {
+    pub fn rotation_at(&self, i_f_d: usize, i_omega_d: usize) -> f64 {
+        self.rotations[i_f_d * self.omega_ds.len() + i_omega_d]
+    }
+
+    /// 某格点的锁频比 p/q（见 locking_ratio）
+    pub fn ratio_at(&self, i_f_d: usize, i_omega_d: usize, max_denominator: u64, tolerance: f64) -> Option<(i64, u64)> {
+        locking_ratio(self.rotation_at(i_f_d, i_omega_d), max_denominator, tolerance)
+    }
+}
+
+/// 与旋转数相差不超过 tolerance 的分母最小的有理数 p/q（q ≤ max_denominator），没有时返回 None
+///
+/// tolerance 取 1 / sample_periods 的量级即可：锁频解的估计误差只来自窗口端点，约为 1/(2N)。
+pub fn locking_ratio(rotation: f64, max_denominator: u64, tolerance: f64) -> Option<(i64, u64)> {
+    if !rotation.is_finite() {
+        return None;
+    }
+    (1..=max_denominator).find_map(|q| {
+        let p = (rotation * q as f64).round();
+        ((rotation - p / q as f64).abs() <= tolerance).then_some((p as i64, q))
+    })
+}
+
+/// 在 (Ω, f_d) 网格上并行计算旋转数
+///
+/// 两个参数各取 steps + 1 个等距值；每个格点从 (initial_theta, initial_omega) 出发，
+/// 丢弃 transient_periods 个驱动周期后在 sample_periods 个周期的窗口内计算旋转数。
+/// 每个周期的步数按该格点的 Ω 由 params.dt 重新取整，各格点独立计算。
+/// Ω 的两端必须是正的有限数（否则驱动周期无意义），不满足时返回 InvalidParams。
+#[allow(clippy::too_many_arguments)]
+pub fn arnold_tongues(
+    params: &PendulumParams,
+    omega_d_range: (f64, f64),
+    omega_d_steps: usize,
+    f_d_range: (f64, f64),
+    f_d_steps: usize,
+    initial_theta: f64,
+    initial_omega: f64,
+    transient_periods: usize,
+    sample_periods: usize,
+) -> Result<ArnoldTongueMap, ChaosError> {
+    params.validate()?;
+    let (omega_lo, omega_hi) = omega_d_range;
+    if !(omega_lo > 0.0 && omega_hi > 0.0 && omega_lo.is_finite() && omega_hi.is_finite()) {
+        return Err(ChaosError::InvalidParams(format!(
+            "omega_d range must be positive and finite, got ({}, {})",
+            omega_lo, omega_hi
+        )));
+    }
+    let omega_ds = sweep_values(omega_d_range, omega_d_steps);
+    let f_ds = sweep_values(f_d_range, f_d_steps);
+    let cells: Vec<(f64, f64)> = f_ds
+        .iter()
+        .flat_map(|&f_d| omega_ds.iter().map(move |&omega_d| (f_d, omega_d)))
+        .collect();
+    let rotations = cells
+        .par_iter()
+        .map(|&(f_d, omega_d)| {
+            let mut p = params.clone();
+            p.f_d = f_d;
+            p.omega_d = omega_d;
+            let spp = steps_per_period(&p);
+            let start = State {
+                theta: initial_theta,
+                omega: initial_omega,
+            };
+            orbit_rotation_number(&StroboscopicMap::with_steps(&p, spp).orbit(start, transient_periods, sample_periods))
+        })
+        .collect();
+    Ok(ArnoldTongueMap { omega_ds, f_ds, rotations })
+}
+
+/// 把旋转数图写成矩阵 CSV：首行为 f_d\omega_d 与各 Ω，之后每行以 f_d 开头，接该 f_d 下各 Ω 的旋转数
+pub fn write_arnold_tongues_csv(path: &str, map: &ArnoldTongueMap) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    write!(f, "f_d\\omega_d")?;
+    for omega_d in &map.omega_ds {
+        write!(f, ",{:.12}", omega_d)?;
+    }
+    writeln!(f)?;
+    for (i, f_d) in map.f_ds.iter().enumerate() {
+        write!(f, "{:.12}", f_d)?;
+        for j in 0..map.omega_ds.len() {
+            write!(f, ",{:.12}", map.rotation_at(i, j))?;
+        }
+        writeln!(f)?;
+    }
+    Ok(())
+}
+
+// 锁频比的颜色：0/1（摆动）为浅灰，其余按 p/q 的值取色相，分母越大越暗；未锁频为黑色
+fn ratio_color(ratio: Option<(i64, u64)>) -> [u8; 3] {
+    let (p, q) = match ratio {
+        None => return [0, 0, 0],
+        Some((0, _)) => return [220, 220, 220],
+        Some(r) => r,
+    };
+    let hue = (p as f64 / q as f64 * 0.618_033_988_75).rem_euclid(1.0) * 6.0;
+    let value = 1.0 / (1.0 + 0.25 * (q - 1) as f64);
+    let x = 1.0 - (hue % 2.0 - 1.0).abs();
+    let (r, g, b) = match hue as usize {
+        0 => (1.0, x, 0.0),
+        1 => (x, 1.0, 0.0),
+        2 => (0.0, 1.0, x),
+        3 => (0.0, x, 1.0),
+        4 => (x, 0.0, 1.0),
+        _ => (1.0, 0.0, x),
+    };
+    let byte = |c: f64| (255.0 * c * value).round() as u8;
+    [byte(r), byte(g), byte(b)]
+}
+
+/// 把锁频图写成 PNG：横轴 Ω（向右增大），纵轴 f_d（向上增大），每个格点一个像素
+///
+/// 分母不超过 max_denominator 的锁频区按 p/q 着色（同一比值同一颜色），摆动区浅灰，未锁频（准周期或混沌）为黑色。
+pub fn write_arnold_tongues_png(
+    path: &str,
+    map: &ArnoldTongueMap,
+    max_denominator: u64,
+    tolerance: f64,
+) -> std::io::Result<()> {
+    let (width, height) = (map.omega_ds.len(), map.f_ds.len());
+    let mut data = Vec::with_capacity(width * height * 3);
+    for i in (0..height).rev() {
+        for j in 0..width {
+            data.extend_from_slice(&ratio_color(map.ratio_at(i, j, max_denominator, tolerance)));
+        }
+    }
+    write_rgb_png(path, width, height, &data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arnold_tongues_lock_rotation_to_rationals() {
+        assert_eq!(locking_ratio(0.5 + 1e-4, 8, 1e-3), Some((1, 2)));
+        assert_eq!(locking_ratio(-2.0 / 3.0, 8, 1e-9), Some((-2, 3)));
+        assert_eq!(locking_ratio(0.0, 8, 1e-3), Some((0, 1)));
+        assert_eq!(locking_ratio(1.0 / std::f64::consts::SQRT_2, 4, 1e-3), None);
+
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.q = 0.5;
+        params.dt = 0.05;
+        // 弱驱动下只有摆动（ρ = 0），强驱动的 Ω = 2/3 处有同步转动 ρ = ±1
+        let map = arnold_tongues(&params, (0.6, 2.0 / 3.0), 1, (0.3, 1.45), 1, 0.0, 0.0, 200, 100).unwrap();
+        assert_eq!(map.rotations.len(), 4);
+        assert_eq!(map.ratio_at(0, 0, 4, 0.02), Some((0, 1)));
+        assert_eq!(map.ratio_at(0, 1, 4, 0.02), Some((0, 1)));
+        assert!(matches!(map.ratio_at(1, 1, 4, 0.02), Some((1 | -1, 1))), "{}", map.rotation_at(1, 1));
+
+        let path = std::env::temp_dir().join("arnold_tongues_test.csv");
+        write_arnold_tongues_csv(path.to_str().unwrap(), &map).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(text.lines().count(), 3);
+        assert!(text.starts_with("f_d\\omega_d,0.6"));
+
+        for range in [(0.0, 1.0), (-0.5, 1.0), (0.5, f64::INFINITY), (f64::NAN, 1.0)] {
+            let result = arnold_tongues(&params, range, 1, (0.3, 1.45), 1, 0.0, 0.0, 1, 1);
+            assert!(matches!(result, Err(ChaosError::InvalidParams(_))), "{:?}", range);
+        }
+    }
+}
@@ -0,0 +1,255 @@
+//attractor.rs
+use std::fs::File;
+use std::io::Write;
+use crate::basin::{section_distance, AttractorClass};
+use crate::solve_equation::wrap_angle;
+
+/// 庞加莱点集的紧凑描述
+///
+/// θ 的质心用圆周平均，包围盒与 RMS 按相对质心的折叠偏差计算，
+/// 因此跨过 ±π 的吸引子不会被误算成横跨整个区间；theta_min/theta_max 可能略超出 [-π, π)。
+#[derive(Debug, Clone, Copy)]
+pub struct AttractorSummary {
+    pub count: usize,
+    pub theta_min: f64,
+    pub theta_max: f64,
+    pub omega_min: f64,
+    pub omega_max: f64,
+    pub theta_centroid: f64,
+    pub omega_centroid: f64,
+    /// 到质心距离的均方根
+    pub rms_extent: f64,
+}
+
+/// 从庞加莱点序列中检测周期
+///
+/// 返回最小的 p（1 <= p <= max_period），使得所有 i 都满足 |x_{i+p} - x_i| <= tolerance；
+/// 找不到时返回 None（可能是混沌、准周期，或周期大于 max_period）。
+/// 至少需要 2p 个点才会判定周期 p。
+pub fn detect_period(samples: &[(f64, f64)], max_period: usize, tolerance: f64) -> Option<usize> {
+    (1..=max_period)
+        .filter(|p| samples.len() >= 2 * p)
+        .find(|&p| {
+            samples
+                .iter()
+                .zip(samples[p..].iter())
+                .all(|(a, b)| section_distance(*a, *b) <= tolerance)
+        })
+}
+
+/// classify_attractor 检查的最大周期
+pub const MAX_CLASSIFIED_PERIOD: usize = 64;
+
+// 由数据估计的平均拉伸率：对每个点找序号不相邻的最近邻，
+// 比较它们的距离 d0 与下一次回归后的距离 d1，返回 ln(d1/d0) 的平均值。
+// 准周期轨道在不变曲线上近似等距平移，该值接近 0；混沌轨道为正（约等于最大 Lyapunov 指数）。
+fn mean_neighbor_stretch(samples: &[(f64, f64)]) -> Option<f64> {
+    let n = samples.len().checked_sub(1)?;
+    let mut sum = 0.0;
+    let mut count = 0;
+    for i in 0..n {
+        let nearest = (0..n)
+            .filter(|&j| j.abs_diff(i) > 1)
+            .map(|j| (j, section_distance(samples[i], samples[j])))
+            .filter(|(_, d)| *d > 0.0)
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+        if let Some((j, d0)) = nearest {
+            let d1 = section_distance(samples[i + 1], samples[j + 1]);
+            if d1 > 0.0 {
+                sum += (d1 / d0).ln();
+                count += 1;
+            }
+        }
+    }
+    (count > 0).then(|| sum / count as f64)
+}
+
+/// 由庞加莱点判定吸引子类型
+///
+/// 先在 tolerance 内检测周期（1 到 MAX_CLASSIFIED_PERIOD，至少需要两个完整周期的点）；
+/// 不是周期轨道时，用最近邻点对在一次回归后的平均拉伸率区分准周期（≈ 0）与混沌（> 0.1）。
+/// samples 应已去掉过渡期，点数少于 16 或无法估计拉伸率时返回 None。
+/// 没有旋转数可用，周期轨道一律记为 Periodic（区分转动解见 basin::classify_attractor_section）。
+pub fn classify_attractor(samples: &[(f64, f64)], tolerance: f64) -> Option<AttractorClass> {
+    if let Some(p) = detect_period(samples, MAX_CLASSIFIED_PERIOD, tolerance) {
+        return Some(AttractorClass::Periodic(p));
+    }
+    if samples.len() < 16 {
+        return None;
+    }
+    mean_neighbor_stretch(samples).map(|stretch| {
+        if stretch > 0.1 {
+            AttractorClass::Chaotic
+        } else {
+            AttractorClass::QuasiPeriodic
+        }
+    })
+}
+
+/// 计算庞加莱点集的包围盒、质心、RMS 尺度与点数；点集为空时返回 None
+pub fn summarize(samples: &[(f64, f64)]) -> Option<AttractorSummary> {
+    if samples.is_empty() {
+        return None;
+    }
+    let n = samples.len() as f64;
+    let (sin_sum, cos_sum) = samples
+        .iter()
+        .fold((0.0, 0.0), |(s, c), (theta, _)| (s + theta.sin(), c + theta.cos()));
+    let theta_centroid = sin_sum.atan2(cos_sum);
+    let omega_centroid = samples.iter().map(|(_, w)| w).sum::<f64>() / n;
+
+    let mut d_min = f64::INFINITY;
+    let mut d_max = f64::NEG_INFINITY;
+    let mut omega_min = f64::INFINITY;
+    let mut omega_max = f64::NEG_INFINITY;
+    let mut sq_sum = 0.0;
+    for (theta, omega) in samples {
+        let d_theta = wrap_angle(theta - theta_centroid);
+        let d_omega = omega - omega_centroid;
+        d_min = d_min.min(d_theta);
+        d_max = d_max.max(d_theta);
+        omega_min = omega_min.min(*omega);
+        omega_max = omega_max.max(*omega);
+        sq_sum += d_theta * d_theta + d_omega * d_omega;
+    }
+
+    Some(AttractorSummary {
+        count: samples.len(),
+        theta_min: theta_centroid + d_min,
+        theta_max: theta_centroid + d_max,
+        omega_min,
+        omega_max,
+        theta_centroid,
+        omega_centroid,
+        rms_extent: (sq_sum / n).sqrt(),
+    })
+}
+
+/// 每个庞加莱点到参考点集（序列的后半段）的最近距离
+///
+/// 已经落在吸引子上的点距离很小（周期轨道为 0，混沌吸引子为点集的典型间距量级）；
+/// 仍在向吸引子漂移的前导点距离明显偏大。少于两个点时返回全 0。
+pub fn transient_drift(samples: &[(f64, f64)]) -> Vec<f64> {
+    let reference = &samples[samples.len() / 2..];
+    if samples.len() < 2 {
+        return vec![0.0; samples.len()];
+    }
+    samples
+        .iter()
+        .map(|p| {
+            reference
+                .iter()
+                .map(|q| section_distance(*p, *q))
+                .fold(f64::INFINITY, f64::min)
+        })
+        .collect()
+}
+
+/// 去掉仍受过渡过程污染的前导庞加莱点，返回（剩余点, 去掉的点数）
+///
+/// 只检查前半段：最后一个漂移距离超过 tolerance 的点及其之前的点都被丢弃。
+/// 丢弃的点数明显大于 0 时，说明 transient_periods 取小了。
+pub fn trim_transient(samples: &[(f64, f64)], tolerance: f64) -> (&[(f64, f64)], usize) {
+    let drift = transient_drift(samples);
+    let dropped = drift[..samples.len() / 2]
+        .iter()
+        .rposition(|d| *d > tolerance)
+        .map_or(0, |n| n + 1);
+    (&samples[dropped..], dropped)
+}
+
+/// 把一组（参数值, 摘要）写成 CSV，每行一次运行
+pub fn write_attractor_summaries_csv(path: &str, rows: &[(f64, AttractorSummary)]) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(
+        f,
+        "parameter,count,theta_min,theta_max,omega_min,omega_max,theta_centroid,omega_centroid,rms_extent"
+    )?;
+    for (parameter, s) in rows {
+        writeln!(
+            f,
+            "{:.12},{},{:.12},{:.12},{:.12},{:.12},{:.12},{:.12},{:.12}",
+            parameter,
+            s.count,
+            s.theta_min,
+            s.theta_max,
+            s.omega_min,
+            s.omega_max,
+            s.theta_centroid,
+            s.omega_centroid,
+            s.rms_extent
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_detect_period() {
+        let period3: Vec<(f64, f64)> = (0..30).map(|i| ((i % 3) as f64 * 0.5, 0.1)).collect();
+        assert_eq!(detect_period(&period3, 8, 1e-9), Some(3));
+
+        let drifting: Vec<(f64, f64)> = (0..30).map(|i| (i as f64 * 0.01, 0.0)).collect();
+        assert_eq!(detect_period(&drifting, 8, 1e-3), None);
+    }
+
+    #[test]
+    fn test_summary_of_set_straddling_pi() {
+        let samples = vec![(PI - 0.1, 1.0), (-PI + 0.1, -1.0)];
+        let s = summarize(&samples).unwrap();
+        assert_eq!(s.count, 2);
+        assert_relative_eq!(s.theta_centroid.abs(), PI, epsilon = 1e-12);
+        assert_relative_eq!(s.theta_max - s.theta_min, 0.2, epsilon = 1e-12);
+        assert_relative_eq!(s.omega_centroid, 0.0, epsilon = 1e-12);
+        assert_relative_eq!(s.rms_extent, (0.01_f64 + 1.0).sqrt(), epsilon = 1e-12);
+        assert!(summarize(&[]).is_none());
+    }
+
+    #[test]
+    fn test_trim_transient_of_converging_period2() {
+        // 周期 2 轨道叠加指数衰减的漂移
+        let samples: Vec<(f64, f64)> = (0..60)
+            .map(|n| {
+                let side = if n % 2 == 0 { 1.0 } else { -1.0 };
+                (side + 0.5 * 0.7_f64.powi(n), 0.2)
+            })
+            .collect();
+        let (kept, dropped) = trim_transient(&samples, 1e-3);
+        // 0.5·0.7^n <= 1e-3 要求 n >= 18
+        assert_eq!(dropped, 18);
+        assert_eq!(kept.len(), 42);
+        assert_eq!(detect_period(kept, 4, 1e-3), Some(2));
+    }
+
+    #[test]
+    fn test_classify_periodic_quasiperiodic_and_chaotic() {
+        let period4: Vec<(f64, f64)> = (0..40).map(|i| ((i % 4) as f64 * 0.3, -0.2)).collect();
+        assert_eq!(classify_attractor(&period4, 1e-9), Some(AttractorClass::Periodic(4)));
+
+        // 以黄金分割角在圆上旋转：不变曲线上的等距映射
+        let golden = (5f64.sqrt() - 1.0) / 2.0;
+        let circle: Vec<(f64, f64)> = (0..500)
+            .map(|n| {
+                let phase = 2.0 * PI * golden * n as f64;
+                (0.5 * phase.cos(), 0.5 * phase.sin())
+            })
+            .collect();
+        assert_eq!(classify_attractor(&circle, 1e-6), Some(AttractorClass::QuasiPeriodic));
+
+        // r = 4 的 logistic 映射，Lyapunov 指数为 ln 2
+        let mut x: f64 = 0.123;
+        let logistic: Vec<(f64, f64)> = (0..500)
+            .map(|_| {
+                x = 4.0 * x * (1.0 - x);
+                (x, 0.0)
+            })
+            .collect();
+        assert_eq!(classify_attractor(&logistic, 1e-6), Some(AttractorClass::Chaotic));
+        assert_eq!(classify_attractor(&logistic[..5], 1e-6), None);
+    }
+}
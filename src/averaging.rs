@@ -0,0 +1,224 @@
+//averaging.rs
+use std::f64::consts::PI;
+use std::fs::File;
+use std::io::Write;
+use crate::model::PendulumParams;
+use crate::solve_equation::{steps_per_period, wrap_angle, State, TrajectoryIter};
+
+// 平均法（慢变流）近似：
+// 把 θ'' + qθ' + ω0² sinθ = F sin(Ωt) 中的 sinθ 展开到三次项，
+// 设 θ = a cos(Ωt + ψ)，θ' = -aΩ sin(Ωt + ψ)，对一个驱动周期取平均得到
+//   a' = -q a / 2 - F cosψ / (2Ω)
+//   ψ' = -(Ω² - ω0²) / (2Ω) - ω0² a² / (16Ω) + F sinψ / (2aΩ)
+// 为避免 a = 0 处的奇点，内部用 u = a cosψ, v = a sinψ 积分：
+//   u' = -q u / 2 + σ(a) v - F / (2Ω)
+//   v' = -q v / 2 - σ(a) u
+// 其中 σ(a) = (Ω² - ω0²) / (2Ω) + ω0² a² / (16Ω)。
+
+#[derive(Debug, Clone, Copy)]
+pub struct SlowFlowState {
+    pub a: f64,
+    pub psi: f64,
+}
+
+/// 平均法与完整模拟在同一时刻的对比
+#[derive(Debug, Clone, Copy)]
+pub struct AveragingComparison {
+    pub t: f64,
+    pub a_averaged: f64,
+    pub psi_averaged: f64,
+    pub a_full: f64,
+    pub psi_full: f64,
+}
+
+// 频率失谐项 σ(a)
+fn detuning(a: f64, params: &PendulumParams) -> f64 {
+    let omega0_sq = params.g / params.l;
+    let big_omega = params.omega_d;
+    (big_omega * big_omega - omega0_sq) / (2.0 * big_omega) + omega0_sq * a * a / (16.0 * big_omega)
+}
+
+/// 慢变流方程（振幅/相位形式），返回 (da/dt, dψ/dt)
+pub fn slow_flow_rhs(state: &SlowFlowState, params: &PendulumParams) -> (f64, f64) {
+    let big_omega = params.omega_d;
+    let da = -0.5 * params.q * state.a - params.f_d * state.psi.cos() / (2.0 * big_omega);
+    let dpsi = -detuning(state.a, params) + params.f_d * state.psi.sin() / (2.0 * state.a * big_omega);
+    (da, dpsi)
+}
+
+// 直角坐标形式的慢变流，输入 (u, v) 返回 (du/dt, dv/dt)
+fn slow_flow_rhs_cartesian(u: f64, v: f64, params: &PendulumParams) -> (f64, f64) {
+    let sigma = detuning((u * u + v * v).sqrt(), params);
+    let du = -0.5 * params.q * u + sigma * v - params.f_d / (2.0 * params.omega_d);
+    let dv = -0.5 * params.q * v - sigma * u;
+    (du, dv)
+}
+
+fn to_polar(u: f64, v: f64) -> SlowFlowState {
+    SlowFlowState {
+        a: (u * u + v * v).sqrt(),
+        psi: v.atan2(u),
+    }
+}
+
+/// 把完整系统在 t 时刻的状态投影到慢变量 (a, ψ)
+pub fn project_to_slow(t: f64, state: &State, params: &PendulumParams) -> SlowFlowState {
+    let big_omega = params.omega_d;
+    let x = state.theta;
+    let y = -state.omega / big_omega;
+    SlowFlowState {
+        a: (x * x + y * y).sqrt(),
+        psi: wrap_angle(y.atan2(x) - big_omega * t),
+    }
+}
+
+/// 用 RK4 积分慢变流，步长与总时长沿用 params.dt / params.t_end
+pub fn solve_slow_flow(params: &PendulumParams, initial: SlowFlowState) -> Vec<(f64, SlowFlowState)> {
+    let mut u = initial.a * initial.psi.cos();
+    let mut v = initial.a * initial.psi.sin();
+    let h = params.dt;
+    let mut t = 0.0;
+    let mut out = vec![(t, to_polar(u, v))];
+
+    let steps = (params.t_end / params.dt) as usize;
+    for _ in 0..steps {
+        let (k1u, k1v) = slow_flow_rhs_cartesian(u, v, params);
+        let (k2u, k2v) = slow_flow_rhs_cartesian(u + 0.5 * h * k1u, v + 0.5 * h * k1v, params);
+        let (k3u, k3v) = slow_flow_rhs_cartesian(u + 0.5 * h * k2u, v + 0.5 * h * k2v, params);
+        let (k4u, k4v) = slow_flow_rhs_cartesian(u + h * k3u, v + h * k3v, params);
+        u += h / 6.0 * (k1u + 2.0 * k2u + 2.0 * k3u + k4u);
+        v += h / 6.0 * (k1v + 2.0 * k2v + 2.0 * k3v + k4v);
+        t += h;
+        out.push((t, to_polar(u, v)));
+    }
+    out
+}
+
+/// 平均法预测的稳态振幅（频率响应方程的所有正实根，从小到大）
+///
+/// 稳态满足 F² = a²[(qΩ)² + (Ω² - ω0² + ω0² a² / 8)²]，
+/// 令 x = a² 即为 x 的三次方程；三个根都为正时对应跳跃现象中的双稳区。
+pub fn steady_state_amplitudes(params: &PendulumParams) -> Vec<f64> {
+    let omega0_sq = params.g / params.l;
+    let big_omega = params.omega_d;
+    let k = omega0_sq / 8.0;
+    let delta = big_omega * big_omega - omega0_sq;
+    let damping = params.q * big_omega;
+
+    let mut roots: Vec<f64> = real_cubic_roots(
+        k * k,
+        2.0 * delta * k,
+        delta * delta + damping * damping,
+        -params.f_d * params.f_d,
+    )
+    .into_iter()
+    .filter(|x| *x > 0.0)
+    .map(|x| x.sqrt())
+    .collect();
+    roots.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    roots
+}
+
+// 实系数三次方程 a x³ + b x² + c x + d = 0 的全部实根（三角/卡尔达诺公式）
+fn real_cubic_roots(a: f64, b: f64, c: f64, d: f64) -> Vec<f64> {
+    // 化为 t³ + p t + q = 0，x = t - b / (3a)
+    let b = b / a;
+    let c = c / a;
+    let d = d / a;
+    let shift = b / 3.0;
+    let p = c - b * b / 3.0;
+    let q = 2.0 * b * b * b / 27.0 - b * c / 3.0 + d;
+    let disc = (q / 2.0).powi(2) + (p / 3.0).powi(3);
+
+    if disc > 0.0 {
+        let s = disc.sqrt();
+        let t = (-q / 2.0 + s).cbrt() + (-q / 2.0 - s).cbrt();
+        vec![t - shift]
+    } else if p == 0.0 {
+        vec![-shift]
+    } else {
+        let r = 2.0 * (-p / 3.0).sqrt();
+        let phi = ((3.0 * q) / (p * r)).clamp(-1.0, 1.0).acos() / 3.0;
+        (0..3)
+            .map(|k| r * (phi - 2.0 * PI * k as f64 / 3.0).cos() - shift)
+            .collect()
+    }
+}
+
+/// 在每个驱动周期时刻对比慢变流与完整 RK4 模拟
+///
+/// 慢变流的初值取完整系统初始状态在 t = 0 处的投影，
+/// 两者使用相同的 dt 与 t_end，因此时间网格一一对应。
+pub fn compare_with_simulation(
+    params: &PendulumParams,
+    initial_theta: f64,
+    initial_omega: f64,
+) -> Vec<AveragingComparison> {
+    let initial = State {
+        theta: initial_theta,
+        omega: initial_omega,
+    };
+    let full: Vec<(f64, State)> = TrajectoryIter::new(params, initial_theta, initial_omega).collect();
+    let slow = solve_slow_flow(params, project_to_slow(0.0, &initial, params));
+
+    full.iter()
+        .zip(slow.iter())
+        .step_by(steps_per_period(params))
+        .map(|((t, s_full), (_, s_slow))| {
+            let projected = project_to_slow(*t, s_full, params);
+            AveragingComparison {
+                t: *t,
+                a_averaged: s_slow.a,
+                psi_averaged: wrap_angle(s_slow.psi),
+                a_full: projected.a,
+                psi_full: projected.psi,
+            }
+        })
+        .collect()
+}
+
+/// 把对比结果写成 CSV（列：t,a_averaged,psi_averaged,a_full,psi_full）
+pub fn write_averaging_comparison_csv(
+    path: &str,
+    params: &PendulumParams,
+    initial_theta: f64,
+    initial_omega: f64,
+) -> std::io::Result<()> {
+    let rows = compare_with_simulation(params, initial_theta, initial_omega);
+    let mut f = File::create(path)?;
+    writeln!(f, "t,a_averaged,psi_averaged,a_full,psi_full")?;
+    for r in rows {
+        writeln!(
+            f,
+            "{:.12},{:.12},{:.12},{:.12},{:.12}",
+            r.t, r.a_averaged, r.psi_averaged, r.a_full, r.psi_full
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_averaging_matches_simulation_for_weak_forcing() {
+        // 弱驱动、弱阻尼、接近共振
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.q = 0.1;
+        params.f_d = 0.05;
+        params.omega_d = 0.9;
+        params.dt = 0.01;
+        params.t_end = 300.0;
+
+        let predicted = steady_state_amplitudes(&params);
+        assert_eq!(predicted.len(), 1);
+
+        let rows = compare_with_simulation(&params, 0.0, 0.0);
+        let last = rows.last().unwrap();
+        assert_relative_eq!(last.a_full, predicted[0], max_relative = 0.05);
+        assert_relative_eq!(last.a_averaged, predicted[0], max_relative = 1e-3);
+    }
+}
@@ -0,0 +1,720 @@
+//basin.rs
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use rayon::prelude::*;
+use crate::attractor::detect_period;
+use crate::model::PendulumParams;
+use crate::random::RandomSource;
+use crate::resumable::ResumableCsv;
+use crate::solve_equation::{wrap_angle, State};
+use crate::stroboscopic::{orbit_rotation_number, section_points, StroboscopicMap};
+
+/// 多吸引子吸引域图
+///
+/// labels 按 θ 为外层、ω 为内层的顺序排列，长度为 thetas.len() * omegas.len()；
+/// labels[i] 是 attractors 中的下标。
+#[derive(Debug, Clone)]
+pub struct BasinMap {
+    pub thetas: Vec<f64>,
+    pub omegas: Vec<f64>,
+    pub labels: Vec<usize>,
+    /// 每个吸引子第一次被发现时的庞加莱点集
+    pub attractors: Vec<Vec<(f64, f64)>>,
+    /// 每个吸引子的代表初值 (theta, omega)，即第一次到达它的格点
+    pub representatives: Vec<(f64, f64)>,
+}
+
+/// 去重后的一个吸引子
+#[derive(Debug, Clone)]
+pub struct DistinctAttractor {
+    pub index: usize,
+    pub initial_theta: f64,
+    pub initial_omega: f64,
+    /// 收敛到该吸引子的格点数及其占比（吸引域面积占比的估计）
+    pub cells: usize,
+    pub fraction: f64,
+    /// 代表轨道的庞加莱点
+    pub section: Vec<(f64, f64)>,
+}
+
+impl BasinMap {
+    pub fn label_at(&self, i_theta: usize, i_omega: usize) -> usize {
+        self.labels[i_theta * self.omegas.len() + i_omega]
+    }
+
+    /// 吸引域边界上的格点 (θ, ω)：与上下左右某个相邻格点的吸引子标签不同
+    ///
+    /// 可交给 analysis::box_counting_dimension 估计边界的分形维数（光滑边界为 1）。
+    pub fn boundary_points(&self) -> Vec<(f64, f64)> {
+        let (nt, no) = (self.thetas.len(), self.omegas.len());
+        let mut out = Vec::new();
+        for i in 0..nt {
+            for j in 0..no {
+                let label = self.label_at(i, j);
+                let differs = (i > 0 && self.label_at(i - 1, j) != label)
+                    || (i + 1 < nt && self.label_at(i + 1, j) != label)
+                    || (j > 0 && self.label_at(i, j - 1) != label)
+                    || (j + 1 < no && self.label_at(i, j + 1) != label);
+                if differs {
+                    out.push((self.thetas[i], self.omegas[j]));
+                }
+            }
+        }
+        out
+    }
+
+    /// 列出所有互不相同的吸引子，附代表初值、代表轨道与吸引域占比
+    pub fn distinct_attractors(&self) -> Vec<DistinctAttractor> {
+        let total = self.labels.len().max(1);
+        self.attractors
+            .iter()
+            .zip(&self.representatives)
+            .enumerate()
+            .map(|(index, (section, (theta, omega)))| {
+                let cells = self.labels.iter().filter(|l| **l == index).count();
+                DistinctAttractor {
+                    index,
+                    initial_theta: *theta,
+                    initial_omega: *omega,
+                    cells,
+                    fraction: cells as f64 / total as f64,
+                    section: section.clone(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// 截面上两点的距离，θ 方向按周期 2π 取最短差
+pub fn section_distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    let d_theta = wrap_angle(a.0 - b.0);
+    let d_omega = a.1 - b.1;
+    (d_theta * d_theta + d_omega * d_omega).sqrt()
+}
+
+// 单向 Hausdorff 距离：a 中每个点到 b 的最近距离的最大值
+fn directed_hausdorff(a: &[(f64, f64)], b: &[(f64, f64)]) -> f64 {
+    a.iter()
+        .map(|p| b.iter().map(|q| section_distance(*p, *q)).fold(f64::INFINITY, f64::min))
+        .fold(0.0, f64::max)
+}
+
+/// 判断两组庞加莱点是否属于同一个吸引子（对称 Hausdorff 距离不超过 tolerance）
+///
+/// 周期轨道的点集会精确重合；对混沌吸引子，tolerance 需要取到点集的典型间距量级。
+pub fn same_attractor(a: &[(f64, f64)], b: &[(f64, f64)], tolerance: f64) -> bool {
+    if a.is_empty() || b.is_empty() {
+        return a.is_empty() && b.is_empty();
+    }
+    directed_hausdorff(a, b) <= tolerance && directed_hausdorff(b, a) <= tolerance
+}
+
+/// 两组庞加莱点的对称平均最近邻距离：各自的每个点到另一组最近点的距离取平均，再对两个方向取平均
+///
+/// 同一个混沌吸引子上的两段有限采样互相覆盖不全，Hausdorff 距离由最稀疏处决定，始终在吸引子尺度；
+/// 平均值则随采样变密而趋于零，适合判断两段采样是否来自同一个混沌吸引子。任一组为空时返回 NaN。
+pub fn mean_section_distance(a: &[(f64, f64)], b: &[(f64, f64)]) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return f64::NAN;
+    }
+    let mean_nearest = |from: &[(f64, f64)], to: &[(f64, f64)]| {
+        from.iter()
+            .map(|p| to.iter().map(|q| section_distance(*p, *q)).fold(f64::INFINITY, f64::min))
+            .sum::<f64>()
+            / from.len() as f64
+    };
+    0.5 * (mean_nearest(a, b) + mean_nearest(b, a))
+}
+
+/// 对一组庞加莱点集去重
+///
+/// 返回 (labels, representatives)：labels[i] 为第 i 组所属吸引子编号，
+/// representatives[k] 为第 k 个吸引子第一次出现的下标。
+pub fn deduplicate_attractors(sections: &[Vec<(f64, f64)>], tolerance: f64) -> (Vec<usize>, Vec<usize>) {
+    let mut labels = Vec::with_capacity(sections.len());
+    let mut representatives: Vec<usize> = Vec::new();
+    for (i, section) in sections.iter().enumerate() {
+        let label = match representatives
+            .iter()
+            .position(|&r| same_attractor(&sections[r], section, tolerance))
+        {
+            Some(k) => k,
+            None => {
+                representatives.push(i);
+                representatives.len() - 1
+            }
+        };
+        labels.push(label);
+    }
+    (labels, representatives)
+}
+
+/// 在 params 的 θ/ω 网格上计算吸引域，并自动枚举共存吸引子
+///
+/// 每个格点积分 transient_periods + sample_periods 个整周期，
+/// 取收敛后的庞加莱点集与已知吸引子逐一比较，匹配则沿用其编号，否则登记为新吸引子。
+pub fn basin_labels(
+    params: &PendulumParams,
+    transient_periods: usize,
+    sample_periods: usize,
+    tolerance: f64,
+) -> BasinMap {
+    let map = StroboscopicMap::new(params);
+    let thetas = params.theta_grid();
+    let omegas = params.omega_grid();
+    let mut labels = Vec::with_capacity(thetas.len() * omegas.len());
+    let mut attractors: Vec<Vec<(f64, f64)>> = Vec::new();
+    let mut representatives = Vec::new();
+
+    for &theta in &thetas {
+        for &omega in &omegas {
+            let section = map.section(State { theta, omega }, transient_periods, sample_periods);
+            let label = match attractors.iter().position(|a| same_attractor(a, &section, tolerance)) {
+                Some(i) => i,
+                None => {
+                    attractors.push(section);
+                    representatives.push((theta, omega));
+                    attractors.len() - 1
+                }
+            };
+            labels.push(label);
+        }
+    }
+
+    BasinMap {
+        thetas,
+        omegas,
+        labels,
+        attractors,
+        representatives,
+    }
+}
+
+/// 终态吸引子的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttractorClass {
+    /// 摆动的周期 p 轨道（无净转动）
+    Periodic(usize),
+    /// 周期性的转动解：庞加莱点周期重复，但每个周期净转过整数圈
+    Rotating,
+    /// 准周期：点填满一条闭曲线，邻近点的像仍然邻近；只有 attractor::classify_attractor 会给出
+    QuasiPeriodic,
+    /// 混沌；classify_attractor_section 在 max_period 内找不到周期时也归为此类（不区分准周期与周期过长）
+    Chaotic,
+}
+
+impl AttractorClass {
+    /// CSV 中的类型名
+    pub fn name(&self) -> &'static str {
+        match self {
+            AttractorClass::Periodic(_) => "periodic",
+            AttractorClass::Rotating => "rotating",
+            AttractorClass::QuasiPeriodic => "quasi_periodic",
+            AttractorClass::Chaotic => "chaotic",
+        }
+    }
+
+    /// 作图用的整数编码：周期 p 为 p，转动为 -1，准周期为 -2，混沌为 0
+    pub fn code(&self) -> i64 {
+        match self {
+            AttractorClass::Periodic(p) => *p as i64,
+            AttractorClass::Rotating => -1,
+            AttractorClass::QuasiPeriodic => -2,
+            AttractorClass::Chaotic => 0,
+        }
+    }
+
+    // code() 的逆映射
+    fn from_code(code: i64) -> Self {
+        match code {
+            -1 => AttractorClass::Rotating,
+            -2 => AttractorClass::QuasiPeriodic,
+            p if p > 0 => AttractorClass::Periodic(p as usize),
+            _ => AttractorClass::Chaotic,
+        }
+    }
+
+    // 吸引域图中的颜色
+    fn color(&self) -> [u8; 3] {
+        match self {
+            AttractorClass::Periodic(1) => [49, 104, 196],
+            AttractorClass::Periodic(2) => [64, 168, 90],
+            AttractorClass::Periodic(_) => [230, 190, 60],
+            AttractorClass::Rotating => [200, 60, 60],
+            AttractorClass::QuasiPeriodic => [150, 90, 200],
+            AttractorClass::Chaotic => [20, 20, 20],
+        }
+    }
+}
+
+/// 由庞加莱点与旋转数判定吸引子类型
+///
+/// 找到周期且 |rotation| 超过 0.01 圈/周期时视为转动解，否则为周期 p 的摆动；找不到周期即为混沌。
+pub fn classify_attractor_section(
+    section: &[(f64, f64)],
+    rotation: f64,
+    max_period: usize,
+    tolerance: f64,
+) -> AttractorClass {
+    match detect_period(section, max_period, tolerance) {
+        Some(_) if rotation.abs() > 0.01 => AttractorClass::Rotating,
+        Some(p) => AttractorClass::Periodic(p),
+        None => AttractorClass::Chaotic,
+    }
+}
+
+/// 按吸引子类型着色的吸引域图，classes 按 θ 为外层、ω 为内层排列
+#[derive(Debug, Clone)]
+pub struct BasinClassification {
+    pub thetas: Vec<f64>,
+    pub omegas: Vec<f64>,
+    pub classes: Vec<AttractorClass>,
+}
+
+impl BasinClassification {
+    pub fn class_at(&self, i_theta: usize, i_omega: usize) -> AttractorClass {
+        self.classes[i_theta * self.omegas.len() + i_omega]
+    }
+}
+
+/// 在 params 的 θ/ω 网格上并行积分每个初值，判定其终态吸引子类型
+///
+/// 每个格点积分 transient_periods + sample_periods 个整周期，
+/// 用后 sample_periods 个周期的庞加莱点与旋转数调用 classify_attractor_section。
+pub fn classify_basin(
+    params: &PendulumParams,
+    transient_periods: usize,
+    sample_periods: usize,
+    max_period: usize,
+    tolerance: f64,
+) -> BasinClassification {
+    let map = StroboscopicMap::new(params);
+    let thetas = params.theta_grid();
+    let omegas = params.omega_grid();
+    let cells: Vec<(f64, f64)> = thetas
+        .iter()
+        .flat_map(|&theta| omegas.iter().map(move |&omega| (theta, omega)))
+        .collect();
+    let classes = cells
+        .par_iter()
+        .map(|&(theta, omega)| classify_cell(&map, (theta, omega), transient_periods, sample_periods, max_period, tolerance))
+        .collect();
+    BasinClassification {
+        thetas,
+        omegas,
+        classes,
+    }
+}
+
+// 单个初值的终态吸引子类型
+fn classify_cell(
+    map: &StroboscopicMap,
+    (theta, omega): (f64, f64),
+    transient_periods: usize,
+    sample_periods: usize,
+    max_period: usize,
+    tolerance: f64,
+) -> AttractorClass {
+    let orbit = map.orbit(State { theta, omega }, transient_periods, sample_periods);
+    classify_attractor_section(&section_points(&orbit), orbit_rotation_number(&orbit), max_period, tolerance)
+}
+
+/// 与 classify_basin 相同，但每算完一列（一个 θ 上的全部 ω）就追加写入 path
+/// （theta,omega,class,code，与 write_basin_classification_csv 相同，格式见 resumable 模块）
+///
+/// path 是上次中断留下的部分结果时跳过已完成的列；已完成列的类型从 code 列读回。
+pub fn classify_basin_resumable(
+    params: &PendulumParams,
+    transient_periods: usize,
+    sample_periods: usize,
+    max_period: usize,
+    tolerance: f64,
+    path: &str,
+) -> std::io::Result<BasinClassification> {
+    let map = StroboscopicMap::new(params);
+    let thetas = params.theta_grid();
+    let omegas = params.omega_grid();
+    let mut csv = ResumableCsv::open(path, "theta,omega,class,code")?;
+    let mut columns: Vec<Option<Vec<AttractorClass>>> = vec![None; thetas.len()];
+    for unit in csv.completed() {
+        let classes = unit
+            .rows
+            .iter()
+            .map(|row| {
+                let code = row.rsplit(',').next().and_then(|c| c.parse().ok());
+                code.map(AttractorClass::from_code).ok_or_else(|| {
+                    std::io::Error::new(std::io::ErrorKind::InvalidData, format!("{}: bad row {:?}", path, row))
+                })
+            })
+            .collect::<std::io::Result<Vec<_>>>()?;
+        if let Some(column) = columns.get_mut(unit.index) {
+            *column = Some(classes);
+        }
+    }
+    for (i, &theta) in thetas.iter().enumerate() {
+        if columns[i].is_some() {
+            continue;
+        }
+        let classes: Vec<AttractorClass> = omegas
+            .par_iter()
+            .map(|&omega| classify_cell(&map, (theta, omega), transient_periods, sample_periods, max_period, tolerance))
+            .collect();
+        csv.write_unit(i, &[], |w| {
+            omegas.iter().zip(&classes).try_for_each(|(omega, c)| {
+                writeln!(w, "{:.12},{:.12},{},{}", theta, omega, c.name(), c.code())
+            })
+        })?;
+        columns[i] = Some(classes);
+    }
+    let classes = columns.into_iter().flatten().flatten().collect();
+    Ok(BasinClassification {
+        thetas,
+        omegas,
+        classes,
+    })
+}
+
+/// 把吸引子类型图写成 CSV（列：theta,omega,class,code）
+pub fn write_basin_classification_csv(path: &str, map: &BasinClassification) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(f, "theta,omega,class,code")?;
+    for (i, theta) in map.thetas.iter().enumerate() {
+        for (j, omega) in map.omegas.iter().enumerate() {
+            let c = map.class_at(i, j);
+            writeln!(f, "{:.12},{:.12},{},{}", theta, omega, c.name(), c.code())?;
+        }
+    }
+    Ok(())
+}
+
+/// 把吸引子类型图写成 PNG：横轴 θ（向右增大），纵轴 ω（向上增大），每个格点一个像素
+///
+/// 颜色：周期 1 蓝、周期 2 绿、更高周期黄、转动红、混沌黑。
+pub fn write_basin_png(path: &str, map: &BasinClassification) -> std::io::Result<()> {
+    let (width, height) = (map.thetas.len(), map.omegas.len());
+    let mut data = Vec::with_capacity(width * height * 3);
+    for j in (0..height).rev() {
+        for i in 0..width {
+            data.extend_from_slice(&map.class_at(i, j).color());
+        }
+    }
+    write_rgb_png(path, width, height, &data)
+}
+
+// 把按行（自上而下）排列的 8 位 RGB 像素写成 PNG
+pub(crate) fn write_rgb_png(path: &str, width: usize, height: usize, data: &[u8]) -> std::io::Result<()> {
+    let file = std::io::BufWriter::new(File::create(path)?);
+    let mut encoder = png::Encoder::new(file, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(std::io::Error::other)?;
+    writer.write_image_data(data).map_err(std::io::Error::other)?;
+    Ok(())
+}
+
+/// 把吸引域标签写成 CSV（三列：theta,omega,label）
+pub fn write_basin_labels_csv(path: &str, map: &BasinMap) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(f, "theta,omega,label")?;
+    for (i, theta) in map.thetas.iter().enumerate() {
+        for (j, omega) in map.omegas.iter().enumerate() {
+            writeln!(f, "{:.12},{:.12},{}", theta, omega, map.label_at(i, j))?;
+        }
+    }
+    Ok(())
+}
+
+/// 随机搜索共存吸引子的设置
+#[derive(Debug, Clone)]
+pub struct AttractorSearchOptions {
+    /// 随机初值个数
+    pub trials: usize,
+    pub transient_periods: usize,
+    pub sample_periods: usize,
+    /// 周期检测的最大周期与容差（classify_attractor_section）
+    pub max_period: usize,
+    pub period_tolerance: f64,
+    /// 周期或转动吸引子判为同一个的 Hausdorff 距离上限
+    pub match_tolerance: f64,
+    /// 混沌吸引子判为同一个的平均最近邻距离上限（mean_section_distance）
+    pub chaotic_tolerance: f64,
+}
+
+impl Default for AttractorSearchOptions {
+    fn default() -> Self {
+        AttractorSearchOptions {
+            trials: 500,
+            transient_periods: 200,
+            sample_periods: 64,
+            max_period: 16,
+            period_tolerance: 1e-5,
+            match_tolerance: 1e-3,
+            chaotic_tolerance: 0.2,
+        }
+    }
+}
+
+/// 随机搜索找到的一个吸引子
+#[derive(Debug, Clone)]
+pub struct FoundAttractor {
+    pub index: usize,
+    pub class: AttractorClass,
+    /// 代表初值：第一个落到该吸引子的随机初值
+    pub initial_theta: f64,
+    pub initial_omega: f64,
+    /// 落到该吸引子的初值个数与占比（吸引域在采样区域内的面积占比的估计）
+    pub hits: usize,
+    pub fraction: f64,
+    /// 占比的二项分布标准误差 √(p(1 - p)/n)
+    pub fraction_error: f64,
+    /// 代表轨道的庞加莱点
+    pub section: Vec<(f64, f64)>,
+}
+
+/// 在 [theta_start, theta_end) × [omega_start, omega_end) 内随机撒 options.trials 个初值，找出全部共存吸引子
+///
+/// 初值按 rng 的顺序一次取齐，积分在 rayon 线程池里并行，去重按初值顺序串行进行，
+/// 因此同一种子给出同样的结果。每个初值积分 transient_periods + sample_periods 个整周期，
+/// 终态先用 classify_attractor_section 分类，再与已知的同类吸引子比较庞加莱点集：
+/// 周期与转动吸引子用 same_attractor，混沌吸引子用 mean_section_distance（有限采样互相覆盖不全）。
+/// 匹配则计入其命中数，否则登记为新吸引子。类型不同的终态不会合并。
+pub fn search_attractors<R: RandomSource>(
+    params: &PendulumParams,
+    rng: &mut R,
+    options: &AttractorSearchOptions,
+) -> Vec<FoundAttractor> {
+    let map = StroboscopicMap::new(params);
+    let initials: Vec<(f64, f64)> = (0..options.trials)
+        .map(|_| {
+            let theta = rng.uniform(params.theta_start, params.theta_end);
+            (theta, rng.uniform(params.omega_start, params.omega_end))
+        })
+        .collect();
+    let finals: Vec<(AttractorClass, Vec<(f64, f64)>)> = initials
+        .par_iter()
+        .map(|&(theta, omega)| {
+            let orbit = map.orbit(State { theta, omega }, options.transient_periods, options.sample_periods);
+            let section = section_points(&orbit);
+            let class = classify_attractor_section(
+                &section,
+                orbit_rotation_number(&orbit),
+                options.max_period,
+                options.period_tolerance,
+            );
+            (class, section)
+        })
+        .collect();
+
+    let mut found: Vec<FoundAttractor> = Vec::new();
+    for (&(theta, omega), (class, section)) in initials.iter().zip(finals) {
+        let matches = |a: &FoundAttractor| match class {
+            AttractorClass::Chaotic => mean_section_distance(&a.section, &section) <= options.chaotic_tolerance,
+            _ => same_attractor(&a.section, &section, options.match_tolerance),
+        };
+        match found.iter_mut().find(|a| a.class == class && matches(a)) {
+            Some(a) => a.hits += 1,
+            None => found.push(FoundAttractor {
+                index: found.len(),
+                class,
+                initial_theta: theta,
+                initial_omega: omega,
+                hits: 1,
+                fraction: 0.0,
+                fraction_error: 0.0,
+                section,
+            }),
+        }
+    }
+    let n = options.trials.max(1) as f64;
+    for a in &mut found {
+        a.fraction = a.hits as f64 / n;
+        a.fraction_error = (a.fraction * (1.0 - a.fraction) / n).sqrt();
+    }
+    found
+}
+
+/// 把随机搜索的结果写成 CSV（列：attractor,class,code,initial_theta,initial_omega,hits,fraction,fraction_error,section_points）
+pub fn write_found_attractors_csv(path: &str, attractors: &[FoundAttractor]) -> std::io::Result<()> {
+    let mut f = BufWriter::new(File::create(path)?);
+    writeln!(f, "attractor,class,code,initial_theta,initial_omega,hits,fraction,fraction_error,section_points")?;
+    for a in attractors {
+        writeln!(
+            f,
+            "{},{},{},{:.12},{:.12},{},{:.12},{:.12},{}",
+            a.index,
+            a.class.name(),
+            a.class.code(),
+            a.initial_theta,
+            a.initial_omega,
+            a.hits,
+            a.fraction,
+            a.fraction_error,
+            a.section.len()
+        )?;
+    }
+    f.flush()
+}
+
+/// 把枚举出的吸引子写成 CSV（三列：attractor,theta,omega）
+pub fn write_attractors_csv(path: &str, map: &BasinMap) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(f, "attractor,theta,omega")?;
+    for (k, points) in map.attractors.iter().enumerate() {
+        for (theta, omega) in points {
+            writeln!(f, "{},{:.12},{:.12}", k, theta, omega)?;
+        }
+    }
+    Ok(())
+}
+
+/// 把去重后的吸引子列表写成 CSV（列：attractor,initial_theta,initial_omega,cells,fraction,section_points）
+pub fn write_distinct_attractors_csv(path: &str, attractors: &[DistinctAttractor]) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(f, "attractor,initial_theta,initial_omega,cells,fraction,section_points")?;
+    for a in attractors {
+        writeln!(
+            f,
+            "{},{:.12},{:.12},{},{:.12},{}",
+            a.index,
+            a.initial_theta,
+            a.initial_omega,
+            a.cells,
+            a.fraction,
+            a.section.len()
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_same_attractor_across_angle_wrap() {
+        // 周期 2 轨道，其中一个点跨过 ±π
+        let a = vec![(PI - 1e-4, 0.5), (0.2, -0.3)];
+        let b = vec![(0.2 + 1e-4, -0.3), (-PI + 2e-4, 0.5)];
+        assert!(same_attractor(&a, &b, 1e-3));
+
+        let c = vec![(0.2, -0.3)];
+        assert!(!same_attractor(&a, &c, 1e-3));
+    }
+
+    #[test]
+    fn test_deduplicate_attractors() {
+        let sections = vec![
+            vec![(0.1, 0.2)],
+            vec![(1.0, -0.5), (-1.0, 0.5)],
+            vec![(0.1 + 1e-6, 0.2)],
+            vec![(-1.0, 0.5), (1.0, -0.5)],
+        ];
+        let (labels, representatives) = deduplicate_attractors(&sections, 1e-4);
+        assert_eq!(labels, vec![0, 1, 0, 1]);
+        assert_eq!(representatives, vec![0, 1]);
+    }
+
+    #[test]
+    fn test_classify_sections_and_write_png() {
+        let period2: Vec<(f64, f64)> = (0..20).map(|i| ((i % 2) as f64, 0.0)).collect();
+        let scattered: Vec<(f64, f64)> = (0..20).map(|i| ((i as f64 * 1.7).sin(), i as f64 * 0.1)).collect();
+        assert_eq!(classify_attractor_section(&period2, 0.0, 8, 1e-6), AttractorClass::Periodic(2));
+        assert_eq!(classify_attractor_section(&period2[..10], 1.0, 8, 1e-6), AttractorClass::Rotating);
+        assert_eq!(classify_attractor_section(&scattered, 0.0, 8, 1e-6), AttractorClass::Chaotic);
+
+        let map = BasinClassification {
+            thetas: vec![0.0, 1.0, 2.0],
+            omegas: vec![0.0, 1.0],
+            classes: vec![
+                AttractorClass::Periodic(1),
+                AttractorClass::Periodic(2),
+                AttractorClass::Rotating,
+                AttractorClass::Chaotic,
+                AttractorClass::Periodic(3),
+                AttractorClass::QuasiPeriodic,
+            ],
+        };
+        for c in &map.classes {
+            assert_eq!(AttractorClass::from_code(c.code()), *c);
+        }
+        let path = std::env::temp_dir().join("chaos_basin_test.png");
+        write_basin_png(path.to_str().unwrap(), &map).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        assert_eq!(&bytes[..8], b"\x89PNG\r\n\x1a\n");
+    }
+
+    #[test]
+    fn test_boundary_points_of_straight_boundary() {
+        // 标签按 θ + ω 的符号划分：边界是一条直线，盒计数维数约为 1
+        let grid: Vec<f64> = (0..256).map(|k| -1.0 + 2.0 * k as f64 / 255.0).collect();
+        let labels = grid
+            .iter()
+            .flat_map(|theta| grid.iter().map(move |omega| usize::from(theta + omega > 0.0)))
+            .collect();
+        let map = BasinMap {
+            thetas: grid.clone(),
+            omegas: grid.clone(),
+            labels,
+            attractors: vec![Vec::new(), Vec::new()],
+            representatives: vec![(0.0, 0.0), (0.0, 0.0)],
+        };
+        let boundary = map.boundary_points();
+        assert!(boundary.iter().all(|(t, o)| (t + o).abs() < 0.02));
+        let sizes: Vec<f64> = (0..5).map(|k| 0.5 / 2f64.powi(k)).collect();
+        let d = crate::analysis::box_counting_dimension(&boundary, &sizes).unwrap();
+        assert!((d.dimension - 1.0).abs() < 0.1, "{}", d.dimension);
+    }
+
+    #[test]
+    fn test_search_finds_coexisting_rotations_and_single_chaotic_attractor() {
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.q = 0.5;
+        params.f_d = 1.35;
+        params.omega_d = 2.0 / 3.0;
+        params.set_steps_per_period(100);
+        params.theta_start = -PI;
+        params.theta_end = PI;
+        params.omega_start = -3.0;
+        params.omega_end = 3.0;
+        let options = AttractorSearchOptions {
+            trials: 40,
+            transient_periods: 150,
+            sample_periods: 32,
+            ..AttractorSearchOptions::default()
+        };
+
+        // f_d = 1.35：顺、逆时针两个转动解，互为镜像，各占约一半
+        let found = search_attractors(&params, &mut crate::random::seeded_rng(1), &options);
+        assert_eq!(found.len(), 2, "{:?}", found);
+        assert!(found.iter().all(|a| a.class == AttractorClass::Rotating && a.hits > 5));
+        assert_eq!(found.iter().map(|a| a.hits).sum::<usize>(), 40);
+        assert!((found[0].fraction + found[1].fraction - 1.0).abs() < 1e-12 && found[0].fraction_error > 0.0);
+        let map = StroboscopicMap::new(&params);
+        let rotation = |a: &FoundAttractor| {
+            let start = State { theta: a.initial_theta, omega: a.initial_omega };
+            orbit_rotation_number(&map.orbit(start, 150, 32))
+        };
+        assert!(rotation(&found[0]) * rotation(&found[1]) < 0.0);
+        let again = search_attractors(&params, &mut crate::random::seeded_rng(1), &options);
+        assert_eq!(again.iter().map(|a| a.hits).collect::<Vec<_>>(), found.iter().map(|a| a.hits).collect::<Vec<_>>());
+
+        // f_d = 1.2：只有一个混沌吸引子，各段有限采样互相覆盖不全，但平均最近邻距离小
+        params.f_d = 1.2;
+        let options = AttractorSearchOptions {
+            trials: 20,
+            sample_periods: 200,
+            ..options
+        };
+        let found = search_attractors(&params, &mut crate::random::seeded_rng(2), &options);
+        assert_eq!(found.len(), 1, "{:?}", found.iter().map(|a| (a.class, a.hits)).collect::<Vec<_>>());
+        assert_eq!((found[0].class, found[0].hits, found[0].fraction_error), (AttractorClass::Chaotic, 20, 0.0));
+
+        let path = std::env::temp_dir().join("chaos_found_attractors.csv");
+        write_found_attractors_csv(path.to_str().unwrap(), &found).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert!(text.lines().nth(1).unwrap().starts_with("0,chaotic,0,"));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,1203 @@
+//bifurcation.rs
+use std::fs::File;
+use std::io::Write;
+use std::ops::ControlFlow;
+use crate::analysis::{post_transient_averages, TimeAverages};
+use crate::attractor::{detect_period, summarize};
+use crate::basin::section_distance;
+use crate::collector::{par_sweep_to_sink, RowSink};
+use crate::error::ChaosError;
+use crate::model::PendulumParams;
+use crate::progress::{with_progress, ProgressInfo};
+use crate::resonance::SweepDirection;
+use crate::resumable::{parse_row, ResumableCsv};
+use crate::spectrum::power_spectrum;
+use crate::stroboscopic::{orbit_rotation_number, section_points, StroboscopicMap};
+use crate::solve_equation::{solve_periods, steps_per_period, wrap_angle, State, TrajectoryIter, WarmStart};
+
+/// 扫描时改变的参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SweepParameter {
+    DriveAmplitude,
+    Damping,
+    DriveFrequency,
+}
+
+impl SweepParameter {
+    /// CSV 列名，与 PendulumParams 字段名一致
+    pub fn name(&self) -> &'static str {
+        match self {
+            SweepParameter::DriveAmplitude => "f_d",
+            SweepParameter::Damping => "q",
+            SweepParameter::DriveFrequency => "omega_d",
+        }
+    }
+
+    /// name() 的逆映射，用于从 CSV 表头恢复扫描参数
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "f_d" => Some(SweepParameter::DriveAmplitude),
+            "q" => Some(SweepParameter::Damping),
+            "omega_d" => Some(SweepParameter::DriveFrequency),
+            _ => None,
+        }
+    }
+
+    pub fn apply(&self, params: &mut PendulumParams, value: f64) {
+        match self {
+            SweepParameter::DriveAmplitude => params.f_d = value,
+            SweepParameter::Damping => params.q = value,
+            SweepParameter::DriveFrequency => params.omega_d = value,
+        }
+    }
+}
+
+/// 分岔图中的一个庞加莱点
+#[derive(Debug, Clone, Copy)]
+pub struct BifurcationPoint {
+    pub parameter: f64,
+    pub direction: SweepDirection,
+    pub theta: f64,
+    pub omega: f64,
+}
+
+// range 上 steps + 1 个等距参数值
+pub(crate) fn sweep_values(range: (f64, f64), steps: usize) -> Vec<f64> {
+    (0..=steps)
+        .map(|i| range.0 + (range.1 - range.0) * i as f64 / steps.max(1) as f64)
+        .collect()
+}
+
+/// 驱动幅度 f_d 的分岔图：返回 (f_d, θ) 点列
+///
+/// f_d 在 f_d_range 上取 steps + 1 个等距值，从 (θ, ω) = (0, 0) 出发，
+/// 每个值积分 transient_periods + sample_periods 个整周期，记录后 sample_periods 个庞加莱点的 θ；
+/// 下一个 f_d 从上一个的末状态出发，分支连续。需要双向扫描或其他参数时用 hysteresis_sweep。
+pub fn bifurcation_diagram(
+    params: &PendulumParams,
+    f_d_range: (f64, f64),
+    steps: usize,
+    transient_periods: usize,
+    sample_periods: usize,
+) -> Vec<(f64, f64)> {
+    bifurcation_diagram_with_progress(params, f_d_range, steps, transient_periods, sample_periods, |_| {
+        ControlFlow::Continue(())
+    })
+}
+
+/// 与 bifurcation_diagram 相同，每算完一个 f_d 调用一次 on_progress；回调返回 Break 时返回已经算完的点
+pub fn bifurcation_diagram_with_progress<F>(
+    params: &PendulumParams,
+    f_d_range: (f64, f64),
+    steps: usize,
+    transient_periods: usize,
+    sample_periods: usize,
+    mut on_progress: F,
+) -> Vec<(f64, f64)>
+where
+    F: FnMut(ProgressInfo) -> ControlFlow<()>,
+{
+    let spp = steps_per_period(params);
+    let mut p = params.clone();
+    let mut state = State { theta: 0.0, omega: 0.0 };
+    let mut out = Vec::with_capacity((steps + 1) * sample_periods);
+    for (i, f_d) in sweep_values(f_d_range, steps).into_iter().enumerate() {
+        p.f_d = f_d;
+        let thetas;
+        (thetas, state) = bifurcation_step(&p, state, spp, transient_periods, sample_periods);
+        out.extend(thetas.into_iter().map(|theta| (f_d, theta)));
+        if on_progress(ProgressInfo { done: i + 1, total: steps + 1 }).is_break() {
+            break;
+        }
+    }
+    out
+}
+
+// 分岔扫描的一个参数值：从 state 出发积分，返回采样的 θ 与折叠后的末状态（下一个参数值的起点）
+fn bifurcation_step(
+    p: &PendulumParams,
+    state: State,
+    spp: usize,
+    transient_periods: usize,
+    sample_periods: usize,
+) -> (Vec<f64>, State) {
+    let orbit = StroboscopicMap::with_steps(p, spp).orbit(state, transient_periods, sample_periods);
+    let thetas = orbit[1..].iter().map(|s| wrap_angle(s.theta)).collect();
+    let last = orbit[orbit.len() - 1];
+    (thetas, State { theta: wrap_angle(last.theta), omega: last.omega })
+}
+
+/// 与 bifurcation_diagram_with_progress 相同，但每算完一个 f_d 就追加写入 path（f_d,theta，格式见 resumable 模块）
+///
+/// path 是上次中断留下的部分结果时，跳过已完成的 f_d，从最后完成的 f_d 的末状态热启动接着算，
+/// 写出的文件与一次跑完相同。返回文件中的全部点（已有部分从文件读回，精度为写出的 12 位小数）。
+#[allow(clippy::too_many_arguments)]
+pub fn bifurcation_diagram_resumable<F>(
+    params: &PendulumParams,
+    f_d_range: (f64, f64),
+    steps: usize,
+    transient_periods: usize,
+    sample_periods: usize,
+    path: &str,
+    mut on_progress: F,
+) -> std::io::Result<Vec<(f64, f64)>>
+where
+    F: FnMut(ProgressInfo) -> ControlFlow<()>,
+{
+    let spp = steps_per_period(params);
+    let mut csv = ResumableCsv::open(path, "f_d,theta")?;
+    let mut out = Vec::with_capacity((steps + 1) * sample_periods);
+    let mut state = State { theta: 0.0, omega: 0.0 };
+    for unit in csv.completed() {
+        for row in &unit.rows {
+            match parse_row(row)?[..] {
+                [f_d, theta] => out.push((f_d, theta)),
+                _ => return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, format!("{}: bad row {:?}", path, row))),
+            }
+        }
+        if let [theta, omega] = unit.state[..] {
+            state = State { theta, omega };
+        }
+    }
+    let mut p = params.clone();
+    for (i, f_d) in sweep_values(f_d_range, steps).into_iter().enumerate() {
+        if csv.is_done(i) {
+            continue;
+        }
+        p.f_d = f_d;
+        let thetas;
+        (thetas, state) = bifurcation_step(&p, state, spp, transient_periods, sample_periods);
+        csv.write_unit(i, &[state.theta, state.omega], |w| {
+            thetas.iter().try_for_each(|theta| writeln!(w, "{:.12},{:.12}", f_d, theta))
+        })?;
+        out.extend(thetas.into_iter().map(|theta| (f_d, theta)));
+        if on_progress(ProgressInfo { done: i + 1, total: steps + 1 }).is_break() {
+            break;
+        }
+    }
+    Ok(out)
+}
+
+/// 冷启动的并行分岔图：每个 f_d 都从 initial 出发，各 f_d 在 rayon 线程池里并行计算，
+/// 庞加莱点经 OrderedCollector 按 f_d 从小到大写入 sink（列 f_d,theta），行序与线程调度无关。返回写出的行数
+///
+/// 没有逐点热启动，多稳态区间里画出的是 initial 所在吸引域的吸引子，与 bifurcation_diagram 的连续分支可能不同。
+pub fn bifurcation_diagram_parallel<S: RowSink + Send + 'static>(
+    params: &PendulumParams,
+    f_d_range: (f64, f64),
+    steps: usize,
+    transient_periods: usize,
+    sample_periods: usize,
+    initial: State,
+    sink: S,
+) -> std::io::Result<usize> {
+    let spp = steps_per_period(params);
+    par_sweep_to_sink(&sweep_values(f_d_range, steps), sink, |&f_d| {
+        let mut p = params.clone();
+        p.f_d = f_d;
+        let (thetas, _) = bifurcation_step(&p, initial, spp, transient_periods, sample_periods);
+        thetas.into_iter().map(|theta| vec![f_d, theta]).collect()
+    })
+}
+
+/// 把分岔图写成 CSV（两列：f_d,theta）
+pub fn write_bifurcation_csv(path: &str, points: &[(f64, f64)]) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(f, "f_d,theta")?;
+    for (f_d, theta) in points {
+        writeln!(f, "{:.12},{:.12}", f_d, theta)?;
+    }
+    Ok(())
+}
+
+/// 动态分岔图中的一个频闪点，带采样时刻的瞬时参数值
+#[derive(Debug, Clone, Copy)]
+pub struct RampPoint {
+    pub t: f64,
+    pub f_d: f64,
+    pub q: f64,
+    pub theta: f64,
+    pub omega: f64,
+}
+
+/// 参数随时间缓慢变化（f_d_schedule / q_schedule）时的动态分岔图
+///
+/// 从 (θ0, ω0) 出发按整周期步长积分到 params.t_end，在每个驱动周期末 t = nT（n ≥ 1）记录状态与该时刻的 f_d、q。
+/// 与 bifurcation_diagram 逐个参数值积分不同，参数在一次积分中连续变化，扫描速度足够慢时接近静态分岔图，
+/// 较快时可以看到分岔的延迟；θ 折叠到 [-π, π)。
+pub fn ramp_sweep(params: &PendulumParams, initial_theta: f64, initial_omega: f64) -> Vec<RampPoint> {
+    ramp_sweep_with_progress(params, initial_theta, initial_omega, usize::MAX, |_| ControlFlow::Continue(()))
+}
+
+/// 与 ramp_sweep 相同，每 every 个积分步调用一次 on_progress；回调返回 Break 时返回已经记录的点
+pub fn ramp_sweep_with_progress<F>(
+    params: &PendulumParams,
+    initial_theta: f64,
+    initial_omega: f64,
+    every: usize,
+    on_progress: F,
+) -> Vec<RampPoint>
+where
+    F: FnMut(ProgressInfo) -> ControlFlow<()>,
+{
+    let spp = steps_per_period(params);
+    let mut p = params.clone();
+    p.set_steps_per_period(spp);
+    with_progress(TrajectoryIter::new(&p, initial_theta, initial_omega), every, on_progress)
+        .step_by(spp)
+        .skip(1)
+        .map(|(t, s)| RampPoint {
+            t,
+            f_d: p.f_d_at(t),
+            q: p.q_at(t),
+            theta: wrap_angle(s.theta),
+            omega: s.omega,
+        })
+        .collect()
+}
+
+/// 把动态分岔图写成 CSV（t,f_d,q,theta,omega）
+pub fn write_ramp_csv(path: &str, points: &[RampPoint]) -> std::io::Result<()> {
+    let mut f = std::io::BufWriter::new(File::create(path)?);
+    writeln!(f, "t,f_d,q,theta,omega")?;
+    for p in points {
+        writeln!(f, "{:.12},{:.12},{:.12},{:.12},{:.12}", p.t, p.f_d, p.q, p.theta, p.omega)?;
+    }
+    Ok(())
+}
+
+/// 带滞回的分岔扫描：参数先升后降，末状态逐点向前传递
+///
+/// 每个参数值积分 transient_periods + sample_periods 个整周期，
+/// 丢弃过渡段后记录 sample_periods 个庞加莱点；下一参数值从上一参数值的末状态出发。
+/// 上扫与下扫两条分支都输出，共存吸引子与滞回环会表现为两分支在同一参数处不重合。
+#[allow(clippy::too_many_arguments)]
+pub fn hysteresis_sweep(
+    params: &PendulumParams,
+    parameter: SweepParameter,
+    range: (f64, f64),
+    steps: usize,
+    initial_theta: f64,
+    initial_omega: f64,
+    transient_periods: usize,
+    sample_periods: usize,
+) -> Vec<BifurcationPoint> {
+    let spp = steps_per_period(params);
+    let values = sweep_values(range, steps);
+
+    let mut p = params.clone();
+    let mut state = State {
+        theta: initial_theta,
+        omega: initial_omega,
+    };
+    let mut out = Vec::with_capacity(2 * values.len() * sample_periods);
+
+    let up = values.iter().map(|v| (*v, SweepDirection::Up));
+    let down = values.iter().rev().map(|v| (*v, SweepDirection::Down));
+    for (value, direction) in up.chain(down) {
+        parameter.apply(&mut p, value);
+        let orbit = StroboscopicMap::with_steps(&p, spp).orbit(state, transient_periods, sample_periods);
+        for (theta, omega) in section_points(&orbit) {
+            out.push(BifurcationPoint {
+                parameter: value,
+                direction,
+                theta,
+                omega,
+            });
+        }
+        let last = orbit[orbit.len() - 1];
+        state = State {
+            theta: wrap_angle(last.theta),
+            omega: last.omega,
+        };
+    }
+    out
+}
+
+/// 自适应扫描中的一个参数点
+#[derive(Debug, Clone)]
+pub struct ScanPoint {
+    pub parameter: f64,
+    /// 检测到的周期，None 表示非周期（混沌/准周期/周期过长）
+    pub period: Option<usize>,
+    pub samples: Vec<(f64, f64)>,
+}
+
+/// 通用的自适应参数细分
+///
+/// 先在 range 上取 coarse_steps + 1 个等距点并逐点 evaluate，
+/// 对相邻两点 same(a, b) 为 false 的区间反复二分，最多细分 max_depth 层。
+/// same 可以比较吸引子周期、Lyapunov 指数的符号等任意判据。
+/// 返回按参数从小到大排列的 (参数, evaluate 结果)。
+pub fn refine_scan<T, F, S>(
+    range: (f64, f64),
+    coarse_steps: usize,
+    max_depth: usize,
+    mut evaluate: F,
+    same: S,
+) -> Vec<(f64, T)>
+where
+    F: FnMut(f64) -> T,
+    S: Fn(&T, &T) -> bool,
+{
+    let mut points: Vec<(f64, T)> = sweep_values(range, coarse_steps)
+        .into_iter()
+        .map(|x| (x, evaluate(x)))
+        .collect();
+
+    let mut stack: Vec<(usize, usize, usize)> = (1..points.len()).map(|i| (i - 1, i, 0)).collect();
+    while let Some((a, b, depth)) = stack.pop() {
+        if depth >= max_depth || same(&points[a].1, &points[b].1) {
+            continue;
+        }
+        let mid = 0.5 * (points[a].0 + points[b].0);
+        points.push((mid, evaluate(mid)));
+        let m = points.len() - 1;
+        stack.push((a, m, depth + 1));
+        stack.push((m, b, depth + 1));
+    }
+
+    points.sort_by(|x, y| x.0.partial_cmp(&y.0).unwrap());
+    points
+}
+
+/// 在吸引子周期发生变化的参数区间内自动加密的分岔扫描
+///
+/// 每个参数值都从同一初值出发积分，检测庞加莱点的周期（不超过 max_period，容差 tolerance），
+/// 相邻参数点周期不同时二分该区间，直到 max_depth 层。
+#[allow(clippy::too_many_arguments)]
+pub fn adaptive_bifurcation_scan(
+    params: &PendulumParams,
+    parameter: SweepParameter,
+    range: (f64, f64),
+    coarse_steps: usize,
+    max_depth: usize,
+    initial_theta: f64,
+    initial_omega: f64,
+    transient_periods: usize,
+    sample_periods: usize,
+    max_period: usize,
+    tolerance: f64,
+) -> Vec<ScanPoint> {
+    let spp = steps_per_period(params);
+    let run = |value: f64| {
+        let mut p = params.clone();
+        parameter.apply(&mut p, value);
+        let start = State {
+            theta: initial_theta,
+            omega: initial_omega,
+        };
+        StroboscopicMap::with_steps(&p, spp).section(start, transient_periods, sample_periods)
+    };
+
+    refine_scan(
+        range,
+        coarse_steps,
+        max_depth,
+        |value| {
+            let samples = run(value);
+            ScanPoint {
+                parameter: value,
+                period: detect_period(&samples, max_period, tolerance),
+                samples,
+            }
+        },
+        |a, b| a.period == b.period,
+    )
+    .into_iter()
+    .map(|(_, point)| point)
+    .collect()
+}
+
+/// 周期窗口：参数区间内吸引子保持同一周期
+#[derive(Debug, Clone, Copy)]
+pub struct PeriodicWindow {
+    pub period: usize,
+    /// 窗口内第一个/最后一个扫描点的参数值
+    pub start: f64,
+    pub end: f64,
+    /// 窗口外侧相邻扫描点的参数值（真实边界位于 (lower_bracket, start] 与 [end, upper_bracket) 内）
+    pub lower_bracket: Option<f64>,
+    pub upper_bracket: Option<f64>,
+    pub points: usize,
+}
+
+/// 从（按参数排序的）扫描结果中整理出周期窗口目录
+///
+/// 相邻且周期相同的扫描点合并为一个窗口；非周期点把窗口隔开。
+/// 配合 adaptive_bifurcation_scan 使用时，窗口边界附近的点会被自动加密。
+pub fn periodic_windows(points: &[ScanPoint]) -> Vec<PeriodicWindow> {
+    let mut windows: Vec<PeriodicWindow> = Vec::new();
+    for (i, point) in points.iter().enumerate() {
+        let Some(period) = point.period else {
+            continue;
+        };
+        let continues = i > 0 && points[i - 1].period == Some(period);
+        if continues && let Some(w) = windows.last_mut() {
+            w.end = point.parameter;
+            w.points += 1;
+            w.upper_bracket = points.get(i + 1).map(|p| p.parameter);
+        } else {
+            windows.push(PeriodicWindow {
+                period,
+                start: point.parameter,
+                end: point.parameter,
+                lower_bracket: i.checked_sub(1).map(|j| points[j].parameter),
+                upper_bracket: points.get(i + 1).map(|p| p.parameter),
+                points: 1,
+            });
+        }
+    }
+    windows
+}
+
+/// 把周期窗口目录写成 CSV（列：period,start,end,lower_bracket,upper_bracket,points），缺失的外侧点留空
+pub fn write_periodic_windows_csv(path: &str, windows: &[PeriodicWindow]) -> std::io::Result<()> {
+    let fmt = |x: Option<f64>| x.map(|v| format!("{:.12}", v)).unwrap_or_default();
+    let mut f = File::create(path)?;
+    writeln!(f, "period,start,end,lower_bracket,upper_bracket,points")?;
+    for w in windows {
+        writeln!(
+            f,
+            "{},{:.12},{:.12},{},{},{}",
+            w.period,
+            w.start,
+            w.end,
+            fmt(w.lower_bracket),
+            fmt(w.upper_bracket),
+            w.points
+        )?;
+    }
+    Ok(())
+}
+
+/// 混沌带：参数区间内连续的非周期扫描点
+#[derive(Debug, Clone, Copy)]
+pub struct ChaoticBand {
+    pub start: f64,
+    pub end: f64,
+    /// 带外侧相邻扫描点的参数值，含义同 PeriodicWindow
+    pub lower_bracket: Option<f64>,
+    pub upper_bracket: Option<f64>,
+    pub points: usize,
+}
+
+/// 从（按参数排序的）扫描结果中整理出混沌带（也包括准周期或周期大于 max_period 的区间）
+pub fn chaotic_bands(points: &[ScanPoint]) -> Vec<ChaoticBand> {
+    let mut bands: Vec<ChaoticBand> = Vec::new();
+    for (i, point) in points.iter().enumerate() {
+        if point.period.is_some() {
+            continue;
+        }
+        let continues = i > 0 && points[i - 1].period.is_none();
+        if continues && let Some(b) = bands.last_mut() {
+            b.end = point.parameter;
+            b.points += 1;
+            b.upper_bracket = points.get(i + 1).map(|p| p.parameter);
+        } else {
+            bands.push(ChaoticBand {
+                start: point.parameter,
+                end: point.parameter,
+                lower_bracket: i.checked_sub(1).map(|j| points[j].parameter),
+                upper_bracket: points.get(i + 1).map(|p| p.parameter),
+                points: 1,
+            });
+        }
+    }
+    bands
+}
+
+/// 把混沌带写成 CSV（列：start,end,lower_bracket,upper_bracket,points），缺失的外侧点留空
+pub fn write_chaotic_bands_csv(path: &str, bands: &[ChaoticBand]) -> std::io::Result<()> {
+    let fmt = |x: Option<f64>| x.map(|v| format!("{:.12}", v)).unwrap_or_default();
+    let mut f = File::create(path)?;
+    writeln!(f, "start,end,lower_bracket,upper_bracket,points")?;
+    for b in bands {
+        writeln!(
+            f,
+            "{:.12},{:.12},{},{},{}",
+            b.start,
+            b.end,
+            fmt(b.lower_bracket),
+            fmt(b.upper_bracket),
+            b.points
+        )?;
+    }
+    Ok(())
+}
+
+/// 危机类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrisisKind {
+    /// 吸引子尺寸突然增大（内部危机、混沌带合并）
+    Expansion,
+    /// 吸引子尺寸突然减小
+    Contraction,
+    /// 吸引子突然消失、轨道跳到相空间另一处（边界危机）
+    Relocation,
+}
+
+/// 在相邻两个扫描点之间检测到的危机
+#[derive(Debug, Clone, Copy)]
+pub struct CrisisEvent {
+    pub kind: CrisisKind,
+    /// 危机发生在 (lower, upper) 区间内
+    pub lower: f64,
+    pub upper: f64,
+    /// 两侧吸引子的 RMS 尺度
+    pub size_before: f64,
+    pub size_after: f64,
+}
+
+/// 危机检测的判据
+#[derive(Debug, Clone, Copy)]
+pub struct CrisisOptions {
+    /// 相邻两点吸引子 RMS 尺度之比的阈值；质心移动也以两侧尺度之和的这么多倍为界
+    pub jump_ratio: f64,
+    /// 质心移动距离的绝对下限（截面坐标）：两侧都是周期轨道时尺度为 0，
+    /// 没有这个下限时不动点随参数的平滑漂移也会被记为迁移（f_d 步长 0.02 时周期 1 不动点一步可移动约 0.3）
+    pub min_shift: f64,
+}
+
+impl Default for CrisisOptions {
+    fn default() -> Self {
+        CrisisOptions {
+            jump_ratio: 2.0,
+            min_shift: 0.5,
+        }
+    }
+}
+
+// 判断相邻两个扫描点之间是否发生危机
+fn crisis_between(a: &ScanPoint, b: &ScanPoint, options: &CrisisOptions) -> Option<CrisisEvent> {
+    let jump_ratio = options.jump_ratio;
+    let sa = summarize(&a.samples)?;
+    let sb = summarize(&b.samples)?;
+    let event = |kind| CrisisEvent {
+        kind,
+        lower: a.parameter,
+        upper: b.parameter,
+        size_before: sa.rms_extent,
+        size_after: sb.rms_extent,
+    };
+
+    let shift = section_distance(
+        (sa.theta_centroid, sa.omega_centroid),
+        (sb.theta_centroid, sb.omega_centroid),
+    );
+    if shift > (jump_ratio * (sa.rms_extent + sb.rms_extent)).max(options.min_shift) {
+        return Some(event(CrisisKind::Relocation));
+    }
+    // 尺寸接近 0（周期轨道）时用一个很小的下限，避免 0/0
+    let floor = 1e-6;
+    let ratio = sb.rms_extent.max(floor) / sa.rms_extent.max(floor);
+    if ratio >= jump_ratio {
+        Some(event(CrisisKind::Expansion))
+    } else if ratio <= 1.0 / jump_ratio {
+        Some(event(CrisisKind::Contraction))
+    } else {
+        None
+    }
+}
+
+/// 在（按参数排序的）扫描结果中检测危机
+///
+/// 对每对相邻扫描点：吸引子质心（θ 按 2π 取最短差）移动的距离超过 max(jump_ratio × 两侧 RMS 尺度之和, min_shift)
+/// 记为吸引子迁移；否则两侧 RMS 尺度之比（尺度低于 1e-6 时按 1e-6 计）不小于 jump_ratio 记为突然扩张，
+/// 不大于 1/jump_ratio 记为突然收缩。
+pub fn detect_crises(points: &[ScanPoint], options: &CrisisOptions) -> Vec<CrisisEvent> {
+    points
+        .windows(2)
+        .filter_map(|w| crisis_between(&w[0], &w[1], options))
+        .collect()
+}
+
+/// 把危机列表写成 CSV（列：kind,lower,upper,size_before,size_after）
+pub fn write_crises_csv(path: &str, events: &[CrisisEvent]) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(f, "kind,lower,upper,size_before,size_after")?;
+    for e in events {
+        let kind = match e.kind {
+            CrisisKind::Expansion => "expansion",
+            CrisisKind::Contraction => "contraction",
+            CrisisKind::Relocation => "relocation",
+        };
+        writeln!(
+            f,
+            "{},{:.12},{:.12},{:.12},{:.12}",
+            kind, e.lower, e.upper, e.size_before, e.size_after
+        )?;
+    }
+    Ok(())
+}
+
+/// 把自适应扫描写成 CSV（列：<参数名>,period,theta,omega），非周期时 period 留空
+pub fn write_scan_csv(path: &str, parameter: SweepParameter, points: &[ScanPoint]) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(f, "{},period,theta,omega", parameter.name())?;
+    for p in points {
+        let period = p.period.map(|n| n.to_string()).unwrap_or_default();
+        for (theta, omega) in &p.samples {
+            writeln!(f, "{:.12},{},{:.12},{:.12}", p.parameter, period, theta, omega)?;
+        }
+    }
+    Ok(())
+}
+
+/// 读取 write_scan_csv 写出的分岔数据，返回（参数列名, 扫描点）
+///
+/// 参数值相同的相邻行合并为一个扫描点；period 列为空表示未检测到周期。
+/// 格式不符时返回 InvalidData 错误。
+pub fn read_scan_csv(path: &str) -> std::io::Result<(String, Vec<ScanPoint>)> {
+    let invalid = |line: usize, msg: &str| {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, format!("{}:{}: {}", path, line + 1, msg))
+    };
+    let text = std::fs::read_to_string(path)?;
+    // 跳过空行与 # 开头的元数据注释行（见 metadata 模块），行号仍按文件中的实际位置报告
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty() && !l.starts_with('#'));
+    let (_, header) = lines.next().ok_or_else(|| invalid(0, "empty file"))?;
+    let name = header.split(',').next().unwrap_or_default().to_string();
+
+    let mut points: Vec<ScanPoint> = Vec::new();
+    for (i, line) in lines {
+        let fields: Vec<&str> = line.split(',').collect();
+        if fields.len() != 4 {
+            return Err(invalid(i, "expected 4 columns"));
+        }
+        let number = |s: &str| s.trim().parse::<f64>().map_err(|_| invalid(i, "bad number"));
+        let parameter = number(fields[0])?;
+        let period = match fields[1].trim() {
+            "" => None,
+            p => Some(p.parse::<usize>().map_err(|_| invalid(i, "bad period"))?),
+        };
+        let sample = (number(fields[2])?, number(fields[3])?);
+        match points.last_mut() {
+            Some(last) if last.parameter == parameter => last.samples.push(sample),
+            _ => points.push(ScanPoint {
+                parameter,
+                period,
+                samples: vec![sample],
+            }),
+        }
+    }
+    Ok((name, points))
+}
+
+/// 从已有的分岔数据文件导出周期窗口与混沌带，无需重新积分
+///
+/// redetect 为 Some((max_period, tolerance)) 时用 detect_period 重新判定每个点的周期，
+/// 适用于原文件没有 period 列信息或想换一个容差的情况。
+pub fn export_windows_from_scan_csv(
+    input: &str,
+    windows_path: &str,
+    bands_path: &str,
+    redetect: Option<(usize, f64)>,
+) -> std::io::Result<(Vec<PeriodicWindow>, Vec<ChaoticBand>)> {
+    let (_, mut points) = read_scan_csv(input)?;
+    points.sort_by(|a, b| a.parameter.total_cmp(&b.parameter));
+    if let Some((max_period, tolerance)) = redetect {
+        for p in points.iter_mut() {
+            p.period = detect_period(&p.samples, max_period, tolerance);
+        }
+    }
+    let windows = periodic_windows(&points);
+    let bands = chaotic_bands(&points);
+    write_periodic_windows_csv(windows_path, &windows)?;
+    write_chaotic_bands_csv(bands_path, &bands)?;
+    Ok((windows, bands))
+}
+
+/// 谱分岔图：行对应参数值，列对应频率
+#[derive(Debug, Clone)]
+pub struct SpectralBifurcation {
+    pub parameters: Vec<f64>,
+    /// 以驱动频率为单位的频率（1 为基频，0.5 为二分频次谐波）
+    pub frequencies: Vec<f64>,
+    /// power[i][j]：第 i 个参数值在 frequencies[j] 处的功率
+    pub power: Vec<Vec<f64>>,
+}
+
+/// 谱分岔图：对每个参数值计算稳态 ω(t) 的功率谱
+///
+/// 用 ω(t) 而不是 θ(t)，因为转动解的 θ 无界，折叠后又会引入跳变。
+/// 每个驱动周期步数固定，因此以驱动频率为单位时各参数值的频率网格完全相同；
+/// 只保留不超过 max_harmonic 倍驱动频率的部分。
+#[allow(clippy::too_many_arguments)]
+pub fn spectral_bifurcation(
+    params: &PendulumParams,
+    parameter: SweepParameter,
+    range: (f64, f64),
+    steps: usize,
+    initial_theta: f64,
+    initial_omega: f64,
+    transient_periods: usize,
+    sample_periods: usize,
+    max_harmonic: f64,
+) -> Result<SpectralBifurcation, ChaosError> {
+    let spp = steps_per_period(params);
+    let parameters = sweep_values(range, steps);
+    let mut frequencies = Vec::new();
+    let mut power = Vec::with_capacity(parameters.len());
+
+    for &value in &parameters {
+        let mut p = params.clone();
+        parameter.apply(&mut p, value);
+        let traj = solve_periods(&p, initial_theta, initial_omega, transient_periods + sample_periods, spp)?;
+        let signal: Vec<f64> = traj[transient_periods * spp..].iter().map(|(_, s)| s.omega).collect();
+
+        // 以驱动频率为单位：dt = T / spp，对应 dt = 1 / spp
+        let spec: Vec<(f64, f64)> = power_spectrum(&signal, 1.0 / spp as f64)
+            .into_iter()
+            .filter(|(f, _)| *f <= max_harmonic)
+            .collect();
+        if frequencies.is_empty() {
+            frequencies = spec.iter().map(|(f, _)| *f).collect();
+        }
+        power.push(spec.into_iter().map(|(_, pw)| pw).collect());
+    }
+
+    Ok(SpectralBifurcation {
+        parameters,
+        frequencies,
+        power,
+    })
+}
+
+/// 把谱分岔图写成矩阵 CSV：表头为 <参数名> 与各频率，每行一个参数值
+pub fn write_spectral_bifurcation_csv(
+    path: &str,
+    parameter: SweepParameter,
+    data: &SpectralBifurcation,
+) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    write!(f, "{}", parameter.name())?;
+    for freq in &data.frequencies {
+        write!(f, ",{:.6}", freq)?;
+    }
+    writeln!(f)?;
+    for (value, row) in data.parameters.iter().zip(&data.power) {
+        write!(f, "{:.12}", value)?;
+        for pw in row {
+            write!(f, ",{:.6e}", pw)?;
+        }
+        writeln!(f)?;
+    }
+    Ok(())
+}
+
+/// 旋转数随参数的变化（例如随 f_d 的台阶曲线）
+///
+/// 每个参数值从同一初值出发，丢弃 transient_periods 个周期后，
+/// 在 sample_periods 个周期的窗口内计算平均每周期转过的圈数。
+/// 返回 Vec<(参数值, 旋转数)>，摆动区为 0，锁频转动区呈现平台。
+#[allow(clippy::too_many_arguments)]
+pub fn winding_number_curve(
+    params: &PendulumParams,
+    parameter: SweepParameter,
+    range: (f64, f64),
+    steps: usize,
+    initial_theta: f64,
+    initial_omega: f64,
+    transient_periods: usize,
+    sample_periods: usize,
+) -> Vec<(f64, f64)> {
+    let spp = steps_per_period(params);
+    sweep_values(range, steps)
+        .into_iter()
+        .map(|value| {
+            let mut p = params.clone();
+            parameter.apply(&mut p, value);
+            let start = State {
+                theta: initial_theta,
+                omega: initial_omega,
+            };
+            let orbit = StroboscopicMap::with_steps(&p, spp).orbit(start, transient_periods, sample_periods);
+            (value, orbit_rotation_number(&orbit))
+        })
+        .collect()
+}
+
+/// 把旋转数曲线写成 CSV（两列：<参数名>,winding_number）
+pub fn write_winding_curve_csv(path: &str, parameter: SweepParameter, curve: &[(f64, f64)]) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(f, "{},winding_number", parameter.name())?;
+    for (value, w) in curve {
+        writeln!(f, "{:.12},{:.12}", value, w)?;
+    }
+    Ok(())
+}
+
+/// 扫描参数，对每个参数值记录过渡期之后的时间平均量；积分发散等无法求平均时跳过该点，参数无效时返回错误
+#[allow(clippy::too_many_arguments)]
+pub fn time_average_sweep(
+    params: &PendulumParams,
+    parameter: SweepParameter,
+    range: (f64, f64),
+    steps: usize,
+    initial_theta: f64,
+    initial_omega: f64,
+    transient_periods: usize,
+    sample_periods: usize,
+) -> Result<Vec<(f64, TimeAverages)>, ChaosError> {
+    sweep_values(range, steps)
+        .into_iter()
+        .filter_map(|value| {
+            let mut p = params.clone();
+            parameter.apply(&mut p, value);
+            post_transient_averages(&p, initial_theta, initial_omega, transient_periods, sample_periods)
+                .map(|avg| avg.map(|avg| (value, avg)))
+                .transpose()
+        })
+        .collect()
+}
+
+/// 把时间平均扫描写成 CSV（列：<参数名>,energy,abs_omega,drive_power,dissipated_power）
+pub fn write_time_average_sweep_csv(
+    path: &str,
+    parameter: SweepParameter,
+    rows: &[(f64, TimeAverages)],
+) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(f, "{},energy,abs_omega,drive_power,dissipated_power", parameter.name())?;
+    for (value, a) in rows {
+        writeln!(
+            f,
+            "{:.12},{:.12},{:.12},{:.12},{:.12}",
+            value, a.energy, a.abs_omega, a.drive_power, a.dissipated_power
+        )?;
+    }
+    Ok(())
+}
+
+/// 从热启动快照出发的单向参数扫描
+///
+/// 第一个参数值直接从 warm.state 出发；之后每个参数值都从上一个参数值的末状态出发。
+/// 相邻参数值的吸引子很接近，因此只需 warm_transient_periods 个周期重新收敛，
+/// 不必每次都付出完整的过渡期代价。输出的 direction 均为 Up。
+/// 积分设置（dt、驱动频率等）取自 params，warm.params 仅作记录。
+pub fn warm_start_sweep(
+    params: &PendulumParams,
+    parameter: SweepParameter,
+    range: (f64, f64),
+    steps: usize,
+    warm: &WarmStart,
+    warm_transient_periods: usize,
+    sample_periods: usize,
+) -> Vec<BifurcationPoint> {
+    let spp = steps_per_period(params);
+    let mut p = params.clone();
+    let mut state = warm.state;
+    let mut out = Vec::new();
+
+    for value in sweep_values(range, steps) {
+        parameter.apply(&mut p, value);
+        let orbit = StroboscopicMap::with_steps(&p, spp).orbit(state, warm_transient_periods, sample_periods);
+        for (theta, omega) in section_points(&orbit) {
+            out.push(BifurcationPoint {
+                parameter: value,
+                direction: SweepDirection::Up,
+                theta,
+                omega,
+            });
+        }
+        let last = orbit[orbit.len() - 1];
+        state = State {
+            theta: wrap_angle(last.theta),
+            omega: last.omega,
+        };
+    }
+    out
+}
+
+/// 把滞回扫描写成 CSV（列：<参数名>,direction,theta,omega）
+pub fn write_hysteresis_csv(
+    path: &str,
+    parameter: SweepParameter,
+    points: &[BifurcationPoint],
+) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(f, "{},direction,theta,omega", parameter.name())?;
+    for p in points {
+        let dir = match p.direction {
+            SweepDirection::Up => "up",
+            SweepDirection::Down => "down",
+        };
+        writeln!(f, "{:.12},{},{:.12},{:.12}", p.parameter, dir, p.theta, p.omega)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_hysteresis_sweep_single_branch_when_weakly_driven() {
+        // 强阻尼、弱驱动：只有一个周期 1 吸引子，上下扫描应重合
+        let mut params = PendulumParams::new();
+        params.q = 1.0;
+        params.omega_d = 2.0 / 3.0;
+        params.dt = 2.0 * std::f64::consts::PI / params.omega_d / 100.0;
+
+        let points = hysteresis_sweep(
+            &params,
+            SweepParameter::DriveAmplitude,
+            (0.1, 0.3),
+            2,
+            0.0,
+            0.0,
+            40,
+            3,
+        );
+        assert_eq!(points.len(), 2 * 3 * 3);
+
+        for up in points.iter().filter(|p| p.direction == SweepDirection::Up) {
+            let down = points
+                .iter()
+                .find(|p| p.direction == SweepDirection::Down && p.parameter == up.parameter)
+                .unwrap();
+            assert_relative_eq!(up.theta, down.theta, epsilon = 1e-6);
+            assert_relative_eq!(up.omega, down.omega, epsilon = 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_refine_scan_brackets_label_change() {
+        let points = refine_scan((0.0, 1.0), 4, 10, |x| x > 0.3, |a, b| a == b);
+        let i = points.iter().position(|(_, l)| *l).unwrap();
+        let (below, above) = (points[i - 1].0, points[i].0);
+        assert!(below <= 0.3 && above > 0.3);
+        assert!(above - below <= 0.25 / 1024.0 + 1e-12);
+    }
+
+    #[test]
+    fn test_periodic_windows_grouping() {
+        let periods = [Some(1), Some(1), Some(2), None, None, Some(3), Some(3), Some(3), None];
+        let points: Vec<ScanPoint> = periods
+            .iter()
+            .enumerate()
+            .map(|(i, p)| ScanPoint {
+                parameter: i as f64 * 0.1,
+                period: *p,
+                samples: Vec::new(),
+            })
+            .collect();
+
+        let windows = periodic_windows(&points);
+        assert_eq!(windows.len(), 3);
+        assert_eq!((windows[0].period, windows[0].points), (1, 2));
+        assert_eq!(windows[0].lower_bracket, None);
+        assert_eq!(windows[1].period, 2);
+        assert_relative_eq!(windows[2].start, 0.5, epsilon = 1e-12);
+        assert_relative_eq!(windows[2].end, 0.7, epsilon = 1e-12);
+        assert_relative_eq!(windows[2].lower_bracket.unwrap(), 0.4, epsilon = 1e-12);
+        assert_relative_eq!(windows[2].upper_bracket.unwrap(), 0.8, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_detect_crises() {
+        let ring = |r: f64, center: f64| -> Vec<(f64, f64)> {
+            (0..16)
+                .map(|k| {
+                    let a = k as f64 * std::f64::consts::PI / 8.0;
+                    (center + r * a.cos(), r * a.sin())
+                })
+                .collect()
+        };
+        let make = |parameter: f64, samples| ScanPoint {
+            parameter,
+            period: None,
+            samples,
+        };
+        let points = vec![
+            make(1.0, ring(0.1, 0.0)),
+            make(1.1, ring(0.11, 0.0)),
+            make(1.2, ring(0.5, 0.0)),
+            make(1.3, ring(0.5, 2.5)),
+        ];
+
+        let crises = detect_crises(&points, &CrisisOptions::default());
+        assert_eq!(crises.len(), 2);
+        assert_eq!(crises[0].kind, CrisisKind::Expansion);
+        assert_relative_eq!(crises[0].lower, 1.1, epsilon = 1e-12);
+        assert_eq!(crises[1].kind, CrisisKind::Relocation);
+
+        // 周期 1 分支（f_d = 0.5..0.9）：不动点随 f_d 平滑漂移，尺度为 0，不是危机
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.q = 0.5;
+        params.omega_d = 2.0 / 3.0;
+        params.set_steps_per_period(100);
+        let branch: Vec<ScanPoint> = (0..=20)
+            .map(|k| {
+                params.f_d = 0.5 + 0.02 * k as f64;
+                let samples = StroboscopicMap::new(&params).section(State { theta: 0.2, omega: 0.0 }, 150, 16);
+                ScanPoint {
+                    parameter: params.f_d,
+                    period: detect_period(&samples, 8, 1e-6),
+                    samples,
+                }
+            })
+            .collect();
+        assert!(branch.iter().all(|p| p.period == Some(1)));
+        assert!(section_distance(branch[0].samples[0], branch[20].samples[0]) > 1.0);
+        assert!(detect_crises(&branch, &CrisisOptions::default()).is_empty());
+    }
+
+    #[test]
+    fn test_windows_and_bands_from_scan_csv() {
+        let make = |parameter: f64, period: Option<usize>| ScanPoint {
+            parameter,
+            period,
+            samples: match period {
+                Some(n) => (0..2 * n).map(|i| ((i % n) as f64 * 0.5, 0.0)).collect(),
+                None => (0..8).map(|i| (i as f64 * 0.3, i as f64 * 0.1)).collect(),
+            },
+        };
+        let points = vec![
+            make(1.0, Some(1)),
+            make(1.1, Some(1)),
+            make(1.2, None),
+            make(1.3, None),
+            make(1.4, Some(3)),
+            make(1.5, None),
+        ];
+        let dir = std::env::temp_dir();
+        let input = dir.join("chaos_scan_read_test.csv");
+        let input = input.to_str().unwrap();
+        write_scan_csv(input, SweepParameter::DriveAmplitude, &points).unwrap();
+
+        let (name, loaded) = read_scan_csv(input).unwrap();
+        assert_eq!(name, "f_d");
+        assert_eq!(loaded.len(), 6);
+        assert_eq!(loaded[4].samples.len(), 6);
+
+        let windows_path = dir.join("chaos_scan_windows_test.csv");
+        let bands_path = dir.join("chaos_scan_bands_test.csv");
+        let (windows, bands) = export_windows_from_scan_csv(
+            input,
+            windows_path.to_str().unwrap(),
+            bands_path.to_str().unwrap(),
+            Some((4, 1e-9)),
+        )
+        .unwrap();
+        assert_eq!(windows.len(), 2);
+        assert_eq!(windows[1].period, 3);
+        assert_eq!(bands.len(), 2);
+        assert_relative_eq!(bands[0].start, 1.2, epsilon = 1e-12);
+        assert_relative_eq!(bands[0].end, 1.3, epsilon = 1e-12);
+        assert_eq!(bands[1].upper_bracket, None);
+    }
+
+    #[test]
+    fn test_bifurcation_diagram_period_doubling() {
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.q = 0.5;
+        params.omega_d = 2.0 / 3.0;
+        params.dt = 2.0 * std::f64::consts::PI / params.omega_d / 100.0;
+
+        // f_d = 1.07 已越过第一次倍周期分岔（约 1.066），θ 在两个值之间交替
+        let points = bifurcation_diagram(&params, (0.9, 1.07), 1, 300, 8);
+        assert_eq!(points.len(), 16);
+        let distinct = |f_d: f64| {
+            let mut thetas: Vec<f64> = points.iter().filter(|(f, _)| *f == f_d).map(|(_, t)| *t).collect();
+            thetas.sort_by(|a, b| a.total_cmp(b));
+            thetas.dedup_by(|a, b| (*a - *b).abs() < 1e-4);
+            thetas.len()
+        };
+        assert_eq!(distinct(0.9), 1);
+        assert_eq!(distinct(1.07), 2);
+    }
+
+    #[test]
+    fn test_resumed_bifurcation_matches_single_run() {
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.q = 0.5;
+        params.omega_d = 2.0 / 3.0;
+        params.dt = 2.0 * std::f64::consts::PI / params.omega_d / 100.0;
+        let dir = std::env::temp_dir();
+        let once = dir.join("chaos_bifurcation_once.csv");
+        let split = dir.join("chaos_bifurcation_split.csv");
+        let (once, split) = (once.to_str().unwrap(), split.to_str().unwrap());
+        let _ = std::fs::remove_file(once);
+        let _ = std::fs::remove_file(split);
+        let run = |path: &str, stop_after: usize| {
+            bifurcation_diagram_resumable(&params, (1.0, 1.1), 5, 20, 4, path, |p| {
+                if p.done == stop_after { ControlFlow::Break(()) } else { ControlFlow::Continue(()) }
+            })
+            .unwrap()
+        };
+        let all = run(once, usize::MAX);
+        assert_eq!(run(split, 2).len(), 8);
+        // 续算时从文件读回前两个 f_d 的点与热启动状态
+        let resumed = run(split, usize::MAX);
+        assert_eq!(resumed.len(), all.len());
+        assert_eq!(std::fs::read_to_string(once).unwrap(), std::fs::read_to_string(split).unwrap());
+        // 与一次性计算的点相同（已有部分为 12 位小数）
+        let direct = bifurcation_diagram(&params, (1.0, 1.1), 5, 20, 4);
+        assert!(resumed.iter().zip(&direct).all(|(a, b)| (a.0 - b.0).abs() < 1e-12 && (a.1 - b.1).abs() < 1e-11));
+    }
+
+    #[test]
+    fn test_parallel_bifurcation_is_sorted_and_matches_serial() {
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.q = 0.5;
+        params.omega_d = 2.0 / 3.0;
+        params.set_steps_per_period(100);
+        let path = std::env::temp_dir().join("chaos_bifurcation_parallel.csv");
+        let path = path.to_str().unwrap();
+        let initial = State { theta: 0.0, omega: 0.0 };
+        let sink = crate::collector::CsvRowSink::create(path, &["f_d", "theta"], false).unwrap();
+        assert_eq!(bifurcation_diagram_parallel(&params, (1.0, 1.1), 7, 20, 3, initial, sink).unwrap(), 24);
+        // 串行冷启动逐个 f_d 写出的结果
+        let mut expected = vec!["f_d,theta".to_string()];
+        for f_d in sweep_values((1.0, 1.1), 7) {
+            params.f_d = f_d;
+            let (thetas, _) = bifurcation_step(&params, initial, 100, 20, 3);
+            expected.extend(thetas.iter().map(|theta| format!("{:.12},{:.12}", f_d, theta)));
+        }
+        assert_eq!(std::fs::read_to_string(path).unwrap(), expected.join("\n") + "\n");
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_ramp_sweep_records_instantaneous_parameters() {
+        use crate::model::Schedule;
+        let mut params = PendulumParams::new();
+        params.q = 0.5;
+        params.omega_d = 2.0 / 3.0;
+        params.set_steps_per_period(100);
+        let period = 2.0 * std::f64::consts::PI / params.omega_d;
+        params.t_end = 20.0 * period;
+        params.f_d_schedule = Some(Schedule::Linear { from: 0.0, to: 1.0, t_start: 0.0, t_end: params.t_end });
+
+        let points = ramp_sweep(&params, 0.2, 0.0);
+        assert_eq!(points.len(), 20);
+        for (n, p) in points.iter().enumerate() {
+            assert_relative_eq!(p.t, (n + 1) as f64 * period, max_relative = 1e-12);
+            assert_relative_eq!(p.f_d, (n + 1) as f64 / 20.0, max_relative = 1e-9);
+            assert_eq!(p.q, 0.5);
+        }
+
+        // 常值日程与固定参数的积分完全一致
+        let piecewise = Schedule::Piecewise { points: vec![(0.0, 1.2), (1.0, 1.2)] };
+        assert_eq!(piecewise.value(-1.0), 1.2);
+        assert_eq!(piecewise.value(5.0), 1.2);
+        params.f_d = 1.2;
+        params.f_d_schedule = Some(piecewise);
+        let ramped = ramp_sweep(&params, 0.2, 0.0);
+        params.f_d_schedule = None;
+        let fixed = ramp_sweep(&params, 0.2, 0.0);
+        assert_eq!(ramped.last().unwrap().theta, fixed.last().unwrap().theta);
+
+        params.q_schedule = Some(Schedule::Piecewise { points: vec![(1.0, 0.5), (0.0, 0.1)] });
+        assert!(params.validate().is_err());
+    }
+}
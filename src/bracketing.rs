@@ -0,0 +1,289 @@
+//bracketing.rs
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use rayon::prelude::*;
+use crate::attractor::detect_period;
+use crate::bifurcation::{sweep_values, SweepParameter};
+use crate::error::ChaosError;
+use crate::model::PendulumParams;
+use crate::periodic_orbits::{check_orbit_params, find_periodic_orbit, NewtonOptions};
+use crate::roots::{bisect_predicate, illinois};
+use crate::solve_equation::State;
+use crate::stroboscopic::StroboscopicMap;
+
+// 分岔点的括号定位与加密：均匀网格只能把阈值定位到网格间距，加密网格又浪费在远离阈值的地方。
+// 这里先在粗网格上对每个参数值判定吸引子周期，相邻两点周期不同即得到一个括号区间，再只在括号内加密：
+//   二分：对 “周期仍与左端相同” 二分。每次判定都要重新积分过渡期，阈值附近收敛很慢（临界慢化），
+//         过渡期内没衰减完的振荡会被判成别的周期，阈值的偏差取决于过渡期长度与周期容差，一般远大于二分的区间宽度；
+//   割线：对周期一侧的周期 p 轨道做 Newton 延拓，对 “最大 Floquet 乘子的模 - 1” 用 Illinois 试位法求根。
+//         乘子是参数的光滑函数，不受临界慢化影响，收敛快得多；只适用于周期轨道失稳的分岔
+//         （倍周期、对称破缺），Newton 不收敛或两端不变号（鞍结、危机）时退回二分。
+// 一个粗网格区间内若有多次周期变化，只定位离周期一侧最近的那一次；割线法求得的阈值可能略出括号区间
+// （粗网格判定错了一格时）。
+
+/// 括号区间内的加密方法
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefineMethod {
+    Bisection,
+    Secant,
+}
+
+impl RefineMethod {
+    pub fn name(&self) -> &'static str {
+        match self {
+            RefineMethod::Bisection => "bisection",
+            RefineMethod::Secant => "secant",
+        }
+    }
+}
+
+/// 括号定位的设置
+#[derive(Debug, Clone, Copy)]
+pub struct BracketOptions {
+    /// 粗网格取 coarse_steps + 1 个等距参数值
+    pub coarse_steps: usize,
+    pub transient_periods: usize,
+    pub sample_periods: usize,
+    /// 判定周期的最大周期与距离容差
+    pub max_period: usize,
+    pub period_tolerance: f64,
+    /// 阈值所在区间的目标宽度
+    pub parameter_tolerance: f64,
+    pub method: RefineMethod,
+    /// 割线法的最大迭代次数
+    pub max_iterations: usize,
+}
+
+impl Default for BracketOptions {
+    fn default() -> Self {
+        BracketOptions {
+            coarse_steps: 20,
+            transient_periods: 300,
+            sample_periods: 64,
+            max_period: 16,
+            period_tolerance: 1e-5,
+            parameter_tolerance: 1e-6,
+            method: RefineMethod::Bisection,
+            max_iterations: 60,
+        }
+    }
+}
+
+/// 加密后的一个周期变化阈值
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transition {
+    /// 阈值所在的区间
+    pub lower: f64,
+    pub upper: f64,
+    /// 粗网格上括号两端的周期，None 表示非周期
+    pub before: Option<usize>,
+    pub after: Option<usize>,
+    /// 实际使用的方法（割线法退回二分时为 Bisection）
+    pub method: RefineMethod,
+    /// 加密阶段调用判定或乘子计算的次数
+    pub evaluations: usize,
+}
+
+impl Transition {
+    /// 区间中点
+    pub fn threshold(&self) -> f64 {
+        0.5 * (self.lower + self.upper)
+    }
+}
+
+// 割线法把括号向外平移的最大次数
+const MAX_SHIFTS: usize = 2;
+
+// 某个参数值下从 initial 出发的吸引子周期与最后一个庞加莱点
+fn classify(
+    params: &PendulumParams,
+    parameter: SweepParameter,
+    value: f64,
+    initial: State,
+    options: &BracketOptions,
+) -> (Option<usize>, State) {
+    let mut p = params.clone();
+    parameter.apply(&mut p, value);
+    let samples = StroboscopicMap::new(&p).section(initial, options.transient_periods, options.sample_periods);
+    let period = detect_period(&samples, options.max_period, options.period_tolerance);
+    let (theta, omega) = samples[samples.len() - 1];
+    (period, State { theta, omega })
+}
+
+// 从 seed = (参数值, 轨道上一点) 出发沿参数延拓周期 period 轨道，对 “谱半径 - 1” 做割线求根；失败时返回 None。
+// 每次求值都从已求得的最近参数点出发，以不超过括号宽度 1/16 的小步延拓过去，避免 Newton 跳到另一条轨道。
+// shift 为真时允许把括号向外平移
+fn refine_secant(
+    params: &PendulumParams,
+    parameter: SweepParameter,
+    bracket: (f64, f64),
+    period: usize,
+    seed: (f64, State),
+    shift: bool,
+    options: &BracketOptions,
+) -> Option<((f64, f64), usize)> {
+    let newton = NewtonOptions::default();
+    let max_step = (bracket.1 - bracket.0).abs() / 16.0;
+    let mut known = vec![seed];
+    let mut evaluations = 0;
+    let mut radius_gap = |value: f64| {
+        evaluations += 1;
+        let (start, mut guess) = *known.iter().min_by(|a, b| (a.0 - value).abs().total_cmp(&(b.0 - value).abs()))?;
+        let substeps = ((value - start).abs() / max_step).ceil().max(1.0) as usize;
+        let mut radius = f64::NAN;
+        for k in 1..=substeps {
+            let mut p = params.clone();
+            parameter.apply(&mut p, start + (value - start) * k as f64 / substeps as f64);
+            let orbit = find_periodic_orbit(&p, guess.theta, guess.omega, period, &newton).ok()?;
+            let (theta, omega) = orbit.points[0];
+            guess = State { theta, omega };
+            radius = orbit.spectral_radius();
+        }
+        known.push((value, guess));
+        Some(radius - 1.0)
+    };
+    // 粗网格的判定本身受临界慢化影响：轨道在括号两端都还稳定时，阈值在远离 seed 的一侧，把括号平移过去
+    let width = bracket.1 - bracket.0;
+    let away = if seed.0 == bracket.0 { width } else { -width };
+    let mut bracket = bracket;
+    for _ in 0..if shift { MAX_SHIFTS } else { 0 } {
+        if radius_gap(bracket.0)? >= 0.0 || radius_gap(bracket.1)? >= 0.0 {
+            break;
+        }
+        bracket = (bracket.0 + away, bracket.1 + away);
+    }
+    let found = illinois(bracket.0, bracket.1, options.parameter_tolerance, options.max_iterations, |value| {
+        radius_gap(value).unwrap_or(f64::NAN)
+    })?;
+    Some((found, evaluations))
+}
+
+/// 在 range 上定位吸引子周期发生变化的参数值，并把每个阈值加密到 parameter_tolerance
+///
+/// 每个参数值都从同一初值 initial 出发积分（不热启动），粗网格各点与各括号区间并行计算。
+/// 割线法以括号中周期一侧（优先左端）的轨道为延拓起点；两端都非周期时直接二分。
+/// 返回按参数从小到大排列的阈值。
+pub fn bracket_transitions(
+    params: &PendulumParams,
+    parameter: SweepParameter,
+    range: (f64, f64),
+    initial: State,
+    options: &BracketOptions,
+) -> Result<Vec<Transition>, ChaosError> {
+    check_orbit_params(params, 1)?;
+    if !(options.parameter_tolerance > 0.0 && options.coarse_steps > 0 && options.sample_periods >= 2) {
+        return Err(ChaosError::InvalidParams(
+            "bracketing needs parameter_tolerance > 0, coarse_steps >= 1 and sample_periods >= 2".to_string(),
+        ));
+    }
+    let grid: Vec<(f64, (Option<usize>, State))> = sweep_values(range, options.coarse_steps)
+        .into_par_iter()
+        .map(|value| (value, classify(params, parameter, value, initial, options)))
+        .collect();
+    let brackets: Vec<_> = grid.windows(2).filter(|w| w[0].1.0 != w[1].1.0).map(|w| (w[0], w[1])).collect();
+
+    let transitions = brackets
+        .into_par_iter()
+        .map(|((lo, (before, lo_state)), (hi, (after, hi_state)))| {
+            if options.method == RefineMethod::Secant {
+                let side = match (before, after) {
+                    (Some(p), _) => Some((p, (lo, lo_state))),
+                    (None, Some(p)) => Some((p, (hi, hi_state))),
+                    (None, None) => None,
+                };
+                // 只有一侧周期是另一侧的倍数（倍周期等）时，粗网格才可能因临界慢化判错一格
+                let shift = matches!((before, after), (Some(a), Some(b)) if b % a == 0 || a % b == 0);
+                if let Some((period, seed)) = side
+                    && let Some(((lower, upper), evaluations)) =
+                        refine_secant(params, parameter, (lo, hi), period, seed, shift, options)
+                {
+                    return Transition {
+                        lower,
+                        upper,
+                        before,
+                        after,
+                        method: RefineMethod::Secant,
+                        evaluations,
+                    };
+                }
+            }
+            let mut evaluations = 0;
+            let still_before = |value: f64| {
+                evaluations += 1;
+                classify(params, parameter, value, initial, options).0 == before
+            };
+            let (lower, upper) = bisect_predicate(lo, hi, options.parameter_tolerance, still_before).unwrap_or((lo, hi));
+            Transition {
+                lower,
+                upper,
+                before,
+                after,
+                method: RefineMethod::Bisection,
+                evaluations,
+            }
+        })
+        .collect();
+    Ok(transitions)
+}
+
+/// 把阈值写成 CSV（列：<参数名>,lower,upper,period_before,period_after,method,evaluations），非周期的一侧留空
+pub fn write_transitions_csv(path: &str, parameter: SweepParameter, transitions: &[Transition]) -> std::io::Result<()> {
+    let mut f = BufWriter::new(File::create(path)?);
+    writeln!(f, "{},lower,upper,period_before,period_after,method,evaluations", parameter.name())?;
+    let period = |p: Option<usize>| p.map_or(String::new(), |p| p.to_string());
+    for t in transitions {
+        writeln!(
+            f,
+            "{:.12},{:.12},{:.12},{},{},{},{}",
+            t.threshold(),
+            t.lower,
+            t.upper,
+            period(t.before),
+            period(t.after),
+            t.method.name(),
+            t.evaluations
+        )?;
+    }
+    f.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secant_and_bisection_bracket_period_doubling() {
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.q = 0.5;
+        params.f_d = 1.0;
+        params.omega_d = 2.0 / 3.0;
+        params.set_steps_per_period(100);
+        let initial = State { theta: 0.2, omega: 0.0 };
+        let mut options = BracketOptions {
+            coarse_steps: 4,
+            parameter_tolerance: 1e-8,
+            method: RefineMethod::Secant,
+            ..BracketOptions::default()
+        };
+        let secant = bracket_transitions(&params, SweepParameter::DriveAmplitude, (1.05, 1.08), initial, &options).unwrap();
+        let periods: Vec<_> = secant.iter().map(|t| (t.before, t.after, t.method)).collect();
+        assert_eq!(
+            periods,
+            [(Some(1), Some(2), RefineMethod::Secant), (Some(2), Some(4), RefineMethod::Secant)],
+            "{:?}",
+            secant
+        );
+        assert!(secant.iter().all(|t| t.upper - t.lower <= 1e-8));
+        assert!((secant[0].threshold() - 1.06637).abs() < 1e-4 && (secant[1].threshold() - 1.07931).abs() < 1e-4);
+
+        options.method = RefineMethod::Bisection;
+        options.parameter_tolerance = 1e-4;
+        let bisection = bracket_transitions(&params, SweepParameter::DriveAmplitude, (1.05, 1.08), initial, &options).unwrap();
+        assert_eq!(bisection.len(), 2);
+        for (b, s) in bisection.iter().zip(&secant) {
+            // 临界慢化使二分的阈值偏离乘子给出的精确值，但仍在粗网格间距之内
+            assert!((b.threshold() - s.threshold()).abs() < 2e-3, "{:?} vs {:?}", b, s);
+        }
+    }
+}
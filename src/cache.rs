@@ -0,0 +1,163 @@
+//cache.rs
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use crate::model::{Forcing, PendulumParams};
+
+// 结果缓存：昂贵的扫描（分岔图、长庞加莱采样）按 (参数, 初值, 采样设置) 存盘，
+// 只改作图设置重跑时直接读回，不必重新积分。
+// 键是上述内容加 crate 版本的 JSON（对象键有序，浮点数可往返），文件名取其 FNV-1a 64 位哈希：
+//   <dir>/<kind>-<hash>.json = {"key": 键, "value": 结果}
+// 读取时比较完整的键，哈希碰撞或文件损坏都只当作未命中。
+// 自定义驱动函数（Forcing::Custom）无法序列化，带它的参数不缓存。
+// 换了积分器实现而版本号未变时缓存不会自动失效，需要手动清空目录。
+
+/// 缓存键
+#[derive(Debug, Clone, PartialEq)]
+pub struct CacheKey {
+    kind: String,
+    key: Value,
+}
+
+// 64 位 FNV-1a
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |h, &b| (h ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3))
+}
+
+impl CacheKey {
+    /// kind 区分结果种类（如 "poincare"、"bifurcation"）；参数含自定义驱动函数时返回 None
+    pub fn new(kind: &str, params: &PendulumParams, theta0: f64, omega0: f64) -> Option<Self> {
+        if matches!(params.forcing, Forcing::Custom { .. }) {
+            return None;
+        }
+        let mut key = Map::new();
+        key.insert("crate_version".to_string(), Value::from(env!("CARGO_PKG_VERSION")));
+        key.insert("params".to_string(), serde_json::to_value(params).ok()?);
+        key.insert("theta0".to_string(), Value::from(theta0));
+        key.insert("omega0".to_string(), Value::from(omega0));
+        Some(CacheKey { kind: kind.to_string(), key: Value::Object(key) })
+    }
+
+    /// 追加一项影响结果的设置（过渡周期数、扫描范围等）
+    pub fn with<V: Serialize>(mut self, name: &str, value: V) -> Self {
+        let value = serde_json::to_value(value).unwrap_or(Value::Null);
+        if let Value::Object(map) = &mut self.key {
+            map.insert(name.to_string(), value);
+        }
+        self
+    }
+
+    /// 16 位十六进制哈希
+    pub fn hash(&self) -> String {
+        format!("{:016x}", fnv1a(self.key.to_string().as_bytes()))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry<K, T> {
+    key: K,
+    value: T,
+}
+
+/// 磁盘上的结果缓存目录
+#[derive(Debug, Clone)]
+pub struct ResultCache {
+    dir: PathBuf,
+}
+
+impl ResultCache {
+    /// 打开（必要时创建）缓存目录
+    pub fn open(dir: &str) -> std::io::Result<Self> {
+        std::fs::create_dir_all(dir)?;
+        Ok(ResultCache { dir: PathBuf::from(dir) })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// 键对应的缓存文件
+    pub fn path(&self, key: &CacheKey) -> PathBuf {
+        self.dir.join(format!("{}-{}.json", key.kind, key.hash()))
+    }
+
+    /// 读取缓存；不存在、损坏或键不一致时返回 None
+    pub fn get<T: DeserializeOwned>(&self, key: &CacheKey) -> Option<T> {
+        let f = File::open(self.path(key)).ok()?;
+        let entry: Entry<Value, T> = serde_json::from_reader(BufReader::new(f)).ok()?;
+        (entry.key == key.key).then_some(entry.value)
+    }
+
+    /// 写入缓存（先写临时文件再改名，中断不会留下半个文件）
+    pub fn put<T: Serialize>(&self, key: &CacheKey, value: &T) -> std::io::Result<()> {
+        let path = self.path(key);
+        let tmp = path.with_extension("json.tmp");
+        let mut f = BufWriter::new(File::create(&tmp)?);
+        serde_json::to_writer(&mut f, &Entry { key: &key.key, value }).map_err(std::io::Error::other)?;
+        f.flush()?;
+        drop(f);
+        std::fs::rename(tmp, path)
+    }
+
+    /// 命中时直接返回缓存结果，否则调用 compute 并存盘；第二个返回值表示是否命中
+    pub fn get_or_compute<T, E, F>(&self, key: &CacheKey, compute: F) -> Result<(T, bool), E>
+    where
+        T: Serialize + DeserializeOwned,
+        E: From<std::io::Error>,
+        F: FnOnce() -> Result<T, E>,
+    {
+        if let Some(value) = self.get(key) {
+            return Ok((value, true));
+        }
+        let value = compute()?;
+        self.put(key, &value)?;
+        Ok((value, false))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ChaosError;
+    use crate::solve_equation::poincare;
+
+    #[test]
+    fn test_cache_hit_miss_and_key_sensitivity() {
+        let dir = std::env::temp_dir().join(format!("chaos_cache_test_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let cache = ResultCache::open(dir.to_str().unwrap()).unwrap();
+        let mut params = PendulumParams::new();
+        params.t_end = 2.0 * std::f64::consts::PI / params.omega_d * 31.0;
+        let key = CacheKey::new("poincare", &params, 0.2, 0.0).unwrap().with("transient", 10).with("samples", 20);
+
+        let mut calls = 0;
+        let mut compute = || {
+            calls += 1;
+            poincare(&params, 0.2, 0.0, 10, 20)
+        };
+        let (first, hit) = cache.get_or_compute::<_, ChaosError, _>(&key, &mut compute).unwrap();
+        assert!(!hit);
+        let (second, hit) = cache.get_or_compute::<_, ChaosError, _>(&key, &mut compute).unwrap();
+        assert!(hit);
+        assert_eq!(calls, 1);
+        // 浮点数逐位相同
+        assert_eq!(first, second);
+
+        // 任何设置或初值变化都换一个键
+        let other = CacheKey::new("poincare", &params, 0.2, 0.0).unwrap().with("transient", 11).with("samples", 20);
+        assert_ne!(other.hash(), key.hash());
+        assert_eq!(cache.get::<Vec<(f64, f64)>>(&other), None);
+        assert_ne!(CacheKey::new("poincare", &params, 0.2, 1e-12).unwrap().hash(), CacheKey::new("poincare", &params, 0.2, 0.0).unwrap().hash());
+
+        // 文件损坏只算未命中
+        std::fs::write(cache.path(&key), "{").unwrap();
+        assert_eq!(cache.get::<Vec<(f64, f64)>>(&key), None);
+
+        params.forcing = Forcing::custom(|t| t.sin());
+        assert!(CacheKey::new("poincare", &params, 0.2, 0.0).is_none());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,185 @@
+//chaos_map.rs
+use std::f64::consts::PI;
+use std::fs::File;
+use std::io::Write;
+use rayon::prelude::*;
+use crate::basin::write_rgb_png;
+use crate::bifurcation::sweep_values;
+use crate::lyapunov::largest_exponent;
+use crate::model::PendulumParams;
+use crate::resumable::ResumableCsv;
+use crate::solve_equation::{steps_per_period, State};
+use crate::stroboscopic::StroboscopicMap;
+
+/// (f_d, q) 平面上的最大 Lyapunov 指数图，exponents 按 q 为行（外层）、f_d 为列（内层）排列
+#[derive(Debug, Clone)]
+pub struct ChaosMap {
+    pub f_ds: Vec<f64>,
+    pub qs: Vec<f64>,
+    pub exponents: Vec<f64>,
+}
+
+impl ChaosMap {
+    pub fn exponent_at(&self, i_q: usize, i_f_d: usize) -> f64 {
+        self.exponents[i_q * self.f_ds.len() + i_f_d]
+    }
+}
+
+/// 在 (f_d, q) 网格上并行估计最大 Lyapunov 指数
+///
+/// 两个参数各取 steps + 1 个等距值；每个格点从 (0, 0) 出发积分 transient_periods 个整周期，
+/// 再用变分方程在之后的 sample_periods 个周期上估计指数（每个驱动周期重归一化一次）。
+/// 各格点独立计算，不做热启动。
+pub fn lyapunov_chaos_map(
+    params: &PendulumParams,
+    f_d_range: (f64, f64),
+    f_d_steps: usize,
+    q_range: (f64, f64),
+    q_steps: usize,
+    transient_periods: usize,
+    sample_periods: usize,
+) -> ChaosMap {
+    let f_ds = sweep_values(f_d_range, f_d_steps);
+    let qs = sweep_values(q_range, q_steps);
+    let cells: Vec<(f64, f64)> = qs
+        .iter()
+        .flat_map(|&q| f_ds.iter().map(move |&f_d| (q, f_d)))
+        .collect();
+    let exponents = cells
+        .par_iter()
+        .map(|&(q, f_d)| cell_exponent(params, q, f_d, transient_periods, sample_periods))
+        .collect();
+    ChaosMap { f_ds, qs, exponents }
+}
+
+// 单个格点的最大 Lyapunov 指数
+fn cell_exponent(params: &PendulumParams, q: f64, f_d: f64, transient_periods: usize, sample_periods: usize) -> f64 {
+    let mut p = params.clone();
+    p.q = q;
+    p.f_d = f_d;
+    let spp = steps_per_period(&p);
+    let period = 2.0 * PI / p.omega_d;
+    let start = StroboscopicMap::with_steps(&p, spp).iterate_n(State { theta: 0.0, omega: 0.0 }, transient_periods);
+    p.dt = period / spp as f64;
+    p.t_end = (sample_periods * spp) as f64 * p.dt + 0.5 * p.dt;
+    largest_exponent(&p, start, period).exponent
+}
+
+/// 与 lyapunov_chaos_map 相同，但每算完一行（一个 q 上的全部 f_d）就追加写入 path
+/// （矩阵格式与 write_chaos_map_csv 相同，另有续算标记，见 resumable 模块）
+///
+/// path 是上次中断留下的部分结果时跳过已完成的行；完成标记中保存了该行指数的精确值。
+#[allow(clippy::too_many_arguments)]
+pub fn lyapunov_chaos_map_resumable(
+    params: &PendulumParams,
+    f_d_range: (f64, f64),
+    f_d_steps: usize,
+    q_range: (f64, f64),
+    q_steps: usize,
+    transient_periods: usize,
+    sample_periods: usize,
+    path: &str,
+) -> std::io::Result<ChaosMap> {
+    let f_ds = sweep_values(f_d_range, f_d_steps);
+    let qs = sweep_values(q_range, q_steps);
+    let header: String = std::iter::once("q\\f_d".to_string())
+        .chain(f_ds.iter().map(|f_d| format!("{:.12}", f_d)))
+        .collect::<Vec<_>>()
+        .join(",");
+    let mut csv = ResumableCsv::open(path, &header)?;
+    let mut rows: Vec<Option<Vec<f64>>> = vec![None; qs.len()];
+    for unit in csv.completed() {
+        if let Some(row) = rows.get_mut(unit.index)
+            && unit.state.len() == f_ds.len()
+        {
+            *row = Some(unit.state.clone());
+        }
+    }
+    for (i, &q) in qs.iter().enumerate() {
+        if rows[i].is_some() {
+            continue;
+        }
+        let row: Vec<f64> = f_ds
+            .par_iter()
+            .map(|&f_d| cell_exponent(params, q, f_d, transient_periods, sample_periods))
+            .collect();
+        csv.write_unit(i, &row, |w| {
+            write!(w, "{:.12}", q)?;
+            for lambda in &row {
+                write!(w, ",{:.12}", lambda)?;
+            }
+            writeln!(w)
+        })?;
+        rows[i] = Some(row);
+    }
+    let exponents = rows.into_iter().flatten().flatten().collect();
+    Ok(ChaosMap { f_ds, qs, exponents })
+}
+
+/// 把指数图写成矩阵 CSV：首行为 q\f_d 与各 f_d，之后每行以 q 开头，接该 q 下各 f_d 的指数
+pub fn write_chaos_map_csv(path: &str, map: &ChaosMap) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    write!(f, "q\\f_d")?;
+    for f_d in &map.f_ds {
+        write!(f, ",{:.12}", f_d)?;
+    }
+    writeln!(f)?;
+    for (i, q) in map.qs.iter().enumerate() {
+        write!(f, "{:.12}", q)?;
+        for j in 0..map.f_ds.len() {
+            write!(f, ",{:.12}", map.exponent_at(i, j))?;
+        }
+        writeln!(f)?;
+    }
+    Ok(())
+}
+
+// 发散色标：λ < 0 由白渐变到蓝，λ > 0 由白渐变到红，按 |λ| 的最大值归一化
+fn heat_color(lambda: f64, scale: f64) -> [u8; 3] {
+    let x = if scale > 0.0 { (lambda / scale).clamp(-1.0, 1.0) } else { 0.0 };
+    let fade = |v: f64| (255.0 * (1.0 - v.abs())).round() as u8;
+    if x >= 0.0 {
+        [255, fade(x), fade(x)]
+    } else {
+        [fade(x), fade(x), 255]
+    }
+}
+
+/// 把指数图写成 PNG 热图：横轴 f_d（向右增大），纵轴 q（向上增大），每个格点一个像素
+///
+/// 红色为混沌（λ > 0），蓝色为规则运动（λ < 0），接近白色处 λ ≈ 0（分岔点或准周期）。
+pub fn write_chaos_map_png(path: &str, map: &ChaosMap) -> std::io::Result<()> {
+    let (width, height) = (map.f_ds.len(), map.qs.len());
+    let scale = map.exponents.iter().map(|l| l.abs()).fold(0.0, f64::max);
+    let mut data = Vec::with_capacity(width * height * 3);
+    for i in (0..height).rev() {
+        for j in 0..width {
+            data.extend_from_slice(&heat_color(map.exponent_at(i, j), scale));
+        }
+    }
+    write_rgb_png(path, width, height, &data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chaos_map_separates_regular_and_chaotic_cells() {
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.omega_d = 2.0 / 3.0;
+        params.dt = 2.0 * PI / params.omega_d / 200.0;
+        // f_d = 0.5 为周期 1，f_d = 1.2 为混沌（q = 0.5）
+        let map = lyapunov_chaos_map(&params, (0.5, 1.2), 1, (0.5, 0.5), 0, 100, 200);
+        assert_eq!(map.exponents.len(), 2);
+        assert!(map.exponent_at(0, 0) < -0.05);
+        assert!(map.exponent_at(0, 1) > 0.05);
+
+        let path = std::env::temp_dir().join("chaos_map_test.csv");
+        write_chaos_map_csv(path.to_str().unwrap(), &map).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(text.lines().count(), 2);
+        assert!(text.starts_with("q\\f_d,0.5"));
+    }
+}
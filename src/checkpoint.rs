@@ -0,0 +1,175 @@
+//checkpoint.rs
+use std::f64::consts::PI;
+use std::fs::File;
+use std::io::Write;
+use std::ops::ControlFlow;
+use serde::{Deserialize, Serialize};
+use crate::error::ChaosError;
+use crate::model::{PendulumParams, TimeDirection};
+use crate::progress::ProgressInfo;
+use crate::random::{seeded_rng, DefaultRng};
+use crate::solve_equation::{step, steps_per_period, wrap_angle, State};
+use crate::stochastic::stochastic_step;
+
+// 长时间（t_end ~ 1e6）的庞加莱采样按整周期分段推进，每段之后把积分状态写盘；
+// 进程中断后从断点文件继续，结果与一次跑完完全相同（噪声的随机数状态也一并保存）。
+
+/// 庞加莱采样的断点：参数、采样设置、当前积分状态与已收集的庞加莱点
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// 积分参数（step_control 固定为 StepsPerPeriod）
+    pub params: PendulumParams,
+    pub initial_theta: f64,
+    pub initial_omega: f64,
+    pub transient_periods: usize,
+    pub sample_periods: usize,
+    /// 已完成的驱动周期数；state 是 t = periods_done·T 时的状态
+    pub periods_done: usize,
+    pub state: State,
+    /// 噪声的随机数状态（noise_intensity = 0 时为 None）
+    pub rng: Option<DefaultRng>,
+    /// 已收集的庞加莱点 (θ, ω)，θ 折叠到 [-π, π)
+    pub samples: Vec<(f64, f64)>,
+}
+
+impl Checkpoint {
+    /// 从初值开始的断点；每个周期的步数取 steps_per_period(params)，噪声由 noise_seed 播种
+    pub fn new(
+        params: &PendulumParams,
+        initial_theta: f64,
+        initial_omega: f64,
+        transient_periods: usize,
+        sample_periods: usize,
+    ) -> Result<Self, ChaosError> {
+        params.validate_periodic()?;
+        if params.direction == TimeDirection::Backward {
+            return Err(ChaosError::InvalidParams("checkpointed sampling only integrates forward".to_string()));
+        }
+        let mut p = params.clone();
+        p.set_steps_per_period(steps_per_period(params));
+        let rng = (p.noise_intensity > 0.0).then(|| seeded_rng(p.noise_seed));
+        Ok(Checkpoint {
+            params: p,
+            initial_theta,
+            initial_omega,
+            transient_periods,
+            sample_periods,
+            periods_done: 0,
+            state: State {
+                theta: initial_theta,
+                omega: initial_omega,
+            },
+            rng,
+            samples: Vec::with_capacity(sample_periods),
+        })
+    }
+
+    /// 当前时刻 t = periods_done·T
+    pub fn t(&self) -> f64 {
+        self.periods_done as f64 * self.period()
+    }
+
+    fn period(&self) -> f64 {
+        2.0 * PI / self.params.omega_d.abs()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.periods_done >= self.transient_periods + self.sample_periods
+    }
+
+    /// 再积分至多 periods 个整周期（不超过剩余周期数），过渡期之后的每个周期末记入 samples
+    pub fn advance(&mut self, periods: usize) {
+        let end = self.periods_done.saturating_add(periods).min(self.transient_periods + self.sample_periods);
+        let spp = steps_per_period(&self.params);
+        let period = self.period();
+        while self.periods_done < end {
+            let n = self.periods_done;
+            for k in 0..spp {
+                // 周期内的时刻从周期起点重新计算，不累积舍入误差
+                let t = n as f64 * period + k as f64 * self.params.dt;
+                self.state = match &mut self.rng {
+                    Some(rng) => stochastic_step(&self.state, t, self.params.dt, &self.params, rng),
+                    None => step(&self.state, t, &self.params).0,
+                };
+            }
+            self.periods_done += 1;
+            if self.periods_done > self.transient_periods {
+                self.samples.push((wrap_angle(self.state.theta), self.state.omega));
+            }
+        }
+    }
+
+    /// 每推进 every_periods 个周期写一次断点文件，直到采样完成
+    pub fn run(&mut self, path: &str, every_periods: usize) -> Result<(), ChaosError> {
+        self.run_with_progress(path, every_periods, |_| ControlFlow::Continue(()))
+    }
+
+    /// 与 run 相同，每写一次断点文件后调用 on_progress（单位为驱动周期）；回调返回 Break 时停下，断点文件已是最新
+    pub fn run_with_progress<F>(&mut self, path: &str, every_periods: usize, mut on_progress: F) -> Result<(), ChaosError>
+    where
+        F: FnMut(ProgressInfo) -> ControlFlow<()>,
+    {
+        while !self.is_complete() {
+            self.advance(every_periods.max(1));
+            self.save(path)?;
+            let info = ProgressInfo {
+                done: self.periods_done,
+                total: self.transient_periods + self.sample_periods,
+            };
+            if on_progress(info).is_break() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// 先写到 path.tmp 并落盘再改名，写盘途中崩溃或写入失败时旧的断点文件仍然完整
+    pub fn save(&self, path: &str) -> std::io::Result<()> {
+        let tmp = format!("{}.tmp", path);
+        let mut f = std::io::BufWriter::new(File::create(&tmp)?);
+        serde_json::to_writer(&mut f, self)?;
+        f.flush()?;
+        f.get_ref().sync_all()?;
+        drop(f);
+        std::fs::rename(&tmp, path)
+    }
+
+    pub fn load(path: &str) -> std::io::Result<Self> {
+        let f = File::open(path)?;
+        Ok(serde_json::from_reader(std::io::BufReader::new(f))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resume_matches_uninterrupted_run() {
+        let mut params = PendulumParams::new();
+        params.q = 0.5;
+        params.f_d = 1.2;
+        params.omega_d = 2.0 / 3.0;
+        params.set_steps_per_period(100);
+        params.noise_intensity = 0.05;
+        params.noise_seed = 3;
+
+        let mut full = Checkpoint::new(&params, 0.2, 0.0, 10, 30).unwrap();
+        full.advance(usize::MAX);
+        assert!(full.is_complete());
+        assert_eq!(full.samples.len(), 30);
+
+        // 跑到一半写盘，读回后继续
+        let path = std::env::temp_dir().join("chaos_checkpoint_test.json");
+        let path = path.to_str().unwrap();
+        let mut first = Checkpoint::new(&params, 0.2, 0.0, 10, 30).unwrap();
+        first.advance(17);
+        first.save(path).unwrap();
+        let mut resumed = Checkpoint::load(path).unwrap();
+        assert_eq!(resumed.periods_done, 17);
+        resumed.run(path, 5).unwrap();
+        assert_eq!(resumed.samples, full.samples);
+        assert_eq!(Checkpoint::load(path).unwrap().samples, full.samples);
+        std::fs::remove_file(path).ok();
+    }
+}
@@ -0,0 +1,270 @@
+//collector.rs
+use std::collections::BTreeMap;
+use std::sync::mpsc;
+use std::thread::JoinHandle;
+use rayon::prelude::*;
+use crate::io::{CsvSink, TrajectoryFormat};
+
+// 并行扫描的单文件输出。rayon 各工作线程算完参数点的先后取决于调度，直接追加写会让每次运行的行序不同，
+// 每个线程各写一个文件又要事后合并（merge 模块）。OrderedCollector 在后台线程里按参数点下标重排：
+// 工作线程把第 i 个参数点的全部行 submit(i, rows) 送进通道，后台线程把还没轮到的块暂存在 BTreeMap 里，
+// 下标 next 一到就连同其后已到的连续块一起写出。行序只取决于下标，与线程数和调度无关，
+// 文件与串行按下标写出的逐字节相同。
+// 暂存的块数取决于最慢的参数点拖后多少：最坏情况下（第 0 个最后完成）整张扫描都留在内存里。
+
+/// 逐行写出扫描结果的后端，每行是固定列数的 f64
+pub trait RowSink {
+    fn write_row(&mut self, row: &[f64]) -> std::io::Result<()>;
+    fn finish(&mut self) -> std::io::Result<()>;
+}
+
+impl<W: RowSink + ?Sized> RowSink for Box<W> {
+    fn write_row(&mut self, row: &[f64]) -> std::io::Result<()> {
+        (**self).write_row(row)
+    }
+
+    fn finish(&mut self) -> std::io::Result<()> {
+        (**self).finish()
+    }
+}
+
+/// CSV 输出（首行为列名），可选 gzip 压缩
+pub struct CsvRowSink {
+    sink: CsvSink,
+}
+
+impl CsvRowSink {
+    pub fn create(path: &str, columns: &[&str], gzip: bool) -> std::io::Result<Self> {
+        let mut sink = CsvSink::create(path, gzip)?;
+        writeln!(sink.out(), "{}", columns.join(","))?;
+        Ok(CsvRowSink { sink })
+    }
+}
+
+impl RowSink for CsvRowSink {
+    fn write_row(&mut self, row: &[f64]) -> std::io::Result<()> {
+        let out = self.sink.out();
+        for (i, v) in row.iter().enumerate() {
+            if i > 0 {
+                write!(out, ",")?;
+            }
+            write!(out, "{:.12}", v)?;
+        }
+        writeln!(out)
+    }
+
+    fn finish(&mut self) -> std::io::Result<()> {
+        self.sink.finish()
+    }
+}
+
+/// Parquet 输出（每列 Float64），每攒够 batch_size 行交给 ArrowWriter 一次
+#[cfg(feature = "parquet")]
+pub struct ParquetRowSink {
+    writer: Option<parquet::arrow::ArrowWriter<std::fs::File>>,
+    schema: std::sync::Arc<arrow_schema::Schema>,
+    batch_size: usize,
+    columns: Vec<Vec<f64>>,
+}
+
+#[cfg(feature = "parquet")]
+impl ParquetRowSink {
+    pub fn create(path: &str, columns: &[&str]) -> std::io::Result<Self> {
+        use arrow_schema::{DataType, Field, Schema};
+        let fields: Vec<Field> = columns.iter().map(|name| Field::new(*name, DataType::Float64, false)).collect();
+        let schema = std::sync::Arc::new(Schema::new(fields));
+        let writer = parquet::arrow::ArrowWriter::try_new(std::fs::File::create(path)?, schema.clone(), None)
+            .map_err(std::io::Error::other)?;
+        Ok(ParquetRowSink {
+            writer: Some(writer),
+            schema,
+            batch_size: crate::io::ParquetTrajectoryWriter::DEFAULT_BATCH_SIZE,
+            columns: vec![Vec::new(); columns.len()],
+        })
+    }
+
+    fn flush_batch(&mut self) -> std::io::Result<()> {
+        if self.columns.first().is_none_or(|c| c.is_empty()) {
+            return Ok(());
+        }
+        let arrays: Vec<arrow_array::ArrayRef> = self
+            .columns
+            .iter_mut()
+            .map(|c| std::sync::Arc::new(arrow_array::Float64Array::from(std::mem::take(c))) as _)
+            .collect();
+        let batch = arrow_array::RecordBatch::try_new(self.schema.clone(), arrays).map_err(std::io::Error::other)?;
+        match &mut self.writer {
+            Some(w) => w.write(&batch).map_err(std::io::Error::other),
+            None => Err(std::io::Error::other("parquet writer already finished")),
+        }
+    }
+}
+
+#[cfg(feature = "parquet")]
+impl RowSink for ParquetRowSink {
+    fn write_row(&mut self, row: &[f64]) -> std::io::Result<()> {
+        if row.len() != self.columns.len() {
+            return Err(std::io::Error::other(format!("expected {} columns, got {}", self.columns.len(), row.len())));
+        }
+        for (column, &v) in self.columns.iter_mut().zip(row) {
+            column.push(v);
+        }
+        if self.columns[0].len() >= self.batch_size {
+            self.flush_batch()?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> std::io::Result<()> {
+        self.flush_batch()?;
+        if let Some(w) = self.writer.take() {
+            w.close().map_err(std::io::Error::other)?;
+        }
+        Ok(())
+    }
+}
+
+/// 按格式创建扫描结果输出；支持 CSV、gzip CSV 与 Parquet（需 feature）
+pub fn create_row_sink(path: &str, format: TrajectoryFormat, columns: &[&str]) -> std::io::Result<Box<dyn RowSink + Send>> {
+    match format {
+        TrajectoryFormat::Csv => Ok(Box::new(CsvRowSink::create(path, columns, false)?)),
+        TrajectoryFormat::CsvGzip => Ok(Box::new(CsvRowSink::create(path, columns, true)?)),
+        #[cfg(feature = "parquet")]
+        TrajectoryFormat::Parquet => Ok(Box::new(ParquetRowSink::create(path, columns)?)),
+        TrajectoryFormat::Binary => Err(std::io::Error::other("sweep output needs a .csv, .csv.gz or .parquet path")),
+    }
+}
+
+type Block = (usize, Vec<Vec<f64>>);
+
+/// 把各工作线程送来的参数点结果按下标 0..units 的顺序写进同一个 RowSink
+///
+/// submit 只需 &self，可以直接在 rayon 的闭包里调用。每个下标必须恰好提交一次；
+/// 后台写出出错、下标重复或越界时后台线程停止，之后的 submit 失败，finish 返回原始错误。
+pub struct OrderedCollector {
+    sender: Option<mpsc::Sender<Block>>,
+    worker: Option<JoinHandle<std::io::Result<usize>>>,
+}
+
+impl OrderedCollector {
+    pub fn spawn<S: RowSink + Send + 'static>(mut sink: S, units: usize) -> Self {
+        let (sender, receiver) = mpsc::channel::<Block>();
+        let worker = std::thread::spawn(move || {
+            let mut pending: BTreeMap<usize, Vec<Vec<f64>>> = BTreeMap::new();
+            let (mut next, mut rows) = (0, 0);
+            for (index, block) in receiver {
+                if index >= units || index < next || pending.insert(index, block).is_some() {
+                    return Err(std::io::Error::other(format!("sweep unit {} submitted twice or out of range", index)));
+                }
+                while let Some(block) = pending.remove(&next) {
+                    for row in &block {
+                        sink.write_row(row)?;
+                    }
+                    rows += block.len();
+                    next += 1;
+                }
+            }
+            if next < units {
+                return Err(std::io::Error::other(format!("sweep unit {} was never submitted", next)));
+            }
+            sink.finish()?;
+            Ok(rows)
+        });
+        OrderedCollector {
+            sender: Some(sender),
+            worker: Some(worker),
+        }
+    }
+
+    /// 提交第 index 个参数点的全部行
+    pub fn submit(&self, index: usize, rows: Vec<Vec<f64>>) -> std::io::Result<()> {
+        match &self.sender {
+            Some(sender) if sender.send((index, rows)).is_ok() => Ok(()),
+            _ => Err(std::io::Error::other("sweep writer thread stopped")),
+        }
+    }
+
+    /// 等后台线程写完并收尾，返回写出的行数；有下标没提交时返回错误
+    pub fn finish(mut self) -> std::io::Result<usize> {
+        self.join()
+    }
+
+    fn join(&mut self) -> std::io::Result<usize> {
+        self.sender = None;
+        match self.worker.take() {
+            Some(worker) => worker.join().map_err(|_| std::io::Error::other("sweep writer thread panicked"))?,
+            None => Err(std::io::Error::other("sweep writer already finished")),
+        }
+    }
+}
+
+impl Drop for OrderedCollector {
+    fn drop(&mut self) {
+        if self.worker.is_some() {
+            let _ = self.join();
+        }
+    }
+}
+
+/// 对 values 并行求值，每个值的行经 OrderedCollector 按 values 的顺序写入 sink；返回写出的行数
+pub fn par_sweep_to_sink<T, S, F>(values: &[T], sink: S, rows: F) -> std::io::Result<usize>
+where
+    T: Sync,
+    S: RowSink + Send + 'static,
+    F: Fn(&T) -> Vec<Vec<f64>> + Sync,
+{
+    let collector = OrderedCollector::spawn(sink, values.len());
+    let sent = values.par_iter().enumerate().try_for_each(|(i, v)| collector.submit(i, rows(v)));
+    // 后台线程的错误比 “线程已停止” 更有用，先取它
+    let written = collector.finish()?;
+    sent.map(|_| written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collector_order_is_independent_of_completion_order() {
+        let dir = std::env::temp_dir();
+        let serial = dir.join("chaos_collector_serial.csv");
+        let parallel = dir.join("chaos_collector_parallel.csv");
+        let (serial, parallel) = (serial.to_str().unwrap(), parallel.to_str().unwrap());
+        let block = |i: usize| (0..=i % 3).map(|k| vec![i as f64, k as f64 / 7.0]).collect::<Vec<_>>();
+
+        let mut sink = CsvRowSink::create(serial, &["i", "x"], false).unwrap();
+        for i in 0..50 {
+            block(i).iter().try_for_each(|row| sink.write_row(row)).unwrap();
+        }
+        sink.finish().unwrap();
+
+        // 倒序提交：全部暂存到最后才开始写
+        let collector = OrderedCollector::spawn(CsvRowSink::create(parallel, &["i", "x"], false).unwrap(), 50);
+        (0..50).rev().try_for_each(|i| collector.submit(i, block(i))).unwrap();
+        assert_eq!(collector.finish().unwrap(), 99);
+        assert_eq!(std::fs::read_to_string(parallel).unwrap(), std::fs::read_to_string(serial).unwrap());
+
+        // rayon 并行，工作量不均使完成顺序被打乱
+        let values: Vec<usize> = (0..50).collect();
+        let sink = create_row_sink(parallel, TrajectoryFormat::Csv, &["i", "x"]).unwrap();
+        let written = par_sweep_to_sink(&values, sink, |&i| {
+            std::thread::sleep(std::time::Duration::from_micros(((50 - i) * 37 % 400) as u64));
+            block(i)
+        })
+        .unwrap();
+        assert_eq!(written, 99);
+        assert_eq!(std::fs::read_to_string(parallel).unwrap(), std::fs::read_to_string(serial).unwrap());
+
+        // 缺下标或重复提交
+        let collector = OrderedCollector::spawn(CsvRowSink::create(parallel, &["i"], false).unwrap(), 3);
+        collector.submit(0, vec![vec![0.0]]).unwrap();
+        collector.submit(2, vec![vec![2.0]]).unwrap();
+        assert!(collector.finish().unwrap_err().to_string().contains("unit 1 was never submitted"));
+        let collector = OrderedCollector::spawn(CsvRowSink::create(parallel, &["i"], false).unwrap(), 3);
+        collector.submit(0, vec![vec![0.0]]).unwrap();
+        let _ = collector.submit(0, vec![vec![0.0]]);
+        assert!(collector.finish().is_err());
+        std::fs::remove_file(serial).unwrap();
+        std::fs::remove_file(parallel).unwrap();
+    }
+}
@@ -0,0 +1,280 @@
+//compare.rs
+use std::f64::consts::PI;
+use std::fmt;
+use std::time::Instant;
+use crate::basin::{mean_section_distance, section_distance};
+use crate::diagnostics::{energy_diagnostics, max_balance_error};
+use crate::error::ChaosError;
+use crate::model::{Integrator, PendulumParams, StepControl};
+use crate::solve_equation::{sample_poincare_from_trajectory, solve, State};
+use crate::trajectory::Trajectory;
+
+// 积分器的横向比较：同一组参数、同一初值，分别用 RK4（定步长）、自适应 RK45（Dormand–Prince）、
+// 速度 Verlet 与 Yoshida 四阶辛方法积分 transient_periods + sample_periods 个驱动周期，给出
+//   每个方案：积分耗时、步数、能量收支残差的最大值（diagnostics 模块，驱动做功与阻尼耗散都计入，
+//             残差只来自数值误差）、庞加莱截面；
+//   每一对方案：两条轨迹重采样到同一均匀网格（Trajectory::resample）后的最大距离、末时刻距离，
+//             距离首次超过 divergence_threshold 的时刻，以及两组庞加莱点的差别。
+// 庞加莱点的差别给两个数：按下标逐点比较的最大距离（轨迹还没分开时有意义），
+// 以及两组点互相取最近邻距离的平均值（对称化）。混沌区里轨迹迟早分开，逐点距离会到吸引子尺度，
+// 而点集距离仍然小，说明两种方案画出的是同一个吸引子；周期区里两者都应在积分误差量级。
+// 距离在截面坐标下计算，θ 按周期 2π 取最短差（basin::section_distance、mean_section_distance）。
+
+/// 参与比较的积分方案
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scheme {
+    Rk4,
+    /// 自适应步长的 Dormand–Prince RK45，容差取 params.atol / rtol
+    Rk45,
+    VelocityVerlet,
+    Yoshida4,
+}
+
+impl Scheme {
+    pub const ALL: [Scheme; 4] = [Scheme::Rk4, Scheme::Rk45, Scheme::VelocityVerlet, Scheme::Yoshida4];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Scheme::Rk4 => "rk4",
+            Scheme::Rk45 => "rk45",
+            Scheme::VelocityVerlet => "verlet",
+            Scheme::Yoshida4 => "yoshida4",
+        }
+    }
+
+    /// 按名称查找（rk4、rk45、verlet、yoshida4）
+    pub fn from_name(name: &str) -> Option<Scheme> {
+        Scheme::ALL.into_iter().find(|s| s.name() == name)
+    }
+
+    /// 在 base 上换成本方案的积分器与步长控制；定步长方案沿用 base 的步长设置
+    pub fn apply(&self, base: &PendulumParams) -> PendulumParams {
+        let mut params = base.clone();
+        match self {
+            Scheme::Rk45 => {
+                params.integrator = Integrator::Rk4;
+                params.step_control = StepControl::Adaptive;
+            }
+            fixed => {
+                if params.step_control == StepControl::Adaptive {
+                    params.step_control = StepControl::FixedDt;
+                }
+                params.integrator = match fixed {
+                    Scheme::VelocityVerlet => Integrator::VelocityVerlet,
+                    Scheme::Yoshida4 => Integrator::Yoshida4,
+                    _ => Integrator::Rk4,
+                };
+            }
+        }
+        params
+    }
+}
+
+/// 比较的设置
+#[derive(Debug, Clone)]
+pub struct CompareOptions {
+    pub schemes: Vec<Scheme>,
+    pub transient_periods: usize,
+    pub sample_periods: usize,
+    /// 比较轨迹时每个驱动周期的重采样点数
+    pub grid_per_period: usize,
+    /// 轨迹距离超过它时记为分开
+    pub divergence_threshold: f64,
+}
+
+impl Default for CompareOptions {
+    fn default() -> Self {
+        CompareOptions {
+            schemes: Scheme::ALL.to_vec(),
+            transient_periods: 100,
+            sample_periods: 200,
+            grid_per_period: 32,
+            divergence_threshold: 1e-3,
+        }
+    }
+}
+
+/// 一个方案的结果
+#[derive(Debug, Clone)]
+pub struct SchemeResult {
+    pub scheme: Scheme,
+    /// 积分与能量诊断的耗时（秒）
+    pub seconds: f64,
+    /// 积分步数（轨迹点数减一）
+    pub steps: usize,
+    /// 能量收支残差绝对值的最大值
+    pub energy_error: f64,
+    pub section: Vec<(f64, f64)>,
+    trajectory: Trajectory,
+}
+
+/// 两个方案之间的差别
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PairComparison {
+    pub a: Scheme,
+    pub b: Scheme,
+    pub max_distance: f64,
+    pub final_distance: f64,
+    /// 距离首次超过 divergence_threshold 的时刻；始终没超过时为 None
+    pub divergence_time: Option<f64>,
+    /// 庞加莱点按下标逐点比较的最大距离
+    pub section_max_gap: f64,
+    /// 两组庞加莱点互取最近邻距离的平均值
+    pub section_set_distance: f64,
+}
+
+/// compare 的结果
+#[derive(Debug, Clone)]
+pub struct Comparison {
+    pub results: Vec<SchemeResult>,
+    pub pairs: Vec<PairComparison>,
+    pub divergence_threshold: f64,
+}
+
+fn compare_pair(a: &SchemeResult, b: &SchemeResult, threshold: f64) -> PairComparison {
+    // 各自的轨迹已经在同一网格上，只需逐点比较
+    let (ta, tb) = (&a.trajectory, &b.trajectory);
+    let distances: Vec<(f64, f64)> = ta
+        .iter()
+        .zip(tb.iter())
+        .map(|((t, sa), (_, sb))| (t, section_distance((sa.theta, sa.omega), (sb.theta, sb.omega))))
+        .collect();
+    PairComparison {
+        a: a.scheme,
+        b: b.scheme,
+        max_distance: distances.iter().map(|d| d.1).fold(0.0, f64::max),
+        final_distance: distances.last().map_or(f64::NAN, |d| d.1),
+        divergence_time: distances.iter().find(|d| d.1 > threshold).map(|d| d.0),
+        section_max_gap: a
+            .section
+            .iter()
+            .zip(&b.section)
+            .map(|(&p, &q)| section_distance(p, q))
+            .fold(0.0, f64::max),
+        section_set_distance: mean_section_distance(&a.section, &b.section),
+    }
+}
+
+/// 用 options.schemes 中的各方案从 initial 出发积分并两两比较
+pub fn compare_integrators(params: &PendulumParams, initial: State, options: &CompareOptions) -> Result<Comparison, ChaosError> {
+    params.validate_periodic()?;
+    if options.schemes.is_empty() || options.sample_periods == 0 || options.grid_per_period == 0 {
+        return Err(ChaosError::InvalidParams(
+            "comparison needs at least one scheme, sample_periods >= 1 and grid_per_period >= 1".to_string(),
+        ));
+    }
+    let period = 2.0 * PI / params.omega_d.abs();
+    let mut base = params.clone();
+    base.t_end = period * (options.transient_periods + options.sample_periods) as f64 + params.dt;
+    let dt_out = period / options.grid_per_period as f64;
+    let t_grid_end = period * (options.transient_periods + options.sample_periods) as f64;
+
+    let mut results = Vec::with_capacity(options.schemes.len());
+    for &scheme in &options.schemes {
+        let p = scheme.apply(&base);
+        let start = Instant::now();
+        let rows = solve(&p, initial.theta, initial.omega)?;
+        let energy_error = max_balance_error(&energy_diagnostics(&rows, &p));
+        let seconds = start.elapsed().as_secs_f64();
+        let section = sample_poincare_from_trajectory(&rows, &p, options.transient_periods, options.sample_periods).complete()?;
+        let trajectory = Trajectory::from(rows.as_slice()).window(0.0, t_grid_end + dt_out).resample(&p, dt_out)?;
+        results.push(SchemeResult {
+            scheme,
+            seconds,
+            steps: rows.len().saturating_sub(1),
+            energy_error,
+            section,
+            trajectory,
+        });
+    }
+    let mut pairs = Vec::new();
+    for (i, a) in results.iter().enumerate() {
+        for b in &results[i + 1..] {
+            pairs.push(compare_pair(a, b, options.divergence_threshold));
+        }
+    }
+    Ok(Comparison {
+        results,
+        pairs,
+        divergence_threshold: options.divergence_threshold,
+    })
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:<10} {:>10} {:>10} {:>14}", "scheme", "steps", "seconds", "energy error")?;
+        for r in &self.results {
+            writeln!(f, "{:<10} {:>10} {:>10.4} {:>14.3e}", r.scheme.name(), r.steps, r.seconds, r.energy_error)?;
+        }
+        writeln!(f)?;
+        write!(
+            f,
+            "{:<20} {:>12} {:>12} {:>14} {:>12} {:>12}",
+            "pair",
+            "max dist",
+            "final dist",
+            format!("t(>{:.0e})", self.divergence_threshold),
+            "section gap",
+            "section set"
+        )?;
+        for p in &self.pairs {
+            let diverged = p.divergence_time.map_or("never".to_string(), |t| format!("{:.3}", t));
+            write!(
+                f,
+                "\n{:<20} {:>12.3e} {:>12.3e} {:>14} {:>12.3e} {:>12.3e}",
+                format!("{} vs {}", p.a.name(), p.b.name()),
+                p.max_distance,
+                p.final_distance,
+                diverged,
+                p.section_max_gap,
+                p.section_set_distance
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_integrators_agree_on_periodic_orbit_and_diverge_in_chaos() {
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.q = 0.5;
+        params.f_d = 0.5;
+        params.omega_d = 2.0 / 3.0;
+        params.set_steps_per_period(200);
+        params.atol = 1e-10;
+        params.rtol = 1e-10;
+        let options = CompareOptions {
+            transient_periods: 20,
+            sample_periods: 20,
+            ..CompareOptions::default()
+        };
+        let start = State { theta: 0.2, omega: 0.0 };
+        let periodic = compare_integrators(&params, start, &options).unwrap();
+        assert_eq!(periodic.results.len(), 4);
+        assert_eq!(periodic.pairs.len(), 6);
+        assert!(periodic.results.iter().all(|r| r.section.len() == 20 && r.energy_error < 1e-2), "{}", periodic);
+        // 周期吸引子：所有方案收敛到同一条轨道；两个 RK 方案之间的差别在容差量级，远小于与辛方法的差别
+        assert!(periodic.pairs.iter().all(|p| p.max_distance < 0.1 && p.section_set_distance < 0.1), "{}", periodic);
+        let rk = periodic.pairs.iter().find(|p| p.a == Scheme::Rk4 && p.b == Scheme::Rk45).unwrap();
+        assert!(rk.divergence_time.is_none() && rk.max_distance < 1e-6, "{}", periodic);
+        assert!(periodic.to_string().contains("rk4 vs rk45"));
+
+        // 混沌：轨迹分开，截面点集仍属同一吸引子
+        params.f_d = 1.2;
+        let options = CompareOptions {
+            schemes: vec![Scheme::Rk4, Scheme::Yoshida4],
+            transient_periods: 50,
+            sample_periods: 300,
+            ..CompareOptions::default()
+        };
+        let chaotic = compare_integrators(&params, start, &options).unwrap();
+        let pair = chaotic.pairs[0];
+        assert!(pair.divergence_time.is_some() && pair.section_max_gap > 0.5, "{}", chaotic);
+        assert!(pair.section_set_distance < 0.2, "{}", chaotic);
+    }
+}
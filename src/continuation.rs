@@ -0,0 +1,357 @@
+//continuation.rs
+use std::f64::consts::PI;
+use std::fs::File;
+use std::io::Write;
+use crate::error::ChaosError;
+use crate::model::PendulumParams;
+use crate::periodic_orbits::{check_orbit_params, eigenvalues, map_with_jacobian, orbit_params, NewtonOptions, PeriodicOrbit};
+use crate::solve_equation::{wrap_angle, State};
+use crate::stroboscopic::StroboscopicMap;
+
+// 伪弧长延拓：把 f_d 当作未知量，X = (θ, ω, f_d) 满足 G(X) = F^k(θ, ω; f_d) - (θ, ω) - (2πm, 0) = 0，
+// 解集是一条曲线。每步沿切向量 T 预测 X + Δs·T，再在垂直于 T 的超平面 T·(X - X_pred) = 0 上 Newton 校正。
+// 与按 f_d 逐点求解不同，它能绕过折点（f_d 方向回头），从而跟踪不稳定的那一支。
+// 沿途监视 Floquet 乘子：det(M + I) 变号即有乘子穿过 -1（倍周期），det(M - I) 变号即有乘子穿过 +1，
+// 若此时切向量的 f_d 分量也变号是鞍结（折点），否则是叉式分岔（单摆的对称破缺）。
+
+/// 沿分支检测到的分岔类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BifurcationKind {
+    /// 乘子穿过 -1
+    PeriodDoubling,
+    /// 乘子穿过 +1 且分支在 f_d 方向折回
+    SaddleNode,
+    /// 乘子穿过 +1 而分支不折回（对称破缺）
+    Pitchfork,
+}
+
+impl BifurcationKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BifurcationKind::PeriodDoubling => "period_doubling",
+            BifurcationKind::SaddleNode => "saddle_node",
+            BifurcationKind::Pitchfork => "pitchfork",
+        }
+    }
+}
+
+/// 分支上的一个点
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContinuationPoint {
+    pub f_d: f64,
+    /// 轨道的第一个庞加莱点，θ 折叠到 [-π, π)
+    pub theta: f64,
+    pub omega: f64,
+    /// Floquet 乘子 (实部, 虚部)，按模从大到小
+    pub multipliers: [(f64, f64); 2],
+    pub stable: bool,
+}
+
+/// 检测到的分岔，位置由两侧检验函数的线性插值估计
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DetectedBifurcation {
+    pub kind: BifurcationKind,
+    /// 分岔位于 points[index - 1] 与 points[index] 之间
+    pub index: usize,
+    pub f_d: f64,
+    pub theta: f64,
+    pub omega: f64,
+}
+
+/// 一条周期轨道分支
+#[derive(Debug, Clone)]
+pub struct Branch {
+    pub period: usize,
+    pub winding: i64,
+    pub points: Vec<ContinuationPoint>,
+    pub bifurcations: Vec<DetectedBifurcation>,
+}
+
+/// 延拓的设置
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContinuationOptions {
+    /// 初始弧长步长，符号决定 f_d 的初始走向
+    pub step: f64,
+    pub min_step: f64,
+    pub max_step: f64,
+    /// 分支上最多的点数（含起点）
+    pub max_points: usize,
+    /// f_d 离开 [f_d_min, f_d_max] 后停止
+    pub f_d_min: f64,
+    pub f_d_max: f64,
+    /// 校正步的 Newton 设置（其中 max_step 不起作用）
+    pub newton: NewtonOptions,
+    /// ∂G/∂f_d 的中心差分相对步长
+    pub parameter_h: f64,
+}
+
+impl Default for ContinuationOptions {
+    fn default() -> Self {
+        ContinuationOptions {
+            step: 0.01,
+            min_step: 1e-5,
+            max_step: 0.05,
+            max_points: 500,
+            f_d_min: 0.0,
+            f_d_max: 2.0,
+            newton: NewtonOptions {
+                max_iterations: 10,
+                ..NewtonOptions::default()
+            },
+            parameter_h: 1e-6,
+        }
+    }
+}
+
+type Vec3 = [f64; 3];
+
+// 某个 X 处的残差 G、扩展雅可比 [M - I | ∂G/∂f_d] 与单值矩阵 M
+struct Evaluation {
+    g: [f64; 2],
+    a: [Vec3; 2],
+    monodromy: [[f64; 2]; 2],
+}
+
+impl Evaluation {
+    // det(M + I) 与 det(M - I)
+    fn test_functions(&self) -> (f64, f64) {
+        let m = &self.monodromy;
+        let (trace, det) = (m[0][0] + m[1][1], m[0][0] * m[1][1] - m[0][1] * m[1][0]);
+        (1.0 + trace + det, 1.0 - trace + det)
+    }
+}
+
+fn evaluate(params: &PendulumParams, x: &Vec3, period: usize, winding: i64, options: &ContinuationOptions) -> Evaluation {
+    let mut p = params.clone();
+    p.f_d = x[2];
+    let start = State {
+        theta: x[0],
+        omega: x[1],
+    };
+    let (fx, m) = map_with_jacobian(&p, start, period, options.newton.jacobian);
+    let h = options.parameter_h * x[2].abs().max(1.0);
+    p.f_d = x[2] + h;
+    let plus = StroboscopicMap::new(&p).iterate_n(start, period);
+    p.f_d = x[2] - h;
+    let minus = StroboscopicMap::new(&p).iterate_n(start, period);
+    let dp = ((plus.theta - minus.theta) / (2.0 * h), (plus.omega - minus.omega) / (2.0 * h));
+    Evaluation {
+        g: [fx.theta - x[0] - 2.0 * PI * winding as f64, fx.omega - x[1]],
+        a: [[m[0][0] - 1.0, m[0][1], dp.0], [m[1][0], m[1][1] - 1.0, dp.1]],
+        monodromy: m,
+    }
+}
+
+fn dot(a: &Vec3, b: &Vec3) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+// 2×3 矩阵的零空间方向（两行的叉积），归一化；与 reference 同向
+fn tangent(a: &[Vec3; 2], reference: &Vec3) -> Vec3 {
+    let (u, v) = (&a[0], &a[1]);
+    let t = [u[1] * v[2] - u[2] * v[1], u[2] * v[0] - u[0] * v[2], u[0] * v[1] - u[1] * v[0]];
+    let norm = dot(&t, &t).sqrt();
+    let sign = if dot(&t, reference) < 0.0 { -1.0 } else { 1.0 };
+    t.map(|c| sign * c / norm)
+}
+
+// 列主元 Gauss 消元解 3×3 线性方程组，矩阵奇异时返回 None
+fn solve3(mut m: [Vec3; 3], mut b: Vec3) -> Option<Vec3> {
+    for col in 0..3 {
+        let pivot = (col..3).max_by(|&i, &j| m[i][col].abs().total_cmp(&m[j][col].abs()))?;
+        if m[pivot][col] == 0.0 || !m[pivot][col].is_finite() {
+            return None;
+        }
+        m.swap(col, pivot);
+        b.swap(col, pivot);
+        let pivot_row = m[col];
+        for row in col + 1..3 {
+            let factor = m[row][col] / pivot_row[col];
+            for (a, p) in m[row].iter_mut().zip(&pivot_row).skip(col) {
+                *a -= factor * p;
+            }
+            b[row] -= factor * b[col];
+        }
+    }
+    let mut x = [0.0; 3];
+    for row in (0..3).rev() {
+        let sum: f64 = (row + 1..3).map(|k| m[row][k] * x[k]).sum();
+        x[row] = (b[row] - sum) / m[row][row];
+    }
+    Some(x)
+}
+
+// 在超平面 T·(X - X_pred) = 0 上的 Newton 校正，返回 (X, 该处的求值, 迭代次数)
+fn correct(
+    params: &PendulumParams,
+    predicted: Vec3,
+    t: &Vec3,
+    period: usize,
+    winding: i64,
+    options: &ContinuationOptions,
+) -> Option<(Vec3, Evaluation, usize)> {
+    let mut x = predicted;
+    for iteration in 0..=options.newton.max_iterations {
+        let e = evaluate(params, &x, period, winding, options);
+        let offset = [x[0] - predicted[0], x[1] - predicted[1], x[2] - predicted[2]];
+        let r = [e.g[0], e.g[1], dot(t, &offset)];
+        if dot(&r, &r).sqrt() < options.newton.tolerance {
+            return Some((x, e, iteration));
+        }
+        if iteration == options.newton.max_iterations {
+            break;
+        }
+        let delta = solve3([e.a[0], e.a[1], *t], r.map(|c| -c))?;
+        for (xi, d) in x.iter_mut().zip(&delta) {
+            *xi += d;
+        }
+    }
+    None
+}
+
+fn point(x: &Vec3, e: &Evaluation) -> ContinuationPoint {
+    let multipliers = eigenvalues(&e.monodromy);
+    ContinuationPoint {
+        f_d: x[2],
+        theta: wrap_angle(x[0]),
+        omega: x[1],
+        multipliers,
+        stable: multipliers[0].0.hypot(multipliers[0].1) < 1.0,
+    }
+}
+
+/// 从已求得的周期轨道（在 params.f_d 处，例如 find_periodic_orbit 的结果）出发，沿 f_d 做伪弧长延拓
+///
+/// 步长按校正的难易自适应：校正失败时减半，直到低于 min_step 为止；3 次迭代内收敛时放大 1.5 倍。
+/// 返回的分支按延拓顺序排列，f_d 在折点处可能回头。积分设置与 find_periodic_orbit 相同（RK4，不看 params.integrator）。
+pub fn continue_orbit(
+    params: &PendulumParams,
+    orbit: &PeriodicOrbit,
+    options: &ContinuationOptions,
+) -> Result<Branch, ChaosError> {
+    check_orbit_params(params, orbit.period)?;
+    let p = orbit_params(params);
+    let (period, winding) = (orbit.period, orbit.winding);
+
+    let mut x = [orbit.points[0].0, orbit.points[0].1, params.f_d];
+    let mut e = evaluate(&p, &x, period, winding, options);
+    let mut t = tangent(&e.a, &[0.0, 0.0, options.step.signum()]);
+    let mut points = vec![point(&x, &e)];
+    let mut bifurcations = Vec::new();
+    let mut ds = options.step.abs();
+
+    while points.len() < options.max_points {
+        let predicted = [x[0] + ds * t[0], x[1] + ds * t[1], x[2] + ds * t[2]];
+        let Some((x_new, e_new, iterations)) = correct(&p, predicted, &t, period, winding, options) else {
+            ds *= 0.5;
+            if ds < options.min_step {
+                break;
+            }
+            continue;
+        };
+        let t_new = tangent(&e_new.a, &t);
+
+        let (pd_old, sn_old) = e.test_functions();
+        let (pd_new, sn_new) = e_new.test_functions();
+        let mut crossings = Vec::new();
+        if pd_old * pd_new < 0.0 {
+            crossings.push((BifurcationKind::PeriodDoubling, pd_old / (pd_old - pd_new)));
+        }
+        if sn_old * sn_new < 0.0 {
+            let kind = if t[2] * t_new[2] < 0.0 {
+                BifurcationKind::SaddleNode
+            } else {
+                BifurcationKind::Pitchfork
+            };
+            crossings.push((kind, sn_old / (sn_old - sn_new)));
+        }
+        for (kind, s) in crossings {
+            let lerp = |i: usize| x[i] + s * (x_new[i] - x[i]);
+            bifurcations.push(DetectedBifurcation {
+                kind,
+                index: points.len(),
+                f_d: lerp(2),
+                theta: wrap_angle(lerp(0)),
+                omega: lerp(1),
+            });
+        }
+
+        points.push(point(&x_new, &e_new));
+        x = x_new;
+        e = e_new;
+        t = t_new;
+        if iterations <= 3 {
+            ds = (ds * 1.5).min(options.max_step);
+        }
+        if x[2] < options.f_d_min || x[2] > options.f_d_max {
+            break;
+        }
+    }
+    Ok(Branch {
+        period,
+        winding,
+        points,
+        bifurcations,
+    })
+}
+
+/// 把分支写成 CSV（列：f_d,theta,omega,multiplier1_re,multiplier1_im,multiplier2_re,multiplier2_im,stable,bifurcation），
+/// bifurcation 列在分岔之后的第一个点上记分岔类型，其余为空
+pub fn write_branch_csv(path: &str, branch: &Branch) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(
+        f,
+        "f_d,theta,omega,multiplier1_re,multiplier1_im,multiplier2_re,multiplier2_im,stable,bifurcation"
+    )?;
+    for (i, p) in branch.points.iter().enumerate() {
+        let [(r1, i1), (r2, i2)] = p.multipliers;
+        let kinds: Vec<&str> = branch.bifurcations.iter().filter(|b| b.index == i).map(|b| b.kind.name()).collect();
+        writeln!(
+            f,
+            "{:.12},{:.12},{:.12},{:.12},{:.12},{:.12},{:.12},{},{}",
+            p.f_d,
+            p.theta,
+            p.omega,
+            r1,
+            i1,
+            r2,
+            i2,
+            p.stable,
+            kinds.join(";")
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::periodic_orbits::find_periodic_orbit;
+
+    #[test]
+    fn test_continuation_detects_symmetry_breaking() {
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.q = 0.5;
+        params.omega_d = 2.0 / 3.0;
+        params.f_d = 0.95;
+        params.set_steps_per_period(100);
+        let orbit = find_periodic_orbit(&params, -2.5, -0.4, 1, &NewtonOptions::default()).unwrap();
+        assert!(orbit.is_stable());
+
+        // 对称的周期 1 振动在 f_d ≈ 1.0 处失稳（乘子穿过 +1），分支继续存在但不稳定
+        let options = ContinuationOptions {
+            step: 0.02,
+            f_d_max: 1.06,
+            ..ContinuationOptions::default()
+        };
+        let branch = continue_orbit(&params, &orbit, &options).unwrap();
+        assert!(branch.points.first().unwrap().stable);
+        assert!(!branch.points.last().unwrap().stable);
+        assert_eq!(branch.bifurcations.len(), 1);
+        let b = branch.bifurcations[0];
+        assert_eq!(b.kind, BifurcationKind::Pitchfork);
+        assert!(b.f_d > 0.97 && b.f_d < 1.03, "{}", b.f_d);
+        assert!(branch.points[b.index - 1].stable && !branch.points[b.index].stable);
+    }
+}
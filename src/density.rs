@@ -0,0 +1,281 @@
+//density.rs
+use std::f64::consts::PI;
+use std::fs::File;
+use std::io::Write;
+use crate::basin::write_rgb_png;
+use crate::model::PendulumParams;
+use crate::solve_equation::{rk4_step, steps_per_period, wrap_angle, State};
+
+/// (θ, ω) 平面上的二维直方图，θ 覆盖 [-π, π)，ω 覆盖 omega_range
+#[derive(Debug, Clone)]
+pub struct Histogram2d {
+    pub theta_bins: usize,
+    pub omega_bins: usize,
+    pub omega_range: (f64, f64),
+    /// 按 θ 为外层、ω 为内层排列
+    pub counts: Vec<usize>,
+    /// 落在 ω 范围之外而未计入的点数
+    pub outside: usize,
+}
+
+impl Histogram2d {
+    pub fn new(theta_bins: usize, omega_bins: usize, omega_range: (f64, f64)) -> Self {
+        Histogram2d {
+            theta_bins,
+            omega_bins,
+            omega_range,
+            counts: vec![0; theta_bins * omega_bins],
+            outside: 0,
+        }
+    }
+
+    /// 点所在格子在 counts 中的下标，θ 自动折叠；ω 越界时为 None
+    pub fn bin_index(&self, theta: f64, omega: f64) -> Option<usize> {
+        let (lo, hi) = self.omega_range;
+        if !(lo..hi).contains(&omega) {
+            return None;
+        }
+        let u = (wrap_angle(theta) + PI) / (2.0 * PI);
+        let i = ((u * self.theta_bins as f64) as usize).min(self.theta_bins - 1);
+        let j = (((omega - lo) / (hi - lo) * self.omega_bins as f64) as usize).min(self.omega_bins - 1);
+        Some(i * self.omega_bins + j)
+    }
+
+    /// 计入一个点，θ 自动折叠
+    pub fn add(&mut self, theta: f64, omega: f64) {
+        match self.bin_index(theta, omega) {
+            Some(k) => self.counts[k] += 1,
+            None => self.outside += 1,
+        }
+    }
+
+    pub fn count(&self, i_theta: usize, i_omega: usize) -> usize {
+        self.counts[i_theta * self.omega_bins + i_omega]
+    }
+
+    /// 已计入的点数（不含越界点）
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    /// 第 (i, j) 个格子中心的 (theta, omega)
+    pub fn bin_center(&self, i_theta: usize, i_omega: usize) -> (f64, f64) {
+        let (lo, hi) = self.omega_range;
+        let theta = -PI + (i_theta as f64 + 0.5) * 2.0 * PI / self.theta_bins as f64;
+        let omega = lo + (i_omega as f64 + 0.5) * (hi - lo) / self.omega_bins as f64;
+        (theta, omega)
+    }
+
+    /// 格子概率 p_k = count / total 的 Shannon 熵 -Σ p ln p（nats），即不变测度在这个划分下的熵
+    pub fn entropy(&self) -> f64 {
+        let total = self.total();
+        if total == 0 {
+            return 0.0;
+        }
+        self.counts
+            .iter()
+            .filter(|&&c| c > 0)
+            .map(|&c| {
+                let p = c as f64 / total as f64;
+                -p * p.ln()
+            })
+            .sum()
+    }
+
+    /// 第 (i, j) 个格子的概率密度 count / (total · 格子面积)，在 (θ, ω) 平面上积分为 1（不含越界点）
+    pub fn density(&self, i_theta: usize, i_omega: usize) -> f64 {
+        let total = self.total();
+        if total == 0 {
+            return 0.0;
+        }
+        let (lo, hi) = self.omega_range;
+        let area = 2.0 * PI / self.theta_bins as f64 * (hi - lo) / self.omega_bins as f64;
+        self.count(i_theta, i_omega) as f64 / (total as f64 * area)
+    }
+}
+
+/// 把庞加莱点装箱成 (θ, ω) 直方图，近似吸引子上的不变测度
+///
+/// θ 自动折叠到 [-π, π)；omega_range 为 None 时取样本 ω 的最小/最大值（上端略放宽，使最大值也落在范围内）。
+/// 10^6 个点的散点图会饱和，密度图才能看出测度在吸引子上的分布。
+pub fn poincare_density(
+    samples: &[(f64, f64)],
+    theta_bins: usize,
+    omega_bins: usize,
+    omega_range: Option<(f64, f64)>,
+) -> Histogram2d {
+    let range = omega_range.unwrap_or_else(|| {
+        let lo = samples.iter().map(|s| s.1).fold(f64::INFINITY, f64::min);
+        let hi = samples.iter().map(|s| s.1).fold(f64::NEG_INFINITY, f64::max);
+        if lo.is_finite() && hi > lo {
+            (lo, hi + 1e-9 * (hi - lo))
+        } else if lo.is_finite() {
+            (lo - 0.5, lo + 0.5)
+        } else {
+            (-1.0, 1.0)
+        }
+    });
+    let mut h = Histogram2d::new(theta_bins, omega_bins, range);
+    for &(theta, omega) in samples {
+        h.add(theta, omega);
+    }
+    h
+}
+
+/// 把直方图写成 CSV 矩阵：首行为 "omega\theta" 与各列 θ 格子中心，之后每行是一个 ω 格子（自上而下 ω 减小，与图片方向一致），值为 density()
+pub fn write_density_matrix_csv(path: &str, h: &Histogram2d) -> std::io::Result<()> {
+    let mut f = std::io::BufWriter::new(File::create(path)?);
+    write!(f, "omega\\theta")?;
+    for i in 0..h.theta_bins {
+        write!(f, ",{:.12}", h.bin_center(i, 0).0)?;
+    }
+    writeln!(f)?;
+    for j in (0..h.omega_bins).rev() {
+        write!(f, "{:.12}", h.bin_center(0, j).1)?;
+        for i in 0..h.theta_bins {
+            write!(f, ",{:.12}", h.density(i, j))?;
+        }
+        writeln!(f)?;
+    }
+    Ok(())
+}
+
+// 对数色标：空格子为白色，计数越大越接近深蓝，按 ln(1 + count) / ln(1 + max) 归一化
+fn density_color(count: usize, max: usize) -> [u8; 3] {
+    if count == 0 || max == 0 {
+        return [255, 255, 255];
+    }
+    let x = (count as f64).ln_1p() / (max as f64).ln_1p();
+    let mix = |from: f64, to: f64| (from + (to - from) * x).round() as u8;
+    [mix(200.0, 8.0), mix(220.0, 29.0), mix(255.0, 88.0)]
+}
+
+/// 把直方图写成 PNG 密度图：横轴 θ（向右增大），纵轴 ω（向上增大），每个格子一个像素，对数色标
+pub fn write_density_png(path: &str, h: &Histogram2d) -> std::io::Result<()> {
+    let max = h.counts.iter().copied().max().unwrap_or(0);
+    let mut data = Vec::with_capacity(h.theta_bins * h.omega_bins * 3);
+    for j in (0..h.omega_bins).rev() {
+        for i in 0..h.theta_bins {
+            data.extend_from_slice(&density_color(h.count(i, j), max));
+        }
+    }
+    write_rgb_png(path, h.theta_bins, h.omega_bins, &data)
+}
+
+/// 以 params 的 θ/ω 网格作为初值云
+pub fn cloud_from_grid(params: &PendulumParams) -> Vec<State> {
+    let omegas = params.omega_grid();
+    params
+        .theta_grid()
+        .into_iter()
+        .flat_map(|theta| omegas.iter().map(move |&omega| State { theta, omega }))
+        .collect()
+}
+
+/// 演化一团初值，在每个驱动周期末对 (θ, ω) 分布做一次直方图快照
+///
+/// 返回 periods + 1 个快照，第 0 个为初始分布；可逐帧播放以观察相空间体积的拉伸与折叠。
+pub fn density_snapshots(
+    params: &PendulumParams,
+    cloud: &[State],
+    periods: usize,
+    theta_bins: usize,
+    omega_bins: usize,
+    omega_range: (f64, f64),
+) -> Vec<Histogram2d> {
+    let spp = steps_per_period(params);
+    let period = 2.0 * PI / params.omega_d;
+    let mut p = params.clone();
+    p.dt = period / spp as f64;
+
+    let snapshot = |states: &[State]| {
+        let mut h = Histogram2d::new(theta_bins, omega_bins, omega_range);
+        for s in states {
+            h.add(s.theta, s.omega);
+        }
+        h
+    };
+
+    let mut states = cloud.to_vec();
+    let mut out = Vec::with_capacity(periods + 1);
+    out.push(snapshot(&states));
+    for n in 0..periods {
+        for s in states.iter_mut() {
+            for k in 0..spp {
+                let t = n as f64 * period + k as f64 * p.dt;
+                *s = rk4_step(s, t, &p).0;
+            }
+        }
+        out.push(snapshot(&states));
+    }
+    out
+}
+
+/// 把密度快照写成 CSV（四列：period,theta,omega,count），只写非零格子，坐标为格子中心
+pub fn write_density_snapshots_csv(path: &str, snapshots: &[Histogram2d]) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(f, "period,theta,omega,count")?;
+    for (n, h) in snapshots.iter().enumerate() {
+        for i in 0..h.theta_bins {
+            for j in 0..h.omega_bins {
+                let c = h.count(i, j);
+                if c > 0 {
+                    let (theta, omega) = h.bin_center(i, j);
+                    writeln!(f, "{},{:.12},{:.12},{}", n, theta, omega, c)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_density_snapshots_conserve_particles() {
+        let mut params = PendulumParams::new();
+        params.q = 0.5;
+        params.f_d = 1.2;
+        params.omega_d = 2.0 / 3.0;
+        params.dt = 2.0 * PI / params.omega_d / 50.0;
+        params.theta_start = -0.5;
+        params.theta_end = 0.5;
+        params.d_theta = 0.25;
+        params.omega_start = -0.5;
+        params.omega_end = 0.5;
+        params.d_omega = 0.25;
+
+        let cloud = cloud_from_grid(&params);
+        assert_eq!(cloud.len(), 25);
+        let snapshots = density_snapshots(&params, &cloud, 3, 16, 16, (-10.0, 10.0));
+        assert_eq!(snapshots.len(), 4);
+        assert!(snapshots.iter().all(|h| h.total() == 25 && h.outside == 0));
+    }
+
+    #[test]
+    fn test_poincare_density_normalization_and_output() {
+        let samples: Vec<(f64, f64)> = (0..10_000)
+            .map(|k| (k as f64 * 0.618_034 * 2.0 * PI, (k as f64 * 0.414_213).fract() * 2.0 - 1.0))
+            .collect();
+        let h = poincare_density(&samples, 40, 20, None);
+        assert_eq!(h.outside, 0);
+        assert_eq!(h.total(), samples.len());
+        let (lo, hi) = h.omega_range;
+        let area = 2.0 * PI / 40.0 * (hi - lo) / 20.0;
+        let integral: f64 = (0..40).flat_map(|i| (0..20).map(move |j| (i, j))).map(|(i, j)| h.density(i, j) * area).sum();
+        assert!((integral - 1.0).abs() < 1e-9);
+
+        let dir = std::env::temp_dir();
+        let csv = dir.join("chaos_density_matrix_test.csv");
+        write_density_matrix_csv(csv.to_str().unwrap(), &h).unwrap();
+        let text = std::fs::read_to_string(&csv).unwrap();
+        assert_eq!(text.lines().count(), 21);
+        assert!(text.starts_with("omega\\theta,"));
+        assert_eq!(text.lines().nth(1).unwrap().split(',').count(), 41);
+        let png = dir.join("chaos_density_test.png");
+        write_density_png(png.to_str().unwrap(), &h).unwrap();
+        assert!(std::fs::metadata(&png).unwrap().len() > 0);
+    }
+}
@@ -0,0 +1,107 @@
+//diagnostics.rs
+use std::fs::File;
+use std::io::Write;
+use crate::model::PendulumParams;
+use crate::solve_equation::State;
+
+// 能量按单位 m·l² 计：动能 ω²/2，势能 (g/l)(1 - cos θ)（与 separatrix::pendulum_energy 一致）。
+// 能量方程 dE/dt = F(t)·ω - q ω²（F 为 params.drive，默认 f_d sin(Ωt)），因此
+//   E(t) - E(0) = W_drive(t) - W_damping(t)
+// 沿轨迹用梯形公式累积两项功，二者之差与实际能量变化的偏差即为数值误差的监测量。
+
+/// 轨迹上某一时刻的能量记录
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EnergyRecord {
+    pub t: f64,
+    pub kinetic: f64,
+    pub potential: f64,
+    pub total: f64,
+    /// 从起点累积的驱动力做功
+    pub drive_work: f64,
+    /// 从起点累积的阻尼耗散（非负）
+    pub damping_loss: f64,
+    /// 能量收支残差 E(t) - E(0) - W_drive + W_damping，理想情况下为 0
+    pub balance_error: f64,
+}
+
+fn drive_power(t: f64, s: &State, params: &PendulumParams) -> f64 {
+    params.drive_torque(s.theta, t) * s.omega
+}
+
+fn damping_power(s: &State, params: &PendulumParams) -> f64 {
+    params.q * s.omega * s.omega
+}
+
+/// 逐点计算能量与累积功，返回与轨迹等长的记录
+pub fn energy_diagnostics(traj: &[(f64, State)], params: &PendulumParams) -> Vec<EnergyRecord> {
+    let Some((_, first)) = traj.first() else {
+        return Vec::new();
+    };
+    let e0 = 0.5 * first.omega * first.omega + (params.g / params.l) * (1.0 - first.theta.cos());
+    let mut drive_work = 0.0;
+    let mut damping_loss = 0.0;
+    let mut records = Vec::with_capacity(traj.len());
+    for (i, (t, s)) in traj.iter().enumerate() {
+        if i > 0 {
+            let (tp, sp) = &traj[i - 1];
+            let h = t - tp;
+            drive_work += 0.5 * h * (drive_power(*tp, sp, params) + drive_power(*t, s, params));
+            damping_loss += 0.5 * h * (damping_power(sp, params) + damping_power(s, params));
+        }
+        let kinetic = 0.5 * s.omega * s.omega;
+        let potential = (params.g / params.l) * (1.0 - s.theta.cos());
+        let total = kinetic + potential;
+        records.push(EnergyRecord {
+            t: *t,
+            kinetic,
+            potential,
+            total,
+            drive_work,
+            damping_loss,
+            balance_error: total - e0 - drive_work + damping_loss,
+        });
+    }
+    records
+}
+
+/// 能量收支残差绝对值的最大值，可作为积分精度的单一指标
+pub fn max_balance_error(records: &[EnergyRecord]) -> f64 {
+    records.iter().map(|r| r.balance_error.abs()).fold(0.0, f64::max)
+}
+
+/// 把能量记录写成 CSV（列：t,kinetic,potential,total,drive_work,damping_loss,balance_error）
+pub fn write_energy_csv(path: &str, records: &[EnergyRecord]) -> std::io::Result<()> {
+    let mut f = std::io::BufWriter::new(File::create(path)?);
+    writeln!(f, "t,kinetic,potential,total,drive_work,damping_loss,balance_error")?;
+    for r in records {
+        writeln!(
+            f,
+            "{:.12},{:.12},{:.12},{:.12},{:.12},{:.12},{:.12}",
+            r.t, r.kinetic, r.potential, r.total, r.drive_work, r.damping_loss, r.balance_error
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solve_equation::solve;
+
+    #[test]
+    fn test_energy_balance_closes_on_chaotic_trajectory() {
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.q = 0.5;
+        params.f_d = 1.2;
+        params.omega_d = 2.0 / 3.0;
+        params.dt = 0.01;
+        params.t_end = 200.0;
+
+        let records = energy_diagnostics(&solve(&params, 0.2, 0.0).unwrap(), &params);
+        let last = records.last().unwrap();
+        assert!(last.drive_work > 1.0 && last.damping_loss > 1.0);
+        // 梯形公式的误差为 O(dt²)，远小于两项功本身
+        assert!(max_balance_error(&records) < 1e-3 * last.damping_loss);
+    }
+}
@@ -0,0 +1,183 @@
+//diffusion.rs
+use std::f64::consts::PI;
+use std::fs::File;
+use std::io::Write;
+use crate::analysis::least_squares;
+use crate::ensemble::EnsembleRun;
+use crate::error::ChaosError;
+use crate::model::PendulumParams;
+use crate::solve_equation::{steps_per_period, TrajectoryIter};
+
+// 转动混沌区的角度扩散：混沌转动的摆时而正转时而反转，展开角 θ（lift，不折叠）做确定性的随机游走，
+// 均方位移 MSD(t) = <(θ(t₀ + t) - θ(t₀))²> 在长时间上随 t 线性增长，MSD ≈ 2 D t，D 为角度扩散系数。
+// 有净转动（同步转动解、偏置的混沌）时位移还带漂移 v t，因此同时给出扣除均值后的方差 Var(t) ≈ 2 D t，
+// 扩散系数由方差拟合，漂移速度由平均位移拟合。摆动解与周期转动的方差有界，D 趋于 0。
+// 两种平均方式：
+//   ensemble_msd      随机初值系综在同一时刻 t₀（过渡期末）起算，对成员平均
+//   time_window_msd   单条长轨道，对所有起点 t₀（按驱动周期）平均
+// 两者都只在频闪时刻取样，时间单位为驱动周期 T 换算后的 t。
+
+/// 某一时间间隔上的位移统计
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MsdPoint {
+    /// 时间间隔
+    pub t: f64,
+    /// 均方位移 <Δθ²>
+    pub msd: f64,
+    /// 平均位移 <Δθ>
+    pub mean_displacement: f64,
+    /// 位移方差 <Δθ²> - <Δθ>²
+    pub variance: f64,
+    /// 参与平均的位移个数
+    pub count: usize,
+}
+
+/// 扩散拟合结果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DiffusionFit {
+    /// 角度扩散系数 D（Var ≈ 2 D t 的斜率的一半），单位 rad²/时间
+    pub coefficient: f64,
+    /// 平均角速度（漂移），<Δθ> ≈ v t 的斜率
+    pub drift: f64,
+    /// 方差拟合直线的截距
+    pub intercept: f64,
+    /// 参与拟合的点数
+    pub points: usize,
+}
+
+fn msd_point(t: f64, displacements: impl Iterator<Item = f64>) -> MsdPoint {
+    let (mut n, mut sum, mut sum_sq) = (0usize, 0.0, 0.0);
+    for d in displacements {
+        n += 1;
+        sum += d;
+        sum_sq += d * d;
+    }
+    let count = n.max(1) as f64;
+    let (msd, mean) = (sum_sq / count, sum / count);
+    MsdPoint {
+        t,
+        msd,
+        mean_displacement: mean,
+        variance: (msd - mean * mean).max(0.0),
+        count: n,
+    }
+}
+
+/// 系综 MSD：从第 transient_periods 个频闪时刻起算，每个后续周期一个点（第一个点 t = 0）
+///
+/// 只统计在该时刻仍有状态的成员；run 的 θ 不折叠，正是需要的 lift。
+pub fn ensemble_msd(run: &EnsembleRun, transient_periods: usize) -> Vec<MsdPoint> {
+    let Some(start) = run.snapshots.get(transient_periods) else {
+        return Vec::new();
+    };
+    run.snapshots[transient_periods..]
+        .iter()
+        .enumerate()
+        .map(|(k, states)| {
+            let displacements = states.iter().zip(start).map(|(s, s0)| s.theta - s0.theta);
+            msd_point(k as f64 * run.period, displacements)
+        })
+        .collect()
+}
+
+/// 单条轨道的时间平均 MSD：thetas 为按驱动周期等间隔的展开角，lag = 0..=max_lag
+///
+/// 每个 lag 对所有起点平均；lag 接近序列长度时参与平均的位移很少，max_lag 一般取长度的 1/10 左右。
+pub fn time_window_msd(thetas: &[f64], period: f64, max_lag: usize) -> Vec<MsdPoint> {
+    (0..=max_lag.min(thetas.len().saturating_sub(1)))
+        .map(|lag| {
+            let displacements = thetas.windows(lag + 1).map(|w| w[lag] - w[0]);
+            msd_point(lag as f64 * period, displacements)
+        })
+        .collect()
+}
+
+/// 从 (theta0, omega0) 积分，丢弃 transient_periods 个周期后返回 periods + 1 个频闪时刻的展开角
+pub fn stroboscopic_lift(
+    params: &PendulumParams,
+    theta0: f64,
+    omega0: f64,
+    transient_periods: usize,
+    periods: usize,
+) -> Result<Vec<f64>, ChaosError> {
+    params.validate_periodic()?;
+    let spp = steps_per_period(params);
+    let mut p = params.clone();
+    p.set_steps_per_period(spp);
+    p.t_end = (transient_periods + periods) as f64 * 2.0 * PI / p.omega_d + 0.5 * p.dt;
+    p.exact_stroboscopic = false;
+    Ok(TrajectoryIter::new(&p, theta0, omega0)
+        .step_by(spp)
+        .skip(transient_periods)
+        .map(|(_, s)| s.theta)
+        .collect())
+}
+
+/// 在 t ∈ [t_min, t_max] 的点上拟合 Var ≈ 2 D t + b 与 <Δθ> ≈ v t；点数少于两个时为 None
+///
+/// 短时间上位移是弹道式的（Var ∝ t²），t_min 应跳过这一段，一般取几十个驱动周期。
+pub fn fit_diffusion(points: &[MsdPoint], t_min: f64, t_max: f64) -> Option<DiffusionFit> {
+    let window: Vec<&MsdPoint> = points.iter().filter(|p| (t_min..=t_max).contains(&p.t)).collect();
+    let variance: Vec<(f64, f64)> = window.iter().map(|p| (p.t, p.variance)).collect();
+    let mean: Vec<(f64, f64)> = window.iter().map(|p| (p.t, p.mean_displacement)).collect();
+    let (slope, intercept) = least_squares(&variance)?;
+    let (drift, _) = least_squares(&mean)?;
+    Some(DiffusionFit {
+        coefficient: slope / 2.0,
+        drift,
+        intercept,
+        points: window.len(),
+    })
+}
+
+/// 把 MSD(t) 写成 CSV（列：t,msd,mean_displacement,variance,count）
+pub fn write_msd_csv(path: &str, points: &[MsdPoint]) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(f, "t,msd,mean_displacement,variance,count")?;
+    for p in points {
+        writeln!(f, "{:.12},{:.12},{:.12},{:.12},{}", p.t, p.msd, p.mean_displacement, p.variance, p.count)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ensemble::run;
+    use crate::model::InitialConditionSampling;
+
+    #[test]
+    fn test_chaotic_rotation_diffuses_and_oscillation_does_not() {
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.q = 0.5;
+        params.f_d = 1.2;
+        params.omega_d = 2.0 / 3.0;
+        params.set_steps_per_period(100);
+        let period = 2.0 * PI / params.omega_d;
+        params.t_end = 250.0 * period;
+        let box_ics = InitialConditionSampling::Box;
+        params.theta_start = -1.0;
+        params.theta_end = 1.0;
+        params.omega_start = -1.0;
+        params.omega_end = 1.0;
+
+        let chaotic = ensemble_msd(&run(&params, 64, 7, box_ics), 50);
+        assert_eq!(chaotic.len(), 201);
+        assert_eq!(chaotic[0].msd, 0.0);
+        let fit = fit_diffusion(&chaotic, 20.0 * period, 200.0 * period).unwrap();
+        assert!(fit.coefficient > 0.5, "{:?}", fit);
+
+        // 单条长轨道的时间平均给出同一量级的 D
+        let thetas = stroboscopic_lift(&params, 0.2, 0.0, 50, 2000).unwrap();
+        assert_eq!(thetas.len(), 2001);
+        let single = fit_diffusion(&time_window_msd(&thetas, period, 200), 20.0 * period, 200.0 * period).unwrap();
+        assert!(single.coefficient > fit.coefficient / 4.0 && single.coefficient < fit.coefficient * 4.0, "{:?} {:?}", single, fit);
+
+        // 周期摆动：位移有界，不扩散
+        params.f_d = 0.5;
+        let bounded = ensemble_msd(&run(&params, 16, 7, box_ics), 50);
+        let fit = fit_diffusion(&bounded, 20.0 * period, 200.0 * period).unwrap();
+        assert!(fit.coefficient.abs() < 1e-6 && fit.drift.abs() < 1e-6, "{:?}", fit);
+    }
+}
@@ -0,0 +1,159 @@
+//double_pendulum.rs
+use std::fs::File;
+use std::io::Write;
+use rayon::prelude::*;
+use crate::model::DoublePendulumParams;
+use crate::ode::Rk4Stepper;
+
+// 双摆没有驱动与阻尼，能量守恒；混沌诊断主要看能量漂移（检验积分精度）
+// 与“翻转时间”：外摆（或内摆）第一次越过竖直向上位置所需的时间。
+// 翻转时间关于初值的分布呈分形结构，是双摆混沌最直观的图像。
+
+/// 双摆状态 [θ1, ω1, θ2, ω2]
+pub type DoubleState = [f64; 4];
+
+/// 从 initial 积分到 params.t_end，返回 (t, 状态) 序列（含初始点）
+///
+/// 步进用 ode 模块的通用 RK4（DoublePendulumParams 实现了 OdeSystem）。
+pub fn solve_double(params: &DoublePendulumParams, initial: DoubleState) -> Vec<(f64, DoubleState)> {
+    let steps = (params.t_end / params.dt) as usize;
+    let mut stepper = Rk4Stepper::new(4);
+    let mut traj = Vec::with_capacity(steps + 1);
+    let (mut y, mut t) = (initial, 0.0);
+    traj.push((t, y));
+    for _ in 0..steps {
+        stepper.step(params, t, &mut y, params.dt);
+        t += params.dt;
+        traj.push((t, y));
+    }
+    traj
+}
+
+/// 沿轨迹的最大相对能量误差 max |E(t) - E(0)| / |E(0)|（E(0) = 0 时用绝对误差）
+pub fn energy_drift(traj: &[(f64, DoubleState)], params: &DoublePendulumParams) -> f64 {
+    let Some((_, first)) = traj.first() else {
+        return 0.0;
+    };
+    let e0 = params.energy(first);
+    let scale = if e0 == 0.0 { 1.0 } else { e0.abs() };
+    traj.iter()
+        .map(|(_, y)| (params.energy(y) - e0).abs() / scale)
+        .fold(0.0, f64::max)
+}
+
+/// 能量判据：外摆越过竖直向上位置至少需要的能量是否足够
+///
+/// 外摆竖直向上、内摆竖直向下且静止时势能最低，为 -(m1+m2)·g·l1 + m2·g·l2；
+/// 总能量低于它时外摆永远不会翻转，无需积分。
+pub fn can_flip(params: &DoublePendulumParams, y: &DoubleState) -> bool {
+    let threshold = -(params.m1 + params.m2) * params.g * params.l1 + params.m2 * params.g * params.l2;
+    params.energy(y) >= threshold
+}
+
+/// 外摆第一次翻转（|θ2| 越过 π）的时间；到 params.t_end 仍未翻转或能量不允许翻转时返回 None
+pub fn flip_time(params: &DoublePendulumParams, initial: DoubleState) -> Option<f64> {
+    if !can_flip(params, &initial) {
+        return None;
+    }
+    let steps = (params.t_end / params.dt) as usize;
+    let mut stepper = Rk4Stepper::new(4);
+    let (mut y, mut t) = (initial, 0.0);
+    for _ in 0..steps {
+        stepper.step(params, t, &mut y, params.dt);
+        t += params.dt;
+        if y[2].abs() > std::f64::consts::PI {
+            return Some(t);
+        }
+    }
+    None
+}
+
+/// 翻转时间图：两摆都从静止开始，times 按 θ1 为外层、θ2 为内层排列
+#[derive(Debug, Clone)]
+pub struct FlipTimeMap {
+    pub thetas1: Vec<f64>,
+    pub thetas2: Vec<f64>,
+    pub times: Vec<Option<f64>>,
+}
+
+impl FlipTimeMap {
+    pub fn time_at(&self, i1: usize, i2: usize) -> Option<f64> {
+        self.times[i1 * self.thetas2.len() + i2]
+    }
+}
+
+/// 在 (θ1, θ2) 网格上并行计算翻转时间
+pub fn flip_time_map(params: &DoublePendulumParams, thetas1: &[f64], thetas2: &[f64]) -> FlipTimeMap {
+    let cells: Vec<(f64, f64)> = thetas1
+        .iter()
+        .flat_map(|&a| thetas2.iter().map(move |&b| (a, b)))
+        .collect();
+    let times = cells
+        .par_iter()
+        .map(|&(theta1, theta2)| flip_time(params, [theta1, 0.0, theta2, 0.0]))
+        .collect();
+    FlipTimeMap {
+        thetas1: thetas1.to_vec(),
+        thetas2: thetas2.to_vec(),
+        times,
+    }
+}
+
+/// 把翻转时间图写成 CSV（三列：theta1,theta2,flip_time），未翻转的格点 flip_time 留空
+pub fn write_flip_time_csv(path: &str, map: &FlipTimeMap) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(f, "theta1,theta2,flip_time")?;
+    for (i, theta1) in map.thetas1.iter().enumerate() {
+        for (j, theta2) in map.thetas2.iter().enumerate() {
+            match map.time_at(i, j) {
+                Some(t) => writeln!(f, "{:.12},{:.12},{:.12}", theta1, theta2, t)?,
+                None => writeln!(f, "{:.12},{:.12},", theta1, theta2)?,
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 把双摆轨迹写成 CSV（列：t,theta1,omega1,theta2,omega2,energy）
+pub fn write_double_trajectory_csv(
+    path: &str,
+    traj: &[(f64, DoubleState)],
+    params: &DoublePendulumParams,
+) -> std::io::Result<()> {
+    let mut f = std::io::BufWriter::new(File::create(path)?);
+    writeln!(f, "t,theta1,omega1,theta2,omega2,energy")?;
+    for (t, y) in traj {
+        writeln!(
+            f,
+            "{:.12},{:.12},{:.12},{:.12},{:.12},{:.12}",
+            t,
+            y[0],
+            y[1],
+            y[2],
+            y[3],
+            params.energy(y)
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_energy_conserved_and_flip_criterion() {
+        let mut params = DoublePendulumParams::new();
+        params.t_end = 10.0;
+        // 大幅度初值处于混沌区，RK4 仍应很好地守恒能量
+        let traj = solve_double(&params, [2.0, 0.0, 2.5, 0.0]);
+        assert!(energy_drift(&traj, &params) < 1e-6);
+
+        // 小幅度初值能量不足，外摆不可能翻转
+        assert!(!can_flip(&params, &[0.3, 0.0, 0.3, 0.0]));
+        assert_eq!(flip_time(&params, [0.3, 0.0, 0.3, 0.0]), None);
+        // 外摆几乎竖直向上且朝 π 方向转动，很快就会越过 π
+        let t = flip_time(&params, [0.0, 0.0, 3.0, 3.0]).unwrap();
+        assert!(t > 0.0 && t < params.t_end);
+    }
+}
@@ -0,0 +1,120 @@
+//embedding.rs
+use rayon::prelude::*;
+use crate::timeseries::{autocorrelation, average_mutual_information, first_zero_crossing};
+
+pub use crate::timeseries::mutual_information;
+
+// 延迟坐标（Takens）嵌入：只测得一个标量 s(t) 时，用 (s_i, s_{i+τ}, ..., s_{i+(m-1)τ}) 重构吸引子。
+// 延迟 τ 取平均互信息 I(τ) 的第一个极小（坐标之间足够独立又不至于完全无关），
+// 维数 m 取假近邻（Kennel 等 1992）比例降到阈值以下的最小维数。
+// 摆角 θ 在转动时无界增长，作为观测量时宜用 sin θ 或折叠后的 θ。
+
+/// 嵌入结果：选定的延迟、维数与嵌入向量
+#[derive(Debug, Clone)]
+pub struct Embedding {
+    pub lag: usize,
+    pub dim: usize,
+    pub vectors: Vec<Vec<f64>>,
+}
+
+/// 延迟嵌入向量 x_i = (s_i, s_{i+lag}, ..., s_{i+(dim-1)·lag})，共 N - (dim-1)·lag 个
+pub fn delay_embed(series: &[f64], dim: usize, lag: usize) -> Vec<Vec<f64>> {
+    let span = dim.saturating_sub(1) * lag;
+    if dim == 0 || series.len() <= span {
+        return Vec::new();
+    }
+    (0..series.len() - span)
+        .map(|i| (0..dim).map(|k| series[i + k * lag]).collect())
+        .collect()
+}
+
+/// 平均互信息在 lag = 1..=max_lag 上的第一个局部极小；单调下降而没有极小时返回 None
+pub fn first_mi_minimum(series: &[f64], max_lag: usize, bins: usize) -> Option<usize> {
+    let mi = average_mutual_information(series, max_lag + 1, bins);
+    (1..=max_lag).find(|&lag| mi[lag] < mi[lag - 1] && mi[lag] <= mi[lag + 1])
+}
+
+/// 维数 1..=max_dim 下的假近邻比例 (dim, fraction)
+///
+/// 嵌入到 dim 维时点 i 的最近邻 j，若加上第 dim + 1 个坐标后距离增大超过 rtol 倍，
+/// 或新距离超过 atol 倍序列标准差，则记为假近邻（常用 rtol = 15、atol = 2）。最近邻按全部点对直接搜索。
+pub fn false_nearest_neighbors(series: &[f64], lag: usize, max_dim: usize, rtol: f64, atol: f64) -> Vec<(usize, f64)> {
+    let n = series.len() as f64;
+    let mean = series.iter().sum::<f64>() / n;
+    let std = (series.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / n).sqrt();
+    (1..=max_dim)
+        .map(|dim| {
+            // 只用第 dim + 1 个坐标也存在的点
+            let points = delay_embed(series, dim + 1, lag);
+            let falses = points
+                .par_iter()
+                .enumerate()
+                .filter(|(i, p)| {
+                    let nearest = points
+                        .iter()
+                        .enumerate()
+                        .filter(|(j, _)| j != i)
+                        .map(|(j, q)| (j, p[..dim].iter().zip(&q[..dim]).map(|(a, b)| (a - b) * (a - b)).sum::<f64>()))
+                        .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+                    let Some((j, d2)) = nearest else {
+                        return false;
+                    };
+                    let extra = (p[dim] - points[j][dim]).abs();
+                    let r = d2.sqrt();
+                    (r > 0.0 && extra / r > rtol) || (d2 + extra * extra).sqrt() / std > atol
+                })
+                .count();
+            (dim, if points.is_empty() { 0.0 } else { falses as f64 / points.len() as f64 })
+        })
+        .collect()
+}
+
+/// 自动嵌入：延迟取互信息的第一个极小（没有时退回自相关的第一个零点，再没有则取 1），维数取假近邻比例低于 threshold 的最小维数
+///
+/// max_dim 内都达不到阈值时返回 None。
+pub fn auto_embed(series: &[f64], max_lag: usize, max_dim: usize, threshold: f64) -> Option<Embedding> {
+    let lag = first_mi_minimum(series, max_lag, 32)
+        .or_else(|| first_zero_crossing(&autocorrelation(series, max_lag)))
+        .unwrap_or(1);
+    let dim = false_nearest_neighbors(series, lag, max_dim, 15.0, 2.0)
+        .into_iter()
+        .find(|(_, fraction)| *fraction < threshold)?
+        .0;
+    Some(Embedding {
+        lag,
+        dim,
+        vectors: delay_embed(series, dim, lag),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embedding_of_chaotic_pendulum() {
+        let v = delay_embed(&[0.0, 1.0, 2.0, 3.0, 4.0], 3, 2);
+        assert_eq!(v, vec![vec![0.0, 2.0, 4.0]]);
+
+        // 混沌单摆（g/l = 1, q = 0.5, f_d = 1.2, Ω = 2/3）的 ω(t)，每个驱动周期采 20 点：
+        // 互信息的第一个极小约在四分之一周期，假近邻在三维时基本消失（扩展相空间是三维的）
+        let mut params = crate::PendulumParams::new();
+        params.l = params.g;
+        params.q = 0.5;
+        params.f_d = 1.2;
+        params.omega_d = 2.0 / 3.0;
+        params.set_steps_per_period(400);
+        params.t_end = 110.0 * 2.0 * std::f64::consts::PI / params.omega_d;
+        let series: Vec<f64> = crate::solve_equation::TrajectoryIter::new(&params, 0.2, 0.0)
+            .step_by(20)
+            .skip(200)
+            .map(|(_, s)| s.omega)
+            .collect();
+        let e = auto_embed(&series, 40, 4, 0.05).unwrap();
+        assert!((4..=6).contains(&e.lag), "{}", e.lag);
+        assert_eq!(e.dim, 3);
+        assert_eq!(e.vectors.len(), series.len() - 2 * e.lag);
+        let fnn = false_nearest_neighbors(&series, e.lag, 2, 15.0, 2.0);
+        assert!(fnn[0].1 > 0.5, "{:?}", fnn);
+    }
+}
@@ -0,0 +1,413 @@
+//ensemble.rs
+use std::f64::consts::PI;
+use std::fs::File;
+use std::io::Write;
+use rayon::prelude::*;
+use crate::basin::{deduplicate_attractors, BasinMap};
+use crate::bifurcation::{sweep_values, SweepParameter};
+use crate::density::{cloud_from_grid, Histogram2d};
+use crate::model::{InitialConditionSampling, PendulumParams};
+use crate::random::{seeded_rng, RandomSource};
+use crate::separatrix::pendulum_energy;
+use crate::solve_equation::{steps_per_period, wrap_angle, State, TrajectoryIter};
+use crate::stroboscopic::StroboscopicMap;
+
+// 能量壳层拒绝采样的尝试次数上限（相对 ic_count 的倍数）
+const MAX_REJECTION_FACTOR: usize = 10_000;
+
+/// 按 params.ic_sampling 生成系综初值
+///
+/// Grid 直接使用 theta/omega 网格；其余方式生成 ic_count 个随机初值，
+/// 随机数由 ic_seed 播种，同样的配置总是得到同样的初值。
+pub fn initial_conditions(params: &PendulumParams) -> Vec<State> {
+    initial_conditions_with(params, &mut seeded_rng(params.ic_seed))
+}
+
+/// 同 initial_conditions，但使用调用者提供的随机数源（忽略 ic_seed）
+pub fn initial_conditions_with<R: RandomSource>(params: &PendulumParams, rng: &mut R) -> Vec<State> {
+    match params.ic_sampling {
+        InitialConditionSampling::Grid => cloud_from_grid(params),
+        InitialConditionSampling::Box => sample_box(params, rng),
+        InitialConditionSampling::Gaussian {
+            theta,
+            omega,
+            sigma_theta,
+            sigma_omega,
+        } => (0..params.ic_count)
+            .map(|_| State {
+                theta: theta + sigma_theta * rng.standard_normal(),
+                omega: omega + sigma_omega * rng.standard_normal(),
+            })
+            .collect(),
+        InitialConditionSampling::EnergyShell { e_min, e_max } => {
+            sample_energy_shell(params, e_min, e_max, rng)
+        }
+    }
+}
+
+fn sample_box<R: RandomSource>(params: &PendulumParams, rng: &mut R) -> Vec<State> {
+    (0..params.ic_count)
+        .map(|_| State {
+            theta: rng.uniform(params.theta_start, params.theta_end),
+            omega: rng.uniform(params.omega_start, params.omega_end),
+        })
+        .collect()
+}
+
+// 在 θ ∈ [-π, π)、|ω| <= √(2 e_max) 的盒子里均匀撒点并拒绝壳层外的点，
+// 得到的初值在壳层内按相空间面积均匀分布；壳层过薄导致尝试次数用尽时返回的点数少于 ic_count
+fn sample_energy_shell<R: RandomSource>(params: &PendulumParams, e_min: f64, e_max: f64, rng: &mut R) -> Vec<State> {
+    if e_max < e_min || e_max < 0.0 {
+        return Vec::new();
+    }
+    let omega_max = (2.0 * e_max).sqrt();
+    let mut out = Vec::with_capacity(params.ic_count);
+    let mut attempts = 0;
+    while out.len() < params.ic_count && attempts < MAX_REJECTION_FACTOR * params.ic_count {
+        attempts += 1;
+        let theta = rng.uniform(-PI, PI);
+        let omega = rng.uniform(-omega_max, omega_max);
+        let e = pendulum_energy(theta, omega, params);
+        if (e_min..=e_max).contains(&e) {
+            out.push(State { theta, omega });
+        }
+    }
+    out
+}
+
+/// 系综中的一次运行：一组参数加一个初值
+#[derive(Debug, Clone)]
+pub struct EnsembleMember {
+    /// 被扫描参数的取值（初值系综中为 NaN），仅用于输出
+    pub parameter: f64,
+    pub params: PendulumParams,
+    pub initial: State,
+}
+
+/// 参数系综：parameter 在 range 上取 steps + 1 个等距值，初值相同
+pub fn parameter_members(
+    params: &PendulumParams,
+    parameter: SweepParameter,
+    range: (f64, f64),
+    steps: usize,
+    initial: State,
+) -> Vec<EnsembleMember> {
+    sweep_values(range, steps)
+        .into_iter()
+        .map(|value| {
+            let mut p = params.clone();
+            parameter.apply(&mut p, value);
+            EnsembleMember {
+                parameter: value,
+                params: p,
+                initial,
+            }
+        })
+        .collect()
+}
+
+/// 初值系综：参数相同，初值取自 initials（例如 initial_conditions(params) 的结果）
+pub fn initial_condition_members(params: &PendulumParams, initials: &[State]) -> Vec<EnsembleMember> {
+    initials
+        .iter()
+        .map(|s| EnsembleMember {
+            parameter: f64::NAN,
+            params: params.clone(),
+            initial: *s,
+        })
+        .collect()
+}
+
+/// 用 rayon 并行地对每个成员执行 run，结果顺序与 members 一致
+pub fn parallel_map<T, F>(members: &[EnsembleMember], run: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(&EnsembleMember) -> T + Sync,
+{
+    members.par_iter().map(&run).collect()
+}
+
+/// 并行计算每个成员的庞加莱点（整周期积分，过渡期后 sample_periods 个点）
+///
+/// 各成员独立从自己的初值出发，不像 hysteresis_sweep 那样沿参数传递末状态。
+pub fn poincare_ensemble(
+    members: &[EnsembleMember],
+    transient_periods: usize,
+    sample_periods: usize,
+) -> Vec<Vec<(f64, f64)>> {
+    parallel_map(members, |m| {
+        StroboscopicMap::new(&m.params).section(m.initial, transient_periods, sample_periods)
+    })
+}
+
+/// basin_labels 的并行版本：各格点的庞加莱点并行计算，再按格点顺序去重，结果与串行版本相同
+pub fn parallel_basin_labels(
+    params: &PendulumParams,
+    transient_periods: usize,
+    sample_periods: usize,
+    tolerance: f64,
+) -> BasinMap {
+    let thetas = params.theta_grid();
+    let omegas = params.omega_grid();
+    let initials: Vec<State> = thetas
+        .iter()
+        .flat_map(|&theta| omegas.iter().map(move |&omega| State { theta, omega }))
+        .collect();
+    let sections = poincare_ensemble(
+        &initial_condition_members(params, &initials),
+        transient_periods,
+        sample_periods,
+    );
+    let (labels, first) = deduplicate_attractors(&sections, tolerance);
+
+    BasinMap {
+        thetas,
+        omegas,
+        labels,
+        representatives: first.iter().map(|&i| (initials[i].theta, initials[i].omega)).collect(),
+        attractors: first.into_iter().map(|i| sections[i].clone()).collect(),
+    }
+}
+
+/// 把系综结果合并写成一个 CSV（列：run,parameter,theta0,omega0,theta,omega），每个庞加莱点一行
+pub fn write_ensemble_csv(
+    path: &str,
+    members: &[EnsembleMember],
+    results: &[Vec<(f64, f64)>],
+) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(f, "run,parameter,theta0,omega0,theta,omega")?;
+    for (run, (m, samples)) in members.iter().zip(results).enumerate() {
+        for (theta, omega) in samples {
+            writeln!(
+                f,
+                "{},{:.12},{:.12},{:.12},{:.12},{:.12}",
+                run, m.parameter, m.initial.theta, m.initial.omega, theta, omega
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// 随机初值系综的积分结果：每个驱动周期末（频闪时刻）所有成员的状态
+#[derive(Debug, Clone)]
+pub struct EnsembleRun {
+    pub period: f64,
+    /// snapshots[k][i] 为第 i 个成员在 t = kT 的状态（θ 不折叠），k = 0 为初值
+    pub snapshots: Vec<Vec<State>>,
+}
+
+/// 某一时刻的系综统计量
+///
+/// θ 用圆统计：theta_mean 为平均方向 arg<e^{iθ}>，theta_variance 为圆方差 1 - |<e^{iθ}>|（0 为完全集中，1 为均匀散开）。
+#[derive(Debug, Clone, Copy)]
+pub struct EnsembleStats {
+    pub t: f64,
+    pub theta_mean: f64,
+    pub theta_variance: f64,
+    pub omega_mean: f64,
+    pub omega_variance: f64,
+}
+
+/// 从 distribution 抽取 n 个初值（随机数由 rng_seed 播种，可复现），并行积分到 params.t_end，
+/// 记录每个驱动周期末的状态
+///
+/// 每个周期按 steps_per_period(params) 步整周期积分，频闪时刻无需插值。
+/// distribution 为 Grid 时使用 params 的 θ/ω 网格，忽略 n 与种子。
+pub fn run(params: &PendulumParams, n: usize, rng_seed: u64, distribution: InitialConditionSampling) -> EnsembleRun {
+    let mut p = params.clone();
+    p.ic_sampling = distribution;
+    p.ic_count = n;
+    let initials = initial_conditions_with(&p, &mut seeded_rng(rng_seed));
+
+    let spp = steps_per_period(params);
+    let period = 2.0 * PI / params.omega_d;
+    let periods = (params.t_end / period + 1e-9) as usize;
+    p.set_steps_per_period(spp);
+    p.t_end = periods as f64 * period;
+    p.exact_stroboscopic = false;
+
+    let members: Vec<Vec<State>> = initials
+        .par_iter()
+        .map(|s| {
+            TrajectoryIter::new(&p, s.theta, s.omega)
+                .step_by(spp)
+                .map(|(_, s)| s)
+                .collect()
+        })
+        .collect();
+    let snapshots = (0..=periods)
+        .map(|k| members.iter().filter_map(|m| m.get(k).copied()).collect())
+        .collect();
+    EnsembleRun { period, snapshots }
+}
+
+impl EnsembleRun {
+    /// 逐个频闪时刻的系综均值与方差；空系综的时刻为 NaN
+    pub fn statistics(&self) -> Vec<EnsembleStats> {
+        self.snapshots
+            .iter()
+            .enumerate()
+            .map(|(k, states)| {
+                let n = states.len() as f64;
+                let (sin, cos) = states
+                    .iter()
+                    .fold((0.0, 0.0), |(a, b), s| (a + s.theta.sin(), b + s.theta.cos()));
+                let omega_mean = states.iter().map(|s| s.omega).sum::<f64>() / n;
+                EnsembleStats {
+                    t: k as f64 * self.period,
+                    theta_mean: sin.atan2(cos),
+                    theta_variance: 1.0 - (sin * sin + cos * cos).sqrt() / n,
+                    omega_mean,
+                    omega_variance: states.iter().map(|s| (s.omega - omega_mean).powi(2)).sum::<f64>() / n,
+                }
+            })
+            .collect()
+    }
+
+    /// 第 k 个频闪时刻 θ（折叠到 [-π, π)）的直方图，bins 个等宽区间
+    pub fn theta_histogram(&self, k: usize, bins: usize) -> Vec<usize> {
+        let values: Vec<f64> = self.snapshots[k].iter().map(|s| wrap_angle(s.theta)).collect();
+        histogram(&values, bins, (-PI, PI))
+    }
+
+    /// 第 k 个频闪时刻 ω 的直方图，bins 个等宽区间覆盖 range，越界的点不计
+    pub fn omega_histogram(&self, k: usize, bins: usize, range: (f64, f64)) -> Vec<usize> {
+        let values: Vec<f64> = self.snapshots[k].iter().map(|s| s.omega).collect();
+        histogram(&values, bins, range)
+    }
+
+    /// 庞加莱截面上的不变测度估计：丢弃前 transient_periods 个周期后，把所有成员的截面点计入二维直方图
+    ///
+    /// 各格子计数除以 total() 即为测度的估计；混沌吸引子上随机初值系综比单条长轨道收敛得更均匀。
+    pub fn invariant_measure(
+        &self,
+        transient_periods: usize,
+        theta_bins: usize,
+        omega_bins: usize,
+        omega_range: (f64, f64),
+    ) -> Histogram2d {
+        let mut h = Histogram2d::new(theta_bins, omega_bins, omega_range);
+        for states in self.snapshots.iter().skip(transient_periods + 1) {
+            for s in states {
+                h.add(s.theta, s.omega);
+            }
+        }
+        h
+    }
+}
+
+// 等宽直方图，区间左闭右开，越界的值不计
+fn histogram(values: &[f64], bins: usize, (lo, hi): (f64, f64)) -> Vec<usize> {
+    let bins = bins.max(1);
+    let mut counts = vec![0; bins];
+    for &x in values {
+        if (lo..hi).contains(&x) {
+            counts[(((x - lo) / (hi - lo) * bins as f64) as usize).min(bins - 1)] += 1;
+        }
+    }
+    counts
+}
+
+/// 把系综统计写成 CSV（列：t,theta_mean,theta_variance,omega_mean,omega_variance）
+pub fn write_ensemble_statistics_csv(path: &str, stats: &[EnsembleStats]) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(f, "t,theta_mean,theta_variance,omega_mean,omega_variance")?;
+    for s in stats {
+        writeln!(
+            f,
+            "{:.12},{:.12},{:.12},{:.12},{:.12}",
+            s.t, s.theta_mean, s.theta_variance, s.omega_mean, s.omega_variance
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seeded_sampling_strategies() {
+        let mut params = PendulumParams::new();
+        params.ic_count = 500;
+        params.ic_seed = 7;
+
+        params.ic_sampling = InitialConditionSampling::Box;
+        let a = initial_conditions(&params);
+        assert_eq!(a.len(), 500);
+        assert!(a.iter().all(|s| (-4.0..=4.0).contains(&s.theta) && (-4.0..=4.0).contains(&s.omega)));
+        let b = initial_conditions(&params);
+        assert!(a.iter().zip(&b).all(|(x, y)| x.theta == y.theta && x.omega == y.omega));
+
+        params.ic_sampling = InitialConditionSampling::EnergyShell { e_min: 5.0, e_max: 6.0 };
+        let shell = initial_conditions(&params);
+        assert_eq!(shell.len(), 500);
+        assert!(shell.iter().all(|s| (5.0..=6.0).contains(&pendulum_energy(s.theta, s.omega, &params))));
+
+        // 旧配置文件没有采样字段时退回规则网格
+        let json = include_str!("../config/config.json");
+        let loaded: PendulumParams = serde_json::from_str(json).unwrap();
+        assert_eq!(loaded.ic_sampling, InitialConditionSampling::Grid);
+        assert_eq!(loaded.ic_count, 1000);
+    }
+
+    #[test]
+    fn test_parallel_basin_matches_serial() {
+        let mut params = PendulumParams::new();
+        params.q = 0.5;
+        params.f_d = 1.2;
+        params.omega_d = 2.0 / 3.0;
+        params.dt = 2.0 * PI / params.omega_d / 50.0;
+        params.theta_start = -2.0;
+        params.theta_end = 2.0;
+        params.d_theta = 1.0;
+        params.omega_start = -1.0;
+        params.omega_end = 1.0;
+        params.d_omega = 1.0;
+
+        let serial = crate::basin::basin_labels(&params, 10, 4, 1e-6);
+        let parallel = parallel_basin_labels(&params, 10, 4, 1e-6);
+        assert_eq!(serial.labels, parallel.labels);
+        assert_eq!(serial.representatives, parallel.representatives);
+
+        let members = parameter_members(
+            &params,
+            SweepParameter::DriveAmplitude,
+            (0.5, 1.5),
+            4,
+            State { theta: 0.2, omega: 0.0 },
+        );
+        let results = poincare_ensemble(&members, 10, 3);
+        assert_eq!(results.len(), 5);
+        assert!(results.iter().all(|r| r.len() == 3));
+    }
+
+    #[test]
+    fn test_random_ensemble_statistics() {
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.q = 0.5;
+        params.f_d = 1.2;
+        params.omega_d = 2.0 / 3.0;
+        params.set_steps_per_period(50);
+        params.t_end = 30.0 * 2.0 * PI / params.omega_d;
+        let cloud = InitialConditionSampling::Gaussian { theta: 0.2, omega: 0.0, sigma_theta: 0.01, sigma_omega: 0.01 };
+
+        let a = run(&params, 200, 3, cloud);
+        let b = run(&params, 200, 3, cloud);
+        assert_eq!(a.snapshots.len(), 31);
+        assert!(a.snapshots.iter().all(|s| s.len() == 200));
+        assert_eq!(a.snapshots[30][17].theta, b.snapshots[30][17].theta);
+
+        // 混沌运动把集中的初值云散开到整个吸引子上
+        let stats = a.statistics();
+        assert!(stats[0].theta_variance < 1e-3);
+        assert!(stats[30].theta_variance > 0.1);
+        assert_eq!(a.theta_histogram(30, 16).iter().sum::<usize>(), 200);
+        // 区间左闭右开：右端点不计入最后一格
+        assert_eq!(histogram(&[0.0, 0.5, 1.0], 2, (0.0, 1.0)), vec![1, 1]);
+        let measure = a.invariant_measure(10, 32, 32, (-3.0, 3.0));
+        assert_eq!(measure.total() + measure.outside, 20 * 200);
+    }
+}
@@ -0,0 +1,79 @@
+//error.rs
+use std::fmt;
+
+/// crate 统一的错误类型
+///
+/// 参数检查失败时在积分之前就返回，而不是静默地得到空结果、无穷周期或巨量内存分配。
+/// 与 std::io::Error 可以互相转换，原有返回 io::Result 的代码里仍可用 ? 传播。
+#[derive(Debug)]
+pub enum ChaosError {
+    /// 参数取值无效（非有限值、非正的摆长等），附带说明
+    InvalidParams(String),
+    /// 积分步长 dt <= 0 或不是有限值
+    NonPositiveDt(f64),
+    /// 驱动角频率为 0（或非有限值），驱动周期无定义，无法按周期采样
+    ZeroDriveFrequency,
+    /// 积分时长不足以覆盖要求的采样：needed 为所需的时间，available 为实际积分到的时间
+    InsufficientTrajectory { needed: f64, available: f64 },
+    /// 迭代求解（如 Newton 法）在 iterations 次迭代内没有收敛，residual 为最后的残差
+    NotConverged { iterations: usize, residual: f64 },
+    /// 轨迹第 index 个点的时间 time 没有沿积分方向前进（重复或倒退的时间戳）
+    NonMonotonicTrajectory { index: usize, time: f64 },
+    /// 保存结果估计需要 needed 字节，超出内存预算 budget（见 plan 模块）
+    MemoryBudget { needed: usize, budget: usize },
+    Io(std::io::Error),
+}
+
+pub type Result<T> = std::result::Result<T, ChaosError>;
+
+impl fmt::Display for ChaosError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChaosError::InvalidParams(msg) => write!(f, "invalid parameters: {}", msg),
+            ChaosError::NonPositiveDt(dt) => write!(f, "time step dt must be positive, got {}", dt),
+            ChaosError::ZeroDriveFrequency => write!(f, "drive frequency omega_d must be non-zero"),
+            ChaosError::InsufficientTrajectory { needed, available } => write!(
+                f,
+                "trajectory too short for sampling: needs t = {}, integrates to t = {}",
+                needed, available
+            ),
+            ChaosError::NotConverged { iterations, residual } => {
+                write!(f, "no convergence after {} iterations, residual {:e}", iterations, residual)
+            }
+            ChaosError::NonMonotonicTrajectory { index, time } => {
+                write!(f, "trajectory time does not advance at point {} (t = {})", index, time)
+            }
+            ChaosError::MemoryBudget { needed, budget } => write!(
+                f,
+                "estimated memory {} exceeds the budget of {}; use a streaming routine or raise the memory budget",
+                crate::plan::format_bytes(*needed),
+                crate::plan::format_bytes(*budget)
+            ),
+            ChaosError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ChaosError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ChaosError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ChaosError {
+    fn from(e: std::io::Error) -> Self {
+        ChaosError::Io(e)
+    }
+}
+
+impl From<ChaosError> for std::io::Error {
+    fn from(e: ChaosError) -> Self {
+        match e {
+            ChaosError::Io(e) => e,
+            other => std::io::Error::new(std::io::ErrorKind::InvalidInput, other.to_string()),
+        }
+    }
+}
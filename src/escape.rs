@@ -0,0 +1,157 @@
+//escape.rs
+use std::f64::consts::PI;
+use std::fs::File;
+use std::io::Write;
+use rayon::prelude::*;
+use crate::basin::write_rgb_png;
+use crate::model::PendulumParams;
+use crate::solve_equation::TrajectoryIter;
+
+// 首次通过时间（逃逸时间）图：对每个网格初值记录展开角 θ 第一次偏离初值达到 threshold 的时刻，
+// 默认 threshold = 2π，即第一次转满一整圈。摆动解永远不会到达，记为 NaN。
+// 吸引域图只区分终态，逃逸时间图还保留了到达之前的过程：分界线附近的初值要绕很久才翻过去，
+// 时间场在这些区域呈现层层嵌套的分形条纹，与吸引域边界的分形结构互为补充。
+// 通过时刻在相邻两个积分点之间线性插值。
+
+/// 网格上的首次通过时间
+///
+/// times 按 θ 为外层、ω 为内层的顺序排列（与 basin::BasinMap 相同）；在 t_max 内未到达的格点为 NaN。
+#[derive(Debug, Clone)]
+pub struct EscapeMap {
+    pub thetas: Vec<f64>,
+    pub omegas: Vec<f64>,
+    pub times: Vec<f64>,
+    pub threshold: f64,
+    pub t_max: f64,
+}
+
+impl EscapeMap {
+    pub fn time_at(&self, i_theta: usize, i_omega: usize) -> f64 {
+        self.times[i_theta * self.omegas.len() + i_omega]
+    }
+
+    /// 在 t_max 内到达阈值的格点占比
+    pub fn escaped_fraction(&self) -> f64 {
+        let escaped = self.times.iter().filter(|t| !t.is_nan()).count();
+        escaped as f64 / self.times.len().max(1) as f64
+    }
+}
+
+/// 从 (theta0, omega0) 积分到 t_max，返回 |θ(t) - theta0| 第一次达到 threshold 的时刻；未到达时为 None
+///
+/// 使用 params 的积分设置（步长、积分器、驱动），忽略 params.t_end。
+pub fn first_passage_time(params: &PendulumParams, theta0: f64, omega0: f64, threshold: f64, t_max: f64) -> Option<f64> {
+    let mut p = params.clone();
+    p.t_end = t_max;
+    let mut prev = (0.0, 0.0);
+    for (t, s) in TrajectoryIter::new(&p, theta0, omega0) {
+        let distance = (s.theta - theta0).abs();
+        if distance >= threshold {
+            let (t1, d1) = prev;
+            let alpha = if distance > d1 { (threshold - d1) / (distance - d1) } else { 1.0 };
+            return Some(t1 + alpha * (t - t1));
+        }
+        prev = (t, distance);
+    }
+    None
+}
+
+/// 在 params 的 θ/ω 网格上并行计算首次通过时间
+pub fn escape_time_map(params: &PendulumParams, threshold: f64, t_max: f64) -> EscapeMap {
+    let thetas = params.theta_grid();
+    let omegas = params.omega_grid();
+    let times = thetas
+        .par_iter()
+        .flat_map_iter(|&theta| {
+            omegas
+                .iter()
+                .map(move |&omega| first_passage_time(params, theta, omega, threshold, t_max).unwrap_or(f64::NAN))
+        })
+        .collect();
+    EscapeMap {
+        thetas,
+        omegas,
+        times,
+        threshold,
+        t_max,
+    }
+}
+
+/// 把首次通过时间写成矩阵 CSV：第一行为 θ，第一列为 ω（自上而下递减，与 PNG 方向一致），未到达为 NaN
+pub fn write_escape_matrix_csv(path: &str, map: &EscapeMap) -> std::io::Result<()> {
+    let mut f = std::io::BufWriter::new(File::create(path)?);
+    write!(f, "omega\\theta")?;
+    for theta in &map.thetas {
+        write!(f, ",{:.12}", theta)?;
+    }
+    writeln!(f)?;
+    for j in (0..map.omegas.len()).rev() {
+        write!(f, "{:.12}", map.omegas[j])?;
+        for i in 0..map.thetas.len() {
+            write!(f, ",{:.12}", map.time_at(i, j))?;
+        }
+        writeln!(f)?;
+    }
+    f.flush()
+}
+
+// 对数色标：越早到达越亮（黄），越晚越暗（深紫），未到达为黑色
+fn escape_color(t: f64, t_min: f64, t_max: f64) -> [u8; 3] {
+    if t.is_nan() {
+        return [0, 0, 0];
+    }
+    let span = (t_max / t_min).ln();
+    let x = if span > 0.0 { ((t / t_min).ln() / span).clamp(0.0, 1.0) } else { 0.0 };
+    let mix = |from: f64, to: f64| (from + (to - from) * x).round() as u8;
+    [mix(253.0, 68.0), mix(231.0, 1.0), mix(37.0, 84.0)]
+}
+
+/// 把首次通过时间写成 PNG：横轴 θ（向右增大），纵轴 ω（向上增大），每个格点一个像素
+pub fn write_escape_png(path: &str, map: &EscapeMap) -> std::io::Result<()> {
+    let reached = map.times.iter().copied().filter(|t| !t.is_nan());
+    let t_min = reached.clone().fold(f64::INFINITY, f64::min).max(1e-3 * 2.0 * PI);
+    let t_max = reached.fold(0.0, f64::max);
+    let (width, height) = (map.thetas.len(), map.omegas.len());
+    let mut data = Vec::with_capacity(width * height * 3);
+    for j in (0..height).rev() {
+        for i in 0..width {
+            data.extend_from_slice(&escape_color(map.time_at(i, j), t_min, t_max));
+        }
+    }
+    write_rgb_png(path, width, height, &data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_passage_matches_free_rotation() {
+        // 无重力、无阻尼、无驱动：θ = θ0 + ω0 t，转满一圈用时 2π / ω0
+        let mut params = PendulumParams::new();
+        params.g = 0.0;
+        params.q = 0.0;
+        params.f_d = 0.0;
+        params.dt = 0.01;
+        let t = first_passage_time(&params, 0.3, 2.0, 2.0 * PI, 10.0).unwrap();
+        assert!((t - PI).abs() < 1e-9, "{}", t);
+        assert_eq!(first_passage_time(&params, 0.3, 0.5, 2.0 * PI, 10.0), None);
+
+        // 有重力时：分界线以内的摆动初值永远不转满一圈，远在分界线外的很快转过去
+        params.g = params.l;
+        params.q = 0.5;
+        params.theta_start = -0.5;
+        params.theta_end = 0.5;
+        params.d_theta = 0.5;
+        params.omega_start = 0.0;
+        params.omega_end = 4.0;
+        params.d_omega = 4.0;
+        let map = escape_time_map(&params, 2.0 * PI, 50.0);
+        assert_eq!(map.times.len(), 6);
+        for i in 0..3 {
+            assert!(map.time_at(i, 0).is_nan());
+            assert!(map.time_at(i, 1) < 5.0);
+        }
+        assert!((map.escaped_fraction() - 0.5).abs() < 1e-12);
+    }
+}
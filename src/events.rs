@@ -0,0 +1,383 @@
+//events.rs
+use std::fs::File;
+use std::io::Write;
+use crate::model::PendulumParams;
+use crate::roots::bisect_predicate;
+use crate::solve_equation::{hermite_state, wrap_angle, State};
+
+// 二分细化事件时刻的相对容差（乘以 max(|t|, 1)，保证大于 t 处的浮点间距）
+const EVENT_TIME_TOL: f64 = 1e-12;
+
+/// 穿越方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Crossing {
+    /// 由负变正
+    Rising,
+    /// 由正变负
+    Falling,
+}
+
+/// 一次穿越事件；omega_crossings / theta_crossings 的时间由相邻两步线性插值得到，
+/// 带 params 的 refined_crossings / turning_points / rotations 在三次 Hermite 稠密输出上二分细化
+#[derive(Debug, Clone, Copy)]
+pub struct Event {
+    pub t: f64,
+    pub direction: Crossing,
+}
+
+// 在相邻两步之间检测 f 的符号变化；f1 恰为 0 时算在这一步，f2 为 0 时留给下一步
+fn detect(t1: f64, f1: f64, t2: f64, f2: f64) -> Option<Event> {
+    let direction = if f1 < 0.0 && f2 >= 0.0 {
+        Crossing::Rising
+    } else if f1 > 0.0 && f2 <= 0.0 {
+        Crossing::Falling
+    } else {
+        return None;
+    };
+    let t = t1 + (t2 - t1) * f1 / (f1 - f2);
+    Some(Event { t, direction })
+}
+
+// 沿轨迹检测 f(state) 的符号变化，返回 (左端点下标, 事件)；
+// angular 为 true 时把 f 视为角度差，忽略折叠造成的 ±π 跳变
+fn scan<F: Fn(&State) -> f64>(traj: &[(f64, State)], f: F, angular: bool) -> Vec<(usize, Event)> {
+    traj.windows(2)
+        .enumerate()
+        .filter_map(|(i, w)| {
+            let (t1, s1) = w[0];
+            let (t2, s2) = w[1];
+            let f1 = f(&s1);
+            let f2 = f(&s2);
+            if angular && (f2 - f1).abs() >= std::f64::consts::PI {
+                return None;
+            }
+            detect(t1, f1, t2, f2).map(|e| (i, e))
+        })
+        .collect()
+}
+
+/// ω 穿越 level 的事件（level = 0 即转折点）
+pub fn omega_crossings(traj: &[(f64, State)], level: f64) -> Vec<Event> {
+    scan(traj, |s| s.omega - level, false).into_iter().map(|(_, e)| e).collect()
+}
+
+/// θ 穿越 level（模 2π）的事件
+///
+/// 用 wrap_angle(θ - level) 判断符号，并忽略折叠造成的 ±π 跳变，
+/// 因此转动解每经过一次 level 都会被记录一次。
+pub fn theta_crossings(traj: &[(f64, State)], level: f64) -> Vec<Event> {
+    scan(traj, |s| wrap_angle(s.theta - level), true)
+        .into_iter()
+        .map(|(_, e)| e)
+        .collect()
+}
+
+/// 由状态条件定义的截面
+#[derive(Debug, Clone, Copy)]
+pub enum SectionCondition {
+    /// θ = level（模 2π）
+    Theta(f64),
+    /// ω = level
+    Omega(f64),
+}
+
+impl SectionCondition {
+    // 条件函数（零点即穿越）以及是否按角度处理折叠跳变
+    fn function(&self) -> (impl Fn(&State) -> f64, bool) {
+        let (level, angular) = match *self {
+            SectionCondition::Theta(level) => (level, true),
+            SectionCondition::Omega(level) => (level, false),
+        };
+        let f = move |s: &State| {
+            if angular {
+                wrap_angle(s.theta - level)
+            } else {
+                s.omega - level
+            }
+        };
+        (f, angular)
+    }
+}
+
+// 检测穿越并在稠密输出上二分细化，返回 (事件, 穿越时刻的状态)；状态 θ 不折叠
+fn refined_events(
+    traj: &[(f64, State)],
+    condition: SectionCondition,
+    params: &PendulumParams,
+) -> Vec<(Event, State)> {
+    let (f, angular) = condition.function();
+    scan(traj, &f, angular)
+        .into_iter()
+        .map(|(i, e)| {
+            let (t1, s1) = &traj[i];
+            let (t2, s2) = &traj[i + 1];
+            if f(s2) == 0.0 {
+                return (Event { t: *t2, ..e }, *s2);
+            }
+            let dense = |t: f64| hermite_state(*t1, s1, *t2, s2, t, params);
+            let positive = f(s1) > 0.0;
+            let tol = EVENT_TIME_TOL * t2.abs().max(1.0);
+            let t = bisect_predicate(*t1, *t2, tol, |t| (f(&dense(t)) > 0.0) == positive)
+                .map_or(e.t, |(lo, hi)| 0.5 * (lo + hi));
+            (Event { t, ..e }, dense(t))
+        })
+        .collect()
+}
+
+/// 与 section_crossings 相同的穿越检测，只返回事件（时刻由稠密输出二分细化）
+pub fn refined_crossings(traj: &[(f64, State)], condition: SectionCondition, params: &PendulumParams) -> Vec<Event> {
+    refined_events(traj, condition, params).into_iter().map(|(e, _)| e).collect()
+}
+
+/// 转折点（ω 变号）；Falling 为 θ 的极大值，Rising 为极小值
+pub fn turning_points(traj: &[(f64, State)], params: &PendulumParams) -> Vec<Event> {
+    refined_crossings(traj, SectionCondition::Omega(0.0), params)
+}
+
+/// 整圈转动：θ 越过 ±π（即倒立位置）的时刻；Rising 为逆时针（θ 增大）越过，Falling 为顺时针
+///
+/// 摆幅超过 π 后折返的运动也会在越过与折返时各记一次，方向相反；净圈数为两个方向计数之差。
+pub fn rotations(traj: &[(f64, State)], params: &PendulumParams) -> Vec<Event> {
+    refined_crossings(traj, SectionCondition::Theta(std::f64::consts::PI), params)
+}
+
+/// 截面上的一个穿越点
+#[derive(Debug, Clone, Copy)]
+pub struct SectionPoint {
+    pub t: f64,
+    pub theta: f64,
+    pub omega: f64,
+    /// 穿越时刻的驱动相位 Ωt mod 2π
+    pub drive_phase: f64,
+}
+
+/// 求轨迹与状态截面的穿越点；direction 为 Some 时只保留该方向的穿越
+///
+/// 穿越时刻在相邻两步之间的三次 Hermite 稠密输出上二分细化，状态取该时刻的稠密输出，θ 折叠到 [-π, π)。
+pub fn section_crossings(
+    traj: &[(f64, State)],
+    condition: SectionCondition,
+    direction: Option<Crossing>,
+    params: &PendulumParams,
+) -> Vec<SectionPoint> {
+    refined_events(traj, condition, params)
+        .into_iter()
+        .filter(|(e, _)| direction.is_none_or(|d| e.direction == d))
+        .map(|(e, s)| {
+            SectionPoint {
+                t: e.t,
+                theta: wrap_angle(s.theta),
+                omega: s.omega,
+                drive_phase: (params.omega_d * e.t).rem_euclid(2.0 * std::f64::consts::PI),
+            }
+        })
+        .collect()
+}
+
+/// 相邻两次穿越之间的回归时间
+pub fn return_times(points: &[SectionPoint]) -> Vec<f64> {
+    points.windows(2).map(|w| w[1].t - w[0].t).collect()
+}
+
+/// 回归时间统计
+#[derive(Debug, Clone, Copy)]
+pub struct ReturnTimeReport {
+    pub stats: IntervalStats,
+    /// 穿越时刻驱动相位的平均合成长度 |<e^{iφ}>|：接近 1 为锁相，接近 0 为相位漂移
+    pub phase_coherence: f64,
+}
+
+/// 汇总回归时间分布与锁相程度；少于两次穿越时返回 None
+pub fn return_time_report(points: &[SectionPoint]) -> Option<ReturnTimeReport> {
+    let stats = interval_statistics(&return_times(points))?;
+    let n = points.len() as f64;
+    let (s, c) = points
+        .iter()
+        .fold((0.0, 0.0), |(s, c), p| (s + p.drive_phase.sin(), c + p.drive_phase.cos()));
+    Some(ReturnTimeReport {
+        stats,
+        phase_coherence: (s * s + c * c).sqrt() / n,
+    })
+}
+
+/// 把截面穿越点写成 CSV（列：crossing,t,return_time,drive_phase,theta,omega），第一行 return_time 留空
+pub fn write_return_times_csv(path: &str, points: &[SectionPoint]) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(f, "crossing,t,return_time,drive_phase,theta,omega")?;
+    for (i, p) in points.iter().enumerate() {
+        let rt = if i == 0 {
+            String::new()
+        } else {
+            format!("{:.12}", p.t - points[i - 1].t)
+        };
+        writeln!(
+            f,
+            "{},{:.12},{},{:.12},{:.12},{:.12}",
+            i, p.t, rt, p.drive_phase, p.theta, p.omega
+        )?;
+    }
+    Ok(())
+}
+
+/// 相邻事件之间的时间间隔；direction 为 Some 时只统计该方向的事件
+pub fn crossing_intervals(events: &[Event], direction: Option<Crossing>) -> Vec<f64> {
+    let times: Vec<f64> = events
+        .iter()
+        .filter(|e| direction.is_none_or(|d| e.direction == d))
+        .map(|e| e.t)
+        .collect();
+    times.windows(2).map(|w| w[1] - w[0]).collect()
+}
+
+/// 间隔分布的摘要
+///
+/// 周期运动的间隔只有一个（或少数几个）取值，变异系数接近 0；
+/// 准周期运动的间隔在有限区间内连续分布；混沌运动的分布宽且不规则。
+#[derive(Debug, Clone, Copy)]
+pub struct IntervalStats {
+    pub count: usize,
+    pub mean: f64,
+    pub std_dev: f64,
+    pub min: f64,
+    pub max: f64,
+    /// 变异系数 std_dev / mean
+    pub coefficient_of_variation: f64,
+}
+
+/// 计算间隔的均值、标准差、极值与变异系数；没有间隔时返回 None
+pub fn interval_statistics(intervals: &[f64]) -> Option<IntervalStats> {
+    if intervals.is_empty() {
+        return None;
+    }
+    let n = intervals.len() as f64;
+    let mean = intervals.iter().sum::<f64>() / n;
+    let var = intervals.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n;
+    let std_dev = var.sqrt();
+    Some(IntervalStats {
+        count: intervals.len(),
+        mean,
+        std_dev,
+        min: intervals.iter().cloned().fold(f64::INFINITY, f64::min),
+        max: intervals.iter().cloned().fold(f64::NEG_INFINITY, f64::max),
+        coefficient_of_variation: std_dev / mean,
+    })
+}
+
+/// 把间隔分布写成直方图 CSV（三列：bin_start,bin_end,count），bins 个等宽区间覆盖 [min, max]
+pub fn write_interval_histogram_csv(path: &str, intervals: &[f64], bins: usize) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(f, "bin_start,bin_end,count")?;
+    let Some(stats) = interval_statistics(intervals) else {
+        return Ok(());
+    };
+    let bins = bins.max(1);
+    let width = (stats.max - stats.min) / bins as f64;
+    let mut counts = vec![0usize; bins];
+    for x in intervals {
+        let i = if width > 0.0 {
+            (((x - stats.min) / width) as usize).min(bins - 1)
+        } else {
+            0
+        };
+        counts[i] += 1;
+    }
+    for (i, c) in counts.iter().enumerate() {
+        let start = stats.min + i as f64 * width;
+        writeln!(f, "{:.12},{:.12},{}", start, start + width, c)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_crossing_intervals_of_harmonic_motion() {
+        // θ = sin t，ω = cos t
+        let traj: Vec<(f64, State)> = (0..=20000)
+            .map(|i| {
+                let t = i as f64 * 0.001;
+                (t, State { theta: t.sin(), omega: t.cos() })
+            })
+            .collect();
+
+        let theta_events = theta_crossings(&traj, 0.0);
+        let stats = interval_statistics(&crossing_intervals(&theta_events, None)).unwrap();
+        assert_relative_eq!(stats.mean, PI, epsilon = 1e-6);
+        assert!(stats.coefficient_of_variation < 1e-6);
+
+        let rising = crossing_intervals(&omega_crossings(&traj, 0.0), Some(Crossing::Rising));
+        assert!(rising.iter().all(|dt| (dt - 2.0 * PI).abs() < 1e-6));
+    }
+
+    #[test]
+    fn test_theta_crossings_ignore_wrap_jump() {
+        // 匀速转动：每圈只穿过 0 一次
+        let traj: Vec<(f64, State)> = (0..=1000)
+            .map(|i| {
+                let t = i as f64 * 0.01;
+                (t, State { theta: wrap_angle(t), omega: 1.0 })
+            })
+            .collect();
+        assert_eq!(theta_crossings(&traj, 0.0).len(), 1);
+    }
+
+    #[test]
+    fn test_return_times_of_phase_locked_orbit() {
+        // 周期 1 驱动响应：强阻尼、弱驱动
+        let mut params = PendulumParams::new();
+        params.q = 1.0;
+        params.f_d = 0.3;
+        params.omega_d = 2.0 / 3.0;
+        let period = 2.0 * PI / params.omega_d;
+        params.dt = period / 400.0;
+        params.t_end = 80.0 * period;
+
+        let traj = crate::solve_equation::solve(&params, 0.0, 0.0).unwrap();
+        let tail = crate::solve_equation::slice_by_periods(&traj, &params, 40, 80);
+        let points = section_crossings(&tail, SectionCondition::Omega(0.0), Some(Crossing::Falling), &params);
+        let report = return_time_report(&points).unwrap();
+        assert_relative_eq!(report.stats.mean, period, max_relative = 1e-6);
+        assert!(report.phase_coherence > 0.999);
+    }
+
+    #[test]
+    fn test_refined_turning_points_and_rotations() {
+        let mut params = PendulumParams::new();
+        params.q = 1.0;
+        params.f_d = 0.3;
+        params.omega_d = 2.0 / 3.0;
+        let period = 2.0 * PI / params.omega_d;
+        params.t_end = 20.0 * period;
+
+        // 粗步长细化后的转折时刻与细步长的结果一致，且优于线性插值
+        params.dt = period / 200.0;
+        let coarse = crate::solve_equation::solve(&params, 0.5, 0.0).unwrap();
+        params.dt = period / 4000.0;
+        let fine = crate::solve_equation::solve(&params, 0.5, 0.0).unwrap();
+        let reference = omega_crossings(&fine, 0.0);
+        let refined = turning_points(&coarse, &params);
+        let linear = omega_crossings(&coarse, 0.0);
+        assert_eq!(refined.len(), reference.len());
+        let max_err = |events: &[Event]| events.iter().zip(&reference).map(|(a, b)| (a.t - b.t).abs()).fold(0.0, f64::max);
+        assert!(max_err(&refined) < 2e-4, "refined {} linear {}", max_err(&refined), max_err(&linear));
+        assert!(max_err(&refined) < 0.1 * max_err(&linear));
+
+        // 无重力的匀速转子：θ = 2t，每 π 秒逆时针越过一次 π
+        params.g = 0.0;
+        params.q = 0.0;
+        params.f_d = 0.0;
+        params.dt = 0.1;
+        params.t_end = 10.0;
+        let rotor = crate::solve_equation::solve(&params, 0.0, 2.0).unwrap();
+        let events = rotations(&rotor, &params);
+        assert_eq!(events.len(), 3);
+        for (k, e) in events.iter().enumerate() {
+            assert_eq!(e.direction, Crossing::Rising);
+            assert_relative_eq!(e.t, PI / 2.0 + k as f64 * PI, epsilon = 1e-10);
+        }
+    }
+}
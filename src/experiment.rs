@@ -0,0 +1,290 @@
+//experiment.rs
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Component, Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use crate::metadata::RunMetadata;
+
+// 可复现的实验目录：每次运行在 root 下新建 <UTC 时间戳>-<名称> 目录，所有产物都写进去，不会覆盖上一次的结果。
+// 目录内容：
+//   manifest.json  运行编号、命令行、版本与 git 信息、状态、元数据（参数、初值、设置）与产物列表
+//   params.json    完整参数，可直接作为 --config 重跑
+//   run.log        运行日志（命令的输出或错误信息）
+//   其余为各命令写出的 CSV / PNG 等
+// root/index.jsonl 为登记表，每次运行结束追加一行（IndexEntry），便于按名称、时间检索。
+// git 信息在运行时从本 crate 的源码目录读取；不在 git 仓库中（如从 crates.io 安装）时为空。
+
+/// 版本与源码信息
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionInfo {
+    pub crate_version: String,
+    /// 源码的 git 提交
+    pub git_commit: Option<String>,
+    /// 工作区是否有未提交的修改（不计未跟踪文件）
+    pub git_dirty: Option<bool>,
+}
+
+fn git(args: &[&str]) -> Option<String> {
+    let output = std::process::Command::new("git")
+        .arg("-C")
+        .arg(env!("CARGO_MANIFEST_DIR"))
+        .args(args)
+        .output()
+        .ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+impl VersionInfo {
+    pub fn current() -> Self {
+        VersionInfo {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            git_commit: git(&["rev-parse", "HEAD"]),
+            git_dirty: git(&["status", "--porcelain", "--untracked-files=no"]).map(|s| !s.is_empty()),
+        }
+    }
+}
+
+/// 登记表中的一行
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndexEntry {
+    pub id: String,
+    pub name: String,
+    /// 创建时刻（UTC，ISO 8601）
+    pub created: String,
+    /// "ok" 或 "failed"
+    pub status: String,
+    /// 实验目录
+    pub dir: String,
+    pub artifacts: Vec<String>,
+}
+
+/// 实验目录中的 manifest.json
+#[derive(Debug, Clone, Serialize)]
+pub struct Manifest {
+    pub id: String,
+    pub name: String,
+    pub created: String,
+    pub command: Vec<String>,
+    pub version: VersionInfo,
+    /// "running"、"ok" 或 "failed"
+    pub status: String,
+    /// 第一份记录的运行元数据（参数、初值与运行设置）
+    pub metadata: Option<RunMetadata>,
+    /// 产物文件名（相对实验目录）
+    pub artifacts: Vec<String>,
+}
+
+/// 一次运行的实验目录
+#[derive(Debug)]
+pub struct Experiment {
+    root: PathBuf,
+    dir: PathBuf,
+    manifest: Manifest,
+}
+
+// Unix 时间（秒）转 UTC 的 (年, 月, 日, 时, 分, 秒)，日期部分用 Howard Hinnant 的 civil_from_days
+fn utc_fields(secs: u64) -> (i64, u32, u32, u32, u32, u32) {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day, (rem / 3600) as u32, (rem / 60 % 60) as u32, (rem % 60) as u32)
+}
+
+// 名称中只保留字母、数字、'-' 与 '_'，其余替换为 '_'
+fn sanitize(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    if name.is_empty() { "run".to_string() } else { name }
+}
+
+impl Experiment {
+    /// 在 root 下新建实验目录；同一秒内同名的运行依次加后缀 -2、-3……
+    ///
+    /// command 为记录在 manifest 中的命令行（通常是 std::env::args()）。
+    pub fn create(root: &str, name: &str, command: Vec<String>) -> std::io::Result<Self> {
+        let secs = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let (y, mo, d, h, mi, s) = utc_fields(secs);
+        let name = sanitize(name);
+        let stamp = format!("{:04}{:02}{:02}T{:02}{:02}{:02}Z", y, mo, d, h, mi, s);
+        let root = PathBuf::from(root);
+        std::fs::create_dir_all(&root)?;
+        let mut suffix = 1;
+        let (id, dir) = loop {
+            let id = match suffix {
+                1 => format!("{}-{}", stamp, name),
+                n => format!("{}-{}-{}", stamp, name, n),
+            };
+            let dir = root.join(&id);
+            match std::fs::create_dir(&dir) {
+                Ok(()) => break (id, dir),
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => suffix += 1,
+                Err(e) => return Err(e),
+            }
+        };
+        let experiment = Experiment {
+            root,
+            dir,
+            manifest: Manifest {
+                id,
+                name,
+                created: format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z", y, mo, d, h, mi, s),
+                command,
+                version: VersionInfo::current(),
+                status: "running".to_string(),
+                metadata: None,
+                artifacts: Vec::new(),
+            },
+        };
+        experiment.write_manifest()?;
+        Ok(experiment)
+    }
+
+    pub fn id(&self) -> &str {
+        &self.manifest.id
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn manifest(&self) -> &Manifest {
+        &self.manifest
+    }
+
+    /// 把原本的输出路径换到实验目录下并登记为产物。保留相对路径（去掉根目录与 `.`、`..`），
+    /// 避免 `a/out.csv` 与 `b/out.csv` 落到同一个文件；父目录由调用方创建
+    pub fn artifact_path(&mut self, output: &str) -> String {
+        let relative: PathBuf = Path::new(output)
+            .components()
+            .filter_map(|c| match c {
+                Component::Normal(part) => Some(part),
+                _ => None,
+            })
+            .collect();
+        let name = if relative.as_os_str().is_empty() {
+            output.to_string()
+        } else {
+            relative.to_string_lossy().into_owned()
+        };
+        if !self.manifest.artifacts.contains(&name) {
+            self.manifest.artifacts.push(name.clone());
+        }
+        self.dir.join(name).to_string_lossy().into_owned()
+    }
+
+    /// 记录运行元数据；只保留第一份，同时写出 params.json
+    pub fn record_metadata(&mut self, meta: &RunMetadata) -> std::io::Result<()> {
+        if self.manifest.metadata.is_some() {
+            return Ok(());
+        }
+        let f = File::create(self.dir.join("params.json"))?;
+        serde_json::to_writer_pretty(f, &meta.params).map_err(std::io::Error::other)?;
+        self.manifest.metadata = Some(meta.clone());
+        self.write_manifest()
+    }
+
+    /// 在 run.log 末尾追加一行
+    pub fn log(&self, line: &str) -> std::io::Result<()> {
+        let mut f = OpenOptions::new().create(true).append(true).open(self.dir.join("run.log"))?;
+        writeln!(f, "{}", line)
+    }
+
+    fn write_manifest(&self) -> std::io::Result<()> {
+        let f = File::create(self.dir.join("manifest.json"))?;
+        serde_json::to_writer_pretty(f, &self.manifest).map_err(std::io::Error::other)
+    }
+
+    /// 结束运行：写入最终状态与日志，并在 root/index.jsonl 登记
+    pub fn finish(mut self, result: Result<&str, &str>) -> std::io::Result<()> {
+        let (status, message) = match result {
+            Ok(message) => ("ok", message),
+            Err(message) => ("failed", message),
+        };
+        self.manifest.status = status.to_string();
+        if !message.is_empty() {
+            self.log(message)?;
+        }
+        self.write_manifest()?;
+        let entry = IndexEntry {
+            id: self.manifest.id.clone(),
+            name: self.manifest.name.clone(),
+            created: self.manifest.created.clone(),
+            status: self.manifest.status.clone(),
+            dir: self.dir.to_string_lossy().into_owned(),
+            artifacts: self.manifest.artifacts.clone(),
+        };
+        let line = serde_json::to_string(&entry).map_err(std::io::Error::other)?;
+        let mut f = OpenOptions::new().create(true).append(true).open(self.root.join("index.jsonl"))?;
+        writeln!(f, "{}", line)
+    }
+}
+
+/// 读取 root/index.jsonl；不存在时为空
+pub fn read_index(root: &str) -> std::io::Result<Vec<IndexEntry>> {
+    let path = Path::new(root).join("index.jsonl");
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    BufReader::new(File::open(path)?)
+        .lines()
+        .filter(|line| line.as_ref().is_ok_and(|l| !l.trim().is_empty()))
+        .map(|line| serde_json::from_str(&line?).map_err(std::io::Error::other))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::PendulumParams;
+
+    #[test]
+    fn test_experiment_directory_and_index() {
+        assert_eq!(utc_fields(0), (1970, 1, 1, 0, 0, 0));
+        assert_eq!(utc_fields(951_782_400 + 3661), (2000, 2, 29, 1, 1, 1));
+
+        let root = std::env::temp_dir().join(format!("chaos_experiment_test_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let root = root.to_str().unwrap();
+        let mut a = Experiment::create(root, "scan f_d", vec!["chaos".to_string(), "poincare".to_string()]).unwrap();
+        let b = Experiment::create(root, "scan f_d", Vec::new()).unwrap();
+        assert_ne!(a.dir(), b.dir());
+        assert!(a.id().ends_with("-scan_f_d"));
+
+        let out = a.artifact_path("data/poincare.csv");
+        assert_eq!(Path::new(&out), a.dir().join("data/poincare.csv"));
+        assert_ne!(a.artifact_path("a/out.csv"), a.artifact_path("b/out.csv"));
+        assert_eq!(Path::new(&a.artifact_path("/tmp/../x.csv")), a.dir().join("tmp/x.csv"));
+        std::fs::create_dir_all(Path::new(&out).parent().unwrap()).unwrap();
+        std::fs::write(&out, "theta,omega\n").unwrap();
+        let meta = RunMetadata::new(&PendulumParams::new(), 0.2, 0.0);
+        a.record_metadata(&meta).unwrap();
+        let params: PendulumParams = serde_json::from_reader(File::open(a.dir().join("params.json")).unwrap()).unwrap();
+        assert_eq!(params.omega_d, meta.params.omega_d);
+        a.finish(Ok("Wrote 1 file")).unwrap();
+        b.finish(Err("boom")).unwrap();
+
+        let index = read_index(root).unwrap();
+        assert_eq!(index.len(), 2);
+        assert_eq!((index[0].status.as_str(), index[0].artifacts.as_slice()), ("ok", ["data/poincare.csv", "a/out.csv", "b/out.csv", "tmp/x.csv"].map(String::from).as_slice()));
+        assert_eq!(index[1].status, "failed");
+        let log = std::fs::read_to_string(Path::new(&index[0].dir).join("run.log")).unwrap();
+        assert_eq!(log, "Wrote 1 file\n");
+        let manifest: serde_json::Value =
+            serde_json::from_reader(File::open(Path::new(&index[0].dir).join("manifest.json")).unwrap()).unwrap();
+        assert_eq!(manifest["command"][1], "poincare");
+        assert_eq!(manifest["metadata"]["initial_theta"], 0.2);
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,163 @@
+//feigenbaum.rs
+use std::fs::File;
+use std::io::Write;
+use crate::attractor::classify_attractor;
+use crate::basin::AttractorClass;
+use crate::bifurcation::sweep_values;
+use crate::model::PendulumParams;
+use crate::roots::bisect_predicate;
+use crate::solve_equation::{wrap_angle, State};
+use crate::stroboscopic::{section_points, StroboscopicMap};
+
+// 倍周期级联 f_1 < f_2 < f_3 < ...（周期 1→2→4→...）的相邻间隔按 Feigenbaum 常数
+// δ = 4.6692... 几何收缩：δ_n = (f_n - f_{n-1}) / (f_{n+1} - f_n) → δ。
+// 阈值先在粗网格上定位（周期从 p 变为 2p 的两个相邻格点），再对
+// “吸引子周期仍为 p” 这一谓词二分。临界点附近收敛很慢（临界慢化），
+// 过渡周期数不足时阈值会向高 f_d 一侧偏移。
+
+/// 一次倍周期分岔：周期从 period 变为 2·period 的驱动幅度
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PeriodDoubling {
+    pub period: usize,
+    pub f_d: f64,
+}
+
+/// 在给定 f_d 下从 initial 出发积分，返回吸引子周期（非周期时为 None）与末状态
+pub fn attractor_period(
+    params: &PendulumParams,
+    f_d: f64,
+    initial: State,
+    transient_periods: usize,
+    sample_periods: usize,
+    tolerance: f64,
+) -> (Option<usize>, State) {
+    let mut p = params.clone();
+    p.f_d = f_d;
+    let orbit = StroboscopicMap::new(&p).orbit(initial, transient_periods, sample_periods);
+    let samples = section_points(&orbit);
+    let period = match classify_attractor(&samples, tolerance) {
+        Some(AttractorClass::Periodic(n)) => Some(n),
+        _ => None,
+    };
+    let last = orbit[orbit.len() - 1];
+    let end = State {
+        theta: wrap_angle(last.theta),
+        omega: last.omega,
+    };
+    (period, end)
+}
+
+/// 沿 f_d 定位倍周期级联的阈值，最多 max_doublings 个
+///
+/// 在 f_d_range 的 steps + 1 个格点上逐点热启动扫描吸引子周期（从 (0, 0) 出发），
+/// 找到周期由 p 变为 2p 的相邻格点后，从左格点的末状态出发对 “周期仍为 p” 二分到 bisection_tolerance。
+/// 判不出周期的格点被跳过；遇到不是倍增的周期变化（例如混沌区中的周期窗口）时停止。
+#[allow(clippy::too_many_arguments)]
+pub fn period_doubling_thresholds(
+    params: &PendulumParams,
+    f_d_range: (f64, f64),
+    steps: usize,
+    max_doublings: usize,
+    transient_periods: usize,
+    sample_periods: usize,
+    tolerance: f64,
+    bisection_tolerance: f64,
+) -> Vec<PeriodDoubling> {
+    let mut doublings = Vec::new();
+    let mut state = State { theta: 0.0, omega: 0.0 };
+    let mut previous: Option<(f64, usize, State)> = None;
+
+    for f_d in sweep_values(f_d_range, steps) {
+        if doublings.len() >= max_doublings {
+            break;
+        }
+        let start = state;
+        let (period, end) = attractor_period(params, f_d, start, transient_periods, sample_periods, tolerance);
+        state = end;
+        // 个别格点可能因过渡期不够而判不出周期，跳过即可；级联进入混沌后不会再出现周期倍增
+        let Some(period) = period else {
+            continue;
+        };
+        if let Some((lo, p, lo_state)) = previous
+            && period != p
+        {
+            if period != 2 * p {
+                break;
+            }
+            let still_p = |x: f64| {
+                attractor_period(params, x, lo_state, transient_periods, sample_periods, tolerance).0 == Some(p)
+            };
+            if let Some((a, b)) = bisect_predicate(lo, f_d, bisection_tolerance, still_p) {
+                doublings.push(PeriodDoubling {
+                    period: p,
+                    f_d: 0.5 * (a + b),
+                });
+            }
+        }
+        previous = Some((f_d, period, start));
+    }
+    doublings
+}
+
+/// 由相邻三个阈值估计 Feigenbaum δ：δ_n = (f_n - f_{n-1}) / (f_{n+1} - f_n)
+///
+/// 返回长度为 thresholds.len() - 2 的序列（不足三个阈值时为空）。
+pub fn feigenbaum_deltas(thresholds: &[f64]) -> Vec<f64> {
+    thresholds
+        .windows(3)
+        .map(|w| (w[1] - w[0]) / (w[2] - w[1]))
+        .collect()
+}
+
+/// 把倍周期阈值写成 CSV（三列：period,f_d,delta），delta 为以该阈值为中间点的估计，无法估计时留空
+pub fn write_period_doublings_csv(path: &str, doublings: &[PeriodDoubling]) -> std::io::Result<()> {
+    let thresholds: Vec<f64> = doublings.iter().map(|d| d.f_d).collect();
+    let deltas = feigenbaum_deltas(&thresholds);
+    let mut f = File::create(path)?;
+    writeln!(f, "period,f_d,delta")?;
+    for (i, d) in doublings.iter().enumerate() {
+        match i.checked_sub(1).and_then(|k| deltas.get(k)) {
+            Some(delta) => writeln!(f, "{},{:.12},{:.12}", d.period, d.f_d, delta)?,
+            None => writeln!(f, "{},{:.12},", d.period, d.f_d)?,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_feigenbaum_deltas_of_geometric_cascade() {
+        // 间隔按 δ = 4.669 几何收缩的阈值序列
+        let delta = 4.669;
+        let mut thresholds = vec![1.0];
+        let mut gap = 0.1;
+        for _ in 0..4 {
+            thresholds.push(thresholds[thresholds.len() - 1] + gap);
+            gap /= delta;
+        }
+        let deltas = feigenbaum_deltas(&thresholds);
+        assert_eq!(deltas.len(), 3);
+        for d in deltas {
+            assert_relative_eq!(d, delta, epsilon = 1e-9);
+        }
+        assert!(feigenbaum_deltas(&thresholds[..2]).is_empty());
+    }
+
+    #[test]
+    fn test_period_doubling_thresholds_locates_first_two() {
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.q = 0.5;
+        params.omega_d = 2.0 / 3.0;
+        params.set_steps_per_period(100);
+        let doublings = period_doubling_thresholds(&params, (1.06, 1.084), 48, 2, 400, 16, 1e-5, 1e-4);
+        assert_eq!(doublings.len(), 2, "{:?}", doublings);
+        assert_eq!((doublings[0].period, doublings[1].period), (1, 2));
+        assert!((doublings[0].f_d - 1.066).abs() < 2e-3, "{:?}", doublings);
+        assert!((doublings[1].f_d - 1.079).abs() < 2e-3, "{:?}", doublings);
+    }
+}
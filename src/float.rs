@@ -0,0 +1,210 @@
+//float.rs
+use std::fs::File;
+use std::io::Write;
+use rayon::prelude::*;
+use crate::model::PendulumParams;
+use crate::solve_equation::{step_with, wrap_angle, State};
+
+// 低精度积分：solve_equation 的单步内核（rk4_step_h、step_with 等）对 F: Float 泛型，f64 只是其中一个实例，
+// 这里用 F = f32 实例化。状态、时间与中间运算都在 F 中进行，参数、驱动与时间表在 f64 下求值后转换。
+// F = f32 时内存与 SIMD 吞吐量约为 f64 的两倍，适合只关心终态类别的大规模系综 / 吸引域扫描；
+// 但 f32 的机器精度约 6e-8，混沌区里轨迹在 ln(1/ε)/λ 之后就与 f64 轨迹逐点无关，
+// 比 f64 早 2～3 倍，Lyapunov 指数等对精度敏感的计算应继续用 f64。
+
+/// 积分器可用的浮点类型（f32、f64）
+pub trait Float: num_traits::Float + num_traits::FloatConst + Send + Sync + std::fmt::Debug + 'static {
+    /// 类型名，用于输出
+    const NAME: &'static str;
+
+    fn from_f64(x: f64) -> Self;
+
+    fn to_f64(self) -> f64;
+}
+
+impl Float for f32 {
+    const NAME: &'static str = "f32";
+
+    fn from_f64(x: f64) -> Self {
+        x as f32
+    }
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+}
+
+impl Float for f64 {
+    const NAME: &'static str = "f64";
+
+    fn from_f64(x: f64) -> Self {
+        x
+    }
+
+    fn to_f64(self) -> f64 {
+        self
+    }
+}
+
+/// 精度为 F 的状态（即 solve_equation::State<F>）
+pub type GenericState<F> = State<F>;
+
+impl<F: Float> State<F> {
+    pub fn from_state(s: State) -> Self {
+        State {
+            theta: F::from_f64(s.theta),
+            omega: F::from_f64(s.omega),
+        }
+    }
+
+    pub fn to_state(&self) -> State {
+        State {
+            theta: self.theta.to_f64(),
+            omega: self.omega.to_f64(),
+        }
+    }
+}
+
+/// 精度为 F 的整周期积分（积分器按 params.integrator），对应 solve_periods：dt = T / steps_per_period
+///
+/// 只返回每个驱动周期末的状态（共 n_periods + 1 个，含初值），转换回 f64。
+/// 时间由步数乘 dt 得到而不是逐步累加，f32 下驱动相位也不会随时间漂移；θ 在每个周期末折叠到 [-π, π)，
+/// 否则转动解的 |θ| 越积越大，f32 的有效位数很快流失。
+pub fn stroboscopic_generic<F: Float>(
+    params: &PendulumParams,
+    initial: State,
+    n_periods: usize,
+    steps_per_period: usize,
+) -> Vec<State> {
+    let dt = F::TAU() / F::from_f64(params.omega_d) / F::from_f64(steps_per_period as f64);
+    let mut state = GenericState::<F>::from_state(initial);
+    let mut out = Vec::with_capacity(n_periods + 1);
+    out.push(state.to_state());
+    for n in 0..n_periods {
+        state = advance_period(state, n, steps_per_period, dt, params);
+        out.push(state.to_state());
+    }
+    out
+}
+
+// 从第 n 个周期初积分一个周期，末了把 θ 折叠到 [-π, π)
+fn advance_period<F: Float>(
+    mut state: GenericState<F>,
+    n: usize,
+    steps_per_period: usize,
+    dt: F,
+    params: &PendulumParams,
+) -> GenericState<F> {
+    for k in 0..steps_per_period {
+        let t = F::from_f64((n * steps_per_period + k) as f64) * dt;
+        state = step_with(&state, t, dt, params);
+    }
+    let turns = (state.theta / F::TAU()).round();
+    GenericState {
+        theta: state.theta - turns * F::TAU(),
+        omega: state.omega,
+    }
+}
+
+/// 把一组初值并行积分 n_periods 个驱动周期，返回精度为 F 的末状态（θ 折叠到 [-π, π)）
+///
+/// 系综与吸引域扫描只需要终态时用它：F = f32 时每个状态只占 8 字节，且整批都在 f32 中计算。
+pub fn stroboscopic_ensemble<F: Float>(
+    params: &PendulumParams,
+    initial: &[State],
+    n_periods: usize,
+    steps_per_period: usize,
+) -> Vec<GenericState<F>> {
+    let dt = F::TAU() / F::from_f64(params.omega_d) / F::from_f64(steps_per_period as f64);
+    initial
+        .par_iter()
+        .map(|s| {
+            (0..n_periods).fold(GenericState::<F>::from_state(*s), |state, n| {
+                advance_period(state, n, steps_per_period, dt, params)
+            })
+        })
+        .collect()
+}
+
+/// F 精度与 f64 的频闪轨迹在每个周期末的相空间距离（θ 差按 2π 折叠），两者步长相同
+pub fn precision_gap<F: Float>(
+    params: &PendulumParams,
+    initial: State,
+    n_periods: usize,
+    steps_per_period: usize,
+) -> Vec<(usize, f64)> {
+    let low = stroboscopic_generic::<F>(params, initial, n_periods, steps_per_period);
+    let high = stroboscopic_generic::<f64>(params, initial, n_periods, steps_per_period);
+    low.iter()
+        .zip(&high)
+        .enumerate()
+        .map(|(n, (a, b))| (n, wrap_angle(a.theta - b.theta).hypot(a.omega - b.omega)))
+        .collect()
+}
+
+/// 距离第一次超过 threshold 的周期数；始终没有超过时返回 None
+pub fn divergence_period(gaps: &[(usize, f64)], threshold: f64) -> Option<usize> {
+    gaps.iter().find(|(_, d)| *d > threshold).map(|(n, _)| *n)
+}
+
+/// 把精度差异曲线写成 CSV（两列：period,distance）
+pub fn write_precision_gap_csv(path: &str, rows: &[(usize, f64)]) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(f, "period,distance")?;
+    for (n, d) in rows {
+        writeln!(f, "{},{:.6e}", n, d)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solve_equation::solve_periods;
+
+    #[test]
+    fn test_f32_diverges_from_f64_earlier_than_f64_roundoff() {
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.q = 0.5;
+        params.f_d = 1.2;
+        params.omega_d = 2.0 / 3.0;
+        let initial = State { theta: 0.2, omega: 0.0 };
+        let spp = 100;
+
+        // f64 实例与 solve_periods 的周期末状态在舍入误差内一致
+        let f64_strobe = stroboscopic_generic::<f64>(&params, initial, 5, spp);
+        let traj = solve_periods(&params, 0.2, 0.0, 5, spp).unwrap();
+        for (n, s) in f64_strobe.iter().enumerate() {
+            assert!(wrap_angle(s.theta - traj[n * spp].1.theta).abs() < 1e-10);
+        }
+        // 与 solve 共用同一个内核：换积分器、换参数激励也随之生效
+        let mut other = params.clone();
+        other.integrator = crate::model::Integrator::Yoshida4;
+        other.drive_type = crate::model::DriveType::Parametric;
+        let strobe = stroboscopic_generic::<f64>(&other, initial, 5, spp);
+        let traj = solve_periods(&other, 0.2, 0.0, 5, spp).unwrap();
+        assert!(strobe.iter().enumerate().all(|(n, s)| wrap_angle(s.theta - traj[n * spp].1.theta).abs() < 1e-10));
+
+        // 从吸引子上的点出发（过渡期的强收缩会把初值的微小差别抹平）。f32 与 f64 的差距从 ~1e-7 起指数增长，
+        // f64 下初值相差 1e-14（转动中 |θ| ~ 10 时的几个 ulp）的两条轨迹要晚 2～3 倍才分开到同样距离
+        let start = stroboscopic_generic::<f64>(&params, initial, 50, spp)[50];
+        let n32 = divergence_period(&precision_gap::<f32>(&params, start, 300, spp), 0.1).unwrap();
+        let nudged = State { theta: start.theta + 1e-14, ..start };
+        let a = stroboscopic_generic::<f64>(&params, start, 300, spp);
+        let b = stroboscopic_generic::<f64>(&params, nudged, 300, spp);
+        let gaps: Vec<(usize, f64)> = a
+            .iter()
+            .zip(&b)
+            .enumerate()
+            .map(|(n, (x, y))| (n, wrap_angle(x.theta - y.theta).hypot(x.omega - y.omega)))
+            .collect();
+        let n64 = divergence_period(&gaps, 0.1).unwrap();
+        assert!(n32 > 3, "{}", n32);
+        let ratio = n64 as f64 / n32 as f64;
+        assert!(ratio > 1.5 && ratio < 5.0, "f32 diverges at period {}, f64 at {}", n32, n64);
+
+        let ensemble = stroboscopic_ensemble::<f32>(&params, &[initial, start], 3, spp);
+        assert_eq!(ensemble.len(), 2);
+        assert!(wrap_angle(ensemble[0].to_state().theta - f64_strobe[3].theta).abs() < 1e-3);
+    }
+}
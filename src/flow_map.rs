@@ -0,0 +1,228 @@
+//flow_map.rs
+use std::f64::consts::PI;
+use std::fs::File;
+use std::io::Write;
+use crate::model::PendulumParams;
+use crate::solve_equation::{rk4_step, steps_per_period, wrap_angle, State};
+
+/// 网格上的 N 周期流映射：每个初值 (θ0, ω0) 在 t = 0 出发，积分恰好 periods 个驱动周期后的状态
+///
+/// finals 按 θ 为外层、ω 为内层排列；末态 θ 不折叠，保证相邻格点的差分连续（FTLE 需要）。
+#[derive(Debug, Clone)]
+pub struct FlowMap {
+    pub thetas: Vec<f64>,
+    pub omegas: Vec<f64>,
+    pub periods: usize,
+    /// 积分时长 periods·T
+    pub duration: f64,
+    pub finals: Vec<State>,
+}
+
+impl FlowMap {
+    pub fn final_at(&self, i_theta: usize, i_omega: usize) -> State {
+        self.finals[i_theta * self.omegas.len() + i_omega]
+    }
+}
+
+/// 在 params 的 θ/ω 网格上计算 periods 个驱动周期的流映射
+///
+/// 每个周期的步数由 params.dt 决定（steps_per_period），dt 调整为周期的整数分之一，
+/// 因此末时刻恰好是 periods·T，不需要插值。
+pub fn flow_map(params: &PendulumParams, periods: usize) -> FlowMap {
+    let spp = steps_per_period(params);
+    let mut p = params.clone();
+    p.dt = 2.0 * PI / params.omega_d / spp as f64;
+
+    let thetas = params.theta_grid();
+    let omegas = params.omega_grid();
+    let mut finals = Vec::with_capacity(thetas.len() * omegas.len());
+    for &theta in &thetas {
+        for &omega in &omegas {
+            let mut s = State { theta, omega };
+            for k in 0..periods * spp {
+                s = rk4_step(&s, k as f64 * p.dt, &p).0;
+            }
+            finals.push(s);
+        }
+    }
+    FlowMap {
+        thetas,
+        omegas,
+        periods,
+        duration: (periods * spp) as f64 * p.dt,
+        finals,
+    }
+}
+
+/// 把流映射写成 CSV（列：theta0,omega0,theta,omega,theta_wrapped），theta 为未折叠的末态角度
+pub fn write_flow_map_csv(path: &str, map: &FlowMap) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(f, "theta0,omega0,theta,omega,theta_wrapped")?;
+    for (i, theta0) in map.thetas.iter().enumerate() {
+        for (j, omega0) in map.omegas.iter().enumerate() {
+            let s = map.final_at(i, j);
+            writeln!(
+                f,
+                "{:.12},{:.12},{:.12},{:.12},{:.12}",
+                theta0,
+                omega0,
+                s.theta,
+                s.omega,
+                wrap_angle(s.theta)
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// 网格上的有限时间 Lyapunov 指数场，values 按 θ 为外层、ω 为内层排列
+#[derive(Debug, Clone)]
+pub struct FtleField {
+    pub thetas: Vec<f64>,
+    pub omegas: Vec<f64>,
+    pub duration: f64,
+    pub values: Vec<f64>,
+}
+
+impl FtleField {
+    pub fn value_at(&self, i_theta: usize, i_omega: usize) -> f64 {
+        self.values[i_theta * self.omegas.len() + i_omega]
+    }
+}
+
+// 沿一个网格方向的差分：内部用中心差分，边界用单侧差分；只有一个点时返回 None
+fn difference(n: usize, i: usize, coord: impl Fn(usize) -> f64, value: impl Fn(usize) -> State) -> Option<(f64, f64)> {
+    if n < 2 {
+        return None;
+    }
+    let (a, b) = (i.saturating_sub(1), (i + 1).min(n - 1));
+    let h = coord(b) - coord(a);
+    let (sa, sb) = (value(a), value(b));
+    Some(((sb.theta - sa.theta) / h, (sb.omega - sa.omega) / h))
+}
+
+/// 由流映射计算 FTLE 场：σ = ln √λ_max(C) / T
+///
+/// 流映射的雅可比矩阵 F 用网格上的有限差分估计（列分别为 ∂/∂θ0 与 ∂/∂ω0），
+/// C = FᵀF 为右 Cauchy–Green 张量，λ_max 为其最大特征值。
+/// 网格需在两个方向上都至少有两个点，否则该方向的导数无法估计，相应 FTLE 记为 NaN。
+pub fn ftle_field(map: &FlowMap) -> FtleField {
+    let (n_theta, n_omega) = (map.thetas.len(), map.omegas.len());
+    let mut values = Vec::with_capacity(n_theta * n_omega);
+    for i in 0..n_theta {
+        for j in 0..n_omega {
+            let d_theta0 = difference(n_theta, i, |k| map.thetas[k], |k| map.final_at(k, j));
+            let d_omega0 = difference(n_omega, j, |k| map.omegas[k], |k| map.final_at(i, k));
+            let value = match (d_theta0, d_omega0) {
+                (Some((a, c)), Some((b, d))) => {
+                    // F = [[a, b], [c, d]]，C = FᵀF
+                    let c11 = a * a + c * c;
+                    let c12 = a * b + c * d;
+                    let c22 = b * b + d * d;
+                    let mean = 0.5 * (c11 + c22);
+                    let lambda_max = mean + (0.25 * (c11 - c22).powi(2) + c12 * c12).sqrt();
+                    0.5 * lambda_max.ln() / map.duration
+                }
+                _ => f64::NAN,
+            };
+            values.push(value);
+        }
+    }
+    FtleField {
+        thetas: map.thetas.clone(),
+        omegas: map.omegas.clone(),
+        duration: map.duration,
+        values,
+    }
+}
+
+/// 提取 FTLE 脊线（拉格朗日相干结构的近似）：不小于 min_value，且在 θ 或 ω 方向上为局部极大的格点
+///
+/// 返回 (theta, omega, ftle)。这是脊线的粗略离散近似，网格越细越接近真实的 LCS。
+pub fn ftle_ridges(field: &FtleField, min_value: f64) -> Vec<(f64, f64, f64)> {
+    let (n_theta, n_omega) = (field.thetas.len(), field.omegas.len());
+    let at = |i: usize, j: usize| field.value_at(i, j);
+    let mut out = Vec::new();
+    for i in 0..n_theta {
+        for j in 0..n_omega {
+            let v = at(i, j);
+            if v.is_nan() || v < min_value {
+                continue;
+            }
+            let peak_theta = i > 0 && i + 1 < n_theta && v >= at(i - 1, j) && v >= at(i + 1, j);
+            let peak_omega = j > 0 && j + 1 < n_omega && v >= at(i, j - 1) && v >= at(i, j + 1);
+            if peak_theta || peak_omega {
+                out.push((field.thetas[i], field.omegas[j], v));
+            }
+        }
+    }
+    out
+}
+
+/// 把 FTLE 场写成 CSV（三列：theta,omega,ftle）
+pub fn write_ftle_csv(path: &str, field: &FtleField) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(f, "theta,omega,ftle")?;
+    for (i, theta) in field.thetas.iter().enumerate() {
+        for (j, omega) in field.omegas.iter().enumerate() {
+            writeln!(f, "{:.12},{:.12},{:.12}", theta, omega, field.value_at(i, j))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_flow_map_matches_solve_periods() {
+        let mut params = PendulumParams::new();
+        params.q = 0.5;
+        params.f_d = 1.2;
+        params.omega_d = 2.0 / 3.0;
+        params.dt = 2.0 * PI / params.omega_d / 100.0;
+        params.theta_start = -1.0;
+        params.theta_end = 1.0;
+        params.d_theta = 1.0;
+        params.omega_start = 0.0;
+        params.omega_end = 0.5;
+        params.d_omega = 0.5;
+
+        let map = flow_map(&params, 3);
+        assert_eq!(map.finals.len(), 6);
+        let traj = crate::solve_equation::solve_periods(&params, 1.0, 0.5, 3, 100).unwrap();
+        let (_, last) = traj[traj.len() - 1];
+        let s = map.final_at(2, 1);
+        assert_relative_eq!(s.theta, last.theta, epsilon = 1e-12);
+        assert_relative_eq!(s.omega, last.omega, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_ftle_near_zero_for_small_oscillations_and_positive_in_chaos() {
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.omega_d = 2.0 / 3.0;
+        params.dt = 2.0 * PI / params.omega_d / 100.0;
+        params.theta_start = -0.1;
+        params.theta_end = 0.1;
+        params.d_theta = 0.05;
+        params.omega_start = -0.1;
+        params.omega_end = 0.1;
+        params.d_omega = 0.05;
+
+        // 无阻尼无驱动的小振动近似为旋转，相空间不被拉伸
+        params.q = 0.0;
+        params.f_d = 0.0;
+        let calm = ftle_field(&flow_map(&params, 5));
+        assert!(calm.values.iter().all(|v| v.abs() < 0.05));
+
+        params.q = 0.5;
+        params.f_d = 1.2;
+        let chaotic = ftle_field(&flow_map(&params, 10));
+        let max = chaotic.values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        assert!(max > 0.05);
+        assert!(!ftle_ridges(&chaotic, 0.0).is_empty());
+    }
+}
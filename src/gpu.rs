@@ -0,0 +1,446 @@
+//gpu.rs
+use std::f64::consts::PI;
+use std::sync::mpsc;
+use wgpu::util::DeviceExt;
+use crate::basin::{classify_attractor_section, BasinClassification};
+use crate::bifurcation::sweep_values;
+use crate::chaos_map::ChaosMap;
+use crate::error::ChaosError;
+use crate::model::{DriveType, Forcing, PendulumParams};
+use crate::solve_equation::{steps_per_period, wrap_angle};
+
+// wgpu 计算着色器后端：整张初值网格（吸引域）或参数网格（Lyapunov 指数图）的频闪映射在 GPU 上并行积分。
+// 每个格点一个着色器调用，在 f32 中做 RK4 并同时积分变分方程，回传庞加莱点、最大 Lyapunov 指数与旋转数；
+// 吸引子类型仍在 CPU 上由 basin::classify_attractor_section 判定，与 basin::classify_basin 的规则一致。
+// f32 的轨迹在混沌区约十个周期后就与 f64 逐点无关（见 float.rs），但终态类别与指数是统计量，
+// 在吸引域边界以外与 CPU 结果一致；边界附近个别格点可能落入另一个吸引子。
+// 只支持常数 f_d、q 的正弦驱动，其余波形与时间表返回 InvalidParams。
+
+const SHADER: &str = include_str!("gpu.wgsl");
+const WORKGROUP_SIZE: usize = 64;
+// 每次提交至多积分的格点数：单次提交过长会触发部分驱动的看门狗
+const MAX_BATCH_CELLS: usize = 1 << 15;
+
+/// GPU 网格上的一个格点：初值与该格点的驱动幅度、阻尼
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GpuCell {
+    pub theta: f64,
+    pub omega: f64,
+    pub f_d: f64,
+    pub q: f64,
+}
+
+/// 一个格点的积分结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct GpuCellResult {
+    /// 采样期内的最大 Lyapunov 指数（每个驱动周期重归一化一次）
+    pub exponent: f64,
+    /// 采样期内的旋转数（圈/周期）
+    pub rotation: f64,
+    /// 采样期内每个周期末的庞加莱点 (θ, ω)；不记录时为空
+    pub section: Vec<(f64, f64)>,
+}
+
+/// 已初始化的 GPU 设备与编译好的计算管线，可重复用于多张网格
+pub struct GpuContext {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipeline: wgpu::ComputePipeline,
+    adapter_name: String,
+    max_binding_size: u64,
+}
+
+fn gpu_error(e: impl std::fmt::Display) -> ChaosError {
+    ChaosError::Io(std::io::Error::other(format!("gpu: {}", e)))
+}
+
+impl GpuContext {
+    /// 请求默认的高性能适配器并编译着色器；没有可用的适配器时返回错误
+    pub fn new() -> Result<Self, ChaosError> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::new_without_display_handle_from_env());
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            ..Default::default()
+        }))
+        .map_err(gpu_error)?;
+        let limits = adapter.limits();
+        let (device, queue) = pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor {
+            label: Some("chaos"),
+            required_limits: limits.clone(),
+            ..Default::default()
+        }))
+        .map_err(gpu_error)?;
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("stroboscopic"),
+            source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("stroboscopic"),
+            layout: None,
+            module: &module,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+        Ok(GpuContext {
+            device,
+            queue,
+            pipeline,
+            adapter_name: adapter.get_info().name,
+            max_binding_size: limits.max_storage_buffer_binding_size as u64,
+        })
+    }
+
+    /// 适配器名称，用于输出
+    pub fn adapter_name(&self) -> &str {
+        &self.adapter_name
+    }
+
+    /// 在 GPU 上积分一组格点
+    ///
+    /// 每个格点积分 transient_periods + sample_periods 个整周期（每周期 steps_per_period(params) 步），
+    /// 指数与旋转数只统计后 sample_periods 个周期；record_sections 为 false 时不回传庞加莱点。
+    /// params 中除 f_d、q 外的参数（g、l、Ω、步长）对所有格点相同；积分器固定为 RK4，不看 params.integrator。
+    pub fn stroboscopic_grid(
+        &self,
+        params: &PendulumParams,
+        cells: &[GpuCell],
+        transient_periods: usize,
+        sample_periods: usize,
+        record_sections: bool,
+    ) -> Result<Vec<GpuCellResult>, ChaosError> {
+        check_supported(params)?;
+        let spp = steps_per_period(params);
+        let dt = 2.0 * PI / params.omega_d / spp as f64;
+        let section_bytes = (sample_periods.max(1) * 8) as u64;
+        let batch = MAX_BATCH_CELLS.min((self.max_binding_size / section_bytes) as usize / WORKGROUP_SIZE * WORKGROUP_SIZE);
+        if batch == 0 {
+            return Err(ChaosError::InvalidParams(format!(
+                "{} sample periods per cell exceed the gpu buffer limit",
+                sample_periods
+            )));
+        }
+        let mut out = Vec::with_capacity(cells.len());
+        for chunk in cells.chunks(batch) {
+            let uniforms = pack_uniforms(params, spp, dt, transient_periods, sample_periods, record_sections, chunk.len());
+            let inputs = pack_cells(chunk);
+            let (summary, section) = self.dispatch(&uniforms, &inputs, chunk.len(), sample_periods, record_sections)?;
+            out.extend(unpack_results(&summary, &section, chunk.len(), sample_periods, record_sections));
+        }
+        Ok(out)
+    }
+
+    // 上传一批格点、执行着色器并读回 summary 与 section 两个缓冲区
+    fn dispatch(
+        &self,
+        uniforms: &[u8],
+        inputs: &[f32],
+        n_cells: usize,
+        sample_periods: usize,
+        record_sections: bool,
+    ) -> Result<(Vec<f32>, Vec<f32>), ChaosError> {
+        let storage = wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC;
+        let uniform_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("uniforms"),
+            contents: uniforms,
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let cell_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("cells"),
+            contents: &to_bytes(inputs),
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+        let summary_size = (n_cells * 8) as u64;
+        // 不记录庞加莱点时仍需绑定一个缓冲区，着色器不会写入
+        let section_size = if record_sections { (n_cells * sample_periods * 8) as u64 } else { 8 }.max(8);
+        let summary_buffer = self.storage_buffer("summary", summary_size, storage);
+        let section_buffer = self.storage_buffer("section", section_size, storage);
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("stroboscopic"),
+            layout: &self.pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: uniform_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: cell_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 2, resource: summary_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 3, resource: section_buffer.as_entire_binding() },
+            ],
+        });
+
+        let read_usage = wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST;
+        let summary_read = self.storage_buffer("summary readback", summary_size, read_usage);
+        let section_read = self.storage_buffer("section readback", section_size, read_usage);
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(n_cells.div_ceil(WORKGROUP_SIZE) as u32, 1, 1);
+        }
+        encoder.copy_buffer_to_buffer(&summary_buffer, 0, &summary_read, 0, summary_size);
+        encoder.copy_buffer_to_buffer(&section_buffer, 0, &section_read, 0, section_size);
+        self.queue.submit([encoder.finish()]);
+
+        let summary = self.read_back(&summary_read)?;
+        let section = self.read_back(&section_read)?;
+        Ok((summary, section))
+    }
+
+    fn storage_buffer(&self, label: &str, size: u64, usage: wgpu::BufferUsages) -> wgpu::Buffer {
+        self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label),
+            size,
+            usage,
+            mapped_at_creation: false,
+        })
+    }
+
+    // 映射回读缓冲区并阻塞到 GPU 完成
+    fn read_back(&self, buffer: &wgpu::Buffer) -> Result<Vec<f32>, ChaosError> {
+        let (tx, rx) = mpsc::channel();
+        buffer.map_async(wgpu::MapMode::Read, .., move |r| {
+            let _ = tx.send(r);
+        });
+        self.device.poll(wgpu::PollType::wait_indefinitely()).map_err(gpu_error)?;
+        rx.recv().map_err(gpu_error)?.map_err(gpu_error)?;
+        let values = {
+            let view = buffer.get_mapped_range(..).map_err(gpu_error)?;
+            view.chunks_exact(4)
+                .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .collect()
+        };
+        buffer.unmap();
+        Ok(values)
+    }
+
+    /// basin::classify_basin 的 GPU 版本：在 params 的 θ/ω 网格上判定每个初值的终态吸引子类型
+    pub fn classify_basin(
+        &self,
+        params: &PendulumParams,
+        transient_periods: usize,
+        sample_periods: usize,
+        max_period: usize,
+        tolerance: f64,
+    ) -> Result<BasinClassification, ChaosError> {
+        let thetas = params.theta_grid();
+        let omegas = params.omega_grid();
+        let cells: Vec<GpuCell> = thetas
+            .iter()
+            .flat_map(|&theta| {
+                omegas.iter().map(move |&omega| GpuCell {
+                    theta,
+                    omega,
+                    f_d: params.f_d,
+                    q: params.q,
+                })
+            })
+            .collect();
+        let results = self.stroboscopic_grid(params, &cells, transient_periods, sample_periods, true)?;
+        let classes = results
+            .iter()
+            .map(|r| classify_attractor_section(&r.section, r.rotation, max_period, tolerance))
+            .collect();
+        Ok(BasinClassification {
+            thetas,
+            omegas,
+            classes,
+        })
+    }
+
+    /// chaos_map::lyapunov_chaos_map 的 GPU 版本：(f_d, q) 网格上的最大 Lyapunov 指数，每个格点从 (0, 0) 出发
+    #[allow(clippy::too_many_arguments)]
+    pub fn lyapunov_chaos_map(
+        &self,
+        params: &PendulumParams,
+        f_d_range: (f64, f64),
+        f_d_steps: usize,
+        q_range: (f64, f64),
+        q_steps: usize,
+        transient_periods: usize,
+        sample_periods: usize,
+    ) -> Result<ChaosMap, ChaosError> {
+        let f_ds = sweep_values(f_d_range, f_d_steps);
+        let qs = sweep_values(q_range, q_steps);
+        let cells: Vec<GpuCell> = qs
+            .iter()
+            .flat_map(|&q| {
+                f_ds.iter().map(move |&f_d| GpuCell {
+                    theta: 0.0,
+                    omega: 0.0,
+                    f_d,
+                    q,
+                })
+            })
+            .collect();
+        let results = self.stroboscopic_grid(params, &cells, transient_periods, sample_periods, false)?;
+        Ok(ChaosMap {
+            f_ds,
+            qs,
+            exponents: results.iter().map(|r| r.exponent).collect(),
+        })
+    }
+}
+
+// 校验参数并排除着色器不支持的驱动：只支持常数 f_d、q 的正弦力矩驱动
+fn check_supported(params: &PendulumParams) -> Result<(), ChaosError> {
+    params.validate()?;
+    if !matches!(params.forcing, Forcing::Sine)
+        || params.drive_type != DriveType::Torque
+        || params.f_d_schedule.is_some()
+        || params.q_schedule.is_some()
+    {
+        return Err(ChaosError::InvalidParams(
+            "gpu backend supports only sine torque forcing with constant f_d and q".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+// 与着色器中 Uniforms 的字段顺序一致：三个 f32（g/l、Ω、dt）后接五个 u32
+fn pack_uniforms(
+    params: &PendulumParams,
+    steps_per_period: usize,
+    dt: f64,
+    transient_periods: usize,
+    sample_periods: usize,
+    record_sections: bool,
+    n_cells: usize,
+) -> Vec<u8> {
+    let mut uniforms = to_bytes(&[(params.g / params.l) as f32, params.omega_d as f32, dt as f32]);
+    for n in [steps_per_period, transient_periods, sample_periods, record_sections as usize, n_cells] {
+        uniforms.extend((n as u32).to_le_bytes());
+    }
+    uniforms
+}
+
+// 每个格点四个 f32：θ、ω、f_d、q
+fn pack_cells(cells: &[GpuCell]) -> Vec<f32> {
+    cells
+        .iter()
+        .flat_map(|c| [c.theta, c.omega, c.f_d, c.q].map(|x| x as f32))
+        .collect()
+}
+
+// summary 每格点两个 f32（指数、旋转数）；section 每格点 sample_periods 个 (θ, ω)，θ 折叠到 (-π, π]
+fn unpack_results(
+    summary: &[f32],
+    section: &[f32],
+    n_cells: usize,
+    sample_periods: usize,
+    record_sections: bool,
+) -> Vec<GpuCellResult> {
+    (0..n_cells)
+        .map(|i| GpuCellResult {
+            exponent: summary[2 * i] as f64,
+            rotation: summary[2 * i + 1] as f64,
+            section: if record_sections {
+                section[2 * i * sample_periods..2 * (i + 1) * sample_periods]
+                    .chunks_exact(2)
+                    .map(|p| (wrap_angle(p[0] as f64), p[1] as f64))
+                    .collect()
+            } else {
+                Vec::new()
+            },
+        })
+        .collect()
+}
+
+fn to_bytes(values: &[f32]) -> Vec<u8> {
+    values.iter().flat_map(|x| x.to_le_bytes()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basin::{classify_basin, AttractorClass};
+    use crate::chaos_map::lyapunov_chaos_map;
+    use crate::model::Schedule;
+
+    fn test_params() -> PendulumParams {
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.q = 0.5;
+        params.omega_d = 2.0 / 3.0;
+        params.set_steps_per_period(100);
+        params
+    }
+
+    #[test]
+    fn test_buffer_packing_and_unpacking() {
+        let params = test_params();
+        let uniforms = pack_uniforms(&params, 100, 0.1, 30, 2, true, 3);
+        assert_eq!(uniforms.len(), 32);
+        let word = |k: usize| [uniforms[4 * k], uniforms[4 * k + 1], uniforms[4 * k + 2], uniforms[4 * k + 3]];
+        assert_eq!(f32::from_le_bytes(word(0)), 1.0);
+        assert_eq!(f32::from_le_bytes(word(1)), (2.0f64 / 3.0) as f32);
+        assert_eq!(f32::from_le_bytes(word(2)), 0.1);
+        let counts: Vec<u32> = (3..8).map(|k| u32::from_le_bytes(word(k))).collect();
+        assert_eq!(counts, vec![100, 30, 2, 1, 3]);
+
+        let cells = [
+            GpuCell { theta: 0.1, omega: 0.2, f_d: 1.2, q: 0.5 },
+            GpuCell { theta: -1.0, omega: 3.0, f_d: 0.9, q: 0.25 },
+        ];
+        assert_eq!(pack_cells(&cells), vec![0.1, 0.2, 1.2, 0.5, -1.0, 3.0, 0.9, 0.25]);
+
+        // 两个格点、每格点两个周期；θ = 4 折叠为 4 - 2π
+        let summary = [0.1, 0.0, -0.2, 1.0];
+        let section = [0.5, 1.0, 4.0, -1.0, 0.0, 0.0, -0.5, 2.0];
+        let results = unpack_results(&summary, &section, 2, 2, true);
+        assert_eq!(results[0].exponent, 0.1f32 as f64);
+        assert_eq!(results[1].rotation, 1.0);
+        assert_eq!(results[0].section.len(), 2);
+        assert!((results[0].section[1].0 - (4.0 - 2.0 * PI)).abs() < 1e-6);
+        assert_eq!(results[1].section, vec![(0.0, 0.0), (-0.5, 2.0)]);
+        assert!(unpack_results(&summary, &[0.0; 2], 2, 2, false).iter().all(|r| r.section.is_empty()));
+    }
+
+    #[test]
+    fn test_unsupported_params_are_rejected() {
+        let params = test_params();
+        assert!(check_supported(&params).is_ok());
+        let mut p = params.clone();
+        p.forcing = Forcing::Square;
+        assert!(matches!(check_supported(&p), Err(ChaosError::InvalidParams(_))));
+        let mut p = params.clone();
+        p.drive_type = DriveType::Parametric;
+        assert!(matches!(check_supported(&p), Err(ChaosError::InvalidParams(_))));
+        let mut p = params.clone();
+        p.f_d_schedule = Some(Schedule::Linear { from: 0.0, to: 1.0, t_start: 0.0, t_end: 10.0 });
+        assert!(matches!(check_supported(&p), Err(ChaosError::InvalidParams(_))));
+        let mut p = params.clone();
+        p.q_schedule = Some(Schedule::Linear { from: 0.5, to: 0.1, t_start: 0.0, t_end: 10.0 });
+        assert!(matches!(check_supported(&p), Err(ChaosError::InvalidParams(_))));
+        let mut p = params;
+        p.l = 0.0;
+        assert!(check_supported(&p).is_err());
+    }
+
+    // 需要 GPU 适配器（Vulkan/Metal/DX12）；在有显卡的机器上用 cargo test --features gpu -- --ignored 运行
+    #[test]
+    #[ignore = "needs a gpu adapter"]
+    fn test_gpu_grid_matches_cpu() {
+        let gpu = GpuContext::new().expect("no gpu adapter");
+        let mut params = test_params();
+
+        // 弱驱动下只有周期 1 吸引子，GPU 与 CPU 的分类逐格点相同
+        params.f_d = 0.5;
+        params.theta_start = -1.0;
+        params.theta_end = 1.0;
+        params.d_theta = 0.5;
+        params.omega_start = -1.0;
+        params.omega_end = 1.0;
+        params.d_omega = 0.5;
+        let cpu = classify_basin(&params, 30, 16, 8, 1e-3);
+        let gpu_map = gpu.classify_basin(&params, 30, 16, 8, 1e-3).unwrap();
+        assert_eq!(gpu_map.classes, cpu.classes);
+        assert!(gpu_map.classes.iter().all(|c| *c == AttractorClass::Periodic(1)));
+
+        let cpu = lyapunov_chaos_map(&params, (0.5, 1.2), 1, (0.5, 0.5), 0, 50, 100);
+        let gpu_map = gpu.lyapunov_chaos_map(&params, (0.5, 1.2), 1, (0.5, 0.5), 0, 50, 100).unwrap();
+        assert!((gpu_map.exponents[0] - cpu.exponents[0]).abs() < 1e-2);
+        assert!(gpu_map.exponents[1] > 0.05 && cpu.exponents[1] > 0.05);
+
+        params.forcing = Forcing::Square;
+        assert!(matches!(gpu.classify_basin(&params, 1, 1, 1, 1e-3), Err(ChaosError::InvalidParams(_))));
+    }
+}
@@ -0,0 +1,88 @@
+// gpu.wgsl
+// 每个调用（invocation）负责网格上的一个格点：从 (θ0, ω0) 出发、以该格点的 f_d 与 q 积分
+// transient + samples 个驱动周期，同时积分切向量 (δθ, δω) 的变分方程。
+// 时间只取周期内的局部时间 k·dt（正弦驱动以 T 为周期），f32 下驱动相位不随周期数漂移；
+// θ 在每个周期末折叠到 [-π, π]，折叠前的位移累加为净转角，用于旋转数。
+
+struct Uniforms {
+    g_over_l: f32,
+    omega_d: f32,
+    dt: f32,
+    steps_per_period: u32,
+    transient: u32,
+    samples: u32,
+    record_section: u32,
+    n_cells: u32,
+}
+
+// 相空间状态与切向量
+struct Phase {
+    s: vec2<f32>,
+    v: vec2<f32>,
+}
+
+@group(0) @binding(0) var<uniform> u: Uniforms;
+// 每个格点：θ0, ω0, f_d, q
+@group(0) @binding(1) var<storage, read> cells: array<vec4<f32>>;
+// 每个格点：最大 Lyapunov 指数, 旋转数（圈/周期）
+@group(0) @binding(2) var<storage, read_write> summary: array<vec2<f32>>;
+// 每个格点 samples 个庞加莱点 (θ, ω)，格点为外层
+@group(0) @binding(3) var<storage, read_write> section: array<vec2<f32>>;
+
+const TAU: f32 = 6.28318530717958647692;
+
+fn deriv(p: Phase, t: f32, f_d: f32, q: f32) -> Phase {
+    let ds = vec2<f32>(p.s.y, -u.g_over_l * sin(p.s.x) - q * p.s.y + f_d * sin(u.omega_d * t));
+    let dv = vec2<f32>(p.v.y, -u.g_over_l * cos(p.s.x) * p.v.x - q * p.v.y);
+    return Phase(ds, dv);
+}
+
+fn advance(p: Phase, h: f32, k: Phase) -> Phase {
+    return Phase(p.s + h * k.s, p.v + h * k.v);
+}
+
+fn rk4(p: Phase, t: f32, f_d: f32, q: f32) -> Phase {
+    let h = u.dt;
+    let half = 0.5 * h;
+    let k1 = deriv(p, t, f_d, q);
+    let k2 = deriv(advance(p, half, k1), t + half, f_d, q);
+    let k3 = deriv(advance(p, half, k2), t + half, f_d, q);
+    let k4 = deriv(advance(p, h, k3), t + h, f_d, q);
+    let sixth = h / 6.0;
+    return Phase(
+        p.s + sixth * (k1.s + 2.0 * k2.s + 2.0 * k3.s + k4.s),
+        p.v + sixth * (k1.v + 2.0 * k2.v + 2.0 * k3.v + k4.v),
+    );
+}
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = id.x;
+    if (i >= u.n_cells) {
+        return;
+    }
+    let cell = cells[i];
+    var p = Phase(cell.xy, vec2<f32>(1.0, 0.0));
+    var log_sum = 0.0;
+    var displacement = 0.0;
+    for (var n = 0u; n < u.transient + u.samples; n++) {
+        let theta_start = p.s.x;
+        for (var k = 0u; k < u.steps_per_period; k++) {
+            p = rk4(p, f32(k) * u.dt, cell.z, cell.w);
+        }
+        // 每个周期重归一化切向量；过渡期内的伸长不计入指数
+        let norm = length(p.v);
+        p.v = p.v / norm;
+        if (n >= u.transient) {
+            log_sum += log(norm);
+            displacement += p.s.x - theta_start;
+        }
+        p.s.x -= round(p.s.x / TAU) * TAU;
+        if (n >= u.transient && u.record_section != 0u) {
+            section[i * u.samples + (n - u.transient)] = p.s;
+        }
+    }
+    let periods = f32(max(u.samples, 1u));
+    let period = TAU / u.omega_d;
+    summary[i] = vec2<f32>(log_sum / (periods * period), displacement / TAU / periods);
+}
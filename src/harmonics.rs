@@ -0,0 +1,163 @@
+//harmonics.rs
+use std::fs::File;
+use std::io::Write;
+use crate::bifurcation::{sweep_values, SweepParameter};
+use crate::error::ChaosError;
+use crate::model::PendulumParams;
+use crate::solve_equation::{solve_periods, steps_per_period, State};
+
+/// 响应中的一个频率分量：θ(t) ≈ amplitude · cos(ratio·Ω t - phase)
+#[derive(Debug, Clone, Copy)]
+pub struct Harmonic {
+    /// 相对驱动频率的倍数：n 为高次谐波，1/m 为次谐波
+    pub ratio: f64,
+    pub frequency: f64,
+    pub amplitude: f64,
+    /// 相位，范围 (-π, π]
+    pub phase: f64,
+}
+
+/// 计算 θ(t) 在角频率 omega 处的傅里叶系数 (A, B)
+///
+/// A = (2/T)∫θ cos(ωt) dt，B = (2/T)∫θ sin(ωt) dt，梯形积分；窗口少于两个点时返回 (0, 0)。
+pub fn fourier_component(window: &[(f64, State)], omega: f64) -> (f64, f64) {
+    if window.len() < 2 {
+        return (0.0, 0.0);
+    }
+    let mut a = 0.0;
+    let mut b = 0.0;
+    for pair in window.windows(2) {
+        let (t1, s1) = pair[0];
+        let (t2, s2) = pair[1];
+        let h = t2 - t1;
+        a += 0.5 * h * (s1.theta * (omega * t1).cos() + s2.theta * (omega * t2).cos());
+        b += 0.5 * h * (s1.theta * (omega * t1).sin() + s2.theta * (omega * t2).sin());
+    }
+    let span = window[window.len() - 1].0 - window[0].0;
+    (2.0 * a / span, 2.0 * b / span)
+}
+
+/// 稳态响应的谐波表：Ω/max_subharmonic, …, Ω/2，再接 Ω, 2Ω, …, max_harmonic·Ω
+///
+/// 窗口应包含 max_subharmonic 的整数倍个驱动周期，否则次谐波会有泄漏；
+/// 转动解的 θ 无界增长，谐波分析只对摆动解有意义。
+pub fn harmonic_table(
+    window: &[(f64, State)],
+    omega_d: f64,
+    max_harmonic: usize,
+    max_subharmonic: usize,
+) -> Vec<Harmonic> {
+    let subharmonics = (2..=max_subharmonic).rev().map(|m| 1.0 / m as f64);
+    let harmonics = (1..=max_harmonic).map(|n| n as f64);
+    subharmonics
+        .chain(harmonics)
+        .map(|ratio| {
+            let frequency = ratio * omega_d;
+            let (a, b) = fourier_component(window, frequency);
+            Harmonic {
+                ratio,
+                frequency,
+                amplitude: (a * a + b * b).sqrt(),
+                phase: b.atan2(a),
+            }
+        })
+        .collect()
+}
+
+/// 总谐波畸变 √(Σ_{n≥2} A_n²) / A_1，衡量响应偏离线性的程度；表中没有基频或基频为 0 时返回 None
+pub fn total_harmonic_distortion(table: &[Harmonic]) -> Option<f64> {
+    let fundamental = table.iter().find(|h| h.ratio == 1.0)?.amplitude;
+    if fundamental == 0.0 {
+        return None;
+    }
+    let higher: f64 = table
+        .iter()
+        .filter(|h| h.ratio > 1.0)
+        .map(|h| h.amplitude * h.amplitude)
+        .sum();
+    Some(higher.sqrt() / fundamental)
+}
+
+/// 扫描参数，对每个参数值的稳态窗口（后 sample_periods 个周期）求谐波表
+#[allow(clippy::too_many_arguments)]
+pub fn harmonic_sweep(
+    params: &PendulumParams,
+    parameter: SweepParameter,
+    range: (f64, f64),
+    steps: usize,
+    initial_theta: f64,
+    initial_omega: f64,
+    transient_periods: usize,
+    sample_periods: usize,
+    max_harmonic: usize,
+    max_subharmonic: usize,
+) -> Result<Vec<(f64, Vec<Harmonic>)>, ChaosError> {
+    let spp = steps_per_period(params);
+    sweep_values(range, steps)
+        .into_iter()
+        .map(|value| {
+            let mut p = params.clone();
+            parameter.apply(&mut p, value);
+            let traj = solve_periods(&p, initial_theta, initial_omega, transient_periods + sample_periods, spp)?;
+            let window = &traj[transient_periods * spp..];
+            Ok((value, harmonic_table(window, p.omega_d, max_harmonic, max_subharmonic)))
+        })
+        .collect()
+}
+
+/// 把谐波扫描写成 CSV（列：<参数名>,ratio,frequency,amplitude,phase,thd），每个参数值每个分量一行
+pub fn write_harmonic_sweep_csv(
+    path: &str,
+    parameter: SweepParameter,
+    rows: &[(f64, Vec<Harmonic>)],
+) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(f, "{},ratio,frequency,amplitude,phase,thd", parameter.name())?;
+    for (value, table) in rows {
+        let thd = total_harmonic_distortion(table).map_or(String::new(), |d| format!("{:.12}", d));
+        for h in table {
+            writeln!(
+                f,
+                "{:.12},{:.12},{:.12},{:.12},{:.12},{}",
+                value, h.ratio, h.frequency, h.amplitude, h.phase, thd
+            )?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_harmonic_table_of_synthetic_response() {
+        let omega = 2.0 / 3.0;
+        let period = 2.0 * PI / omega;
+        let n = 8000;
+        // 窗口含 6 个驱动周期，同时是 Ω/2 与 Ω/3 的整周期
+        let window: Vec<(f64, State)> = (0..=n)
+            .map(|i| {
+                let t = 6.0 * period * i as f64 / n as f64;
+                let theta = 0.1
+                    + (omega * t).cos()
+                    + 0.2 * (3.0 * omega * t - 0.5).cos()
+                    + 0.3 * (0.5 * omega * t + 1.0).cos();
+                (t, State { theta, omega: 0.0 })
+            })
+            .collect();
+
+        let table = harmonic_table(&window, omega, 3, 3);
+        let ratios: Vec<f64> = table.iter().map(|h| h.ratio).collect();
+        assert_eq!(ratios, vec![1.0 / 3.0, 0.5, 1.0, 2.0, 3.0]);
+        assert_relative_eq!(table[0].amplitude, 0.0, epsilon = 1e-6);
+        assert_relative_eq!(table[1].amplitude, 0.3, epsilon = 1e-6);
+        assert_relative_eq!(table[1].phase, -1.0, epsilon = 1e-6);
+        assert_relative_eq!(table[2].amplitude, 1.0, epsilon = 1e-6);
+        assert_relative_eq!(table[4].amplitude, 0.2, epsilon = 1e-6);
+        assert_relative_eq!(table[4].phase, 0.5, epsilon = 1e-6);
+        assert_relative_eq!(total_harmonic_distortion(&table).unwrap(), 0.2, epsilon = 1e-6);
+    }
+}
@@ -0,0 +1,140 @@
+//hdf5_export.rs
+use std::str::FromStr;
+use hdf5::types::VarLenUnicode;
+use crate::model::PendulumParams;
+use crate::solve_equation::State;
+
+// 把同一组参数下的轨迹、庞加莱点、分岔图写进同一个 HDF5 文件：
+// 参数作为根组的属性（数值字段逐个存为 f64 属性，另存一份完整 JSON 字符串 params_json），
+// 每个数据集是 N × k 的 f64 矩阵，列名放在数据集的 columns 属性里，
+// 额外的数值信息（初值、过渡周期数等）作为数据集属性。
+// Python 中 h5py.File(path).attrs / f[name][...] 即可读取。
+
+fn h5_error(e: hdf5::Error) -> std::io::Error {
+    std::io::Error::other(e.to_string())
+}
+
+fn write_f64_attr(location: &hdf5::Location, name: &str, value: f64) -> std::io::Result<()> {
+    location
+        .new_attr::<f64>()
+        .shape(())
+        .create(name)
+        .and_then(|attr| attr.write_scalar(&value))
+        .map_err(h5_error)
+}
+
+fn write_string_attr(location: &hdf5::Location, name: &str, value: &str) -> std::io::Result<()> {
+    let text = VarLenUnicode::from_str(value).map_err(|e| std::io::Error::other(e.to_string()))?;
+    location
+        .new_attr::<VarLenUnicode>()
+        .shape(())
+        .create(name)
+        .and_then(|attr| attr.write_scalar(&text))
+        .map_err(h5_error)
+}
+
+/// 一个自描述的 HDF5 结果文件
+pub struct Hdf5Archive {
+    file: hdf5::File,
+}
+
+impl Hdf5Archive {
+    /// 新建（覆盖）文件，并把 params 写成根组属性
+    pub fn create(path: &str, params: &PendulumParams) -> std::io::Result<Self> {
+        let file = hdf5::File::create(path).map_err(h5_error)?;
+        let json = serde_json::to_value(params).map_err(std::io::Error::other)?;
+        write_string_attr(&file, "params_json", &json.to_string())?;
+        write_string_attr(&file, "crate_version", env!("CARGO_PKG_VERSION"))?;
+        if let Some(fields) = json.as_object() {
+            for (name, value) in fields {
+                if let Some(v) = value.as_f64() {
+                    write_f64_attr(&file, name, v)?;
+                }
+            }
+        }
+        Ok(Hdf5Archive { file })
+    }
+
+    // 写一个 rows.len() × columns.len() 的矩阵数据集，并附上列名与额外属性
+    fn write_matrix(
+        &self,
+        name: &str,
+        columns: &[&str],
+        flat: &[f64],
+        attrs: &[(&str, f64)],
+    ) -> std::io::Result<()> {
+        let rows = flat.len() / columns.len();
+        let dataset = self
+            .file
+            .new_dataset::<f64>()
+            .shape([rows, columns.len()])
+            .create(name)
+            .map_err(h5_error)?;
+        dataset.write_raw(flat).map_err(h5_error)?;
+        write_string_attr(&dataset, "columns", &columns.join(","))?;
+        for (key, value) in attrs {
+            write_f64_attr(&dataset, key, *value)?;
+        }
+        Ok(())
+    }
+
+    /// 轨迹数据集（列：t,theta,omega）；attrs 例如 [("theta0", 0.2), ("omega0", 0.0)]
+    pub fn write_trajectory(&self, name: &str, traj: &[(f64, State)], attrs: &[(&str, f64)]) -> std::io::Result<()> {
+        let flat: Vec<f64> = traj.iter().flat_map(|(t, s)| [*t, s.theta, s.omega]).collect();
+        self.write_matrix(name, &["t", "theta", "omega"], &flat, attrs)
+    }
+
+    /// 庞加莱点数据集（列：theta,omega）
+    pub fn write_poincare(&self, name: &str, samples: &[(f64, f64)], attrs: &[(&str, f64)]) -> std::io::Result<()> {
+        let flat: Vec<f64> = samples.iter().flat_map(|(theta, omega)| [*theta, *omega]).collect();
+        self.write_matrix(name, &["theta", "omega"], &flat, attrs)
+    }
+
+    /// 分岔图数据集（列：f_d,theta），points 为 bifurcation_diagram 的输出
+    pub fn write_bifurcation(&self, name: &str, points: &[(f64, f64)], attrs: &[(&str, f64)]) -> std::io::Result<()> {
+        let flat: Vec<f64> = points.iter().flat_map(|(f_d, theta)| [*f_d, *theta]).collect();
+        self.write_matrix(name, &["f_d", "theta"], &flat, attrs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_round_trip() {
+        let mut params = PendulumParams::new();
+        params.f_d = 1.2;
+        let path = std::env::temp_dir().join(format!("chaos_archive_test_{}.h5", std::process::id()));
+        let path = path.to_str().unwrap();
+        let archive = Hdf5Archive::create(path, &params).unwrap();
+        let traj = vec![(0.0, State { theta: 0.2, omega: 0.0 }), (0.01, State { theta: 0.199, omega: -0.1 })];
+        archive.write_trajectory("trajectory", &traj, &[("theta0", 0.2), ("omega0", 0.0)]).unwrap();
+        let samples = vec![(0.1, 0.2), (0.3, 0.4), (0.5, 0.6)];
+        archive.write_poincare("poincare", &samples, &[("transient_periods", 100.0)]).unwrap();
+        archive.write_bifurcation("bifurcation", &[(1.0, -0.5), (1.1, 0.5)], &[]).unwrap();
+        drop(archive);
+
+        let file = hdf5::File::open(path).unwrap();
+        let q: f64 = file.attr("q").unwrap().read_scalar().unwrap();
+        assert_eq!(q, params.q);
+        let json: VarLenUnicode = file.attr("params_json").unwrap().read_scalar().unwrap();
+        let restored: PendulumParams = serde_json::from_str(json.as_str()).unwrap();
+        assert_eq!(restored.f_d, params.f_d);
+
+        let dataset = file.dataset("trajectory").unwrap();
+        assert_eq!(dataset.shape(), vec![2, 3]);
+        assert_eq!(dataset.read_raw::<f64>().unwrap(), vec![0.0, 0.2, 0.0, 0.01, 0.199, -0.1]);
+        let columns: VarLenUnicode = dataset.attr("columns").unwrap().read_scalar().unwrap();
+        assert_eq!(columns.as_str(), "t,theta,omega");
+        let theta0: f64 = dataset.attr("theta0").unwrap().read_scalar().unwrap();
+        assert_eq!(theta0, 0.2);
+
+        let dataset = file.dataset("poincare").unwrap();
+        assert_eq!(dataset.shape(), vec![3, 2]);
+        assert_eq!(dataset.read_raw::<f64>().unwrap(), vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6]);
+        assert_eq!(file.dataset("bifurcation").unwrap().read_raw::<f64>().unwrap(), vec![1.0, -0.5, 1.1, 0.5]);
+        drop(file);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,577 @@
+//io.rs
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use crate::solve_equation::{winding_number, wrap_angle, State};
+
+// 轨迹输出的可插拔后端。几百万个点写成 CSV 又慢又大：
+// 原始二进制每点 24 字节且无需格式化，gzip 压缩的 CSV 兼顾可读性与体积，
+// Parquet（feature = "parquet"）是列式存储，可直接被 pandas / polars / DuckDB 读取。
+// 格式化与写盘都可以交给 AsyncTrajectoryWriter 的后台线程，积分线程只把点按块送进有界通道；
+// 通道满时 write_point 阻塞（背压），内存占用不超过 (queued_chunks + 2) · chunk_size 个点。
+
+/// 逐点写出 (t, State) 的轨迹输出后端
+pub trait TrajectoryWriter {
+    fn write_point(&mut self, t: f64, state: &State) -> std::io::Result<()>;
+
+    /// 写完所有点后调用：刷新缓冲、写入文件尾；之后不应再写点
+    fn finish(&mut self) -> std::io::Result<()>;
+}
+
+impl<W: TrajectoryWriter + ?Sized> TrajectoryWriter for Box<W> {
+    fn write_point(&mut self, t: f64, state: &State) -> std::io::Result<()> {
+        (**self).write_point(t, state)
+    }
+
+    fn finish(&mut self) -> std::io::Result<()> {
+        (**self).finish()
+    }
+}
+
+/// 把整条轨迹（或惰性的 TrajectoryIter）写入 writer 并 finish
+pub fn write_trajectory<I>(writer: &mut dyn TrajectoryWriter, points: I) -> std::io::Result<()>
+where
+    I: IntoIterator<Item = (f64, State)>,
+{
+    for (t, s) in points {
+        writer.write_point(t, &s)?;
+    }
+    writer.finish()
+}
+
+/// 输出抽稀方式：只写出部分积分点，得到适合作图的样本
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Decimation {
+    /// 写出每一步
+    #[default]
+    None,
+    /// 每 k 步写一个点（含第 0 步）
+    Every(usize),
+    /// 至多写 n 个点，均匀抽取
+    MaxPoints(usize),
+}
+
+/// 与 write_trajectory 相同，但按 decimation 抽稀后再写
+///
+/// MaxPoints 时若迭代器给出长度上界（TrajectoryIter、StochasticTrajectory 等），按等间隔步长一次写出；
+/// 否则（如自适应步长）在内存中保留至多 n 个点，超出时隔一个丢一个、步长加倍，最后写出的点数在 n/2 到 n 之间。
+pub fn write_trajectory_decimated<I>(writer: &mut dyn TrajectoryWriter, points: I, decimation: Decimation) -> std::io::Result<()>
+where
+    I: IntoIterator<Item = (f64, State)>,
+{
+    let points = points.into_iter();
+    match decimation {
+        Decimation::None => write_trajectory(writer, points),
+        Decimation::Every(k) => write_trajectory(writer, points.step_by(k.max(1))),
+        Decimation::MaxPoints(n) => {
+            let n = n.max(1);
+            match points.size_hint().1 {
+                Some(len) => write_trajectory(writer, points.step_by(len.div_ceil(n).max(1))),
+                None => write_trajectory(writer, thin_to(points, n)),
+            }
+        }
+    }
+}
+
+// 长度未知时的均匀抽稀：保留下标为 stride 倍数的点，缓冲超过 n 个时丢掉一半并把 stride 加倍
+fn thin_to<I: Iterator<Item = (f64, State)>>(points: I, n: usize) -> Vec<(f64, State)> {
+    let mut kept = Vec::with_capacity(n + 1);
+    let mut stride = 1;
+    for (i, point) in points.enumerate() {
+        if !i.is_multiple_of(stride) {
+            continue;
+        }
+        kept.push(point);
+        if kept.len() > n {
+            let mut j = 0;
+            kept.retain(|_| {
+                j += 1;
+                j % 2 == 1
+            });
+            stride *= 2;
+        }
+    }
+    kept
+}
+
+// CSV 的底层输出：普通文件或 gzip 流
+pub(crate) enum CsvSink {
+    Plain(BufWriter<File>),
+    Gzip(GzEncoder<BufWriter<File>>),
+}
+
+impl CsvSink {
+    pub(crate) fn create(path: &str, gzip: bool) -> std::io::Result<Self> {
+        let file = BufWriter::new(File::create(path)?);
+        Ok(match gzip {
+            true => CsvSink::Gzip(GzEncoder::new(file, Compression::default())),
+            false => CsvSink::Plain(file),
+        })
+    }
+
+    pub(crate) fn out(&mut self) -> &mut dyn Write {
+        match self {
+            CsvSink::Plain(w) => w,
+            CsvSink::Gzip(w) => w,
+        }
+    }
+
+    pub(crate) fn finish(&mut self) -> std::io::Result<()> {
+        match self {
+            CsvSink::Plain(w) => w.flush(),
+            // 写出 gzip 尾部（CRC 与长度）
+            CsvSink::Gzip(w) => {
+                w.try_finish()?;
+                w.get_mut().flush()
+            }
+        }
+    }
+}
+
+/// CSV 输出（三列：t,theta,omega），可选 gzip 压缩；lift 时为五列 t,theta,theta_wrapped,winding,omega
+pub struct CsvTrajectoryWriter {
+    sink: CsvSink,
+    lift: bool,
+}
+
+impl CsvTrajectoryWriter {
+    pub fn create(path: &str) -> std::io::Result<Self> {
+        Self::with_sink(CsvSink::create(path, false)?)
+    }
+
+    /// gzip 压缩的 CSV（通常以 .csv.gz 结尾）
+    pub fn create_gzip(path: &str) -> std::io::Result<Self> {
+        Self::with_sink(CsvSink::create(path, true)?)
+    }
+
+    /// 同时写出展开角 theta（lift）、折叠到 [-π, π) 的 theta_wrapped 与圈数 winding
+    pub fn create_lifted(path: &str, gzip: bool) -> std::io::Result<Self> {
+        let mut writer = CsvTrajectoryWriter { sink: CsvSink::create(path, gzip)?, lift: true };
+        writeln!(writer.out(), "t,theta,theta_wrapped,winding,omega")?;
+        Ok(writer)
+    }
+
+    fn with_sink(sink: CsvSink) -> std::io::Result<Self> {
+        let mut writer = CsvTrajectoryWriter { sink, lift: false };
+        writeln!(writer.out(), "t,theta,omega")?;
+        Ok(writer)
+    }
+
+    fn out(&mut self) -> &mut dyn Write {
+        self.sink.out()
+    }
+}
+
+impl TrajectoryWriter for CsvTrajectoryWriter {
+    fn write_point(&mut self, t: f64, state: &State) -> std::io::Result<()> {
+        if self.lift {
+            let (theta, wrapped, winding) = (state.theta, wrap_angle(state.theta), winding_number(state.theta));
+            return writeln!(self.out(), "{:.12},{:.12},{:.12},{},{:.12}", t, theta, wrapped, winding, state.omega);
+        }
+        writeln!(self.out(), "{:.12},{:.12},{:.12}", t, state.theta, state.omega)
+    }
+
+    fn finish(&mut self) -> std::io::Result<()> {
+        self.sink.finish()
+    }
+}
+
+/// 原始二进制输出：每个点依次为 t、theta、omega 三个小端 f64，无文件头
+pub struct BinaryTrajectoryWriter {
+    out: BufWriter<File>,
+}
+
+impl BinaryTrajectoryWriter {
+    pub fn create(path: &str) -> std::io::Result<Self> {
+        Ok(BinaryTrajectoryWriter {
+            out: BufWriter::new(File::create(path)?),
+        })
+    }
+}
+
+impl TrajectoryWriter for BinaryTrajectoryWriter {
+    fn write_point(&mut self, t: f64, state: &State) -> std::io::Result<()> {
+        for v in [t, state.theta, state.omega] {
+            self.out.write_all(&v.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> std::io::Result<()> {
+        self.out.flush()
+    }
+}
+
+/// 读取 BinaryTrajectoryWriter 写出的文件；长度不是 24 字节整数倍时返回 InvalidData
+pub fn read_binary_trajectory(path: &str) -> std::io::Result<Vec<(f64, State)>> {
+    let mut bytes = Vec::new();
+    BufReader::new(File::open(path)?).read_to_end(&mut bytes)?;
+    if bytes.len() % 24 != 0 {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("{}: length {} is not a multiple of 24 bytes", path, bytes.len()),
+        ));
+    }
+    let value = |chunk: &[u8]| f64::from_le_bytes(chunk.try_into().unwrap());
+    Ok(bytes
+        .chunks_exact(24)
+        .map(|c| {
+            (
+                value(&c[0..8]),
+                State {
+                    theta: value(&c[8..16]),
+                    omega: value(&c[16..24]),
+                },
+            )
+        })
+        .collect())
+}
+
+/// Parquet 输出（三列 t、theta、omega，均为 Float64）
+///
+/// 每攒够 batch_size 个点交给 ArrowWriter 一次，行组大小沿用 parquet 的默认设置。
+#[cfg(feature = "parquet")]
+pub struct ParquetTrajectoryWriter {
+    writer: Option<parquet::arrow::ArrowWriter<File>>,
+    schema: std::sync::Arc<arrow_schema::Schema>,
+    batch_size: usize,
+    columns: [Vec<f64>; 3],
+}
+
+#[cfg(feature = "parquet")]
+impl ParquetTrajectoryWriter {
+    pub const DEFAULT_BATCH_SIZE: usize = 65536;
+
+    pub fn create(path: &str) -> std::io::Result<Self> {
+        use arrow_schema::{DataType, Field, Schema};
+        let schema = std::sync::Arc::new(Schema::new(vec![
+            Field::new("t", DataType::Float64, false),
+            Field::new("theta", DataType::Float64, false),
+            Field::new("omega", DataType::Float64, false),
+        ]));
+        let writer = parquet::arrow::ArrowWriter::try_new(File::create(path)?, schema.clone(), None)
+            .map_err(std::io::Error::other)?;
+        Ok(ParquetTrajectoryWriter {
+            writer: Some(writer),
+            schema,
+            batch_size: Self::DEFAULT_BATCH_SIZE,
+            columns: Default::default(),
+        })
+    }
+
+    fn flush_batch(&mut self) -> std::io::Result<()> {
+        if self.columns[0].is_empty() {
+            return Ok(());
+        }
+        let arrays: Vec<arrow_array::ArrayRef> = self
+            .columns
+            .iter_mut()
+            .map(|c| std::sync::Arc::new(arrow_array::Float64Array::from(std::mem::take(c))) as _)
+            .collect();
+        let batch = arrow_array::RecordBatch::try_new(self.schema.clone(), arrays).map_err(std::io::Error::other)?;
+        match &mut self.writer {
+            Some(w) => w.write(&batch).map_err(std::io::Error::other),
+            None => Err(std::io::Error::other("parquet writer already finished")),
+        }
+    }
+}
+
+#[cfg(feature = "parquet")]
+impl TrajectoryWriter for ParquetTrajectoryWriter {
+    fn write_point(&mut self, t: f64, state: &State) -> std::io::Result<()> {
+        for (column, v) in self.columns.iter_mut().zip([t, state.theta, state.omega]) {
+            column.push(v);
+        }
+        if self.columns[0].len() >= self.batch_size {
+            self.flush_batch()?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> std::io::Result<()> {
+        self.flush_batch()?;
+        if let Some(w) = self.writer.take() {
+            w.close().map_err(std::io::Error::other)?;
+        }
+        Ok(())
+    }
+}
+
+/// AsyncTrajectoryWriter 的设置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AsyncWriterOptions {
+    /// 每块的点数
+    pub chunk_size: usize,
+    /// 通道中最多排队的块数，满时 write_point 阻塞
+    pub queued_chunks: usize,
+}
+
+impl Default for AsyncWriterOptions {
+    fn default() -> Self {
+        AsyncWriterOptions {
+            chunk_size: 8192,
+            queued_chunks: 4,
+        }
+    }
+}
+
+type Chunk = Vec<(f64, State)>;
+
+/// 在后台线程里驱动另一个 TrajectoryWriter：点按块经有界通道送出，格式化与写盘不占积分线程
+///
+/// 后台写出出错时，下一次送块或 finish 返回该错误。未调用 finish 就丢弃时仍会等后台写完并收尾，但错误被忽略。
+pub struct AsyncTrajectoryWriter {
+    chunk: Chunk,
+    chunk_size: usize,
+    sender: Option<std::sync::mpsc::SyncSender<Chunk>>,
+    worker: Option<std::thread::JoinHandle<std::io::Result<()>>>,
+}
+
+impl AsyncTrajectoryWriter {
+    pub fn spawn<W: TrajectoryWriter + Send + 'static>(mut inner: W, opts: &AsyncWriterOptions) -> Self {
+        let chunk_size = opts.chunk_size.max(1);
+        let (sender, receiver) = std::sync::mpsc::sync_channel::<Chunk>(opts.queued_chunks);
+        let worker = std::thread::spawn(move || {
+            for chunk in receiver {
+                for (t, s) in &chunk {
+                    inner.write_point(*t, s)?;
+                }
+            }
+            inner.finish()
+        });
+        AsyncTrajectoryWriter {
+            chunk: Vec::with_capacity(chunk_size),
+            chunk_size,
+            sender: Some(sender),
+            worker: Some(worker),
+        }
+    }
+
+    fn send_chunk(&mut self) -> std::io::Result<()> {
+        if self.chunk.is_empty() {
+            return Ok(());
+        }
+        let chunk = std::mem::replace(&mut self.chunk, Vec::with_capacity(self.chunk_size));
+        match &self.sender {
+            Some(sender) if sender.send(chunk).is_ok() => Ok(()),
+            // 接收端已经关闭：后台线程出错退出，取回它的错误
+            _ => self.join().and(Err(std::io::Error::other("trajectory writer thread stopped"))),
+        }
+    }
+
+    // 关闭通道并等待后台线程写完
+    fn join(&mut self) -> std::io::Result<()> {
+        self.sender = None;
+        match self.worker.take() {
+            Some(worker) => worker
+                .join()
+                .map_err(|_| std::io::Error::other("trajectory writer thread panicked"))?,
+            None => Err(std::io::Error::other("trajectory writer already finished")),
+        }
+    }
+}
+
+impl TrajectoryWriter for AsyncTrajectoryWriter {
+    fn write_point(&mut self, t: f64, state: &State) -> std::io::Result<()> {
+        self.chunk.push((t, *state));
+        if self.chunk.len() >= self.chunk_size {
+            self.send_chunk()?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> std::io::Result<()> {
+        self.send_chunk()?;
+        self.join()
+    }
+}
+
+impl Drop for AsyncTrajectoryWriter {
+    fn drop(&mut self) {
+        if self.worker.is_some() {
+            let _ = self.send_chunk().and_then(|_| self.join());
+        }
+    }
+}
+
+/// 轨迹文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrajectoryFormat {
+    Csv,
+    CsvGzip,
+    Binary,
+    #[cfg(feature = "parquet")]
+    Parquet,
+}
+
+impl TrajectoryFormat {
+    /// 按扩展名推断：.csv.gz、.bin、.parquet（需 feature），其余按 CSV
+    pub fn from_path(path: &str) -> Self {
+        let lower = path.to_ascii_lowercase();
+        if lower.ends_with(".csv.gz") || lower.ends_with(".gz") {
+            TrajectoryFormat::CsvGzip
+        } else if lower.ends_with(".bin") {
+            TrajectoryFormat::Binary
+        } else {
+            #[cfg(feature = "parquet")]
+            if lower.ends_with(".parquet") {
+                return TrajectoryFormat::Parquet;
+            }
+            TrajectoryFormat::Csv
+        }
+    }
+}
+
+/// 按格式创建轨迹输出
+pub fn create_trajectory_writer(path: &str, format: TrajectoryFormat) -> std::io::Result<Box<dyn TrajectoryWriter + Send>> {
+    Ok(match format {
+        TrajectoryFormat::Csv => Box::new(CsvTrajectoryWriter::create(path)?),
+        TrajectoryFormat::CsvGzip => Box::new(CsvTrajectoryWriter::create_gzip(path)?),
+        TrajectoryFormat::Binary => Box::new(BinaryTrajectoryWriter::create(path)?),
+        #[cfg(feature = "parquet")]
+        TrajectoryFormat::Parquet => Box::new(ParquetTrajectoryWriter::create(path)?),
+    })
+}
+
+/// 按格式创建同时写 lift 与折叠角的轨迹输出；只支持 CSV 与 gzip CSV
+pub fn create_lifted_trajectory_writer(
+    path: &str,
+    format: TrajectoryFormat,
+) -> std::io::Result<Box<dyn TrajectoryWriter + Send>> {
+    match format {
+        TrajectoryFormat::Csv => Ok(Box::new(CsvTrajectoryWriter::create_lifted(path, false)?)),
+        TrajectoryFormat::CsvGzip => Ok(Box::new(CsvTrajectoryWriter::create_lifted(path, true)?)),
+        _ => Err(std::io::Error::other("lifted output needs a .csv or .csv.gz path")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+
+    #[test]
+    fn test_binary_roundtrip_and_gzip_csv() {
+        let traj: Vec<(f64, State)> = (0..100)
+            .map(|i| (i as f64 * 0.1, State { theta: (i as f64).sin(), omega: (i as f64).cos() }))
+            .collect();
+        let dir = std::env::temp_dir();
+
+        let bin = dir.join("chaos_io_test.bin");
+        let bin = bin.to_str().unwrap();
+        let mut writer = create_trajectory_writer(bin, TrajectoryFormat::from_path(bin)).unwrap();
+        write_trajectory(writer.as_mut(), traj.iter().copied()).unwrap();
+        let back = read_binary_trajectory(bin).unwrap();
+        assert_eq!(back.len(), traj.len());
+        assert!(back.iter().zip(&traj).all(|(a, b)| a.0 == b.0 && a.1.theta == b.1.theta && a.1.omega == b.1.omega));
+
+        let gz = dir.join("chaos_io_test.csv.gz");
+        let gz = gz.to_str().unwrap();
+        assert_eq!(TrajectoryFormat::from_path(gz), TrajectoryFormat::CsvGzip);
+        let mut writer = create_trajectory_writer(gz, TrajectoryFormat::CsvGzip).unwrap();
+        write_trajectory(writer.as_mut(), traj.iter().copied()).unwrap();
+        let mut text = String::new();
+        GzDecoder::new(File::open(gz).unwrap()).read_to_string(&mut text).unwrap();
+        assert_eq!(text.lines().count(), traj.len() + 1);
+        assert!(text.starts_with("t,theta,omega\n0.000000000000,"));
+    }
+
+    #[test]
+    fn test_lifted_csv_has_both_angles() {
+        let traj = [(0.0, State { theta: 3.0, omega: 1.0 }), (0.1, State { theta: 3.5, omega: 1.0 })];
+        let path = std::env::temp_dir().join("chaos_io_lift_test.csv");
+        let path = path.to_str().unwrap();
+        let mut writer = create_lifted_trajectory_writer(path, TrajectoryFormat::Csv).unwrap();
+        write_trajectory(writer.as_mut(), traj).unwrap();
+        let text = std::fs::read_to_string(path).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "t,theta,theta_wrapped,winding,omega");
+        assert_eq!(lines[2], format!("0.100000000000,3.500000000000,{:.12},1,1.000000000000", 3.5 - 2.0 * std::f64::consts::PI));
+        assert!(create_lifted_trajectory_writer(path, TrajectoryFormat::Binary).is_err());
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_parquet_writer_row_count() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+        let path = std::env::temp_dir().join("chaos_io_test.parquet");
+        let path = path.to_str().unwrap();
+        let mut writer = ParquetTrajectoryWriter::create(path).unwrap();
+        writer.batch_size = 300;
+        let points = (0..1000).map(|i| (i as f64, State { theta: 0.5, omega: -0.5 }));
+        write_trajectory(&mut writer, points).unwrap();
+        let reader = SerializedFileReader::new(File::open(path).unwrap()).unwrap();
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 1000);
+    }
+
+    // 把写出的点收集到内存
+    struct Collect(Vec<(f64, State)>);
+
+    impl TrajectoryWriter for Collect {
+        fn write_point(&mut self, t: f64, state: &State) -> std::io::Result<()> {
+            self.0.push((t, *state));
+            Ok(())
+        }
+        fn finish(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    // 写第 n 个点时出错
+    struct FailAt(usize);
+
+    impl TrajectoryWriter for FailAt {
+        fn write_point(&mut self, _: f64, _: &State) -> std::io::Result<()> {
+            self.0 = self.0.checked_sub(1).ok_or_else(|| std::io::Error::other("disk full"))?;
+            Ok(())
+        }
+        fn finish(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_async_writer_matches_direct_output() {
+        let points = || (0..10_000).map(|i| (i as f64 * 0.01, State { theta: (i as f64).sin(), omega: 0.5 }));
+        let dir = std::env::temp_dir();
+        let direct = dir.join("chaos_io_direct.csv");
+        let threaded = dir.join("chaos_io_async.csv");
+        let mut writer = create_trajectory_writer(direct.to_str().unwrap(), TrajectoryFormat::Csv).unwrap();
+        write_trajectory(writer.as_mut(), points()).unwrap();
+        let inner = create_trajectory_writer(threaded.to_str().unwrap(), TrajectoryFormat::Csv).unwrap();
+        let opts = AsyncWriterOptions { chunk_size: 300, queued_chunks: 1 };
+        write_trajectory(&mut AsyncTrajectoryWriter::spawn(inner, &opts), points()).unwrap();
+        assert_eq!(std::fs::read(&direct).unwrap(), std::fs::read(&threaded).unwrap());
+
+        // 后台写出的错误传回调用方
+        let mut writer = AsyncTrajectoryWriter::spawn(FailAt(1000), &opts);
+        let err = write_trajectory(&mut writer, points()).unwrap_err();
+        assert_eq!(err.to_string(), "disk full");
+        assert!(writer.finish().is_err());
+    }
+
+    #[test]
+    fn test_decimated_output() {
+        let points = || (0..10_001).map(|i| (i as f64, State { theta: 0.0, omega: 0.0 }));
+        let written = |points: &mut dyn Iterator<Item = (f64, State)>, decimation| {
+            let mut out = Collect(Vec::new());
+            write_trajectory_decimated(&mut out, points, decimation).unwrap();
+            out.0.iter().map(|(t, _)| *t as usize).collect::<Vec<_>>()
+        };
+
+        let every = written(&mut points(), Decimation::Every(100));
+        assert_eq!(every.len(), 101);
+        assert_eq!(every[1], 100);
+
+        let budget = written(&mut points(), Decimation::MaxPoints(300));
+        assert!(budget.len() <= 300 && budget.len() > 250);
+        assert!(budget.windows(2).all(|w| w[1] - w[0] == budget[1]));
+
+        // 长度未知：按 2 的幂次步长抽稀，点数在 n/2 到 n 之间且仍等间隔
+        let unknown = written(&mut points().filter(|_| true), Decimation::MaxPoints(300));
+        assert!(unknown.len() <= 300 && unknown.len() >= 150);
+        assert_eq!(unknown[0], 0);
+        assert!(unknown.windows(2).all(|w| w[1] - w[0] == unknown[1]));
+    }
+}
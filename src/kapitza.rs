@@ -0,0 +1,100 @@
+//kapitza.rs
+use std::f64::consts::PI;
+use crate::model::PendulumParams;
+use crate::solve_equation::{wrap_angle, State};
+
+// 参数激励（DriveType::Parametric）的理论参考值。悬点竖直振动时运动方程为
+//   θ'' = -(ω0² + F(t)) sin θ - q θ'，ω0² = g/l，F = f_d sin(Ωt) 为悬点加速度除以摆长。
+// 1. Kapitza 摆：Ω ≫ ω0 时把快变量平均掉，慢运动在有效势
+//      V_eff(θ) = ω0² (1 - cos θ) + f_d² / (4Ω²) · sin² θ
+//    中运动；f_d > √2 Ω ω0（即悬点振幅 a 满足 a²Ω² > 2gl）时倒立位置 θ = π 变为稳定。
+// 2. 参数共振：小角度下方程是阻尼 Mathieu 方程，主共振舌位于 Ω ≈ 2ω0，
+//      |Ω - 2ω0| < √((f_d / 2ω0)² - q²)
+//    时竖直下垂的平衡 θ = 0 失稳，振幅指数增长直到非线性饱和；需要 f_d > 2 q ω0。
+// 两者都是一阶近似，f_d 不小时只作定性参考。
+
+/// 倒立位置被 Kapitza 机制稳定所需的最小驱动幅度 f_d = √2 Ω ω0
+pub fn inverted_stability_threshold(params: &PendulumParams) -> f64 {
+    let omega0 = (params.g / params.l).sqrt();
+    2f64.sqrt() * params.omega_d.abs() * omega0
+}
+
+/// 平均后的有效势 V_eff(θ) = ω0² (1 - cos θ) + f_d² / (4Ω²) · sin² θ（单位质量、除以 l²）
+pub fn effective_potential(theta: f64, params: &PendulumParams) -> f64 {
+    let omega0_sq = params.g / params.l;
+    let s = theta.sin();
+    omega0_sq * (1.0 - theta.cos()) + params.f_d * params.f_d / (4.0 * params.omega_d * params.omega_d) * s * s
+}
+
+/// 倒立位置附近慢振动的角频率 √(f_d² / 2Ω² - ω0²)；未被稳定时返回 None
+pub fn inverted_frequency(params: &PendulumParams) -> Option<f64> {
+    let omega0_sq = params.g / params.l;
+    let k = params.f_d * params.f_d / (2.0 * params.omega_d * params.omega_d) - omega0_sq;
+    (k > 0.0).then(|| k.sqrt())
+}
+
+/// 主参数共振舌在当前 f_d、q 下的驱动频率区间 (Ω_min, Ω_max)；f_d <= 2 q ω0 时没有不稳定区，返回 None
+pub fn principal_resonance_band(params: &PendulumParams) -> Option<(f64, f64)> {
+    let omega0 = (params.g / params.l).sqrt();
+    let half_width_sq = (params.f_d / (2.0 * omega0)).powi(2) - params.q * params.q;
+    (half_width_sq > 0.0).then(|| {
+        let half_width = half_width_sq.sqrt();
+        ((2.0 * omega0 - half_width).max(0.0), 2.0 * omega0 + half_width)
+    })
+}
+
+/// 悬点竖直振动的振幅 a = f_d l / Ω²（正弦驱动）
+pub fn pivot_amplitude(params: &PendulumParams) -> f64 {
+    params.f_d * params.l / (params.omega_d * params.omega_d)
+}
+
+/// 轨迹与倒立位置的最大偏离 max |θ - π|（折叠到 [-π, π)），用来检验倒立是否被稳定；
+/// 稳定时包含悬点振动带来的快速微振动（幅度约 a / l）
+pub fn max_inverted_deviation(traj: &[(f64, State)]) -> f64 {
+    traj.iter().map(|(_, s)| wrap_angle(s.theta - PI).abs()).fold(0.0, f64::max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::DriveType;
+    use crate::solve_equation::solve;
+
+    fn parametric(f_d: f64, omega_d: f64, q: f64, periods: f64) -> PendulumParams {
+        let mut params = PendulumParams::new();
+        params.g = 1.0;
+        params.l = 1.0;
+        params.q = q;
+        params.f_d = f_d;
+        params.omega_d = omega_d;
+        params.drive_type = DriveType::Parametric;
+        params.set_steps_per_period(100);
+        params.t_end = 2.0 * PI / omega_d * periods;
+        params
+    }
+
+    #[test]
+    fn test_kapitza_stabilization_and_parametric_resonance() {
+        // Ω = 20ω0：阈值 f_d ≈ 28.3，高于阈值时倒立稳定，低于阈值时倒下
+        let stable = parametric(45.0, 20.0, 0.1, 300.0);
+        assert!(stable.f_d > inverted_stability_threshold(&stable) && inverted_frequency(&stable).is_some());
+        assert!(effective_potential(PI - 0.1, &stable) > effective_potential(PI, &stable));
+        let traj = solve(&stable, PI - 0.1, 0.0).unwrap();
+        assert!(max_inverted_deviation(&traj) < 0.3);
+        let weak = parametric(15.0, 20.0, 0.1, 300.0);
+        let traj = solve(&weak, PI - 0.1, 0.0).unwrap();
+        assert!(max_inverted_deviation(&traj) > 1.0);
+
+        // Ω = 2ω0 落在共振舌内，小振幅被放大；Ω = 3ω0 在舌外，振幅衰减
+        let resonant = parametric(0.5, 2.0, 0.05, 60.0);
+        let (lo, hi) = principal_resonance_band(&resonant).unwrap();
+        assert!(lo < 2.0 && 2.0 < hi && hi < 3.0);
+        let amplitude = |params: &PendulumParams| {
+            let traj = solve(params, 0.01, 0.0).unwrap();
+            let tail = &traj[traj.len() * 3 / 4..];
+            tail.iter().map(|(_, s)| s.theta.abs()).fold(0.0, f64::max)
+        };
+        assert!(amplitude(&resonant) > 0.1);
+        assert!(amplitude(&parametric(0.5, 3.0, 0.05, 90.0)) < 0.01);
+    }
+}
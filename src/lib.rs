@@ -0,0 +1,98 @@
+//lib.rs
+//! 受迫阻尼单摆 θ'' = -(g/l) sin θ - q θ' + f_d sin(Ω t) 的数值求解与混沌分析（驱动波形可换，见 `model::Forcing`）
+//!
+//! - `model`：参数 `PendulumParams`（可由 config/config.json 反序列化）；`DoublePendulumParams` 见 `double_pendulum`，耦合单摆链 `PendulumChain` 见 `chain`
+//! - `solve_equation`：RK4 积分、庞加莱截面采样、轨迹切片与插值
+//! - `ode`：通用的 `OdeSystem` 接口与 RK4、频闪采样、Lyapunov 指数，可接入自定义方程
+//! - 其余模块为建立在轨迹之上的分析工具（分岔、吸引域、Lyapunov 指数、谱分析等）
+//!
+//! 在自己的程序中依赖本 crate 后即可直接调用，例如
+//! `chaos::poincare_via_solve(&params, 0.2, 0.0, 100, 1000)`，完整示例见 examples/poincare.rs。
+pub mod adaptive;
+pub mod analysis;
+#[cfg(feature = "plot")]
+pub mod animation;
+pub mod arnold;
+pub mod attractor;
+pub mod averaging;
+pub mod basin;
+pub mod bifurcation;
+pub mod bracketing;
+pub mod cache;
+pub mod chain;
+pub mod chaos_map;
+pub mod collector;
+pub mod compare;
+pub mod continuation;
+pub mod checkpoint;
+pub mod density;
+pub mod diagnostics;
+pub mod diffusion;
+pub mod double_pendulum;
+pub mod embedding;
+pub mod ensemble;
+pub mod error;
+pub mod escape;
+pub mod events;
+pub mod experiment;
+pub mod feigenbaum;
+pub mod float;
+pub mod flow_map;
+#[cfg(feature = "gpu")]
+pub mod gpu;
+pub mod harmonics;
+#[cfg(feature = "hdf5")]
+pub mod hdf5_export;
+pub mod intermittency;
+pub mod io;
+pub mod kapitza;
+pub mod lyapunov;
+pub mod manifolds;
+pub mod merge;
+pub mod melnikov;
+pub mod metadata;
+pub mod metric_entropy;
+pub mod model;
+pub mod observable;
+pub mod ode;
+pub mod periodic_orbits;
+pub mod pipeline;
+pub mod plan;
+#[cfg(feature = "plot")]
+pub mod plot;
+#[cfg(feature = "extended-precision")]
+pub mod precision;
+pub mod presets;
+pub mod progress;
+pub mod progressive;
+pub mod random;
+pub mod recurrence;
+pub mod report;
+pub mod resonance;
+pub mod resumable;
+pub mod return_map;
+pub mod roots;
+pub mod separatrix;
+#[cfg(feature = "serve")]
+pub mod serve;
+pub mod solve_equation;
+pub mod spectrum;
+pub mod stats;
+pub mod stochastic;
+pub mod stroboscopic;
+pub mod surrogate;
+pub mod symbolic;
+pub mod timeseries;
+pub mod torus;
+pub mod trajectory;
+pub mod transient;
+#[cfg(feature = "tui")]
+pub mod tui;
+pub mod verify;
+pub mod zero_one;
+
+pub use error::ChaosError;
+pub use model::PendulumParams;
+pub use ode::OdeSystem;
+pub use solve_equation::{poincare, poincare_via_solve, solve, State};
+pub use trajectory::Trajectory;
@@ -0,0 +1,416 @@
+//lyapunov.rs
+use std::fs::File;
+use std::io::Write;
+use crate::model::PendulumParams;
+use crate::solve_equation::{rhs, rk4_step, wrap_angle, State};
+
+/// Benettin 两轨道法的初始分离距离
+pub const INITIAL_SEPARATION: f64 = 1e-8;
+
+// 相空间中两点的距离，θ 差按 2π 折叠
+fn separation(a: &State, b: &State) -> (f64, f64, f64) {
+    let d_theta = wrap_angle(b.theta - a.theta);
+    let d_omega = b.omega - a.omega;
+    (d_theta, d_omega, (d_theta * d_theta + d_omega * d_omega).sqrt())
+}
+
+/// Benettin 两轨道法估计最大 Lyapunov 指数，并记录每次重归一化后的运行估计
+///
+/// 参考轨道与相距 INITIAL_SEPARATION 的扰动轨道一起用 RK4 积分到 params.t_end，
+/// 每隔 renormalization_interval（按 effective_dt() 取整为步数）累加 ln(d / d0) 并把扰动拉回 d0。
+/// 两条轨道重合（d = 0，例如落到同一个稳定不动点）的段与 ode::largest_lyapunov_system 一样不计入累加，
+/// 扰动沿 θ 重新放置；λ(t) 只按计入累加的时间平均，还没有计入任何一段时不记录。
+/// 返回 Vec<(t, λ(t))>，最后一项即为最终估计；初值应已越过过渡期。
+pub fn benettin_trace(
+    params: &PendulumParams,
+    initial_theta: f64,
+    initial_omega: f64,
+    renormalization_interval: f64,
+) -> Vec<(f64, f64)> {
+    // StepsPerPeriod 时按实际步长积分，与 solve 一致
+    let mut params = params.clone();
+    params.dt = params.effective_dt();
+    let params = &params;
+    let steps_per_renorm = ((renormalization_interval / params.dt).round() as usize).max(1);
+    let total_steps = (params.t_end / params.dt) as usize;
+
+    let mut reference = State {
+        theta: initial_theta,
+        omega: initial_omega,
+    };
+    let mut perturbed = State {
+        theta: initial_theta + INITIAL_SEPARATION,
+        omega: initial_omega,
+    };
+    let mut t = 0.0;
+    let mut log_sum = 0.0;
+    let mut counted = 0.0;
+    let mut segment_start = 0.0;
+    let mut trace = Vec::with_capacity(total_steps / steps_per_renorm);
+
+    for step in 1..=total_steps {
+        let (next_ref, next_t) = rk4_step(&reference, t, params);
+        let (next_pert, _) = rk4_step(&perturbed, t, params);
+        reference = next_ref;
+        perturbed = next_pert;
+        t = next_t;
+
+        if step % steps_per_renorm == 0 {
+            let (d_theta, d_omega, d) = separation(&reference, &perturbed);
+            if d > 0.0 {
+                log_sum += (d / INITIAL_SEPARATION).ln();
+                counted += t - segment_start;
+                let scale = INITIAL_SEPARATION / d;
+                perturbed = State {
+                    theta: reference.theta + d_theta * scale,
+                    omega: reference.omega + d_omega * scale,
+                };
+            } else {
+                // 两条轨道在浮点意义上重合，没有方向可以缩放，这一段不计入，沿 θ 重新放置扰动
+                perturbed = State {
+                    theta: reference.theta + INITIAL_SEPARATION,
+                    omega: reference.omega,
+                };
+            }
+            segment_start = t;
+            if counted > 0.0 {
+                trace.push((t, log_sum / counted));
+            }
+        }
+    }
+    trace
+}
+
+/// 最大 Lyapunov 指数的估计结果
+#[derive(Debug, Clone)]
+pub struct LyapunovEstimate {
+    /// 积分到 params.t_end 时的估计值
+    pub exponent: f64,
+    /// 每次重归一化后的 (t, λ(t))，可用来判断是否已收敛
+    pub history: Vec<(f64, f64)>,
+}
+
+// 变分方程 δ' = J δ，J = [[0, 1], [-(g/l) cos θ + F(t) dc/dθ, -q(t)]]（c 见 PendulumParams::drive_coupling）
+fn tangent_rhs(state: &State, tangent: (f64, f64), t: f64, params: &PendulumParams) -> (f64, f64) {
+    let (d_theta, d_omega) = tangent;
+    let stiffness = -(params.g / params.l) * state.theta.cos() + params.drive(t) * params.drive_coupling_slope(state.theta);
+    (d_omega, stiffness * d_theta - params.q_at(t) * d_omega)
+}
+
+// 状态与切向量一起做一步 RK4（切向量的各级斜率使用对应级的状态）
+pub(crate) fn rk4_tangent_step(state: &State, tangent: (f64, f64), t: f64, params: &PendulumParams) -> (State, (f64, f64)) {
+    let h = params.dt;
+    let shift = |s: &State, k: (f64, f64), c: f64| State {
+        theta: s.theta + c * k.0,
+        omega: s.omega + c * k.1,
+    };
+    let add = |v: (f64, f64), k: (f64, f64), c: f64| (v.0 + c * k.0, v.1 + c * k.1);
+
+    let k1 = rhs(state.theta, state.omega, t, params);
+    let l1 = tangent_rhs(state, tangent, t, params);
+    let s2 = shift(state, k1, 0.5 * h);
+    let k2 = rhs(s2.theta, s2.omega, t + 0.5 * h, params);
+    let l2 = tangent_rhs(&s2, add(tangent, l1, 0.5 * h), t + 0.5 * h, params);
+    let s3 = shift(state, k2, 0.5 * h);
+    let k3 = rhs(s3.theta, s3.omega, t + 0.5 * h, params);
+    let l3 = tangent_rhs(&s3, add(tangent, l2, 0.5 * h), t + 0.5 * h, params);
+    let s4 = shift(state, k3, h);
+    let k4 = rhs(s4.theta, s4.omega, t + h, params);
+    let l4 = tangent_rhs(&s4, add(tangent, l3, h), t + h, params);
+
+    let next = State {
+        theta: state.theta + h / 6.0 * (k1.0 + 2.0 * k2.0 + 2.0 * k3.0 + k4.0),
+        omega: state.omega + h / 6.0 * (k1.1 + 2.0 * k2.1 + 2.0 * k3.1 + k4.1),
+    };
+    let next_tangent = (
+        tangent.0 + h / 6.0 * (l1.0 + 2.0 * l2.0 + 2.0 * l3.0 + l4.0),
+        tangent.1 + h / 6.0 * (l1.1 + 2.0 * l2.1 + 2.0 * l3.1 + l4.1),
+    );
+    (next, next_tangent)
+}
+
+/// 用变分方程估计最大 Lyapunov 指数
+///
+/// 与 benettin_trace 相同的重归一化方案，但扰动不是第二条轨道，而是沿参考轨道积分的切向量，
+/// 不受有限分离距离与 θ 折叠的影响。积分到 params.t_end，每隔 renormalization_interval
+/// （按 effective_dt() 取整为步数）把切向量归一化并累加 ln|δ|。初值应已越过过渡期。
+pub fn largest_exponent(
+    params: &PendulumParams,
+    initial_state: State,
+    renormalization_interval: f64,
+) -> LyapunovEstimate {
+    let mut params = params.clone();
+    params.dt = params.effective_dt();
+    let params = &params;
+    let steps_per_renorm = ((renormalization_interval / params.dt).round() as usize).max(1);
+    let total_steps = (params.t_end / params.dt) as usize;
+
+    let mut state = initial_state;
+    let mut tangent = (1.0, 0.0);
+    let mut t = 0.0;
+    let mut log_sum = 0.0;
+    let mut history = Vec::with_capacity(total_steps / steps_per_renorm);
+
+    for step in 1..=total_steps {
+        let (next, next_tangent) = rk4_tangent_step(&state, tangent, t, params);
+        state = next;
+        tangent = next_tangent;
+        t += params.dt;
+
+        if step % steps_per_renorm == 0 {
+            let norm = (tangent.0 * tangent.0 + tangent.1 * tangent.1).sqrt();
+            log_sum += norm.ln();
+            tangent = (tangent.0 / norm, tangent.1 / norm);
+            history.push((t, log_sum / t));
+        }
+    }
+
+    LyapunovEstimate {
+        exponent: history.last().map_or(0.0, |(_, l)| *l),
+        history,
+    }
+}
+
+/// 扩展相空间 (θ, ω, φ = Ωt) 中的完整 Lyapunov 谱
+#[derive(Debug, Clone, Copy)]
+pub struct LyapunovSpectrum {
+    /// 从大到小排列；其中一个对应驱动相位方向，理论值为 0，三者之和理论值为 -q
+    pub exponents: [f64; 3],
+    pub kaplan_yorke_dimension: f64,
+}
+
+type Vec3 = [f64; 3];
+
+// 扩展系统的雅可比矩阵作用在切向量上
+fn extended_tangent_rhs(state: &State, phase: f64, v: &Vec3, params: &PendulumParams) -> Vec3 {
+    let t = phase / params.omega_d;
+    let stiffness = -(params.g / params.l) * state.theta.cos() + params.drive(t) * params.drive_coupling_slope(state.theta);
+    [
+        v[1],
+        stiffness * v[0] - params.q_at(t) * v[1] + params.drive_coupling(state.theta) * params.drive_rate(t) / params.omega_d * v[2],
+        0.0,
+    ]
+}
+
+// 状态与三个切向量一起做一步 RK4
+fn rk4_extended_step(state: &State, vectors: &[Vec3; 3], t: f64, params: &PendulumParams) -> (State, [Vec3; 3]) {
+    let h = params.dt;
+    let shift = |s: &State, k: (f64, f64), c: f64| State {
+        theta: s.theta + c * k.0,
+        omega: s.omega + c * k.1,
+    };
+    let k1 = rhs(state.theta, state.omega, t, params);
+    let s2 = shift(state, k1, 0.5 * h);
+    let k2 = rhs(s2.theta, s2.omega, t + 0.5 * h, params);
+    let s3 = shift(state, k2, 0.5 * h);
+    let k3 = rhs(s3.theta, s3.omega, t + 0.5 * h, params);
+    let s4 = shift(state, k3, h);
+    let k4 = rhs(s4.theta, s4.omega, t + h, params);
+    let next = State {
+        theta: state.theta + h / 6.0 * (k1.0 + 2.0 * k2.0 + 2.0 * k3.0 + k4.0),
+        omega: state.omega + h / 6.0 * (k1.1 + 2.0 * k2.1 + 2.0 * k3.1 + k4.1),
+    };
+
+    let stages = [(state, t), (&s2, t + 0.5 * h), (&s3, t + 0.5 * h), (&s4, t + h)];
+    let next_vectors = vectors.map(|v| {
+        let f = |s: &State, tt: f64, u: &Vec3| extended_tangent_rhs(s, params.omega_d * tt, u, params);
+        let axpy = |u: &Vec3, k: &Vec3, c: f64| [u[0] + c * k[0], u[1] + c * k[1], u[2] + c * k[2]];
+        let l1 = f(stages[0].0, stages[0].1, &v);
+        let l2 = f(stages[1].0, stages[1].1, &axpy(&v, &l1, 0.5 * h));
+        let l3 = f(stages[2].0, stages[2].1, &axpy(&v, &l2, 0.5 * h));
+        let l4 = f(stages[3].0, stages[3].1, &axpy(&v, &l3, h));
+        [0, 1, 2].map(|i| v[i] + h / 6.0 * (l1[i] + 2.0 * l2[i] + 2.0 * l3[i] + l4[i]))
+    });
+    (next, next_vectors)
+}
+
+fn dot(a: &Vec3, b: &Vec3) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+// 改进的 Gram–Schmidt 正交归一化，返回各向量正交化后的长度
+fn gram_schmidt(vectors: &mut [Vec3; 3]) -> Vec3 {
+    let mut norms = [0.0; 3];
+    for i in 0..3 {
+        for j in 0..i {
+            let p = dot(&vectors[i], &vectors[j]);
+            let u = vectors[j];
+            for (x, y) in vectors[i].iter_mut().zip(&u) {
+                *x -= p * y;
+            }
+        }
+        norms[i] = dot(&vectors[i], &vectors[i]).sqrt();
+        for x in vectors[i].iter_mut() {
+            *x /= norms[i];
+        }
+    }
+    norms
+}
+
+/// Kaplan–Yorke 维数 D = j + (λ_1 + … + λ_j) / |λ_{j+1}|，j 为部分和仍非负的最大下标
+///
+/// exponents 需从大到小排列；全部部分和都非负时返回指数个数，λ_1 < 0 时返回 0。
+pub fn kaplan_yorke_dimension(exponents: &[f64]) -> f64 {
+    let mut sum = 0.0;
+    for (j, lambda) in exponents.iter().enumerate() {
+        if sum + lambda < 0.0 {
+            return j as f64 + sum / lambda.abs();
+        }
+        sum += lambda;
+    }
+    exponents.len() as f64
+}
+
+/// 在扩展相空间 (θ, ω, φ) 中沿轨道积分三个切向量，定期 Gram–Schmidt 正交化，得到完整 Lyapunov 谱
+///
+/// 积分到 params.t_end，每隔 renormalization_interval（按 effective_dt() 取整为步数）正交化一次并累加各方向的 ln 伸长。
+/// 初值应已越过过渡期。
+pub fn spectrum(params: &PendulumParams, initial_state: State, renormalization_interval: f64) -> LyapunovSpectrum {
+    let mut params = params.clone();
+    params.dt = params.effective_dt();
+    let params = &params;
+    let steps_per_renorm = ((renormalization_interval / params.dt).round() as usize).max(1);
+    let total_steps = (params.t_end / params.dt) as usize;
+
+    let mut state = initial_state;
+    let mut vectors = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+    let mut log_sums = [0.0; 3];
+    let mut t = 0.0;
+    let mut t_last = 0.0;
+    for step in 1..=total_steps {
+        let (next, next_vectors) = rk4_extended_step(&state, &vectors, t, params);
+        state = next;
+        vectors = next_vectors;
+        t += params.dt;
+        if step % steps_per_renorm == 0 {
+            let norms = gram_schmidt(&mut vectors);
+            for (s, n) in log_sums.iter_mut().zip(&norms) {
+                *s += n.ln();
+            }
+            t_last = t;
+        }
+    }
+
+    let mut exponents = log_sums.map(|s| if t_last > 0.0 { s / t_last } else { 0.0 });
+    exponents.sort_by(|a, b| b.total_cmp(a));
+    LyapunovSpectrum {
+        exponents,
+        kaplan_yorke_dimension: kaplan_yorke_dimension(&exponents),
+    }
+}
+
+/// 把 λ(t) 收敛曲线写成 CSV（两列：t,lambda）
+pub fn write_lyapunov_trace_csv(path: &str, trace: &[(f64, f64)]) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(f, "t,lambda")?;
+    for (t, lambda) in trace {
+        writeln!(f, "{:.12},{:.12}", t, lambda)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::StepControl;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_benettin_sign_for_periodic_and_chaotic_regimes() {
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.q = 0.5;
+        params.omega_d = 2.0 / 3.0;
+        let period = 2.0 * PI / params.omega_d;
+        params.dt = period / 100.0;
+        params.t_end = 300.0 * period;
+
+        params.f_d = 0.5;
+        let periodic = benettin_trace(&params, 0.2, 0.0, period);
+        assert_eq!(periodic.len(), 300);
+        assert!(periodic.last().unwrap().1 < 0.0);
+
+        params.f_d = 1.2;
+        let chaotic = benettin_trace(&params, 0.2, 0.0, period);
+        assert!(chaotic.last().unwrap().1 > 0.05);
+    }
+
+    #[test]
+    fn test_benettin_survives_merged_orbits() {
+        // 无驱动的临界阻尼摆停在不动点 (0, 0)：两次重归一化之间扰动下溢到 0，两条轨道重合
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.q = 2.0;
+        params.f_d = 0.0;
+        params.dt = 0.05;
+        params.t_end = 3000.0;
+        let merged = benettin_trace(&params, 0.0, 0.0, 1000.0);
+        assert!(merged.iter().all(|(_, lambda)| lambda.is_finite()));
+        assert!(merged.is_empty());
+
+        // 重归一化足够频繁时不会重合，指数明显为负
+        let trace = benettin_trace(&params, 0.0, 0.0, 10.0);
+        assert_eq!(trace.len(), 300);
+        assert!(trace.iter().all(|(_, lambda)| lambda.is_finite()));
+        assert!(trace.last().unwrap().1 < -0.5);
+    }
+
+    #[test]
+    fn test_variational_exponent_agrees_with_benettin() {
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.q = 0.5;
+        params.f_d = 1.2;
+        params.omega_d = 2.0 / 3.0;
+        let period = 2.0 * PI / params.omega_d;
+        params.dt = period / 100.0;
+        params.t_end = 400.0 * period;
+
+        let estimate = largest_exponent(&params, State { theta: 0.2, omega: 0.0 }, period);
+        assert_eq!(estimate.history.len(), 400);
+        let benettin = benettin_trace(&params, 0.2, 0.0, period).last().unwrap().1;
+        assert!(estimate.exponent > 0.05);
+        assert!((estimate.exponent - benettin).abs() < 0.03);
+
+        // 无阻尼线性化的小振动：指数趋于 0
+        params.q = 0.0;
+        params.f_d = 0.0;
+        let calm = largest_exponent(&params, State { theta: 0.01, omega: 0.0 }, period);
+        assert!(calm.exponent.abs() < 0.01);
+
+        // 按每周期步数设定步长后再改 omega_d：积分按 effective_dt() 跟随新的周期，而不是沿用旧的 dt
+        params.q = 0.5;
+        params.f_d = 1.2;
+        params.t_end = 50.0 * period;
+        params.set_steps_per_period(100);
+        params.omega_d = 0.7;
+        let mut fixed = params.clone();
+        fixed.step_control = StepControl::FixedDt;
+        fixed.dt = params.effective_dt();
+        let start = State { theta: 0.2, omega: 0.0 };
+        assert_eq!(largest_exponent(&params, start, period).exponent, largest_exponent(&fixed, start, period).exponent);
+        assert_eq!(benettin_trace(&params, 0.2, 0.0, period), benettin_trace(&fixed, 0.2, 0.0, period));
+        assert_eq!(spectrum(&params, start, period).exponents, spectrum(&fixed, start, period).exponents);
+    }
+
+    #[test]
+    fn test_spectrum_sums_to_minus_damping() {
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.q = 0.5;
+        params.f_d = 1.2;
+        params.omega_d = 2.0 / 3.0;
+        let period = 2.0 * PI / params.omega_d;
+        params.dt = period / 100.0;
+        params.t_end = 400.0 * period;
+
+        let s = spectrum(&params, State { theta: 0.2, omega: 0.0 }, period);
+        assert!((s.exponents.iter().sum::<f64>() + params.q).abs() < 1e-6);
+        assert!(s.exponents[0] > 0.05);
+        assert!(s.exponents[1].abs() < 0.02);
+        assert!(s.kaplan_yorke_dimension > 2.0 && s.kaplan_yorke_dimension < 3.0);
+
+        assert_eq!(kaplan_yorke_dimension(&[0.2, 0.0, -0.5]), 2.4);
+        assert_eq!(kaplan_yorke_dimension(&[-0.1, -0.2]), 0.0);
+    }
+}
@@ -1,49 +1,1787 @@
 //main.rs
-mod model;
-mod solve_equation;
+use std::f64::consts::PI;
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use chaos::arnold::{arnold_tongues, write_arnold_tongues_csv, write_arnold_tongues_png};
+use chaos::bifurcation::{
+    bifurcation_diagram_parallel, bifurcation_diagram_resumable, bifurcation_diagram_with_progress, ramp_sweep_with_progress, write_bifurcation_csv, write_ramp_csv,
+    SweepParameter,
+};
+use chaos::basin::{search_attractors, write_found_attractors_csv, AttractorClass, AttractorSearchOptions};
+use chaos::bracketing::{bracket_transitions, write_transitions_csv, BracketOptions, RefineMethod};
+use chaos::cache::{CacheKey, ResultCache};
+use chaos::chain::{random_initial, solve_chain, synchronization, write_spacetime_csv, write_sync_csv};
+use chaos::checkpoint::Checkpoint;
+use chaos::collector::create_row_sink;
+use chaos::compare::{compare_integrators, CompareOptions, Scheme};
+use chaos::density::{poincare_density, write_density_matrix_csv, write_density_png};
+use chaos::diffusion::{ensemble_msd, fit_diffusion, stroboscopic_lift, time_window_msd, write_msd_csv};
+use chaos::error::ChaosError;
+use chaos::escape::{escape_time_map, write_escape_matrix_csv, write_escape_png};
+use chaos::experiment::Experiment;
+use chaos::melnikov::{critical_forcing, threshold_curve, write_threshold_curve_csv};
+use chaos::metadata::RunMetadata;
+use chaos::model::{
+    ChainBoundary, DriveType, Forcing, InitialConditionSampling, PendulumChain, PendulumParams, Schedule, StepControl, TimeDirection,
+};
+use chaos::observable::{builtin_observable, write_poincare_observables, ObservableTrajectoryWriter, BUILTIN_OBSERVABLES};
+use chaos::periodic_orbits::monodromy;
+use chaos::io::{
+    create_lifted_trajectory_writer, create_trajectory_writer, write_trajectory_decimated, AsyncTrajectoryWriter,
+    AsyncWriterOptions, Decimation, TrajectoryFormat, TrajectoryWriter,
+};
+use chaos::plan::{
+    check_memory_budget, estimated_steps, format_bytes, parse_byte_size, poincare_cost, trajectory_cost,
+    DEFAULT_MEMORY_BUDGET_BYTES,
+};
+use chaos::presets::{preset_names, PRESETS};
+use chaos::progress::{with_progress, ProgressInfo};
+use chaos::random::{seeded_rng, RandomSource};
+use chaos::report::{diagnostics_report, ReportOptions};
+use chaos::separatrix::{
+    energy_surface, pendulum_energy, separatrix_energy, write_energy_contours_csv, write_energy_surface_csv,
+    write_separatrix_csv,
+};
+use chaos::solve_equation::{
+    poincare, poincare_runs, poincare_with_progress, steps_per_period, wrap_angle, write_poincare_runs_csv,
+    write_poincare_samples_csv, State, TrajectoryIter,
+};
+use chaos::stochastic::StochasticTrajectory;
+use chaos::stats::{summary, write_ecdf_csv, write_histogram_csv, Bins, Ecdf, Histogram};
+use chaos::symbolic::{
+    block_entropies, entropy_estimates, reliable_block_length, symbolize, write_block_entropy_csv, Coordinate, Partition,
+};
+use chaos::torus::{detect_torus, TorusOptions};
+use chaos::transient::{poincare_auto_transient, TransientOptions};
+use indicatif::ProgressBar;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 
-use crate::model::PendulumParams;
-use crate::solve_equation::{poincare_via_solve, write_poincare_csv};
+/// 受迫阻尼单摆的数值实验
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    /// 把所有输出写进新建的实验目录 <runs-dir>/<UTC 时间戳>-<名称>/，附 manifest.json、params.json 与 run.log，并登记到 <runs-dir>/index.jsonl
+    #[arg(long, global = true)]
+    experiment: Option<String>,
+    /// 实验目录的根目录
+    #[arg(long, global = true, default_value = "runs")]
+    runs_dir: String,
+    /// 不给子命令时按默认参数计算庞加莱截面
+    #[command(subcommand)]
+    command: Option<Command>,
+}
 
-fn main() {
-    // 设置参数与初始条件（可以根据需要调整）
-    let mut params = PendulumParams::new();
-    // 例如启用驱动/阻尼为默认值
-    params.q = 0.5;
-    params.l = 9.8;
-    params.f_d = 1.2;
-    params.omega_d = 2.0/3.0;
-    params.t_end = 10000.0;
-
-    let initial_theta = 1.0;
-    let initial_omega = 0.0;
-
-    // 采样设置：丢弃的过渡周期与采样周期数
-    let transient_periods = 100_usize;
-    let sample_periods = 2000_usize;
-    let period = 2.0 * std::f64::consts::PI / params.omega_d;
-    params.dt = period / 400.0;
-    params.t_end = period * (transient_periods + sample_periods) as f64 + params.dt;
-
-    // 方式 A：直接计算并写出 CSV 到 chaos/data 目录
-    let out_dir = "data";
-    // 确保目录存在
-    if let Err(e) = std::fs::create_dir_all(out_dir) {
-        eprintln!("Failed to create output directory {}: {}", out_dir, e);
-        return;
-    }
-    let out_path = format!("{}/poincare.csv", out_dir);
-    match write_poincare_csv(
-        &out_path,
-        &params,
-        initial_theta,
-        initial_omega,
-        transient_periods,
-        sample_periods,
-    ) {
-        Ok(()) => println!("Wrote Poincaré data to {}", out_path),
-        Err(e) => eprintln!("Failed to write CSV: {}", e),
+#[derive(Subcommand)]
+enum Command {
+    /// 庞加莱截面（两列：theta,omega）
+    Poincare {
+        #[command(flatten)]
+        common: Common,
+        #[command(flatten)]
+        sampling: Sampling,
+        #[arg(long, default_value = "data/poincare.csv")]
+        out: String,
+        /// 初值 θ,ω，可重复给出：每个初值一个 run，CSV 多一列 run_id，图中按 run 着色（代替 --theta0/--omega0）
+        #[arg(long, value_parser = parse_initial_condition, allow_hyphen_values = true, conflicts_with = "checkpoint")]
+        ic: Vec<(f64, f64)>,
+        /// 自动检测过渡期（庞加莱点落到吸引子上为止），代替 --transient；实际丢弃的周期数写进元数据
+        #[arg(long, conflicts_with_all = ["checkpoint", "ic"])]
+        auto_transient: bool,
+        /// 断点文件：每 --checkpoint-every 个周期写一次积分状态与已收集的庞加莱点
+        #[arg(long)]
+        checkpoint: Option<String>,
+        #[arg(long, default_value_t = 1000)]
+        checkpoint_every: usize,
+        /// 从 --checkpoint 给出的断点文件继续（参数与采样设置取自断点文件，命令行上的忽略）
+        #[arg(long, requires = "checkpoint")]
+        resume: bool,
+        /// 同时输出庞加莱点的密度图（不变测度）：.png 为图片，其余为 CSV 矩阵
+        #[arg(long)]
+        density: Option<String>,
+        /// 密度图 θ、ω 方向的格子数
+        #[arg(long, default_value_t = 400)]
+        density_bins: usize,
+        /// 同时出图（.svg 为 SVG，其余为 PNG）
+        #[cfg(feature = "plot")]
+        #[arg(long)]
+        figure: Option<String>,
+        /// 只打印积分步数、内存与输出大小的估计，不积分
+        #[arg(long, conflicts_with = "resume")]
+        dry_run: bool,
+        /// 追加的输出列（逗号分隔：energy、sin_theta、cos_theta、theta_wrapped、drive_phase、drive）；
+        /// 给出时输出改为 t,theta,omega,<各列>，格式按扩展名（.csv、.csv.gz、.parquet）
+        #[arg(long, value_delimiter = ',', value_parser = parse_observable_name, conflicts_with = "ic")]
+        observable: Vec<String>,
+    },
+    /// 完整轨迹（t,theta,omega）；按扩展名选择格式：.csv、.csv.gz、.bin（小端 f64），启用 parquet feature 时还支持 .parquet
+    Trajectory {
+        #[command(flatten)]
+        common: Common,
+        /// 积分时长
+        #[arg(long, default_value_t = 100.0)]
+        t_end: f64,
+        #[arg(long, default_value = "data/trajectory.csv")]
+        out: String,
+        /// 每 k 步写一个点
+        #[arg(long, conflicts_with = "max_points")]
+        every: Option<usize>,
+        /// 至多写出的点数（均匀抽稀），用于只需要作图分辨率的长时间积分
+        #[arg(long)]
+        max_points: Option<usize>,
+        /// CSV 同时写展开角与折叠角：t,theta,theta_wrapped,winding,omega（theta 为 lift，winding 为圈数）
+        #[arg(long)]
+        lift: bool,
+        /// 后台写出线程每块的点数；0 表示在积分线程上直接写
+        #[arg(long, default_value_t = 8192)]
+        chunk_size: usize,
+        /// 只打印积分步数、内存与输出大小的估计，不积分
+        #[arg(long)]
+        dry_run: bool,
+        /// 追加的输出列（逗号分隔：energy、sin_theta、cos_theta、theta_wrapped、drive_phase、drive）
+        #[arg(long, value_delimiter = ',', value_parser = parse_observable_name, conflicts_with = "lift")]
+        observable: Vec<String>,
+    },
+    /// 驱动幅度分岔图（两列：f_d,theta）
+    Bifurcation {
+        #[command(flatten)]
+        common: Common,
+        #[command(flatten)]
+        sampling: Sampling,
+        #[arg(long, default_value_t = 0.9)]
+        fd_min: f64,
+        #[arg(long, default_value_t = 1.5)]
+        fd_max: f64,
+        /// f_d 的取值个数减一
+        #[arg(long, default_value_t = 300)]
+        steps: usize,
+        #[arg(long, default_value = "data/bifurcation.csv")]
+        out: String,
+        /// 每算完一个 f_d 就追加写入 out；out 是上次中断留下的部分结果时跳过已算完的 f_d 接着算
+        #[arg(long, conflicts_with = "cache")]
+        resumable: bool,
+        /// 各 f_d 都从 (0, 0) 冷启动、并行计算，按 f_d 顺序写入同一个文件（.csv、.csv.gz 或 .parquet）
+        #[arg(long, conflicts_with_all = ["cache", "resumable"])]
+        parallel: bool,
+        /// 同时出图（.svg 为 SVG，其余为 PNG）
+        #[cfg(feature = "plot")]
+        #[arg(long)]
+        figure: Option<String>,
+    },
+    /// 分岔点定位：粗网格上找出吸引子周期变化的区间，再在区间内加密到给定精度
+    /// （列：<参数名>,lower,upper,period_before,period_after,method,evaluations）
+    Bracket {
+        #[command(flatten)]
+        common: Common,
+        /// 扫描的参数：f_d、q 或 omega_d
+        #[arg(long, value_parser = parse_sweep_parameter, default_value = "f_d")]
+        parameter: SweepParameter,
+        #[arg(long, default_value_t = 1.05)]
+        from: f64,
+        #[arg(long, default_value_t = 1.08)]
+        to: f64,
+        /// 粗网格的取值个数减一
+        #[arg(long, default_value_t = 20)]
+        steps: usize,
+        /// 每次判定丢弃的过渡周期数
+        #[arg(long, default_value_t = 300)]
+        transient: usize,
+        /// 每次判定的采样周期数
+        #[arg(long, default_value_t = 64)]
+        samples: usize,
+        /// 阈值区间的目标宽度
+        #[arg(long, default_value_t = 1e-6)]
+        tolerance: f64,
+        #[arg(long, value_enum, default_value_t = Refinement::Secant)]
+        method: Refinement,
+        #[arg(long, default_value = "data/thresholds.csv")]
+        out: String,
+    },
+    /// 动态分岔图：一次积分中 f_d 从 fd_from 线性变到 fd_to，每周期记录一点（t,f_d,q,theta,omega）
+    Ramp {
+        #[command(flatten)]
+        common: Common,
+        #[arg(long, default_value_t = 0.9)]
+        fd_from: f64,
+        #[arg(long, default_value_t = 1.5)]
+        fd_to: f64,
+        /// 扫描经历的驱动周期数（越多扫描越慢）
+        #[arg(long, default_value_t = 3000)]
+        periods: usize,
+        #[arg(long, default_value = "data/ramp.csv")]
+        out: String,
+    },
+    /// Melnikov 阈值：同宿缠结出现的临界驱动幅度随驱动频率的曲线（两列：omega_d,f_critical）
+    Melnikov {
+        #[command(flatten)]
+        common: Common,
+        #[arg(long, default_value_t = 0.1)]
+        omega_from: f64,
+        #[arg(long, default_value_t = 2.0)]
+        omega_to: f64,
+        /// 频率的取值个数减一
+        #[arg(long, default_value_t = 200)]
+        steps: usize,
+        #[arg(long, default_value = "data/melnikov_threshold.csv")]
+        out: String,
+    },
+    /// 角度扩散：展开角的均方位移 MSD(t)（列：t,msd,mean_displacement,variance,count）与扩散系数 D
+    Diffusion {
+        #[command(flatten)]
+        common: Common,
+        /// 系综成员数；初值在 (theta0, omega0) 附近按 --spread 高斯分布
+        #[arg(long, default_value_t = 200)]
+        members: usize,
+        #[arg(long, default_value_t = 0.1)]
+        spread: f64,
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+        /// 改为单条轨道的时间平均（最大间隔为 --periods 的 1/10）
+        #[arg(long)]
+        single: bool,
+        /// 丢弃的过渡周期数
+        #[arg(long, default_value_t = 100)]
+        transient: usize,
+        /// 过渡期之后积分的周期数
+        #[arg(long, default_value_t = 1000)]
+        periods: usize,
+        /// 拟合从第几个周期的间隔开始（跳过短时间的弹道段）
+        #[arg(long, default_value_t = 20)]
+        fit_from: usize,
+        #[arg(long, default_value = "data/msd.csv")]
+        out: String,
+    },
+    /// 首次通过时间图：每个网格初值的 θ 第一次偏离初值 --threshold（默认转满一圈）的时刻，矩阵 CSV（行为 ω、列为 θ）
+    Escape {
+        #[command(flatten)]
+        common: Common,
+        /// θ 与 ω 方向各取的格点数，覆盖 [-π, π] × [-omega_max, omega_max]
+        #[arg(long, default_value_t = 200)]
+        cells: usize,
+        #[arg(long, default_value_t = 3.0)]
+        omega_max: f64,
+        /// 角度阈值（弧度）[默认: 2π]
+        #[arg(long)]
+        threshold: Option<f64>,
+        /// 最长积分时间，之前未到达的格点记为 NaN
+        #[arg(long, default_value_t = 200.0)]
+        t_max: f64,
+        #[arg(long, default_value = "data/escape.csv")]
+        out: String,
+        /// 同时输出 PNG（对数色标，未到达为黑色）
+        #[arg(long)]
+        png: Option<String>,
+    },
+    /// 无驱动、无阻尼单摆的能量面 H(θ, ω)（矩阵 CSV）、若干条等能线与分界线，用来与驱动系统的相图叠加对照
+    Energy {
+        #[command(flatten)]
+        common: Common,
+        /// θ 与 ω 方向各取的格点数，覆盖 [-π, π] × [-omega_max, omega_max]
+        #[arg(long, default_value_t = 200)]
+        cells: usize,
+        #[arg(long, default_value_t = 3.0)]
+        omega_max: f64,
+        /// 等能线的能量（逗号分隔），以分界线能量 2g/l 为单位
+        #[arg(long, value_delimiter = ',', default_value = "0.25,0.5,0.75,1,1.5,2")]
+        levels: Vec<f64>,
+        #[arg(long, default_value = "data/energy.csv")]
+        out: String,
+        /// 等能线（列：energy,theta,omega_upper,omega_lower）
+        #[arg(long, default_value = "data/energy_contours.csv")]
+        contours: String,
+        /// 分界线（列：theta,omega_upper,omega_lower）
+        #[arg(long, default_value = "data/separatrix.csv")]
+        separatrix: String,
+    },
+    /// 共存吸引子的随机搜索：在 θ ∈ [-π, π)、ω ∈ [-omega_max, omega_max) 上随机撒初值，按终态吸引子去重，
+    /// 每个吸引子给出类型、代表初值与吸引域占比（列：attractor,class,code,initial_theta,initial_omega,hits,fraction,fraction_error,section_points）
+    Attractors {
+        #[command(flatten)]
+        common: Common,
+        /// 随机初值个数
+        #[arg(long, default_value_t = 500)]
+        trials: usize,
+        /// 随机初值的种子
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+        #[arg(long, default_value_t = 3.0)]
+        omega_max: f64,
+        /// 丢弃的过渡周期数
+        #[arg(long, default_value_t = 200)]
+        transient: usize,
+        /// 用于分类与比较的庞加莱点数
+        #[arg(long, default_value_t = 64)]
+        samples: usize,
+        /// 周期检测的最大周期
+        #[arg(long, default_value_t = 16)]
+        max_period: usize,
+        /// 周期与转动吸引子判为同一个的 Hausdorff 距离上限
+        #[arg(long, default_value_t = 1e-3)]
+        tolerance: f64,
+        /// 混沌吸引子判为同一个的平均最近邻距离上限
+        #[arg(long, default_value_t = 0.2)]
+        chaotic_tolerance: f64,
+        #[arg(long, default_value = "data/attractors.csv")]
+        out: String,
+    },
+    /// 最近邻耦合的单摆链：每个驱动周期记录一次同步指标（列：t,order,mean_phase,sync_error,coherent_fraction）
+    Chain {
+        #[command(flatten)]
+        common: Common,
+        /// 单摆个数
+        #[arg(long, default_value_t = 16)]
+        n: usize,
+        /// 耦合强度 κ
+        #[arg(long, default_value_t = 0.5)]
+        coupling: f64,
+        /// 首尾相连成环（默认两端自由）
+        #[arg(long)]
+        periodic: bool,
+        /// 各单摆固有频率平方的相对偏差在 [-spread, spread] 上均匀随机
+        #[arg(long, default_value_t = 0.0)]
+        spread: f64,
+        /// 随机初值（θ 均匀分布、ω = 0）与偏差的种子
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+        /// 积分的驱动周期数
+        #[arg(long, default_value_t = 500)]
+        periods: usize,
+        /// 局部序参量的邻域半径
+        #[arg(long, default_value_t = 2)]
+        radius: usize,
+        /// 局部序参量不低于该值的单摆计为相干
+        #[arg(long, default_value_t = 0.9)]
+        threshold: f64,
+        #[arg(long, default_value = "data/chain_sync.csv")]
+        out: String,
+        /// 时空图 CSV（列：t,theta_0,...,theta_{N-1}）
+        #[arg(long)]
+        spacetime: Option<String>,
+    },
+    /// 频闪映射在 (theta0, omega0) 处的单值矩阵与 Floquet 乘子（变分方程），用于局部稳定性分析
+    Floquet {
+        #[command(flatten)]
+        common: Common,
+        /// 映射的驱动周期数 k（F^k）
+        #[arg(long, default_value_t = 1)]
+        periods: usize,
+    },
+    /// 符号动力学：按划分把庞加莱点转成符号序列，输出块熵、熵率与拓扑熵估计
+    /// （列：length,blocks,distinct,entropy,entropy_rate,topological）
+    Symbolic {
+        #[command(flatten)]
+        common: Common,
+        #[command(flatten)]
+        sampling: Sampling,
+        /// 划分依据的坐标
+        #[arg(long, value_enum, default_value_t = PartitionCoordinate::Theta)]
+        coordinate: PartitionCoordinate,
+        /// 切点（逗号分隔），符号为不超过坐标值的切点个数
+        #[arg(long, value_delimiter = ',', allow_hyphen_values = true, default_value = "0")]
+        cuts: Vec<f64>,
+        /// 最大块长 [默认: 按样本数自动选取]
+        #[arg(long)]
+        max_block: Option<usize>,
+        #[arg(long, default_value = "data/block_entropy.csv")]
+        out: String,
+    },
+    /// 环面（准周期）吸引子检测：截面点的保序比例、旋转数及其有理逼近；写出所用的庞加莱点（列：theta,omega）
+    Torus {
+        #[command(flatten)]
+        common: Common,
+        #[command(flatten)]
+        sampling: Sampling,
+        /// 判定锁频时有理逼近的最大分母
+        #[arg(long, default_value_t = 20)]
+        max_denominator: u64,
+        #[arg(long, default_value = "data/torus_section.csv")]
+        out: String,
+    },
+    /// 诊断汇总：吸引子类型、旋转数、Lyapunov 指数与谱、可预测时间、0–1 检验、关联维数与环面检测；
+    /// 打印文字报告并写出 JSON
+    Report {
+        #[command(flatten)]
+        common: Common,
+        /// 丢掉的过渡周期数
+        #[arg(long, default_value_t = 100)]
+        transient: usize,
+        /// 统计用的驱动周期数
+        #[arg(long, default_value_t = 2000)]
+        samples: usize,
+        /// 可预测时间的初始误差 ε0
+        #[arg(long, default_value_t = 1e-6)]
+        epsilon0: f64,
+        /// 可预测时间的容差 εmax
+        #[arg(long, default_value_t = 0.1)]
+        epsilon_max: f64,
+        #[arg(long, default_value = "data/report.json")]
+        json: String,
+    },
+    /// 积分器比较：RK4、自适应 RK45、速度 Verlet 与 Yoshida4 在同一参数与初值下的耗时、能量误差、
+    /// 两两轨迹距离与庞加莱截面差别，打印汇总表
+    Compare {
+        #[command(flatten)]
+        common: Common,
+        /// 参与比较的方案（逗号分隔）
+        #[arg(long, value_delimiter = ',', value_parser = parse_scheme, default_value = "rk4,rk45,verlet,yoshida4")]
+        schemes: Vec<Scheme>,
+        /// 丢弃的过渡周期数
+        #[arg(long, default_value_t = 100)]
+        transient: usize,
+        /// 比较庞加莱截面的采样周期数
+        #[arg(long, default_value_t = 200)]
+        samples: usize,
+        /// 比较轨迹时每个驱动周期的重采样点数
+        #[arg(long, default_value_t = 32)]
+        grid: usize,
+        /// 轨迹距离超过它时记为分开
+        #[arg(long, default_value_t = 1e-3)]
+        threshold: f64,
+    },
+    /// 标量观测量的统计：矩（打印）、直方图 CSV（列：bin_start,bin_end,count,density）与可选的经验分布函数
+    Stats {
+        #[command(flatten)]
+        common: Common,
+        #[command(flatten)]
+        sampling: Sampling,
+        #[arg(long, value_enum, default_value_t = Quantity::Omega)]
+        quantity: Quantity,
+        /// 只取庞加莱点（每个驱动周期一个），否则取过渡期后的每个积分步
+        #[arg(long)]
+        section: bool,
+        /// 等宽区间个数 [默认: Freedman–Diaconis 规则]
+        #[arg(long)]
+        bins: Option<usize>,
+        #[arg(long, default_value = "data/histogram.csv")]
+        out: String,
+        /// 经验分布函数 CSV（列：value,cdf）
+        #[arg(long)]
+        cdf: Option<String>,
+    },
+    /// 锁频图（Arnold 舌）：(Ω, f_d) 网格上的旋转数矩阵，行为 f_d、列为 omega_d
+    Arnold {
+        #[command(flatten)]
+        common: Common,
+        #[arg(long, default_value_t = 0.2)]
+        omega_from: f64,
+        #[arg(long, default_value_t = 2.0)]
+        omega_to: f64,
+        /// Ω 的取值个数减一
+        #[arg(long, default_value_t = 120)]
+        omega_steps: usize,
+        #[arg(long, default_value_t = 0.0)]
+        fd_from: f64,
+        #[arg(long, default_value_t = 2.0)]
+        fd_to: f64,
+        /// f_d 的取值个数减一
+        #[arg(long, default_value_t = 120)]
+        fd_steps: usize,
+        /// 丢弃的过渡周期数
+        #[arg(long, default_value_t = 100)]
+        transient: usize,
+        /// 计算旋转数的周期数
+        #[arg(long, default_value_t = 200)]
+        samples: usize,
+        #[arg(long, default_value = "data/arnold.csv")]
+        out: String,
+        /// 同时输出按锁频比 p/q 着色的 PNG
+        #[arg(long)]
+        png: Option<String>,
+        /// PNG 中区分的锁频比的最大分母
+        #[arg(long, default_value_t = 6)]
+        max_denominator: u64,
+    },
+    /// JSON Lines 管道：从标准输入逐行读参数记录，向标准输出逐行写庞加莱点、Lyapunov 指数与吸引子类型
+    Pipe {
+        #[command(flatten)]
+        common: Common,
+        /// 每批并行计算的记录数
+        #[arg(long, default_value_t = 64)]
+        batch: usize,
+    },
+    /// 终端交互界面：实时画庞加莱截面或相图，方向键调节 f_d、q、omega_d
+    #[cfg(feature = "tui")]
+    Explore {
+        #[command(flatten)]
+        common: Common,
+        /// 丢弃的过渡周期数
+        #[arg(long, default_value_t = 100)]
+        transient: usize,
+        /// 庞加莱截面的采样周期数
+        #[arg(long, default_value_t = 1000)]
+        samples: usize,
+        /// 从相图开始（默认庞加莱截面，界面中按 v 切换）
+        #[arg(long)]
+        phase: bool,
+    },
+    /// 列出 --preset 可用的经典参数组、预期行为与建议的初值
+    Presets,
+    /// WebSocket 服务：客户端发来参数 JSON，边积分边推送 (t, theta, omega) 帧与庞加莱点
+    #[cfg(feature = "serve")]
+    Serve {
+        /// 监听地址
+        #[arg(long, default_value = "127.0.0.1:8765")]
+        addr: String,
+    },
+}
+
+/// 运动方程与积分参数
+///
+/// 给出 --config 或 --preset 时以参数文件或预设为基础，否则以默认的混沌参数（g/l = 1, q = 0.5, f_d = 1.2, Ω = 2/3）为基础；
+/// 命令行上显式给出的选项覆盖基础值。
+#[derive(Args)]
+struct Common {
+    /// 参数文件（.toml 或 .json），见 config/
+    #[arg(long)]
+    config: Option<String>,
+    /// 经典参数组（periodic、chaotic、period-2、period-4、small-angle、overdamped，见 presets 子命令）；
+    /// 初值仍取 --theta0/--omega0
+    #[arg(long, conflicts_with = "config")]
+    preset: Option<String>,
+    #[arg(long)]
+    g: Option<f64>,
+    #[arg(long)]
+    l: Option<f64>,
+    /// 阻尼系数 [默认: 0.5]
+    #[arg(long)]
+    q: Option<f64>,
+    /// 驱动幅度 [默认: 1.2]
+    #[arg(long)]
+    fd: Option<f64>,
+    /// 驱动角频率 [默认: 2/3]
+    #[arg(long)]
+    omega_d: Option<f64>,
+    /// 第二个驱动的幅度；与 --omega2 一起给出时改为双频驱动 fd sin(omega_d t) + fd2 sin(omega2 t)
+    #[arg(long, requires = "omega2")]
+    fd2: Option<f64>,
+    /// 第二个驱动的角频率（与 omega_d 不可公度时为准周期驱动；截面仍按 omega_d 取样）
+    #[arg(long, requires = "fd2")]
+    omega2: Option<f64>,
+    /// 参数激励（悬点竖直振动，Kapitza 摆）：驱动乘以 -sin θ 进入方程，fd 为悬点加速度幅度除以摆长
+    #[arg(long)]
+    parametric: bool,
+    /// 积分步长；与 --steps-per-period 二选一，都不给且无参数文件时每个驱动周期 400 步
+    #[arg(long, conflicts_with = "steps_per_period")]
+    dt: Option<f64>,
+    /// 每个驱动周期的步数（dt = T / n，频闪采样无需插值）
+    #[arg(long)]
+    steps_per_period: Option<usize>,
+    /// 每个驱动周期的最后一步截短，使积分恰好落在 t = nT 上（配合 --dt 使用）
+    #[arg(long)]
+    exact_stroboscopic: bool,
+    /// 反向积分（t 从 0 递减到 -t_end）
+    #[arg(long)]
+    backward: bool,
+    /// ω 方程上加性高斯噪声的强度 σ（dω = ... dt + σ dW）
+    #[arg(long)]
+    noise: Option<f64>,
+    /// 噪声的随机数种子
+    #[arg(long)]
+    noise_seed: Option<u64>,
+    /// 内存预算（如 512M、4G）：保存完整轨迹的估计大小超过它时拒绝积分，庞加莱采样改走流式路径 [默认: 参数文件中的值，否则 2G]
+    #[arg(long, value_parser = parse_byte_size)]
+    memory_budget: Option<usize>,
+    #[arg(long, default_value_t = 1.0)]
+    theta0: f64,
+    #[arg(long, default_value_t = 0.0)]
+    omega0: f64,
+    /// 运行参数的记录方式
+    #[arg(long, value_enum, default_value_t = MetadataMode::Sidecar)]
+    metadata: MetadataMode,
+}
+
+/// 输出文件的元数据记录方式
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum MetadataMode {
+    /// CSV 最前面加 # 注释行（非 CSV 输出改写旁路文件）
+    Header,
+    /// 旁路 JSON 文件（data/poincare.csv -> data/poincare.run.json）
+    Sidecar,
+    /// 不记录
+    None,
+}
+
+/// 符号划分依据的坐标
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum PartitionCoordinate {
+    /// θ（折叠到 [-π, π)）
+    Theta,
+    Omega,
+}
+
+/// 分岔点的加密方法
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Refinement {
+    /// 对吸引子周期的判定二分
+    Bisection,
+    /// 对周期轨道的 Floquet 乘子用割线法求根（失败时退回二分）
+    Secant,
+}
+
+/// 统计的标量观测量
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Quantity {
+    /// θ（折叠到 [-π, π)）
+    Theta,
+    Omega,
+    /// 单位质量的机械能 ω²/2 + (g/l)(1 - cos θ)
+    Energy,
+}
+
+/// 庞加莱采样设置
+#[derive(Args)]
+struct Sampling {
+    /// 丢弃的过渡周期数
+    #[arg(long, default_value_t = 100)]
+    transient: usize,
+    /// 采样周期数
+    #[arg(long, default_value_t = 2000)]
+    samples: usize,
+    /// 结果缓存目录：参数、初值与采样设置都相同时直接读回上次的庞加莱点 / 分岔图，不重新积分
+    #[arg(long)]
+    cache: Option<String>,
+}
+
+impl Common {
+    fn params(&self) -> std::io::Result<PendulumParams> {
+        let mut params = match (&self.config, &self.preset) {
+            (Some(path), _) => PendulumParams::from_file(path)?,
+            (None, Some(name)) => PendulumParams::preset(name).ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("unknown preset '{}' (available: {})", name, preset_names().join(", ")),
+                )
+            })?,
+            (None, None) => {
+                let mut p = PendulumParams::new();
+                p.l = p.g;
+                p.q = 0.5;
+                p.f_d = 1.2;
+                p.omega_d = 2.0 / 3.0;
+                p.set_steps_per_period(400);
+                p
+            }
+        };
+        let set = |field: &mut f64, value: Option<f64>| {
+            if let Some(v) = value {
+                *field = v;
+            }
+        };
+        set(&mut params.g, self.g);
+        set(&mut params.l, self.l);
+        set(&mut params.q, self.q);
+        set(&mut params.f_d, self.fd);
+        set(&mut params.omega_d, self.omega_d);
+        if let (Some(f2), Some(omega2)) = (self.fd2, self.omega2) {
+            params.forcing = Forcing::two_frequency(params.f_d, params.omega_d, f2, omega2);
+        }
+        if let Some(dt) = self.dt {
+            params.dt = dt;
+            params.step_control = StepControl::FixedDt;
+        }
+        // 改了驱动频率时 set_steps_per_period 会让步长跟着新的周期走
+        if let Some(n) = self.steps_per_period {
+            params.set_steps_per_period(n);
+        } else if let StepControl::StepsPerPeriod { steps } = params.step_control {
+            params.set_steps_per_period(steps);
+        }
+        if self.exact_stroboscopic {
+            params.exact_stroboscopic = true;
+        }
+        if self.parametric {
+            params.drive_type = DriveType::Parametric;
+        }
+        if self.backward {
+            params.direction = TimeDirection::Backward;
+        }
+        set(&mut params.noise_intensity, self.noise);
+        if let Some(seed) = self.noise_seed {
+            params.noise_seed = seed;
+        }
+        params.memory_budget_bytes = self
+            .memory_budget
+            .or(params.memory_budget_bytes)
+            .or(Some(DEFAULT_MEMORY_BUDGET_BYTES));
+        Ok(params)
+    }
+}
+
+// 按 --metadata 记录运行参数
+fn attach_metadata(mode: MetadataMode, out: &str, meta: &RunMetadata) -> std::io::Result<()> {
+    if let Some(experiment) = EXPERIMENT.lock().unwrap().as_mut() {
+        experiment.record_metadata(meta)?;
+    }
+    match mode {
+        MetadataMode::Header if out.ends_with(".csv") => meta.prepend_header(out),
+        MetadataMode::Header | MetadataMode::Sidecar => meta.write_sidecar(out).map(|_| ()),
+        MetadataMode::None => Ok(()),
+    }
+}
+
+// 有 --cache 时先查缓存，未命中再计算并存盘；被 Ctrl-C 打断的部分结果不存。key 为 None（不可缓存）时直接计算
+fn cached<T, F>(dir: Option<&str>, key: Option<CacheKey>, compute: F) -> Result<T, ChaosError>
+where
+    T: Serialize + DeserializeOwned,
+    F: FnOnce() -> Result<T, ChaosError>,
+{
+    let (Some(dir), Some(key)) = (dir, key) else {
+        return compute();
+    };
+    let cache = ResultCache::open(dir)?;
+    if let Some(value) = cache.get(&key) {
+        eprintln!("Using cached result {}", cache.path(&key).display());
+        return Ok(value);
+    }
+    let value = compute()?;
+    if !INTERRUPTED.load(Ordering::Relaxed) {
+        cache.put(&key, &value)?;
+    }
+    Ok(value)
+}
+
+// 给了 --experiment 时的当前实验目录
+static EXPERIMENT: Mutex<Option<Experiment>> = Mutex::new(None);
+
+// 确定实际的输出路径并确保其所在目录存在；在实验目录中运行时换到实验目录下并登记为产物
+fn prepare_output(path: &str) -> std::io::Result<String> {
+    let path = match EXPERIMENT.lock().unwrap().as_mut() {
+        Some(experiment) => experiment.artifact_path(path),
+        None => path.to_string(),
+    };
+    match std::path::Path::new(&path).parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => std::fs::create_dir_all(dir)?,
+        _ => {}
+    }
+    Ok(path)
+}
+
+// 单条轨道庞加莱采样的缓存键（params.t_end 已按采样周期数设好）
+fn poincare_key(params: &PendulumParams, common: &Common, sampling: &Sampling) -> Option<CacheKey> {
+    CacheKey::new("poincare", params, common.theta0, common.omega0).map(|key| {
+        key.with("transient_periods", sampling.transient)
+            .with("sample_periods", sampling.samples)
+    })
+}
+
+fn parse_sweep_parameter(s: &str) -> Result<SweepParameter, String> {
+    SweepParameter::from_name(s).ok_or_else(|| format!("expected f_d, q or omega_d, got {:?}", s))
+}
+
+fn parse_observable_name(s: &str) -> Result<String, String> {
+    match BUILTIN_OBSERVABLES.iter().find(|(name, _)| *name == s) {
+        Some(_) => Ok(s.to_string()),
+        None => {
+            let names: Vec<&str> = BUILTIN_OBSERVABLES.iter().map(|(name, _)| *name).collect();
+            Err(format!("expected one of {}, got {:?}", names.join(", "), s))
+        }
     }
+}
 
+fn parse_scheme(s: &str) -> Result<Scheme, String> {
+    Scheme::from_name(s).ok_or_else(|| format!("expected rk4, rk45, verlet or yoshida4, got {:?}", s))
 }
 
+// 解析 "θ,ω" 形式的初值
+fn parse_initial_condition(s: &str) -> Result<(f64, f64), String> {
+    let (theta, omega) = s.split_once(',').ok_or_else(|| format!("expected THETA,OMEGA, got {:?}", s))?;
+    let parse = |v: &str| v.trim().parse::<f64>().map_err(|e| format!("{:?}: {}", v, e));
+    Ok((parse(theta)?, parse(omega)?))
+}
+
+#[cfg(feature = "plot")]
+type Renderer = fn(&str, &[(f64, f64)], &chaos::plot::PlotOptions) -> std::io::Result<()>;
+
+// 按扩展名选择 SVG 或 PNG 出图
+#[cfg(feature = "plot")]
+fn draw_figure(path: &str, points: &[(f64, f64)], svg: Renderer, png: Renderer) -> std::io::Result<()> {
+    let path = &prepare_output(path)?;
+    let opts = chaos::plot::PlotOptions::default();
+    if path.ends_with(".svg") {
+        svg(path, points, &opts)
+    } else {
+        png(path, points, &opts)
+    }
+}
+
+// Ctrl-C 只置位标志：各例程在下一次进度回调时停下，已有的结果照常写出
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+// 按积分步回调时的间隔
+const PROGRESS_EVERY_STEPS: usize = 10_000;
+
+// 更新进度条（stderr 不是终端时不显示），收到 Ctrl-C 后要求例程停下
+fn progress(bar: &ProgressBar) -> impl FnMut(ProgressInfo) -> ControlFlow<()> + '_ {
+    move |info| {
+        bar.set_length(info.total as u64);
+        bar.set_position(info.done as u64);
+        if INTERRUPTED.load(Ordering::Relaxed) {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    }
+}
+
+// 被中断时在完成消息后注明结果不完整
+fn finish(bar: ProgressBar, message: String) -> String {
+    bar.finish_and_clear();
+    if INTERRUPTED.load(Ordering::Relaxed) {
+        format!("{} (interrupted, partial results)", message)
+    } else {
+        message
+    }
+}
+
+fn run(command: Command) -> Result<String, ChaosError> {
+    let bar = ProgressBar::new(0);
+    match command {
+        Command::Poincare {
+            common,
+            sampling,
+            out,
+            ic,
+            auto_transient,
+            checkpoint,
+            checkpoint_every,
+            resume,
+            density,
+            density_bins,
+            #[cfg(feature = "plot")]
+            figure,
+            dry_run,
+            observable,
+        } => {
+            if dry_run {
+                let mut params = common.params()?;
+                params.validate_periodic()?;
+                params.t_end = 2.0 * PI / params.omega_d * (sampling.transient + sampling.samples) as f64 + params.dt;
+                let runs = ic.len().max(1);
+                let cost = poincare_cost(&params, runs * sampling.samples);
+                return Ok(format!("{}\n({} run(s); without --ic sampling always streams, with --ic a run keeps its full trajectory unless that exceeds the budget)", cost, runs));
+            }
+            let out = prepare_output(&out)?;
+            // 给出 --observable 时重建采样时刻所需的参数与过渡期
+            let (runs, meta, origin) = if let Some(path) = &checkpoint {
+                let mut cp = if resume {
+                    Checkpoint::load(path)?
+                } else {
+                    Checkpoint::new(&common.params()?, common.theta0, common.omega0, sampling.transient, sampling.samples)?
+                };
+                cp.run_with_progress(path, checkpoint_every, progress(&bar))?;
+                let meta = RunMetadata::new(&cp.params, cp.initial_theta, cp.initial_omega)
+                    .with("transient_periods", cp.transient_periods)
+                    .with("sample_periods", cp.sample_periods);
+                (vec![cp.samples], meta, Some((cp.params, cp.transient_periods)))
+            } else if !ic.is_empty() {
+                let mut params = common.params()?;
+                params.validate_periodic()?;
+                let period = 2.0 * PI / params.omega_d;
+                params.t_end = period * (sampling.transient + sampling.samples) as f64 + params.dt;
+                let cost = poincare_cost(&params, sampling.samples);
+                if cost.exceeds_budget() {
+                    eprintln!(
+                        "Warning: each full trajectory would need {}, above the memory budget; sampling in streaming mode",
+                        format_bytes(cost.trajectory_bytes)
+                    );
+                }
+                let runs = poincare_runs(&params, &ic, sampling.transient, sampling.samples)?;
+                let listed: Vec<String> = ic.iter().map(|(th, om)| format!("({}, {})", th, om)).collect();
+                let meta = RunMetadata::new(&params, ic[0].0, ic[0].1)
+                    .with("transient_periods", sampling.transient)
+                    .with("sample_periods", sampling.samples)
+                    .with("initial_conditions", listed.join(" "));
+                (runs, meta, None)
+            } else if auto_transient {
+                let params = common.params()?;
+                let opts = TransientOptions::default();
+                let (samples, estimate) =
+                    poincare_auto_transient(&params, common.theta0, common.omega0, sampling.samples, &opts)?;
+                if !estimate.settled {
+                    eprintln!("Warning: no attractor detected within {} periods", opts.max_periods);
+                }
+                eprintln!("Detected transient: {} periods", estimate.transient_periods);
+                let meta = RunMetadata::new(&params, common.theta0, common.omega0)
+                    .with("transient_periods", estimate.transient_periods)
+                    .with("transient_settled", estimate.settled)
+                    .with("sample_periods", sampling.samples);
+                (vec![samples], meta, Some((params, estimate.transient_periods)))
+            } else {
+                let mut params = common.params()?;
+                params.validate_periodic()?;
+                let period = 2.0 * PI / params.omega_d;
+                params.t_end = period * (sampling.transient + sampling.samples) as f64 + params.dt;
+                let samples = cached(sampling.cache.as_deref(), poincare_key(&params, &common, &sampling), || {
+                    poincare_with_progress(
+                        &params,
+                        common.theta0,
+                        common.omega0,
+                        sampling.transient,
+                        sampling.samples,
+                        PROGRESS_EVERY_STEPS,
+                        progress(&bar),
+                    )
+                })?;
+                let meta = RunMetadata::new(&params, common.theta0, common.omega0)
+                    .with("transient_periods", sampling.transient)
+                    .with("sample_periods", sampling.samples);
+                (vec![samples], meta, Some((params, sampling.transient)))
+            };
+            if let (false, Some((params, transient))) = (observable.is_empty(), &origin) {
+                let observables = observable.iter().filter_map(|name| builtin_observable(name)).collect();
+                write_poincare_observables(&out, TrajectoryFormat::from_path(&out), &runs[0], *transient, params, observables)?;
+            } else if ic.is_empty() {
+                write_poincare_samples_csv(&out, &runs[0])?;
+            } else {
+                write_poincare_runs_csv(&out, &runs)?;
+            }
+            let samples = runs.concat();
+            attach_metadata(common.metadata, &out, &meta)?;
+            if let Some(path) = &density {
+                let path = &prepare_output(path)?;
+                let h = poincare_density(&samples, density_bins, density_bins, None);
+                if path.ends_with(".png") {
+                    write_density_png(path, &h)?;
+                } else {
+                    write_density_matrix_csv(path, &h)?;
+                }
+            }
+            #[cfg(feature = "plot")]
+            if let Some(path) = &figure {
+                if ic.is_empty() {
+                    draw_figure(path, &samples, chaos::plot::poincare_svg, chaos::plot::poincare_png)?;
+                } else {
+                    let path = &prepare_output(path)?;
+                    let opts = chaos::plot::PlotOptions::default();
+                    if path.ends_with(".svg") {
+                        chaos::plot::poincare_runs_svg(path, &runs, &opts)?;
+                    } else {
+                        chaos::plot::poincare_runs_png(path, &runs, &opts)?;
+                    }
+                }
+            }
+            Ok(finish(bar, format!("Wrote Poincaré data to {}", out)))
+        }
+        Command::Trajectory {
+            common,
+            t_end,
+            out,
+            every,
+            max_points,
+            lift,
+            chunk_size,
+            dry_run,
+            observable,
+        } => {
+            let decimation = match (every, max_points) {
+                (Some(k), _) => Decimation::Every(k),
+                (None, Some(n)) => Decimation::MaxPoints(n),
+                (None, None) => Decimation::None,
+            };
+            let mut params = common.params()?;
+            params.t_end = t_end;
+            params.validate()?;
+            if dry_run {
+                let cost = trajectory_cost(&params, decimation, TrajectoryFormat::from_path(&out), if lift { 5 } else { 3 + observable.len() });
+                return Ok(format!("{}\n(trajectory output streams to disk; memory use does not grow with t_end)", cost));
+            }
+            let out = prepare_output(&out)?;
+            let writer = if lift {
+                create_lifted_trajectory_writer(&out, TrajectoryFormat::from_path(&out))?
+            } else if !observable.is_empty() {
+                let observables = observable.iter().filter_map(|name| builtin_observable(name)).collect();
+                Box::new(ObservableTrajectoryWriter::create(&out, TrajectoryFormat::from_path(&out), &params, observables)?)
+            } else {
+                create_trajectory_writer(&out, TrajectoryFormat::from_path(&out))?
+            };
+            let mut writer: Box<dyn TrajectoryWriter> = if chunk_size > 0 {
+                let opts = AsyncWriterOptions { chunk_size, ..AsyncWriterOptions::default() };
+                Box::new(AsyncTrajectoryWriter::spawn(writer, &opts))
+            } else {
+                writer
+            };
+            if params.noise_intensity > 0.0 {
+                let traj = StochasticTrajectory::new(&params, common.theta0, common.omega0);
+                write_trajectory_decimated(writer.as_mut(), with_progress(traj, PROGRESS_EVERY_STEPS, progress(&bar)), decimation)?;
+            } else {
+                let traj = TrajectoryIter::new(&params, common.theta0, common.omega0);
+                write_trajectory_decimated(writer.as_mut(), with_progress(traj, PROGRESS_EVERY_STEPS, progress(&bar)), decimation)?;
+            }
+            let mut meta = RunMetadata::new(&params, common.theta0, common.omega0);
+            match decimation {
+                Decimation::Every(k) => meta = meta.with("every", k),
+                Decimation::MaxPoints(n) => meta = meta.with("max_points", n),
+                Decimation::None => {}
+            }
+            attach_metadata(common.metadata, &out, &meta)?;
+            Ok(finish(bar, format!("Wrote trajectory to {}", out)))
+        }
+        Command::Bifurcation {
+            common,
+            sampling,
+            fd_min,
+            fd_max,
+            steps,
+            out,
+            resumable,
+            parallel,
+            #[cfg(feature = "plot")]
+            figure,
+        } => {
+            let params = common.params()?;
+            params.validate_periodic()?;
+            let out = prepare_output(&out)?;
+            if parallel {
+                #[cfg(feature = "plot")]
+                if figure.is_some() {
+                    return Err(ChaosError::InvalidParams("--figure is not supported with --parallel".to_string()));
+                }
+                let sink = create_row_sink(&out, TrajectoryFormat::from_path(&out), &["f_d", "theta"])?;
+                let initial = State { theta: 0.0, omega: 0.0 };
+                let rows =
+                    bifurcation_diagram_parallel(&params, (fd_min, fd_max), steps, sampling.transient, sampling.samples, initial, sink)?;
+                let meta = RunMetadata::new(&params, 0.0, 0.0)
+                    .with("fd_min", fd_min)
+                    .with("fd_max", fd_max)
+                    .with("steps", steps)
+                    .with("transient_periods", sampling.transient)
+                    .with("sample_periods", sampling.samples)
+                    .with("parallel", true);
+                attach_metadata(common.metadata, &out, &meta)?;
+                return Ok(finish(bar, format!("Wrote {} bifurcation points to {}", rows, out)));
+            }
+            // 续算前文件已带元数据注释头（上次已跑完）时不再重复添加
+            let has_header = resumable && std::fs::read_to_string(&out).is_ok_and(|text| text.starts_with('#'));
+            let key = CacheKey::new("bifurcation", &params, 0.0, 0.0).map(|key| {
+                key.with("fd_min", fd_min)
+                    .with("fd_max", fd_max)
+                    .with("steps", steps)
+                    .with("transient_periods", sampling.transient)
+                    .with("sample_periods", sampling.samples)
+            });
+            let points = if resumable {
+                bifurcation_diagram_resumable(
+                    &params,
+                    (fd_min, fd_max),
+                    steps,
+                    sampling.transient,
+                    sampling.samples,
+                    &out,
+                    progress(&bar),
+                )?
+            } else {
+                cached(sampling.cache.as_deref(), key, || {
+                    Ok(bifurcation_diagram_with_progress(
+                        &params,
+                        (fd_min, fd_max),
+                        steps,
+                        sampling.transient,
+                        sampling.samples,
+                        progress(&bar),
+                    ))
+                })?
+            };
+            if !resumable {
+                write_bifurcation_csv(&out, &points)?;
+            }
+            // 分岔扫描从 (0, 0) 出发并逐点热启动，与 --theta0/--omega0 无关
+            let meta = RunMetadata::new(&params, 0.0, 0.0)
+                .with("fd_min", fd_min)
+                .with("fd_max", fd_max)
+                .with("steps", steps)
+                .with("transient_periods", sampling.transient)
+                .with("sample_periods", sampling.samples);
+            // 被打断时不加元数据注释头，续算跑完后再加
+            if !(resumable && (has_header || INTERRUPTED.load(Ordering::Relaxed))) {
+                attach_metadata(common.metadata, &out, &meta)?;
+            }
+            #[cfg(feature = "plot")]
+            if let Some(path) = &figure {
+                draw_figure(path, &points, chaos::plot::bifurcation_svg, chaos::plot::bifurcation_png)?;
+            }
+            Ok(finish(bar, format!("Wrote bifurcation diagram to {}", out)))
+        }
+        Command::Bracket {
+            common,
+            parameter,
+            from,
+            to,
+            steps,
+            transient,
+            samples,
+            tolerance,
+            method,
+            out,
+        } => {
+            let params = common.params()?;
+            let out = prepare_output(&out)?;
+            let options = BracketOptions {
+                coarse_steps: steps,
+                transient_periods: transient,
+                sample_periods: samples,
+                parameter_tolerance: tolerance,
+                method: match method {
+                    Refinement::Bisection => RefineMethod::Bisection,
+                    Refinement::Secant => RefineMethod::Secant,
+                },
+                ..BracketOptions::default()
+            };
+            let initial = State {
+                theta: common.theta0,
+                omega: common.omega0,
+            };
+            let transitions = bracket_transitions(&params, parameter, (from, to), initial, &options)?;
+            write_transitions_csv(&out, parameter, &transitions)?;
+            let meta = RunMetadata::new(&params, common.theta0, common.omega0)
+                .with("parameter", parameter.name())
+                .with("range", format!("{} {}", from, to))
+                .with("coarse_steps", steps)
+                .with("transient_periods", transient)
+                .with("sample_periods", samples);
+            attach_metadata(common.metadata, &out, &meta)?;
+            let period = |p: Option<usize>| p.map_or("aperiodic".to_string(), |p| format!("period {}", p));
+            let lines: Vec<String> = transitions
+                .iter()
+                .map(|t| {
+                    format!(
+                        "  {} = {:.10} (+/- {:.1e}, {}): {} -> {}",
+                        parameter.name(),
+                        t.threshold(),
+                        0.5 * (t.upper - t.lower),
+                        t.method.name(),
+                        period(t.before),
+                        period(t.after)
+                    )
+                })
+                .collect();
+            Ok(format!("Wrote {} thresholds to {}\n{}", transitions.len(), out, lines.join("\n")))
+        }
+        Command::Ramp {
+            common,
+            fd_from,
+            fd_to,
+            periods,
+            out,
+        } => {
+            let mut params = common.params()?;
+            params.validate_periodic()?;
+            params.t_end = 2.0 * PI / params.omega_d.abs() * periods as f64;
+            params.f_d_schedule = Some(Schedule::Linear {
+                from: fd_from,
+                to: fd_to,
+                t_start: 0.0,
+                t_end: params.t_end,
+            });
+            params.validate()?;
+            let out = prepare_output(&out)?;
+            let points = ramp_sweep_with_progress(&params, common.theta0, common.omega0, PROGRESS_EVERY_STEPS, progress(&bar));
+            write_ramp_csv(&out, &points)?;
+            let meta = RunMetadata::new(&params, common.theta0, common.omega0).with("periods", periods);
+            attach_metadata(common.metadata, &out, &meta)?;
+            Ok(finish(bar, format!("Wrote ramped bifurcation diagram to {}", out)))
+        }
+        Command::Melnikov {
+            common,
+            omega_from,
+            omega_to,
+            steps,
+            out,
+        } => {
+            let params = common.params()?;
+            params.validate()?;
+            let omegas: Vec<f64> = (0..=steps)
+                .map(|i| omega_from + (omega_to - omega_from) * i as f64 / steps.max(1) as f64)
+                .collect();
+            let out = prepare_output(&out)?;
+            write_threshold_curve_csv(&out, &threshold_curve(&params, &omegas))?;
+            let meta = RunMetadata::new(&params, common.theta0, common.omega0)
+                .with("omega_from", omega_from)
+                .with("omega_to", omega_to);
+            attach_metadata(common.metadata, &out, &meta)?;
+            Ok(format!(
+                "Wrote Melnikov threshold curve to {} (f_c = {:.6} at omega_d = {})",
+                out,
+                critical_forcing(&params),
+                params.omega_d
+            ))
+        }
+        Command::Diffusion {
+            common,
+            members,
+            spread,
+            seed,
+            single,
+            transient,
+            periods,
+            fit_from,
+            out,
+        } => {
+            let mut params = common.params()?;
+            params.validate_periodic()?;
+            let out = prepare_output(&out)?;
+            let period = 2.0 * PI / params.omega_d;
+            let points = if single {
+                let thetas = stroboscopic_lift(&params, common.theta0, common.omega0, transient, periods)?;
+                time_window_msd(&thetas, period, periods / 10)
+            } else {
+                params.t_end = (transient + periods) as f64 * period;
+                let spread = InitialConditionSampling::Gaussian {
+                    theta: common.theta0,
+                    omega: common.omega0,
+                    sigma_theta: spread,
+                    sigma_omega: spread,
+                };
+                ensemble_msd(&chaos::ensemble::run(&params, members, seed, spread), transient)
+            };
+            write_msd_csv(&out, &points)?;
+            let meta = RunMetadata::new(&params, common.theta0, common.omega0)
+                .with("transient_periods", transient)
+                .with("periods", periods)
+                .with("average", if single { "time" } else { "ensemble" });
+            attach_metadata(common.metadata, &out, &meta)?;
+            let t_max = points.last().map_or(0.0, |p| p.t);
+            let fit = fit_diffusion(&points, fit_from as f64 * period, t_max)
+                .ok_or(ChaosError::InsufficientTrajectory {
+                    needed: (fit_from + 1) as f64 * period,
+                    available: t_max,
+                })?;
+            Ok(format!(
+                "Wrote MSD to {} (D = {:.6} rad^2 per unit time, drift = {:.6} rad per unit time)",
+                out, fit.coefficient, fit.drift
+            ))
+        }
+        Command::Escape {
+            common,
+            cells,
+            omega_max,
+            threshold,
+            t_max,
+            out,
+            png,
+        } => {
+            let mut params = common.params()?;
+            params.validate()?;
+            let cells = cells.max(2);
+            params.theta_start = -PI;
+            params.theta_end = PI;
+            params.d_theta = 2.0 * PI / (cells - 1) as f64;
+            params.omega_start = -omega_max;
+            params.omega_end = omega_max;
+            params.d_omega = 2.0 * omega_max / (cells - 1) as f64;
+            let threshold = threshold.unwrap_or(2.0 * PI);
+            let out = prepare_output(&out)?;
+            let map = escape_time_map(&params, threshold, t_max);
+            write_escape_matrix_csv(&out, &map)?;
+            let meta = RunMetadata::new(&params, common.theta0, common.omega0)
+                .with("threshold", threshold)
+                .with("t_max", t_max);
+            attach_metadata(common.metadata, &out, &meta)?;
+            if let Some(path) = &png {
+                let path = &prepare_output(path)?;
+                write_escape_png(path, &map)?;
+            }
+            Ok(format!(
+                "Wrote first-passage times to {} ({:.1}% of cells escaped within t = {})",
+                out,
+                100.0 * map.escaped_fraction(),
+                t_max
+            ))
+        }
+        Command::Energy {
+            common,
+            cells,
+            omega_max,
+            levels,
+            out,
+            contours,
+            separatrix,
+        } => {
+            let mut params = common.params()?;
+            params.validate()?;
+            let cells = cells.max(2);
+            params.theta_start = -PI;
+            params.theta_end = PI;
+            params.d_theta = 2.0 * PI / (cells - 1) as f64;
+            params.omega_start = -omega_max;
+            params.omega_end = omega_max;
+            params.d_omega = 2.0 * omega_max / (cells - 1) as f64;
+            let out = prepare_output(&out)?;
+            let contours = prepare_output(&contours)?;
+            let separatrix = prepare_output(&separatrix)?;
+            let e_sep = separatrix_energy(&params);
+            let energies: Vec<f64> = levels.iter().map(|level| level * e_sep).collect();
+            write_energy_surface_csv(&out, &energy_surface(&params))?;
+            write_energy_contours_csv(&contours, &params, &energies)?;
+            write_separatrix_csv(&separatrix, &params)?;
+            let meta = RunMetadata::new(&params, common.theta0, common.omega0)
+                .with("cells", cells)
+                .with("separatrix_energy", e_sep);
+            attach_metadata(common.metadata, &out, &meta)?;
+            Ok(format!(
+                "Wrote energy surface to {}, {} contours to {} and separatrix (E = {:.6}) to {}",
+                out,
+                energies.len(),
+                contours,
+                e_sep,
+                separatrix
+            ))
+        }
+        Command::Chain {
+            common,
+            n,
+            coupling,
+            periodic,
+            spread,
+            seed,
+            periods,
+            radius,
+            threshold,
+            out,
+            spacetime,
+        } => {
+            let mut params = common.params()?;
+            params.validate_periodic()?;
+            let out = prepare_output(&out)?;
+            params.t_end = 2.0 * PI / params.omega_d * periods as f64;
+            let mut chain = PendulumChain::new(params.clone(), n, coupling);
+            if periodic {
+                chain.boundary = ChainBoundary::Periodic;
+            }
+            let mut rng = seeded_rng(seed);
+            if spread > 0.0 {
+                chain.detuning = (0..n).map(|_| rng.uniform(-spread, spread)).collect();
+            }
+            let initial = random_initial(n, &mut rng);
+            let samples = solve_chain(&chain, &initial, steps_per_period(&params))?;
+            let records = synchronization(&chain, &samples, radius, threshold);
+            write_sync_csv(&out, &records)?;
+            // 初值由 seed 随机生成，与 --theta0/--omega0 无关
+            let meta = RunMetadata::new(&params, 0.0, 0.0)
+                .with("n", n)
+                .with("coupling", coupling)
+                .with("boundary", if periodic { "periodic" } else { "free" })
+                .with("spread", spread)
+                .with("seed", seed)
+                .with("periods", periods);
+            attach_metadata(common.metadata, &out, &meta)?;
+            if let Some(path) = &spacetime {
+                let path = &prepare_output(path)?;
+                write_spacetime_csv(path, &samples)?;
+            }
+            let last = records.last().copied().ok_or(ChaosError::InsufficientTrajectory {
+                needed: 1.0,
+                available: 0.0,
+            })?;
+            Ok(format!(
+                "Wrote synchronization indicators to {} (final R = {:.4}, sync error = {:.3e}, coherent fraction = {:.3})",
+                out, last.order, last.sync_error, last.coherent_fraction
+            ))
+        }
+        Command::Floquet { common, periods } => {
+            let params = common.params()?;
+            params.validate()?;
+            let m = monodromy(&params, common.theta0, common.omega0, periods)?;
+            let [(r1, i1), (r2, i2)] = m.multipliers;
+            let [e1, e2] = m.exponents();
+            Ok(format!(
+                "M = [[{:.9}, {:.9}], [{:.9}, {:.9}]]\nmultipliers: {:.9}{:+.9}i, {:.9}{:+.9}i (|mu|max = {:.9})\n\
+                 local exponents: {:.9}, {:.9}; det M = {:.9e}",
+                m.matrix[0][0],
+                m.matrix[0][1],
+                m.matrix[1][0],
+                m.matrix[1][1],
+                r1,
+                i1,
+                r2,
+                i2,
+                m.spectral_radius(),
+                e1,
+                e2,
+                m.determinant()
+            ))
+        }
+        Command::Symbolic {
+            common,
+            sampling,
+            coordinate,
+            cuts,
+            max_block,
+            out,
+        } => {
+            let mut params = common.params()?;
+            params.validate_periodic()?;
+            let out = prepare_output(&out)?;
+            params.t_end = 2.0 * PI / params.omega_d * (sampling.transient + sampling.samples) as f64 + params.dt;
+            let samples = cached(sampling.cache.as_deref(), poincare_key(&params, &common, &sampling), || {
+                poincare(&params, common.theta0, common.omega0, sampling.transient, sampling.samples)
+            })?;
+            let coordinate = match coordinate {
+                PartitionCoordinate::Theta => Coordinate::Theta,
+                PartitionCoordinate::Omega => Coordinate::Omega,
+            };
+            let partition = Partition::new(coordinate, cuts);
+            let symbols = symbolize(&samples, &partition);
+            let max_block = max_block.unwrap_or_else(|| reliable_block_length(symbols.len(), partition.alphabet_size()));
+            let entropies = block_entropies(&symbols, max_block);
+            write_block_entropy_csv(&out, &entropies)?;
+            let meta = RunMetadata::new(&params, common.theta0, common.omega0)
+                .with("transient_periods", sampling.transient)
+                .with("sample_periods", sampling.samples)
+                .with("partition", format!("{:?} {:?}", partition.coordinate, partition.cuts));
+            attach_metadata(common.metadata, &out, &meta)?;
+            let estimate = entropy_estimates(&entropies).last().copied().ok_or(ChaosError::InsufficientTrajectory {
+                needed: 1.0,
+                available: 0.0,
+            })?;
+            Ok(format!(
+                "Wrote block entropies to {} (block length {}: entropy rate = {:.6}, topological = {:.6} nats per period)",
+                out, estimate.length, estimate.entropy_rate, estimate.topological
+            ))
+        }
+        Command::Report {
+            common,
+            transient,
+            samples,
+            epsilon0,
+            epsilon_max,
+            json,
+        } => {
+            let params = common.params()?;
+            params.validate_periodic()?;
+            let json = prepare_output(&json)?;
+            let options = ReportOptions {
+                transient_periods: transient,
+                sample_periods: samples,
+                initial_error: epsilon0,
+                tolerance: epsilon_max,
+                ..ReportOptions::default()
+            };
+            let report = diagnostics_report(&params, common.theta0, common.omega0, &options)?;
+            report.write_json(&json)?;
+            Ok(format!("{}\nWrote report to {}", report, json))
+        }
+        Command::Attractors {
+            common,
+            trials,
+            seed,
+            omega_max,
+            transient,
+            samples,
+            max_period,
+            tolerance,
+            chaotic_tolerance,
+            out,
+        } => {
+            let mut params = common.params()?;
+            params.validate_periodic()?;
+            let out = prepare_output(&out)?;
+            params.theta_start = -PI;
+            params.theta_end = PI;
+            params.omega_start = -omega_max;
+            params.omega_end = omega_max;
+            let options = AttractorSearchOptions {
+                trials,
+                transient_periods: transient,
+                sample_periods: samples,
+                max_period,
+                period_tolerance: AttractorSearchOptions::default().period_tolerance,
+                match_tolerance: tolerance,
+                chaotic_tolerance,
+            };
+            let found = search_attractors(&params, &mut seeded_rng(seed), &options);
+            write_found_attractors_csv(&out, &found)?;
+            // 初值由 seed 随机生成，与 --theta0/--omega0 无关
+            let meta = RunMetadata::new(&params, 0.0, 0.0)
+                .with("trials", trials)
+                .with("seed", seed)
+                .with("omega_max", omega_max)
+                .with("transient_periods", transient)
+                .with("sample_periods", samples)
+                .with("tolerance", tolerance)
+                .with("chaotic_tolerance", chaotic_tolerance);
+            attach_metadata(common.metadata, &out, &meta)?;
+            let lines: Vec<String> = found
+                .iter()
+                .map(|a| {
+                    let class = match a.class {
+                        AttractorClass::Periodic(p) => format!("period {}", p),
+                        other => other.name().to_string(),
+                    };
+                    format!(
+                        "  #{} {:<10} from ({:.4}, {:.4}): basin fraction {:.3} (+/- {:.3})",
+                        a.index, class, a.initial_theta, a.initial_omega, a.fraction, a.fraction_error
+                    )
+                })
+                .collect();
+            Ok(format!("Wrote {} attractors from {} trials to {}\n{}", found.len(), trials, out, lines.join("\n")))
+        }
+        Command::Compare {
+            common,
+            schemes,
+            transient,
+            samples,
+            grid,
+            threshold,
+        } => {
+            let params = common.params()?;
+            let options = CompareOptions {
+                schemes,
+                transient_periods: transient,
+                sample_periods: samples,
+                grid_per_period: grid,
+                divergence_threshold: threshold,
+            };
+            let initial = State {
+                theta: common.theta0,
+                omega: common.omega0,
+            };
+            Ok(compare_integrators(&params, initial, &options)?.to_string())
+        }
+        Command::Torus {
+            common,
+            sampling,
+            max_denominator,
+            out,
+        } => {
+            let mut params = common.params()?;
+            params.validate_periodic()?;
+            let out = prepare_output(&out)?;
+            params.t_end = 2.0 * PI / params.omega_d * (sampling.transient + sampling.samples) as f64 + params.dt;
+            let samples = cached(sampling.cache.as_deref(), poincare_key(&params, &common, &sampling), || {
+                poincare(&params, common.theta0, common.omega0, sampling.transient, sampling.samples)
+            })?;
+            write_poincare_samples_csv(&out, &samples)?;
+            let meta = RunMetadata::new(&params, common.theta0, common.omega0)
+                .with("transient_periods", sampling.transient)
+                .with("sample_periods", sampling.samples);
+            attach_metadata(common.metadata, &out, &meta)?;
+            let options = TorusOptions {
+                max_denominator,
+                ..TorusOptions::default()
+            };
+            let report = detect_torus(&samples, &options).ok_or(ChaosError::InsufficientTrajectory {
+                needed: 16.0,
+                available: samples.len() as f64,
+            })?;
+            let verdict = if let Some(p) = report.period {
+                format!("periodic orbit (period {})", p)
+            } else if report.is_torus(&options) {
+                "torus (quasi-periodic)".to_string()
+            } else if report.is_locked(&options) {
+                format!("closed curve locked near {}/{}", report.rational.0, report.rational.1)
+            } else {
+                "no invariant curve (chaotic or higher-dimensional torus)".to_string()
+            };
+            Ok(format!(
+                "Wrote {} section points to {}
+{}: order preservation = {:.4}, rotation number = {:.6} \
+                 (nearest {}/{}, error {:.2e}, {:?} angle)",
+                samples.len(),
+                out,
+                verdict,
+                report.order_preservation,
+                report.rotation_number,
+                report.rational.0,
+                report.rational.1,
+                report.rational_error,
+                report.angle
+            ))
+        }
+        Command::Stats {
+            common,
+            sampling,
+            quantity,
+            section,
+            bins,
+            out,
+            cdf,
+        } => {
+            let mut params = common.params()?;
+            params.validate_periodic()?;
+            let out = prepare_output(&out)?;
+            let period = 2.0 * PI / params.omega_d;
+            params.t_end = period * (sampling.transient + sampling.samples) as f64 + params.dt;
+            let states: Vec<(f64, f64)> = if section {
+                cached(sampling.cache.as_deref(), poincare_key(&params, &common, &sampling), || {
+                    poincare(&params, common.theta0, common.omega0, sampling.transient, sampling.samples)
+                })?
+            } else {
+                let t_start = period * sampling.transient as f64;
+                check_memory_budget(&params, estimated_steps(&params).saturating_add(1))?;
+                TrajectoryIter::new(&params, common.theta0, common.omega0)
+                    .filter(|(t, _)| *t >= t_start)
+                    .map(|(_, s)| (s.theta, s.omega))
+                    .collect()
+            };
+            let values: Vec<f64> = states
+                .iter()
+                .map(|&(theta, omega)| match quantity {
+                    Quantity::Theta => wrap_angle(theta),
+                    Quantity::Omega => omega,
+                    Quantity::Energy => pendulum_energy(theta, omega, &params),
+                })
+                .collect();
+            let s = summary(&values).ok_or(ChaosError::InsufficientTrajectory { needed: 1.0, available: 0.0 })?;
+            let h = Histogram::new(&values, &bins.map_or(Bins::Auto, Bins::Count));
+            write_histogram_csv(&out, &h)?;
+            let meta = RunMetadata::new(&params, common.theta0, common.omega0)
+                .with("transient_periods", sampling.transient)
+                .with("sample_periods", sampling.samples)
+                .with("section", section);
+            attach_metadata(common.metadata, &out, &meta)?;
+            if let Some(path) = &cdf {
+                let path = &prepare_output(path)?;
+                write_ecdf_csv(path, &Ecdf::new(&values))?;
+                attach_metadata(common.metadata, path, &meta)?;
+            }
+            Ok(format!(
+                "Wrote {}-bin histogram of {} values to {}\nmean = {:.6}, std = {:.6}, skewness = {:.6}, kurtosis = {:.6}, range = [{:.6}, {:.6}]",
+                h.len(),
+                s.count,
+                out,
+                s.mean,
+                s.std_dev,
+                s.skewness,
+                s.kurtosis,
+                s.min,
+                s.max
+            ))
+        }
+        Command::Arnold {
+            common,
+            omega_from,
+            omega_to,
+            omega_steps,
+            fd_from,
+            fd_to,
+            fd_steps,
+            transient,
+            samples,
+            out,
+            png,
+            max_denominator,
+        } => {
+            let params = common.params()?;
+            params.validate()?;
+            let map = arnold_tongues(
+                &params,
+                (omega_from, omega_to),
+                omega_steps,
+                (fd_from, fd_to),
+                fd_steps,
+                common.theta0,
+                common.omega0,
+                transient,
+                samples,
+            )?;
+            let out = prepare_output(&out)?;
+            write_arnold_tongues_csv(&out, &map)?;
+            let meta = RunMetadata::new(&params, common.theta0, common.omega0)
+                .with("omega_from", omega_from)
+                .with("omega_to", omega_to)
+                .with("fd_from", fd_from)
+                .with("fd_to", fd_to)
+                .with("transient_periods", transient)
+                .with("sample_periods", samples);
+            attach_metadata(common.metadata, &out, &meta)?;
+            if let Some(png) = &png {
+                let png = &prepare_output(png)?;
+                write_arnold_tongues_png(png, &map, max_denominator, 1.0 / samples.max(1) as f64)?;
+            }
+            Ok(format!("Wrote {}x{} rotation numbers to {}", map.f_ds.len(), map.omega_ds.len(), out))
+        }
+        Command::Pipe { common, batch } => {
+            let params = common.params()?;
+            params.validate()?;
+            let stdin = std::io::stdin().lock();
+            let count = chaos::pipeline::run_pipeline(&params, stdin, std::io::stdout().lock(), batch)?;
+            // 标准输出只留给结果行
+            eprintln!("Processed {} records", count);
+            Ok(String::new())
+        }
+        #[cfg(feature = "tui")]
+        Command::Explore {
+            common,
+            transient,
+            samples,
+            phase,
+        } => {
+            let params = common.params()?;
+            params.validate()?;
+            let mut explorer = chaos::tui::Explorer::new(params, common.theta0, common.omega0);
+            explorer.transient_periods = transient;
+            explorer.sample_periods = samples;
+            if phase {
+                explorer.view = chaos::tui::ExplorerView::PhasePortrait;
+            }
+            let p = chaos::tui::run_explorer(explorer)?.params;
+            Ok(format!("Explorer closed at f_d = {}, q = {}, omega_d = {}", p.f_d, p.q, p.omega_d))
+        }
+        Command::Presets => Ok(PRESETS
+            .iter()
+            .map(|p| {
+                format!(
+                    "{:<12} {:<17} --theta0 {} --omega0 {}  {}",
+                    p.name,
+                    p.expected.to_string(),
+                    p.theta0,
+                    p.omega0,
+                    p.description
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")),
+        #[cfg(feature = "serve")]
+        Command::Serve { addr } => {
+            eprintln!("Listening on ws://{}/", addr);
+            chaos::serve::serve(addr.as_str(), |peer| eprintln!("Client connected from {}", peer))?;
+            Ok("Server stopped".to_string())
+        }
+    }
+}
+
+fn main() {
+    // 第一次 Ctrl-C 让当前计算停下并写出部分结果，第二次直接退出
+    let _ = ctrlc::set_handler(|| {
+        if INTERRUPTED.swap(true, Ordering::Relaxed) {
+            std::process::exit(130);
+        }
+    });
+    let cli = Cli::parse();
+    let command = cli.command.unwrap_or_else(|| {
+        Cli::parse_from(["chaos", "poincare"])
+            .command
+            .expect("poincare subcommand")
+    });
+    if let Some(name) = &cli.experiment {
+        match Experiment::create(&cli.runs_dir, name, std::env::args().collect()) {
+            Ok(experiment) => {
+                eprintln!("Experiment directory: {}", experiment.dir().display());
+                *EXPERIMENT.lock().unwrap() = Some(experiment);
+            }
+            Err(e) => {
+                eprintln!("Error: cannot create experiment directory in {}: {}", cli.runs_dir, e);
+                std::process::exit(1);
+            }
+        }
+    }
+    let result = run(command);
+    if let Some(experiment) = EXPERIMENT.lock().unwrap().take() {
+        let logged = match &result {
+            Ok(msg) => experiment.finish(Ok(msg)),
+            Err(e) => experiment.finish(Err(&e.to_string())),
+        };
+        if let Err(e) = logged {
+            eprintln!("Warning: failed to finalize experiment directory: {}", e);
+        }
+    }
+    match result {
+        Ok(msg) if msg.is_empty() => {}
+        Ok(msg) => println!("{}", msg),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,287 @@
+//manifolds.rs
+use std::fs::File;
+use std::io::Write;
+use rayon::prelude::*;
+use crate::error::ChaosError;
+use crate::model::{PendulumParams, TimeDirection};
+use crate::periodic_orbits::{check_orbit_params, map_with_jacobian, orbit_params, Jacobian, PeriodicOrbit};
+use crate::solve_equation::{wrap_angle, State};
+use crate::stroboscopic::StroboscopicMap;
+
+// 鞍点 x* 的不稳定流形 W^u 是在 F^{-n} 下趋于 x* 的点集，稳定流形 W^s 是在 F^n 下趋于 x* 的点集。
+// 在 x* 附近它们分别与单值矩阵的不稳定、稳定特征向量相切，因此从特征方向上的一小段线段出发，
+// 正向迭代这段线段得到 W^u，用反向积分（逆映射）迭代得到 W^s。每次迭代线段被拉长，
+// 相邻像点距离超过 max_gap 时在原像之间插入中点再映射，保持曲线的分辨率。
+// W^u 与 W^s 横截相交（同宿点）后，两者来回折叠形成同宿缠结，这正是混沌吸引子的骨架。
+
+/// 流形生长的设置
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ManifoldOptions {
+    /// 初始线段远端离鞍点的距离（应在线性近似有效的范围内）
+    pub initial_distance: f64,
+    /// 初始线段上的点数
+    pub segment_points: usize,
+    /// 映射的迭代次数
+    pub iterations: usize,
+    /// 相邻点的最大间距，超过时插点
+    pub max_gap: f64,
+    /// 每个分支的最大点数，达到后停止插点与迭代
+    pub max_points: usize,
+    /// |ω| 超过它的部分不再插点（有阻尼时稳定流形在反向积分下能量无限增长），写 CSV 时略去
+    pub max_omega: f64,
+}
+
+impl Default for ManifoldOptions {
+    fn default() -> Self {
+        ManifoldOptions {
+            initial_distance: 1e-4,
+            segment_points: 20,
+            iterations: 8,
+            max_gap: 0.05,
+            max_points: 20_000,
+            max_omega: 5.0,
+        }
+    }
+}
+
+/// 鞍点的两条一维流形，每条有沿特征向量正负两个方向的分支
+///
+/// 曲线上的 θ 不折叠（连续），写 CSV 时再折叠并分段；|ω| > max_omega 的点只是稀疏地保留下来维持曲线的连接顺序。
+#[derive(Debug, Clone)]
+pub struct SaddleManifolds {
+    pub saddle: (f64, f64),
+    pub max_omega: f64,
+    /// 不稳定、稳定乘子
+    pub unstable_multiplier: f64,
+    pub stable_multiplier: f64,
+    pub unstable: [Vec<(f64, f64)>; 2],
+    pub stable: [Vec<(f64, f64)>; 2],
+}
+
+// 逆映射 F^{-1}：反向积分一个驱动周期
+fn inverse_map(params: &PendulumParams) -> StroboscopicMap {
+    let mut backward = params.clone();
+    backward.direction = TimeDirection::Backward;
+    StroboscopicMap::new(&backward)
+}
+
+// 2×2 矩阵属于特征值 lambda 的单位特征向量
+fn eigenvector(m: &[[f64; 2]; 2], lambda: f64) -> (f64, f64) {
+    let a = (m[0][1], lambda - m[0][0]);
+    let b = (lambda - m[1][1], m[1][0]);
+    let v = if a.0.hypot(a.1) >= b.0.hypot(b.1) { a } else { b };
+    let norm = v.0.hypot(v.1);
+    (v.0 / norm, v.1 / norm)
+}
+
+// 从鞍点沿 direction 的线段出发，用 map 迭代生长一条分支
+//
+// 初始线段取 [d/|λ|, d]（λ 为负时取 [d/λ², d]，用两次迭代覆盖同一侧），恰好是一个基本区域，
+// 它的像首尾相接，合起来就是整条分支；点按几何级数分布，|λ| 很大时靠近鞍点的一端也有足够的点。
+fn grow<M: Fn(State) -> State + Sync>(
+    saddle: State,
+    direction: (f64, f64),
+    stretch: f64,
+    map: M,
+    options: &ManifoldOptions,
+) -> Vec<(f64, f64)> {
+    let n = options.segment_points.max(2);
+    let d = options.initial_distance;
+    let mut level: Vec<State> = (0..n)
+        .map(|i| {
+            let s = d * stretch.powf(i as f64 / (n - 1) as f64 - 1.0);
+            State {
+                theta: saddle.theta + s * direction.0,
+                omega: saddle.omega + s * direction.1,
+            }
+        })
+        .collect();
+    let mut curve: Vec<(f64, f64)> = level.iter().map(|s| (s.theta, s.omega)).collect();
+
+    for _ in 0..options.iterations {
+        let mut preimages = level;
+        let mut images: Vec<State> = preimages.par_iter().map(|&s| map(s)).collect();
+        // 在间距过大的相邻点之间插入原像的中点，直到分辨率足够或点数达到上限
+        loop {
+            let gaps: Vec<usize> = (1..images.len())
+                .filter(|&i| {
+                    let (a, b) = (&images[i - 1], &images[i]);
+                    a.omega.abs().max(b.omega.abs()) <= options.max_omega
+                        && (a.theta - b.theta).hypot(a.omega - b.omega) > options.max_gap
+                })
+                .collect();
+            if gaps.is_empty() || curve.len() + images.len() + gaps.len() > options.max_points {
+                break;
+            }
+            let midpoints: Vec<State> = gaps
+                .iter()
+                .map(|&i| State {
+                    theta: 0.5 * (preimages[i - 1].theta + preimages[i].theta),
+                    omega: 0.5 * (preimages[i - 1].omega + preimages[i].omega),
+                })
+                .collect();
+            let mapped: Vec<State> = midpoints.par_iter().map(|&s| map(s)).collect();
+            let mut new_pre = Vec::with_capacity(preimages.len() + gaps.len());
+            let mut new_img = Vec::with_capacity(images.len() + gaps.len());
+            let mut next_gap = 0;
+            for i in 0..images.len() {
+                if next_gap < gaps.len() && gaps[next_gap] == i {
+                    new_pre.push(midpoints[next_gap]);
+                    new_img.push(mapped[next_gap]);
+                    next_gap += 1;
+                }
+                new_pre.push(preimages[i]);
+                new_img.push(images[i]);
+            }
+            preimages = new_pre;
+            images = new_img;
+        }
+        if curve.len() + images.len() > options.max_points {
+            break;
+        }
+        // 像的第一个点与上一段的最后一个点重合（基本区域首尾相接），跳过
+        curve.extend(images.iter().skip(1).map(|s| (s.theta, s.omega)));
+        level = images;
+    }
+    curve
+}
+
+/// 计算周期轨道第一个点（orbit.points[0]）处的稳定与不稳定流形
+///
+/// 映射取 F^period；轨道必须是乘子为实数、一个模大于 1 一个模小于 1 的鞍点，否则返回 InvalidParams。
+/// 稳定流形靠反向积分生长，耗散系统反向积分时面积指数增长，iterations 不宜太大。
+/// 积分设置与 find_periodic_orbit 相同（RK4，不看 params.integrator）。
+pub fn saddle_manifolds(
+    params: &PendulumParams,
+    orbit: &PeriodicOrbit,
+    options: &ManifoldOptions,
+) -> Result<SaddleManifolds, ChaosError> {
+    check_orbit_params(params, orbit.period)?;
+    let p = orbit_params(params);
+    let period = orbit.period;
+    let saddle = State {
+        theta: orbit.points[0].0,
+        omega: orbit.points[0].1,
+    };
+    let (_, m) = map_with_jacobian(&p, saddle, period, Jacobian::Variational);
+    let [(lu, iu), (ls, is)] = orbit.multipliers;
+    if iu != 0.0 || is != 0.0 || lu.abs() <= 1.0 || ls.abs() >= 1.0 {
+        return Err(ChaosError::InvalidParams(format!(
+            "orbit is not a saddle, multipliers {:?}",
+            orbit.multipliers
+        )));
+    }
+    let vu = eigenvector(&m, lu);
+    let vs = eigenvector(&m, ls);
+    // 负乘子每次迭代翻到另一侧，基本区域按两次迭代取
+    let (stretch_u, steps_u) = if lu > 0.0 { (lu, 1) } else { (lu * lu, 2) };
+    let (stretch_s, steps_s) = if ls > 0.0 { (1.0 / ls, 1) } else { (1.0 / (ls * ls), 2) };
+    let (map, inverse) = (StroboscopicMap::new(&p), inverse_map(&p));
+    let forward = |s: State| map.iterate_n(s, period * steps_u);
+    let backward = |s: State| inverse.iterate_n(s, period * steps_s);
+
+    let branch = |v: (f64, f64), sign: f64, stretch: f64, forward_map: bool| {
+        let dir = (sign * v.0, sign * v.1);
+        if forward_map {
+            grow(saddle, dir, stretch, forward, options)
+        } else {
+            grow(saddle, dir, stretch, backward, options)
+        }
+    };
+    Ok(SaddleManifolds {
+        saddle: orbit.points[0],
+        max_omega: options.max_omega,
+        unstable_multiplier: lu,
+        stable_multiplier: ls,
+        unstable: [branch(vu, 1.0, stretch_u, true), branch(vu, -1.0, stretch_u, true)],
+        stable: [branch(vs, 1.0, stretch_s, false), branch(vs, -1.0, stretch_s, false)],
+    })
+}
+
+/// 把流形写成 CSV（列：manifold,branch,segment,theta,omega）
+///
+/// manifold 为 unstable 或 stable，branch 为 0/1；θ 折叠到 [-π, π)，跨越 ±π 或略去 |ω| > max_omega 的点时
+/// segment 加一，作图时按 (manifold, branch, segment) 分组连线即可。
+pub fn write_manifolds_csv(path: &str, manifolds: &SaddleManifolds) -> std::io::Result<()> {
+    let mut f = std::io::BufWriter::new(File::create(path)?);
+    writeln!(f, "manifold,branch,segment,theta,omega")?;
+    let curves = [("unstable", &manifolds.unstable), ("stable", &manifolds.stable)];
+    for (name, branches) in curves {
+        for (b, curve) in branches.iter().enumerate() {
+            let mut segment = 0;
+            let mut last: Option<f64> = None;
+            for &(theta, omega) in curve.iter() {
+                if omega.is_nan() || omega.abs() > manifolds.max_omega {
+                    if last.take().is_some() {
+                        segment += 1;
+                    }
+                    continue;
+                }
+                let wrapped = wrap_angle(theta);
+                if let Some(prev) = last
+                    && (wrapped - prev).abs() > std::f64::consts::PI
+                {
+                    segment += 1;
+                }
+                last = Some(wrapped);
+                writeln!(f, "{},{},{},{:.12},{:.12}", name, b, segment, wrapped, omega)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::periodic_orbits::{find_periodic_orbit, NewtonOptions};
+
+    #[test]
+    fn test_manifolds_of_inverted_saddle() {
+        // 弱驱动下倒立位置附近的周期 1 轨道是鞍点
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.q = 0.5;
+        params.omega_d = 2.0 / 3.0;
+        params.f_d = 0.3;
+        params.set_steps_per_period(100);
+        let orbit = find_periodic_orbit(&params, 3.1, 0.0, 1, &NewtonOptions::default()).unwrap();
+        assert!(!orbit.is_stable());
+        assert!(orbit.multipliers[1].0.abs() < 1.0);
+
+        let options = ManifoldOptions {
+            iterations: 3,
+            max_points: 2000,
+            ..ManifoldOptions::default()
+        };
+        let manifolds = saddle_manifolds(&params, &orbit, &options).unwrap();
+        let saddle = orbit.points[0];
+        let distance = |(theta, omega): (f64, f64)| wrap_angle(theta - saddle.0).hypot(omega - saddle.1);
+        for curve in manifolds.unstable.iter().chain(&manifolds.stable) {
+            assert!(curve.len() > options.segment_points);
+            // 曲线从鞍点附近出发，逐渐远离
+            assert!(distance(curve[0]) < options.initial_distance);
+            assert!(distance(curve[curve.len() - 1]) > 0.1);
+        }
+        let mut p = params.clone();
+        p.set_steps_per_period(100);
+        // 不稳定流形上的点映射后仍落在流形上
+        let curve = &manifolds.unstable[0];
+        let (theta, omega) = curve[curve.len() / 3];
+        let map = StroboscopicMap::new(&p);
+        let image = map.iterate(State { theta, omega });
+        let nearest = curve
+            .iter()
+            .map(|(t, w)| (t - image.theta).hypot(w - image.omega))
+            .fold(f64::INFINITY, f64::min);
+        assert!(nearest < options.max_gap, "{}", nearest);
+        // 稳定流形上的点正向迭代后趋近鞍点
+        let point = manifolds.stable[0][manifolds.stable[0].len() / 2];
+        let image = map.iterate_n(State { theta: point.0, omega: point.1 }, 2);
+        assert!(distance((image.theta, image.omega)) < distance(point));
+
+        let path = std::env::temp_dir().join("chaos_manifolds_test.csv");
+        write_manifolds_csv(path.to_str().unwrap(), &manifolds).unwrap();
+        std::fs::remove_file(path).ok();
+    }
+}
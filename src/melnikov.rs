@@ -0,0 +1,152 @@
+//melnikov.rs
+use std::f64::consts::PI;
+use std::fs::File;
+use std::io::Write;
+use crate::model::PendulumParams;
+
+// Melnikov 方法：把阻尼与驱动看作对无阻尼单摆 θ'' + ω0² sin θ = 0（ω0² = g/l）的小扰动，
+// 分界线（同宿轨道）θ0(t) = 2 arctan(sinh ω0 t)、θ0'(t) = 2 ω0 sech(ω0 t) 上扰动做功的积分
+//   M(t0) = ∫ θ0'(t) [ -q θ0'(t) + f(t + t0) ] dt
+// 度量了鞍点的稳定与不稳定流形在 t0 相位处的分离距离。M 有简单零点时两条流形横截相交，
+// 出现同宿缠结（Smale 马蹄），即瞬态混沌与分形吸引域的开端。正弦驱动 f = f_d sin Ωt 时
+//   M(t0) = -8 q ω0 + 2π f_d sech(πΩ / 2ω0) sin(Ω t0)，
+// 阈值 f_c = (4 q ω0 / π) cosh(πΩ / 2ω0)。这是一阶微扰结果，q、f_d 不小时只是定性估计；
+// 横截相交是持续混沌吸引子的必要条件而非充分条件。
+
+/// 分界线上半支的角速度 θ0'(t) = 2 ω0 sech(ω0 t)，t = 0 时经过 θ = 0
+pub fn homoclinic_velocity(t: f64, params: &PendulumParams) -> f64 {
+    let omega0 = (params.g / params.l).sqrt();
+    2.0 * omega0 / (omega0 * t).cosh()
+}
+
+/// 分界线上半支的角度 θ0(t) = 2 arctan(sinh ω0 t)
+pub fn homoclinic_angle(t: f64, params: &PendulumParams) -> f64 {
+    let omega0 = (params.g / params.l).sqrt();
+    2.0 * (omega0 * t).sinh().atan()
+}
+
+/// 正弦驱动下 Melnikov 函数的解析式 M(t0) = -8 q ω0 + 2π f_d sech(πΩ / 2ω0) sin(Ω t0)
+///
+/// 只对 Forcing::Sine 与外加力矩（DriveType::Torque）成立；其他波形与参数激励用 melnikov_numerical。
+pub fn melnikov_function(t0: f64, params: &PendulumParams) -> f64 {
+    let omega0 = (params.g / params.l).sqrt();
+    -8.0 * params.q * omega0
+        + 2.0 * PI * params.f_d / (PI * params.omega_d / (2.0 * omega0)).cosh() * (params.omega_d * t0).sin()
+}
+
+/// 数值积分的 Melnikov 函数，驱动项取 params.drive_torque 在分界线上的值（任意波形与驱动方式），
+/// 在 [-L, L]（L = 40 / ω0）上用 Simpson 公式积分 steps 段
+pub fn melnikov_numerical(t0: f64, params: &PendulumParams, steps: usize) -> f64 {
+    let omega0 = (params.g / params.l).sqrt();
+    let half_width = 40.0 / omega0;
+    let n = steps.max(2).next_multiple_of(2);
+    let h = 2.0 * half_width / n as f64;
+    let integrand = |t: f64| {
+        let v = homoclinic_velocity(t, params);
+        v * (-params.q * v + params.drive_torque(homoclinic_angle(t, params), t + t0))
+    };
+    let mut sum = integrand(-half_width) + integrand(half_width);
+    for i in 1..n {
+        let weight = if i % 2 == 1 { 4.0 } else { 2.0 };
+        sum += weight * integrand(-half_width + i as f64 * h);
+    }
+    sum * h / 3.0
+}
+
+/// M 随驱动相位 φ = Ω t0 ∈ [0, 2π) 的变化，返回 n_phases 个 (φ, M)，数值积分 steps 段
+pub fn melnikov_curve(params: &PendulumParams, n_phases: usize, steps: usize) -> Vec<(f64, f64)> {
+    (0..n_phases)
+        .map(|k| {
+            let phase = 2.0 * PI * k as f64 / n_phases as f64;
+            (phase, melnikov_numerical(phase / params.omega_d, params, steps))
+        })
+        .collect()
+}
+
+/// 正弦驱动下同宿缠结出现的临界驱动幅度 f_c = (4 q ω0 / π) cosh(πΩ / 2ω0)
+pub fn critical_forcing(params: &PendulumParams) -> f64 {
+    let omega0 = (params.g / params.l).sqrt();
+    4.0 * params.q * omega0 / PI * (PI * params.omega_d / (2.0 * omega0)).cosh()
+}
+
+/// 数值版临界幅度：M 的驱动项对 f_d 是线性的，f_c = 8 q ω0 / max_φ ∫ θ0' w dt（w 为 f_d = 1 的波形）
+///
+/// 适用于以 f_d 为幅度的波形（正弦、方波、锯齿），相位取 n_phases 个；驱动项恒不为正时返回无穷大。
+pub fn critical_forcing_numerical(params: &PendulumParams, n_phases: usize, steps: usize) -> f64 {
+    let omega0 = (params.g / params.l).sqrt();
+    let mut unit = params.clone();
+    unit.f_d = 1.0;
+    unit.q = 0.0;
+    let peak = melnikov_curve(&unit, n_phases, steps).into_iter().map(|(_, m)| m).fold(f64::NEG_INFINITY, f64::max);
+    if peak > 0.0 {
+        8.0 * params.q * omega0 / peak
+    } else {
+        f64::INFINITY
+    }
+}
+
+/// 固定 q 时阈值 f_c 随驱动频率的曲线 (Ω, f_c)，用于与模拟得到的混沌区边界比较
+pub fn threshold_curve(params: &PendulumParams, omega_values: &[f64]) -> Vec<(f64, f64)> {
+    omega_values
+        .iter()
+        .map(|&omega_d| {
+            let mut p = params.clone();
+            p.omega_d = omega_d;
+            (omega_d, critical_forcing(&p))
+        })
+        .collect()
+}
+
+/// 把 Melnikov 函数写成 CSV（两列：phase,melnikov）
+pub fn write_melnikov_csv(path: &str, curve: &[(f64, f64)]) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(f, "phase,melnikov")?;
+    for (phase, m) in curve {
+        writeln!(f, "{:.12},{:.12}", phase, m)?;
+    }
+    Ok(())
+}
+
+/// 把阈值曲线写成 CSV（两列：omega_d,f_critical）
+pub fn write_threshold_curve_csv(path: &str, curve: &[(f64, f64)]) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(f, "omega_d,f_critical")?;
+    for (omega_d, f_c) in curve {
+        writeln!(f, "{:.12},{:.12}", omega_d, f_c)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_melnikov_threshold_matches_closed_form() {
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.q = 0.5;
+        params.omega_d = 2.0 / 3.0;
+        params.f_d = 1.2;
+
+        // 经典参数下 f_c = (2/π) cosh(π/3) ≈ 1.0188
+        let f_c = critical_forcing(&params);
+        assert!((f_c - 2.0 / PI * (PI / 3.0).cosh()).abs() < 1e-12);
+        assert!((f_c - 1.0188).abs() < 1e-3, "{}", f_c);
+
+        for t0 in [0.0, 1.0, 2.5, 4.0] {
+            let exact = melnikov_function(t0, &params);
+            assert!((melnikov_numerical(t0, &params, 4000) - exact).abs() < 1e-8);
+        }
+        assert!((critical_forcing_numerical(&params, 64, 2000) - f_c).abs() < 1e-6);
+
+        // f_d 高于阈值时 M 变号（有简单零点），低于阈值时恒为负
+        let sign_changes = |p: &PendulumParams| melnikov_curve(p, 64, 2000).iter().any(|(_, m)| *m > 0.0);
+        assert!(sign_changes(&params));
+        params.f_d = 0.9;
+        assert!(!sign_changes(&params));
+
+        let curve = threshold_curve(&params, &[0.5, 1.0, 1.5]);
+        assert!(curve.windows(2).all(|w| w[1].1 > w[0].1));
+    }
+}
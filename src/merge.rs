@@ -0,0 +1,198 @@
+//merge.rs
+use std::io::{Error, ErrorKind};
+use crate::basin::{same_attractor, BasinMap};
+use crate::bifurcation::{read_scan_csv, write_scan_csv, ScanPoint, SweepParameter};
+use crate::density::Histogram2d;
+
+// 两个参数/坐标值视为同一个格点的相对容差
+const GRID_EPS: f64 = 1e-9;
+
+fn same_value(a: f64, b: f64) -> bool {
+    (a - b).abs() <= GRID_EPS * a.abs().max(b.abs()).max(1.0)
+}
+
+fn inconsistent(msg: String) -> Error {
+    Error::new(ErrorKind::InvalidData, msg)
+}
+
+/// 合并分段计算的分岔扫描结果，按参数排序
+///
+/// 各分段可以在边界处重叠：同一参数值出现多次时，要求周期一致且庞加莱点集在 tolerance 内重合，
+/// 然后只保留一份；不一致（例如分段用了不同的初值或积分设置）时返回 InvalidData 错误。
+pub fn merge_scan_shards(shards: &[Vec<ScanPoint>], tolerance: f64) -> std::io::Result<Vec<ScanPoint>> {
+    let mut all: Vec<&ScanPoint> = shards.iter().flatten().collect();
+    all.sort_by(|a, b| a.parameter.total_cmp(&b.parameter));
+
+    let mut merged: Vec<ScanPoint> = Vec::with_capacity(all.len());
+    for point in all {
+        if let Some(last) = merged.last()
+            && same_value(last.parameter, point.parameter)
+        {
+            if last.period != point.period || !same_attractor(&last.samples, &point.samples, tolerance) {
+                return Err(inconsistent(format!(
+                    "shards disagree at parameter {}",
+                    point.parameter
+                )));
+            }
+            continue;
+        }
+        merged.push(point.clone());
+    }
+    Ok(merged)
+}
+
+/// 读取若干 write_scan_csv 输出文件，合并后写到 output
+///
+/// 所有输入的参数列名必须一致。返回合并后的扫描点。
+pub fn merge_scan_csv(inputs: &[&str], output: &str, tolerance: f64) -> std::io::Result<Vec<ScanPoint>> {
+    let mut name: Option<String> = None;
+    let mut shards = Vec::with_capacity(inputs.len());
+    for path in inputs {
+        let (n, points) = read_scan_csv(path)?;
+        match &name {
+            Some(expected) if *expected != n => {
+                return Err(inconsistent(format!(
+                    "{}: parameter column {} does not match {}",
+                    path, n, expected
+                )));
+            }
+            _ => name = Some(n),
+        }
+        shards.push(points);
+    }
+    let name = name.ok_or_else(|| inconsistent("no input shards".to_string()))?;
+    let parameter = SweepParameter::from_name(&name)
+        .ok_or_else(|| inconsistent(format!("unknown sweep parameter {}", name)))?;
+
+    let merged = merge_scan_shards(&shards, tolerance)?;
+    write_scan_csv(output, parameter, &merged)?;
+    Ok(merged)
+}
+
+/// 把按 θ 分块计算的吸引域图拼成一张
+///
+/// 各块必须使用相同的 ω 网格；吸引子按 same_attractor 在块之间重新编号，
+/// 编号顺序为第一次出现的顺序。重叠的 θ 行要求重新编号后的标签完全一致。
+pub fn merge_basin_tiles(tiles: &[BasinMap], tolerance: f64) -> std::io::Result<BasinMap> {
+    let first = tiles.first().ok_or_else(|| inconsistent("no basin tiles".to_string()))?;
+    let omegas = first.omegas.clone();
+
+    let mut attractors: Vec<Vec<(f64, f64)>> = Vec::new();
+    let mut representatives = Vec::new();
+    let mut rows: Vec<(f64, Vec<usize>)> = Vec::new();
+
+    for (k, tile) in tiles.iter().enumerate() {
+        if tile.omegas.len() != omegas.len() || !tile.omegas.iter().zip(&omegas).all(|(a, b)| same_value(*a, *b)) {
+            return Err(inconsistent(format!("tile {} uses a different omega grid", k)));
+        }
+        let relabel: Vec<usize> = tile
+            .attractors
+            .iter()
+            .zip(&tile.representatives)
+            .map(|(section, rep)| {
+                match attractors.iter().position(|a| same_attractor(a, section, tolerance)) {
+                    Some(i) => i,
+                    None => {
+                        attractors.push(section.clone());
+                        representatives.push(*rep);
+                        attractors.len() - 1
+                    }
+                }
+            })
+            .collect();
+        for (i, theta) in tile.thetas.iter().enumerate() {
+            let row = (0..omegas.len()).map(|j| relabel[tile.label_at(i, j)]).collect();
+            rows.push((*theta, row));
+        }
+    }
+
+    rows.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut thetas = Vec::with_capacity(rows.len());
+    let mut labels = Vec::with_capacity(rows.len() * omegas.len());
+    let mut last: Option<&(f64, Vec<usize>)> = None;
+    for row in &rows {
+        if let Some(prev) = last
+            && same_value(prev.0, row.0)
+        {
+            if prev.1 != row.1 {
+                return Err(inconsistent(format!("tiles disagree on overlapping row theta = {}", row.0)));
+            }
+            continue;
+        }
+        thetas.push(row.0);
+        labels.extend_from_slice(&row.1);
+        last = Some(row);
+    }
+
+    Ok(BasinMap {
+        thetas,
+        omegas,
+        labels,
+        attractors,
+        representatives,
+    })
+}
+
+/// 合并由初值云分块演化得到的直方图（例如同一时刻的密度快照）
+///
+/// 各块的分箱设置必须一致；计数与越界点数直接相加。
+pub fn merge_histograms(chunks: &[Histogram2d]) -> std::io::Result<Histogram2d> {
+    let first = chunks.first().ok_or_else(|| inconsistent("no histogram chunks".to_string()))?;
+    let mut merged = Histogram2d::new(first.theta_bins, first.omega_bins, first.omega_range);
+    for (k, h) in chunks.iter().enumerate() {
+        if h.theta_bins != merged.theta_bins
+            || h.omega_bins != merged.omega_bins
+            || !same_value(h.omega_range.0, merged.omega_range.0)
+            || !same_value(h.omega_range.1, merged.omega_range.1)
+        {
+            return Err(inconsistent(format!("chunk {} uses different bins", k)));
+        }
+        for (c, x) in merged.counts.iter_mut().zip(&h.counts) {
+            *c += x;
+        }
+        merged.outside += h.outside;
+    }
+    Ok(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_overlapping_shards() {
+        let point = |parameter: f64, period: Option<usize>, x: f64| ScanPoint {
+            parameter,
+            period,
+            samples: vec![(x, 0.0); 4],
+        };
+        let a = vec![point(1.0, Some(1), 0.1), point(1.1, Some(1), 0.2)];
+        let b = vec![point(1.1, Some(1), 0.2), point(1.2, None, 0.3)];
+        let merged = merge_scan_shards(&[b, a.clone()], 1e-9).unwrap();
+        let params: Vec<f64> = merged.iter().map(|p| p.parameter).collect();
+        assert_eq!(params, vec![1.0, 1.1, 1.2]);
+
+        let conflicting = vec![point(1.1, Some(2), 0.2)];
+        assert!(merge_scan_shards(&[a, conflicting], 1e-9).is_err());
+
+        // 两块吸引域图的吸引子编号相反，合并后应统一
+        let left = BasinMap {
+            thetas: vec![0.0, 0.5],
+            omegas: vec![0.0, 1.0],
+            labels: vec![0, 1, 0, 0],
+            attractors: vec![vec![(0.1, 0.0)], vec![(2.0, 1.0)]],
+            representatives: vec![(0.0, 0.0), (0.0, 1.0)],
+        };
+        let right = BasinMap {
+            thetas: vec![0.5, 1.0],
+            omegas: vec![0.0, 1.0],
+            labels: vec![1, 1, 0, 1],
+            attractors: vec![vec![(2.0, 1.0)], vec![(0.1, 0.0)]],
+            representatives: vec![(1.0, 0.0), (0.5, 0.0)],
+        };
+        let map = merge_basin_tiles(&[left, right], 1e-9).unwrap();
+        assert_eq!(map.thetas, vec![0.0, 0.5, 1.0]);
+        assert_eq!(map.labels, vec![0, 1, 0, 0, 1, 0]);
+        assert_eq!(map.attractors.len(), 2);
+    }
+}
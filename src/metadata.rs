@@ -0,0 +1,104 @@
+//metadata.rs
+use std::fs::File;
+use std::io::Write;
+use serde::Serialize;
+use crate::model::PendulumParams;
+
+// 输出文件本身不记录参数，事后无法复现。这里把 crate 版本、完整参数、初值与运行设置
+// 以两种方式之一附在输出旁边：
+// - 注释头：在 CSV 最前面加若干以 "# " 开头的行（pandas 用 comment='#' 读取）；
+// - 旁路文件：与输出同名、扩展名换成 .run.json 的 JSON 文件，适合二进制或压缩输出。
+// 本 crate 的 CSV 读取函数（read_scan_csv 等）会跳过注释行。
+
+/// 一次运行的元数据
+#[derive(Debug, Clone, Serialize)]
+pub struct RunMetadata {
+    pub crate_version: String,
+    pub params: PendulumParams,
+    pub initial_theta: f64,
+    pub initial_omega: f64,
+    /// 其他运行设置（过渡周期数、扫描范围等），按插入顺序输出
+    pub settings: Vec<(String, String)>,
+}
+
+impl RunMetadata {
+    pub fn new(params: &PendulumParams, initial_theta: f64, initial_omega: f64) -> Self {
+        RunMetadata {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            params: params.clone(),
+            initial_theta,
+            initial_omega,
+            settings: Vec::new(),
+        }
+    }
+
+    /// 追加一项运行设置
+    pub fn with<V: ToString>(mut self, key: &str, value: V) -> Self {
+        self.settings.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// 写注释头：版本、初值、各项设置各占一行，参数为单行 JSON
+    pub fn write_header<W: Write>(&self, w: &mut W) -> std::io::Result<()> {
+        let params = serde_json::to_string(&self.params).map_err(std::io::Error::other)?;
+        writeln!(w, "# chaos {}", self.crate_version)?;
+        writeln!(w, "# params: {}", params)?;
+        writeln!(w, "# initial_theta: {}", self.initial_theta)?;
+        writeln!(w, "# initial_omega: {}", self.initial_omega)?;
+        for (key, value) in &self.settings {
+            writeln!(w, "# {}: {}", key, value)?;
+        }
+        Ok(())
+    }
+
+    /// 在已经写好的文本文件最前面插入注释头（流式复制，不把整个文件读进内存）
+    pub fn prepend_header(&self, path: &str) -> std::io::Result<()> {
+        let original = format!("{}.tmp", path);
+        std::fs::rename(path, &original)?;
+        let mut out = std::io::BufWriter::new(File::create(path)?);
+        self.write_header(&mut out)?;
+        std::io::copy(&mut File::open(&original)?, &mut out)?;
+        out.flush()?;
+        std::fs::remove_file(&original)
+    }
+
+    /// 旁路文件路径：data/poincare.csv -> data/poincare.run.json（.csv.gz 等多重扩展名整体替换）
+    pub fn sidecar_path(output: &str) -> String {
+        let path = std::path::Path::new(output);
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or(output);
+        let stem = name.split('.').next().filter(|s| !s.is_empty()).unwrap_or(name);
+        path.with_file_name(format!("{}.run.json", stem)).to_string_lossy().into_owned()
+    }
+
+    /// 把元数据写成 output 的旁路 JSON 文件，返回其路径
+    pub fn write_sidecar(&self, output: &str) -> std::io::Result<String> {
+        let path = Self::sidecar_path(output);
+        let f = File::create(&path)?;
+        serde_json::to_writer_pretty(f, self).map_err(std::io::Error::other)?;
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header_prepend_and_sidecar_path() {
+        let meta = RunMetadata::new(&PendulumParams::new(), 0.2, 0.0).with("transient_periods", 100);
+        let path = std::env::temp_dir().join("chaos_metadata_test.csv");
+        let path = path.to_str().unwrap();
+        std::fs::write(path, "theta,omega\n0.1,0.2\n").unwrap();
+        meta.prepend_header(path).unwrap();
+
+        let text = std::fs::read_to_string(path).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert!(lines[0].starts_with("# chaos "));
+        assert!(lines[1].starts_with("# params: {") && lines[1].contains("\"omega_d\""));
+        assert_eq!(lines[4], "# transient_periods: 100");
+        assert_eq!(&lines[5..], ["theta,omega", "0.1,0.2"]);
+
+        assert_eq!(RunMetadata::sidecar_path("data/poincare.csv"), "data/poincare.run.json");
+        assert_eq!(RunMetadata::sidecar_path("traj.csv.gz"), "traj.run.json");
+    }
+}
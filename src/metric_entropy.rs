@@ -0,0 +1,140 @@
+//metric_entropy.rs
+use serde::Serialize;
+use crate::analysis::least_squares;
+use crate::density::poincare_density;
+use crate::symbolic::{block_entropies, reliable_block_length};
+
+// 测度熵（Kolmogorov–Sinai 熵）的直方图估计：把庞加莱截面按不变测度直方图的格子划分，
+// 每个截面点记成所在格子的编号，再对这个符号序列做块熵统计（symbolic 模块）。
+//   H_1 即自然测度在该划分下的 Shannon 熵；
+//   条件熵 h_n = H_n - H_{n-1} 随块长单调减小并趋于划分下的测度熵，有限块长的偏差近似按 1/n 衰减，
+//   因此对 n = 2..=max_block 的 h_n 关于 1/n 做直线拟合，截距作为 n → ∞ 的外推值。
+// 有限样本使 H_n 偏低，块长越大偏差越大（约 (N_n - 1) / 2M，N_n 为出现过的块数、M 为块总数），
+// 外推前先对每个 H_n 做 Miller–Madow 修正，否则偏差会被 1/n 外推放大。
+// 格子划分一般不是生成划分，估计值只是 KS 熵的下界附近的粗略值；块长受样本数限制（字母表取被占据的格子数）。
+// Pesin 恒等式：对 SRB 测度 h_KS = Σ_{λ_i > 0} λ_i。熵以 nats / 驱动周期计，Lyapunov 指数按时间计，比较时乘以周期 T。
+
+/// 测度熵估计的设置
+#[derive(Debug, Clone, Copy)]
+pub struct MetricEntropyOptions {
+    /// 直方图在 θ 与 ω 方向的格子数（ω 范围取样本的最小/最大值）
+    pub theta_bins: usize,
+    pub omega_bins: usize,
+    /// 最大块长；None 时按样本数与被占据的格子数用 reliable_block_length 选取
+    pub max_block: Option<usize>,
+}
+
+impl Default for MetricEntropyOptions {
+    fn default() -> Self {
+        MetricEntropyOptions {
+            theta_bins: 4,
+            omega_bins: 4,
+            max_block: None,
+        }
+    }
+}
+
+/// 测度熵估计的结果（nats / 驱动周期）
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MetricEntropy {
+    pub theta_bins: usize,
+    pub omega_bins: usize,
+    /// 至少有一个点的格子数（字母表大小）
+    pub occupied: usize,
+    /// 不变测度直方图的 Shannon 熵 H_1
+    pub measure_entropy: f64,
+    /// 块长 1..=max_block 的条件熵 h_n（Miller–Madow 修正后）
+    pub rates: Vec<f64>,
+    /// 1/n → 0 外推的 h_∞（不小于 0）；只有一个可用块长时取 h_max_block
+    pub extrapolated: f64,
+}
+
+/// 由庞加莱点估计测度熵；点数少于 2 时返回 None
+///
+/// ω 范围按样本自适应，周期轨道上舍入误差量级的散布也会被拆进不同格子，周期吸引子应先由 detect_period 等排除。
+pub fn metric_entropy(section: &[(f64, f64)], options: &MetricEntropyOptions) -> Option<MetricEntropy> {
+    if section.len() < 2 {
+        return None;
+    }
+    let histogram = poincare_density(section, options.theta_bins.max(1), options.omega_bins.max(1), None);
+    let symbols: Vec<usize> = section.iter().filter_map(|&(theta, omega)| histogram.bin_index(theta, omega)).collect();
+    let occupied = histogram.counts.iter().filter(|&&c| c > 0).count();
+    let max_block = options
+        .max_block
+        .unwrap_or_else(|| reliable_block_length(symbols.len(), occupied).max(2));
+    let corrected: Vec<f64> = block_entropies(&symbols, max_block)
+        .iter()
+        .map(|b| b.entropy + (b.distinct as f64 - 1.0) / (2.0 * b.blocks as f64))
+        .collect();
+    let rates: Vec<f64> = corrected
+        .iter()
+        .scan(0.0, |prev, &h| {
+            let rate = h - *prev;
+            *prev = h;
+            Some(rate)
+        })
+        .collect();
+    let last = *rates.last()?;
+    let fit: Vec<(f64, f64)> = rates.iter().enumerate().skip(1).map(|(k, &h)| (1.0 / (k + 1) as f64, h)).collect();
+    let extrapolated = match least_squares(&fit) {
+        Some((_, intercept)) => intercept.max(0.0),
+        None => last.max(0.0),
+    };
+    Some(MetricEntropy {
+        theta_bins: histogram.theta_bins,
+        omega_bins: histogram.omega_bins,
+        occupied,
+        measure_entropy: histogram.entropy(),
+        rates,
+        extrapolated,
+    })
+}
+
+/// Pesin 恒等式的核对：测度熵与正 Lyapunov 指数之和（折合为每个驱动周期）
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct PesinCheck {
+    pub entropy: f64,
+    /// T Σ_{λ_i > 0} λ_i
+    pub positive_exponent_sum: f64,
+    /// entropy / positive_exponent_sum；没有正指数时为 None
+    pub ratio: Option<f64>,
+}
+
+/// exponents 为按时间计的 Lyapunov 指数（如 lyapunov::spectrum 的输出），period 为驱动周期
+pub fn pesin_check(entropy: f64, exponents: &[f64], period: f64) -> PesinCheck {
+    let sum: f64 = exponents.iter().filter(|&&l| l > 0.0).sum::<f64>() * period;
+    PesinCheck {
+        entropy,
+        positive_exponent_sum: sum,
+        ratio: (sum > 0.0).then(|| entropy / sum),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::{seeded_rng, RandomSource};
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_metric_entropy_of_random_and_periodic_sections() {
+        // 独立均匀分布的点：每个格子等概率，h = ln(格子数)
+        let mut rng = seeded_rng(3);
+        let random: Vec<(f64, f64)> = (0..100_000).map(|_| (rng.uniform(-PI, PI), rng.uniform(-1.0, 1.0))).collect();
+        let estimate = metric_entropy(&random, &MetricEntropyOptions::default()).unwrap();
+        assert_eq!(estimate.occupied, 16);
+        assert!((estimate.measure_entropy - 16f64.ln()).abs() < 1e-3, "{:?}", estimate);
+        assert!((estimate.extrapolated - 16f64.ln()).abs() < 0.05, "{:?}", estimate);
+
+        // 周期 3 轨道：熵率为 0
+        let periodic: Vec<(f64, f64)> = (0..3000).map(|i| [(-2.0, 0.5), (0.5, -0.3), (2.5, 0.9)][i % 3]).collect();
+        let estimate = metric_entropy(&periodic, &MetricEntropyOptions::default()).unwrap();
+        assert_eq!(estimate.occupied, 3);
+        assert!(estimate.extrapolated < 1e-3, "{:?}", estimate);
+
+        let check = pesin_check(0.9, &[0.15, 0.0, -0.65], 2.0 * PI / (2.0 / 3.0));
+        assert!((check.positive_exponent_sum - 0.15 * 3.0 * PI).abs() < 1e-12);
+        assert!((check.ratio.unwrap() - 0.9 / (0.45 * PI)).abs() < 1e-12);
+        assert_eq!(pesin_check(0.0, &[0.0, -0.1, -0.4], 1.0).ratio, None);
+    }
+}
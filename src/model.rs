@@ -1,5 +1,8 @@
 //model.rs
-use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+use std::sync::Arc;
+use serde::{Deserialize, Deserializer, Serialize};
+use crate::error::ChaosError;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PendulumParams {
@@ -9,10 +12,38 @@ pub struct PendulumParams {
     pub q: f64,
     pub f_d: f64,
     pub omega_d: f64,
+    //驱动波形（旧配置文件缺省时为 f_d sin(Ωt)）
+    #[serde(default)]
+    pub forcing: Forcing,
+    //驱动方式：外加力矩或悬点竖直振动（旧配置文件缺省时为外加力矩）
+    #[serde(default)]
+    pub drive_type: DriveType,
+    //f_d 与 q 随时间的变化规律（缺省时为常数 f_d、q），用于一次积分缓慢扫过分岔点
+    #[serde(default)]
+    pub f_d_schedule: Option<Schedule>,
+    #[serde(default)]
+    pub q_schedule: Option<Schedule>,
     
     //积分参数
     pub dt: f64,
     pub t_end: f64,
+    //自适应积分（Dormand–Prince）的绝对/相对容差
+    #[serde(default = "default_tolerance")]
+    pub atol: f64,
+    #[serde(default = "default_tolerance")]
+    pub rtol: f64,
+    //固定步长积分器（旧配置文件缺省时为 RK4）
+    #[serde(default)]
+    pub integrator: Integrator,
+    //步长控制（旧配置文件缺省时直接使用 dt）
+    #[serde(default)]
+    pub step_control: StepControl,
+    //每个驱动周期的最后一步截短，使积分恰好落在 t = nT 上（固定步长与自适应步长都适用）
+    #[serde(default)]
+    pub exact_stroboscopic: bool,
+    //积分的时间方向（旧配置文件缺省时正向）
+    #[serde(default)]
+    pub direction: TimeDirection,
 
     //遍历参数
     pub theta_start: f64,
@@ -21,6 +52,269 @@ pub struct PendulumParams {
     pub omega_start: f64,
     pub omega_end: f64,
     pub d_omega: f64,
+
+    //系综初值采样参数（旧配置文件缺省时使用规则网格）
+    #[serde(default)]
+    pub ic_sampling: InitialConditionSampling,
+    #[serde(default = "default_ic_count")]
+    pub ic_count: usize,
+    #[serde(default)]
+    pub ic_seed: u64,
+
+    //内存预算（字节）：完整轨迹的估计大小超过它时，solve 返回 MemoryBudget 错误而不积分，庞加莱采样改走不保存轨迹的流式路径（见 plan 模块）
+    #[serde(default)]
+    pub memory_budget_bytes: Option<usize>,
+
+    //加性高斯噪声 dω = (...) dt + noise_intensity dW（0 为确定性系统）；solve、poincare 与 stochastic 模块使用，其余分析工具按确定性系统积分
+    #[serde(default)]
+    pub noise_intensity: f64,
+    #[serde(default)]
+    pub noise_seed: u64,
+    #[serde(default)]
+    pub noise_scheme: NoiseScheme,
+}
+
+/// 随机微分方程的积分格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NoiseScheme {
+    /// Euler–Maruyama（强 1/2 阶；加性噪声时为强 1 阶，但漂移项只有一阶）
+    EulerMaruyama,
+    /// 随机 Heun（预测–校正，加性噪声时强 1 阶、漂移项二阶）
+    #[default]
+    Heun,
+}
+
+/// 驱动力的波形；omega_d 始终定义频闪采样的周期 T = 2π/Ω
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Forcing {
+    /// f_d sin(Ωt)
+    #[default]
+    Sine,
+    /// f_d cos(Ωt)
+    Cosine,
+    /// 方波：sin(Ωt) >= 0 时为 f_d，否则为 -f_d
+    Square,
+    /// 锯齿波：t = 0 处为 0，在每个周期内从 -f_d 线性升到 f_d，于 Ωt = π (mod 2π) 处跳回
+    Sawtooth,
+    /// 多频驱动 Σ a_k sin(ω_k t + φ_k)，不使用 f_d 与 omega_d；频率不可公度时为准周期驱动
+    SumOfSines { components: Vec<SineComponent> },
+    /// 任意函数 F(t)，不使用 f_d 与 omega_d；序列化为 {"kind":"custom"}，不能从文件读取
+    Custom {
+        #[serde(skip_serializing, deserialize_with = "reject_custom_forcing")]
+        function: CustomForcing,
+    },
+}
+
+/// 参数随时间的变化规律
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Schedule {
+    /// t <= t_start 时为 from，t >= t_end 时为 to，其间线性过渡
+    Linear { from: f64, to: f64, t_start: f64, t_end: f64 },
+    /// 节点 (t, value) 之间分段线性插值，节点按 t 递增；首末节点之外取端点值
+    Piecewise { points: Vec<(f64, f64)> },
+}
+
+impl Schedule {
+    /// t 时刻的参数值
+    pub fn value(&self, t: f64) -> f64 {
+        match self {
+            Schedule::Linear { from, to, t_start, t_end } => {
+                if t <= *t_start {
+                    *from
+                } else if t >= *t_end {
+                    *to
+                } else {
+                    from + (to - from) * (t - t_start) / (t_end - t_start)
+                }
+            }
+            Schedule::Piecewise { points } => {
+                let i = points.partition_point(|(tk, _)| *tk <= t);
+                match (i.checked_sub(1).and_then(|j| points.get(j)), points.get(i)) {
+                    (Some(&(t1, v1)), Some(&(t2, v2))) => v1 + (v2 - v1) * (t - t1) / (t2 - t1),
+                    (Some(&(_, v)), None) | (None, Some(&(_, v))) => v,
+                    (None, None) => 0.0,
+                }
+            }
+        }
+    }
+
+    /// 参数对时间的变化率（节点处取右侧斜率）
+    pub fn rate(&self, t: f64) -> f64 {
+        match self {
+            Schedule::Linear { from, to, t_start, t_end } => {
+                if t >= *t_start && t < *t_end {
+                    (to - from) / (t_end - t_start)
+                } else {
+                    0.0
+                }
+            }
+            Schedule::Piecewise { points } => {
+                let i = points.partition_point(|(tk, _)| *tk <= t);
+                match (i.checked_sub(1).and_then(|j| points.get(j)), points.get(i)) {
+                    (Some(&(t1, v1)), Some(&(t2, v2))) => (v2 - v1) / (t2 - t1),
+                    _ => 0.0,
+                }
+            }
+        }
+    }
+
+    // 数值有限、时间区间有序、分段节点非空且严格递增
+    fn check(&self, name: &str) -> Result<(), ChaosError> {
+        let ok = match self {
+            Schedule::Linear { from, to, t_start, t_end } => {
+                [from, to, t_start, t_end].iter().all(|v| v.is_finite()) && t_end > t_start
+            }
+            Schedule::Piecewise { points } => {
+                !points.is_empty()
+                    && points.iter().all(|(t, v)| t.is_finite() && v.is_finite())
+                    && points.windows(2).all(|w| w[1].0 > w[0].0)
+            }
+        };
+        if ok {
+            Ok(())
+        } else {
+            Err(ChaosError::InvalidParams(format!(
+                "{} schedule needs finite values and strictly increasing times",
+                name
+            )))
+        }
+    }
+}
+
+/// 驱动进入运动方程的方式，F(t) 为 forcing 给出的驱动
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DriveType {
+    /// 外加力矩：θ'' = -(g/l) sin θ - q θ' + F(t)
+    #[default]
+    Torque,
+    /// 悬点竖直振动（参数激励，Kapitza 摆）：θ'' = -(g/l + F(t)) sin θ - q θ'，
+    /// F(t) = ÿ_p(t) / l 为悬点加速度除以摆长（向上为正）；正弦驱动时悬点振幅为 f_d l / Ω²
+    Parametric,
+}
+
+/// 多频驱动中的一个正弦分量 amplitude · sin(frequency · t + phase)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SineComponent {
+    pub amplitude: f64,
+    pub frequency: f64,
+    #[serde(default)]
+    pub phase: f64,
+}
+
+/// 自定义驱动函数 F(t)
+#[derive(Clone)]
+pub struct CustomForcing(pub Arc<dyn Fn(f64) -> f64 + Send + Sync>);
+
+impl std::fmt::Debug for CustomForcing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("CustomForcing(..)")
+    }
+}
+
+fn reject_custom_forcing<'de, D: Deserializer<'de>>(_: D) -> Result<CustomForcing, D::Error> {
+    Err(serde::de::Error::custom("custom forcing cannot be read from a file"))
+}
+
+impl Forcing {
+    /// 由闭包构造自定义驱动
+    pub fn custom<F: Fn(f64) -> f64 + Send + Sync + 'static>(f: F) -> Self {
+        Forcing::Custom {
+            function: CustomForcing(Arc::new(f)),
+        }
+    }
+
+    /// 双频驱动 f1 sin(ω1 t) + f2 sin(ω2 t)；ω2/ω1 为无理数时为准周期驱动
+    ///
+    /// 频闪截面仍按 omega_d 取样，通常令 omega_d = ω1，第二个驱动的相位在截面上自由转动。
+    pub fn two_frequency(f1: f64, omega1: f64, f2: f64, omega2: f64) -> Self {
+        Forcing::SumOfSines {
+            components: vec![
+                SineComponent { amplitude: f1, frequency: omega1, phase: 0.0 },
+                SineComponent { amplitude: f2, frequency: omega2, phase: 0.0 },
+            ],
+        }
+    }
+}
+
+/// 固定步长积分器
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Integrator {
+    /// 经典四阶 Runge–Kutta；长时间保守积分时能量会缓慢单向漂移
+    #[default]
+    Rk4,
+    /// 速度 Verlet（二阶辛方法）
+    VelocityVerlet,
+    /// Yoshida 四阶辛方法（三段 Verlet 复合）
+    Yoshida4,
+}
+
+/// 积分的时间方向
+///
+/// 反向时 t 从 0 递减到 -t_end，dt 与 t_end 仍填正值。solve、TrajectoryIter、poincare 与
+/// solve_equation::stroboscopic_map 与 stroboscopic::StroboscopicMap 遵循该设置；直接调用 rk4_step 的分析例程（Lyapunov 指数、流映射等）总是正向积分。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeDirection {
+    #[default]
+    Forward,
+    Backward,
+}
+
+impl TimeDirection {
+    /// 时间增量的符号：正向 1，反向 -1
+    pub fn sign(&self) -> f64 {
+        match self {
+            TimeDirection::Forward => 1.0,
+            TimeDirection::Backward => -1.0,
+        }
+    }
+}
+
+/// 步长的确定方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StepControl {
+    /// 直接使用 dt
+    #[default]
+    FixedDt,
+    /// 每个驱动周期恰好 steps 步：积分器使用 dt = T / steps，忽略 dt 字段，
+    /// 采样时刻 t = nT 恰好是第 n·steps 步，频闪采样无需插值
+    StepsPerPeriod { steps: usize },
+    /// Dormand–Prince 自适应步长（见 adaptive），dt 只作为初始试探步长
+    Adaptive,
+}
+
+/// 系综初值的采样方式
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum InitialConditionSampling {
+    /// theta/omega 规则网格（忽略 ic_count 与 ic_seed）
+    #[default]
+    Grid,
+    /// 在 [theta_start, theta_end] × [omega_start, omega_end] 内均匀采样
+    Box,
+    /// 以 (theta, omega) 为中心、各方向独立的高斯分布
+    Gaussian {
+        theta: f64,
+        omega: f64,
+        sigma_theta: f64,
+        sigma_omega: f64,
+    },
+    /// 无驱动无阻尼能量 E = ω²/2 + (g/l)(1 - cos θ) 落在 [e_min, e_max] 内的壳层
+    EnergyShell { e_min: f64, e_max: f64 },
+}
+
+fn default_tolerance() -> f64 {
+    1e-9
+}
+
+fn default_ic_count() -> usize {
+    1000
 }
 
 impl PendulumParams {
@@ -31,17 +325,657 @@ impl PendulumParams {
             q: 0.1,
             f_d: 1.0,
             omega_d: 1.0,
+            forcing: Forcing::Sine,
+            drive_type: DriveType::Torque,
+            f_d_schedule: None,
+            q_schedule: None,
 
             // 使用更小的步长以提高 RK4 与解析解的一致性
             dt: 0.001,
             t_end: 10.0,
-            
+            atol: default_tolerance(),
+            rtol: default_tolerance(),
+            integrator: Integrator::Rk4,
+            step_control: StepControl::FixedDt,
+            exact_stroboscopic: false,
+            direction: TimeDirection::Forward,
+
             theta_start: -4.0,
             theta_end: 4.0,
             d_theta: 0.01,
             omega_start: -4.0,
             omega_end: 4.0,
             d_omega: 0.01,
+
+            ic_sampling: InitialConditionSampling::Grid,
+            ic_count: default_ic_count(),
+            ic_seed: 0,
+
+            memory_budget_bytes: None,
+
+            noise_intensity: 0.0,
+            noise_seed: 0,
+            noise_scheme: NoiseScheme::Heun,
+        }
+    }
+
+    /// 从参数文件读取：扩展名为 .toml 时按 TOML 解析，否则按 JSON 解析
+    ///
+    /// 文件中缺省的可选字段（采样、容差、内存预算等）取默认值；解析失败返回 InvalidData 错误。
+    pub fn from_file(path: &str) -> std::io::Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| std::io::Error::new(e.kind(), format!("{}: {}", path, e)))?;
+        let invalid = |e: String| std::io::Error::new(std::io::ErrorKind::InvalidData, format!("{}: {}", path, e));
+        let is_toml = std::path::Path::new(path)
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"));
+        if is_toml {
+            toml::from_str(&text).map_err(|e| invalid(e.to_string()))
+        } else {
+            serde_json::from_str(&text).map_err(|e| invalid(e.to_string()))
+        }
+    }
+
+    /// 按名称取 presets 模块中的经典参数组（如 "chaotic"）；名称未知时返回 None
+    pub fn preset(name: &str) -> Option<Self> {
+        crate::presets::preset(name).map(|p| p.params())
+    }
+
+    /// t 时刻的驱动幅度：有 f_d_schedule 时按其取值，否则为 f_d
+    pub fn f_d_at(&self, t: f64) -> f64 {
+        self.f_d_schedule.as_ref().map_or(self.f_d, |s| s.value(t))
+    }
+
+    /// t 时刻的阻尼系数：有 q_schedule 时按其取值，否则为 q
+    pub fn q_at(&self, t: f64) -> f64 {
+        self.q_schedule.as_ref().map_or(self.q, |s| s.value(t))
+    }
+
+    /// t 时刻的驱动力（按 forcing 选择波形，幅度为 f_d_at(t)）
+    pub fn drive(&self, t: f64) -> f64 {
+        match &self.forcing {
+            Forcing::SumOfSines { components } => components
+                .iter()
+                .map(|c| c.amplitude * (c.frequency * t + c.phase).sin())
+                .sum(),
+            Forcing::Custom { function } => (function.0)(t),
+            _ => self.f_d_at(t) * self.waveform(t).0,
+        }
+    }
+
+    /// 驱动项在运动方程中的系数 c(θ)：驱动贡献的角加速度为 c(θ) F(t)
+    ///
+    /// 外加力矩时 c = 1，参数激励时 c = -sin θ。
+    pub fn drive_coupling(&self, theta: f64) -> f64 {
+        match self.drive_type {
+            DriveType::Torque => 1.0,
+            DriveType::Parametric => -theta.sin(),
+        }
+    }
+
+    /// dc/dθ，变分方程中驱动项对 θ 的偏导为 F(t) dc/dθ
+    pub fn drive_coupling_slope(&self, theta: f64) -> f64 {
+        match self.drive_type {
+            DriveType::Torque => 0.0,
+            DriveType::Parametric => -theta.cos(),
+        }
+    }
+
+    /// 驱动贡献的角加速度 c(θ) F(t)；乘以 ω 即驱动对单摆输入的功率
+    pub fn drive_torque(&self, theta: f64, t: f64) -> f64 {
+        self.drive_coupling(theta) * self.drive(t)
+    }
+
+    /// 驱动力对时间的导数（方波、锯齿波不计跳变处的冲激；自定义驱动用中心差分；含 f_d_schedule 的斜率）
+    pub fn drive_rate(&self, t: f64) -> f64 {
+        match &self.forcing {
+            Forcing::SumOfSines { components } => components
+                .iter()
+                .map(|c| c.amplitude * c.frequency * (c.frequency * t + c.phase).cos())
+                .sum(),
+            Forcing::Custom { function } => {
+                let h = 1e-6 * t.abs().max(1.0);
+                ((function.0)(t + h) - (function.0)(t - h)) / (2.0 * h)
+            }
+            _ => {
+                let (w, dw) = self.waveform(t);
+                let f_d_rate = self.f_d_schedule.as_ref().map_or(0.0, |s| s.rate(t));
+                f_d_rate * w + self.f_d_at(t) * dw
+            }
+        }
+    }
+
+    // 以 f_d 为幅度的波形在 t 处的单位幅度值及其导数；多频与自定义驱动不使用 f_d，返回 (0, 0)
+    fn waveform(&self, t: f64) -> (f64, f64) {
+        let phase = self.omega_d * t;
+        match &self.forcing {
+            Forcing::Sine => (phase.sin(), self.omega_d * phase.cos()),
+            Forcing::Cosine => (phase.cos(), -self.omega_d * phase.sin()),
+            Forcing::Square => {
+                if phase.rem_euclid(2.0 * PI) < PI {
+                    (1.0, 0.0)
+                } else {
+                    (-1.0, 0.0)
+                }
+            }
+            Forcing::Sawtooth => ((phase + PI).rem_euclid(2.0 * PI) / PI - 1.0, self.omega_d / PI),
+            Forcing::SumOfSines { .. } | Forcing::Custom { .. } => (0.0, 0.0),
+        }
+    }
+
+    /// 改为每个驱动周期恰好 n 步（StepControl::StepsPerPeriod），同时把 dt 更新为 T / n
+    ///
+    /// 之后再修改 omega_d 时，积分器按 effective_dt() 跟随新的周期。
+    pub fn set_steps_per_period(&mut self, n: usize) {
+        self.step_control = StepControl::StepsPerPeriod { steps: n };
+        self.dt = self.effective_dt();
+    }
+
+    /// 积分器实际使用的步长：StepsPerPeriod 时为 T / steps，否则为 dt
+    pub fn effective_dt(&self) -> f64 {
+        match self.step_control {
+            StepControl::StepsPerPeriod { steps } => 2.0 * std::f64::consts::PI / self.omega_d.abs() / steps as f64,
+            StepControl::FixedDt | StepControl::Adaptive => self.dt,
+        }
+    }
+
+    /// 积分前的基本检查：g、l、q、f_d、omega_d 为有限值，l 非零，dt 为正，t_end 为非负有限值
+    ///
+    /// StepsPerPeriod 时检查的是 effective_dt()，并要求 steps > 0、omega_d != 0；exact_stroboscopic 时同样要求 omega_d != 0；
+    /// noise_intensity 须为非负有限值，且不能与 StepControl::Adaptive 同时使用；反向积分不支持自适应步长与噪声。
+    /// f_d_schedule、q_schedule 的数值须有限、时间严格递增；f_d_schedule 只能配合以 f_d 为幅度的波形。
+    pub fn validate(&self) -> Result<(), ChaosError> {
+        let fields = [("g", self.g), ("l", self.l), ("q", self.q), ("f_d", self.f_d), ("omega_d", self.omega_d)];
+        if let Some((name, value)) = fields.iter().find(|(_, v)| !v.is_finite()) {
+            return Err(ChaosError::InvalidParams(format!("{} must be finite, got {}", name, value)));
+        }
+        if self.l == 0.0 {
+            return Err(ChaosError::InvalidParams("pendulum length l must be non-zero".to_string()));
+        }
+        if let Some(schedule) = &self.f_d_schedule {
+            schedule.check("f_d")?;
+            if matches!(self.forcing, Forcing::SumOfSines { .. } | Forcing::Custom { .. }) {
+                return Err(ChaosError::InvalidParams("f_d_schedule has no effect on this forcing".to_string()));
+            }
+        }
+        if let Some(schedule) = &self.q_schedule {
+            schedule.check("q")?;
+        }
+        if let StepControl::StepsPerPeriod { steps } = self.step_control {
+            if steps == 0 {
+                return Err(ChaosError::InvalidParams("steps per period must be positive".to_string()));
+            }
+            if self.omega_d == 0.0 {
+                return Err(ChaosError::ZeroDriveFrequency);
+            }
+        }
+        if !(self.noise_intensity >= 0.0 && self.noise_intensity.is_finite()) {
+            return Err(ChaosError::InvalidParams(format!(
+                "noise_intensity must be non-negative and finite, got {}",
+                self.noise_intensity
+            )));
+        }
+        if self.noise_intensity > 0.0 && self.step_control == StepControl::Adaptive {
+            return Err(ChaosError::InvalidParams("adaptive stepping does not support noise".to_string()));
+        }
+        if self.exact_stroboscopic && self.omega_d == 0.0 {
+            return Err(ChaosError::ZeroDriveFrequency);
+        }
+        if self.direction == TimeDirection::Backward && (self.step_control == StepControl::Adaptive || self.noise_intensity > 0.0) {
+            return Err(ChaosError::InvalidParams(
+                "backward integration supports neither adaptive stepping nor noise".to_string(),
+            ));
+        }
+        let dt = self.effective_dt();
+        if !(dt > 0.0 && dt.is_finite()) {
+            return Err(ChaosError::NonPositiveDt(dt));
+        }
+        if !(self.t_end >= 0.0 && self.t_end.is_finite()) {
+            return Err(ChaosError::InvalidParams(format!("t_end must be non-negative and finite, got {}", self.t_end)));
+        }
+        Ok(())
+    }
+
+    /// 在 validate 的基础上要求驱动周期有定义（omega_d != 0），按周期采样前使用
+    pub fn validate_periodic(&self) -> Result<(), ChaosError> {
+        self.validate()?;
+        if self.omega_d == 0.0 {
+            return Err(ChaosError::ZeroDriveFrequency);
+        }
+        Ok(())
+    }
+
+    /// 由 theta_start / theta_end / d_theta 生成的 θ 网格（包含两端）
+    pub fn theta_grid(&self) -> Vec<f64> {
+        grid_points(self.theta_start, self.theta_end, self.d_theta)
+    }
+
+    /// 由 omega_start / omega_end / d_omega 生成的 ω 网格（包含两端）
+    pub fn omega_grid(&self) -> Vec<f64> {
+        grid_points(self.omega_start, self.omega_end, self.d_omega)
+    }
+}
+
+// 按固定步数生成等距网格，避免浮点累加误差导致端点丢失
+fn grid_points(start: f64, end: f64, step: f64) -> Vec<f64> {
+    if step <= 0.0 || end < start {
+        return vec![start];
+    }
+    let n = ((end - start) / step).round() as usize;
+    (0..=n).map(|i| start + i as f64 * step).collect()
+}
+
+impl Default for PendulumParams {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// PendulumParams 的构建器：从 PendulumParams::new() 的默认值出发链式设置，build() 时统一检查
+///
+/// 检查项：l、dt、t_end 为正，q 非负，所有方程参数为有限值；
+/// 有驱动（omega_d != 0）时还要求每个驱动周期至少 min_steps_per_period 步（默认 20）。
+#[derive(Debug, Clone)]
+pub struct PendulumParamsBuilder {
+    params: PendulumParams,
+    min_steps_per_period: usize,
+}
+
+impl PendulumParams {
+    pub fn builder() -> PendulumParamsBuilder {
+        PendulumParamsBuilder::new()
+    }
+}
+
+impl PendulumParamsBuilder {
+    pub fn new() -> Self {
+        Self::from_params(PendulumParams::new())
+    }
+
+    /// 以已有参数（例如从配置文件读取的）为起点
+    pub fn from_params(params: PendulumParams) -> Self {
+        Self {
+            params,
+            min_steps_per_period: 20,
+        }
+    }
+
+    pub fn g(mut self, g: f64) -> Self {
+        self.params.g = g;
+        self
+    }
+
+    pub fn l(mut self, l: f64) -> Self {
+        self.params.l = l;
+        self
+    }
+
+    pub fn q(mut self, q: f64) -> Self {
+        self.params.q = q;
+        self
+    }
+
+    pub fn f_d(mut self, f_d: f64) -> Self {
+        self.params.f_d = f_d;
+        self
+    }
+
+    pub fn omega_d(mut self, omega_d: f64) -> Self {
+        self.params.omega_d = omega_d;
+        self
+    }
+
+    pub fn forcing(mut self, forcing: Forcing) -> Self {
+        self.params.forcing = forcing;
+        self
+    }
+
+    pub fn f_d_schedule(mut self, schedule: Schedule) -> Self {
+        self.params.f_d_schedule = Some(schedule);
+        self
+    }
+
+    pub fn q_schedule(mut self, schedule: Schedule) -> Self {
+        self.params.q_schedule = Some(schedule);
+        self
+    }
+
+    pub fn dt(mut self, dt: f64) -> Self {
+        self.params.dt = dt;
+        self
+    }
+
+    pub fn t_end(mut self, t_end: f64) -> Self {
+        self.params.t_end = t_end;
+        self
+    }
+
+    pub fn integrator(mut self, integrator: Integrator) -> Self {
+        self.params.integrator = integrator;
+        self
+    }
+
+    /// 每个驱动周期恰好 n 步（见 PendulumParams::set_steps_per_period）；dt 在 build() 时按最终的 omega_d 计算
+    pub fn steps_per_period(mut self, n: usize) -> Self {
+        self.params.step_control = StepControl::StepsPerPeriod { steps: n };
+        self
+    }
+
+    pub fn step_control(mut self, control: StepControl) -> Self {
+        self.params.step_control = control;
+        self
+    }
+
+    pub fn exact_stroboscopic(mut self, exact: bool) -> Self {
+        self.params.exact_stroboscopic = exact;
+        self
+    }
+
+    pub fn direction(mut self, direction: TimeDirection) -> Self {
+        self.params.direction = direction;
+        self
+    }
+
+    pub fn tolerances(mut self, atol: f64, rtol: f64) -> Self {
+        self.params.atol = atol;
+        self.params.rtol = rtol;
+        self
+    }
+
+    pub fn theta_range(mut self, start: f64, end: f64, step: f64) -> Self {
+        self.params.theta_start = start;
+        self.params.theta_end = end;
+        self.params.d_theta = step;
+        self
+    }
+
+    pub fn omega_range(mut self, start: f64, end: f64, step: f64) -> Self {
+        self.params.omega_start = start;
+        self.params.omega_end = end;
+        self.params.d_omega = step;
+        self
+    }
+
+    pub fn memory_budget_bytes(mut self, budget: Option<usize>) -> Self {
+        self.params.memory_budget_bytes = budget;
+        self
+    }
+
+    /// 加性噪声强度与随机数种子
+    pub fn noise(mut self, intensity: f64, seed: u64) -> Self {
+        self.params.noise_intensity = intensity;
+        self.params.noise_seed = seed;
+        self
+    }
+
+    /// 每个驱动周期的最少步数，build() 据此检查 dt 是否过大
+    pub fn min_steps_per_period(mut self, steps: usize) -> Self {
+        self.min_steps_per_period = steps;
+        self
+    }
+
+    pub fn build(self) -> Result<PendulumParams, ChaosError> {
+        let mut p = self.params;
+        p.validate()?;
+        p.dt = p.effective_dt();
+        if p.l <= 0.0 {
+            return Err(ChaosError::InvalidParams(format!("pendulum length l must be positive, got {}", p.l)));
+        }
+        if p.t_end <= 0.0 {
+            return Err(ChaosError::InvalidParams(format!("t_end must be positive, got {}", p.t_end)));
+        }
+        if p.q < 0.0 {
+            return Err(ChaosError::InvalidParams(format!("damping q must be non-negative, got {}", p.q)));
         }
+        if p.omega_d != 0.0 {
+            let max_dt = 2.0 * std::f64::consts::PI / p.omega_d.abs() / self.min_steps_per_period.max(1) as f64;
+            if p.dt > max_dt {
+                return Err(ChaosError::InvalidParams(format!(
+                    "dt = {} exceeds drive period / {} = {}",
+                    p.dt, self.min_steps_per_period, max_dt
+                )));
+            }
+        }
+        Ok(p)
+    }
+}
+
+impl Default for PendulumParamsBuilder {
+    fn default() -> Self {
+        Self::new()
     }
-}
\ No newline at end of file
+}
+
+/// 平面双摆（两个质点、无质量刚性杆，无阻尼无驱动）的参数
+///
+/// 状态按 [θ1, ω1, θ2, ω2] 排列，θ 从竖直向下方向量起。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DoublePendulumParams {
+    pub g: f64,
+    pub m1: f64,
+    pub m2: f64,
+    pub l1: f64,
+    pub l2: f64,
+
+    //积分参数
+    pub dt: f64,
+    pub t_end: f64,
+}
+
+impl DoublePendulumParams {
+    pub fn new() -> Self {
+        Self {
+            g: 9.8,
+            m1: 1.0,
+            m2: 1.0,
+            l1: 1.0,
+            l2: 1.0,
+            dt: 0.001,
+            t_end: 20.0,
+        }
+    }
+
+    /// 运动方程右端：返回 [θ1', ω1', θ2', ω2']
+    pub fn rhs(&self, y: &[f64; 4]) -> [f64; 4] {
+        let [theta1, omega1, theta2, omega2] = *y;
+        let (m1, m2, l1, l2, g) = (self.m1, self.m2, self.l1, self.l2, self.g);
+        let delta = theta2 - theta1;
+        let (sin_d, cos_d) = delta.sin_cos();
+
+        let den1 = (m1 + m2) * l1 - m2 * l1 * cos_d * cos_d;
+        let d_omega1 = (m2 * l1 * omega1 * omega1 * sin_d * cos_d
+            + m2 * g * theta2.sin() * cos_d
+            + m2 * l2 * omega2 * omega2 * sin_d
+            - (m1 + m2) * g * theta1.sin())
+            / den1;
+
+        let den2 = (l2 / l1) * den1;
+        let d_omega2 = (-m2 * l2 * omega2 * omega2 * sin_d * cos_d
+            + (m1 + m2) * (g * theta1.sin() * cos_d - l1 * omega1 * omega1 * sin_d - g * theta2.sin()))
+            / den2;
+
+        [omega1, d_omega1, omega2, d_omega2]
+    }
+
+    /// 总机械能（势能零点在悬挂点）
+    pub fn energy(&self, y: &[f64; 4]) -> f64 {
+        let [theta1, omega1, theta2, omega2] = *y;
+        let (m1, m2, l1, l2, g) = (self.m1, self.m2, self.l1, self.l2, self.g);
+        let kinetic = 0.5 * (m1 + m2) * l1 * l1 * omega1 * omega1
+            + 0.5 * m2 * l2 * l2 * omega2 * omega2
+            + m2 * l1 * l2 * omega1 * omega2 * (theta1 - theta2).cos();
+        let potential = -(m1 + m2) * g * l1 * theta1.cos() - m2 * g * l2 * theta2.cos();
+        kinetic + potential
+    }
+}
+
+impl Default for DoublePendulumParams {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Lorenz 系统 x' = σ(y - x), y' = x(ρ - z) - y, z' = xy - βz，默认取经典混沌参数
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Lorenz {
+    pub sigma: f64,
+    pub rho: f64,
+    pub beta: f64,
+}
+
+impl Default for Lorenz {
+    fn default() -> Self {
+        Self {
+            sigma: 10.0,
+            rho: 28.0,
+            beta: 8.0 / 3.0,
+        }
+    }
+}
+
+/// Rössler 系统 x' = -y - z, y' = x + ay, z' = b + z(x - c)，默认取经典混沌参数
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Rossler {
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+}
+
+impl Default for Rossler {
+    fn default() -> Self {
+        Self { a: 0.2, b: 0.2, c: 5.7 }
+    }
+}
+
+/// 单摆链的边界条件
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChainBoundary {
+    /// 两端自由，端点只有一个邻居
+    #[default]
+    Free,
+    /// 首尾相连成环
+    Periodic,
+}
+
+/// N 个受同一驱动的单摆组成的链，相邻单摆之间用扭转弹簧耦合：
+///   θ_i'' = -(g/l)(1 + δ_i) sin θ_i - q θ_i' + c(θ_i) F(t) + κ Σ_{j 为 i 的邻居} (θ_j - θ_i)
+///
+/// 单摆参数、驱动与积分步长都取自 pendulum；δ_i 为第 i 个单摆固有频率平方的相对偏差（空表示全部相同）。
+/// 状态按 [θ_0, ω_0, θ_1, ω_1, ...] 排列，维数 2N。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendulumChain {
+    pub pendulum: PendulumParams,
+    pub n: usize,
+    /// 耦合强度 κ
+    pub coupling: f64,
+    #[serde(default)]
+    pub boundary: ChainBoundary,
+    #[serde(default)]
+    pub detuning: Vec<f64>,
+}
+
+impl PendulumChain {
+    /// n 个相同单摆、两端自由的链
+    pub fn new(pendulum: PendulumParams, n: usize, coupling: f64) -> Self {
+        Self {
+            pendulum,
+            n,
+            coupling,
+            boundary: ChainBoundary::Free,
+            detuning: Vec::new(),
+        }
+    }
+
+    /// 在单摆参数检查之外要求 n >= 2、κ 有限、detuning 为空或长度为 n 且各项有限
+    pub fn validate(&self) -> Result<(), ChaosError> {
+        self.pendulum.validate()?;
+        if self.n < 2 {
+            return Err(ChaosError::InvalidParams(format!("a chain needs at least 2 pendulums, got {}", self.n)));
+        }
+        if !self.coupling.is_finite() {
+            return Err(ChaosError::InvalidParams(format!("coupling must be finite, got {}", self.coupling)));
+        }
+        if !(self.detuning.is_empty() || self.detuning.len() == self.n) || self.detuning.iter().any(|d| !d.is_finite()) {
+            return Err(ChaosError::InvalidParams(format!(
+                "detuning needs {} finite values or none, got {}",
+                self.n,
+                self.detuning.len()
+            )));
+        }
+        Ok(())
+    }
+
+    /// 第 i 个单摆的邻居（自由边界的端点只有一个；n = 2 的环上两个邻居是同一个）
+    pub fn neighbors(&self, i: usize) -> [Option<usize>; 2] {
+        match self.boundary {
+            ChainBoundary::Free => [i.checked_sub(1), (i + 1 < self.n).then_some(i + 1)],
+            ChainBoundary::Periodic => [Some((i + self.n - 1) % self.n), Some((i + 1) % self.n)],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_file_reads_json_and_toml() {
+        let json = PendulumParams::from_file("config/config.json").unwrap();
+        let toml = PendulumParams::from_file("config/chaotic.toml").unwrap();
+        assert_eq!(json.q, 0.1);
+        assert_eq!(toml.q, 0.5);
+        assert_eq!(toml.f_d, 1.2);
+        assert_eq!(toml.atol, 1e-9);
+        assert!(PendulumParams::from_file("Cargo.toml").is_err());
+    }
+
+    #[test]
+    fn test_builder_validates() {
+        let params = PendulumParams::builder().q(0.5).f_d(1.2).omega_d(2.0 / 3.0).dt(0.01).t_end(100.0).build().unwrap();
+        assert_eq!(params.f_d, 1.2);
+        assert_eq!(params.dt, 0.01);
+
+        assert!(PendulumParams::builder().l(-1.0).build().is_err());
+        assert!(PendulumParams::builder().q(-0.1).build().is_err());
+        assert!(PendulumParams::builder().t_end(0.0).build().is_err());
+        assert!(matches!(PendulumParams::builder().dt(0.0).build(), Err(ChaosError::NonPositiveDt(_))));
+        // 周期 2π，每周期至少 20 步，dt = 0.5 过大
+        assert!(PendulumParams::builder().dt(0.5).build().is_err());
+        assert!(PendulumParams::builder().dt(0.5).min_steps_per_period(10).build().is_ok());
+    }
+
+    #[test]
+    fn test_forcing_waveforms_and_serde() {
+        let mut params = PendulumParams::new();
+        params.f_d = 2.0;
+        params.omega_d = 1.0;
+        params.forcing = Forcing::Square;
+        assert_eq!(params.drive(1.0), 2.0);
+        assert_eq!(params.drive(4.0), -2.0);
+        params.forcing = Forcing::Sawtooth;
+        assert_eq!(params.drive(0.0), 0.0);
+        assert!((params.drive(PI / 2.0) - 1.0).abs() < 1e-12);
+        params.forcing = Forcing::SumOfSines {
+            components: vec![
+                SineComponent { amplitude: 1.0, frequency: 1.0, phase: 0.0 },
+                SineComponent { amplitude: 0.5, frequency: 5f64.sqrt(), phase: 0.0 },
+            ],
+        };
+        assert!((params.drive(1.0) - (1f64.sin() + 0.5 * 5f64.sqrt().sin())).abs() < 1e-12);
+
+        // 自定义闭包与内置正弦波形给出同样的轨迹
+        let mut custom = PendulumParams::new();
+        custom.forcing = Forcing::custom(|t| t.sin());
+        let sine = PendulumParams::new();
+        let a = crate::solve(&sine, 0.2, 0.0).unwrap();
+        let b = crate::solve(&custom, 0.2, 0.0).unwrap();
+        assert_eq!(a.last().unwrap().1.theta, b.last().unwrap().1.theta);
+
+        let json = serde_json::to_string(&custom).unwrap();
+        assert!(json.contains(r#""forcing":{"kind":"custom"}"#));
+        assert!(serde_json::from_str::<PendulumParams>(&json).is_err());
+        let restored: PendulumParams = serde_json::from_str(&serde_json::to_string(&params).unwrap()).unwrap();
+        assert!(matches!(restored.forcing, Forcing::SumOfSines { ref components } if components.len() == 2));
+    }
+}
@@ -0,0 +1,184 @@
+//observable.rs
+use std::f64::consts::PI;
+use crate::collector::{create_row_sink, RowSink};
+use crate::io::{TrajectoryFormat, TrajectoryWriter};
+use crate::model::PendulumParams;
+use crate::separatrix::pendulum_energy;
+use crate::solve_equation::{wrap_angle, State};
+
+// 自定义输出列：Observable 是 (t, 状态, 参数) 的任意函数，带一个列名。
+// ObservableTrajectoryWriter 在 t,theta,omega 之后按给定顺序追加各个可观测量的列，
+// 输出走 collector 模块的 RowSink，格式与 create_row_sink 相同（CSV、gzip CSV、Parquet）；
+// 庞加莱点没有保存采样时刻，write_poincare_observables 按第 k 个点取 t = (transient_periods + 1 + k)·T 重建
+// （逆向积分时为负，与 sample_poincare_from_iter 的采样时刻一致）。
+// 常用的几个量（能量、sin θ、驱动相位等）可以按名称用 builtin_observable 取得，命令行的 --observable 即用这张表。
+
+/// 一个输出列：列名与 (t, state, params) 的函数
+pub trait Observable: Send + Sync {
+    fn name(&self) -> &str;
+    fn value(&self, t: f64, state: &State, params: &PendulumParams) -> f64;
+}
+
+/// 由闭包构造的可观测量
+pub struct FnObservable<F> {
+    name: String,
+    f: F,
+}
+
+impl<F: Fn(f64, &State, &PendulumParams) -> f64 + Send + Sync> Observable for FnObservable<F> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn value(&self, t: f64, state: &State, params: &PendulumParams) -> f64 {
+        (self.f)(t, state, params)
+    }
+}
+
+/// 以 name 为列名、f 为取值的可观测量
+pub fn observable<F>(name: &str, f: F) -> FnObservable<F>
+where
+    F: Fn(f64, &State, &PendulumParams) -> f64 + Send + Sync,
+{
+    FnObservable { name: name.to_string(), f }
+}
+
+type BuiltinFn = fn(f64, &State, &PendulumParams) -> f64;
+
+/// 内置可观测量的名称与取值
+pub const BUILTIN_OBSERVABLES: &[(&str, BuiltinFn)] = &[
+    // 无驱动无阻尼的能量 ω²/2 + (g/l)(1 - cos θ)
+    ("energy", |_, s, p| pendulum_energy(s.theta, s.omega, p)),
+    ("sin_theta", |_, s, _| s.theta.sin()),
+    ("cos_theta", |_, s, _| s.theta.cos()),
+    // θ 折叠到 [-π, π)
+    ("theta_wrapped", |_, s, _| wrap_angle(s.theta)),
+    // 驱动相位 Ωt mod 2π，取值于 [0, 2π)
+    ("drive_phase", |t, _, p| (p.omega_d * t).rem_euclid(2.0 * PI)),
+    // 瞬时驱动力矩
+    ("drive", |t, s, p| p.drive_torque(s.theta, t)),
+];
+
+/// 按名称取内置可观测量；名称未知时返回 None
+pub fn builtin_observable(name: &str) -> Option<Box<dyn Observable>> {
+    BUILTIN_OBSERVABLES
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|&(n, f)| Box::new(observable(n, f)) as Box<dyn Observable>)
+}
+
+// 输出的列名：t,theta,omega 加上各可观测量
+fn column_names(observables: &[Box<dyn Observable>]) -> Vec<&str> {
+    ["t", "theta", "omega"]
+        .into_iter()
+        .chain(observables.iter().map(|o| o.name()))
+        .collect()
+}
+
+/// 轨迹输出（列：t,theta,omega,<各可观测量>），格式见 collector::create_row_sink
+pub struct ObservableTrajectoryWriter {
+    sink: Box<dyn RowSink + Send>,
+    params: PendulumParams,
+    observables: Vec<Box<dyn Observable>>,
+    row: Vec<f64>,
+}
+
+impl ObservableTrajectoryWriter {
+    pub fn create(
+        path: &str,
+        format: TrajectoryFormat,
+        params: &PendulumParams,
+        observables: Vec<Box<dyn Observable>>,
+    ) -> std::io::Result<Self> {
+        let sink = create_row_sink(path, format, &column_names(&observables))?;
+        Ok(ObservableTrajectoryWriter {
+            sink,
+            params: params.clone(),
+            row: Vec::with_capacity(3 + observables.len()),
+            observables,
+        })
+    }
+}
+
+impl TrajectoryWriter for ObservableTrajectoryWriter {
+    fn write_point(&mut self, t: f64, state: &State) -> std::io::Result<()> {
+        self.row.clear();
+        self.row.extend([t, state.theta, state.omega]);
+        self.row.extend(self.observables.iter().map(|o| o.value(t, state, &self.params)));
+        self.sink.write_row(&self.row)
+    }
+
+    fn finish(&mut self) -> std::io::Result<()> {
+        self.sink.finish()
+    }
+}
+
+/// 把 poincare 的结果连同可观测量写出（列：t,theta,omega,<各可观测量>），t 为重建的采样时刻
+pub fn write_poincare_observables(
+    path: &str,
+    format: TrajectoryFormat,
+    samples: &[(f64, f64)],
+    transient_periods: usize,
+    params: &PendulumParams,
+    observables: Vec<Box<dyn Observable>>,
+) -> std::io::Result<()> {
+    let period = params.direction.sign() * 2.0 * PI / params.omega_d;
+    let mut writer = ObservableTrajectoryWriter::create(path, format, params, observables)?;
+    for (k, &(theta, omega)) in samples.iter().enumerate() {
+        writer.write_point((transient_periods + 1 + k) as f64 * period, &State { theta, omega })?;
+    }
+    writer.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::write_trajectory;
+    use crate::solve_equation::{poincare, solve};
+
+    #[test]
+    fn test_observable_columns_in_trajectory_and_section() {
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.q = 0.5;
+        params.f_d = 1.2;
+        params.omega_d = 2.0 / 3.0;
+        params.set_steps_per_period(100);
+        params.t_end = 2.0;
+        let path = std::env::temp_dir().join("chaos_observables.csv");
+        let path = path.to_str().unwrap();
+
+        let traj = solve(&params, 0.2, 0.0).unwrap();
+        let observables = vec![
+            builtin_observable("energy").unwrap(),
+            builtin_observable("drive_phase").unwrap(),
+            Box::new(observable("theta_squared", |_, s: &State, _: &PendulumParams| s.theta * s.theta)) as Box<dyn Observable>,
+        ];
+        let mut writer = ObservableTrajectoryWriter::create(path, TrajectoryFormat::Csv, &params, observables).unwrap();
+        write_trajectory(&mut writer, traj.iter().copied()).unwrap();
+        let text = std::fs::read_to_string(path).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "t,theta,omega,energy,drive_phase,theta_squared");
+        assert_eq!(lines.len(), traj.len() + 1);
+        let (t, s) = traj[10];
+        let fields: Vec<f64> = lines[11].split(',').map(|x| x.parse().unwrap()).collect();
+        assert!((fields[3] - pendulum_energy(s.theta, s.omega, &params)).abs() < 1e-11);
+        assert!((fields[4] - params.omega_d * t).abs() < 1e-11);
+        assert!((fields[5] - s.theta * s.theta).abs() < 1e-11);
+        assert!(builtin_observable("entropy").is_none());
+
+        // 庞加莱点的驱动相位都是 0（mod 2π）
+        params.t_end = 2.0 * PI / params.omega_d * 8.0 + params.dt;
+        let samples = poincare(&params, 0.2, 0.0, 3, 5).unwrap();
+        let observables = vec![builtin_observable("drive_phase").unwrap()];
+        write_poincare_observables(path, TrajectoryFormat::Csv, &samples, 3, &params, observables).unwrap();
+        let text = std::fs::read_to_string(path).unwrap();
+        for (k, line) in text.lines().skip(1).enumerate() {
+            let fields: Vec<f64> = line.split(',').map(|x| x.parse().unwrap()).collect();
+            assert!((fields[0] - (4 + k) as f64 * 3.0 * PI).abs() < 1e-9, "{}", line);
+            assert!((fields[1] - samples[k].0).abs() < 1e-11 && (fields[2] - samples[k].1).abs() < 1e-11);
+            assert!(!(1e-6..=2.0 * PI - 1e-6).contains(&fields[3]), "{}", line);
+        }
+        std::fs::remove_file(path).unwrap();
+    }
+}
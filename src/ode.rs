@@ -0,0 +1,282 @@
+//ode.rs
+use crate::model::{DoublePendulumParams, Lorenz, PendulumChain, PendulumParams, Rossler};
+
+// 与具体方程无关的积分工具：任何实现 OdeSystem 的系统都可以复用这里的
+// RK4、频闪（庞加莱）采样与 Benettin 最大 Lyapunov 指数。
+// 受迫单摆仍用 solve_equation 中按 State 特化、对浮点类型泛型的步进核（热路径，无堆分配），
+// 这里的 Rk4Stepper 面向任意维数的系统（双摆、单摆链、Lorenz 等）；两者对同一初值给出相同的结果。
+
+/// 一阶常微分方程组 y' = f(t, y)
+pub trait OdeSystem {
+    /// 状态维数；可以随实例变化（如 N 个单摆组成的链）
+    fn dim(&self) -> usize;
+
+    /// 把 f(t, y) 写入 dy（两者长度均为 dim()）
+    fn rhs(&self, t: f64, y: &[f64], dy: &mut [f64]);
+}
+
+/// 受迫阻尼单摆，y = [θ, ω]
+impl OdeSystem for PendulumParams {
+    fn dim(&self) -> usize {
+        2
+    }
+
+    fn rhs(&self, t: f64, y: &[f64], dy: &mut [f64]) {
+        let (d_theta, d_omega) = crate::solve_equation::rhs(y[0], y[1], t, self);
+        dy[0] = d_theta;
+        dy[1] = d_omega;
+    }
+}
+
+/// 平面双摆（自治系统），y = [θ1, ω1, θ2, ω2]
+impl OdeSystem for DoublePendulumParams {
+    fn dim(&self) -> usize {
+        4
+    }
+
+    fn rhs(&self, _t: f64, y: &[f64], dy: &mut [f64]) {
+        dy.copy_from_slice(&DoublePendulumParams::rhs(self, &[y[0], y[1], y[2], y[3]]));
+    }
+}
+
+/// 最近邻耦合的单摆链，y = [θ_0, ω_0, θ_1, ω_1, ...]
+impl OdeSystem for PendulumChain {
+    fn dim(&self) -> usize {
+        2 * self.n
+    }
+
+    fn rhs(&self, t: f64, y: &[f64], dy: &mut [f64]) {
+        let p = &self.pendulum;
+        let g_over_l = p.g / p.l;
+        let q = p.q_at(t);
+        let drive = p.drive(t);
+        for i in 0..self.n {
+            let (theta, omega) = (y[2 * i], y[2 * i + 1]);
+            let stiffness = g_over_l * (1.0 + self.detuning.get(i).copied().unwrap_or(0.0));
+            let coupling: f64 = self.neighbors(i).iter().flatten().map(|&j| y[2 * j] - theta).sum();
+            dy[2 * i] = omega;
+            dy[2 * i + 1] = -stiffness * theta.sin() - q * omega + p.drive_coupling(theta) * drive + self.coupling * coupling;
+        }
+    }
+}
+
+impl OdeSystem for Lorenz {
+    fn dim(&self) -> usize {
+        3
+    }
+
+    fn rhs(&self, _t: f64, y: &[f64], dy: &mut [f64]) {
+        dy[0] = self.sigma * (y[1] - y[0]);
+        dy[1] = y[0] * (self.rho - y[2]) - y[1];
+        dy[2] = y[0] * y[1] - self.beta * y[2];
+    }
+}
+
+impl OdeSystem for Rossler {
+    fn dim(&self) -> usize {
+        3
+    }
+
+    fn rhs(&self, _t: f64, y: &[f64], dy: &mut [f64]) {
+        dy[0] = -y[1] - y[2];
+        dy[1] = y[0] + self.a * y[1];
+        dy[2] = self.b + y[2] * (y[0] - self.c);
+    }
+}
+
+/// 带预分配缓冲区的 RK4 步进器，反复调用 step 不再分配内存
+#[derive(Debug, Clone)]
+pub struct Rk4Stepper {
+    k: [Vec<f64>; 4],
+    tmp: Vec<f64>,
+}
+
+impl Rk4Stepper {
+    pub fn new(dim: usize) -> Self {
+        Rk4Stepper {
+            k: std::array::from_fn(|_| vec![0.0; dim]),
+            tmp: vec![0.0; dim],
+        }
+    }
+
+    /// 原地把 y 从 t 推进到 t + h
+    pub fn step<S: OdeSystem>(&mut self, system: &S, t: f64, y: &mut [f64], h: f64) {
+        let [k1, k2, k3, k4] = &mut self.k;
+        system.rhs(t, y, k1);
+        for ((tmp, yi), ki) in self.tmp.iter_mut().zip(y.iter()).zip(k1.iter()) {
+            *tmp = yi + 0.5 * h * ki;
+        }
+        system.rhs(t + 0.5 * h, &self.tmp, k2);
+        for ((tmp, yi), ki) in self.tmp.iter_mut().zip(y.iter()).zip(k2.iter()) {
+            *tmp = yi + 0.5 * h * ki;
+        }
+        system.rhs(t + 0.5 * h, &self.tmp, k3);
+        for ((tmp, yi), ki) in self.tmp.iter_mut().zip(y.iter()).zip(k3.iter()) {
+            *tmp = yi + h * ki;
+        }
+        system.rhs(t + h, &self.tmp, k4);
+        for (i, yi) in y.iter_mut().enumerate() {
+            *yi += h / 6.0 * (k1[i] + 2.0 * k2[i] + 2.0 * k3[i] + k4[i]);
+        }
+    }
+}
+
+/// 固定步长 RK4 积分到 t_end，返回 (t, y) 序列（含初始点）；步数为 t_end / dt 向下取整
+pub fn solve_system<S: OdeSystem>(system: &S, initial: &[f64], dt: f64, t_end: f64) -> Vec<(f64, Vec<f64>)> {
+    assert_eq!(initial.len(), system.dim());
+    let steps = (t_end / dt) as usize;
+    let mut stepper = Rk4Stepper::new(system.dim());
+    let mut y = initial.to_vec();
+    let mut t = 0.0;
+    let mut traj = Vec::with_capacity(steps + 1);
+    traj.push((t, y.clone()));
+    for _ in 0..steps {
+        stepper.step(system, t, &mut y, dt);
+        t += dt;
+        traj.push((t, y.clone()));
+    }
+    traj
+}
+
+/// 频闪采样：每隔 period 记录一次状态（period 取驱动周期即为庞加莱截面）
+///
+/// 每个周期恰好积分 steps_per_period 步，丢弃前 transient_periods 个周期，
+/// 返回之后 sample_periods 个周期末的状态（不折叠角度）。不保存轨迹。
+pub fn stroboscopic_system<S: OdeSystem>(
+    system: &S,
+    initial: &[f64],
+    period: f64,
+    steps_per_period: usize,
+    transient_periods: usize,
+    sample_periods: usize,
+) -> Vec<Vec<f64>> {
+    assert_eq!(initial.len(), system.dim());
+    let h = period / steps_per_period as f64;
+    let mut stepper = Rk4Stepper::new(system.dim());
+    let mut y = initial.to_vec();
+    let mut samples = Vec::with_capacity(sample_periods);
+    for n in 1..=transient_periods + sample_periods {
+        let t0 = (n - 1) as f64 * period;
+        for i in 0..steps_per_period {
+            stepper.step(system, t0 + i as f64 * h, &mut y, h);
+        }
+        if n > transient_periods {
+            samples.push(y.clone());
+        }
+    }
+    samples
+}
+
+/// Benettin 两轨道法估计任意系统的最大 Lyapunov 指数
+///
+/// 扰动轨道沿第一个分量偏移 separation，每 renormalization_steps 步累加 ln(d / d0)
+/// 并把扰动拉回 d0（欧氏距离，不折叠角度）。积分 total_steps 步后返回平均增长率。
+/// 距离收缩到 0 的段不计入累加，其时间也不计入平均，扰动沿第一个分量重新放置；
+/// 每一段都收缩到 0（强烈收缩的系统）时返回 f64::NEG_INFINITY。
+pub fn largest_lyapunov_system<S: OdeSystem>(
+    system: &S,
+    initial: &[f64],
+    dt: f64,
+    total_steps: usize,
+    renormalization_steps: usize,
+    separation: f64,
+) -> f64 {
+    assert_eq!(initial.len(), system.dim());
+    let renormalization_steps = renormalization_steps.max(1);
+    let mut stepper = Rk4Stepper::new(system.dim());
+    let mut reference = initial.to_vec();
+    let mut perturbed = initial.to_vec();
+    perturbed[0] += separation;
+    let mut t = 0.0;
+    let mut log_sum = 0.0;
+    let mut counted = 0.0;
+    let mut collapsed = false;
+    let mut segment_start = 0.0;
+
+    for step in 1..=total_steps {
+        stepper.step(system, t, &mut reference, dt);
+        stepper.step(system, t, &mut perturbed, dt);
+        t += dt;
+        if step % renormalization_steps == 0 {
+            let d = reference
+                .iter()
+                .zip(&perturbed)
+                .map(|(a, b)| (b - a) * (b - a))
+                .sum::<f64>()
+                .sqrt();
+            if d > 0.0 {
+                log_sum += (d / separation).ln();
+                counted += t - segment_start;
+                for (p, r) in perturbed.iter_mut().zip(&reference) {
+                    *p = r + (*p - r) * separation / d;
+                }
+            } else {
+                // 两条轨道在浮点意义上重合（例如落到同一个稳定不动点），没有方向可以缩放，
+                // 这一段不计入累加，沿第一个分量重新放置扰动
+                perturbed.copy_from_slice(&reference);
+                perturbed[0] += separation;
+                collapsed = true;
+            }
+            segment_start = t;
+        }
+    }
+    if counted > 0.0 {
+        log_sum / counted
+    } else if collapsed {
+        f64::NEG_INFINITY
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_generic_rk4_matches_specialized_stepper() {
+        let mut params = PendulumParams::new();
+        params.t_end = 5.0;
+        let specialized = crate::solve_equation::solve(&params, 0.2, 0.0).unwrap();
+        let generic = solve_system(&params, &[0.2, 0.0], params.dt, params.t_end);
+        assert_eq!(specialized.len(), generic.len());
+        let ((_, a), (_, b)) = (specialized.last().unwrap(), generic.last().unwrap());
+        assert_relative_eq!(a.theta, b[0], epsilon = 1e-12);
+        assert_relative_eq!(a.omega, b[1], epsilon = 1e-12);
+
+    }
+
+    #[test]
+    fn test_lorenz_and_rossler_are_chaotic() {
+        // 先越过过渡期落到吸引子上，再估计最大 Lyapunov 指数（文献值约 0.906 与 0.071）
+        let lorenz = Lorenz::default();
+        let start = solve_system(&lorenz, &[1.0, 1.0, 1.0], 0.01, 20.0).pop().unwrap().1;
+        let lambda = largest_lyapunov_system(&lorenz, &start, 0.01, 20_000, 10, 1e-8);
+        assert!((0.75..1.05).contains(&lambda), "lorenz λ = {}", lambda);
+
+        let rossler = Rossler::default();
+        let start = solve_system(&rossler, &[1.0, 1.0, 0.0], 0.02, 100.0).pop().unwrap().1;
+        let lambda = largest_lyapunov_system(&rossler, &start, 0.02, 50_000, 10, 1e-8);
+        assert!((0.04..0.11).contains(&lambda), "rossler λ = {}", lambda);
+    }
+
+    #[test]
+    fn test_lyapunov_survives_collapsed_separation() {
+        // y' = -100 y：两次重整化之间扰动下溢到 0，每段都收缩到 0 时给出 -∞，而不是把收缩段的时间算进平均得到 ≈ 0
+        struct Decay;
+        impl OdeSystem for Decay {
+            fn dim(&self) -> usize {
+                1
+            }
+
+            fn rhs(&self, _t: f64, y: &[f64], dy: &mut [f64]) {
+                dy[0] = -100.0 * y[0];
+            }
+        }
+        let lambda = largest_lyapunov_system(&Decay, &[0.0], 0.01, 2000, 1000, 1e-8);
+        assert_eq!(lambda, f64::NEG_INFINITY);
+        let lambda = largest_lyapunov_system(&Decay, &[0.0], 0.01, 2000, 10, 1e-8);
+        assert!((lambda + 98.1).abs() < 0.5, "λ = {}", lambda);
+    }
+}
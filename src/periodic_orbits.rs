@@ -0,0 +1,425 @@
+//periodic_orbits.rs
+use std::f64::consts::PI;
+use std::fs::File;
+use std::io::Write;
+use rayon::prelude::*;
+use crate::basin::same_attractor;
+use crate::error::ChaosError;
+use crate::lyapunov::rk4_tangent_step;
+use crate::model::{Integrator, PendulumParams, TimeDirection};
+use crate::solve_equation::{rk4_step, steps_per_period, wrap_angle, State};
+use crate::stroboscopic::StroboscopicMap;
+
+// 频闪映射 F：从驱动相位 0 出发积分一个驱动周期。周期 k 轨道是 F^k(x) = x + (2πm, 0) 的解，
+// m 为 k 个周期内转过的整圈数（振动轨道 m = 0，转动轨道 m ≠ 0）。用 Newton 法求解
+// G(x) = F^k(x) - x - (2πm, 0) = 0，雅可比 DG = M - I，M 为 F^k 的雅可比（单值矩阵）。
+// M 的特征值即 Floquet 乘子：模都小于 1 时轨道稳定；二者之积恒为 exp(-∫q dt)。
+// 对不稳定轨道 Newton 法同样收敛，这是仅靠长时间积分看不到的。
+
+/// F^k 的雅可比的计算方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Jacobian {
+    /// 与轨道一起积分变分方程（精确到积分误差）
+    Variational,
+    /// 步长为 h 的中心差分（每次多积分 4 条轨道）
+    FiniteDifference { h: f64 },
+}
+
+/// Newton 迭代的设置
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NewtonOptions {
+    /// 残差 |G(x)| 小于 tolerance 时视为收敛
+    pub tolerance: f64,
+    pub max_iterations: usize,
+    /// 单步修正的最大长度，离解较远时防止跳到别的吸引域
+    pub max_step: f64,
+    pub jacobian: Jacobian,
+}
+
+impl Default for NewtonOptions {
+    fn default() -> Self {
+        NewtonOptions {
+            tolerance: 1e-10,
+            max_iterations: 50,
+            max_step: 0.5,
+            jacobian: Jacobian::Variational,
+        }
+    }
+}
+
+/// 频闪映射的周期轨道
+#[derive(Debug, Clone)]
+pub struct PeriodicOrbit {
+    /// 周期（驱动周期数）
+    pub period: usize,
+    /// 轨道上的 k 个庞加莱点 (θ, ω)，θ 折叠到 [-π, π)，按映射的顺序排列
+    pub points: Vec<(f64, f64)>,
+    /// 一个周期内转过的整圈数 m
+    pub winding: i64,
+    /// Floquet 乘子 (实部, 虚部)，按模从大到小
+    pub multipliers: [(f64, f64); 2],
+    pub iterations: usize,
+    pub residual: f64,
+}
+
+impl PeriodicOrbit {
+    /// 最大 Floquet 乘子的模
+    pub fn spectral_radius(&self) -> f64 {
+        let (re, im) = self.multipliers[0];
+        re.hypot(im)
+    }
+
+    /// 所有 Floquet 乘子的模都小于 1
+    pub fn is_stable(&self) -> bool {
+        self.spectral_radius() < 1.0
+    }
+}
+
+// 周期轨道、单值矩阵、延拓与流形共用的积分设置：每周期 steps_per_period(params) 步，积分器固定为 RK4。
+// 变分方程只有 RK4 版本（lyapunov::rk4_tangent_step），映射本身也用 RK4，
+// 否则 Newton 法的雅可比与它求根的映射不是同一个离散映射；params.integrator 因此不起作用。
+pub(crate) fn orbit_params(params: &PendulumParams) -> PendulumParams {
+    let mut p = params.clone();
+    p.set_steps_per_period(steps_per_period(params));
+    p.integrator = Integrator::Rk4;
+    p
+}
+
+// F^k(x) 及其雅可比 [[∂θ/∂θ0, ∂θ/∂ω0], [∂ω/∂θ0, ∂ω/∂ω0]]；params 应来自 orbit_params
+pub(crate) fn map_with_jacobian(params: &PendulumParams, x: State, periods: usize, jacobian: Jacobian) -> (State, [[f64; 2]; 2]) {
+    match jacobian {
+        Jacobian::Variational => {
+            let mut state = x;
+            let mut columns = [(1.0, 0.0), (0.0, 1.0)];
+            for n in 0..periods * steps_per_period(params) {
+                let t = n as f64 * params.dt;
+                columns = columns.map(|v| rk4_tangent_step(&state, v, t, params).1);
+                state = rk4_step(&state, t, params).0;
+            }
+            (state, [[columns[0].0, columns[1].0], [columns[0].1, columns[1].1]])
+        }
+        Jacobian::FiniteDifference { h } => {
+            let map = StroboscopicMap::new(params);
+            let shifted = |d_theta: f64, d_omega: f64| {
+                let s = State {
+                    theta: x.theta + d_theta,
+                    omega: x.omega + d_omega,
+                };
+                map.iterate_n(s, periods)
+            };
+            let (tp, tm) = (shifted(h, 0.0), shifted(-h, 0.0));
+            let (wp, wm) = (shifted(0.0, h), shifted(0.0, -h));
+            let d = 2.0 * h;
+            let m = [
+                [(tp.theta - tm.theta) / d, (wp.theta - wm.theta) / d],
+                [(tp.omega - tm.omega) / d, (wp.omega - wm.omega) / d],
+            ];
+            (map.iterate_n(x, periods), m)
+        }
+    }
+}
+
+// 2×2 矩阵的特征值，按模从大到小
+pub(crate) fn eigenvalues(m: &[[f64; 2]; 2]) -> [(f64, f64); 2] {
+    let half_trace = 0.5 * (m[0][0] + m[1][1]);
+    let det = m[0][0] * m[1][1] - m[0][1] * m[1][0];
+    let disc = half_trace * half_trace - det;
+    if disc >= 0.0 {
+        let r = disc.sqrt();
+        let (a, b) = (half_trace + r, half_trace - r);
+        if a.abs() >= b.abs() { [(a, 0.0), (b, 0.0)] } else { [(b, 0.0), (a, 0.0)] }
+    } else {
+        let r = (-disc).sqrt();
+        [(half_trace, r), (half_trace, -r)]
+    }
+}
+
+/// 频闪映射 F^k 在某点的单值矩阵（monodromy matrix）及其特征值
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Monodromy {
+    /// 映射的像 F^k(x)，θ 不折叠
+    pub image: State,
+    /// [[∂θ/∂θ0, ∂θ/∂ω0], [∂ω/∂θ0, ∂ω/∂ω0]]
+    pub matrix: [[f64; 2]; 2],
+    /// Floquet 乘子 (实部, 虚部)，按模从大到小
+    pub multipliers: [(f64, f64); 2],
+    /// 积分的驱动周期数 k
+    pub periods: usize,
+    /// 驱动周期 T
+    pub period: f64,
+}
+
+impl Monodromy {
+    /// 最大 Floquet 乘子的模
+    pub fn spectral_radius(&self) -> f64 {
+        let (re, im) = self.multipliers[0];
+        re.hypot(im)
+    }
+
+    /// det M，理论值为 exp(-q k T)（相体积每周期按阻尼收缩）
+    pub fn determinant(&self) -> f64 {
+        let m = &self.matrix;
+        m[0][0] * m[1][1] - m[0][1] * m[1][0]
+    }
+
+    /// 局部 Floquet 指数 ln|μ| / (kT)，按模从大到小；不在周期轨道上时只描述这 k 个周期内的局部拉伸
+    pub fn exponents(&self) -> [f64; 2] {
+        let time = self.periods as f64 * self.period;
+        self.multipliers.map(|(re, im)| re.hypot(im).ln() / time)
+    }
+}
+
+/// 从驱动相位 0 处的状态 (θ, ω) 出发，与轨道一起积分变分方程 periods 个驱动周期，得到单值矩阵与 Floquet 乘子
+///
+/// 任意相空间点都可以计算（局部稳定性分析）；在周期 k 轨道上时即为该轨道的 Floquet 乘子。
+/// 与 find_periodic_orbit 相同：每周期 steps_per_period(params) 步 RK4（不看 params.integrator），要求驱动是周期的。
+pub fn monodromy(params: &PendulumParams, theta: f64, omega: f64, periods: usize) -> Result<Monodromy, ChaosError> {
+    check_orbit_params(params, periods)?;
+    let p = orbit_params(params);
+    let (image, matrix) = map_with_jacobian(&p, State { theta, omega }, periods, Jacobian::Variational);
+    Ok(Monodromy {
+        image,
+        matrix,
+        multipliers: eigenvalues(&matrix),
+        periods,
+        period: 2.0 * PI / p.omega_d,
+    })
+}
+
+// 周期轨道要求驱动是周期的、正向积分，且周期至少为 1
+pub(crate) fn check_orbit_params(params: &PendulumParams, period: usize) -> Result<(), ChaosError> {
+    params.validate_periodic()?;
+    if period == 0 {
+        return Err(ChaosError::InvalidParams("orbit period must be at least 1".to_string()));
+    }
+    if params.f_d_schedule.is_some() || params.q_schedule.is_some() {
+        return Err(ChaosError::InvalidParams(
+            "periodic orbits need a time-periodic drive, f_d_schedule and q_schedule must be unset".to_string(),
+        ));
+    }
+    if params.direction == TimeDirection::Backward {
+        return Err(ChaosError::InvalidParams("periodic orbits are computed with forward time".to_string()));
+    }
+    Ok(())
+}
+
+/// 从初始猜测 (θ0, ω0) 出发，用 Newton 法求频闪映射的周期 period 轨道
+///
+/// 积分步数取 steps_per_period(params)，dt 调整为周期的整数分之一；积分器固定为 RK4（与变分方程一致），
+/// params.integrator、t_end、噪声与 f_d/q 的时间表不起作用（要求驱动是周期的）。周期 k 的解也可能是周期 k 的因子的轨道，
+/// 需要时检查 points 是否有重复。
+pub fn find_periodic_orbit(
+    params: &PendulumParams,
+    initial_theta: f64,
+    initial_omega: f64,
+    period: usize,
+    options: &NewtonOptions,
+) -> Result<PeriodicOrbit, ChaosError> {
+    check_orbit_params(params, period)?;
+    let p = orbit_params(params);
+
+    let mut x = State {
+        theta: initial_theta,
+        omega: initial_omega,
+    };
+    let mut residual = f64::INFINITY;
+    for iteration in 0..=options.max_iterations {
+        let (fx, m) = map_with_jacobian(&p, x, period, options.jacobian);
+        let d_theta = fx.theta - x.theta;
+        let winding = (d_theta / (2.0 * PI)).round();
+        let g = (d_theta - 2.0 * PI * winding, fx.omega - x.omega);
+        residual = g.0.hypot(g.1);
+        if residual < options.tolerance {
+            return Ok(orbit(&p, x, period, winding as i64, &m, iteration, residual));
+        }
+        if iteration == options.max_iterations {
+            break;
+        }
+        // (M - I) δ = -G
+        let (a, b, c, d) = (m[0][0] - 1.0, m[0][1], m[1][0], m[1][1] - 1.0);
+        let det = a * d - b * c;
+        if det == 0.0 || !det.is_finite() {
+            break;
+        }
+        let mut delta = ((-d * g.0 + b * g.1) / det, (c * g.0 - a * g.1) / det);
+        let length = delta.0.hypot(delta.1);
+        if length > options.max_step {
+            let scale = options.max_step / length;
+            delta = (delta.0 * scale, delta.1 * scale);
+        }
+        x = State {
+            theta: wrap_angle(x.theta + delta.0),
+            omega: x.omega + delta.1,
+        };
+    }
+    Err(ChaosError::NotConverged {
+        iterations: options.max_iterations,
+        residual,
+    })
+}
+
+fn orbit(
+    params: &PendulumParams,
+    x: State,
+    period: usize,
+    winding: i64,
+    monodromy: &[[f64; 2]; 2],
+    iterations: usize,
+    residual: f64,
+) -> PeriodicOrbit {
+    let map = StroboscopicMap::new(params);
+    let points = std::iter::once(x)
+        .chain(map.images(x))
+        .take(period)
+        .map(|s| (wrap_angle(s.theta), s.omega))
+        .collect();
+    PeriodicOrbit {
+        period,
+        points,
+        winding,
+        multipliers: eigenvalues(monodromy),
+        iterations,
+        residual,
+    }
+}
+
+/// 从多个初始猜测并行求周期 period 轨道，去掉不收敛的与重复的（点集相距不超过 tolerance 视为同一条）
+pub fn find_periodic_orbits(
+    params: &PendulumParams,
+    seeds: &[(f64, f64)],
+    period: usize,
+    options: &NewtonOptions,
+    tolerance: f64,
+) -> Result<Vec<PeriodicOrbit>, ChaosError> {
+    params.validate_periodic()?;
+    let found: Vec<PeriodicOrbit> = seeds
+        .par_iter()
+        .filter_map(|&(theta, omega)| find_periodic_orbit(params, theta, omega, period, options).ok())
+        .collect();
+    let mut distinct: Vec<PeriodicOrbit> = Vec::new();
+    for orbit in found {
+        if !distinct.iter().any(|o| o.winding == orbit.winding && same_attractor(&o.points, &orbit.points, tolerance)) {
+            distinct.push(orbit);
+        }
+    }
+    Ok(distinct)
+}
+
+/// 把周期轨道写成 CSV（列：orbit,period,winding,index,theta,omega,multiplier1_re,multiplier1_im,multiplier2_re,multiplier2_im,stable），
+/// 每个庞加莱点一行
+pub fn write_periodic_orbits_csv(path: &str, orbits: &[PeriodicOrbit]) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(
+        f,
+        "orbit,period,winding,index,theta,omega,multiplier1_re,multiplier1_im,multiplier2_re,multiplier2_im,stable"
+    )?;
+    for (k, o) in orbits.iter().enumerate() {
+        let [(r1, i1), (r2, i2)] = o.multipliers;
+        for (index, (theta, omega)) in o.points.iter().enumerate() {
+            writeln!(
+                f,
+                "{},{},{},{},{:.12},{:.12},{:.12},{:.12},{:.12},{:.12},{}",
+                k,
+                o.period,
+                o.winding,
+                index,
+                theta,
+                omega,
+                r1,
+                i1,
+                r2,
+                i2,
+                o.is_stable()
+            )?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_newton_finds_stable_and_unstable_orbits() {
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.q = 0.5;
+        params.omega_d = 2.0 / 3.0;
+        params.set_steps_per_period(200);
+
+        // 弱驱动：唯一的周期 1 振动是稳定的，乘子之积等于 exp(-qT)
+        params.f_d = 0.5;
+        let options = NewtonOptions::default();
+        let orbit = find_periodic_orbit(&params, 0.0, 0.0, 1, &options).unwrap();
+        assert_eq!(orbit.winding, 0);
+        assert!(orbit.is_stable());
+        let modulus = |(re, im): (f64, f64)| re.hypot(im);
+        let product = modulus(orbit.multipliers[0]) * modulus(orbit.multipliers[1]);
+        let period = 2.0 * PI / params.omega_d;
+        assert!((product - (-params.q * period).exp()).abs() < 1e-6, "{}", product);
+        // 积分该点一个周期回到自身
+        let p = orbit.points[0];
+        let back = StroboscopicMap::new(&params).iterate(State { theta: p.0, omega: p.1 });
+        assert!((wrap_angle(back.theta - p.0)).abs() < 1e-8 && (back.omega - p.1).abs() < 1e-8);
+
+        // 差分雅可比给出相同的乘子
+        let fd = NewtonOptions {
+            jacobian: Jacobian::FiniteDifference { h: 1e-6 },
+            ..options
+        };
+        let orbit_fd = find_periodic_orbit(&params, 0.0, 0.0, 1, &fd).unwrap();
+        assert!((orbit_fd.points[0].0 - p.0).abs() < 1e-8);
+        assert!((orbit_fd.multipliers[0].0 - orbit.multipliers[0].0).abs() < 1e-5);
+
+        // 混沌区：吸引子里嵌着不稳定的周期 1 轨道
+        params.f_d = 1.2;
+        let seeds: Vec<(f64, f64)> = (0..5)
+            .flat_map(|i| (0..4).map(move |j| (-PI + 1.2 * i as f64, -2.0 + 1.2 * j as f64)))
+            .collect();
+        let orbits = find_periodic_orbits(&params, &seeds, 1, &options, 1e-6).unwrap();
+        assert!(orbits.iter().any(|o| !o.is_stable()), "{:?}", orbits);
+        assert!(matches!(
+            find_periodic_orbit(&params, 0.0, 0.0, 0, &options),
+            Err(ChaosError::InvalidParams(_))
+        ));
+    }
+
+    #[test]
+    fn test_monodromy_anywhere_in_phase_space() {
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.q = 0.5;
+        params.f_d = 1.2;
+        params.omega_d = 2.0 / 3.0;
+        params.set_steps_per_period(200);
+        let period = 2.0 * PI / params.omega_d;
+
+        // 任意点：变分方程与差分雅可比一致，行列式为 exp(-q k T)
+        let m = monodromy(&params, 0.7, -0.4, 2).unwrap();
+        let (_, fd) = map_with_jacobian(&params, State { theta: 0.7, omega: -0.4 }, 2, Jacobian::FiniteDifference { h: 1e-6 });
+        for i in 0..2 {
+            for j in 0..2 {
+                assert!((m.matrix[i][j] - fd[i][j]).abs() < 1e-4 * fd[i][j].abs().max(1.0), "{:?} {:?}", m.matrix, fd);
+            }
+        }
+        assert!((m.determinant() - (-params.q * 2.0 * period).exp()).abs() < 1e-8);
+        let [e1, e2] = m.exponents();
+        assert!((e1 + e2 + params.q).abs() < 1e-6);
+        assert_eq!(m.image, StroboscopicMap::new(&params).iterate_n(State { theta: 0.7, omega: -0.4 }, 2));
+
+        // 在周期轨道上即为轨道的 Floquet 乘子
+        params.f_d = 0.5;
+        let orbit = find_periodic_orbit(&params, 0.0, 0.0, 1, &NewtonOptions::default()).unwrap();
+        let (theta, omega) = orbit.points[0];
+        let m = monodromy(&params, theta, omega, 1).unwrap();
+        assert!((m.spectral_radius() - orbit.spectral_radius()).abs() < 1e-9);
+        assert!(m.spectral_radius() < 1.0);
+
+        // 积分器固定为 RK4：换成辛方法不改变结果
+        params.integrator = Integrator::Yoshida4;
+        let again = find_periodic_orbit(&params, 0.0, 0.0, 1, &NewtonOptions::default()).unwrap();
+        assert_eq!(again.points, orbit.points);
+        assert_eq!(monodromy(&params, theta, omega, 1).unwrap(), m);
+    }
+}
@@ -0,0 +1,241 @@
+//pipeline.rs
+use std::f64::consts::PI;
+use std::io::{BufRead, Write};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::basin::classify_attractor_section;
+use crate::error::ChaosError;
+use crate::lyapunov::largest_exponent;
+use crate::model::PendulumParams;
+use crate::solve_equation::{steps_per_period, State};
+use crate::stroboscopic::{orbit_rotation_number, section_points, StroboscopicMap};
+
+// JSON Lines 管道：每行一条参数记录进、每行一条结果出，便于与 shell 管道、jq、作业调度器组合做大规模参数研究。
+// 一批记录并行计算、按输入顺序输出；单条记录出错（JSON 无法解析、参数无效）时输出带 error 字段的结果行，不中断整个管道。
+
+/// 一条记录要求计算的量
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PipelineOutput {
+    /// 庞加莱点
+    Poincare,
+    /// 最大 Lyapunov 指数
+    Lyapunov,
+    /// 终态吸引子类型（basin::AttractorClass）
+    Classification,
+}
+
+/// 输入的一行；缺省的字段取默认值
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PipelineRecord {
+    /// 原样复制到结果行，用来把结果与输入对应起来
+    pub id: Option<serde_json::Value>,
+    /// 完整参数；缺省时用管道的基础参数（命令行上的 --config 等）
+    pub params: Option<PendulumParams>,
+    /// 覆盖基础参数或 params 中的对应值
+    pub f_d: Option<f64>,
+    pub q: Option<f64>,
+    pub omega_d: Option<f64>,
+    pub theta0: f64,
+    pub omega0: f64,
+    pub transient_periods: usize,
+    pub sample_periods: usize,
+    /// 判定周期时尝试的最大周期与容差
+    pub max_period: usize,
+    pub tolerance: f64,
+    pub outputs: Vec<PipelineOutput>,
+}
+
+impl Default for PipelineRecord {
+    fn default() -> Self {
+        PipelineRecord {
+            id: None,
+            params: None,
+            f_d: None,
+            q: None,
+            omega_d: None,
+            theta0: 0.2,
+            omega0: 0.0,
+            transient_periods: 100,
+            sample_periods: 200,
+            max_period: 16,
+            tolerance: 1e-3,
+            outputs: vec![PipelineOutput::Poincare, PipelineOutput::Lyapunov, PipelineOutput::Classification],
+        }
+    }
+}
+
+/// 终态吸引子类型：class 为 periodic / rotating / chaotic，code 同 AttractorClass::code
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Classification {
+    pub class: String,
+    pub code: i64,
+    pub rotation: f64,
+}
+
+/// 输出的一行；没有要求或出错时对应字段省略
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PipelineResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub f_d: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub q: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub omega_d: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub poincare: Option<Vec<(f64, f64)>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lyapunov: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub classification: Option<Classification>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// 计算一条记录；base 为记录未给出 params 时使用的参数
+///
+/// 积分 transient_periods + sample_periods 个整周期（每周期 steps_per_period(params) 步），
+/// 庞加莱点与吸引子类型取自后 sample_periods 个周期，Lyapunov 指数在同一段上从过渡期末的状态估计。
+pub fn process_record(base: &PendulumParams, record: &PipelineRecord) -> PipelineResult {
+    let mut params = record.params.clone().unwrap_or_else(|| base.clone());
+    params.f_d = record.f_d.unwrap_or(params.f_d);
+    params.q = record.q.unwrap_or(params.q);
+    params.omega_d = record.omega_d.unwrap_or(params.omega_d);
+    let mut result = PipelineResult {
+        id: record.id.clone(),
+        f_d: Some(params.f_d),
+        q: Some(params.q),
+        omega_d: Some(params.omega_d),
+        ..PipelineResult::default()
+    };
+    if let Err(e) = compute(&mut params, record, &mut result) {
+        result.error = Some(e.to_string());
+    }
+    result
+}
+
+fn compute(params: &mut PendulumParams, record: &PipelineRecord, result: &mut PipelineResult) -> Result<(), ChaosError> {
+    // 改了 omega_d 时每周期步数不变，步长跟着新的周期走
+    if let crate::model::StepControl::StepsPerPeriod { steps } = params.step_control {
+        params.set_steps_per_period(steps);
+    }
+    params.validate()?;
+    let (transient, samples) = (record.transient_periods, record.sample_periods);
+    let spp = steps_per_period(params);
+    let start = State {
+        theta: record.theta0,
+        omega: record.omega0,
+    };
+    let orbit = StroboscopicMap::with_steps(params, spp).orbit(start, transient, samples);
+    let section = section_points(&orbit);
+    let wants = |o: PipelineOutput| record.outputs.contains(&o);
+    if wants(PipelineOutput::Classification) {
+        let rotation = orbit_rotation_number(&orbit);
+        let class = classify_attractor_section(&section, rotation, record.max_period, record.tolerance);
+        result.classification = Some(Classification {
+            class: class.name().to_string(),
+            code: class.code(),
+            rotation,
+        });
+    }
+    if wants(PipelineOutput::Lyapunov) {
+        let period = 2.0 * PI / params.omega_d;
+        let mut p = params.clone();
+        p.dt = period / spp as f64;
+        p.t_end = (samples * spp) as f64 * p.dt + 0.5 * p.dt;
+        result.lyapunov = Some(largest_exponent(&p, orbit[0], period).exponent);
+    }
+    if wants(PipelineOutput::Poincare) {
+        result.poincare = Some(section);
+    }
+    Ok(())
+}
+
+// 解析并计算一行；无法解析时结果只有 error 字段
+fn process_line(base: &PendulumParams, line: &str) -> PipelineResult {
+    match serde_json::from_str::<PipelineRecord>(line) {
+        Ok(record) => process_record(base, &record),
+        Err(e) => PipelineResult {
+            error: Some(format!("invalid record: {}", e)),
+            ..PipelineResult::default()
+        },
+    }
+}
+
+/// 从 input 逐行读记录、向 output 逐行写结果，返回处理的记录数（空行跳过）
+///
+/// 每次读入 batch_size 行并行计算，按输入顺序写出并 flush，下游可以边算边读。
+pub fn run_pipeline<R: BufRead, W: Write>(
+    base: &PendulumParams,
+    input: R,
+    mut output: W,
+    batch_size: usize,
+) -> std::io::Result<usize> {
+    let mut lines = input.lines();
+    let mut count = 0;
+    loop {
+        let batch: Vec<String> = lines
+            .by_ref()
+            .filter(|l| !matches!(l, Ok(s) if s.trim().is_empty()))
+            .take(batch_size.max(1))
+            .collect::<std::io::Result<_>>()?;
+        if batch.is_empty() {
+            return Ok(count);
+        }
+        let results: Vec<PipelineResult> = batch.par_iter().map(|line| process_line(base, line)).collect();
+        for result in &results {
+            serde_json::to_writer(&mut output, result)?;
+            writeln!(output)?;
+        }
+        output.flush()?;
+        count += batch.len();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pipeline_keeps_order_and_reports_errors() {
+        let mut base = PendulumParams::new();
+        base.l = base.g;
+        base.q = 0.5;
+        base.omega_d = 2.0 / 3.0;
+        base.set_steps_per_period(100);
+        let input = [
+            r#"{"id": 1, "f_d": 0.5, "transient_periods": 50, "sample_periods": 32}"#,
+            "",
+            r#"{"id": "chaotic", "f_d": 1.2, "transient_periods": 50, "sample_periods": 100, "outputs": ["lyapunov", "classification"]}"#,
+            r#"{"id": 3, "omega_d": 0.0}"#,
+            "not json",
+        ]
+        .join("\n");
+        let mut out = Vec::new();
+        assert_eq!(run_pipeline(&base, input.as_bytes(), &mut out, 2).unwrap(), 4);
+        let rows: Vec<PipelineResult> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(rows.len(), 4);
+
+        // 弱驱动：周期 1 摆动，指数为负
+        assert_eq!(rows[0].id, Some(serde_json::json!(1)));
+        assert_eq!(rows[0].poincare.as_ref().unwrap().len(), 32);
+        assert_eq!(rows[0].classification.as_ref().unwrap().class, "periodic");
+        assert_eq!(rows[0].classification.as_ref().unwrap().code, 1);
+        assert!(rows[0].lyapunov.unwrap() < 0.0);
+
+        assert_eq!(rows[1].id, Some(serde_json::json!("chaotic")));
+        assert!(rows[1].poincare.is_none());
+        assert_eq!(rows[1].classification.as_ref().unwrap().class, "chaotic");
+        assert!(rows[1].lyapunov.unwrap() > 0.05);
+
+        assert!(rows[2].error.as_ref().unwrap().contains("omega_d"));
+        assert!(rows[3].error.as_ref().unwrap().starts_with("invalid record"));
+    }
+}
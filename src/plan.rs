@@ -0,0 +1,182 @@
+//plan.rs
+use std::fmt;
+use crate::error::ChaosError;
+use crate::io::{Decimation, TrajectoryFormat};
+use crate::model::{PendulumParams, StepControl};
+use crate::solve_equation::{estimated_trajectory_bytes, fixed_step_count, step_count, State};
+
+// 干跑（dry run）：积分之前按参数估计积分步数、保存完整轨迹所需的内存与输出文件大小。
+// t_end = 1e6、dt = 1e-3 就是 10^9 步，solve 要分配约 24 GB，CSV 输出约 50 GB。
+// 内存预算 params.memory_budget_bytes 生效的地方：
+//   solve / solve_trajectory 在估计超出预算时直接返回 ChaosError::MemoryBudget，不开始积分；
+//   poincare 改走不保存轨迹的 poincare_streaming（结果相同）。
+// 自适应步长的步数事先未知，按初始步长 dt 估计；CSV 的大小按每个字段 17 字节（{:.12} 加分隔符）估计，
+// gzip 按 CSV 的 1/3 粗略估计。
+
+/// 命令行不给 --memory-budget 时的默认预算：2 GiB
+pub const DEFAULT_MEMORY_BUDGET_BYTES: usize = 2 << 30;
+
+// CSV 每个字段的平均字节数
+const CSV_FIELD_BYTES: usize = 17;
+
+/// 一次运行的成本估计
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CostEstimate {
+    /// 积分步数（不含初始点）
+    pub steps: usize,
+    /// 保存完整轨迹（solve 的返回值）所需的内存
+    pub trajectory_bytes: usize,
+    /// 输出的行数与字节数
+    pub output_rows: usize,
+    pub output_bytes: usize,
+    pub memory_budget: Option<usize>,
+}
+
+impl CostEstimate {
+    /// 完整轨迹超出内存预算（没有预算时为 false）
+    pub fn exceeds_budget(&self) -> bool {
+        self.memory_budget.is_some_and(|budget| self.trajectory_bytes > budget)
+    }
+}
+
+impl fmt::Display for CostEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "integration steps: {}", self.steps)?;
+        write!(f, "full trajectory in memory: {}", format_bytes(self.trajectory_bytes))?;
+        match self.memory_budget {
+            Some(budget) if self.exceeds_budget() => writeln!(f, " (exceeds the memory budget of {})", format_bytes(budget))?,
+            Some(budget) => writeln!(f, " (memory budget {})", format_bytes(budget))?,
+            None => writeln!(f, " (no memory budget)")?,
+        }
+        write!(f, "output: {} rows, about {}", self.output_rows, format_bytes(self.output_bytes))
+    }
+}
+
+/// 积分到 params.t_end 的步数；自适应步长按 dt 估计
+pub fn estimated_steps(params: &PendulumParams) -> usize {
+    match params.step_control {
+        StepControl::Adaptive => step_count(params.t_end, params.dt),
+        StepControl::FixedDt | StepControl::StepsPerPeriod { .. } => fixed_step_count(params),
+    }
+}
+
+/// 写出整条轨迹（每行 columns 个字段，按 decimation 抽稀）的成本
+pub fn trajectory_cost(params: &PendulumParams, decimation: Decimation, format: TrajectoryFormat, columns: usize) -> CostEstimate {
+    let steps = estimated_steps(params);
+    let points = steps.saturating_add(1);
+    let output_rows = match decimation {
+        Decimation::None => points,
+        Decimation::Every(k) => points.div_ceil(k.max(1)),
+        Decimation::MaxPoints(n) => points.div_ceil(points.div_ceil(n.max(1)).max(1)),
+    };
+    let csv_row = columns * CSV_FIELD_BYTES;
+    let row_bytes = match format {
+        TrajectoryFormat::Csv => csv_row,
+        TrajectoryFormat::CsvGzip => csv_row / 3,
+        TrajectoryFormat::Binary => columns * std::mem::size_of::<f64>(),
+        #[cfg(feature = "parquet")]
+        TrajectoryFormat::Parquet => columns * std::mem::size_of::<f64>(),
+    };
+    CostEstimate {
+        steps,
+        trajectory_bytes: estimated_trajectory_bytes(params),
+        output_rows,
+        output_bytes: output_rows.saturating_mul(row_bytes),
+        memory_budget: params.memory_budget_bytes,
+    }
+}
+
+/// 取 sample_periods 个庞加莱点（CSV 两列）的成本；积分时长取 params.t_end
+pub fn poincare_cost(params: &PendulumParams, sample_periods: usize) -> CostEstimate {
+    CostEstimate {
+        steps: estimated_steps(params),
+        trajectory_bytes: estimated_trajectory_bytes(params),
+        output_rows: sample_periods,
+        output_bytes: sample_periods.saturating_mul(2 * CSV_FIELD_BYTES),
+        memory_budget: params.memory_budget_bytes,
+    }
+}
+
+/// 把 rows 个状态收集进内存（Vec<(f64, State)>）所需的字节数是否超出 params 的预算
+pub fn check_memory_budget(params: &PendulumParams, rows: usize) -> Result<(), ChaosError> {
+    let needed = rows.saturating_mul(std::mem::size_of::<(f64, State)>());
+    match params.memory_budget_bytes {
+        Some(budget) if needed > budget => Err(ChaosError::MemoryBudget { needed, budget }),
+        _ => Ok(()),
+    }
+}
+
+/// 解析字节数："1048576"、"512K"、"64MB"、"1.5G"、"2GiB"（单位按 1024 进位，大小写不敏感）
+pub fn parse_byte_size(s: &str) -> Result<usize, String> {
+    let text = s.trim().to_ascii_uppercase();
+    let unit_start = text.find(|c: char| c.is_ascii_alphabetic()).unwrap_or(text.len());
+    let (number, unit) = text.split_at(unit_start);
+    let scale: u64 = match unit.trim_end_matches("IB").trim_end_matches('B') {
+        "" => 1,
+        "K" => 1 << 10,
+        "M" => 1 << 20,
+        "G" => 1 << 30,
+        "T" => 1 << 40,
+        _ => return Err(format!("unknown size unit in '{}'", s)),
+    };
+    let value: f64 = number.trim().parse().map_err(|_| format!("invalid size '{}'", s))?;
+    if !(value.is_finite() && value >= 0.0) {
+        return Err(format!("invalid size '{}'", s));
+    }
+    Ok((value * scale as f64).round() as usize)
+}
+
+/// 以 B、KiB、MiB、GiB、TiB 显示字节数
+pub fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solve_equation::solve;
+
+    #[test]
+    fn test_cost_estimate_and_memory_guard() {
+        assert_eq!(parse_byte_size("512"), Ok(512));
+        assert_eq!(parse_byte_size("64k"), Ok(64 << 10));
+        assert_eq!(parse_byte_size("1.5GiB"), Ok(3 << 29));
+        assert_eq!(parse_byte_size(" 2 GB "), Ok(2 << 30));
+        assert!(parse_byte_size("3 parsecs").is_err());
+        assert_eq!(format_bytes(1000), "1000 B");
+        assert_eq!(format_bytes(3 << 29), "1.5 GiB");
+
+        // t_end = 1e6、dt = 1e-3：10^9 步，不积分就能拒绝
+        let mut params = PendulumParams::new();
+        params.dt = 1e-3;
+        params.t_end = 1e6;
+        params.memory_budget_bytes = Some(DEFAULT_MEMORY_BUDGET_BYTES);
+        let cost = trajectory_cost(&params, Decimation::None, TrajectoryFormat::Csv, 3);
+        assert_eq!(cost.steps, 1_000_000_000);
+        assert_eq!(cost.trajectory_bytes, 24 * 1_000_000_001);
+        assert!(cost.exceeds_budget());
+        assert!(cost.to_string().contains("exceeds the memory budget of 2.0 GiB"), "{}", cost);
+        assert!(matches!(solve(&params, 0.2, 0.0), Err(ChaosError::MemoryBudget { .. })));
+
+        let every = trajectory_cost(&params, Decimation::Every(1000), TrajectoryFormat::Binary, 3);
+        assert_eq!((every.output_rows, every.output_bytes), (1_000_001, 24 * 1_000_001));
+        let thinned = trajectory_cost(&params, Decimation::MaxPoints(1000), TrajectoryFormat::Csv, 3);
+        assert!(thinned.output_rows <= 1000 && thinned.output_rows > 900, "{:?}", thinned);
+
+        // 预算内照常积分
+        params.t_end = 1.0;
+        assert_eq!(solve(&params, 0.2, 0.0).unwrap().len(), 1001);
+        assert!(check_memory_budget(&params, 1001).is_ok());
+    }
+}
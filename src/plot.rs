@@ -0,0 +1,228 @@
+//plot.rs
+use std::ops::Range;
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use crate::solve_equation::{AnglePolicy, State};
+
+// 直接出图，不依赖 Python/gnuplot。每种图都有 PNG 与 SVG 两个版本，
+// 绘制逻辑对后端泛型，只在入口处选择 BitMapBackend 或 SVGBackend。
+
+/// 出图选项
+#[derive(Debug, Clone)]
+pub struct PlotOptions {
+    pub width: u32,
+    pub height: u32,
+    /// 标题，为空时不画
+    pub title: String,
+    /// 散点半径（像素）
+    pub point_size: u32,
+    /// 相图中 θ 的处理方式（庞加莱点与分岔图已经折叠，不受影响）
+    pub angle: AnglePolicy,
+}
+
+impl Default for PlotOptions {
+    fn default() -> Self {
+        PlotOptions {
+            width: 1000,
+            height: 800,
+            title: String::new(),
+            point_size: 1,
+            angle: AnglePolicy::Symmetric,
+        }
+    }
+}
+
+fn plot_error<E: std::fmt::Display>(e: E) -> std::io::Error {
+    std::io::Error::other(e.to_string())
+}
+
+// 点集的坐标范围，两侧各留 5% 空白；空点集或退化方向给出单位宽度
+fn padded_range(values: impl Iterator<Item = f64>) -> Range<f64> {
+    let (lo, hi) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| (lo.min(v), hi.max(v)));
+    if !lo.is_finite() || !hi.is_finite() {
+        return 0.0..1.0;
+    }
+    let pad = if hi > lo { 0.05 * (hi - lo) } else { 0.5 };
+    (lo - pad)..(hi + pad)
+}
+
+enum Style {
+    Points(u32),
+    Line,
+    /// 相邻两点 x 方向跳变超过给定值时断开的折线
+    BrokenLine(f64),
+}
+
+fn draw_xy<DB: DrawingBackend>(
+    root: DrawingArea<DB, Shift>,
+    points: &[(f64, f64)],
+    labels: (&str, &str),
+    style: Style,
+    opts: &PlotOptions,
+) -> std::io::Result<()> {
+    root.fill(&WHITE).map_err(plot_error)?;
+    let x_range = padded_range(points.iter().map(|p| p.0));
+    let y_range = padded_range(points.iter().map(|p| p.1));
+    let mut builder = ChartBuilder::on(&root);
+    builder.margin(15).x_label_area_size(40).y_label_area_size(60);
+    if !opts.title.is_empty() {
+        builder.caption(&opts.title, ("sans-serif", 24));
+    }
+    let mut chart = builder.build_cartesian_2d(x_range, y_range).map_err(plot_error)?;
+    chart
+        .configure_mesh()
+        .x_desc(labels.0)
+        .y_desc(labels.1)
+        .disable_mesh()
+        .draw()
+        .map_err(plot_error)?;
+    match style {
+        Style::Points(size) => {
+            chart
+                .draw_series(points.iter().map(|&p| Circle::new(p, size, BLUE.filled())))
+                .map_err(plot_error)?;
+        }
+        Style::Line => {
+            chart
+                .draw_series(LineSeries::new(points.iter().copied(), &BLUE))
+                .map_err(plot_error)?;
+        }
+        Style::BrokenLine(jump) => {
+            for segment in points.chunk_by(|a, b| (b.0 - a.0).abs() <= jump) {
+                chart
+                    .draw_series(LineSeries::new(segment.iter().copied(), &BLUE))
+                    .map_err(plot_error)?;
+            }
+        }
+    }
+    root.present().map_err(plot_error)
+}
+
+// 多个初值的庞加莱点画在同一张图上，每个 run 一种颜色，图例为 run_id
+fn draw_runs<DB: DrawingBackend>(
+    root: DrawingArea<DB, Shift>,
+    runs: &[Vec<(f64, f64)>],
+    opts: &PlotOptions,
+) -> std::io::Result<()> {
+    root.fill(&WHITE).map_err(plot_error)?;
+    let x_range = padded_range(runs.iter().flatten().map(|p| p.0));
+    let y_range = padded_range(runs.iter().flatten().map(|p| p.1));
+    let mut builder = ChartBuilder::on(&root);
+    builder.margin(15).x_label_area_size(40).y_label_area_size(60);
+    if !opts.title.is_empty() {
+        builder.caption(&opts.title, ("sans-serif", 24));
+    }
+    let mut chart = builder.build_cartesian_2d(x_range, y_range).map_err(plot_error)?;
+    chart
+        .configure_mesh()
+        .x_desc("theta")
+        .y_desc("omega")
+        .disable_mesh()
+        .draw()
+        .map_err(plot_error)?;
+    for (run_id, samples) in runs.iter().enumerate() {
+        let color = Palette99::pick(run_id).to_rgba();
+        chart
+            .draw_series(samples.iter().map(|&p| Circle::new(p, opts.point_size, color.filled())))
+            .map_err(plot_error)?
+            .label(format!("run {}", run_id))
+            .legend(move |(x, y)| Circle::new((x + 10, y), 4, color.filled()));
+    }
+    chart
+        .configure_series_labels()
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()
+        .map_err(plot_error)?;
+    root.present().map_err(plot_error)
+}
+
+// 相图：θ 折叠时按 ±π 处的跳变断开折线，避免横穿整幅图的连线
+fn draw_phase_portrait<DB: DrawingBackend>(
+    root: DrawingArea<DB, Shift>,
+    traj: &[(f64, State)],
+    opts: &PlotOptions,
+) -> std::io::Result<()> {
+    let points: Vec<(f64, f64)> = traj.iter().map(|(_, s)| (opts.angle.apply(s.theta), s.omega)).collect();
+    let style = if opts.angle == AnglePolicy::Raw {
+        Style::Line
+    } else {
+        Style::BrokenLine(std::f64::consts::PI)
+    };
+    draw_xy(root, &points, ("theta", "omega"), style, opts)
+}
+
+/// 庞加莱截面散点图（PNG）
+pub fn poincare_png(path: &str, samples: &[(f64, f64)], opts: &PlotOptions) -> std::io::Result<()> {
+    let root = BitMapBackend::new(path, (opts.width, opts.height)).into_drawing_area();
+    draw_xy(root, samples, ("theta", "omega"), Style::Points(opts.point_size), opts)
+}
+
+/// 庞加莱截面散点图（SVG）
+pub fn poincare_svg(path: &str, samples: &[(f64, f64)], opts: &PlotOptions) -> std::io::Result<()> {
+    let root = SVGBackend::new(path, (opts.width, opts.height)).into_drawing_area();
+    draw_xy(root, samples, ("theta", "omega"), Style::Points(opts.point_size), opts)
+}
+
+/// 多个初值的庞加莱截面画在一张图上（PNG），runs[i] 的点用第 i 种颜色，图例为 run_id
+pub fn poincare_runs_png(path: &str, runs: &[Vec<(f64, f64)>], opts: &PlotOptions) -> std::io::Result<()> {
+    let root = BitMapBackend::new(path, (opts.width, opts.height)).into_drawing_area();
+    draw_runs(root, runs, opts)
+}
+
+/// 多个初值的庞加莱截面画在一张图上（SVG），runs[i] 的点用第 i 种颜色，图例为 run_id
+pub fn poincare_runs_svg(path: &str, runs: &[Vec<(f64, f64)>], opts: &PlotOptions) -> std::io::Result<()> {
+    let root = SVGBackend::new(path, (opts.width, opts.height)).into_drawing_area();
+    draw_runs(root, runs, opts)
+}
+
+/// 相图 ω–θ 折线（PNG），θ 按 opts.angle 处理
+pub fn phase_portrait_png(path: &str, traj: &[(f64, State)], opts: &PlotOptions) -> std::io::Result<()> {
+    let root = BitMapBackend::new(path, (opts.width, opts.height)).into_drawing_area();
+    draw_phase_portrait(root, traj, opts)
+}
+
+/// 相图 ω–θ 折线（SVG），θ 按 opts.angle 处理
+pub fn phase_portrait_svg(path: &str, traj: &[(f64, State)], opts: &PlotOptions) -> std::io::Result<()> {
+    let root = SVGBackend::new(path, (opts.width, opts.height)).into_drawing_area();
+    draw_phase_portrait(root, traj, opts)
+}
+
+/// 分岔图 θ–f_d 散点（PNG），points 为 bifurcation_diagram 的输出
+pub fn bifurcation_png(path: &str, points: &[(f64, f64)], opts: &PlotOptions) -> std::io::Result<()> {
+    let root = BitMapBackend::new(path, (opts.width, opts.height)).into_drawing_area();
+    draw_xy(root, points, ("f_d", "theta"), Style::Points(opts.point_size), opts)
+}
+
+/// 分岔图 θ–f_d 散点（SVG），points 为 bifurcation_diagram 的输出
+pub fn bifurcation_svg(path: &str, points: &[(f64, f64)], opts: &PlotOptions) -> std::io::Result<()> {
+    let root = SVGBackend::new(path, (opts.width, opts.height)).into_drawing_area();
+    draw_xy(root, points, ("f_d", "theta"), Style::Points(opts.point_size), opts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_poincare_png_and_svg_are_written() {
+        let samples: Vec<(f64, f64)> = (0..200).map(|i| ((i as f64 * 0.1).sin(), (i as f64 * 0.07).cos())).collect();
+        let dir = std::env::temp_dir();
+        let png = dir.join("chaos_plot_test.png");
+        let svg = dir.join("chaos_plot_test.svg");
+        let opts = PlotOptions {
+            width: 200,
+            height: 150,
+            title: "test".to_string(),
+            ..PlotOptions::default()
+        };
+        poincare_png(png.to_str().unwrap(), &samples, &opts).unwrap();
+        poincare_svg(svg.to_str().unwrap(), &samples, &opts).unwrap();
+        assert_eq!(&std::fs::read(&png).unwrap()[..4], b"\x89PNG");
+        assert!(std::fs::read_to_string(&svg).unwrap().contains("<svg"));
+
+        let runs = vec![samples[..100].to_vec(), samples[100..].to_vec()];
+        poincare_runs_svg(svg.to_str().unwrap(), &runs, &opts).unwrap();
+        assert!(std::fs::read_to_string(&svg).unwrap().contains("run 1"));
+    }
+}
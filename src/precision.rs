@@ -0,0 +1,242 @@
+//precision.rs
+use std::fs::File;
+use std::io::Write;
+use twofloat::TwoFloat;
+use crate::error::ChaosError;
+use crate::lyapunov::LyapunovEstimate;
+use crate::model::{DriveType, Forcing, PendulumParams};
+use crate::solve_equation::{solve_periods, wrap_angle, State};
+
+// 双双精度（TwoFloat，约 32 位十进制有效数字）版本的 RK4。
+// 参数本身仍以 f64 给出，只有状态、时间与中间运算使用扩展精度，
+// 因此与 f64 结果的差异只来自舍入误差的累积，可作为验证 f64 轨迹的参考解。
+
+/// 扩展精度状态
+#[derive(Debug, Clone, Copy)]
+pub struct ExtendedState {
+    pub theta: TwoFloat,
+    pub omega: TwoFloat,
+}
+
+impl ExtendedState {
+    /// 舍入回 f64 状态
+    pub fn to_state(&self) -> State {
+        State {
+            theta: self.theta.into(),
+            omega: self.omega.into(),
+        }
+    }
+}
+
+fn rhs_extended(theta: TwoFloat, omega: TwoFloat, t: TwoFloat, params: &PendulumParams) -> (TwoFloat, TwoFloat) {
+    let g_over_l = TwoFloat::from(params.g) / TwoFloat::from(params.l);
+    // 正弦驱动按扩展精度计算；其余波形在 f64 下求值
+    let drive = match params.forcing {
+        Forcing::Sine => TwoFloat::from(params.f_d_at(t.into())) * (TwoFloat::from(params.omega_d) * t).sin(),
+        _ => TwoFloat::from(params.drive(t.into())),
+    };
+    let coupling = match params.drive_type {
+        DriveType::Torque => TwoFloat::from(1.0),
+        DriveType::Parametric => -theta.sin(),
+    };
+    (omega, -g_over_l * theta.sin() - TwoFloat::from(params.q_at(t.into())) * omega + coupling * drive)
+}
+
+/// 扩展精度的单步 RK4，步长为 dt
+pub fn rk4_step_extended(
+    state: &ExtendedState,
+    t: TwoFloat,
+    dt: TwoFloat,
+    params: &PendulumParams,
+) -> ExtendedState {
+    let half = dt / 2.0;
+    let (k1t, k1w) = rhs_extended(state.theta, state.omega, t, params);
+    let (k2t, k2w) = rhs_extended(state.theta + half * k1t, state.omega + half * k1w, t + half, params);
+    let (k3t, k3w) = rhs_extended(state.theta + half * k2t, state.omega + half * k2w, t + half, params);
+    let (k4t, k4w) = rhs_extended(state.theta + dt * k3t, state.omega + dt * k3w, t + dt, params);
+    ExtendedState {
+        theta: state.theta + dt / 6.0 * (k1t + 2.0 * k2t + 2.0 * k3t + k4t),
+        omega: state.omega + dt / 6.0 * (k1w + 2.0 * k2w + 2.0 * k3w + k4w),
+    }
+}
+
+/// 扩展精度的整周期积分，对应 solve_periods：dt = T / steps_per_period
+///
+/// 只返回每个驱动周期末的状态（共 n_periods + 1 个，含初值），舍入为 f64，避免长时间积分占用大量内存。
+/// 时间由步数乘 dt 得到而不是逐步累加，驱动相位不会漂移。
+pub fn stroboscopic_extended(
+    params: &PendulumParams,
+    initial_theta: f64,
+    initial_omega: f64,
+    n_periods: usize,
+    steps_per_period: usize,
+) -> Vec<State> {
+    let dt = twofloat::consts::TAU / TwoFloat::from(params.omega_d) / TwoFloat::from(steps_per_period as f64);
+    let mut state = ExtendedState {
+        theta: TwoFloat::from(initial_theta),
+        omega: TwoFloat::from(initial_omega),
+    };
+    let mut out = Vec::with_capacity(n_periods + 1);
+    out.push(state.to_state());
+    for n in 0..n_periods {
+        for k in 0..steps_per_period {
+            let t = TwoFloat::from((n * steps_per_period + k) as f64) * dt;
+            state = rk4_step_extended(&state, t, dt, params);
+        }
+        out.push(state.to_state());
+    }
+    out
+}
+
+// 变分方程 δ' = J δ（扩展精度），J = [[0, 1], [-(g/l) cos θ + F(t) dc/dθ, -q(t)]]
+fn tangent_rhs_extended(
+    state: &ExtendedState,
+    tangent: (TwoFloat, TwoFloat),
+    t: TwoFloat,
+    params: &PendulumParams,
+) -> (TwoFloat, TwoFloat) {
+    let g_over_l = TwoFloat::from(params.g) / TwoFloat::from(params.l);
+    let stiffness = match params.drive_type {
+        DriveType::Torque => -g_over_l * state.theta.cos(),
+        DriveType::Parametric => -(g_over_l + TwoFloat::from(params.drive(t.into()))) * state.theta.cos(),
+    };
+    (tangent.1, stiffness * tangent.0 - TwoFloat::from(params.q_at(t.into())) * tangent.1)
+}
+
+/// 扩展精度的最大 Lyapunov 指数，与 lyapunov::largest_exponent 的算法、参数与返回值相同
+///
+/// 状态与切向量都按双双精度积分（时间由步数乘 dt 得到），只有每次重归一化的 ln|δ| 在 f64 中累加。
+/// 分岔点附近 λ 接近 0，f64 舍入可能让轨迹落到另一个吸引子上、改变 λ 的符号，用它核对 f64 的结果。
+pub fn largest_exponent_extended(
+    params: &PendulumParams,
+    initial_state: State,
+    renormalization_interval: f64,
+) -> LyapunovEstimate {
+    let steps_per_renorm = ((renormalization_interval / params.dt).round() as usize).max(1);
+    let total_steps = (params.t_end / params.dt) as usize;
+    let dt = TwoFloat::from(params.dt);
+    let half = dt / 2.0;
+
+    let mut state = ExtendedState {
+        theta: TwoFloat::from(initial_state.theta),
+        omega: TwoFloat::from(initial_state.omega),
+    };
+    let mut tangent = (TwoFloat::from(1.0), TwoFloat::from(0.0));
+    let mut log_sum = 0.0;
+    let mut history = Vec::with_capacity(total_steps / steps_per_renorm);
+    let shift = |s: &ExtendedState, k: (TwoFloat, TwoFloat), c: TwoFloat| ExtendedState {
+        theta: s.theta + c * k.0,
+        omega: s.omega + c * k.1,
+    };
+    let add = |v: (TwoFloat, TwoFloat), k: (TwoFloat, TwoFloat), c: TwoFloat| (v.0 + c * k.0, v.1 + c * k.1);
+
+    for step in 1..=total_steps {
+        let t = TwoFloat::from((step - 1) as f64) * dt;
+        // 状态与切向量一起做一步 RK4（切向量的各级斜率使用对应级的状态）
+        let k1 = rhs_extended(state.theta, state.omega, t, params);
+        let l1 = tangent_rhs_extended(&state, tangent, t, params);
+        let s2 = shift(&state, k1, half);
+        let k2 = rhs_extended(s2.theta, s2.omega, t + half, params);
+        let l2 = tangent_rhs_extended(&s2, add(tangent, l1, half), t + half, params);
+        let s3 = shift(&state, k2, half);
+        let k3 = rhs_extended(s3.theta, s3.omega, t + half, params);
+        let l3 = tangent_rhs_extended(&s3, add(tangent, l2, half), t + half, params);
+        let s4 = shift(&state, k3, dt);
+        let k4 = rhs_extended(s4.theta, s4.omega, t + dt, params);
+        let l4 = tangent_rhs_extended(&s4, add(tangent, l3, dt), t + dt, params);
+        state = ExtendedState {
+            theta: state.theta + dt / 6.0 * (k1.0 + 2.0 * k2.0 + 2.0 * k3.0 + k4.0),
+            omega: state.omega + dt / 6.0 * (k1.1 + 2.0 * k2.1 + 2.0 * k3.1 + k4.1),
+        };
+        tangent = (
+            tangent.0 + dt / 6.0 * (l1.0 + 2.0 * l2.0 + 2.0 * l3.0 + l4.0),
+            tangent.1 + dt / 6.0 * (l1.1 + 2.0 * l2.1 + 2.0 * l3.1 + l4.1),
+        );
+
+        if step % steps_per_renorm == 0 {
+            let norm = (tangent.0 * tangent.0 + tangent.1 * tangent.1).sqrt();
+            log_sum += f64::from(norm).ln();
+            tangent = (tangent.0 / norm, tangent.1 / norm);
+            let elapsed = step as f64 * params.dt;
+            history.push((elapsed, log_sum / elapsed));
+        }
+    }
+
+    LyapunovEstimate {
+        exponent: history.last().map_or(0.0, |(_, l)| *l),
+        history,
+    }
+}
+
+/// 每个驱动周期末 f64 轨迹与扩展精度参考轨迹的相空间距离（θ 差按 2π 折叠）
+///
+/// 两者使用相同的步长，差异只来自 f64 舍入；距离增长到 O(1) 的时刻即 f64 轨迹失去逐点意义的时刻，
+/// 此后只有统计量（以及 shadowing 意义下的“某条真实轨迹”）仍然可信。
+pub fn precision_divergence(
+    params: &PendulumParams,
+    initial_theta: f64,
+    initial_omega: f64,
+    n_periods: usize,
+    steps_per_period: usize,
+) -> Result<Vec<(usize, f64)>, ChaosError> {
+    let traj = solve_periods(params, initial_theta, initial_omega, n_periods, steps_per_period)?;
+    let reference = stroboscopic_extended(params, initial_theta, initial_omega, n_periods, steps_per_period);
+    Ok(reference
+        .iter()
+        .enumerate()
+        .filter_map(|(n, r)| {
+            let (_, s) = traj.get(n * steps_per_period)?;
+            let d_theta = wrap_angle(s.theta - r.theta);
+            let d_omega = s.omega - r.omega;
+            Some((n, (d_theta * d_theta + d_omega * d_omega).sqrt()))
+        })
+        .collect())
+}
+
+/// 把精度差异曲线写成 CSV（两列：period,distance）
+pub fn write_precision_divergence_csv(path: &str, rows: &[(usize, f64)]) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(f, "period,distance")?;
+    for (n, d) in rows {
+        writeln!(f, "{},{:.6e}", n, d)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extended_reference_tracks_f64_then_diverges_in_chaos() {
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.q = 0.5;
+        params.f_d = 1.2;
+        params.omega_d = 2.0 / 3.0;
+
+        let rows = precision_divergence(&params, 0.2, 0.0, 150, 100).unwrap();
+        assert_eq!(rows.len(), 151);
+        assert_eq!(rows[0].1, 0.0);
+        // 前几个周期只差舍入误差
+        assert!(rows[5].1 < 1e-10);
+        // 混沌区舍入误差被指数放大
+        assert!(rows.iter().any(|(_, d)| *d > 1e-2));
+
+        // 周期区两种精度的指数一致；混沌区逐点轨迹不同，但指数作为统计量仍一致
+        let period = 2.0 * std::f64::consts::PI / params.omega_d;
+        params.dt = period / 100.0;
+        params.t_end = 200.0 * period;
+        let start = State { theta: 0.2, omega: 0.0 };
+        let f64_chaotic = crate::lyapunov::largest_exponent(&params, start, period);
+        let extended_chaotic = largest_exponent_extended(&params, start, period);
+        assert_eq!(extended_chaotic.history.len(), 200);
+        assert!(extended_chaotic.exponent > 0.05);
+        assert!((extended_chaotic.exponent - f64_chaotic.exponent).abs() < 0.05);
+        params.f_d = 0.5;
+        let f64_regular = crate::lyapunov::largest_exponent(&params, start, period);
+        let extended_regular = largest_exponent_extended(&params, start, period);
+        assert!(extended_regular.exponent < -0.05);
+        assert!((extended_regular.exponent - f64_regular.exponent).abs() < 1e-6);
+    }
+}
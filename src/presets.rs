@@ -0,0 +1,172 @@
+//presets.rs
+use std::fmt;
+use crate::model::PendulumParams;
+
+// 教科书上的经典参数组（Giordano & Nakanishi《Computational Physics》第 3 章）：g = l = 9.8（g/l = 1），
+// 阻尼 q = 1/2，驱动角频率 Ω = 2/3，初值 (θ, ω) = (0.2, 0)。f_d 依次取
+//   0.5   周期 1：过渡期后以驱动频率做小幅振荡；
+//   1.2   混沌：θ 不断翻越顶点，庞加莱截面为奇怪吸引子；
+//   1.44  周期 2（第一次倍周期分岔约在 f_d ≈ 1.42 之后）；
+//   1.465 周期 4。
+// 另有两个无驱动的对照：小角度线性区（无阻尼，θ0 = 0.05，近似简谐振动 θ ≈ θ0 cos(√(g/l) t)），
+// 以及过阻尼（q = 3 > 2√(g/l)，从静止释放后单调回到 θ = 0，不越过平衡位置）。
+// 每个驱动周期取 400 步，与命令行不给 --config 时的默认设置相同；无驱动的两组仍保留 Ω = 2/3，
+// 以便频闪采样等需要驱动周期的分析照常使用。
+
+/// 预设参数组的预期行为
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpectedBehavior {
+    /// 过渡期后锁定在驱动周期的 period 倍上
+    Periodic { period: usize },
+    /// 混沌吸引子（庞加莱截面上没有周期）
+    Chaotic,
+    /// 小角度线性区：以固有角频率 √(g/l) 做简谐振动
+    Harmonic,
+    /// 过阻尼：单调衰减到 θ = 0，不越过平衡位置
+    Overdamped,
+}
+
+impl fmt::Display for ExpectedBehavior {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExpectedBehavior::Periodic { period } => write!(f, "period {}", period),
+            ExpectedBehavior::Chaotic => write!(f, "chaotic"),
+            ExpectedBehavior::Harmonic => write!(f, "harmonic"),
+            ExpectedBehavior::Overdamped => write!(f, "overdamped decay"),
+        }
+    }
+}
+
+/// 一个命名的参数组，附带建议的初值与预期行为
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Preset {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub q: f64,
+    pub f_d: f64,
+    pub theta0: f64,
+    pub omega0: f64,
+    pub expected: ExpectedBehavior,
+}
+
+impl Preset {
+    /// g = l = 9.8、Ω = 2/3、每个驱动周期 400 步的参数
+    pub fn params(&self) -> PendulumParams {
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.q = self.q;
+        params.f_d = self.f_d;
+        params.omega_d = 2.0 / 3.0;
+        params.set_steps_per_period(400);
+        params
+    }
+}
+
+/// 全部预设，按名称查找用 preset
+pub const PRESETS: &[Preset] = &[
+    Preset {
+        name: "periodic",
+        description: "Giordano-Nakanishi f_d = 0.5: small periodic oscillation locked to the drive",
+        q: 0.5,
+        f_d: 0.5,
+        theta0: 0.2,
+        omega0: 0.0,
+        expected: ExpectedBehavior::Periodic { period: 1 },
+    },
+    Preset {
+        name: "chaotic",
+        description: "Giordano-Nakanishi f_d = 1.2: strange attractor, the pendulum flips over the top irregularly",
+        q: 0.5,
+        f_d: 1.2,
+        theta0: 0.2,
+        omega0: 0.0,
+        expected: ExpectedBehavior::Chaotic,
+    },
+    Preset {
+        name: "period-2",
+        description: "Giordano-Nakanishi f_d = 1.44: after the first period doubling",
+        q: 0.5,
+        f_d: 1.44,
+        theta0: 0.2,
+        omega0: 0.0,
+        expected: ExpectedBehavior::Periodic { period: 2 },
+    },
+    Preset {
+        name: "period-4",
+        description: "Giordano-Nakanishi f_d = 1.465: after the second period doubling",
+        q: 0.5,
+        f_d: 1.465,
+        theta0: 0.2,
+        omega0: 0.0,
+        expected: ExpectedBehavior::Periodic { period: 4 },
+    },
+    Preset {
+        name: "small-angle",
+        description: "undriven, undamped, theta0 = 0.05: linear regime, theta ~ theta0 cos(t)",
+        q: 0.0,
+        f_d: 0.0,
+        theta0: 0.05,
+        omega0: 0.0,
+        expected: ExpectedBehavior::Harmonic,
+    },
+    Preset {
+        name: "overdamped",
+        description: "undriven, q = 3 > 2 sqrt(g/l): released from rest, creeps back to theta = 0 without overshoot",
+        q: 3.0,
+        f_d: 0.0,
+        theta0: 1.0,
+        omega0: 0.0,
+        expected: ExpectedBehavior::Overdamped,
+    },
+];
+
+/// 按名称查找预设
+pub fn preset(name: &str) -> Option<&'static Preset> {
+    PRESETS.iter().find(|p| p.name == name)
+}
+
+/// 全部预设的名称
+pub fn preset_names() -> Vec<&'static str> {
+    PRESETS.iter().map(|p| p.name).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::attractor::detect_period;
+    use crate::solve_equation::{solve, State};
+    use crate::stroboscopic::StroboscopicMap;
+
+    #[test]
+    fn test_presets_show_expected_behavior() {
+        for p in PRESETS {
+            let mut params = p.params();
+            match p.expected {
+                ExpectedBehavior::Periodic { .. } | ExpectedBehavior::Chaotic => {
+                    let start = State { theta: p.theta0, omega: p.omega0 };
+                    let section = StroboscopicMap::new(&params).section(start, 300, 64);
+                    let period = detect_period(&section, 16, 1e-5);
+                    match p.expected {
+                        ExpectedBehavior::Periodic { period: expected } => assert_eq!(period, Some(expected), "{}", p.name),
+                        _ => assert_eq!(period, None, "{}", p.name),
+                    }
+                }
+                ExpectedBehavior::Harmonic => {
+                    params.t_end = 20.0;
+                    let traj = solve(&params, p.theta0, p.omega0).unwrap();
+                    let worst = traj.iter().map(|(t, s)| (s.theta - p.theta0 * t.cos()).abs()).fold(0.0, f64::max);
+                    assert!(worst < 0.01 * p.theta0, "{}: {}", p.name, worst);
+                }
+                ExpectedBehavior::Overdamped => {
+                    params.t_end = 20.0;
+                    let traj = solve(&params, p.theta0, p.omega0).unwrap();
+                    assert!(traj.windows(2).all(|w| w[1].1.theta <= w[0].1.theta && w[1].1.theta > 0.0), "{}", p.name);
+                    assert!(traj[traj.len() - 1].1.theta < 1e-3 * p.theta0, "{}", p.name);
+                }
+            }
+        }
+        assert_eq!(PendulumParams::preset("chaotic").map(|p| p.f_d), Some(1.2));
+        assert!(PendulumParams::preset("nonexistent").is_none());
+        assert_eq!(preset_names().len(), PRESETS.len());
+    }
+}
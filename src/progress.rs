@@ -0,0 +1,104 @@
+//progress.rs
+use std::ops::ControlFlow;
+
+// 长时间积分与参数扫描的进度回调：回调形如 FnMut(ProgressInfo) -> ControlFlow<()>，
+// 返回 ControlFlow::Break(()) 时例程提前结束并返回已经得到的部分结果（不是错误）。
+
+/// 进度回调收到的信息：已完成 done 个单位（积分步、扫描参数点或驱动周期），共 total 个
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProgressInfo {
+    pub done: usize,
+    pub total: usize,
+}
+
+impl ProgressInfo {
+    /// 完成比例，total = 0 时为 1
+    pub fn fraction(&self) -> f64 {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.done as f64 / self.total as f64
+        }
+    }
+}
+
+/// 迭代器适配器：每产出 every 项调用一次回调，回调返回 Break 后不再产出新的项
+pub struct WithProgress<I, F> {
+    inner: I,
+    every: usize,
+    done: usize,
+    total: usize,
+    callback: F,
+    stopped: bool,
+}
+
+/// 给任意迭代器加上进度回调；total 取自 size_hint 的下界
+pub fn with_progress<I, F>(inner: I, every: usize, callback: F) -> WithProgress<I, F>
+where
+    I: Iterator,
+    F: FnMut(ProgressInfo) -> ControlFlow<()>,
+{
+    let total = inner.size_hint().0;
+    WithProgress {
+        inner,
+        every: every.max(1),
+        done: 0,
+        total,
+        callback,
+        stopped: false,
+    }
+}
+
+impl<I, F> Iterator for WithProgress<I, F>
+where
+    I: Iterator,
+    F: FnMut(ProgressInfo) -> ControlFlow<()>,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.stopped {
+            return None;
+        }
+        let item = self.inner.next()?;
+        self.done += 1;
+        if self.done.is_multiple_of(self.every) {
+            let info = ProgressInfo {
+                done: self.done,
+                total: self.total,
+            };
+            self.stopped = (self.callback)(info).is_break();
+        }
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.stopped {
+            (0, Some(0))
+        } else {
+            (0, self.inner.size_hint().1)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_progress_reports_and_stops() {
+        let mut seen = Vec::new();
+        let taken: Vec<usize> = with_progress(0..100, 10, |info| {
+            seen.push(info.done);
+            if info.done >= 30 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        })
+        .collect();
+        assert_eq!(taken.len(), 30);
+        assert_eq!(seen, vec![10, 20, 30]);
+        assert_eq!(ProgressInfo { done: 25, total: 100 }.fraction(), 0.25);
+    }
+}
@@ -0,0 +1,179 @@
+//progressive.rs
+use crate::attractor::detect_period;
+use crate::basin::{same_attractor, BasinMap};
+use crate::bifurcation::{sweep_values, ScanPoint, SweepParameter};
+use crate::model::PendulumParams;
+use crate::solve_equation::{steps_per_period, State};
+use crate::stroboscopic::StroboscopicMap;
+
+// 由粗到细的计算顺序：先算下标为 stride 倍数的点，再把 stride 减半，直到 1。
+// 每一轮结束后都有一份完整但分辨率较低的结果可供预览，最后一轮与逐点顺序计算的结果相同。
+
+/// 把 0..n 按由粗到细的层次排列，返回 (stride, 本层新增下标)，stride 依次为 2^k, …, 2, 1
+pub fn progressive_levels(n: usize) -> Vec<(usize, Vec<usize>)> {
+    if n == 0 {
+        return Vec::new();
+    }
+    let mut stride = (n - 1).max(1).next_power_of_two();
+    let mut levels = Vec::new();
+    let mut first = true;
+    loop {
+        let indices: Vec<usize> = (0..n)
+            .step_by(stride)
+            .filter(|i| first || i % (2 * stride) != 0)
+            .collect();
+        levels.push((stride, indices));
+        first = false;
+        if stride == 1 {
+            break;
+        }
+        stride /= 2;
+    }
+    levels
+}
+
+/// 由粗到细的参数扫描，每完成一层调用一次 on_pass(stride, 目前已算出的点（按参数排序）)
+///
+/// 交错顺序下相邻计算的参数值相距很远，因此每个参数值都从同一初值独立积分，不向后传递末状态。
+/// 返回最终的完整扫描结果。
+#[allow(clippy::too_many_arguments)]
+pub fn progressive_scan<F: FnMut(usize, &[ScanPoint])>(
+    params: &PendulumParams,
+    parameter: SweepParameter,
+    range: (f64, f64),
+    steps: usize,
+    initial_theta: f64,
+    initial_omega: f64,
+    transient_periods: usize,
+    sample_periods: usize,
+    max_period: usize,
+    tolerance: f64,
+    mut on_pass: F,
+) -> Vec<ScanPoint> {
+    let spp = steps_per_period(params);
+    let values = sweep_values(range, steps);
+    let mut slots: Vec<Option<ScanPoint>> = vec![None; values.len()];
+
+    for (stride, indices) in progressive_levels(values.len()) {
+        for i in indices {
+            let mut p = params.clone();
+            parameter.apply(&mut p, values[i]);
+            let start = State {
+                theta: initial_theta,
+                omega: initial_omega,
+            };
+            let samples = StroboscopicMap::with_steps(&p, spp).section(start, transient_periods, sample_periods);
+            slots[i] = Some(ScanPoint {
+                parameter: values[i],
+                period: detect_period(&samples, max_period, tolerance),
+                samples,
+            });
+        }
+        let done: Vec<ScanPoint> = slots.iter().flatten().cloned().collect();
+        on_pass(stride, &done);
+    }
+    slots.into_iter().flatten().collect()
+}
+
+/// 由粗到细计算吸引域，每完成一层调用一次 on_pass(stride, 预览图)
+///
+/// 预览图中尚未计算的格点沿用其所在 stride × stride 方块左下角格点的标签。
+/// 吸引子编号按发现顺序分配，可能与 basin_labels 不同，但划分相同。
+pub fn progressive_basin<F: FnMut(usize, &BasinMap)>(
+    params: &PendulumParams,
+    transient_periods: usize,
+    sample_periods: usize,
+    tolerance: f64,
+    mut on_pass: F,
+) -> BasinMap {
+    let strobe = StroboscopicMap::new(params);
+    let thetas = params.theta_grid();
+    let omegas = params.omega_grid();
+    let (n_theta, n_omega) = (thetas.len(), omegas.len());
+    let mut computed: Vec<Option<usize>> = vec![None; n_theta * n_omega];
+    let mut map = BasinMap {
+        thetas,
+        omegas,
+        labels: vec![0; n_theta * n_omega],
+        attractors: Vec::new(),
+        representatives: Vec::new(),
+    };
+
+    for (stride, _) in progressive_levels(n_theta.max(n_omega)) {
+        for i in (0..n_theta).step_by(stride) {
+            for j in (0..n_omega).step_by(stride) {
+                if computed[i * n_omega + j].is_some() {
+                    continue;
+                }
+                let (theta, omega) = (map.thetas[i], map.omegas[j]);
+                let section = strobe.section(State { theta, omega }, transient_periods, sample_periods);
+                let label = match map.attractors.iter().position(|a| same_attractor(a, &section, tolerance)) {
+                    Some(k) => k,
+                    None => {
+                        map.attractors.push(section);
+                        map.representatives.push((theta, omega));
+                        map.attractors.len() - 1
+                    }
+                };
+                computed[i * n_omega + j] = Some(label);
+            }
+        }
+        for i in 0..n_theta {
+            for j in 0..n_omega {
+                let anchor = (i - i % stride) * n_omega + (j - j % stride);
+                map.labels[i * n_omega + j] = computed[i * n_omega + j].or(computed[anchor]).unwrap_or(0);
+            }
+        }
+        on_pass(stride, &map);
+    }
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progressive_levels_cover_each_index_once() {
+        let levels = progressive_levels(11);
+        let strides: Vec<usize> = levels.iter().map(|(s, _)| *s).collect();
+        assert_eq!(strides, vec![16, 8, 4, 2, 1]);
+        assert_eq!(levels[0].1, vec![0]);
+        assert_eq!(levels[1].1, vec![8]);
+        assert_eq!(levels[2].1, vec![4]);
+
+        let mut all: Vec<usize> = levels.into_iter().flat_map(|(_, idx)| idx).collect();
+        all.sort();
+        assert_eq!(all, (0..11).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_progressive_basin_matches_direct_computation() {
+        let mut params = PendulumParams::new();
+        params.q = 0.5;
+        params.f_d = 0.5;
+        params.omega_d = 2.0 / 3.0;
+        params.dt = 2.0 * std::f64::consts::PI / params.omega_d / 50.0;
+        params.theta_start = -2.0;
+        params.theta_end = 2.0;
+        params.d_theta = 1.0;
+        params.omega_start = -2.0;
+        params.omega_end = 2.0;
+        params.d_omega = 2.0;
+
+        let mut passes = 0;
+        let progressive = progressive_basin(&params, 20, 4, 1e-3, |_, _| passes += 1);
+        let direct = crate::basin::basin_labels(&params, 20, 4, 1e-3);
+        assert_eq!(passes, 3);
+        assert_eq!(progressive.attractors.len(), direct.attractors.len());
+        // 两种顺序的编号可能不同，但同标签的格点集合应一致
+        for a in 0..progressive.labels.len() {
+            for b in 0..progressive.labels.len() {
+                assert_eq!(
+                    progressive.labels[a] == progressive.labels[b],
+                    direct.labels[a] == direct.labels[b]
+                );
+            }
+        }
+    }
+}
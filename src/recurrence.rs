@@ -0,0 +1,221 @@
+//recurrence.rs
+use std::fs::File;
+use std::io::Write;
+use rayon::prelude::*;
+use crate::basin::write_rgb_png;
+use crate::solve_equation::State;
+
+// 递归图：R(i, j) = 1 当且仅当 |x_i - x_j| < ε。对角线段表示轨道在一段时间内平行地重访（确定性），
+// 竖直线段表示状态停滞（层流）。点可以是 (θ, ω)、庞加莱点或延迟嵌入向量，距离为欧氏距离；
+// θ 跨 ±π 折叠会造成假的远距离，周期坐标宜先换成 (sin θ, cos θ)。
+
+/// 递归矩阵（按位存储，对称，主对角线恒为 1）
+#[derive(Debug, Clone)]
+pub struct RecurrencePlot {
+    pub size: usize,
+    pub epsilon: f64,
+    // 第 i 行占 words_per_row 个 u64
+    words_per_row: usize,
+    bits: Vec<u64>,
+}
+
+/// 递归量化分析（RQA）的指标，均不计主对角线
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RqaMeasures {
+    /// 递归率：递归点占全部点对的比例
+    pub recurrence_rate: f64,
+    /// 确定性：长度不小于 l_min 的对角线段上的递归点占比
+    pub determinism: f64,
+    /// 层流性：长度不小于 l_min 的竖直线段上的递归点占比
+    pub laminarity: f64,
+    /// 长度不小于 l_min 的对角线段的平均长度与最大长度
+    pub mean_diagonal: f64,
+    pub max_diagonal: usize,
+    /// 捕获时间：长度不小于 l_min 的竖直线段的平均长度
+    pub trapping_time: f64,
+}
+
+/// 把轨迹转成 (sin θ, cos θ, ω) 向量，避免 θ 折叠造成的假距离
+pub fn state_vectors(traj: &[(f64, State)]) -> Vec<[f64; 3]> {
+    traj.iter().map(|(_, s)| [s.theta.sin(), s.theta.cos(), s.omega]).collect()
+}
+
+impl RecurrencePlot {
+    /// 计算 points 两两之间的递归关系（按行并行）
+    pub fn new<P: AsRef<[f64]> + Sync>(points: &[P], epsilon: f64) -> Self {
+        let size = points.len();
+        let words_per_row = size.div_ceil(64);
+        let eps2 = epsilon * epsilon;
+        let bits = points
+            .par_iter()
+            .flat_map_iter(|a| {
+                let mut row = vec![0u64; words_per_row];
+                for (j, b) in points.iter().enumerate() {
+                    let d2: f64 = a.as_ref().iter().zip(b.as_ref()).map(|(x, y)| (x - y) * (x - y)).sum();
+                    if d2 < eps2 {
+                        row[j / 64] |= 1 << (j % 64);
+                    }
+                }
+                row
+            })
+            .collect();
+        RecurrencePlot {
+            size,
+            epsilon,
+            words_per_row,
+            bits,
+        }
+    }
+
+    pub fn is_recurrent(&self, i: usize, j: usize) -> bool {
+        self.bits[i * self.words_per_row + j / 64] >> (j % 64) & 1 == 1
+    }
+
+    /// 所有递归点 (i, j)，i ≠ j
+    pub fn recurrent_pairs(&self) -> Vec<(usize, usize)> {
+        (0..self.size)
+            .flat_map(|i| (0..self.size).map(move |j| (i, j)))
+            .filter(|&(i, j)| i != j && self.is_recurrent(i, j))
+            .collect()
+    }
+
+    /// RQA 指标，l_min 为计入确定性/层流性的最短线段（通常取 2）
+    pub fn rqa(&self, l_min: usize) -> RqaMeasures {
+        let n = self.size;
+        let l_min = l_min.max(1);
+        // 上三角的对角线段（下三角与之对称，比例不变）
+        let mut diagonal = Vec::new();
+        for k in 1..n {
+            let mut run = 0;
+            for i in 0..n - k {
+                if self.is_recurrent(i, i + k) {
+                    run += 1;
+                } else if run > 0 {
+                    diagonal.push(run);
+                    run = 0;
+                }
+            }
+            if run > 0 {
+                diagonal.push(run);
+            }
+        }
+        // 各列的竖直线段，跳过主对角线上的点
+        let mut vertical = Vec::new();
+        for j in 0..n {
+            let mut run = 0;
+            for i in 0..n {
+                if i != j && self.is_recurrent(i, j) {
+                    run += 1;
+                } else if run > 0 {
+                    vertical.push(run);
+                    run = 0;
+                }
+            }
+            if run > 0 {
+                vertical.push(run);
+            }
+        }
+
+        let ratio = |a: usize, b: usize| if b == 0 { 0.0 } else { a as f64 / b as f64 };
+        let recurrent: usize = diagonal.iter().sum();
+        let long_diag: Vec<usize> = diagonal.iter().copied().filter(|l| *l >= l_min).collect();
+        let vertical_points: usize = vertical.iter().sum();
+        let long_vert: Vec<usize> = vertical.iter().copied().filter(|v| *v >= l_min).collect();
+        let pairs = n * n.saturating_sub(1) / 2;
+        RqaMeasures {
+            recurrence_rate: ratio(recurrent, pairs),
+            determinism: ratio(long_diag.iter().sum(), recurrent),
+            laminarity: ratio(long_vert.iter().sum(), vertical_points),
+            mean_diagonal: ratio(long_diag.iter().sum(), long_diag.len()),
+            max_diagonal: long_diag.iter().copied().max().unwrap_or(0),
+            trapping_time: ratio(long_vert.iter().sum(), long_vert.len()),
+        }
+    }
+}
+
+/// 滑动窗口 RQA：每个窗口含 window 个点，起点间隔 step，返回 (起点下标, 指标)
+///
+/// 指标随窗口的突变（例如 determinism 下降）标志着动力学状态的转变，如阵发、危机或参数扫描中的分岔。
+pub fn windowed_rqa<P: AsRef<[f64]> + Sync>(
+    points: &[P],
+    epsilon: f64,
+    window: usize,
+    step: usize,
+    l_min: usize,
+) -> Vec<(usize, RqaMeasures)> {
+    if window == 0 || points.len() < window {
+        return Vec::new();
+    }
+    (0..=points.len() - window)
+        .step_by(step.max(1))
+        .map(|start| (start, RecurrencePlot::new(&points[start..start + window], epsilon).rqa(l_min)))
+        .collect()
+}
+
+/// 把递归图写成 PNG：横轴 i、纵轴 j（向上增大），递归点为黑色
+pub fn write_recurrence_png(path: &str, plot: &RecurrencePlot) -> std::io::Result<()> {
+    let n = plot.size;
+    let mut data = Vec::with_capacity(n * n * 3);
+    for j in (0..n).rev() {
+        for i in 0..n {
+            let v = if plot.is_recurrent(i, j) { 0 } else { 255 };
+            data.extend_from_slice(&[v, v, v]);
+        }
+    }
+    write_rgb_png(path, n, n, &data)
+}
+
+/// 把递归点写成稀疏列表 CSV（两列：i,j），只写 i < j 的一半
+pub fn write_recurrence_csv(path: &str, plot: &RecurrencePlot) -> std::io::Result<()> {
+    let mut f = std::io::BufWriter::new(File::create(path)?);
+    writeln!(f, "i,j")?;
+    for (i, j) in plot.recurrent_pairs() {
+        if i < j {
+            writeln!(f, "{},{}", i, j)?;
+        }
+    }
+    Ok(())
+}
+
+/// 把滑动窗口 RQA 写成 CSV（列：start,recurrence_rate,determinism,laminarity,mean_diagonal,max_diagonal,trapping_time）
+pub fn write_windowed_rqa_csv(path: &str, rows: &[(usize, RqaMeasures)]) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(f, "start,recurrence_rate,determinism,laminarity,mean_diagonal,max_diagonal,trapping_time")?;
+    for (start, m) in rows {
+        writeln!(
+            f,
+            "{},{:.12},{:.12},{:.12},{:.12},{},{:.12}",
+            start, m.recurrence_rate, m.determinism, m.laminarity, m.mean_diagonal, m.max_diagonal, m.trapping_time
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::{seeded_rng, RandomSource};
+
+    #[test]
+    fn test_rqa_of_periodic_and_random_series() {
+        // 周期 10 的序列：递归点全部排成对角线，确定性为 1
+        let periodic: Vec<[f64; 1]> = (0..200).map(|i| [(i % 10) as f64]).collect();
+        let plot = RecurrencePlot::new(&periodic, 0.5);
+        assert!(plot.is_recurrent(3, 13) && !plot.is_recurrent(3, 14));
+        let m = plot.rqa(2);
+        assert_eq!(m.recurrence_rate, 1900.0 / 19900.0);
+        assert_eq!(m.determinism, 1.0);
+        assert_eq!(m.laminarity, 0.0);
+        assert_eq!(m.max_diagonal, 190);
+
+        // 独立随机点：对角线段很少，确定性远低于 1
+        let mut rng = seeded_rng(5);
+        let noise: Vec<[f64; 1]> = (0..400).map(|_| [rng.uniform(0.0, 1.0)]).collect();
+        let m = RecurrencePlot::new(&noise, 0.05).rqa(2);
+        assert!(m.determinism < 0.3, "{}", m.determinism);
+
+        let rows = windowed_rqa(&periodic, 0.5, 50, 25, 2);
+        assert_eq!(rows.len(), 7);
+        assert!(rows.iter().all(|(_, m)| m.determinism == 1.0));
+    }
+}
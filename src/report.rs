@@ -0,0 +1,337 @@
+//report.rs
+use std::f64::consts::PI;
+use std::fmt;
+use serde::Serialize;
+use crate::analysis::correlation_dimension;
+use crate::attractor::{classify_attractor, summarize};
+use crate::basin::AttractorClass;
+use crate::error::ChaosError;
+use crate::lyapunov::{largest_exponent, spectrum};
+use crate::metadata::RunMetadata;
+use crate::metric_entropy::{metric_entropy, pesin_check, MetricEntropy, MetricEntropyOptions, PesinCheck};
+use crate::model::PendulumParams;
+use crate::random::seeded_rng;
+use crate::solve_equation::{steps_per_period, State};
+use crate::stroboscopic::{orbit_rotation_number, section_points, StroboscopicMap};
+use crate::torus::{detect_torus, TorusOptions, TorusReport};
+use crate::zero_one::zero_one_test;
+
+// 一组参数的诊断汇总：从同一条轨道（丢掉过渡期后）计算吸引子类型、旋转数、Lyapunov 指数与谱、
+// 可预测时间、0–1 检验、环面检测、关联维数与测度熵（及其与正 Lyapunov 指数之和的 Pesin 核对），既可以打印成文字，也可以序列化为 JSON。
+// 可预测时间：初始误差 ε0 按 e^{λt} 放大，到达容差 εmax 需要 T = ln(εmax/ε0) / λ；
+// λ 的倒数 1/λ 称为 Lyapunov 时间。λ <= 0 时误差不指数增长，两者都记为 None（无穷）。
+// 这是平均意义下的估计，具体初值附近的有限时间增长率可能偏离 λ。
+
+/// 可预测时间
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Predictability {
+    pub exponent: f64,
+    /// 1/λ
+    pub lyapunov_time: Option<f64>,
+    pub initial_error: f64,
+    pub tolerance: f64,
+    /// ln(εmax/ε0) / λ
+    pub horizon: Option<f64>,
+    /// horizon 折合的驱动周期数
+    pub horizon_periods: Option<f64>,
+}
+
+/// 由最大 Lyapunov 指数计算可预测时间；period 为驱动周期，用来折算周期数
+pub fn predictability(exponent: f64, initial_error: f64, tolerance: f64, period: f64) -> Predictability {
+    let growing = exponent > 0.0 && tolerance > initial_error && initial_error > 0.0;
+    let horizon = growing.then(|| (tolerance / initial_error).ln() / exponent);
+    Predictability {
+        exponent,
+        lyapunov_time: (exponent > 0.0).then(|| 1.0 / exponent),
+        initial_error,
+        tolerance,
+        horizon,
+        horizon_periods: horizon.map(|h| h / period),
+    }
+}
+
+/// 汇总报告的设置
+#[derive(Debug, Clone, Copy)]
+pub struct ReportOptions {
+    pub transient_periods: usize,
+    pub sample_periods: usize,
+    /// 可预测时间的初始误差 ε0 与容差 εmax
+    pub initial_error: f64,
+    pub tolerance: f64,
+    /// 判定周期轨道的距离容差
+    pub period_tolerance: f64,
+    /// 0–1 检验的随机频率个数与种子
+    pub zero_one_frequencies: usize,
+    pub seed: u64,
+    /// 测度熵的直方图划分与块长
+    pub entropy: MetricEntropyOptions,
+}
+
+impl Default for ReportOptions {
+    fn default() -> Self {
+        ReportOptions {
+            transient_periods: 100,
+            sample_periods: 2000,
+            initial_error: 1e-6,
+            tolerance: 0.1,
+            period_tolerance: 1e-6,
+            zero_one_frequencies: 20,
+            seed: 0,
+            entropy: MetricEntropyOptions::default(),
+        }
+    }
+}
+
+/// 一组参数的诊断汇总
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticsReport {
+    pub metadata: RunMetadata,
+    /// "periodic"、"quasi_periodic"、"chaotic" 或 "undetermined"
+    pub attractor: String,
+    pub period: Option<usize>,
+    /// 每个驱动周期转过的圈数
+    pub rotation_number: f64,
+    /// 庞加莱点的质心 (θ, ω) 与到质心距离的均方根
+    pub section_centroid: (f64, f64),
+    pub section_rms_extent: f64,
+    pub largest_exponent: f64,
+    /// 扩展相空间中的完整 Lyapunov 谱（从大到小）
+    pub lyapunov_spectrum: [f64; 3],
+    pub kaplan_yorke_dimension: f64,
+    pub predictability: Predictability,
+    /// 0–1 检验的 K（对庞加莱点的 ω 序列）
+    pub zero_one_k: Option<f64>,
+    /// 关联维数，只对准周期与混沌吸引子计算
+    pub correlation_dimension: Option<f64>,
+    pub torus: Option<TorusReport>,
+    /// 截面直方图划分下的测度熵（nats / 驱动周期），周期吸引子不计算
+    pub metric_entropy: Option<MetricEntropy>,
+    /// 测度熵外推值与 T Σ λ_i⁺ 的比较
+    pub pesin: Option<PesinCheck>,
+}
+
+// 判不出类型（点数不足）时记为 undetermined
+fn kind_name(kind: Option<AttractorClass>) -> (&'static str, Option<usize>) {
+    match kind {
+        Some(AttractorClass::Periodic(p)) => ("periodic", Some(p)),
+        Some(other) => (other.name(), None),
+        None => ("undetermined", None),
+    }
+}
+
+/// 从 (theta0, omega0) 出发计算诊断汇总；要求 0 < initial_error < tolerance
+///
+/// 轨道按整周期积分（每周期 steps_per_period(params) 步），丢掉 transient_periods 个周期后
+/// 在 sample_periods 个周期上统计；Lyapunov 指数与谱每个驱动周期重归一化一次。
+/// 关联维数在 [0.02, 0.2] 倍 RMS 尺度上拟合，只作粗略参考。
+pub fn diagnostics_report(
+    params: &PendulumParams,
+    theta0: f64,
+    omega0: f64,
+    options: &ReportOptions,
+) -> Result<DiagnosticsReport, ChaosError> {
+    params.validate_periodic()?;
+    if !(options.initial_error > 0.0 && options.tolerance > options.initial_error) {
+        return Err(ChaosError::InvalidParams(
+            "predictability needs 0 < initial_error < tolerance".to_string(),
+        ));
+    }
+    let (transient, samples) = (options.transient_periods, options.sample_periods);
+    if samples < 16 {
+        return Err(ChaosError::InsufficientTrajectory {
+            needed: 16.0,
+            available: samples as f64,
+        });
+    }
+    let spp = steps_per_period(params);
+    let period = 2.0 * PI / params.omega_d;
+    let orbit = StroboscopicMap::with_steps(params, spp).orbit(State { theta: theta0, omega: omega0 }, transient, samples);
+    let section = section_points(&orbit);
+    let rotation = orbit_rotation_number(&orbit);
+    let kind = classify_attractor(&section, options.period_tolerance);
+    let summary = summarize(&section).ok_or(ChaosError::InsufficientTrajectory {
+        needed: 1.0,
+        available: 0.0,
+    })?;
+
+    let mut p = params.clone();
+    p.dt = period / spp as f64;
+    p.t_end = (samples * spp) as f64 * p.dt + 0.5 * p.dt;
+    let start = orbit[0];
+    let lambda = largest_exponent(&p, start, period).exponent;
+    let full = spectrum(&p, start, period);
+
+    let omegas: Vec<f64> = section.iter().map(|&(_, omega)| omega).collect();
+    let zero_one = zero_one_test(&omegas, options.zero_one_frequencies, &mut seeded_rng(options.seed)).map(|z| z.k);
+    let correlation = match kind {
+        Some(AttractorClass::QuasiPeriodic | AttractorClass::Chaotic) if summary.rms_extent > 0.0 => {
+            correlation_dimension(&section, (0.02 * summary.rms_extent, 0.2 * summary.rms_extent)).map(|c| c.dimension)
+        }
+        _ => None,
+    };
+
+    // 周期轨道的截面点只差舍入误差与残余的过渡振荡，按样本范围自适应的直方图会把它们拆进不同格子
+    let entropy = match kind {
+        Some(AttractorClass::Periodic(_)) => None,
+        _ => metric_entropy(&section, &options.entropy),
+    };
+    let pesin = entropy.as_ref().map(|e| pesin_check(e.extrapolated, &full.exponents, period));
+
+    let (attractor, period_count) = kind_name(kind);
+    let metadata = RunMetadata::new(params, theta0, omega0)
+        .with("transient_periods", transient)
+        .with("sample_periods", samples)
+        .with("initial_error", options.initial_error)
+        .with("tolerance", options.tolerance);
+    Ok(DiagnosticsReport {
+        metadata,
+        attractor: attractor.to_string(),
+        period: period_count,
+        rotation_number: rotation,
+        section_centroid: (summary.theta_centroid, summary.omega_centroid),
+        section_rms_extent: summary.rms_extent,
+        largest_exponent: lambda,
+        lyapunov_spectrum: full.exponents,
+        kaplan_yorke_dimension: full.kaplan_yorke_dimension,
+        predictability: predictability(lambda, options.initial_error, options.tolerance, period),
+        zero_one_k: zero_one,
+        correlation_dimension: correlation,
+        torus: detect_torus(&section, &TorusOptions::default()),
+        metric_entropy: entropy,
+        pesin,
+    })
+}
+
+impl DiagnosticsReport {
+    /// 写成缩进的 JSON
+    pub fn write_json(&self, path: &str) -> std::io::Result<()> {
+        let f = std::io::BufWriter::new(std::fs::File::create(path)?);
+        serde_json::to_writer_pretty(f, self).map_err(std::io::Error::other)
+    }
+}
+
+fn or_infinite(value: Option<f64>, unit: &str) -> String {
+    value.map_or_else(|| "infinite".to_string(), |v| format!("{:.4} {}", v, unit))
+}
+
+impl fmt::Display for DiagnosticsReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let p = &self.metadata.params;
+        let pred = &self.predictability;
+        writeln!(
+            f,
+            "parameters: g/l = {:.6}, q = {}, f_d = {}, omega_d = {:.6}; start (theta, omega) = ({}, {})",
+            p.g / p.l,
+            p.q,
+            p.f_d,
+            p.omega_d,
+            self.metadata.initial_theta,
+            self.metadata.initial_omega
+        )?;
+        match self.period {
+            Some(period) => writeln!(f, "attractor: {} (period {})", self.attractor, period)?,
+            None => writeln!(f, "attractor: {}", self.attractor)?,
+        }
+        writeln!(f, "rotation number: {:.6} turns per drive period", self.rotation_number)?;
+        writeln!(
+            f,
+            "section: centroid ({:.6}, {:.6}), rms extent {:.6}",
+            self.section_centroid.0, self.section_centroid.1, self.section_rms_extent
+        )?;
+        writeln!(
+            f,
+            "largest Lyapunov exponent: {:.6}; spectrum [{:.6}, {:.6}, {:.6}]; Kaplan-Yorke dimension {:.4}",
+            self.largest_exponent,
+            self.lyapunov_spectrum[0],
+            self.lyapunov_spectrum[1],
+            self.lyapunov_spectrum[2],
+            self.kaplan_yorke_dimension
+        )?;
+        writeln!(f, "Lyapunov time: {}", or_infinite(pred.lyapunov_time, "time units"))?;
+        writeln!(
+            f,
+            "predictability horizon ({:e} -> {:e}): {} ({})",
+            pred.initial_error,
+            pred.tolerance,
+            or_infinite(pred.horizon, "time units"),
+            or_infinite(pred.horizon_periods, "drive periods")
+        )?;
+        if let Some(k) = self.zero_one_k {
+            writeln!(f, "0-1 test K: {:.4}", k)?;
+        }
+        if let Some(d) = self.correlation_dimension {
+            writeln!(f, "correlation dimension: {:.4}", d)?;
+        }
+        if let Some(entropy) = &self.metric_entropy {
+            writeln!(
+                f,
+                "metric entropy ({}x{} section histogram, blocks up to {}): {:.4} nats per drive period (measure entropy {:.4})",
+                entropy.theta_bins,
+                entropy.omega_bins,
+                entropy.rates.len(),
+                entropy.extrapolated,
+                entropy.measure_entropy
+            )?;
+        }
+        if let Some(pesin) = &self.pesin {
+            match pesin.ratio {
+                Some(ratio) => writeln!(
+                    f,
+                    "Pesin check: T * sum of positive exponents = {:.4}, entropy / sum = {:.3}",
+                    pesin.positive_exponent_sum, ratio
+                )?,
+                None => writeln!(f, "Pesin check: no positive exponent")?,
+            }
+        }
+        if let Some(torus) = &self.torus {
+            write!(
+                f,
+                "invariant curve: order preservation {:.4}, section rotation number {:.6} (nearest {}/{})",
+                torus.order_preservation, torus.rotation_number, torus.rational.0, torus.rational.1
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_predictability_horizon_and_report() {
+        let pred = predictability(0.5, 1e-6, 1e-2, 2.0);
+        assert!((pred.lyapunov_time.unwrap() - 2.0).abs() < 1e-12);
+        assert!((pred.horizon.unwrap() - 2.0 * 1e4f64.ln()).abs() < 1e-12);
+        assert!((pred.horizon_periods.unwrap() - 1e4f64.ln()).abs() < 1e-12);
+        assert_eq!(predictability(-0.1, 1e-6, 1e-2, 2.0).horizon, None);
+
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.q = 0.5;
+        params.omega_d = 2.0 / 3.0;
+        params.set_steps_per_period(100);
+        let options = ReportOptions {
+            transient_periods: 50,
+            sample_periods: 300,
+            ..ReportOptions::default()
+        };
+        params.f_d = 1.2;
+        let chaotic = diagnostics_report(&params, 0.2, 0.0, &options).unwrap();
+        assert_eq!(chaotic.attractor, "chaotic");
+        assert!(chaotic.predictability.horizon.is_some_and(|h| h > 0.0));
+        let json = serde_json::to_value(&chaotic).unwrap();
+        assert_eq!(json["metadata"]["params"]["f_d"], 1.2);
+        assert!(json["predictability"]["lyapunov_time"].is_number());
+        assert!(chaotic.to_string().contains("predictability horizon"));
+        let entropy = chaotic.metric_entropy.as_ref().unwrap();
+        assert!(entropy.extrapolated > 0.1 && chaotic.pesin.unwrap().ratio.is_some(), "{:?}", chaotic.pesin);
+
+        params.f_d = 0.5;
+        let periodic = diagnostics_report(&params, 0.2, 0.0, &options).unwrap();
+        assert_eq!((periodic.attractor.as_str(), periodic.period), ("periodic", Some(1)));
+        assert_eq!(periodic.predictability.horizon, None);
+        assert!(periodic.to_string().contains("infinite"));
+        assert!(periodic.metric_entropy.is_none() && periodic.pesin.is_none());
+    }
+}
@@ -0,0 +1,129 @@
+//resonance.rs
+use std::fs::File;
+use std::io::Write;
+use crate::harmonics::fourier_component;
+use crate::error::ChaosError;
+use crate::model::PendulumParams;
+use crate::solve_equation::{solve_periods, steps_per_period, wrap_angle, State};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SweepDirection {
+    Up,
+    Down,
+}
+
+/// 共振曲线上的一个点
+#[derive(Debug, Clone, Copy)]
+pub struct ResponsePoint {
+    pub omega_d: f64,
+    pub direction: SweepDirection,
+    /// 稳态窗口内 max |θ|（θ 折叠到 [-π, π)）
+    pub max_abs_theta: f64,
+    /// θ(t) 在驱动频率处的傅里叶分量幅值
+    pub fundamental_amplitude: f64,
+}
+
+/// 计算 θ(t) 在角频率 omega 处的傅里叶幅值 √(A² + B²)
+///
+/// A = (2/T)∫θ cos(ωt) dt，B = (2/T)∫θ sin(ωt) dt，梯形积分；
+/// 窗口应包含整数个周期，否则会有泄漏。
+pub fn fundamental_amplitude(window: &[(f64, State)], omega: f64) -> f64 {
+    let (a, b) = fourier_component(window, omega);
+    (a * a + b * b).sqrt()
+}
+
+/// 固定 f_d 扫描 omega_d，得到非线性共振曲线
+///
+/// 先从 omega_d_range.0 升到 omega_d_range.1，再降回来；
+/// 每个参数点以上一个点的末状态为初值（整数周期积分，驱动相位连续），
+/// 因此双稳区内上扫与下扫会停在不同分支上，跳跃现象直接体现在两条曲线的差异中。
+/// 每个驱动周期的步数由 params.dt 与起始 omega_d 决定，并在整个扫描中保持不变。
+pub fn resonance_curve(
+    params: &PendulumParams,
+    omega_d_range: (f64, f64),
+    steps: usize,
+    initial_theta: f64,
+    initial_omega: f64,
+    transient_periods: usize,
+    sample_periods: usize,
+) -> Result<Vec<ResponsePoint>, ChaosError> {
+    let mut p = params.clone();
+    p.omega_d = omega_d_range.0;
+    let spp = steps_per_period(&p);
+
+    let values: Vec<f64> = (0..=steps)
+        .map(|i| omega_d_range.0 + (omega_d_range.1 - omega_d_range.0) * i as f64 / steps.max(1) as f64)
+        .collect();
+
+    let mut out = Vec::with_capacity(2 * values.len());
+    let mut state = State {
+        theta: initial_theta,
+        omega: initial_omega,
+    };
+    let up = values.iter().map(|w| (*w, SweepDirection::Up));
+    let down = values.iter().rev().map(|w| (*w, SweepDirection::Down));
+    for (omega_d, direction) in up.chain(down) {
+        p.omega_d = omega_d;
+        let traj = solve_periods(&p, state.theta, state.omega, transient_periods + sample_periods, spp)?;
+        let window = &traj[transient_periods * spp..];
+
+        let max_abs_theta = window
+            .iter()
+            .map(|(_, s)| wrap_angle(s.theta).abs())
+            .fold(0.0, f64::max);
+        out.push(ResponsePoint {
+            omega_d,
+            direction,
+            max_abs_theta,
+            fundamental_amplitude: fundamental_amplitude(window, omega_d),
+        });
+
+        // 末状态 t = nT，折叠角度后作为下一个参数点的初值
+        let (_, last) = traj[traj.len() - 1];
+        state = State {
+            theta: wrap_angle(last.theta),
+            omega: last.omega,
+        };
+    }
+    Ok(out)
+}
+
+/// 把共振曲线写成 CSV（列：omega_d,direction,max_abs_theta,fundamental_amplitude）
+pub fn write_resonance_curve_csv(path: &str, points: &[ResponsePoint]) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(f, "omega_d,direction,max_abs_theta,fundamental_amplitude")?;
+    for p in points {
+        let dir = match p.direction {
+            SweepDirection::Up => "up",
+            SweepDirection::Down => "down",
+        };
+        writeln!(
+            f,
+            "{:.12},{},{:.12},{:.12}",
+            p.omega_d, dir, p.max_abs_theta, p.fundamental_amplitude
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_fundamental_amplitude_of_pure_tone() {
+        let omega = 2.0 / 3.0;
+        let period = 2.0 * PI / omega;
+        let n = 4000;
+        let window: Vec<(f64, State)> = (0..=n)
+            .map(|i| {
+                let t = 5.0 * period * i as f64 / n as f64;
+                let theta = 0.3 * (omega * t + 0.4).cos() + 0.05 * (3.0 * omega * t).sin();
+                (t, State { theta, omega: 0.0 })
+            })
+            .collect();
+        assert_relative_eq!(fundamental_amplitude(&window, omega), 0.3, epsilon = 1e-6);
+    }
+}
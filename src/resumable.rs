@@ -0,0 +1,168 @@
+//resumable.rs
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+
+// 可续算的扫描输出：大扫描按“单元”（一个参数值、一列格点、热图的一行）逐个写出，
+// 每个单元的数据行写完后追加一行完成标记并刷新到磁盘：
+//   # done <单元编号> [状态值 ...]
+// 状态值是续算需要的最少信息（如热启动的末状态），以最短往返形式写出，读回后逐位相同。
+// 文件第二行（表头之后）为 "# resumable"，用来与普通 CSV 区分。中断后重新打开同一文件时，
+// 最后一个标记之后不完整的行被截掉，已完成的单元跳过，其余接着追加，结果与一次跑完相同。
+// 标记行以 # 开头，本 crate 的 CSV 读取函数与 pandas（comment='#'）都会跳过。
+// 不检查续算时的参数是否与第一次一致，换参数时请换输出文件。
+
+const RESUMABLE_MARK: &str = "# resumable";
+const DONE_PREFIX: &str = "# done ";
+
+/// 已完成的单元
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompletedUnit {
+    pub index: usize,
+    /// 完成标记中记录的状态值
+    pub state: Vec<f64>,
+    /// 该单元的数据行（不含换行符）
+    pub rows: Vec<String>,
+}
+
+/// 按单元追加写入、可从部分写出的文件续算的 CSV
+pub struct ResumableCsv {
+    out: BufWriter<File>,
+    completed: Vec<CompletedUnit>,
+}
+
+fn invalid(path: &str, msg: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, format!("{}: {}", path, msg))
+}
+
+impl ResumableCsv {
+    /// 打开 path：不存在或为空时新建并写表头；已存在时要求表头一致且带有 "# resumable" 标记，
+    /// 读出已完成的单元并截掉最后一个完成标记之后的内容
+    pub fn open(path: &str, header: &str) -> std::io::Result<Self> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e),
+        };
+        if text.trim().is_empty() {
+            let mut out = BufWriter::new(File::create(path)?);
+            writeln!(out, "{}", header)?;
+            writeln!(out, "{}", RESUMABLE_MARK)?;
+            out.flush()?;
+            return Ok(ResumableCsv { out, completed: Vec::new() });
+        }
+
+        // 逐行扫描（保留行尾以计算字节偏移），metadata 注释头可能出现在表头之前
+        let mut offset = 0;
+        let mut keep = 0;
+        let mut seen_header = false;
+        let mut marked = false;
+        let mut completed = Vec::new();
+        let mut pending = Vec::new();
+        for line in text.split_inclusive('\n') {
+            offset += line.len();
+            let line = line.trim_end_matches(['\n', '\r']);
+            if !seen_header {
+                if !line.starts_with('#') && !line.trim().is_empty() {
+                    if line != header {
+                        return Err(invalid(path, &format!("expected header {:?}, found {:?}", header, line)));
+                    }
+                    seen_header = true;
+                    keep = offset;
+                }
+                continue;
+            }
+            if line == RESUMABLE_MARK {
+                marked = true;
+                keep = offset;
+            } else if let Some(rest) = line.strip_prefix(DONE_PREFIX) {
+                let mut fields = rest.split_whitespace();
+                let index = fields
+                    .next()
+                    .and_then(|s| s.parse().ok())
+                    .ok_or_else(|| invalid(path, &format!("bad completion marker {:?}", line)))?;
+                let state = fields
+                    .map(|s| s.parse::<f64>())
+                    .collect::<Result<Vec<f64>, _>>()
+                    .map_err(|_| invalid(path, &format!("bad completion marker {:?}", line)))?;
+                completed.push(CompletedUnit { index, state, rows: std::mem::take(&mut pending) });
+                keep = offset;
+            } else if !line.starts_with('#') && !line.trim().is_empty() {
+                pending.push(line.to_string());
+            }
+        }
+        if !marked {
+            return Err(invalid(path, "not written by a resumable sweep; refusing to overwrite it"));
+        }
+        let mut file = OpenOptions::new().write(true).open(path)?;
+        file.set_len(keep as u64)?;
+        file.seek(SeekFrom::End(0))?;
+        Ok(ResumableCsv { out: BufWriter::new(file), completed })
+    }
+
+    /// 打开时已经完成的单元，按写出顺序
+    pub fn completed(&self) -> &[CompletedUnit] {
+        &self.completed
+    }
+
+    pub fn is_done(&self, index: usize) -> bool {
+        self.completed.iter().any(|u| u.index == index)
+    }
+
+    /// 写出一个单元：rows 写数据行，随后写完成标记并刷新
+    pub fn write_unit<F>(&mut self, index: usize, state: &[f64], rows: F) -> std::io::Result<()>
+    where
+        F: FnOnce(&mut dyn Write) -> std::io::Result<()>,
+    {
+        rows(&mut self.out)?;
+        write!(self.out, "{}{}", DONE_PREFIX, index)?;
+        for v in state {
+            write!(self.out, " {}", v)?;
+        }
+        writeln!(self.out)?;
+        self.out.flush()
+    }
+}
+
+/// 把 CSV 数据行解析为数值列；任何一列不是数时返回 InvalidData
+pub(crate) fn parse_row(row: &str) -> std::io::Result<Vec<f64>> {
+    row.split(',')
+        .map(|s| s.trim().parse::<f64>())
+        .collect::<Result<Vec<f64>, _>>()
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, format!("{:?}: {}", row, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_unit_is_dropped_on_resume() {
+        let path = std::env::temp_dir().join("chaos_resumable_test.csv");
+        let path = path.to_str().unwrap();
+        let _ = std::fs::remove_file(path);
+        let mut csv = ResumableCsv::open(path, "x,y").unwrap();
+        csv.write_unit(0, &[0.1, -1.0 / 3.0], |w| writeln!(w, "0,1")).unwrap();
+        drop(csv);
+        // 模拟写到一半被打断
+        let mut f = OpenOptions::new().append(true).open(path).unwrap();
+        write!(f, "1,2\n1,").unwrap();
+        drop(f);
+
+        let mut csv = ResumableCsv::open(path, "x,y").unwrap();
+        assert_eq!(csv.completed().len(), 1);
+        assert_eq!(csv.completed()[0].state, vec![0.1, -1.0 / 3.0]);
+        assert_eq!(csv.completed()[0].rows, vec!["0,1".to_string()]);
+        assert!(csv.is_done(0) && !csv.is_done(1));
+        csv.write_unit(1, &[], |w| writeln!(w, "1,3")).unwrap();
+        drop(csv);
+        assert_eq!(
+            std::fs::read_to_string(path).unwrap(),
+            "x,y\n# resumable\n0,1\n# done 0 0.1 -0.3333333333333333\n1,3\n# done 1\n"
+        );
+
+        // 表头不一致或不是续算文件时拒绝
+        assert!(ResumableCsv::open(path, "x,z").is_err());
+        std::fs::write(path, "x,y\n0,1\n").unwrap();
+        assert!(ResumableCsv::open(path, "x,y").is_err());
+    }
+}
@@ -0,0 +1,104 @@
+//return_map.rs
+use std::fs::File;
+use std::io::Write;
+
+/// 由庞加莱点构造一维首次回归映射 (θ_n, θ_{n+1})
+pub fn return_map(samples: &[(f64, f64)]) -> Vec<(f64, f64)> {
+    return_map_lag(samples, 1)
+}
+
+/// 滞后 k 的回归映射 (θ_n, θ_{n+k})
+///
+/// 周期 p 轨道在 k = p 的映射上全部落在对角线上，倍周期级联中可逐级检查。lag 为 0 时按 1 处理。
+pub fn return_map_lag(samples: &[(f64, f64)], lag: usize) -> Vec<(f64, f64)> {
+    let lag = lag.max(1);
+    samples.iter().zip(samples.iter().skip(lag)).map(|(a, b)| (a.0, b.0)).collect()
+}
+
+/// 把回归映射写成 CSV（两列：theta_n,theta_next）
+pub fn write_return_map_csv(path: &str, map: &[(f64, f64)]) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(f, "theta_n,theta_next")?;
+    for (x, y) in map {
+        writeln!(f, "{:.12},{:.12}", x, y)?;
+    }
+    Ok(())
+}
+
+// 在按 x 排序的映射点上做分段线性插值，超出范围时取端点值
+fn evaluate_sorted(sorted: &[(f64, f64)], x: f64) -> f64 {
+    let i = sorted.partition_point(|(xi, _)| *xi < x);
+    if i == 0 {
+        return sorted[0].1;
+    }
+    if i == sorted.len() {
+        return sorted[sorted.len() - 1].1;
+    }
+    let (x1, y1) = sorted[i - 1];
+    let (x2, y2) = sorted[i];
+    if x2 == x1 {
+        return y2;
+    }
+    y1 + (x - x1) / (x2 - x1) * (y2 - y1)
+}
+
+/// 生成蛛网图（阶梯路径）数据
+///
+/// map 为 (x_n, x_{n+1}) 点对，先按 x 排序后做分段线性插值得到 f(x)；
+/// 路径从对角线上的 (x0, x0) 出发，每次迭代依次经过 (x, f(x)) 与 (f(x), f(x))，
+/// 共返回 2 * iterations + 1 个点。map 为空时只返回起点。
+pub fn cobweb(map: &[(f64, f64)], x0: f64, iterations: usize) -> Vec<(f64, f64)> {
+    let mut path = vec![(x0, x0)];
+    if map.is_empty() {
+        return path;
+    }
+    let mut sorted = map.to_vec();
+    sorted.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+    let mut x = x0;
+    for _ in 0..iterations {
+        let y = evaluate_sorted(&sorted, x);
+        path.push((x, y));
+        path.push((y, y));
+        x = y;
+    }
+    path
+}
+
+/// 把蛛网路径写成 CSV（三列：step,x,y）
+pub fn write_cobweb_csv(path: &str, points: &[(f64, f64)]) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(f, "step,x,y")?;
+    for (i, (x, y)) in points.iter().enumerate() {
+        writeln!(f, "{},{:.12},{:.12}", i, x, y)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_cobweb_on_linear_contraction() {
+        // f(x) = 0.5 x 的采样点
+        let map: Vec<(f64, f64)> = (-10..=10).map(|i| (i as f64 * 0.1, i as f64 * 0.05)).collect();
+        let path = cobweb(&map, 0.8, 5);
+        assert_eq!(path.len(), 11);
+        assert_eq!(path[0], (0.8, 0.8));
+        assert_relative_eq!(path[1].1, 0.4, epsilon = 1e-12);
+        assert_relative_eq!(path[2].0, 0.4, epsilon = 1e-12);
+        assert_relative_eq!(path[10].0, 0.8 / 32.0, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_return_map_lag_on_period_two_orbit() {
+        let samples: Vec<(f64, f64)> = (0..10).map(|i| (if i % 2 == 0 { 0.3 } else { -0.7 }, 0.0)).collect();
+        assert_eq!(return_map(&samples).len(), 9);
+        assert_eq!(return_map(&samples)[0], (0.3, -0.7));
+        let lag2 = return_map_lag(&samples, 2);
+        assert_eq!(lag2.len(), 8);
+        assert!(lag2.iter().all(|(x, y)| x == y));
+    }
+}
@@ -0,0 +1,106 @@
+//roots.rs
+
+/// 对单调谓词做二分：要求 pred(lo) 为真、pred(hi) 为假
+///
+/// 返回收缩到宽度不超过 tolerance 的区间 (lo, hi)，真假交界位于其中；
+/// 两端不满足要求时返回 None。每次迭代调用一次 pred。
+/// tolerance 不为正或小于 lo、hi 处的浮点间距时，区间缩到相邻两个浮点数（中点等于某一端）即停止。
+pub fn bisect_predicate<F: FnMut(f64) -> bool>(
+    mut lo: f64,
+    mut hi: f64,
+    tolerance: f64,
+    mut pred: F,
+) -> Option<(f64, f64)> {
+    if !pred(lo) || pred(hi) {
+        return None;
+    }
+    while (hi - lo).abs() > tolerance {
+        let mid = 0.5 * (lo + hi);
+        if mid == lo || mid == hi {
+            break;
+        }
+        if pred(mid) {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    Some((lo, hi))
+}
+
+/// 带括号的割线法（Illinois 改进的试位法）：要求 f(lo) 与 f(hi) 异号
+///
+/// 每次取两端点连线的零点替换同号的一端；同一端连续两次保留时把它的函数值减半，
+/// 避免普通试位法一端不动、区间收缩很慢。对光滑函数超线性收敛。
+/// 返回宽度不超过 tolerance 的括号区间（或 f 恰为 0 的点构成的退化区间）；
+/// 两端同号、f 出现非有限值或 max_iterations 次内未收敛时返回 None。
+pub fn illinois<F: FnMut(f64) -> f64>(
+    lo: f64,
+    hi: f64,
+    tolerance: f64,
+    max_iterations: usize,
+    mut f: F,
+) -> Option<(f64, f64)> {
+    let (mut a, mut b) = (lo, hi);
+    let (mut fa, mut fb) = (f(a), f(b));
+    if !fa.is_finite() || !fb.is_finite() || fa * fb > 0.0 {
+        return None;
+    }
+    for _ in 0..max_iterations {
+        if fa == 0.0 {
+            return Some((a, a));
+        }
+        if fb == 0.0 {
+            return Some((b, b));
+        }
+        if (b - a).abs() <= tolerance {
+            return Some((a.min(b), a.max(b)));
+        }
+        let c = b - fb * (b - a) / (fb - fa);
+        let fc = f(c);
+        if !fc.is_finite() {
+            return None;
+        }
+        if fc * fb < 0.0 {
+            (a, fa) = (b, fb);
+        } else {
+            fa *= 0.5;
+        }
+        (b, fb) = (c, fc);
+    }
+    ((b - a).abs() <= tolerance).then(|| (a.min(b), a.max(b)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bisect_predicate_brackets_threshold() {
+        let (lo, hi) = bisect_predicate(0.0, 4.0, 1e-10, |x| x * x < 2.0).unwrap();
+        assert!(lo * lo < 2.0 && hi * hi >= 2.0);
+        assert!(hi - lo <= 1e-10);
+        assert!(bisect_predicate(2.0, 4.0, 1e-6, |x| x * x < 2.0).is_none());
+
+        // 容差为 0 或低于浮点间距：缩到相邻浮点数后停止
+        let mut calls = 0;
+        let (lo, hi) = bisect_predicate(1.0, 2.0, 0.0, |x| {
+            calls += 1;
+            x < 1.5 + 1e-13
+        })
+        .unwrap();
+        assert!(lo < hi && hi - lo <= f64::EPSILON * 2.0, "{} {}", lo, hi);
+        assert!(calls < 100, "{} evaluations", calls);
+        assert!(bisect_predicate(1e6, 1e6 + 1.0, 1e-20, |x| x < 1e6 + 0.5).is_some());
+
+        let mut calls = 0;
+        let (lo, hi) = illinois(0.0, 4.0, 1e-12, 100, |x| {
+            calls += 1;
+            x * x - 2.0
+        })
+        .unwrap();
+        assert!(lo <= 2f64.sqrt() && 2f64.sqrt() <= hi && hi - lo <= 1e-12);
+        assert!(calls < 20, "{} evaluations", calls);
+        assert!(illinois(2.0, 4.0, 1e-6, 100, |x| x * x - 2.0).is_none());
+    }
+}
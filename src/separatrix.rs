@@ -0,0 +1,243 @@
+//separatrix.rs
+use std::f64::consts::PI;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use crate::model::PendulumParams;
+
+// 无驱动、无阻尼单摆 θ'' + (g/l) sinθ = 0 的守恒量（按单位 m l² 归一化）：
+//   E = ω² / 2 + (g/l)(1 - cosθ)
+// 分界线对应 E = 2 g/l，即 ω(θ) = ±2√(g/l)·cos(θ/2)。
+// 等能线 H = E 可以直接解出 ω(θ) = ±√(2(E - (g/l)(1 - cosθ)))，不需要在网格上做 marching squares；
+// 网格上的能量面（energy_surface）留给外部画图程序画填色等值图，驱动系统的相图、庞加莱点叠在上面对照可积结构。
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MotionKind {
+    /// 往复摆动（E < 2 g/l）
+    Libration,
+    /// 整圈转动（E > 2 g/l）
+    Rotation,
+    /// 恰好在分界线上
+    Separatrix,
+}
+
+/// 单位 m l² 下的单摆能量
+pub fn pendulum_energy(theta: f64, omega: f64, params: &PendulumParams) -> f64 {
+    0.5 * omega * omega + (params.g / params.l) * (1.0 - theta.cos())
+}
+
+/// 分界线上的能量 2 g/l
+pub fn separatrix_energy(params: &PendulumParams) -> f64 {
+    2.0 * params.g / params.l
+}
+
+/// 分界线上半支 ω(θ) = 2√(g/l)·|cos(θ/2)|，对任意 θ 都成立
+pub fn separatrix_omega(theta: f64, params: &PendulumParams) -> f64 {
+    2.0 * (params.g / params.l).sqrt() * (0.5 * theta).cos().abs()
+}
+
+/// 在 params 的 θ 网格上采样分界线，返回 Vec<(theta, omega_upper, omega_lower)>
+pub fn separatrix(params: &PendulumParams) -> Vec<(f64, f64, f64)> {
+    params
+        .theta_grid()
+        .into_iter()
+        .map(|theta| {
+            let w = separatrix_omega(theta, params);
+            (theta, w, -w)
+        })
+        .collect()
+}
+
+/// 等能线 H = energy 在 θ 处的上半支 ω ≥ 0；势能超过 energy（禁区）时返回 None
+pub fn energy_level_omega(theta: f64, energy: f64, params: &PendulumParams) -> Option<f64> {
+    let kinetic = energy - (params.g / params.l) * (1.0 - theta.cos());
+    (kinetic >= 0.0).then(|| (2.0 * kinetic).sqrt())
+}
+
+/// 在 params 的 θ 网格上采样等能线 H = energy，返回 Vec<(theta, omega_upper, omega_lower)>
+///
+/// 跳过禁区内的格点；摆动（energy < 2 g/l）时补上落在网格范围内的转折点 θ = 2πk ± θ_max（ω = 0），
+/// 使每个 2π 周期内的闭曲线两端闭合。energy 不小于 2 g/l 时与网格一一对应，energy = 2 g/l 即 separatrix(params)。
+pub fn energy_contour(energy: f64, params: &PendulumParams) -> Vec<(f64, f64, f64)> {
+    let grid = params.theta_grid();
+    let mut contour: Vec<(f64, f64, f64)> = grid
+        .iter()
+        .filter_map(|&theta| energy_level_omega(theta, energy, params).map(|w| (theta, w, -w)))
+        .collect();
+    let cos_max = 1.0 - energy * params.l / params.g;
+    if cos_max > -1.0 && cos_max < 1.0 {
+        let theta_max = cos_max.acos();
+        let (lo, hi) = (grid[0], grid[grid.len() - 1]);
+        let first = ((lo - theta_max) / (2.0 * PI)).floor() as i64;
+        let last = ((hi + theta_max) / (2.0 * PI)).ceil() as i64;
+        for k in first..=last {
+            let center = 2.0 * PI * k as f64;
+            for theta in [center - theta_max, center + theta_max] {
+                if (lo..=hi).contains(&theta) {
+                    contour.push((theta, 0.0, 0.0));
+                }
+            }
+        }
+        contour.sort_by(|a, b| a.0.total_cmp(&b.0));
+    }
+    contour
+}
+
+/// 能量面 H(θ, ω) 在 θ/ω 网格上的取值
+#[derive(Debug, Clone)]
+pub struct EnergySurface {
+    pub thetas: Vec<f64>,
+    pub omegas: Vec<f64>,
+    /// 按 θ 优先排列：energies[i_theta * omegas.len() + i_omega]
+    pub energies: Vec<f64>,
+}
+
+impl EnergySurface {
+    pub fn energy_at(&self, i_theta: usize, i_omega: usize) -> f64 {
+        self.energies[i_theta * self.omegas.len() + i_omega]
+    }
+}
+
+/// 在 params 的 θ/ω 网格上计算 H(θ, ω)
+pub fn energy_surface(params: &PendulumParams) -> EnergySurface {
+    let thetas = params.theta_grid();
+    let omegas = params.omega_grid();
+    let energies = thetas
+        .iter()
+        .flat_map(|&theta| omegas.iter().map(move |&omega| pendulum_energy(theta, omega, params)))
+        .collect();
+    EnergySurface { thetas, omegas, energies }
+}
+
+/// 按能量判断初值属于摆动还是转动（无驱动、无阻尼情形）
+pub fn classify_motion(theta: f64, omega: f64, params: &PendulumParams) -> MotionKind {
+    let e = pendulum_energy(theta, omega, params);
+    let e_sep = separatrix_energy(params);
+    if e < e_sep {
+        MotionKind::Libration
+    } else if e > e_sep {
+        MotionKind::Rotation
+    } else {
+        MotionKind::Separatrix
+    }
+}
+
+/// 对 params 的 θ/ω 网格上的所有初值分类，返回 Vec<(theta, omega, kind)>
+pub fn classify_grid(params: &PendulumParams) -> Vec<(f64, f64, MotionKind)> {
+    let omegas = params.omega_grid();
+    let mut out = Vec::new();
+    for theta in params.theta_grid() {
+        for &omega in &omegas {
+            out.push((theta, omega, classify_motion(theta, omega, params)));
+        }
+    }
+    out
+}
+
+/// 把分界线写成 CSV（三列：theta,omega_upper,omega_lower）
+pub fn write_separatrix_csv(path: &str, params: &PendulumParams) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(f, "theta,omega_upper,omega_lower")?;
+    for (theta, upper, lower) in separatrix(params) {
+        writeln!(f, "{:.12},{:.12},{:.12}", theta, upper, lower)?;
+    }
+    Ok(())
+}
+
+/// 把能量面写成矩阵 CSV：首行为 omega\theta 与各 θ，之后每行以 ω 开头（ω 从大到小），接该 ω 下各 θ 的能量
+pub fn write_energy_surface_csv(path: &str, surface: &EnergySurface) -> std::io::Result<()> {
+    let mut f = BufWriter::new(File::create(path)?);
+    write!(f, "omega\\theta")?;
+    for theta in &surface.thetas {
+        write!(f, ",{:.12}", theta)?;
+    }
+    writeln!(f)?;
+    for j in (0..surface.omegas.len()).rev() {
+        write!(f, "{:.12}", surface.omegas[j])?;
+        for i in 0..surface.thetas.len() {
+            write!(f, ",{:.12}", surface.energy_at(i, j))?;
+        }
+        writeln!(f)?;
+    }
+    f.flush()
+}
+
+/// 把若干条等能线写成 CSV（列：energy,theta,omega_upper,omega_lower），按 levels 的顺序逐条输出
+pub fn write_energy_contours_csv(path: &str, params: &PendulumParams, levels: &[f64]) -> std::io::Result<()> {
+    let mut f = BufWriter::new(File::create(path)?);
+    writeln!(f, "energy,theta,omega_upper,omega_lower")?;
+    for &energy in levels {
+        for (theta, upper, lower) in energy_contour(energy, params) {
+            writeln!(f, "{:.12},{:.12},{:.12},{:.12}", energy, theta, upper, lower)?;
+        }
+    }
+    f.flush()
+}
+
+/// 把网格分类写成 CSV（三列：theta,omega,kind），kind 为 libration/rotation/separatrix
+pub fn write_motion_classification_csv(path: &str, params: &PendulumParams) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(f, "theta,omega,kind")?;
+    for (theta, omega, kind) in classify_grid(params) {
+        let label = match kind {
+            MotionKind::Libration => "libration",
+            MotionKind::Rotation => "rotation",
+            MotionKind::Separatrix => "separatrix",
+        };
+        writeln!(f, "{:.12},{:.12},{}", theta, omega, label)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_separatrix_energy_and_classification() {
+        let params = PendulumParams::new();
+        let e_sep = separatrix_energy(&params);
+
+        // 分界线上每一点的能量都等于 2 g/l
+        for (theta, upper, lower) in separatrix(&params) {
+            assert_relative_eq!(pendulum_energy(theta, upper, &params), e_sep, epsilon = 1e-9);
+            assert_relative_eq!(pendulum_energy(theta, lower, &params), e_sep, epsilon = 1e-9);
+        }
+
+        let w_max = separatrix_omega(0.0, &params);
+        assert_eq!(classify_motion(0.0, 0.5 * w_max, &params), MotionKind::Libration);
+        assert_eq!(classify_motion(0.0, 1.5 * w_max, &params), MotionKind::Rotation);
+        assert_eq!(classify_motion(1.0, -1.5 * w_max, &params), MotionKind::Rotation);
+
+        // 等能线上能量守恒；摆动的等能线在 ±θ_max 处闭合，分界能量下与 separatrix 相同
+        let mut params = PendulumParams::new();
+        params.theta_start = -3.0 * PI;
+        params.theta_end = 3.0 * PI;
+        let energy = 0.5 * e_sep;
+        let contour = energy_contour(energy, &params);
+        for &(theta, upper, lower) in &contour {
+            assert_relative_eq!(pendulum_energy(theta, upper, &params), energy, epsilon = 1e-9);
+            assert_eq!(lower, -upper);
+        }
+        let turning: Vec<f64> = contour.iter().filter(|c| c.1 == 0.0).map(|c| c.0).collect();
+        let theta_max = 0.5 * PI;
+        assert_eq!(turning.len(), 6, "{:?}", turning);
+        assert_relative_eq!(turning[2], -theta_max, epsilon = 1e-12);
+        assert_relative_eq!(turning[5], 2.0 * PI + theta_max, epsilon = 1e-12);
+        let sep = separatrix(&params);
+        let contour = energy_contour(e_sep, &params);
+        assert_eq!(contour.len(), sep.len());
+        for (a, b) in contour.iter().zip(&sep) {
+            assert_eq!(a.0, b.0);
+            assert_relative_eq!(a.1, b.1, epsilon = 1e-7);
+        }
+
+        let surface = energy_surface(&params);
+        assert_eq!(surface.energies.len(), surface.thetas.len() * surface.omegas.len());
+        assert_relative_eq!(
+            surface.energy_at(3, 5),
+            pendulum_energy(surface.thetas[3], surface.omegas[5], &params),
+            epsilon = 1e-15
+        );
+    }
+}
@@ -0,0 +1,300 @@
+//serve.rs
+use std::f64::consts::PI;
+use std::io::ErrorKind;
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use tungstenite::{Message, WebSocket};
+use crate::error::ChaosError;
+use crate::model::PendulumParams;
+use crate::solve_equation::{steps_per_period, wrap_angle, TrajectoryIter};
+
+// WebSocket 流式服务：浏览器里的可视化面板直接连到求解器，不必先编译到 WASM。
+// 协议（全部为 JSON 文本消息）：
+//   客户端 -> 服务端：StreamRequest，如 {"params": {...}, "theta0": 0.2, "periods": 500}；
+//     积分过程中再发一条请求即以新参数从头开始（拖动滑块时不必断开重连）。
+//   服务端 -> 客户端：StreamMessage，按 "type" 区分
+//     {"type":"frame","t":..,"theta":..,"omega":..}       每 frame_every 个积分步一帧，θ 折叠到 [-π, π)
+//     {"type":"poincare","period":n,"theta":..,"omega":..} 过渡期后每个驱动周期末一个庞加莱点
+//     {"type":"done","periods":n}                          积分完 periods 个周期
+//     {"type":"error","message":".."}                      请求无法解析或参数无效，连接保持
+// 为使采样时刻恰好落在积分步上，积分总是按每周期 steps_per_period(params) 步进行；噪声与自适应步长不适用。
+// 每个连接一个线程，积分在该线程里同步进行，期间每推送一帧检查一次有无新请求。
+
+/// 客户端发来的一次积分请求；缺省的字段取默认值
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StreamRequest {
+    /// 完整参数（与 config/config.json 格式相同）；缺省时为经典混沌参数 g/l = 1, q = 0.5, f_d = 1.2, Ω = 2/3
+    pub params: Option<PendulumParams>,
+    pub theta0: f64,
+    pub omega0: f64,
+    /// 不推送庞加莱点的过渡周期数（帧照常推送）
+    pub transient_periods: usize,
+    /// 积分的驱动周期数（含过渡期）
+    pub periods: usize,
+    /// 每多少个积分步推送一帧；0 表示只推送庞加莱点
+    pub frame_every: usize,
+    /// 每秒至多推送的帧数，用于按动画速度播放；缺省（null）不限速，给出时须为正的有限数，按 [1e-3, 1e3] 截断
+    pub frames_per_second: Option<f64>,
+}
+
+impl Default for StreamRequest {
+    fn default() -> Self {
+        StreamRequest {
+            params: None,
+            theta0: 0.2,
+            omega0: 0.0,
+            transient_periods: 0,
+            periods: 1000,
+            frame_every: 10,
+            frames_per_second: None,
+        }
+    }
+}
+
+/// 服务端推送的消息
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StreamMessage {
+    Frame { t: f64, theta: f64, omega: f64 },
+    Poincare { period: usize, theta: f64, omega: f64 },
+    Done { periods: usize },
+    Error { message: String },
+}
+
+// 积分被新请求打断，或连接已关闭
+enum Interrupt {
+    Restart(Box<StreamRequest>),
+    Closed,
+}
+
+// 默认的经典混沌参数，每个驱动周期 400 步
+fn default_params() -> PendulumParams {
+    let mut params = PendulumParams::new();
+    params.l = params.g;
+    params.q = 0.5;
+    params.f_d = 1.2;
+    params.omega_d = 2.0 / 3.0;
+    params.set_steps_per_period(400);
+    params
+}
+
+impl StreamRequest {
+    /// 实际使用的参数：按整周期步进，t_end 恰好覆盖 periods 个周期；参数无效时返回错误
+    pub fn resolved_params(&self) -> Result<PendulumParams, ChaosError> {
+        let mut params = self.params.clone().unwrap_or_else(default_params);
+        params.validate()?;
+        if params.noise_intensity > 0.0 {
+            return Err(ChaosError::InvalidParams("streaming does not support noise".to_string()));
+        }
+        let spp = steps_per_period(&params);
+        params.set_steps_per_period(spp);
+        params.t_end = self.periods as f64 * 2.0 * PI / params.omega_d;
+        Ok(params)
+    }
+
+    /// 相邻两帧的最小间隔；不限速时为 0，frames_per_second 不是正的有限数时返回错误
+    pub fn frame_interval(&self) -> Result<Duration, ChaosError> {
+        match self.frames_per_second {
+            None => Ok(Duration::ZERO),
+            Some(fps) if fps.is_finite() && fps > 0.0 => {
+                let fps = fps.clamp(1e-3, 1e3);
+                Duration::try_from_secs_f64(1.0 / fps).map_err(|e| ChaosError::InvalidParams(e.to_string()))
+            }
+            Some(fps) => Err(ChaosError::InvalidParams(format!(
+                "frames_per_second must be a positive finite number, got {}",
+                fps
+            ))),
+        }
+    }
+}
+
+fn send(ws: &mut WebSocket<TcpStream>, msg: &StreamMessage) -> tungstenite::Result<()> {
+    let text = serde_json::to_string(msg).expect("stream messages serialize");
+    ws.send(Message::text(text))
+}
+
+// 不阻塞地检查客户端有无新消息（套接字设置了很短的读超时）
+fn poll_request(ws: &mut WebSocket<TcpStream>) -> Result<Option<String>, Interrupt> {
+    match ws.read() {
+        Ok(Message::Text(text)) => Ok(Some(text.as_str().to_string())),
+        Ok(Message::Close(_)) => Err(Interrupt::Closed),
+        Ok(_) => Ok(None),
+        Err(tungstenite::Error::Io(e)) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => Ok(None),
+        Err(_) => Err(Interrupt::Closed),
+    }
+}
+
+// 解析请求；无法解析或帧率无效时回一条 error 消息
+fn parse_request(ws: &mut WebSocket<TcpStream>, text: &str) -> Result<Option<StreamRequest>, Interrupt> {
+    let message = match serde_json::from_str::<StreamRequest>(text) {
+        Ok(request) => match request.frame_interval() {
+            Ok(_) => return Ok(Some(request)),
+            Err(e) => format!("invalid request: {}", e),
+        },
+        Err(e) => format!("invalid request: {}", e),
+    };
+    send(ws, &StreamMessage::Error { message }).map_err(|_| Interrupt::Closed)?;
+    Ok(None)
+}
+
+// 按请求积分并推送，直到完成、收到新请求或连接关闭
+fn run_request(ws: &mut WebSocket<TcpStream>, request: &StreamRequest) -> Result<(), Interrupt> {
+    let closed = |_| Interrupt::Closed;
+    let (params, min_interval) = match request.resolved_params().and_then(|p| Ok((p, request.frame_interval()?))) {
+        Ok(resolved) => resolved,
+        Err(e) => return send(ws, &StreamMessage::Error { message: e.to_string() }).map_err(closed),
+    };
+    let spp = steps_per_period(&params);
+    // 第一帧不等待；时钟无法回拨 min_interval 时（进程刚启动）同样不等待
+    let mut last_frame = Instant::now().checked_sub(min_interval);
+    for (k, (t, s)) in TrajectoryIter::new(&params, request.theta0, request.omega0).enumerate() {
+        let mut pushed = false;
+        if request.frame_every > 0 && k % request.frame_every == 0 {
+            if let Some(wait) = last_frame.and_then(|last| min_interval.checked_sub(last.elapsed())) {
+                std::thread::sleep(wait);
+            }
+            last_frame = Some(Instant::now());
+            let frame = StreamMessage::Frame { t, theta: wrap_angle(s.theta), omega: s.omega };
+            send(ws, &frame).map_err(closed)?;
+            pushed = true;
+        }
+        let period = k / spp;
+        if k > 0 && k % spp == 0 && period > request.transient_periods {
+            let point = StreamMessage::Poincare { period, theta: wrap_angle(s.theta), omega: s.omega };
+            send(ws, &point).map_err(closed)?;
+            pushed = true;
+        }
+        if pushed && let Some(text) = poll_request(ws)? && let Some(next) = parse_request(ws, &text)? {
+            return Err(Interrupt::Restart(Box::new(next)));
+        }
+    }
+    send(ws, &StreamMessage::Done { periods: request.periods }).map_err(closed)
+}
+
+/// 处理一个已建立的 TCP 连接：完成 WebSocket 握手后循环等待请求并推送结果，直到客户端断开
+pub fn handle_connection(stream: TcpStream) -> std::io::Result<()> {
+    let mut ws = tungstenite::accept(stream).map_err(|e| std::io::Error::other(e.to_string()))?;
+    loop {
+        // 空闲时阻塞等待下一条请求
+        ws.get_ref().set_read_timeout(None)?;
+        let mut request = match ws.read() {
+            Ok(Message::Text(text)) => match parse_request(&mut ws, text.as_str()) {
+                Ok(Some(request)) => request,
+                Ok(None) => continue,
+                Err(_) => return Ok(()),
+            },
+            Ok(Message::Close(_)) | Err(_) => return Ok(()),
+            Ok(_) => continue,
+        };
+        ws.get_ref().set_read_timeout(Some(Duration::from_millis(1)))?;
+        loop {
+            match run_request(&mut ws, &request) {
+                Ok(()) => break,
+                Err(Interrupt::Restart(next)) => request = *next,
+                Err(Interrupt::Closed) => return Ok(()),
+            }
+        }
+    }
+}
+
+/// 在 listener 上接受连接，每个连接一个线程；on_connect 在每个新连接时以对端地址调用（用于日志）
+pub fn serve_listener(listener: TcpListener, mut on_connect: impl FnMut(&str)) -> std::io::Result<()> {
+    for stream in listener.incoming() {
+        let stream = stream?;
+        on_connect(&stream.peer_addr().map_or_else(|_| "unknown".to_string(), |a| a.to_string()));
+        std::thread::spawn(move || {
+            let _ = handle_connection(stream);
+        });
+    }
+    Ok(())
+}
+
+/// 在 addr（如 "127.0.0.1:8765"）上启动服务，一直运行
+pub fn serve(addr: impl ToSocketAddrs, on_connect: impl FnMut(&str)) -> std::io::Result<()> {
+    serve_listener(TcpListener::bind(addr)?, on_connect)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solve_equation::poincare;
+
+    fn next(ws: &mut WebSocket<TcpStream>) -> StreamMessage {
+        loop {
+            if let Message::Text(text) = ws.read().unwrap() {
+                return serde_json::from_str(text.as_str()).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_stream_matches_poincare_and_restarts() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || serve_listener(listener, |_| {}));
+        let (mut ws, _) = tungstenite::client(format!("ws://{}/", addr), TcpStream::connect(addr).unwrap()).unwrap();
+
+        let mut params = default_params();
+        params.set_steps_per_period(100);
+        let request = StreamRequest {
+            params: Some(params.clone()),
+            transient_periods: 2,
+            periods: 6,
+            frame_every: 50,
+            ..StreamRequest::default()
+        };
+        ws.send(Message::text(serde_json::to_string(&request).unwrap())).unwrap();
+        let mut frames = 0;
+        let mut points = Vec::new();
+        loop {
+            match next(&mut ws) {
+                StreamMessage::Frame { .. } => frames += 1,
+                StreamMessage::Poincare { theta, omega, .. } => points.push((theta, omega)),
+                StreamMessage::Done { periods } => {
+                    assert_eq!(periods, 6);
+                    break;
+                }
+                StreamMessage::Error { message } => panic!("{}", message),
+            }
+        }
+        // 600 步，每 50 步一帧（含 t = 0）；推送的庞加莱点与 poincare 的结果相同
+        assert_eq!(frames, 13);
+        params.t_end = 6.0 * 2.0 * PI / params.omega_d;
+        assert_eq!(points, poincare(&params, 0.2, 0.0, 2, 4).unwrap());
+
+        // 无效参数回 error 消息，连接保持可用
+        ws.send(Message::text(r#"{"periods": "many"}"#)).unwrap();
+        assert!(matches!(next(&mut ws), StreamMessage::Error { .. }));
+        params.dt = -1.0;
+        params.step_control = crate::model::StepControl::FixedDt;
+        let bad = StreamRequest { params: Some(params), ..StreamRequest::default() };
+        ws.send(Message::text(serde_json::to_string(&bad).unwrap())).unwrap();
+        assert!(matches!(next(&mut ws), StreamMessage::Error { .. }));
+        // 帧率为负或为 0 在解析时拒绝；过大过小的帧率截断到 [1e-3, 1e3]，都不会让连接线程 panic
+        for fps in ["-5", "0"] {
+            ws.send(Message::text(format!(r#"{{"periods": 1, "frames_per_second": {}}}"#, fps))).unwrap();
+            assert!(matches!(next(&mut ws), StreamMessage::Error { ref message } if message.contains("frames_per_second")), "{}", fps);
+        }
+        let fast = StreamRequest { frames_per_second: Some(1e300), ..StreamRequest::default() };
+        assert_eq!(fast.frame_interval().unwrap(), Duration::from_millis(1));
+        let slow = StreamRequest { frames_per_second: Some(1e-300), ..StreamRequest::default() };
+        assert_eq!(slow.frame_interval().unwrap(), Duration::from_secs(1000));
+        assert!(StreamRequest { frames_per_second: Some(f64::NAN), ..StreamRequest::default() }.frame_interval().is_err());
+
+        // 一次很长的积分中途发新请求，服务端改用新请求从头开始
+        let long = StreamRequest { periods: 1_000_000, ..StreamRequest::default() };
+        ws.send(Message::text(serde_json::to_string(&long).unwrap())).unwrap();
+        assert!(matches!(next(&mut ws), StreamMessage::Frame { .. }));
+        let short = StreamRequest { periods: 1, frame_every: 0, ..StreamRequest::default() };
+        ws.send(Message::text(serde_json::to_string(&short).unwrap())).unwrap();
+        loop {
+            if let StreamMessage::Done { periods } = next(&mut ws) {
+                assert_eq!(periods, 1);
+                break;
+            }
+        }
+        ws.close(None).unwrap();
+    }
+}
@@ -2,69 +2,409 @@
 use std::f64::consts::PI;
 use std::fs::File;
 use std::io::Write;
-use crate::model::PendulumParams;
+use std::ops::ControlFlow;
+use serde::{Deserialize, Serialize};
+use crate::error::ChaosError;
+use crate::float::Float;
+use crate::model::{Integrator, PendulumParams, StepControl};
+use crate::plan::{check_memory_budget, estimated_steps};
+use crate::progress::{with_progress, ProgressInfo};
+use crate::stroboscopic::StroboscopicMap;
+use crate::trajectory::Trajectory;
 
-#[derive(Debug, Clone, Copy)]
-pub struct State {
-    pub theta: f64,
-    pub omega: f64,
+/// 单摆的状态；F 为积分所用的浮点类型（见 float 模块），默认 f64
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct State<F: Float = f64> {
+    pub theta: F,
+    pub omega: F,
 }
 
-// 微分方程变成一个向量场，输入向量场的坐标，返回该处向量的y分量
+// 微分方程变成一个向量场，输入向量场的坐标，返回该处向量的y分量；驱动项由 params.forcing 给出、
+// 按 params.drive_type 进入方程，q 与 f_d 按 q_schedule / f_d_schedule 取 t 时刻的值
 pub fn rhs(theta: f64, omega: f64, t: f64, params: &PendulumParams) -> (f64, f64) {
+    rhs_generic(theta, omega, t, params)
+}
+
+// rhs 对浮点类型的泛型版本：参数、驱动与时间表在 f64 下求值后转换，其余运算在 F 中进行
+pub(crate) fn rhs_generic<F: Float>(theta: F, omega: F, t: F, params: &PendulumParams) -> (F, F) {
+    let t64 = t.to_f64();
     let d_theta_dt = omega;
-    let d_omega_dt = -(params.g / params.l) * theta.sin()
-        - params.q * d_theta_dt
-        + params.f_d * (params.omega_d * t).sin();
+    let d_omega_dt = -F::from_f64(params.g / params.l) * theta.sin()
+        - F::from_f64(params.q_at(t64)) * d_theta_dt
+        + F::from_f64(params.drive_torque(theta.to_f64(), t64));
     (d_theta_dt, d_omega_dt)
 }
 
 // 给出当前的状态和时间，返回下一步的状态和时间（RK4）
 pub fn rk4_step(state: &State, t: f64, params: &PendulumParams) -> (State, f64) {
-    let (k1_theta, k1_omega) = rhs(state.theta, state.omega, t, params);
+    (rk4_step_h(state, t, params.dt, params), t + params.dt)
+}
+
+// 步长为 h 的 RK4 单步
+pub(crate) fn rk4_step_h<F: Float>(state: &State<F>, t: F, h: F, params: &PendulumParams) -> State<F> {
+    let (half, two, sixth) = (F::from_f64(0.5), F::from_f64(2.0), F::from_f64(6.0));
+    let (k1_theta, k1_omega) = rhs_generic(state.theta, state.omega, t, params);
     let middle_step_1 = State {
-        theta: state.theta + 0.5 * params.dt * k1_theta,
-        omega: state.omega + 0.5 * params.dt * k1_omega,
+        theta: state.theta + half * h * k1_theta,
+        omega: state.omega + half * h * k1_omega,
     };
-    let (k2_theta, k2_omega) = rhs(middle_step_1.theta, middle_step_1.omega, t + 0.5 * params.dt, params);
+    let (k2_theta, k2_omega) = rhs_generic(middle_step_1.theta, middle_step_1.omega, t + half * h, params);
     let middle_step_2 = State {
-        theta: state.theta + 0.5 * params.dt * k2_theta,
-        omega: state.omega + 0.5 * params.dt * k2_omega,
+        theta: state.theta + half * h * k2_theta,
+        omega: state.omega + half * h * k2_omega,
     };
-    let (k3_theta, k3_omega) = rhs(middle_step_2.theta, middle_step_2.omega, t + 0.5 * params.dt, params);
+    let (k3_theta, k3_omega) = rhs_generic(middle_step_2.theta, middle_step_2.omega, t + half * h, params);
     let middle_step_3 = State {
-        theta: state.theta + params.dt * k3_theta,
-        omega: state.omega + params.dt * k3_omega,
+        theta: state.theta + h * k3_theta,
+        omega: state.omega + h * k3_omega,
     };
-    let (k4_theta, k4_omega) = rhs(middle_step_3.theta, middle_step_3.omega, t + params.dt, params);
+    let (k4_theta, k4_omega) = rhs_generic(middle_step_3.theta, middle_step_3.omega, t + h, params);
 
-    let new_theta = state.theta + params.dt / 6.0 * (k1_theta + 2.0 * k2_theta + 2.0 * k3_theta + k4_theta);
-    let new_omega = state.omega + params.dt / 6.0 * (k1_omega + 2.0 * k2_omega + 2.0 * k3_omega + k4_omega);
-    (State { theta: new_theta, omega: new_omega }, t + params.dt)
+    let new_theta = state.theta + h / sixth * (k1_theta + two * k2_theta + two * k3_theta + k4_theta);
+    let new_omega = state.omega + h / sixth * (k1_omega + two * k2_omega + two * k3_omega + k4_omega);
+    State { theta: new_theta, omega: new_omega }
 }
 
-pub fn solve(params: &PendulumParams, initial_theta: f64, initial_omega: f64) -> Vec<(f64, State)> {
-    let mut trajectory = Vec::new();
-    let mut state = State {
-        theta: initial_theta,
-        omega: initial_omega,
-    };
-    let mut t = 0.0;
-    trajectory.push((t, state.clone()));
+// 速度 Verlet 单步（步长 h）：ω 先走半步、θ 走整步、ω 再走半步。
+// q = 0 时是辛映射（含驱动时为含时哈密顿系统的辛映射）；阻尼项按显式方式处理。
+pub(crate) fn verlet_step<F: Float>(state: &State<F>, t: F, h: F, params: &PendulumParams) -> State<F> {
+    let half = F::from_f64(0.5);
+    let (_, a0) = rhs_generic(state.theta, state.omega, t, params);
+    let omega_half = state.omega + half * h * a0;
+    let theta = state.theta + h * omega_half;
+    let (_, a1) = rhs_generic(theta, omega_half, t + h, params);
+    State {
+        theta,
+        omega: omega_half + half * h * a1,
+    }
+}
+
+// Yoshida 四阶复合系数：w1, w0, w1 三段 Verlet，w0 = -2^{1/3} w1
+pub(crate) fn yoshida_weights() -> (f64, f64) {
+    let cbrt2 = 2f64.cbrt();
+    let w1 = 1.0 / (2.0 - cbrt2);
+    (w1, -cbrt2 * w1)
+}
+
+/// 按 params.integrator 选择的积分器走一步 dt，返回下一步的状态和时间
+pub fn step(state: &State, t: f64, params: &PendulumParams) -> (State, f64) {
+    (step_with(state, t, params.dt, params), t + params.dt)
+}
+
+// 按 params.integrator 走一步 h（h 可以不等于 params.dt，例如为落在周期末而截短的步）；
+// F 为 f64 以外的浮点类型时即 float 模块的低精度积分
+pub(crate) fn step_with<F: Float>(state: &State<F>, t: F, h: F, params: &PendulumParams) -> State<F> {
+    match params.integrator {
+        Integrator::Rk4 => rk4_step_h(state, t, h, params),
+        Integrator::VelocityVerlet => verlet_step(state, t, h, params),
+        Integrator::Yoshida4 => yoshida_step(state, t, h, yoshida_weights(), params),
+    }
+}
+
+// Yoshida 四阶单步（步长 h），weights 为 yoshida_weights() 的结果
+pub(crate) fn yoshida_step<F: Float>(state: &State<F>, t: F, h: F, (w1, w0): (f64, f64), params: &PendulumParams) -> State<F> {
+    let (w1, w0) = (F::from_f64(w1), F::from_f64(w0));
+    let s1 = verlet_step(state, t, w1 * h, params);
+    let s2 = verlet_step(&s1, t + w1 * h, w0 * h, params);
+    verlet_step(&s2, t + (w1 + w0) * h, w1 * h, params)
+}
+
+/// 积分到 params.t_end，返回每一步的 (t, State)（含初始点）；参数无效时返回错误而不积分
+///
+/// StepControl::Adaptive 时改用 adaptive::solve_adaptive，返回的时间点不等距；
+/// noise_intensity > 0 时改用 stochastic::solve_stochastic，积分一条由 noise_seed 决定的样本路径。
+/// 设置了 params.memory_budget_bytes 且完整轨迹的估计大小超出预算时返回 ChaosError::MemoryBudget（见 plan 模块）。
+pub fn solve(params: &PendulumParams, initial_theta: f64, initial_omega: f64) -> Result<Vec<(f64, State)>, ChaosError> {
+    solve_with_progress(params, initial_theta, initial_omega, usize::MAX, |_| ControlFlow::Continue(()))
+}
+
+/// 与 solve 相同，但按列存放（见 trajectory::Trajectory）；固定步长时边积分边写入三列，不经过行格式
+pub fn solve_trajectory(params: &PendulumParams, initial_theta: f64, initial_omega: f64) -> Result<Trajectory, ChaosError> {
+    params.validate()?;
+    if params.noise_intensity > 0.0 || params.step_control == StepControl::Adaptive {
+        return solve(params, initial_theta, initial_omega).map(Trajectory::from);
+    }
+    check_memory_budget(params, fixed_step_count(params).saturating_add(1))?;
+    Ok(TrajectoryIter::new(params, initial_theta, initial_omega).collect())
+}
+
+/// 与 solve 相同，但每 every 步调用一次 on_progress（单位为积分步）；回调返回 Break 时返回到此为止的部分轨迹
+///
+/// 自适应步长的总步数事先未知，只在积分结束后回调一次。
+pub fn solve_with_progress<F>(
+    params: &PendulumParams,
+    initial_theta: f64,
+    initial_omega: f64,
+    every: usize,
+    mut on_progress: F,
+) -> Result<Vec<(f64, State)>, ChaosError>
+where
+    F: FnMut(ProgressInfo) -> ControlFlow<()>,
+{
+    params.validate()?;
+    check_memory_budget(params, estimated_steps(params).saturating_add(1))?;
+    if params.noise_intensity > 0.0 {
+        let traj = crate::stochastic::StochasticTrajectory::new(params, initial_theta, initial_omega);
+        return Ok(with_progress(traj, every, on_progress).collect());
+    }
+    Ok(match params.step_control {
+        StepControl::Adaptive => {
+            let traj = crate::adaptive::solve_adaptive(params, initial_theta, initial_omega);
+            let _ = on_progress(ProgressInfo {
+                done: traj.len(),
+                total: traj.len(),
+            });
+            traj
+        }
+        StepControl::FixedDt | StepControl::StepsPerPeriod { .. } => {
+            with_progress(TrajectoryIter::new(params, initial_theta, initial_omega), every, on_progress).collect()
+        }
+    })
+}
+
+// 积分到 t_end 的固定步数；留 1e-9 步的余量，避免 t_end 恰为整数步（如 T / steps 的整数倍）时因舍入少一步
+pub(crate) fn step_count(t_end: f64, dt: f64) -> usize {
+    (t_end / dt + 1e-9).max(0.0) as usize
+}
+
+// exact_stroboscopic 时每个驱动周期的步数：前 k - 1 步为 dt，第 k 步截短到周期末（不会短于 1e-9 dt）
+fn exact_period_steps(params: &PendulumParams, dt: f64) -> usize {
+    let period = 2.0 * PI / params.omega_d.abs();
+    ((period / dt - 1e-9).ceil() as usize).max(1)
+}
+
+// TrajectoryIter 的总步数（不含初始点）
+pub(crate) fn fixed_step_count(params: &PendulumParams) -> usize {
+    let dt = params.effective_dt();
+    if !params.exact_stroboscopic {
+        return step_count(params.t_end, dt);
+    }
+    // 完整的周期各 k 步；最后不足一个周期的部分只走不越过 t_end 的整步
+    let period = 2.0 * PI / params.omega_d.abs();
+    let k = exact_period_steps(params, dt);
+    let m = step_count(params.t_end, period);
+    let rest = (params.t_end - m as f64 * period).max(0.0);
+    m * k + step_count(rest, dt).min(k - 1)
+}
+
+/// 惰性轨迹（积分器由 params.integrator 决定）：每次 next() 才积分一步，依次给出与 solve 相同的 (t, State)（含初始点）
+///
+/// 步长取 params.effective_dt()；StepControl::Adaptive 时仍按 dt 固定步长积分。
+/// params.direction 为 Backward 时 t 依次为 0, -dt, -2dt, ...，直到 -t_end。
+/// params.exact_stroboscopic 时每个驱动周期的最后一步截短，t = nT 恰好出现在轨迹上。
+/// 不保存历史，适合长时间积分时边算边处理。
+#[derive(Debug, Clone)]
+pub struct TrajectoryIter {
+    params: PendulumParams,
+    state: State,
+    t: f64,
+    remaining: usize,
+    started: bool,
+    // exact_stroboscopic 时为 (驱动周期, 每周期步数)，以及已走的步数
+    period_steps: Option<(f64, usize)>,
+    taken: usize,
+}
+
+impl TrajectoryIter {
+    pub fn new(params: &PendulumParams, initial_theta: f64, initial_omega: f64) -> Self {
+        let mut params = params.clone();
+        params.dt = params.effective_dt();
+        let remaining = fixed_step_count(&params);
+        // 反向积分时周期与步长都取负，t = nT 依次为 0, -T, -2T, ...
+        let sign = params.direction.sign();
+        let period_steps = params
+            .exact_stroboscopic
+            .then(|| (sign * 2.0 * PI / params.omega_d.abs(), exact_period_steps(&params, params.dt)));
+        params.dt *= sign;
+        TrajectoryIter {
+            remaining,
+            params,
+            state: State {
+                theta: initial_theta,
+                omega: initial_omega,
+            },
+            t: 0.0,
+            started: false,
+            period_steps,
+            taken: 0,
+        }
+    }
 
-    // 为了避免浮点累计误差导致多一步或少一步，按固定步数迭代
-    let steps = (params.t_end / params.dt) as usize;
-    for _ in 0..steps {
-        let (new_state, new_t) = rk4_step(&state, t, params);
-        state = new_state;
-        t = new_t;
-        trajectory.push((t, state.clone()));
+    // 迭代结束时到达的时刻（不实际积分）
+    fn end_time(&self) -> f64 {
+        match self.period_steps {
+            Some((period, k)) => {
+                let total = self.taken + self.remaining;
+                (total / k) as f64 * period + (total % k) as f64 * self.params.dt
+            }
+            None => self.t + self.remaining as f64 * self.params.dt,
+        }
+    }
+}
+
+impl Iterator for TrajectoryIter {
+    type Item = (f64, State);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.started {
+            self.started = true;
+            return Some((self.t, self.state));
+        }
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let (state, t) = match self.period_steps {
+            Some((period, k)) => {
+                let (n, j) = (self.taken / k, self.taken % k);
+                // 周期内的时刻从周期起点重新计算，末步直接取 (n + 1)·T，不累积舍入误差
+                let t = if j + 1 == k {
+                    (n + 1) as f64 * period
+                } else {
+                    n as f64 * period + (j + 1) as f64 * self.params.dt
+                };
+                (step_with(&self.state, self.t, t - self.t, &self.params), t)
+            }
+            None => step(&self.state, self.t, &self.params),
+        };
+        self.taken += 1;
+        self.state = state;
+        self.t = t;
+        Some((t, state))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.remaining + usize::from(!self.started);
+        (n, Some(n))
+    }
+}
+
+impl ExactSizeIterator for TrajectoryIter {}
+
+/// 以整数个驱动周期积分：dt = 周期 / steps_per_period，共 n_periods 个周期
+///
+/// 末状态恰好落在 t = n_periods·T 上，驱动相位回到 0，
+/// 可以直接作为下一段（例如参数扫描中下一个参数值）的初值。
+/// 参数无效或 omega_d = 0（周期无定义）时返回错误而不积分。
+pub fn solve_periods(
+    params: &PendulumParams,
+    initial_theta: f64,
+    initial_omega: f64,
+    n_periods: usize,
+    steps_per_period: usize,
+) -> Result<Vec<(f64, State)>, ChaosError> {
+    params.validate_periodic()?;
+    let mut p = params.clone();
+    p.set_steps_per_period(steps_per_period);
+    // 多加半步，保证 t_end / dt 向下取整后恰好是 n_periods * steps_per_period
+    p.t_end = (n_periods * steps_per_period) as f64 * p.dt + 0.5 * p.dt;
+    Ok(TrajectoryIter::new(&p, initial_theta, initial_omega).collect())
+}
+
+/// 从 solve_periods 生成的轨迹中取庞加莱点
+///
+/// 第 n 个周期末恰好是下标 n * steps_per_period，无需插值；
+/// 返回第 transient_periods + 1 到 transient_periods + sample_periods 个周期末的 (theta, omega)。
+pub fn stroboscopic_samples(
+    traj: &[(f64, State)],
+    steps_per_period: usize,
+    transient_periods: usize,
+    sample_periods: usize,
+) -> Vec<(f64, f64)> {
+    ((transient_periods + 1)..=(transient_periods + sample_periods))
+        .filter_map(|n| traj.get(n * steps_per_period))
+        .map(|(_, s)| (wrap_angle(s.theta), s.omega))
+        .collect()
+}
+
+/// 过渡期结束时的状态快照，用于热启动
+///
+/// 末状态位于整周期 t = transient_periods·T 处（驱动相位为 0），
+/// 因此可以直接作为 t = 0 的初值继续积分，也可以保存到 JSON 文件供之后的运行复用。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WarmStart {
+    pub params: PendulumParams,
+    pub transient_periods: usize,
+    pub state: State,
+}
+
+impl WarmStart {
+    /// 从初值积分 transient_periods 个整周期，记录末状态（θ 折叠到 [-π, π)）；omega_d = 0 等无效参数返回错误
+    pub fn compute(
+        params: &PendulumParams,
+        initial_theta: f64,
+        initial_omega: f64,
+        transient_periods: usize,
+    ) -> Result<Self, ChaosError> {
+        let spp = steps_per_period(params);
+        let traj = solve_periods(params, initial_theta, initial_omega, transient_periods, spp)?;
+        let (_, last) = traj[traj.len() - 1];
+        Ok(WarmStart {
+            params: params.clone(),
+            transient_periods,
+            state: State {
+                theta: wrap_angle(last.theta),
+                omega: last.omega,
+            },
+        })
+    }
+
+    pub fn save(&self, path: &str) -> std::io::Result<()> {
+        let f = File::create(path)?;
+        serde_json::to_writer_pretty(f, self)?;
+        Ok(())
     }
-    trajectory
+
+    pub fn load(path: &str) -> std::io::Result<Self> {
+        let f = File::open(path)?;
+        Ok(serde_json::from_reader(std::io::BufReader::new(f))?)
+    }
+}
+
+/// 频闪映射 F^periods：从 t = 0（驱动相位 0）出发积分 periods 个驱动周期，每个周期 steps_per_period(params) 步
+///
+/// 积分器由 params.integrator 决定；params.direction 为 Backward 时积分到 t = -periods·T，即逆映射 F^{-periods}。
+/// θ 不折叠。不检查参数，omega_d 须非零。反复迭代同一参数下的映射时直接构造 StroboscopicMap。
+pub fn stroboscopic_map(params: &PendulumParams, state: State, periods: usize) -> State {
+    StroboscopicMap::new(params).iterate_n(state, periods)
+}
+
+/// 每个驱动周期的步数：StepsPerPeriod 时即为设定值，否则按当前 dt 估计（至少 1 步）
+///
+/// 周期取 2π / |omega_d|。不检查参数：omega_d = 0 时没有周期（FixedDt 下结果为 usize::MAX），
+/// 调用前须先通过 params.validate_periodic()。
+pub fn steps_per_period(params: &PendulumParams) -> usize {
+    if let StepControl::StepsPerPeriod { steps } = params.step_control {
+        return steps.max(1);
+    }
+    let period = 2.0 * PI / params.omega_d.abs();
+    ((period / params.dt).round() as usize).max(1)
+}
+
+/// 把角度折叠到 [-π, π)
+pub fn wrap_angle(theta: f64) -> f64 {
+    let wrapped = (theta + PI).rem_euclid(2.0 * PI) - PI;
+    // θ + π 是绝对值很小的负数时 rem_euclid 会舍入到 2π，结果落在区间外的 π 上
+    if wrapped >= PI { -PI } else { wrapped }
 }
 
-fn wrap_angle(theta: f64) -> f64 {
-    (theta + PI).rem_euclid(2.0 * PI) - PI
+/// 展开角（lift）所在的圈数 n，使 theta = wrap_angle(theta) + 2πn
+pub fn winding_number(theta: f64) -> i64 {
+    ((theta - wrap_angle(theta)) / (2.0 * PI)).round() as i64
+}
+
+/// 由折叠过的角度序列恢复连续的展开角（lift）：逐段累加 wrap_angle(θ_{k+1} - θ_k)
+///
+/// 要求相邻两点间转过不到半圈；第一个点保持原值。对已经连续的序列结果不变（至多差舍入误差）。
+pub fn unwrap_angles(thetas: &[f64]) -> Vec<f64> {
+    let mut lifted = Vec::with_capacity(thetas.len());
+    for (i, &theta) in thetas.iter().enumerate() {
+        lifted.push(match i {
+            0 => theta,
+            _ => lifted[i - 1] + wrap_angle(theta - thetas[i - 1]),
+        });
+    }
+    lifted
 }
 
 /// 计算庞加莱截面：在每个驱动周期处采样。
@@ -75,131 +415,601 @@ fn wrap_angle(theta: f64) -> f64 {
 /// sample_periods: 采样的周期数
 /// 返回值：Vec<(theta, omega)>，按采样顺序排列
 /// 改进的庞加莱采样：在精确的驱动周期时刻采样
+/// 设置了 params.memory_budget_bytes 且完整轨迹估计超出预算时，自动改用 poincare_streaming
+/// StepControl::Adaptive 时改用 adaptive::poincare_adaptive（步长在周期末截短，无需插值）；
+/// noise_intensity > 0 时改用 stochastic::poincare_stochastic
+/// 参数无效、omega_d = 0 或 t_end 不足以覆盖 transient_periods + sample_periods 个周期时返回错误
 pub fn poincare(
     params: &PendulumParams,
     initial_theta: f64,
     initial_omega: f64,
     transient_periods: usize,
     sample_periods: usize,
+) -> Result<Vec<(f64, f64)>, ChaosError> {
+    check_sampling_span(params, transient_periods + sample_periods)?;
+    if params.noise_intensity > 0.0 {
+        return Ok(crate::stochastic::poincare_stochastic(params, initial_theta, initial_omega, transient_periods, sample_periods));
+    }
+    if params.step_control == StepControl::Adaptive {
+        return Ok(crate::adaptive::poincare_adaptive(params, initial_theta, initial_omega, transient_periods, sample_periods));
+    }
+    if params
+        .memory_budget_bytes
+        .is_some_and(|budget| estimated_trajectory_bytes(params) > budget)
+    {
+        return Ok(poincare_streaming(params, initial_theta, initial_omega, transient_periods, sample_periods));
+    }
+    // 先计算完整轨迹，再单向扫描一次取样
+    let traj = solve(params, initial_theta, initial_omega)?;
+    sample_poincare_from_trajectory(&traj, params, transient_periods, sample_periods).complete()
+}
+
+/// 流式庞加莱采样，每 every 个积分步调用一次 on_progress；回调返回 Break 时返回已经得到的庞加莱点
+///
+/// 不保存完整轨迹，结果与 poincare 相同；自适应步长时只在结束后回调一次。
+pub fn poincare_with_progress<F>(
+    params: &PendulumParams,
+    initial_theta: f64,
+    initial_omega: f64,
+    transient_periods: usize,
+    sample_periods: usize,
+    every: usize,
+    mut on_progress: F,
+) -> Result<Vec<(f64, f64)>, ChaosError>
+where
+    F: FnMut(ProgressInfo) -> ControlFlow<()>,
+{
+    check_sampling_span(params, transient_periods + sample_periods)?;
+    if params.noise_intensity > 0.0 {
+        let mut p = params.clone();
+        p.set_steps_per_period(steps_per_period(params));
+        let traj = crate::stochastic::StochasticTrajectory::new(&p, initial_theta, initial_omega);
+        return Ok(sample_poincare_from_iter(with_progress(traj, every, on_progress), &p, transient_periods, sample_periods));
+    }
+    if params.step_control == StepControl::Adaptive {
+        let samples = crate::adaptive::poincare_adaptive(params, initial_theta, initial_omega, transient_periods, sample_periods);
+        let _ = on_progress(ProgressInfo {
+            done: 1,
+            total: 1,
+        });
+        return Ok(samples);
+    }
+    let traj = with_progress(TrajectoryIter::new(params, initial_theta, initial_omega), every, on_progress);
+    Ok(sample_poincare_from_iter(traj, params, transient_periods, sample_periods))
+}
+
+// 检查参数，并确认按固定步数积分到的最后时刻覆盖第 n_periods 个周期末（留 1e-9 的相对余量）
+fn check_sampling_span(params: &PendulumParams, n_periods: usize) -> Result<(), ChaosError> {
+    params.validate_periodic()?;
+    let needed = n_periods as f64 * 2.0 * PI / params.omega_d.abs();
+    let available = match params.step_control {
+        StepControl::Adaptive => params.t_end,
+        StepControl::FixedDt | StepControl::StepsPerPeriod { .. } => TrajectoryIter::new(params, 0.0, 0.0).end_time().abs(),
+    };
+    if available < needed * (1.0 - 1e-9) {
+        return Err(ChaosError::InsufficientTrajectory { needed, available });
+    }
+    Ok(())
+}
+
+/// solve 返回的完整轨迹所占内存的估计值（字节）
+pub fn estimated_trajectory_bytes(params: &PendulumParams) -> usize {
+    let steps = fixed_step_count(params);
+    (steps + 1).saturating_mul(std::mem::size_of::<(f64, State)>())
+}
+
+/// 与 poincare 结果相同的流式版本：边积分边采样，只保留相邻两步，内存占用与 t_end 无关
+pub fn poincare_streaming(
+    params: &PendulumParams,
+    initial_theta: f64,
+    initial_omega: f64,
+    transient_periods: usize,
+    sample_periods: usize,
+) -> Vec<(f64, f64)> {
+    let traj = TrajectoryIter::new(params, initial_theta, initial_omega);
+    sample_poincare_from_iter(traj, params, transient_periods, sample_periods)
+}
+
+/// 与 sample_poincare_from_trajectory 相同的采样规则，但逐点消费轨迹迭代器
+///
+/// 取满 sample_periods 个点后立即停止消费，不会把轨迹剩余部分积分完。
+/// StepControl::StepsPerPeriod 时第 n 个周期末恰好是第 n·steps 个点，直接取值而不插值；
+/// 只有轨迹从 t = 0 开始、前两点的间隔等于 params.effective_dt() 时才走这条捷径（TrajectoryIter / solve 的输出），
+/// 截取过的片段或续算的轨迹按时间戳插值。
+pub fn sample_poincare_from_iter<I: Iterator<Item = (f64, State)>>(
+    mut traj: I,
+    params: &PendulumParams,
+    transient_periods: usize,
+    sample_periods: usize,
 ) -> Vec<(f64, f64)> {
+    // 反向积分时采样时刻为 -nT
+    let sign = params.direction.sign();
+    let head: Vec<(f64, State)> = traj.by_ref().take(2).collect();
+    let dt = params.effective_dt();
+    let from_zero_on_grid = match head.as_slice() {
+        [(t0, _), (t1, _)] => *t0 == 0.0 && (t1 - t0 - sign * dt).abs() <= 1e-9 * dt,
+        _ => false,
+    };
+    let traj = head.into_iter().chain(traj);
+    if let StepControl::StepsPerPeriod { steps } = params.step_control
+        && from_zero_on_grid
+    {
+        return traj
+            .step_by(steps.max(1))
+            .skip(transient_periods + 1)
+            .take(sample_periods)
+            .map(|(_, s)| (wrap_angle(s.theta), s.omega))
+            .collect();
+    }
     let period = 2.0 * PI / params.omega_d;
-    
-    // 先计算完整轨迹
-    let traj = solve(params, initial_theta, initial_omega);
-    
-    let mut samples = Vec::new();
-    
-    for n in (transient_periods + 1)..=(transient_periods + sample_periods) {
-        let target_time = (n as f64) * period;
-        
-        // 找到目标时间前后的点
-        if let Some(idx) = traj.iter().position(|(t, _)| *t >= target_time) {
-            if idx > 0 {
-                let (t1, s1) = traj[idx - 1];
-                let (t2, s2) = traj[idx];
-                
-                // 线性插值
-                let frac = (target_time - t1) / (t2 - t1);
-                
-                // 角度插值需要考虑周期性
-                let mut theta_diff = s2.theta - s1.theta;
-                theta_diff = (theta_diff + PI).rem_euclid(2.0 * PI) - PI;
-                
-                let theta_interp = s1.theta + frac * theta_diff;
-                let omega_interp = s1.omega + frac * (s2.omega - s1.omega);
-                
-                samples.push((wrap_angle(theta_interp), omega_interp));
-            }
+    let mut samples = Vec::with_capacity(sample_periods);
+    let mut n = transient_periods + 1;
+    let last_n = transient_periods + sample_periods;
+    let mut prev: Option<(f64, State)> = None;
+    let mut before: Option<(f64, State)> = None;
+
+    for (t, s) in traj {
+        if n > last_n {
+            break;
         }
+        // 一步之内可能跨过多个采样时刻（dt 大于周期时）
+        while n <= last_n && sign * t >= n as f64 * period {
+            let target = sign * n as f64 * period;
+            samples.push(match prev {
+                // exact_stroboscopic 的轨迹恰好落在 nT 上，直接取值
+                Some((t1, s1)) if sign * (t - t1) > 0.0 && t != target => hermite_sample(t1, &s1, t, &s, target, params),
+                _ => (wrap_angle(s.theta), s.omega),
+            });
+            n += 1;
+        }
+        before = prev;
+        prev = Some((t, s));
+    }
+    // 时间按步累加的舍入误差可能让轨迹差一点到不了最后一个采样时刻（check_sampling_span 允许 1e-9 的相对差），
+    // 此时用最后两点外推这一点，而不是少给一个点
+    if n == last_n
+        && let (Some((t1, s1)), Some((t2, s2))) = (before, prev)
+        && sign * t2 >= n as f64 * period * (1.0 - 1e-9)
+    {
+        samples.push(hermite_sample(t1, &s1, t2, &s2, sign * n as f64 * period, params));
     }
-    
     samples
 }
 
-/// 把 poincare 结果写成 CSV 文件（两列：theta,omega）
+/// 对每个初值 (θ0, ω0) 各算一次 poincare，结果写进同一个 CSV（三列：run_id,theta,omega），
+/// run_id 为初值在 initial_conditions 中的下标，同一参数下共存的几个吸引子可在一张截面上区分
 pub fn write_poincare_csv(
     path: &str,
     params: &PendulumParams,
-    initial_theta: f64,
-    initial_omega: f64,
+    initial_conditions: &[(f64, f64)],
     transient_periods: usize,
     sample_periods: usize,
-) -> std::io::Result<()> {
-    let samples = poincare(params, initial_theta, initial_omega, transient_periods, sample_periods);
-    let mut f = File::create(path)?;
+) -> Result<(), ChaosError> {
+    let runs = poincare_runs(params, initial_conditions, transient_periods, sample_periods)?;
+    Ok(write_poincare_runs_csv(path, &runs)?)
+}
+
+/// 每个初值各算一次 poincare（并行），按初值顺序返回
+pub fn poincare_runs(
+    params: &PendulumParams,
+    initial_conditions: &[(f64, f64)],
+    transient_periods: usize,
+    sample_periods: usize,
+) -> Result<Vec<Vec<(f64, f64)>>, ChaosError> {
+    use rayon::prelude::*;
+    initial_conditions
+        .par_iter()
+        .map(|&(theta, omega)| poincare(params, theta, omega, transient_periods, sample_periods))
+        .collect()
+}
+
+/// 把多个初值的庞加莱点写成 CSV（三列：run_id,theta,omega），run_id 为 runs 中的下标
+pub fn write_poincare_runs_csv(path: &str, runs: &[Vec<(f64, f64)>]) -> std::io::Result<()> {
+    let mut f = std::io::BufWriter::new(File::create(path)?);
+    writeln!(f, "run_id,theta,omega")?;
+    for (run_id, samples) in runs.iter().enumerate() {
+        for (th, om) in samples {
+            writeln!(f, "{},{:.12},{:.12}", run_id, th, om)?;
+        }
+    }
+    f.flush()
+}
+
+/// 把已经算好的庞加莱点写成 CSV（两列：theta,omega）
+pub fn write_poincare_samples_csv(path: &str, samples: &[(f64, f64)]) -> std::io::Result<()> {
+    let mut f = std::io::BufWriter::new(File::create(path)?);
     writeln!(f, "theta,omega")?;
     for (th, om) in samples {
         writeln!(f, "{:.12},{:.12}", th, om)?;
     }
-    Ok(())
+    f.flush()
 }
 
-/// 从已经计算好的轨迹中按驱动周期采样（线性/角度插值）
+/// 扩展相空间的环面坐标：(θ mod 2π, 驱动相位 Ωt mod 2π, ω)，前两者取值于 [0, 2π)
+pub fn torus_coordinates(traj: &[(f64, State)], params: &PendulumParams) -> Vec<(f64, f64, f64)> {
+    traj.iter()
+        .map(|(t, s)| {
+            (
+                s.theta.rem_euclid(2.0 * PI),
+                (params.omega_d * t).rem_euclid(2.0 * PI),
+                s.omega,
+            )
+        })
+        .collect()
+}
+
+/// 把轨迹按环面坐标写成 CSV（三列：theta,phase,omega），可直接做三维可视化
+pub fn write_torus_csv(path: &str, traj: &[(f64, State)], params: &PendulumParams) -> std::io::Result<()> {
+    let mut f = std::io::BufWriter::new(File::create(path)?);
+    writeln!(f, "theta,phase,omega")?;
+    for (theta, phase, omega) in torus_coordinates(traj, params) {
+        writeln!(f, "{:.12},{:.12},{:.12}", theta, phase, omega)?;
+    }
+    f.flush()
+}
+
+/// 庞加莱采样停止的原因
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SamplingStop {
+    /// 取满了要求的点数
+    Complete,
+    /// 轨迹在 last_time 处结束，没有覆盖到最后一个采样时刻 needed
+    TrajectoryEnded { last_time: f64, needed: f64 },
+    /// 第 index 个点的时间 time 没有沿积分方向前进（重复或倒退的时间戳），只用了它之前的部分
+    NonMonotonicTime { index: usize, time: f64 },
+}
+
+/// 从给定轨迹采样的结果：实际得到的点与停止原因
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoincareSampling {
+    pub samples: Vec<(f64, f64)>,
+    /// 要求的点数 sample_periods
+    pub requested: usize,
+    pub stop: SamplingStop,
+}
+
+impl PoincareSampling {
+    pub fn is_complete(&self) -> bool {
+        self.stop == SamplingStop::Complete
+    }
+
+    /// 取满时返回采样点，否则返回错误而不是截短的结果
+    ///
+    /// 轨迹提前结束时为 InsufficientTrajectory，时间戳不单调时为 NonMonotonicTrajectory。
+    pub fn complete(self) -> Result<Vec<(f64, f64)>, ChaosError> {
+        match self.stop {
+            SamplingStop::Complete => Ok(self.samples),
+            SamplingStop::TrajectoryEnded { last_time, needed } => Err(ChaosError::InsufficientTrajectory {
+                needed: needed.abs(),
+                available: last_time.abs(),
+            }),
+            SamplingStop::NonMonotonicTime { index, time } => Err(ChaosError::NonMonotonicTrajectory { index, time }),
+        }
+    }
+}
+
+/// 从已经计算好的轨迹中按驱动周期采样（三次 Hermite 插值）
+///
+/// 轨迹中第一个不沿积分方向前进的时间戳（重复或倒退）之后的部分不参与采样；
+/// 采不满 sample_periods 个点时 stop 给出原因（轨迹提前结束或时间戳不单调），需要报错时用 complete()。
 pub fn sample_poincare_from_trajectory(
-    traj: &Vec<(f64, State)>,
+    traj: &[(f64, State)],
     params: &PendulumParams,
     transient_periods: usize,
     sample_periods: usize,
-) -> Vec<(f64, f64)> {
-    let period = 2.0 * PI / params.omega_d;
+) -> PoincareSampling {
+    let sign = params.direction.sign();
+    let broken = traj.windows(2).position(|w| sign * (w[1].0 - w[0].0) <= 0.0).map(|i| i + 1);
+    let usable = &traj[..broken.unwrap_or(traj.len())];
+    // 采样时刻单调递增，沿轨迹单向推进一次即可，总代价 O(N + M)
+    let samples = sample_poincare_from_iter(usable.iter().copied(), params, transient_periods, sample_periods);
+    let stop = match broken {
+        _ if samples.len() >= sample_periods => SamplingStop::Complete,
+        Some(index) => SamplingStop::NonMonotonicTime { index, time: traj[index].0 },
+        None => SamplingStop::TrajectoryEnded {
+            last_time: traj.last().map_or(0.0, |p| p.0),
+            needed: sign * (transient_periods + sample_periods) as f64 * 2.0 * PI / params.omega_d,
+        },
+    };
+    PoincareSampling {
+        samples,
+        requested: sample_periods,
+        stop,
+    }
+}
 
-    let mut samples = Vec::with_capacity(sample_periods);
+/// 在相邻两点 (t1, s1)、(t2, s2) 之间做线性/角度插值，返回 (折叠后的 theta, omega)
+pub fn interpolate_sample(t1: f64, s1: &State, t2: f64, s2: &State, t: f64) -> (f64, f64) {
+    let alpha = (t - t1) / (t2 - t1);
+    // 角度需要做周期展开再插值
+    let dtheta = wrap_angle(s2.theta - s1.theta);
+    let theta_interp = s1.theta + alpha * dtheta;
+    let omega_interp = s1.omega + alpha * (s2.omega - s1.omega);
+    (wrap_angle(theta_interp), omega_interp)
+}
 
-    // 生成要采样的时刻列表（以驱动周期为单位，从 1..=total_periods）
-    let start_n = transient_periods + 1;
-    let end_n = transient_periods + sample_periods;
-
-    for n in start_n..=end_n {
-        let t_sample = (n as f64) * period;
-        // 在 traj 中找到包含 t_sample 的相邻点
-        if let Some(i) = traj.iter().position(|(t, _)| *t >= t_sample) {
-            if i == 0 {
-                // 如果第一个点就大于等于采样时间，直接取该点
-                let (_, s) = &traj[i];
-                samples.push((wrap_angle(s.theta), s.omega));
+/// 三次 Hermite 插值（稠密输出）：用两端的状态与导数 (θ' = ω, ω' 由 rhs 给出) 构造 t1..t2 上的三次多项式
+///
+/// θ 不折叠，保持轨迹连续。误差为 O(h⁴)，与 RK4 的局部精度相当，远小于线性插值的 O(h²)。
+pub fn hermite_state(t1: f64, s1: &State, t2: f64, s2: &State, t: f64, params: &PendulumParams) -> State {
+    let h = t2 - t1;
+    if h <= 0.0 {
+        return *s1;
+    }
+    let (d_theta1, d_omega1) = rhs(s1.theta, s1.omega, t1, params);
+    let (d_theta2, d_omega2) = rhs(s2.theta, s2.omega, t2, params);
+    let u = (t - t1) / h;
+    let (u2, u3) = (u * u, u * u * u);
+    // Hermite 基函数
+    let h00 = 2.0 * u3 - 3.0 * u2 + 1.0;
+    let h10 = u3 - 2.0 * u2 + u;
+    let h01 = -2.0 * u3 + 3.0 * u2;
+    let h11 = u3 - u2;
+    State {
+        theta: h00 * s1.theta + h10 * h * d_theta1 + h01 * s2.theta + h11 * h * d_theta2,
+        omega: h00 * s1.omega + h10 * h * d_omega1 + h01 * s2.omega + h11 * h * d_omega2,
+    }
+}
+
+/// 与 hermite_state 相同的三次插值，返回 (折叠后的 theta, omega)，供庞加莱采样使用
+pub fn hermite_sample(t1: f64, s1: &State, t2: f64, s2: &State, t: f64, params: &PendulumParams) -> (f64, f64) {
+    let s = hermite_state(t1, s1, t2, s2, t, params);
+    (wrap_angle(s.theta), s.omega)
+}
+
+/// 轨迹在任意时刻 t 的状态（三次 Hermite 稠密输出，θ 不折叠）；t 超出轨迹时间范围时返回 None
+///
+/// 轨迹须按时间升序（solve、solve_adaptive 的输出均满足），时间点可以不等距。
+pub fn evaluate_at(traj: &[(f64, State)], params: &PendulumParams, t: f64) -> Option<State> {
+    let i = traj.partition_point(|(ti, _)| *ti < t);
+    let (t2, s2) = traj.get(i)?;
+    if *t2 == t {
+        return Some(*s2);
+    }
+    let (t1, s1) = traj.get(i.checked_sub(1)?)?;
+    Some(hermite_state(*t1, s1, *t2, s2, t, params))
+}
+
+/// 在相邻两点之间线性插值出 t 时刻的完整状态（θ 不折叠，保持轨迹连续）
+pub fn interpolate_state(t1: f64, s1: &State, t2: f64, s2: &State, t: f64) -> State {
+    let alpha = if t2 > t1 { (t - t1) / (t2 - t1) } else { 0.0 };
+    State {
+        theta: s1.theta + alpha * (s2.theta - s1.theta),
+        omega: s1.omega + alpha * (s2.omega - s1.omega),
+    }
+}
+
+// 轨迹在 t 处的插值状态；t 超出轨迹时间范围时返回 None
+fn state_at(traj: &[(f64, State)], t: f64) -> Option<State> {
+    let i = traj.partition_point(|(ti, _)| *ti < t);
+    if i == traj.len() {
+        return None;
+    }
+    let (t2, s2) = &traj[i];
+    if *t2 == t {
+        return Some(*s2);
+    }
+    if i == 0 {
+        return None;
+    }
+    let (t1, s1) = &traj[i - 1];
+    Some(interpolate_state(*t1, s1, *t2, s2, t))
+}
+
+/// 按时间范围截取轨迹片段 [t_start, t_end]
+///
+/// 两端点按线性插值补齐到恰好 t_start 与 t_end（超出轨迹范围时截到轨迹端点），
+/// 中间保留原始积分点。t_start >= t_end 或区间与轨迹不相交时返回空 Vec。
+pub fn slice_by_time(traj: &[(f64, State)], t_start: f64, t_end: f64) -> Vec<(f64, State)> {
+    if traj.is_empty() || t_start >= t_end {
+        return Vec::new();
+    }
+    let t_start = t_start.max(traj[0].0);
+    let t_end = t_end.min(traj[traj.len() - 1].0);
+    if t_start > t_end {
+        return Vec::new();
+    }
+
+    let first = traj.partition_point(|(t, _)| *t <= t_start);
+    let last = traj.partition_point(|(t, _)| *t < t_end);
+    let mut out = Vec::with_capacity(last.saturating_sub(first) + 2);
+    if let Some(s) = state_at(traj, t_start) {
+        out.push((t_start, s));
+    }
+    out.extend_from_slice(&traj[first..last]);
+    if t_end > t_start
+        && let Some(s) = state_at(traj, t_end)
+    {
+        out.push((t_end, s));
+    }
+    out
+}
+
+/// 按驱动周期范围截取轨迹片段，即时间范围 [start_period·T, end_period·T]
+pub fn slice_by_periods(
+    traj: &[(f64, State)],
+    params: &PendulumParams,
+    start_period: usize,
+    end_period: usize,
+) -> Vec<(f64, State)> {
+    let period = 2.0 * PI / params.omega_d;
+    slice_by_time(traj, start_period as f64 * period, end_period as f64 * period)
+}
+
+/// 在任意给定时刻（可不均匀、可乱序）上对轨迹插值，返回 Vec<(t, State)>，θ 折叠到 [-π, π)
+///
+/// 使用与庞加莱采样相同的三次 Hermite 插值；超出轨迹时间范围的时刻会被跳过。
+pub fn resample(traj: &[(f64, State)], params: &PendulumParams, times: &[f64]) -> Vec<(f64, State)> {
+    times
+        .iter()
+        .filter_map(|&t| {
+            let i = traj.partition_point(|(ti, _)| *ti < t);
+            let (t2, s2) = traj.get(i)?;
+            let (theta, omega) = if *t2 == t {
+                (wrap_angle(s2.theta), s2.omega)
+            } else if i == 0 {
+                return None;
             } else {
                 let (t1, s1) = &traj[i - 1];
-                let (t2, s2) = &traj[i];
-                let dt = t2 - t1;
-                if dt <= 0.0 {
-                    let (_, s) = &traj[i];
-                    samples.push((wrap_angle(s.theta), s.omega));
-                } else {
-                    let alpha = (t_sample - t1) / dt;
-                    // 角度需要做周期展开再插值
-                    let mut dtheta = s2.theta - s1.theta;
-                    dtheta = (dtheta + PI).rem_euclid(2.0 * PI) - PI;
-                    let theta_interp = s1.theta + alpha * dtheta;
-                    let omega_interp = s1.omega + alpha * (s2.omega - s1.omega);
-                    samples.push((wrap_angle(theta_interp), omega_interp));
-                }
+                hermite_sample(*t1, s1, *t2, s2, t, params)
+            };
+            Some((t, State { theta, omega }))
+        })
+        .collect()
+}
+
+/// 把重采样结果写成 CSV（三列：t,theta,omega）
+pub fn write_resampled_csv(path: &str, samples: &[(f64, State)]) -> std::io::Result<()> {
+    let mut f = std::io::BufWriter::new(File::create(path)?);
+    writeln!(f, "t,theta,omega")?;
+    for (t, s) in samples {
+        writeln!(f, "{:.12},{:.12},{:.12}", t, s.theta, s.omega)?;
+    }
+    f.flush()
+}
+
+/// 边积分边把轨迹写成 CSV（三列：t,theta,omega），θ 不折叠；不在内存中保存轨迹
+pub fn write_trajectory_csv(
+    path: &str,
+    params: &PendulumParams,
+    initial_theta: f64,
+    initial_omega: f64,
+) -> Result<(), ChaosError> {
+    params.validate()?;
+    let mut f = std::io::BufWriter::new(File::create(path)?);
+    writeln!(f, "t,theta,omega")?;
+    for (t, s) in TrajectoryIter::new(params, initial_theta, initial_omega) {
+        writeln!(f, "{:.12},{:.12},{:.12}", t, s.theta, s.omega)?;
+    }
+    f.flush()?;
+    Ok(())
+}
+
+/// 相图导出时 θ 的取值方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AnglePolicy {
+    /// 不折叠的原始（展开）角度
+    #[default]
+    Raw,
+    /// 折叠到 [-π, π)
+    Symmetric,
+    /// 折叠到 [0, 2π)
+    Positive,
+}
+
+impl AnglePolicy {
+    pub fn apply(&self, theta: f64) -> f64 {
+        match self {
+            AnglePolicy::Raw => theta,
+            AnglePolicy::Symmetric => wrap_angle(theta),
+            AnglePolicy::Positive => theta.rem_euclid(2.0 * PI),
+        }
+    }
+}
+
+/// 把整条轨迹写成相图 CSV（三列：t,theta,omega），θ 按 policy 处理
+pub fn write_phase_portrait_csv(path: &str, traj: &[(f64, State)], policy: AnglePolicy) -> std::io::Result<()> {
+    let mut f = std::io::BufWriter::new(File::create(path)?);
+    writeln!(f, "t,theta,omega")?;
+    for (t, s) in traj {
+        writeln!(f, "{:.12},{:.12},{:.12}", t, policy.apply(s.theta), s.omega)?;
+    }
+    f.flush()
+}
+
+/// 一次积分同时得到 K 个相位上的庞加莱截面
+///
+/// 第 j 个截面（j = 0..k）在 t = (n + j/k)·T 处采样，n 取过渡期之后的 sample_periods 个周期；
+/// 返回 Vec（长度 k），第 j 个元素为该相位下按时间顺序排列的 (theta, omega)。
+/// 采样时刻单调递增，因此只需沿轨迹向前移动一次游标。
+pub fn sample_poincare_phases_from_trajectory(
+    traj: &[(f64, State)],
+    params: &PendulumParams,
+    transient_periods: usize,
+    sample_periods: usize,
+    k: usize,
+) -> Vec<Vec<(f64, f64)>> {
+    let period = 2.0 * PI / params.omega_d;
+    let mut sections = vec![Vec::with_capacity(sample_periods); k];
+    let mut cursor = 0;
+
+    'outer: for n in transient_periods..(transient_periods + sample_periods) {
+        // 相位 0 取周期末 (n + 1)T，与 sample_poincare_from_trajectory 保持一致，
+        // 因此周期内的顺序为 j = 1, 2, ..., k - 1, 0
+        for step in 1..=k {
+            let t_sample = (n as f64 + step as f64 / k as f64) * period;
+            while cursor < traj.len() && traj[cursor].0 < t_sample {
+                cursor += 1;
             }
-        } else {
-            // 未找到大于等于 t_sample 的点（轨迹不够长），跳出
-            break;
+            if cursor >= traj.len() {
+                break 'outer;
+            }
+            let (t2, s2) = &traj[cursor];
+            let sample = if cursor == 0 || *t2 == t_sample {
+                (wrap_angle(s2.theta), s2.omega)
+            } else {
+                let (t1, s1) = &traj[cursor - 1];
+                hermite_sample(*t1, s1, *t2, s2, t_sample, params)
+            };
+            sections[step % k].push(sample);
         }
     }
+    sections
+}
 
-    samples
+/// 先积分出完整轨迹再在 K 个相位上采样；参数无效或 omega_d = 0 时返回错误
+pub fn poincare_phases(
+    params: &PendulumParams,
+    initial_theta: f64,
+    initial_omega: f64,
+    transient_periods: usize,
+    sample_periods: usize,
+    k: usize,
+) -> Result<Vec<Vec<(f64, f64)>>, ChaosError> {
+    params.validate_periodic()?;
+    let traj: Vec<(f64, State)> = TrajectoryIter::new(params, initial_theta, initial_omega).collect();
+    Ok(sample_poincare_phases_from_trajectory(&traj, params, transient_periods, sample_periods, k))
+}
+
+/// 把多相位截面写成 CSV（四列：phase_index,phase,theta,omega），phase 为驱动相位 2πj/k
+pub fn write_poincare_phases_csv(path: &str, sections: &[Vec<(f64, f64)>]) -> std::io::Result<()> {
+    let k = sections.len();
+    let mut f = std::io::BufWriter::new(File::create(path)?);
+    writeln!(f, "phase_index,phase,theta,omega")?;
+    for (j, section) in sections.iter().enumerate() {
+        let phase = 2.0 * PI * j as f64 / k as f64;
+        for (th, om) in section {
+            writeln!(f, "{},{:.12},{:.12},{:.12}", j, phase, th, om)?;
+        }
+    }
+    f.flush()
 }
 
-/// 使用现有的 solve() 先计算完整轨迹，然后从轨迹中按周期采样（推荐）
+/// 沿 TrajectoryIter 边积分边按周期采样（推荐），结果与先 solve() 再 sample_poincare_from_trajectory 相同，
+/// 但不保存完整轨迹
 pub fn poincare_via_solve(
     params: &PendulumParams,
     initial_theta: f64,
     initial_omega: f64,
     transient_periods: usize,
     sample_periods: usize,
-) -> Vec<(f64, f64)> {
-    let traj = solve(params, initial_theta, initial_omega);
-    sample_poincare_from_trajectory(&traj, params, transient_periods, sample_periods)
+) -> Result<Vec<(f64, f64)>, ChaosError> {
+    check_sampling_span(params, transient_periods + sample_periods)?;
+    if params.noise_intensity > 0.0 {
+        return Ok(crate::stochastic::poincare_stochastic(params, initial_theta, initial_omega, transient_periods, sample_periods));
+    }
+    if params.step_control == StepControl::Adaptive {
+        return Ok(crate::adaptive::poincare_adaptive(params, initial_theta, initial_omega, transient_periods, sample_periods));
+    }
+    let traj = TrajectoryIter::new(params, initial_theta, initial_omega);
+    Ok(sample_poincare_from_iter(traj, params, transient_periods, sample_periods))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use approx::assert_relative_eq;
-    use std::f64::consts::PI;
+    use crate::model::TimeDirection;
 
     #[test]
     fn test_small_angle_pendulum_analytical_consistency() {
@@ -213,7 +1023,7 @@ mod tests {
         let initial_omega: f64 = 0.0;
 
         // 调用数值求解函数
-        let trajectory = solve(&params, initial_theta, initial_omega);
+        let trajectory = solve(&params, initial_theta, initial_omega).unwrap();
 
         // 计算自然频率 ω = sqrt(g / l)
         let omega_n = (params.g / params.l).sqrt();
@@ -241,4 +1051,416 @@ mod tests {
             assert_relative_eq!(conserved, initial_energy, epsilon = 1e-3);  // 稍松阈值容忍累积误差
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_poincare_phases_first_section_matches_single_phase() {
+        let mut params = PendulumParams::new();
+        params.q = 0.5;
+        params.f_d = 1.2;
+        params.omega_d = 2.0 / 3.0;
+        let period = 2.0 * PI / params.omega_d;
+        params.dt = period / 97.0;
+        params.t_end = 30.0 * period;
+
+        let traj = solve(&params, 1.0, 0.0).unwrap();
+        let single = sample_poincare_from_trajectory(&traj, &params, 5, 20).samples;
+        let sections = sample_poincare_phases_from_trajectory(&traj, &params, 5, 20, 4);
+
+        assert_eq!(sections.len(), 4);
+        assert!(sections.iter().all(|sec| sec.len() == single.len()));
+        for (a, b) in sections[0].iter().zip(single.iter()) {
+            assert_relative_eq!(a.0, b.0, epsilon = 1e-12);
+            assert_relative_eq!(a.1, b.1, epsilon = 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_write_poincare_csv_labels_runs() {
+        let mut params = PendulumParams::new();
+        params.q = 0.5;
+        params.f_d = 1.2;
+        params.omega_d = 2.0 / 3.0;
+        params.set_steps_per_period(100);
+        params.t_end = 31.0 * 2.0 * PI / params.omega_d;
+        let ics = [(1.0, 0.0), (-0.5, 1.0)];
+        let path = std::env::temp_dir().join("chaos_poincare_runs_test.csv");
+        write_poincare_csv(path.to_str().unwrap(), &params, &ics, 5, 20).unwrap();
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        let mut lines = text.lines();
+        assert_eq!(lines.next(), Some("run_id,theta,omega"));
+        let rows: Vec<Vec<f64>> = lines.map(|l| l.split(',').map(|v| v.parse().unwrap()).collect()).collect();
+        assert_eq!(rows.len(), 40);
+        let second = poincare(&params, -0.5, 1.0, 5, 20).unwrap();
+        for (row, expected) in rows[20..].iter().zip(&second) {
+            assert_eq!(row[0], 1.0);
+            assert_relative_eq!(row[1], expected.0, epsilon = 1e-11);
+            assert_relative_eq!(row[2], expected.1, epsilon = 1e-11);
+        }
+        assert!(rows[..20].iter().all(|r| r[0] == 0.0));
+    }
+
+    #[test]
+    fn test_warm_start_roundtrip_and_continuation() {
+        let mut params = PendulumParams::new();
+        params.q = 0.5;
+        params.f_d = 0.5;
+        params.omega_d = 2.0 / 3.0;
+        params.dt = 2.0 * PI / params.omega_d / 100.0;
+
+        let warm = WarmStart::compute(&params, 1.0, 0.0, 30).unwrap();
+        let path = std::env::temp_dir().join("chaos_warm_start_test.json");
+        let path = path.to_str().unwrap();
+        warm.save(path).unwrap();
+        let loaded = WarmStart::load(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(loaded.transient_periods, 30);
+        assert_eq!(loaded.state.theta, warm.state.theta);
+        assert_eq!(loaded.state.omega, warm.state.omega);
+
+        // 热启动后再积分 5 个周期，应与一次性积分 35 个周期一致
+        let direct = solve_periods(&params, 1.0, 0.0, 35, 100).unwrap();
+        let resumed = solve_periods(&params, loaded.state.theta, loaded.state.omega, 5, 100).unwrap();
+        let (_, a) = direct[direct.len() - 1];
+        let (_, b) = resumed[resumed.len() - 1];
+        assert_relative_eq!(wrap_angle(a.theta), wrap_angle(b.theta), epsilon = 1e-8);
+        assert_relative_eq!(a.omega, b.omega, epsilon = 1e-8);
+    }
+
+    #[test]
+    fn test_slice_by_time_interpolates_endpoints() {
+        let traj: Vec<(f64, State)> = (0..=10)
+            .map(|i| {
+                let t = i as f64;
+                (t, State { theta: 2.0 * t, omega: -t })
+            })
+            .collect();
+
+        let seg = slice_by_time(&traj, 2.5, 6.25);
+        assert_eq!(seg.len(), 6);
+        assert_eq!(seg[0].0, 2.5);
+        assert_relative_eq!(seg[0].1.theta, 5.0, epsilon = 1e-12);
+        assert_eq!(seg[1].0, 3.0);
+        assert_eq!(seg[5].0, 6.25);
+        assert_relative_eq!(seg[5].1.omega, -6.25, epsilon = 1e-12);
+
+        // 端点恰好落在积分点上时不重复
+        assert_eq!(slice_by_time(&traj, 2.0, 4.0).len(), 3);
+        assert!(slice_by_time(&traj, 11.0, 12.0).is_empty());
+    }
+
+    #[test]
+    fn test_resample_at_drive_periods_matches_poincare() {
+        let mut params = PendulumParams::new();
+        params.q = 0.5;
+        params.f_d = 1.2;
+        params.omega_d = 2.0 / 3.0;
+        let period = 2.0 * PI / params.omega_d;
+        params.dt = period / 97.0;
+        params.t_end = 12.0 * period;
+
+        let traj = solve(&params, 1.0, 0.0).unwrap();
+        let poincare = sample_poincare_from_trajectory(&traj, &params, 2, 8).samples;
+        // 乱序给出，且包含一个越界时刻
+        let mut times: Vec<f64> = (3..=10).rev().map(|n| n as f64 * period).collect();
+        times.push(1e6);
+        let resampled = resample(&traj, &params, &times);
+        assert_eq!(resampled.len(), 8);
+        for ((_, s), (theta, omega)) in resampled.iter().rev().zip(poincare.iter()) {
+            assert_relative_eq!(s.theta, *theta, epsilon = 1e-12);
+            assert_relative_eq!(s.omega, *omega, epsilon = 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_memory_budget_switches_to_streaming() {
+        let mut params = PendulumParams::new();
+        params.q = 0.5;
+        params.f_d = 1.2;
+        params.omega_d = 2.0 / 3.0;
+        let period = 2.0 * PI / params.omega_d;
+        params.dt = period / 100.0;
+        params.t_end = 60.0 * period;
+
+        let full = poincare(&params, 0.2, 0.0, 10, 40).unwrap();
+        params.memory_budget_bytes = Some(1024);
+        assert!(estimated_trajectory_bytes(&params) > 1024);
+        let streamed = poincare(&params, 0.2, 0.0, 10, 40).unwrap();
+        assert_eq!(full.len(), 40);
+        assert_eq!(streamed.len(), 40);
+        for (a, b) in full.iter().zip(&streamed) {
+            assert_relative_eq!(a.0, b.0, epsilon = 1e-12);
+            assert_relative_eq!(a.1, b.1, epsilon = 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_streaming_poincare_matches_stored_trajectory() {
+        let mut params = PendulumParams::new();
+        params.q = 0.5;
+        params.f_d = 1.2;
+        params.omega_d = 2.0 / 3.0;
+        let period = 2.0 * PI / params.omega_d;
+        params.dt = period / 97.0;
+        params.t_end = 40.0 * period;
+
+        let traj = solve(&params, 0.2, 0.0).unwrap();
+        assert_eq!(TrajectoryIter::new(&params, 0.2, 0.0).len(), traj.len());
+        let stored = sample_poincare_from_trajectory(&traj, &params, 10, 25);
+        let streamed = poincare_via_solve(&params, 0.2, 0.0, 10, 25).unwrap();
+        assert!(stored.is_complete());
+        assert_eq!(stored.samples, streamed);
+
+        // 轨迹太短或含重复时间戳：返回已得到的点与停止原因，complete() 报错
+        let short = sample_poincare_from_trajectory(&traj, &params, 10, 40);
+        assert_eq!(short.samples.len(), 29);
+        assert!(matches!(short.stop, SamplingStop::TrajectoryEnded { needed, .. } if (needed - 50.0 * period).abs() < 1e-9));
+        assert!(matches!(short.complete(), Err(ChaosError::InsufficientTrajectory { .. })));
+        let cut = 15 * (traj.len() - 1) / 40;
+        let mut repeated = traj.clone();
+        repeated.insert(cut, traj[cut]);
+        let partial = sample_poincare_from_trajectory(&repeated, &params, 10, 25);
+        assert_eq!(partial.stop, SamplingStop::NonMonotonicTime { index: cut + 1, time: traj[cut].0 });
+        assert_eq!(partial.samples, stored.samples[..partial.samples.len()]);
+        assert!(matches!(partial.complete(), Err(ChaosError::NonMonotonicTrajectory { .. })));
+    }
+
+    #[test]
+    fn test_sampling_stop_reasons() {
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.q = 0.5;
+        params.f_d = 1.2;
+        params.omega_d = 2.0 / 3.0;
+        params.set_steps_per_period(100);
+        let period = 2.0 * PI / params.omega_d;
+        params.t_end = 30.0 * period;
+        let traj = solve(&params, 0.2, 0.0).unwrap();
+
+        // 取满：点数等于要求的数目
+        let full = sample_poincare_from_trajectory(&traj, &params, 10, 20);
+        assert_eq!(full.stop, SamplingStop::Complete);
+        assert!(full.is_complete());
+        assert_eq!((full.requested, full.samples.len()), (20, 20));
+        assert_eq!(full.clone().complete().unwrap(), full.samples);
+
+        // 轨迹太短：得到过渡期之后轨迹覆盖到的 20 个点，记下轨迹末尾与需要到达的时刻
+        let short = sample_poincare_from_trajectory(&traj, &params, 10, 25);
+        assert!(!short.is_complete());
+        assert_eq!((short.requested, short.samples.len()), (25, 20));
+        assert_eq!(short.samples, full.samples);
+        match short.stop {
+            SamplingStop::TrajectoryEnded { last_time, needed } => {
+                assert_eq!(last_time, traj.last().unwrap().0);
+                assert_relative_eq!(needed, 35.0 * period, epsilon = 1e-9);
+            }
+            other => panic!("unexpected stop {:?}", other),
+        }
+
+        // 过渡期就已超出轨迹：一个点也没有
+        let none = sample_poincare_from_trajectory(&traj, &params, 40, 5);
+        assert!(none.samples.is_empty());
+        assert!(matches!(none.stop, SamplingStop::TrajectoryEnded { .. }));
+        assert!(matches!(none.complete(), Err(ChaosError::InsufficientTrajectory { .. })));
+    }
+
+    #[test]
+    fn test_angle_policy_ranges() {
+        for theta in [-7.0, -PI, -0.5, 0.0, PI, 4.0, 13.0] {
+            let sym = AnglePolicy::Symmetric.apply(theta);
+            let pos = AnglePolicy::Positive.apply(theta);
+            assert!((-PI..PI).contains(&sym));
+            assert!((0.0..2.0 * PI).contains(&pos));
+            // 两种折叠与原角度只差 2π 的整数倍
+            assert_relative_eq!(((theta - sym) / (2.0 * PI)).fract().abs(), 0.0, epsilon = 1e-12);
+            assert_relative_eq!(((theta - pos) / (2.0 * PI)).fract().abs(), 0.0, epsilon = 1e-12);
+        }
+        assert_eq!(AnglePolicy::Raw.apply(13.0), 13.0);
+    }
+
+    #[test]
+    fn test_symplectic_integrators_bound_energy_error() {
+        let mut params = PendulumParams::new();
+        params.q = 0.0;
+        params.f_d = 0.0;
+        params.dt = 0.05;
+        params.t_end = 1e5;
+        let energy = |s: &State| 0.5 * s.omega * s.omega + (params.g / params.l) * (1.0 - s.theta.cos());
+        let e0 = energy(&State { theta: 2.0, omega: 0.0 });
+        let max_error = |p: &PendulumParams| {
+            TrajectoryIter::new(p, 2.0, 0.0)
+                .map(|(_, s)| (energy(&s) - e0).abs() / e0)
+                .fold(0.0, f64::max)
+        };
+
+        let rk4 = max_error(&params);
+        params.integrator = Integrator::VelocityVerlet;
+        let verlet = max_error(&params);
+        params.integrator = Integrator::Yoshida4;
+        let yoshida = max_error(&params);
+        // 辛方法的能量误差有界振荡，不随时间累积；RK4 在同样步长下单向漂移
+        assert!(verlet < 1e-2, "verlet {}", verlet);
+        assert!(yoshida < 1e-4, "yoshida {}", yoshida);
+        assert!(rk4 > 10.0 * yoshida, "rk4 {} yoshida {}", rk4, yoshida);
+    }
+
+    #[test]
+    fn test_invalid_params_return_errors() {
+        let mut params = PendulumParams::new();
+        params.omega_d = 0.0;
+        assert!(matches!(poincare(&params, 0.2, 0.0, 10, 10), Err(ChaosError::ZeroDriveFrequency)));
+        // 按周期积分的入口先检查周期，不会拿 usize::MAX 步去分配
+        let spp = steps_per_period(&params);
+        assert!(matches!(solve_periods(&params, 0.2, 0.0, 2, spp), Err(ChaosError::ZeroDriveFrequency)));
+        assert!(matches!(poincare_phases(&params, 0.2, 0.0, 1, 1, 4), Err(ChaosError::ZeroDriveFrequency)));
+        assert!(WarmStart::compute(&params, 0.2, 0.0, 1).is_err());
+
+        let mut params = PendulumParams::new();
+        params.dt = 0.0;
+        assert!(matches!(solve(&params, 0.2, 0.0), Err(ChaosError::NonPositiveDt(_))));
+
+        // t_end 只够 5 个周期，却要求 10 + 10 个
+        let mut params = PendulumParams::new();
+        params.t_end = 5.0 * 2.0 * PI / params.omega_d;
+        let err = poincare_via_solve(&params, 0.2, 0.0, 10, 10).unwrap_err();
+        assert!(matches!(err, ChaosError::InsufficientTrajectory { .. }));
+        assert_eq!(std::io::Error::from(err).kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_steps_per_period_samples_exactly() {
+        let mut params = PendulumParams::new();
+        params.q = 0.5;
+        params.f_d = 1.2;
+        params.set_steps_per_period(100);
+        // 之后修改 omega_d，步长跟随新的周期
+        params.omega_d = 2.0 / 3.0;
+        let period = 2.0 * PI / params.omega_d;
+        assert_relative_eq!(params.effective_dt(), period / 100.0, epsilon = 1e-15);
+        params.t_end = 30.0 * period;
+
+        let traj = TrajectoryIter::new(&params, 0.2, 0.0);
+        assert_eq!(traj.len(), 3001);
+        let (t_last, _) = traj.last().unwrap();
+        assert_relative_eq!(t_last, 30.0 * period, epsilon = 1e-9);
+
+        let samples = poincare(&params, 0.2, 0.0, 10, 20).unwrap();
+        let reference = stroboscopic_samples(&solve_periods(&params, 0.2, 0.0, 30, 100).unwrap(), 100, 10, 20);
+        assert_eq!(samples, reference);
+
+        // 不从 t = 0 开始的片段不能按下标取点，改按时间戳插值，结果仍是同样的采样时刻
+        let rows = solve(&params, 0.2, 0.0).unwrap();
+        let shifted = sample_poincare_from_trajectory(&rows[37..], &params, 10, 20).complete().unwrap();
+        assert!(shifted.iter().zip(&samples).all(|(a, b)| (a.0 - b.0).abs() < 1e-9 && (a.1 - b.1).abs() < 1e-9));
+
+        // FixedDt 下按 |omega_d| 估计，驱动方向反过来时步数不变
+        params.step_control = StepControl::FixedDt;
+        params.dt = period / 100.0;
+        assert_eq!(steps_per_period(&params), 100);
+        params.omega_d = -params.omega_d;
+        assert_eq!(steps_per_period(&params), 100);
+    }
+
+    #[test]
+    fn test_exact_stroboscopic_lands_on_periods() {
+        let mut params = PendulumParams::new();
+        params.q = 0.5;
+        params.f_d = 0.5;
+        params.omega_d = 2.0 / 3.0;
+        let period = 2.0 * PI / params.omega_d;
+        params.dt = period / 400.5;
+        params.t_end = 8.0 * period;
+        let reference = stroboscopic_samples(&solve_periods(&params, 0.2, 0.0, 8, 4000).unwrap(), 4000, 0, 8);
+        let interpolated = poincare(&params, 0.2, 0.0, 0, 8).unwrap();
+        // 轨迹末端差舍入误差没到 8T 时仍给出第 8 个点
+        assert_eq!(interpolated.len(), 8);
+
+        params.exact_stroboscopic = true;
+        let traj = solve(&params, 0.2, 0.0).unwrap();
+        assert_eq!(TrajectoryIter::new(&params, 0.2, 0.0).len(), traj.len());
+        assert_eq!(traj.len(), 8 * 401 + 1);
+        assert!((1..=8).all(|n| traj.iter().any(|&(t, _)| t == n as f64 * period)));
+        let exact = poincare(&params, 0.2, 0.0, 0, 8).unwrap();
+        let error = |samples: &[(f64, f64)]| {
+            samples.iter().zip(&reference).map(|(a, b)| (a.0 - b.0).abs() + (a.1 - b.1).abs()).fold(0.0, f64::max)
+        };
+        assert!(error(&exact) < 1e-5, "exact {}", error(&exact));
+        // 三次 Hermite 插值的误差已与积分误差同阶，截短末步不会更差
+        assert!(error(&exact) <= error(&interpolated), "exact {} interpolated {}", error(&exact), error(&interpolated));
+
+        // 自适应步长同样落在 nT 上
+        params.step_control = StepControl::Adaptive;
+        let traj = solve(&params, 0.2, 0.0).unwrap();
+        assert!((1..=8).all(|n| traj.iter().any(|&(t, _)| t == n as f64 * period)));
+    }
+
+    #[test]
+    fn test_hermite_dense_output_beats_linear() {
+        let mut params = PendulumParams::new();
+        params.q = 0.5;
+        params.f_d = 0.5;
+        params.omega_d = 2.0 / 3.0;
+        let period = 2.0 * PI / params.omega_d;
+        params.dt = period / 60.0;
+        params.t_end = 4.0 * period;
+        let traj = solve(&params, 0.2, 0.0).unwrap();
+
+        // 参考值：从步起点出发，以小 100 倍的步长积分半步
+        let mut fine = params.clone();
+        fine.dt = params.dt / 100.0;
+
+        let (mut hermite_err, mut linear_err) = (0.0f64, 0.0f64);
+        for k in 0..60 {
+            let i = 3 * k + 1;
+            let t = traj[i].0 + 0.5 * params.dt;
+            let (mut exact, mut t_fine) = (traj[i].1, traj[i].0);
+            for _ in 0..50 {
+                (exact, t_fine) = rk4_step(&exact, t_fine, &fine);
+            }
+            let dense = evaluate_at(&traj, &params, t).unwrap();
+            let linear = interpolate_state(traj[i].0, &traj[i].1, traj[i + 1].0, &traj[i + 1].1, t);
+            hermite_err = hermite_err.max((dense.theta - exact.theta).abs() + (dense.omega - exact.omega).abs());
+            linear_err = linear_err.max((linear.theta - exact.theta).abs() + (linear.omega - exact.omega).abs());
+        }
+        assert!(hermite_err < 5e-4, "hermite {}", hermite_err);
+        assert!(hermite_err < 0.02 * linear_err, "hermite {} linear {}", hermite_err, linear_err);
+        assert!(evaluate_at(&traj, &params, params.t_end + 1.0).is_none());
+    }
+
+    #[test]
+    fn test_backward_integration_retraces_forward() {
+        // 保守极限（q = 0）下正向积分两个周期再反向积分两个周期，回到初值（只差 RK4 的截断误差）
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.q = 0.0;
+        params.f_d = 0.5;
+        params.omega_d = 2.0 / 3.0;
+        params.set_steps_per_period(400);
+        let period = 2.0 * PI / params.omega_d;
+        params.t_end = 2.0 * period;
+        let forward = solve(&params, 0.4, 0.1).unwrap();
+        let (_, end) = forward[forward.len() - 1];
+
+        params.direction = TimeDirection::Backward;
+        let backward = solve(&params, end.theta, end.omega).unwrap();
+        assert_eq!(backward.len(), forward.len());
+        let (t_last, start) = backward[backward.len() - 1];
+        assert_relative_eq!(t_last, -2.0 * period, epsilon = 1e-9);
+        assert!((start.theta - 0.4).abs() < 1e-6 && (start.omega - 0.1).abs() < 1e-6, "{:?}", start);
+        // 反向轨迹的每个周期末就是逆频闪映射
+        let inverse = stroboscopic_map(&params, end, 1);
+        assert_relative_eq!(backward[400].1.theta, inverse.theta, epsilon = 1e-10);
+        assert_relative_eq!(backward[400].1.omega, inverse.omega, epsilon = 1e-10);
+        params.direction = TimeDirection::Forward;
+        assert!((stroboscopic_map(&params, inverse, 1).theta - end.theta).abs() < 1e-6);
+
+        // 反向的庞加莱采样取 t = -nT
+        params.direction = TimeDirection::Backward;
+        params.step_control = StepControl::FixedDt;
+        params.t_end = 2.5 * period;
+        let samples = poincare(&params, end.theta, end.omega, 0, 2).unwrap();
+        assert!((samples[1].0 - wrap_angle(0.4)).abs() < 1e-6, "{:?}", samples);
+        params.step_control = StepControl::Adaptive;
+        assert!(matches!(solve(&params, 0.0, 0.0), Err(ChaosError::InvalidParams(_))));
+    }
+}
@@ -0,0 +1,208 @@
+//spectrum.rs
+use std::f64::consts::PI;
+use std::fs::File;
+use std::io::Write;
+use crate::solve_equation::State;
+
+/// 原地基 2 FFT（长度必须是 2 的幂），re/im 分别为实部与虚部
+pub fn fft(re: &mut [f64], im: &mut [f64]) {
+    let n = re.len();
+    assert_eq!(n, im.len());
+    assert!(n.is_power_of_two(), "FFT length must be a power of two");
+
+    // 位反转重排
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    // 蝶形运算
+    let mut len = 2;
+    while len <= n {
+        let angle = -2.0 * PI / len as f64;
+        let (w_im, w_re) = angle.sin_cos();
+        for start in (0..n).step_by(len) {
+            let mut cur_re = 1.0;
+            let mut cur_im = 0.0;
+            for k in 0..len / 2 {
+                let a = start + k;
+                let b = a + len / 2;
+                let t_re = re[b] * cur_re - im[b] * cur_im;
+                let t_im = re[b] * cur_im + im[b] * cur_re;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+                let next_re = cur_re * w_re - cur_im * w_im;
+                cur_im = cur_re * w_im + cur_im * w_re;
+                cur_re = next_re;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+/// 原地逆 FFT（含 1/n 归一化），利用 ifft(x) = conj(fft(conj(x))) / n
+pub fn ifft(re: &mut [f64], im: &mut [f64]) {
+    let n = re.len() as f64;
+    im.iter_mut().for_each(|v| *v = -*v);
+    fft(re, im);
+    re.iter_mut().for_each(|v| *v /= n);
+    im.iter_mut().for_each(|v| *v = -*v / n);
+}
+
+/// 解析信号 x + i·H[x]（FFT 法 Hilbert 变换），返回 (实部, 虚部)
+///
+/// 长度截断为不超过 signal.len() 的最大 2 的幂；|解析信号| 即瞬时包络。
+pub fn analytic_signal(signal: &[f64]) -> (Vec<f64>, Vec<f64>) {
+    if signal.is_empty() {
+        return (Vec::new(), Vec::new());
+    }
+    let n = 1usize << (usize::BITS - 1 - signal.len().leading_zeros());
+    let mut re = signal[..n].to_vec();
+    let mut im = vec![0.0; n];
+    fft(&mut re, &mut im);
+    // 正频率加倍，负频率置零，直流与奈奎斯特保持不变
+    for k in 1..n {
+        let factor = if k < n / 2 {
+            2.0
+        } else if k == n / 2 {
+            1.0
+        } else {
+            0.0
+        };
+        re[k] *= factor;
+        im[k] *= factor;
+    }
+    ifft(&mut re, &mut im);
+    (re, im)
+}
+
+/// 谱分析前施加的窗函数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Window {
+    /// 不加窗（矩形窗），频率分辨率最高但泄漏最大
+    Rectangular,
+    #[default]
+    Hann,
+    /// 第一旁瓣更低，但远处旁瓣衰减比 Hann 慢
+    Hamming,
+}
+
+impl Window {
+    /// 长度为 n 的窗系数（周期形式）
+    pub fn coefficients(&self, n: usize) -> Vec<f64> {
+        (0..n)
+            .map(|i| {
+                let c = (2.0 * PI * i as f64 / n as f64).cos();
+                match self {
+                    Window::Rectangular => 1.0,
+                    Window::Hann => 0.5 - 0.5 * c,
+                    Window::Hamming => 0.54 - 0.46 * c,
+                }
+            })
+            .collect()
+    }
+}
+
+/// 单边功率谱，返回 Vec<(频率, 功率)>，频率单位为 1 / 时间单位
+///
+/// signal 按步长 dt 均匀采样；先去均值、加 Hann 窗，
+/// 长度截断为不超过 signal.len() 的最大 2 的幂。
+pub fn power_spectrum(signal: &[f64], dt: f64) -> Vec<(f64, f64)> {
+    power_spectrum_windowed(signal, dt, Window::Hann)
+}
+
+/// 与 power_spectrum 相同，但可选窗函数；功率按窗的能量归一化
+pub fn power_spectrum_windowed(signal: &[f64], dt: f64, window: Window) -> Vec<(f64, f64)> {
+    if signal.len() < 2 {
+        return Vec::new();
+    }
+    let n = 1usize << (usize::BITS - 1 - signal.len().leading_zeros());
+    let data = &signal[..n];
+    let mean = data.iter().sum::<f64>() / n as f64;
+
+    let window = window.coefficients(n);
+    let window_power: f64 = window.iter().map(|w| w * w).sum();
+
+    let mut re: Vec<f64> = data.iter().zip(&window).map(|(x, w)| (x - mean) * w).collect();
+    let mut im = vec![0.0; n];
+    fft(&mut re, &mut im);
+
+    (0..=n / 2)
+        .map(|k| {
+            let mut p = (re[k] * re[k] + im[k] * im[k]) / window_power;
+            // 除直流与奈奎斯特频率外，负频率部分折叠到正频率
+            if k != 0 && k != n / 2 {
+                p *= 2.0;
+            }
+            (k as f64 / (n as f64 * dt), p)
+        })
+        .collect()
+}
+
+/// θ(t) 的功率谱：轨迹须按固定步长 dt 采样（例如 solve 的输出）
+pub fn theta_power_spectrum(traj: &[(f64, State)], dt: f64, window: Window) -> Vec<(f64, f64)> {
+    let theta: Vec<f64> = traj.iter().map(|(_, s)| s.theta).collect();
+    power_spectrum_windowed(&theta, dt, window)
+}
+
+/// 把功率谱写成 CSV（两列：frequency,power）
+pub fn write_power_spectrum_csv(path: &str, spectrum: &[(f64, f64)]) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(f, "frequency,power")?;
+    for (freq, power) in spectrum {
+        writeln!(f, "{:.12},{:.12}", freq, power)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_power_spectrum_peak_at_signal_frequency() {
+        let dt = 0.01;
+        let n = 4096;
+        let freq = 64.0 / (n as f64 * dt);
+        let signal: Vec<f64> = (0..n).map(|i| (2.0 * PI * freq * i as f64 * dt).sin()).collect();
+
+        let spec = power_spectrum(&signal, dt);
+        assert_eq!(spec.len(), n / 2 + 1);
+        let (f_peak, _) = spec
+            .iter()
+            .cloned()
+            .fold((0.0, 0.0), |best, x| if x.1 > best.1 { x } else { best });
+        assert!((f_peak - freq).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_windows_keep_peak_and_differ_in_leakage() {
+        let dt = 0.01;
+        let n = 4096;
+        // 频率落在两个频点之间，泄漏明显
+        let freq = 64.5 / (n as f64 * dt);
+        let signal: Vec<f64> = (0..n).map(|i| (2.0 * PI * freq * i as f64 * dt).sin()).collect();
+
+        let far_leakage = |w: Window| {
+            let spec = power_spectrum_windowed(&signal, dt, w);
+            let peak = spec.iter().map(|x| x.1).fold(0.0, f64::max);
+            spec[200].1 / peak
+        };
+        let rect = far_leakage(Window::Rectangular);
+        let hann = far_leakage(Window::Hann);
+        let hamming = far_leakage(Window::Hamming);
+        assert!(hann < hamming && hamming < rect);
+        assert_eq!(power_spectrum(&signal, dt), power_spectrum_windowed(&signal, dt, Window::Hann));
+    }
+}
@@ -0,0 +1,285 @@
+//stats.rs
+use std::fs::File;
+use std::io::Write;
+
+// 任意标量观测量（θ、ω、能量、庞加莱点坐标、回归时间……）的一维统计：矩、直方图与经验分布函数。
+// 各分析模块只需先把关心的量取成 &[f64]，分箱与归一化统一在这里做，例如
+//   let omegas: Vec<f64> = traj.iter().map(|(_, s)| s.omega).collect();
+//   let h = Histogram::new(&omegas, &Bins::Count(100));
+// 非有限值（NaN、±∞，如 escape 图中未到达的格点）一律忽略，不计入任何统计。
+// 方差等中心矩按总体定义（除以 n），与 events::interval_statistics、ensemble 的统计一致。
+
+/// 矩与极值
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Summary {
+    pub count: usize,
+    pub mean: f64,
+    pub variance: f64,
+    pub std_dev: f64,
+    /// 偏度 m3 / m2^1.5；方差为 0 时为 NaN
+    pub skewness: f64,
+    /// 超额峰度 m4 / m2² - 3（正态分布为 0）；方差为 0 时为 NaN
+    pub kurtosis: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+fn finite(values: &[f64]) -> impl Iterator<Item = f64> + '_ {
+    values.iter().copied().filter(|x| x.is_finite())
+}
+
+/// 均值、方差、偏度、峰度与极值；没有有限值时返回 None
+pub fn summary(values: &[f64]) -> Option<Summary> {
+    let (count, sum, min, max) = finite(values).fold((0usize, 0.0, f64::INFINITY, f64::NEG_INFINITY), |(n, s, lo, hi), x| {
+        (n + 1, s + x, lo.min(x), hi.max(x))
+    });
+    if count == 0 {
+        return None;
+    }
+    let n = count as f64;
+    let mean = sum / n;
+    let (m2, m3, m4) = finite(values).fold((0.0, 0.0, 0.0), |(a, b, c), x| {
+        let d = x - mean;
+        (a + d * d, b + d * d * d, c + d * d * d * d)
+    });
+    let (m2, m3, m4) = (m2 / n, m3 / n, m4 / n);
+    let (skewness, kurtosis) = if m2 > 0.0 {
+        (m3 / m2.powf(1.5), m4 / (m2 * m2) - 3.0)
+    } else {
+        (f64::NAN, f64::NAN)
+    };
+    Some(Summary {
+        count,
+        mean,
+        variance: m2,
+        std_dev: m2.sqrt(),
+        skewness,
+        kurtosis,
+        min,
+        max,
+    })
+}
+
+/// 分箱方式
+#[derive(Debug, Clone, PartialEq)]
+pub enum Bins {
+    /// 在 [min, max] 上等分为给定个数
+    Count(usize),
+    /// 在给定区间上等分为给定个数，区间外的值计入 underflow / overflow
+    Range { count: usize, lo: f64, hi: f64 },
+    /// 从 min 开始的固定宽度
+    Width(f64),
+    /// 升序边界（至少两个）
+    Edges(Vec<f64>),
+    /// Freedman–Diaconis 规则：宽度 2·IQR / n^(1/3)，IQR 为 0 时退回 Sturges 规则 ⌈log2 n⌉ + 1 个
+    Auto,
+}
+
+/// 一维直方图：第 i 个区间为 [edges[i], edges[i + 1])，最后一个区间包含右端点
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    pub edges: Vec<f64>,
+    pub counts: Vec<usize>,
+    /// 小于第一个边界的值的个数
+    pub underflow: usize,
+    /// 大于最后一个边界的值的个数
+    pub overflow: usize,
+}
+
+fn uniform_edges(lo: f64, hi: f64, count: usize) -> Vec<f64> {
+    let count = count.max(1);
+    // 退化区间（所有值相同）两侧各扩 0.5，保证区间宽度为正
+    let (lo, hi) = if hi > lo { (lo, hi) } else { (lo - 0.5, hi + 0.5) };
+    (0..=count).map(|i| lo + (hi - lo) * i as f64 / count as f64).collect()
+}
+
+impl Histogram {
+    /// 按 bins 分箱并计数；没有有限值且分箱依赖数据范围时得到一个空区间 [0, 1]
+    pub fn new(values: &[f64], bins: &Bins) -> Self {
+        let range = summary(values).map_or((0.0, 1.0), |s| (s.min, s.max));
+        let edges = match bins {
+            Bins::Count(count) => uniform_edges(range.0, range.1, *count),
+            Bins::Range { count, lo, hi } => uniform_edges(*lo, *hi, *count),
+            Bins::Width(width) => {
+                let count = if *width > 0.0 { ((range.1 - range.0) / width).floor() as usize + 1 } else { 1 };
+                (0..=count).map(|i| range.0 + i as f64 * width.max(0.0)).collect()
+            }
+            Bins::Edges(edges) => edges.clone(),
+            Bins::Auto => {
+                let ecdf = Ecdf::new(values);
+                let iqr = ecdf.quantile(0.75) - ecdf.quantile(0.25);
+                let n = ecdf.len().max(1) as f64;
+                let count = if iqr > 0.0 {
+                    ((range.1 - range.0) / (2.0 * iqr / n.cbrt())).ceil() as usize
+                } else {
+                    n.log2().ceil() as usize + 1
+                };
+                uniform_edges(range.0, range.1, count)
+            }
+        };
+        Self::with_edges(values, edges)
+    }
+
+    /// 按给定的升序边界计数；边界少于两个时所有值都计入 underflow
+    pub fn with_edges(values: &[f64], edges: Vec<f64>) -> Self {
+        let bins = edges.len().saturating_sub(1);
+        let mut h = Histogram { counts: vec![0; bins], edges, underflow: 0, overflow: 0 };
+        for x in finite(values) {
+            if bins == 0 || x < h.edges[0] {
+                h.underflow += 1;
+            } else if x > h.edges[bins] {
+                h.overflow += 1;
+            } else {
+                let i = h.edges.partition_point(|e| *e <= x);
+                h.counts[(i.max(1) - 1).min(bins - 1)] += 1;
+            }
+        }
+        h
+    }
+
+    /// 区间个数
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// 落在区间内的值的总数（不含 underflow / overflow）
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    /// 区间中点
+    pub fn centers(&self) -> Vec<f64> {
+        self.edges.windows(2).map(|w| 0.5 * (w[0] + w[1])).collect()
+    }
+
+    /// 概率密度估计 count / (total · width)，在区间内积分为 1
+    pub fn density(&self) -> Vec<f64> {
+        let total = self.total().max(1) as f64;
+        self.edges
+            .windows(2)
+            .zip(&self.counts)
+            .map(|(w, &c)| c as f64 / (total * (w[1] - w[0])))
+            .collect()
+    }
+}
+
+/// 经验分布函数 F(x) = #{x_i ≤ x} / n
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ecdf {
+    sorted: Vec<f64>,
+}
+
+impl Ecdf {
+    pub fn new(values: &[f64]) -> Self {
+        let mut sorted: Vec<f64> = finite(values).collect();
+        sorted.sort_by(f64::total_cmp);
+        Ecdf { sorted }
+    }
+
+    pub fn len(&self) -> usize {
+        self.sorted.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sorted.is_empty()
+    }
+
+    /// 升序排列的样本
+    pub fn values(&self) -> &[f64] {
+        &self.sorted
+    }
+
+    /// F(x)；空样本为 NaN
+    pub fn evaluate(&self, x: f64) -> f64 {
+        if self.sorted.is_empty() {
+            return f64::NAN;
+        }
+        self.sorted.partition_point(|v| *v <= x) as f64 / self.sorted.len() as f64
+    }
+
+    /// p 分位数（相邻次序统计量之间线性插值，p 截断到 [0, 1]）；空样本为 NaN
+    pub fn quantile(&self, p: f64) -> f64 {
+        let n = self.sorted.len();
+        if n == 0 {
+            return f64::NAN;
+        }
+        let pos = p.clamp(0.0, 1.0) * (n - 1) as f64;
+        let i = (pos.floor() as usize).min(n - 1);
+        let j = (i + 1).min(n - 1);
+        self.sorted[i] + (pos - i as f64) * (self.sorted[j] - self.sorted[i])
+    }
+
+    /// 阶梯函数的跳跃点 (x, F(x))，重复值只保留最后一个
+    pub fn points(&self) -> Vec<(f64, f64)> {
+        let n = self.sorted.len() as f64;
+        self.sorted
+            .iter()
+            .enumerate()
+            .filter(|(i, x)| self.sorted.get(i + 1) != Some(x))
+            .map(|(i, &x)| (x, (i + 1) as f64 / n))
+            .collect()
+    }
+}
+
+/// 把直方图写成 CSV（列：bin_start,bin_end,count,density）
+pub fn write_histogram_csv(path: &str, h: &Histogram) -> std::io::Result<()> {
+    let mut f = std::io::BufWriter::new(File::create(path)?);
+    writeln!(f, "bin_start,bin_end,count,density")?;
+    for ((w, c), d) in h.edges.windows(2).zip(&h.counts).zip(h.density()) {
+        writeln!(f, "{:.12},{:.12},{},{:.12}", w[0], w[1], c, d)?;
+    }
+    f.flush()
+}
+
+/// 把经验分布函数写成 CSV（列：value,cdf）
+pub fn write_ecdf_csv(path: &str, ecdf: &Ecdf) -> std::io::Result<()> {
+    let mut f = std::io::BufWriter::new(File::create(path)?);
+    writeln!(f, "value,cdf")?;
+    for (x, p) in ecdf.points() {
+        writeln!(f, "{:.12},{:.12}", x, p)?;
+    }
+    f.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::{seeded_rng, RandomSource};
+
+    #[test]
+    fn test_moments_histogram_and_ecdf() {
+        let mut rng = seeded_rng(3);
+        let mut normal: Vec<f64> = (0..200_000).map(|_| 2.0 + 0.5 * rng.standard_normal()).collect();
+        normal.push(f64::NAN);
+        let s = summary(&normal).unwrap();
+        assert_eq!(s.count, 200_000);
+        assert!((s.mean - 2.0).abs() < 0.01 && (s.std_dev - 0.5).abs() < 0.01, "{:?}", s);
+        assert!(s.skewness.abs() < 0.03 && s.kurtosis.abs() < 0.05, "{:?}", s);
+        assert_eq!(summary(&[f64::NAN]), None);
+
+        // 均匀分布的矩：偏度 0，超额峰度 -1.2
+        let uniform: Vec<f64> = (0..1000).map(|i| i as f64).collect();
+        let s = summary(&uniform).unwrap();
+        assert!(s.skewness.abs() < 1e-12 && (s.kurtosis + 1.2).abs() < 1e-3, "{:?}", s);
+
+        // 最大值落在最后一个区间；区间外的值计入 underflow / overflow
+        let h = Histogram::new(&uniform, &Bins::Count(10));
+        assert_eq!(h.counts, vec![100; 10]);
+        let h = Histogram::new(&uniform, &Bins::Range { count: 4, lo: 100.0, hi: 900.0 });
+        assert_eq!((h.underflow, h.total(), h.overflow), (100, 801, 99));
+        let h = Histogram::new(&normal, &Bins::Auto);
+        let integral: f64 = h.density().iter().zip(h.edges.windows(2)).map(|(d, w)| d * (w[1] - w[0])).sum();
+        assert!((integral - 1.0).abs() < 1e-9 && h.len() > 50);
+        assert_eq!(Histogram::new(&[1.0, 1.0], &Bins::Width(0.25)).total(), 2);
+
+        let ecdf = Ecdf::new(&[3.0, 1.0, 2.0, 2.0]);
+        assert_eq!(ecdf.evaluate(2.0), 0.75);
+        assert_eq!(ecdf.evaluate(0.0), 0.0);
+        assert_eq!(ecdf.quantile(0.5), 2.0);
+        assert_eq!(ecdf.points(), vec![(1.0, 0.25), (2.0, 0.75), (3.0, 1.0)]);
+    }
+}
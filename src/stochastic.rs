@@ -0,0 +1,147 @@
+//stochastic.rs
+use crate::model::{NoiseScheme, PendulumParams};
+use crate::random::{seeded_rng, DefaultRng, RandomSource};
+use crate::solve_equation::{rhs, sample_poincare_from_iter, step_count, steps_per_period, State};
+
+// 加性噪声的 Langevin 方程：dθ = ω dt，dω = [-(g/l) sin θ - q ω + F(t)] dt + σ dW，σ = params.noise_intensity。
+// 噪声只作用在 ω 上，因此两种格式中 dW 的增量都只加到 ω。
+
+/// 按 params.noise_scheme 走一步 h，rng 提供 Wiener 增量 ΔW = √h·N(0, 1)
+pub fn stochastic_step<R: RandomSource>(state: &State, t: f64, h: f64, params: &PendulumParams, rng: &mut R) -> State {
+    let dw = params.noise_intensity * h.sqrt() * rng.standard_normal();
+    let (d_theta, d_omega) = rhs(state.theta, state.omega, t, params);
+    let euler = State {
+        theta: state.theta + h * d_theta,
+        omega: state.omega + h * d_omega + dw,
+    };
+    match params.noise_scheme {
+        NoiseScheme::EulerMaruyama => euler,
+        NoiseScheme::Heun => {
+            // 以 Euler–Maruyama 结果为预测值，漂移项取两端平均，噪声增量不变
+            let (d_theta2, d_omega2) = rhs(euler.theta, euler.omega, t + h, params);
+            State {
+                theta: state.theta + 0.5 * h * (d_theta + d_theta2),
+                omega: state.omega + 0.5 * h * (d_omega + d_omega2) + dw,
+            }
+        }
+    }
+}
+
+/// 带噪声的惰性轨迹：与 TrajectoryIter 相同的步长与步数（params.effective_dt()），含初始点
+///
+/// 同一个随机数源依次提供各步的噪声，因此同样的种子总是得到同样的样本路径。
+#[derive(Debug, Clone)]
+pub struct StochasticTrajectory<R: RandomSource> {
+    params: PendulumParams,
+    state: State,
+    t: f64,
+    remaining: usize,
+    started: bool,
+    rng: R,
+}
+
+impl StochasticTrajectory<DefaultRng> {
+    /// 噪声由 params.noise_seed 播种
+    pub fn new(params: &PendulumParams, initial_theta: f64, initial_omega: f64) -> Self {
+        Self::with_rng(params, initial_theta, initial_omega, seeded_rng(params.noise_seed))
+    }
+}
+
+impl<R: RandomSource> StochasticTrajectory<R> {
+    /// 使用调用者提供的随机数源（忽略 noise_seed），例如在多条样本路径之间共用一个生成器
+    pub fn with_rng(params: &PendulumParams, initial_theta: f64, initial_omega: f64, rng: R) -> Self {
+        let mut params = params.clone();
+        params.dt = params.effective_dt();
+        StochasticTrajectory {
+            remaining: step_count(params.t_end, params.dt),
+            params,
+            state: State {
+                theta: initial_theta,
+                omega: initial_omega,
+            },
+            t: 0.0,
+            started: false,
+            rng,
+        }
+    }
+}
+
+impl<R: RandomSource> Iterator for StochasticTrajectory<R> {
+    type Item = (f64, State);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.started {
+            self.started = true;
+            return Some((self.t, self.state));
+        }
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        self.state = stochastic_step(&self.state, self.t, self.params.dt, &self.params, &mut self.rng);
+        self.t += self.params.dt;
+        Some((self.t, self.state))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.remaining + usize::from(!self.started);
+        (n, Some(n))
+    }
+}
+
+impl<R: RandomSource> ExactSizeIterator for StochasticTrajectory<R> {}
+
+/// 积分一条样本路径到 params.t_end，返回每一步的 (t, State)（含初始点）
+pub fn solve_stochastic(params: &PendulumParams, initial_theta: f64, initial_omega: f64) -> Vec<(f64, State)> {
+    StochasticTrajectory::new(params, initial_theta, initial_omega).collect()
+}
+
+/// 带噪声的庞加莱截面：每个周期按 steps_per_period(params) 步整周期积分，频闪时刻直接取样本路径上的点
+///
+/// 样本路径不可微，不能像确定性轨迹那样做 Hermite 插值，因此总是按整周期步进。
+pub fn poincare_stochastic(
+    params: &PendulumParams,
+    initial_theta: f64,
+    initial_omega: f64,
+    transient_periods: usize,
+    sample_periods: usize,
+) -> Vec<(f64, f64)> {
+    let mut p = params.clone();
+    p.set_steps_per_period(steps_per_period(params));
+    let traj = StochasticTrajectory::new(&p, initial_theta, initial_omega);
+    sample_poincare_from_iter(traj, &p, transient_periods, sample_periods)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_free_particle_diffusion_and_reproducibility() {
+        // 无重力、无阻尼、无驱动：ω 为 σW(t)，Var ω(T) = σ² T
+        let mut params = PendulumParams::new();
+        params.g = 0.0;
+        params.q = 0.0;
+        params.f_d = 0.0;
+        params.noise_intensity = 0.5;
+        params.dt = 0.01;
+        params.t_end = 2.0;
+
+        let a = solve_stochastic(&params, 0.0, 0.0);
+        let b = solve_stochastic(&params, 0.0, 0.0);
+        assert_eq!(a.len(), 201);
+        assert_eq!(a.last().unwrap().1.omega, b.last().unwrap().1.omega);
+
+        for scheme in [NoiseScheme::EulerMaruyama, NoiseScheme::Heun] {
+            params.noise_scheme = scheme;
+            let mut rng = seeded_rng(11);
+            let finals: Vec<f64> = (0..4000)
+                .map(|_| StochasticTrajectory::with_rng(&params, 0.0, 0.0, &mut rng).last().unwrap().1.omega)
+                .collect();
+            let mean = finals.iter().sum::<f64>() / finals.len() as f64;
+            let var = finals.iter().map(|w| (w - mean).powi(2)).sum::<f64>() / finals.len() as f64;
+            assert_relative_eq!(var, 0.25 * 2.0, max_relative = 0.08);
+        }
+    }
+}
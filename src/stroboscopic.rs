@@ -0,0 +1,185 @@
+//stroboscopic.rs
+use std::f64::consts::PI;
+use crate::model::{Integrator, PendulumParams};
+use crate::solve_equation::{rk4_step_h, steps_per_period, verlet_step, wrap_angle, yoshida_step, yoshida_weights, State};
+
+// 频闪映射 F：从驱动相位 0 出发积分一个驱动周期。求周期轨道、吸引域、不变流形、分岔扫描等算法
+// 只需要 F 的像，不需要中间的轨迹点；StroboscopicMap 在构造时定好每周期步数、带符号的步长与积分器
+// （Yoshida 的复合系数也只算一次），迭代时不分配内存、不保存轨迹。
+// 第 k 步（从映射的第 0 次作用算起）的时刻取 k·h 而不是累加 h，与 solve_equation::stroboscopic_map 一致；
+// 连续迭代时驱动时间接着往下走，非周期驱动（双频驱动、f_d/q 时间表）下也与一次积分到底相同。
+
+#[derive(Debug, Clone, Copy)]
+enum Stepper {
+    Rk4,
+    Verlet,
+    Yoshida { weights: (f64, f64) },
+}
+
+/// 频闪映射 F
+#[derive(Debug, Clone)]
+pub struct StroboscopicMap {
+    params: PendulumParams,
+    steps: usize,
+    /// 带符号的步长（Backward 时为负）
+    h: f64,
+    stepper: Stepper,
+}
+
+impl StroboscopicMap {
+    /// 每周期 steps_per_period(params) 步；积分器与时间方向取自 params。不检查参数，omega_d 须非零
+    pub fn new(params: &PendulumParams) -> Self {
+        StroboscopicMap::with_steps(params, steps_per_period(params))
+    }
+
+    /// 每周期 steps 步（至少 1 步）
+    pub fn with_steps(params: &PendulumParams, steps: usize) -> Self {
+        let steps = steps.max(1);
+        let mut params = params.clone();
+        params.set_steps_per_period(steps);
+        let h = params.direction.sign() * params.dt;
+        let stepper = match params.integrator {
+            Integrator::Rk4 => Stepper::Rk4,
+            Integrator::VelocityVerlet => Stepper::Verlet,
+            Integrator::Yoshida4 => Stepper::Yoshida { weights: yoshida_weights() },
+        };
+        StroboscopicMap { params, steps, h, stepper }
+    }
+
+    pub fn params(&self) -> &PendulumParams {
+        &self.params
+    }
+
+    pub fn steps_per_period(&self) -> usize {
+        self.steps
+    }
+
+    /// 驱动周期 2π/|Ω|
+    pub fn period(&self) -> f64 {
+        2.0 * PI / self.params.omega_d.abs()
+    }
+
+    // 从第 first 个周期的起点（t = first·T）出发积分 periods 个周期
+    fn advance(&self, state: State, first: usize, periods: usize) -> State {
+        let (h, p) = (self.h, &self.params);
+        let steps = first * self.steps..(first + periods) * self.steps;
+        match self.stepper {
+            Stepper::Rk4 => steps.fold(state, |s, k| rk4_step_h(&s, k as f64 * h, h, p)),
+            Stepper::Verlet => steps.fold(state, |s, k| verlet_step(&s, k as f64 * h, h, p)),
+            Stepper::Yoshida { weights } => steps.fold(state, |s, k| yoshida_step(&s, k as f64 * h, h, weights, p)),
+        }
+    }
+
+    /// F(state)，θ 不折叠
+    pub fn iterate(&self, state: State) -> State {
+        self.advance(state, 0, 1)
+    }
+
+    /// F^n(state)，θ 不折叠
+    pub fn iterate_n(&self, state: State, n: usize) -> State {
+        self.advance(state, 0, n)
+    }
+
+    /// 依次产生 F(state), F²(state), ... 的无穷迭代器（θ 不折叠）
+    pub fn images(&self, state: State) -> Images<'_> {
+        Images {
+            map: self,
+            state,
+            period: 0,
+        }
+    }
+
+    /// F^transient(state), F^{transient+1}(state), ..., F^{transient+samples}(state)，共 samples + 1 个状态，θ 不折叠
+    ///
+    /// 首个元素是过渡期结束时的状态，末个元素可以作为下一段（例如下一个参数值）的初值。
+    pub fn orbit(&self, state: State, transient: usize, samples: usize) -> Vec<State> {
+        let first = self.iterate_n(state, transient);
+        let images = Images {
+            map: self,
+            state: first,
+            period: transient,
+        };
+        std::iter::once(first).chain(images.take(samples)).collect()
+    }
+
+    /// 丢掉前 transient 个像后的 samples 个庞加莱点（θ 折叠到 [-π, π)）
+    ///
+    /// 与 stroboscopic_samples(&solve_periods(..), ..) 给出相同的点（差别在舍入误差量级），但不保存轨迹。
+    pub fn section(&self, state: State, transient: usize, samples: usize) -> Vec<(f64, f64)> {
+        section_points(&self.orbit(state, transient, samples))
+    }
+}
+
+/// orbit 的结果去掉首个状态、θ 折叠到 [-π, π) 后的庞加莱点
+pub fn section_points(orbit: &[State]) -> Vec<(f64, f64)> {
+    orbit.iter().skip(1).map(|s| (wrap_angle(s.theta), s.omega)).collect()
+}
+
+/// orbit 的结果上平均每个驱动周期转过的圈数（与 analysis::rotation_number 在整条轨迹上的结果相同）
+pub fn orbit_rotation_number(orbit: &[State]) -> f64 {
+    match (orbit.first(), orbit.last()) {
+        (Some(first), Some(last)) if orbit.len() >= 2 => (last.theta - first.theta) / (2.0 * PI * (orbit.len() - 1) as f64),
+        _ => 0.0,
+    }
+}
+
+/// StroboscopicMap::images 返回的迭代器
+#[derive(Debug, Clone)]
+pub struct Images<'a> {
+    map: &'a StroboscopicMap,
+    state: State,
+    period: usize,
+}
+
+impl Iterator for Images<'_> {
+    type Item = State;
+
+    fn next(&mut self) -> Option<State> {
+        self.state = self.map.advance(self.state, self.period, 1);
+        self.period += 1;
+        Some(self.state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Forcing, TimeDirection};
+    use crate::solve_equation::{solve_periods, stroboscopic_samples};
+
+    #[test]
+    fn test_map_matches_trajectory_samples() {
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.q = 0.5;
+        params.f_d = 1.2;
+        params.omega_d = 2.0 / 3.0;
+        params.set_steps_per_period(100);
+        let start = State { theta: 0.2, omega: 0.0 };
+        let map = StroboscopicMap::new(&params);
+        // 时刻的取法不同（k·h 与累加 h），混沌轨道上舍入误差按 e^{λt} 放大
+        let reference = stroboscopic_samples(&solve_periods(&params, 0.2, 0.0, 8, 100).unwrap(), 100, 3, 5);
+        let orbit = map.orbit(start, 3, 5);
+        let section = section_points(&orbit);
+        assert_eq!(section.len(), 5);
+        for (a, b) in section.iter().zip(&reference) {
+            assert!((a.0 - b.0).abs() < 1e-8 && (a.1 - b.1).abs() < 1e-8, "{:?} vs {:?}", a, b);
+        }
+        assert_eq!(orbit[5], map.iterate_n(start, 8));
+        let traj = solve_periods(&params, 0.2, 0.0, 8, 100).unwrap();
+        assert!((orbit_rotation_number(&orbit) - crate::analysis::rotation_number(&traj[300..], &params)).abs() < 1e-9);
+        assert_eq!(map.images(start).nth(4), Some(map.iterate_n(start, 5)));
+
+        // 非周期驱动下连续迭代沿用绝对时间；逆向映射还原
+        params.forcing = Forcing::two_frequency(0.3, params.omega_d, 0.2, 1.0787);
+        let map = StroboscopicMap::new(&params);
+        let section = map.section(start, 3, 4);
+        let reference = stroboscopic_samples(&solve_periods(&params, 0.2, 0.0, 7, 100).unwrap(), 100, 3, 4);
+        assert!(section.iter().zip(&reference).all(|(a, b)| (a.0 - b.0).abs() < 1e-8));
+        params.forcing = Forcing::Sine;
+        let forward = StroboscopicMap::with_steps(&params, 400);
+        params.direction = TimeDirection::Backward;
+        let back = StroboscopicMap::with_steps(&params, 400).iterate(forward.iterate(start));
+        assert!((back.theta - start.theta).abs() < 1e-6 && (back.omega - start.omega).abs() < 1e-6);
+    }
+}
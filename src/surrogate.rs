@@ -0,0 +1,134 @@
+//surrogate.rs
+use std::f64::consts::PI;
+use std::fs::File;
+use std::io::Write;
+use crate::random::RandomSource;
+use crate::spectrum::{fft, ifft};
+
+// 替代数据（surrogate）用于非线性/混沌检验的零假设：
+// 相位随机化替代数据保留功率谱（线性相关结构），AAFT 进一步保留幅值分布。
+// 两者都要求 FFT 长度为 2 的幂，输入序列会被截断到不超过其长度的最大 2 的幂。
+
+fn power_of_two_len(n: usize) -> usize {
+    if n == 0 {
+        0
+    } else {
+        1usize << (usize::BITS - 1 - n.leading_zeros())
+    }
+}
+
+// 返回 values 按 ranks_of 的秩重新排列后的序列：
+// 结果的第 i 个元素是 values 中与 ranks_of[i] 同秩的值
+fn rank_remap(values: &[f64], ranks_of: &[f64]) -> Vec<f64> {
+    let mut sorted_values = values.to_vec();
+    sorted_values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let mut order: Vec<usize> = (0..ranks_of.len()).collect();
+    order.sort_by(|&a, &b| ranks_of[a].partial_cmp(&ranks_of[b]).unwrap());
+
+    let mut out = vec![0.0; ranks_of.len()];
+    for (rank, &idx) in order.iter().enumerate() {
+        out[idx] = sorted_values[rank];
+    }
+    out
+}
+
+/// 相位随机化替代数据：保持各频率分量的幅值，相位在 [0, 2π) 上均匀随机
+///
+/// 直流与奈奎斯特分量保持不变，并维持共轭对称，保证结果为实序列。
+pub fn phase_randomized<R: RandomSource>(series: &[f64], rng: &mut R) -> Vec<f64> {
+    let n = power_of_two_len(series.len());
+    if n < 4 {
+        return series[..n].to_vec();
+    }
+    let mut re = series[..n].to_vec();
+    let mut im = vec![0.0; n];
+    fft(&mut re, &mut im);
+
+    for k in 1..n / 2 {
+        let amplitude = (re[k] * re[k] + im[k] * im[k]).sqrt();
+        let phase = rng.uniform(0.0, 2.0 * PI);
+        re[k] = amplitude * phase.cos();
+        im[k] = amplitude * phase.sin();
+        re[n - k] = re[k];
+        im[n - k] = -im[k];
+    }
+
+    ifft(&mut re, &mut im);
+    re
+}
+
+/// 幅值调整傅里叶变换（AAFT）替代数据
+///
+/// 1. 生成高斯序列并按原序列的秩排列（把原序列“高斯化”）；
+/// 2. 对高斯化序列做相位随机化；
+/// 3. 把原序列的值按第 2 步结果的秩重新排列。
+///
+/// 结果与原序列（截断后）的值集合完全相同，功率谱近似保持。
+pub fn aaft<R: RandomSource>(series: &[f64], rng: &mut R) -> Vec<f64> {
+    let n = power_of_two_len(series.len());
+    let data = &series[..n];
+    let gaussian: Vec<f64> = (0..n).map(|_| rng.standard_normal()).collect();
+    let gaussianized = rank_remap(&gaussian, data);
+    let randomized = phase_randomized(&gaussianized, rng);
+    rank_remap(data, &randomized)
+}
+
+/// 把原序列与若干替代序列写成 CSV（列：index,original,surrogate_0,surrogate_1,...）
+///
+/// 行数取所有序列中最短的长度。
+pub fn write_surrogates_csv(path: &str, original: &[f64], surrogates: &[Vec<f64>]) -> std::io::Result<()> {
+    let rows = surrogates
+        .iter()
+        .map(|s| s.len())
+        .fold(original.len(), usize::min);
+    let mut f = File::create(path)?;
+    write!(f, "index,original")?;
+    for k in 0..surrogates.len() {
+        write!(f, ",surrogate_{}", k)?;
+    }
+    writeln!(f)?;
+    for i in 0..rows {
+        write!(f, "{},{:.12}", i, original[i])?;
+        for s in surrogates {
+            write!(f, ",{:.12}", s[i])?;
+        }
+        writeln!(f)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use crate::random::seeded_rng;
+
+    #[test]
+    fn test_surrogates_preserve_spectrum_and_distribution() {
+        let mut rng = seeded_rng(7);
+        let series: Vec<f64> = (0..1000)
+            .map(|i| (0.05 * i as f64).sin() + 0.3 * (0.31 * i as f64).cos().powi(3))
+            .collect();
+        let n = 512;
+
+        // 相位随机化：各频率幅值不变
+        let surrogate = phase_randomized(&series, &mut rng);
+        assert_eq!(surrogate.len(), n);
+        let magnitudes = |x: &[f64]| {
+            let mut re = x.to_vec();
+            let mut im = vec![0.0; x.len()];
+            fft(&mut re, &mut im);
+            re.iter().zip(&im).map(|(a, b)| (a * a + b * b).sqrt()).collect::<Vec<_>>()
+        };
+        for (a, b) in magnitudes(&series[..n]).iter().zip(magnitudes(&surrogate).iter()) {
+            assert_relative_eq!(a, b, epsilon = 1e-8);
+        }
+
+        // AAFT：值的集合与原序列相同
+        let mut sorted_original = series[..n].to_vec();
+        sorted_original.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let mut sorted_aaft = aaft(&series, &mut rng);
+        sorted_aaft.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(sorted_original, sorted_aaft);
+    }
+}
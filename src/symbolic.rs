@@ -0,0 +1,202 @@
+//symbolic.rs
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::fs::File;
+use std::io::Write;
+use crate::events::{section_crossings, Crossing, SectionCondition};
+use crate::model::PendulumParams;
+use crate::solve_equation::{wrap_angle, State};
+
+// 符号动力学：用相空间的一个划分把截面点序列粗粒化成符号序列（itinerary），
+// 例如频闪点落在 θ = 0 的左/右、或每次穿过 θ = 0 时 ω 的正负。
+// 长度为 n 的符号块的 Shannon 熵 H_n = -Σ p log p 随 n 近似线性增长：
+//   熵率 h_n = H_n - H_{n-1}   → 划分下的测度熵（生成划分时即 KS 熵）
+//   拓扑熵 ln N_n - ln N_{n-1} → N_n 为出现过的不同块数，统计可容许的符号序列数目的增长率
+// 周期轨道两者都趋于 0，混沌运动为正。熵以自然对数计（nats），可直接与 Lyapunov 指数比较（每个截面点）。
+// 有限样本只能可靠估计到 alphabet^n 远小于序列长度的块长，超出后 N_n 饱和在样本数附近，估计偏低。
+
+/// 划分所依据的坐标
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Coordinate {
+    /// θ，折叠到 [-π, π) 后比较
+    Theta,
+    /// ω
+    Omega,
+}
+
+/// 按一个坐标的若干切点划分相空间：符号为坐标值大于等于的切点个数，字母表大小为 cuts.len() + 1
+#[derive(Debug, Clone, PartialEq)]
+pub struct Partition {
+    pub coordinate: Coordinate,
+    /// 升序切点
+    pub cuts: Vec<f64>,
+}
+
+impl Partition {
+    /// 切点会被排序
+    pub fn new(coordinate: Coordinate, mut cuts: Vec<f64>) -> Self {
+        cuts.sort_by(f64::total_cmp);
+        Partition { coordinate, cuts }
+    }
+
+    /// θ = 0 的左 (0) / 右 (1)
+    pub fn theta_sign() -> Self {
+        Partition::new(Coordinate::Theta, vec![0.0])
+    }
+
+    /// ω 为负 (0) / 非负 (1)
+    pub fn omega_sign() -> Self {
+        Partition::new(Coordinate::Omega, vec![0.0])
+    }
+
+    /// 字母表大小
+    pub fn alphabet_size(&self) -> usize {
+        self.cuts.len() + 1
+    }
+
+    /// 截面点 (θ, ω) 的符号
+    pub fn symbol(&self, theta: f64, omega: f64) -> u8 {
+        let x = match self.coordinate {
+            Coordinate::Theta => wrap_angle(theta),
+            Coordinate::Omega => omega,
+        };
+        self.cuts.partition_point(|c| *c <= x) as u8
+    }
+}
+
+/// 把截面点序列（如 poincare 的输出）转成符号序列
+pub fn symbolize(points: &[(f64, f64)], partition: &Partition) -> Vec<u8> {
+    points.iter().map(|&(theta, omega)| partition.symbol(theta, omega)).collect()
+}
+
+/// 轨迹穿越状态截面时的符号序列；direction 为 Some 时只取该方向的穿越
+///
+/// 例如 SectionCondition::Theta(0.0) 配合 Partition::omega_sign() 即每次经过最低点时的转向。
+pub fn itinerary(
+    traj: &[(f64, State)],
+    condition: SectionCondition,
+    direction: Option<Crossing>,
+    partition: &Partition,
+    params: &PendulumParams,
+) -> Vec<u8> {
+    section_crossings(traj, condition, direction, params)
+        .iter()
+        .map(|p| partition.symbol(p.theta, p.omega))
+        .collect()
+}
+
+/// 一个块长上的统计
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlockEntropy {
+    pub length: usize,
+    /// 长度为 length 的（重叠）块总数
+    pub blocks: usize,
+    /// 出现过的不同块数 N_n
+    pub distinct: usize,
+    /// 块熵 H_n（nats）
+    pub entropy: f64,
+}
+
+/// 块长 1..=max_length 的块熵；块数为 0 的长度不再计算
+///
+/// 符号可以是任意可哈希的类型（字母表超过 256 时例如直方图格子的编号）。
+pub fn block_entropies<T: Eq + Hash>(symbols: &[T], max_length: usize) -> Vec<BlockEntropy> {
+    let mut out = Vec::new();
+    for length in 1..=max_length.min(symbols.len()) {
+        let mut counts: HashMap<&[T], usize> = HashMap::new();
+        for block in symbols.windows(length) {
+            *counts.entry(block).or_default() += 1;
+        }
+        let blocks = symbols.len() + 1 - length;
+        let entropy = counts
+            .values()
+            .map(|&c| {
+                let p = c as f64 / blocks as f64;
+                -p * p.ln()
+            })
+            .sum();
+        out.push(BlockEntropy { length, blocks, distinct: counts.len(), entropy });
+    }
+    out
+}
+
+/// 块长 n 上的熵率与拓扑熵估计（均为相邻两个块长之差，n = 1 时即 H_1 与 ln N_1）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EntropyEstimate {
+    pub length: usize,
+    /// h_n = H_n - H_{n-1}（nats / 符号）
+    pub entropy_rate: f64,
+    /// ln N_n - ln N_{n-1}（nats / 符号）
+    pub topological: f64,
+}
+
+/// 由 block_entropies 的输出逐块长给出熵估计
+pub fn entropy_estimates(entropies: &[BlockEntropy]) -> Vec<EntropyEstimate> {
+    let mut prev = (0.0, 0.0);
+    entropies
+        .iter()
+        .map(|b| {
+            let ln_n = (b.distinct as f64).ln();
+            let estimate = EntropyEstimate {
+                length: b.length,
+                entropy_rate: b.entropy - prev.0,
+                topological: ln_n - prev.1,
+            };
+            prev = (b.entropy, ln_n);
+            estimate
+        })
+        .collect()
+}
+
+/// 按字母表与样本数给出块统计可靠的最大块长：alphabet^n 不超过 symbols / 10
+pub fn reliable_block_length(symbols: usize, alphabet: usize) -> usize {
+    let alphabet = alphabet.max(2) as f64;
+    ((symbols as f64 / 10.0).ln() / alphabet.ln()).floor().max(1.0) as usize
+}
+
+/// 把块熵与熵估计写成 CSV（length,blocks,distinct,entropy,entropy_rate,topological）
+pub fn write_block_entropy_csv(path: &str, entropies: &[BlockEntropy]) -> std::io::Result<()> {
+    let mut f = std::io::BufWriter::new(File::create(path)?);
+    writeln!(f, "length,blocks,distinct,entropy,entropy_rate,topological")?;
+    for (b, e) in entropies.iter().zip(entropy_estimates(entropies)) {
+        writeln!(
+            f,
+            "{},{},{},{:.12},{:.12},{:.12}",
+            b.length, b.blocks, b.distinct, b.entropy, e.entropy_rate, e.topological
+        )?;
+    }
+    f.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::seeded_rng;
+    use rand::Rng;
+
+    #[test]
+    fn test_entropies_of_periodic_and_random_sequences() {
+        // 周期 2 序列：块长 ≥ 2 后只有两种块，熵率与拓扑熵都为 0
+        let periodic: Vec<u8> = (0..1000).map(|i| (i % 2) as u8).collect();
+        let estimates = entropy_estimates(&block_entropies(&periodic, 6));
+        assert_eq!(estimates.len(), 6);
+        for e in &estimates[1..] {
+            assert!(e.entropy_rate.abs() < 1e-3 && e.topological.abs() < 1e-12, "{:?}", e);
+        }
+
+        // 均匀随机二元序列：两者都约为 ln 2
+        let mut rng = seeded_rng(7);
+        let random: Vec<u8> = (0..100_000).map(|_| rng.random_range(0..2u8)).collect();
+        let n = reliable_block_length(random.len(), 2);
+        assert_eq!(n, 13);
+        let last = *entropy_estimates(&block_entropies(&random, 8)).last().unwrap();
+        assert!((last.entropy_rate - 2f64.ln()).abs() < 0.01, "{:?}", last);
+        assert!((last.topological - 2f64.ln()).abs() < 1e-12, "{:?}", last);
+
+        // 划分：θ 先折叠，切点自动排序
+        let partition = Partition::new(Coordinate::Theta, vec![1.0, -1.0]);
+        assert_eq!(partition.alphabet_size(), 3);
+        assert_eq!(symbolize(&[(-2.0, 0.0), (0.0, 0.0), (1.0, 0.0), (2.0 * std::f64::consts::PI, 0.0)], &partition), vec![0, 1, 2, 1]);
+        assert_eq!(Partition::omega_sign().symbol(3.0, -0.1), 0);
+    }
+}
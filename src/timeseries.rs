@@ -0,0 +1,169 @@
+//timeseries.rs
+use std::fs::File;
+use std::io::Write;
+use rayon::prelude::*;
+use crate::solve_equation::{wrap_angle, State};
+use crate::spectrum::{fft, ifft};
+
+// 标量时间序列的线性与非线性相关：自相关函数 C(τ) 只反映线性相关，衰减到 0（或 1/e）的时间是常用的
+// 相关时间；平均互信息 I(τ) 还能看到非线性相关，它的第一个极小是延迟嵌入常用的延迟（见 embedding）。
+// 自相关用 Wiener–Khinchin 定理经 FFT 计算，O(N log N)。
+
+/// 某个延迟下的自相关与平均互信息
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LagStatistics {
+    pub lag: usize,
+    pub autocorrelation: f64,
+    pub mutual_information: f64,
+}
+
+/// 归一化自相关 C(τ)，τ = 0..=max_lag（超过 N - 1 的部分截去），C(0) = 1
+///
+/// 先减去均值，补零到不小于 2N 的 2 的幂以避免循环相关的回绕；采用有偏估计（除以 N 而不是 N - τ），
+/// 大延迟处更稳定。常数序列返回全 0（除 C(0) = 1）。
+pub fn autocorrelation(series: &[f64], max_lag: usize) -> Vec<f64> {
+    let n = series.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    let mean = series.iter().sum::<f64>() / n as f64;
+    let size = (2 * n).next_power_of_two();
+    let mut re = vec![0.0; size];
+    let mut im = vec![0.0; size];
+    for (r, x) in re.iter_mut().zip(series) {
+        *r = x - mean;
+    }
+    fft(&mut re, &mut im);
+    for (r, i) in re.iter_mut().zip(im.iter_mut()) {
+        *r = *r * *r + *i * *i;
+        *i = 0.0;
+    }
+    ifft(&mut re, &mut im);
+    let c0 = re[0];
+    (0..=max_lag.min(n - 1))
+        .map(|lag| if lag == 0 { 1.0 } else if c0 > 0.0 { re[lag] / c0 } else { 0.0 })
+        .collect()
+}
+
+/// 自相关第一次降到 0 以下的延迟；没有过零时返回 None
+pub fn first_zero_crossing(acf: &[f64]) -> Option<usize> {
+    acf.iter().position(|c| *c <= 0.0)
+}
+
+/// s_i 与 s_{i+lag} 的平均互信息（单位 nat），按秩分成 bins 个等概率格子估计
+///
+/// 等概率格子对正弦这类在两端聚集的分布比等宽格子稳定。
+pub fn mutual_information(series: &[f64], lag: usize, bins: usize) -> f64 {
+    if bins == 0 || series.len() <= lag {
+        return 0.0;
+    }
+    let bin = rank_bins(series, bins);
+    mutual_information_of_bins(&bin, lag, bins)
+}
+
+/// 平均互信息 I(τ)，τ = 0..=max_lag；格子只划分一次，各延迟并行计算
+pub fn average_mutual_information(series: &[f64], max_lag: usize, bins: usize) -> Vec<f64> {
+    if bins == 0 {
+        return vec![0.0; max_lag + 1];
+    }
+    let bin = rank_bins(series, bins);
+    (0..=max_lag).into_par_iter().map(|lag| mutual_information_of_bins(&bin, lag, bins)).collect()
+}
+
+// 每个点按秩所在的等概率格子
+fn rank_bins(series: &[f64], bins: usize) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..series.len()).collect();
+    order.sort_by(|&a, &b| series[a].partial_cmp(&series[b]).unwrap());
+    let mut bin = vec![0; series.len()];
+    for (rank, &i) in order.iter().enumerate() {
+        bin[i] = rank * bins / series.len();
+    }
+    bin
+}
+
+fn mutual_information_of_bins(bin: &[usize], lag: usize, bins: usize) -> f64 {
+    if bin.len() <= lag {
+        return 0.0;
+    }
+    let n = bin.len() - lag;
+    let mut joint = vec![0usize; bins * bins];
+    let mut first = vec![0usize; bins];
+    let mut second = vec![0usize; bins];
+    for i in 0..n {
+        let (a, b) = (bin[i], bin[i + lag]);
+        joint[a * bins + b] += 1;
+        first[a] += 1;
+        second[b] += 1;
+    }
+    let n = n as f64;
+    let mut info = 0.0;
+    for a in 0..bins {
+        for b in 0..bins {
+            let c = joint[a * bins + b];
+            if c > 0 {
+                let p = c as f64 / n;
+                info += p * (p * n * n / (first[a] as f64 * second[b] as f64)).ln();
+            }
+        }
+    }
+    info
+}
+
+/// 同一序列在 τ = 0..=max_lag 上的自相关与平均互信息
+pub fn lag_statistics(series: &[f64], max_lag: usize, bins: usize) -> Vec<LagStatistics> {
+    let acf = autocorrelation(series, max_lag);
+    let ami = average_mutual_information(series, max_lag, bins);
+    acf.into_iter()
+        .zip(ami)
+        .enumerate()
+        .map(|(lag, (autocorrelation, mutual_information))| LagStatistics {
+            lag,
+            autocorrelation,
+            mutual_information,
+        })
+        .collect()
+}
+
+/// 等间隔采样轨迹中 θ 的自相关与平均互信息；θ 先折叠到 [-π, π)，转动时不至于被线性漂移主导
+pub fn theta_lag_statistics(traj: &[(f64, State)], max_lag: usize, bins: usize) -> Vec<LagStatistics> {
+    let theta: Vec<f64> = traj.iter().map(|(_, s)| wrap_angle(s.theta)).collect();
+    lag_statistics(&theta, max_lag, bins)
+}
+
+/// 把自相关与互信息写成 CSV（列：lag,time,autocorrelation,mutual_information），time = lag·dt
+pub fn write_lag_statistics_csv(path: &str, rows: &[LagStatistics], dt: f64) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(f, "lag,time,autocorrelation,mutual_information")?;
+    for r in rows {
+        writeln!(f, "{},{:.12},{:.12},{:.12}", r.lag, r.lag as f64 * dt, r.autocorrelation, r.mutual_information)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_autocorrelation_matches_direct_sum_and_mi_peaks_at_zero() {
+        // 周期 40 的正弦加一点确定性扰动
+        let series: Vec<f64> = (0..500)
+            .map(|i| (2.0 * std::f64::consts::PI * i as f64 / 40.0).sin() + 0.1 * (i as f64 * 0.37).sin())
+            .collect();
+        let acf = autocorrelation(&series, 60);
+        assert_eq!(acf.len(), 61);
+        let n = series.len();
+        let mean = series.iter().sum::<f64>() / n as f64;
+        let c = |lag: usize| (0..n - lag).map(|i| (series[i] - mean) * (series[i + lag] - mean)).sum::<f64>();
+        for lag in [1, 10, 25, 60] {
+            assert!((acf[lag] - c(lag) / c(0)).abs() < 1e-12);
+        }
+        assert!(matches!(first_zero_crossing(&acf), Some(10..=11)));
+        assert!(acf[40] > 0.8);
+
+        let rows = lag_statistics(&series, 60, 16);
+        assert_eq!(rows.len(), 61);
+        assert!(rows.iter().skip(1).all(|r| r.mutual_information < rows[0].mutual_information));
+        assert!((rows[5].mutual_information - mutual_information(&series, 5, 16)).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,223 @@
+//torus.rs
+use std::f64::consts::PI;
+use serde::Serialize;
+use crate::attractor::{detect_period, summarize};
+use crate::solve_equation::wrap_angle;
+
+// 环面（准周期）吸引子的检测：准周期运动在频闪截面上是一条不变闭曲线，截面映射限制在曲线上
+// 与一个圆周旋转共轭，因此
+//   1. 沿曲线的角坐标每次前进的比例（旋转数 ρ）收敛到一个无理数；锁频时 ρ = p/q，截面上只剩 q 个点；
+//   2. 映射保持曲线上各点的循环顺序：排序后相邻的两点，它们的像仍然相邻。
+// 混沌吸引子与填满一片区域的三维环面（T³）都不满足第 2 条。
+// 曲线的角坐标取两种之一：绕质心的极角（曲线围住质心时），或折叠后的 θ 本身（曲线绕柱面一圈时，转动解）；
+// 自动选保序程度较高的一个。既不围住质心也不绕柱面的曲线（如月牙形）检测不出来。
+//
+// 这里给出 Ruelle–Takens 路径的诊断：双频驱动（model::Forcing::two_frequency）下，
+// 周期 → T²（截面上一条闭曲线）→ 锁频或 T³ / 混沌。
+
+/// 曲线上角坐标的取法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CurveAngle {
+    /// 绕质心的极角，顺时针为正（(θ, ω) 平面上振动的自然转向）
+    Centroid,
+    /// 折叠后的 θ
+    Theta,
+}
+
+/// 环面检测的结果
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct TorusReport {
+    pub angle: CurveAngle,
+    /// 旋转数 ρ ∈ [0, 1)
+    pub rotation_number: f64,
+    /// 分母不超过 max_denominator 的最佳有理逼近 (p, q)
+    pub rational: (u64, u64),
+    /// |ρ - p/q|
+    pub rational_error: f64,
+    /// 排序后相邻点对中，像仍然相邻的比例（闭曲线上为 1）
+    pub order_preservation: f64,
+    /// 截面点是否为周期轨道（此时不是环面）
+    pub period: Option<usize>,
+}
+
+/// 环面检测的设置
+#[derive(Debug, Clone, Copy)]
+pub struct TorusOptions {
+    /// 检测周期轨道的最大周期与距离容差
+    pub max_period: usize,
+    pub period_tolerance: f64,
+    /// 判定锁频时有理逼近的最大分母与容差
+    pub max_denominator: u64,
+    pub rational_tolerance: f64,
+    /// 判定为闭曲线所需的最低保序比例
+    pub min_order_preservation: f64,
+}
+
+impl Default for TorusOptions {
+    fn default() -> Self {
+        TorusOptions {
+            max_period: 16,
+            period_tolerance: 1e-6,
+            max_denominator: 20,
+            rational_tolerance: 1e-3,
+            min_order_preservation: 0.95,
+        }
+    }
+}
+
+impl TorusReport {
+    /// 截面点落在一条闭曲线上，且旋转数离所有分母不超过 max_denominator 的分数都足够远
+    pub fn is_torus(&self, options: &TorusOptions) -> bool {
+        self.period.is_none()
+            && self.order_preservation >= options.min_order_preservation
+            && self.rational_error > options.rational_tolerance
+    }
+
+    /// 截面点落在闭曲线上，但旋转数接近 p/q（锁频或接近锁频）
+    pub fn is_locked(&self, options: &TorusOptions) -> bool {
+        self.period.is_some()
+            || (self.order_preservation >= options.min_order_preservation && self.rational_error <= options.rational_tolerance)
+    }
+}
+
+/// x ∈ [0, 1) 在分母不超过 max_denominator 时的最佳有理逼近（连分数的渐近分数与中间分数）
+pub fn best_rational(x: f64, max_denominator: u64) -> (u64, u64) {
+    let max_denominator = max_denominator.max(1);
+    let (mut p0, mut q0, mut p1, mut q1) = (0u64, 1u64, 1u64, 0u64);
+    let mut r = x;
+    loop {
+        let a = r.floor();
+        if q0 as f64 + a * q1 as f64 > max_denominator as f64 {
+            // 下一个渐近分数的分母超限：在中间分数与当前渐近分数中取较近者
+            let k = (max_denominator - q0) / q1;
+            let (pm, qm) = (p0 + k * p1, q0 + k * q1);
+            let err = |p: u64, q: u64| (x - p as f64 / q as f64).abs();
+            return if err(pm, qm) < err(p1, q1) { (pm, qm) } else { (p1, q1) };
+        }
+        let a = a as u64;
+        (p0, q0, p1, q1) = (p1, q1, a * p1 + p0, a * q1 + q0);
+        let frac = r - r.floor();
+        if frac < 1e-12 {
+            return (p1, q1);
+        }
+        r = 1.0 / frac;
+    }
+}
+
+// 每个点在曲线上的角坐标
+fn curve_angles(samples: &[(f64, f64)], angle: CurveAngle) -> Vec<f64> {
+    match angle {
+        CurveAngle::Theta => samples.iter().map(|&(theta, _)| wrap_angle(theta)).collect(),
+        CurveAngle::Centroid => {
+            let Some(summary) = summarize(samples) else {
+                return Vec::new();
+            };
+            samples
+                .iter()
+                .map(|&(theta, omega)| (summary.omega_centroid - omega).atan2(wrap_angle(theta - summary.theta_centroid)))
+                .collect()
+        }
+    }
+}
+
+// 旋转数：每次回归角坐标前进量（折到 [0, 2π)）的平均值除以 2π
+fn rotation_number(angles: &[f64]) -> f64 {
+    let n = angles.len() - 1;
+    let total: f64 = angles.windows(2).map(|w| (w[1] - w[0]).rem_euclid(2.0 * PI)).sum();
+    (total / (2.0 * PI * n as f64)).rem_euclid(1.0)
+}
+
+// 保序比例：点 0..n-1 与它们的像 1..n 各自按角坐标排序，
+// 统计循环相邻的点对 (a, b) 中像 (a+1, b+1) 也循环相邻的比例
+fn order_preservation(angles: &[f64]) -> f64 {
+    let m = angles.len() - 1;
+    let sorted = |range: std::ops::Range<usize>| {
+        let mut idx: Vec<usize> = range.collect();
+        idx.sort_by(|&a, &b| angles[a].total_cmp(&angles[b]));
+        idx
+    };
+    let domain = sorted(0..m);
+    let mut image_rank = vec![0; m + 1];
+    for (rank, &i) in sorted(1..m + 1).iter().enumerate() {
+        image_rank[i] = rank;
+    }
+    let kept = (0..m)
+        .filter(|&k| {
+            let (a, b) = (domain[k], domain[(k + 1) % m]);
+            image_rank[b + 1] == (image_rank[a + 1] + 1) % m
+        })
+        .count();
+    kept as f64 / m as f64
+}
+
+/// 由庞加莱点（已去掉过渡期）检测环面吸引子；点数少于 16 时返回 None
+///
+/// 先检测周期轨道；再分别用两种角坐标计算保序比例，取较高者估计旋转数与有理逼近。
+/// 旋转数的误差约为 1/N，rational_tolerance 不宜小于几倍 1/N。
+pub fn detect_torus(samples: &[(f64, f64)], options: &TorusOptions) -> Option<TorusReport> {
+    if samples.len() < 16 {
+        return None;
+    }
+    let period = detect_period(samples, options.max_period, options.period_tolerance);
+    let (angle, angles, order) = [CurveAngle::Centroid, CurveAngle::Theta]
+        .into_iter()
+        .map(|angle| {
+            let angles = curve_angles(samples, angle);
+            let order = order_preservation(&angles);
+            (angle, angles, order)
+        })
+        .max_by(|a, b| a.2.total_cmp(&b.2))?;
+    let rotation = rotation_number(&angles);
+    let rational = best_rational(rotation, options.max_denominator);
+    let rational_error = (rotation - rational.0 as f64 / rational.1 as f64).abs();
+    Some(TorusReport {
+        angle,
+        rotation_number: rotation,
+        rational,
+        rational_error,
+        order_preservation: order,
+        period,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Forcing, PendulumParams};
+    use crate::solve_equation::poincare;
+
+    #[test]
+    fn test_two_frequency_response_is_torus_and_chaos_is_not() {
+        assert_eq!(best_rational(0.618_034, 20), (8, 13));
+        assert_eq!(best_rational(0.333_4, 20), (1, 3));
+        assert_eq!(best_rational(0.999, 5), (1, 1));
+
+        // 弱双频驱动：近似线性响应，截面上是第二个驱动相位参数化的椭圆，ρ = ω2/ω1 mod 1
+        let golden = (5f64.sqrt() - 1.0) / 2.0;
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.q = 0.5;
+        params.omega_d = 2.0 / 3.0;
+        params.forcing = Forcing::two_frequency(0.3, params.omega_d, 0.2, params.omega_d * (1.0 + golden));
+        params.set_steps_per_period(200);
+        params.t_end = 2.0 * PI / params.omega_d * 331.0;
+        let section = poincare(&params, 0.0, 0.0, 30, 300).unwrap();
+        let options = TorusOptions::default();
+        let report = detect_torus(&section, &options).unwrap();
+        assert!(report.is_torus(&options), "{:?}", report);
+        assert!((report.rotation_number - golden).abs() < 1e-2, "{:?}", report);
+
+        // 单频驱动的混沌吸引子不保序
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.q = 0.5;
+        params.f_d = 1.2;
+        params.omega_d = 2.0 / 3.0;
+        params.set_steps_per_period(200);
+        params.t_end = 2.0 * PI / params.omega_d * 331.0;
+        let section = poincare(&params, 0.2, 0.0, 30, 300).unwrap();
+        let report = detect_torus(&section, &options).unwrap();
+        assert!(!report.is_torus(&options) && !report.is_locked(&options), "{:?}", report);
+    }
+}
@@ -0,0 +1,470 @@
+//trajectory.rs
+use std::ops::Range;
+use crate::error::ChaosError;
+use crate::model::PendulumParams;
+use crate::solve_equation::{hermite_state, unwrap_angles, winding_number, wrap_angle, State};
+
+// 按列存放的轨迹（struct of arrays）：时间、θ、ω 各占一段连续内存。
+// 只读一列的分析（FFT、直方图、自相关等）直接拿 thetas() / omegas() 切片，不必先从 Vec<(f64, State)>
+// 拷出一列，缓存行里也不夹带用不到的另外两列。slice / window 返回借用原数据的视图，不复制。
+// Trajectory 与行格式并存而不取代它：solve 与大多数分析函数仍收发 Vec<(f64, State)> / &[(f64, State)]，
+// 需要时用 to_rows() 或 From 互相转换。
+// 时间列单调：正向积分升序，TimeDirection::Backward 的轨迹降序（t = 0, -dt, -2dt, ...）；
+// window、evaluate_at、resample 按首末两点判断方向，两种顺序都能处理。
+// θ 列保存展开角（lift，积分器本来就不折叠）；windings 列为每点所在的圈数，θ = wrap_angle(θ) + 2π·winding。
+// 旋转数、转动解的扩散等需要 lift 的分析直接用 θ 列；作图要折叠时用 wrapped_thetas()。
+// 从折叠过的数据（如读入的 CSV）构造的轨迹先调用 unwrap_thetas() 恢复 lift；
+// 两种坐标一起导出用 io::CsvTrajectoryWriter::create_lifted。
+// 积分步长（自适应时还不等距）与分析需要的采样间隔无关：resample(params, dt_out) 用稠密输出插值到均匀网格。
+
+/// 按时间单调排列（升序或降序）的轨迹，各列等长
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Trajectory {
+    times: Vec<f64>,
+    thetas: Vec<f64>,
+    omegas: Vec<f64>,
+    windings: Vec<i64>,
+}
+
+/// Trajectory 的一段连续视图（借用，不复制）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrajectorySlice<'a> {
+    times: &'a [f64],
+    thetas: &'a [f64],
+    omegas: &'a [f64],
+    windings: &'a [i64],
+}
+
+impl Trajectory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(n: usize) -> Self {
+        Trajectory {
+            times: Vec::with_capacity(n),
+            thetas: Vec::with_capacity(n),
+            omegas: Vec::with_capacity(n),
+            windings: Vec::with_capacity(n),
+        }
+    }
+
+    /// 追加一个点；调用方保证时间保持单调（与已有的点同一方向）
+    pub fn push(&mut self, t: f64, state: State) {
+        self.times.push(t);
+        self.thetas.push(state.theta);
+        self.omegas.push(state.omega);
+        self.windings.push(winding_number(state.theta));
+    }
+
+    /// 整条轨迹的视图
+    pub fn as_slice(&self) -> TrajectorySlice<'_> {
+        TrajectorySlice {
+            times: &self.times,
+            thetas: &self.thetas,
+            omegas: &self.omegas,
+            windings: &self.windings,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.times.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.times.is_empty()
+    }
+
+    pub fn times(&self) -> &[f64] {
+        &self.times
+    }
+
+    pub fn thetas(&self) -> &[f64] {
+        &self.thetas
+    }
+
+    pub fn omegas(&self) -> &[f64] {
+        &self.omegas
+    }
+
+    /// 每点所在的圈数（见 solve_equation::winding_number）
+    pub fn windings(&self) -> &[i64] {
+        &self.windings
+    }
+
+    /// 折叠到 [-π, π) 的 θ
+    pub fn wrapped_thetas(&self) -> Vec<f64> {
+        self.as_slice().wrapped_thetas()
+    }
+
+    /// θ 列由折叠过的角度恢复为连续的 lift，并重算圈数；要求相邻两点间转过不到半圈
+    pub fn unwrap_thetas(&mut self) {
+        self.thetas = unwrap_angles(&self.thetas);
+        self.windings = self.thetas.iter().map(|&theta| winding_number(theta)).collect();
+    }
+
+    pub fn get(&self, i: usize) -> Option<(f64, State)> {
+        self.as_slice().get(i)
+    }
+
+    pub fn first(&self) -> Option<(f64, State)> {
+        self.get(0)
+    }
+
+    pub fn last(&self) -> Option<(f64, State)> {
+        self.len().checked_sub(1).and_then(|i| self.get(i))
+    }
+
+    /// 依次给出 (t, State)
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (f64, State)> + '_ {
+        self.as_slice().iter()
+    }
+
+    /// 下标区间 range 上的视图；越界时 panic，与切片下标一致
+    pub fn slice(&self, range: Range<usize>) -> TrajectorySlice<'_> {
+        self.as_slice().slice(range)
+    }
+
+    /// 时间落在 [t_start, t_end] 内的点组成的视图，保持原来的时间顺序（不插值端点，见 solve_equation::slice_by_time）
+    pub fn window(&self, t_start: f64, t_end: f64) -> TrajectorySlice<'_> {
+        self.as_slice().window(t_start, t_end)
+    }
+
+    /// 任意时刻 t 的状态（三次 Hermite 稠密输出，θ 不折叠）；t 超出时间范围时返回 None
+    pub fn evaluate_at(&self, params: &PendulumParams, t: f64) -> Option<State> {
+        self.as_slice().evaluate_at(params, t)
+    }
+
+    /// 在均匀时间网格上重采样（三次 Hermite 稠密输出），见 TrajectorySlice::resample
+    pub fn resample(&self, params: &PendulumParams, dt_out: f64) -> Result<Trajectory, ChaosError> {
+        self.as_slice().resample(params, dt_out)
+    }
+
+    /// 转成按行存放的 Vec<(f64, State)>，供仍接受行格式的分析函数使用
+    pub fn to_rows(&self) -> Vec<(f64, State)> {
+        self.iter().collect()
+    }
+}
+
+impl<'a> TrajectorySlice<'a> {
+    pub fn len(&self) -> usize {
+        self.times.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.times.is_empty()
+    }
+
+    pub fn times(&self) -> &'a [f64] {
+        self.times
+    }
+
+    pub fn thetas(&self) -> &'a [f64] {
+        self.thetas
+    }
+
+    pub fn omegas(&self) -> &'a [f64] {
+        self.omegas
+    }
+
+    pub fn windings(&self) -> &'a [i64] {
+        self.windings
+    }
+
+    pub fn wrapped_thetas(&self) -> Vec<f64> {
+        self.thetas.iter().map(|&theta| wrap_angle(theta)).collect()
+    }
+
+    pub fn get(&self, i: usize) -> Option<(f64, State)> {
+        Some((
+            *self.times.get(i)?,
+            State {
+                theta: self.thetas[i],
+                omega: self.omegas[i],
+            },
+        ))
+    }
+
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (f64, State)> + use<'a> {
+        let (thetas, omegas) = (self.thetas, self.omegas);
+        self.times
+            .iter()
+            .enumerate()
+            .map(move |(i, &t)| (t, State { theta: thetas[i], omega: omegas[i] }))
+    }
+
+    pub fn slice(&self, range: Range<usize>) -> TrajectorySlice<'a> {
+        TrajectorySlice {
+            times: &self.times[range.clone()],
+            thetas: &self.thetas[range.clone()],
+            omegas: &self.omegas[range.clone()],
+            windings: &self.windings[range],
+        }
+    }
+
+    // 时间列是否降序（逆向积分）；少于两个点时视为升序
+    fn descending(&self) -> bool {
+        matches!((self.times.first(), self.times.last()), (Some(first), Some(last)) if last < first)
+    }
+
+    pub fn window(&self, t_start: f64, t_end: f64) -> TrajectorySlice<'a> {
+        let (start, end) = if self.descending() {
+            let start = self.times.partition_point(|t| *t > t_end);
+            (start, self.times.partition_point(|t| *t >= t_start).max(start))
+        } else {
+            let start = self.times.partition_point(|t| *t < t_start);
+            (start, self.times.partition_point(|t| *t <= t_end).max(start))
+        };
+        self.slice(start..end)
+    }
+
+    pub fn evaluate_at(&self, params: &PendulumParams, t: f64) -> Option<State> {
+        let descending = self.descending();
+        let i = if descending {
+            self.times.partition_point(|ti| *ti > t)
+        } else {
+            self.times.partition_point(|ti| *ti < t)
+        };
+        let (t2, s2) = self.get(i)?;
+        if t2 == t {
+            return Some(s2);
+        }
+        let (t1, s1) = self.get(i.checked_sub(1)?)?;
+        // hermite_state 要求第一个点在前
+        Some(if descending {
+            hermite_state(t2, &s2, t1, &s1, t, params)
+        } else {
+            hermite_state(t1, &s1, t2, &s2, t, params)
+        })
+    }
+
+    /// 在均匀时间网格 t_0, t_0 ± dt_out, t_0 ± 2 dt_out, ...（不越过最后一个时间点）上用三次 Hermite 稠密输出重采样，θ 保持 lift
+    ///
+    /// 输出步长与积分步长无关：自适应步长的轨迹也能得到 FFT、自相关需要的等距序列，
+    /// 结果的 thetas() / omegas() 直接以 dt_out 为采样间隔交给 spectrum、timeseries。
+    /// 网格沿轨迹的时间方向前进（逆向积分的轨迹时间递减，网格也递减）；
+    /// 第 k 个时刻取 t_0 ± k·dt_out（不累加），dt_out 不是正的有限数时返回 NonPositiveDt；空轨迹返回空轨迹。
+    pub fn resample(&self, params: &PendulumParams, dt_out: f64) -> Result<Trajectory, ChaosError> {
+        if !(dt_out > 0.0 && dt_out.is_finite()) {
+            return Err(ChaosError::NonPositiveDt(dt_out));
+        }
+        let (Some(&t0), Some(&t_last)) = (self.times.first(), self.times.last()) else {
+            return Ok(Trajectory::new());
+        };
+        let descending = self.descending();
+        let step = if descending { -dt_out } else { dt_out };
+        // 容许舍入误差让最后一个网格点略越过 t_last
+        let n = ((t_last - t0).abs() / dt_out + 1e-9).floor() as usize + 1;
+        let mut out = Trajectory::with_capacity(n);
+        let mut i = 0;
+        for k in 0..n {
+            let t = t0 + k as f64 * step;
+            let t = if descending { t.max(t_last) } else { t.min(t_last) };
+            // 单向游标：t 位于 times[i] 与 times[i + 1] 之间
+            while i + 1 < self.len() && (if descending { self.times[i + 1] > t } else { self.times[i + 1] < t }) {
+                i += 1;
+            }
+            let state = match (self.get(i), self.get(i + 1)) {
+                (Some((t1, s1)), _) if t1 == t => s1,
+                (Some((t1, s1)), Some((t2, s2))) if descending => hermite_state(t2, &s2, t1, &s1, t, params),
+                (Some((t1, s1)), Some((t2, s2))) => hermite_state(t1, &s1, t2, &s2, t, params),
+                (Some((_, s1)), None) => s1,
+                (None, _) => unreachable!("cursor stays inside a non-empty trajectory"),
+            };
+            out.push(t, state);
+        }
+        Ok(out)
+    }
+
+    /// 复制成独立的 Trajectory
+    pub fn to_trajectory(&self) -> Trajectory {
+        Trajectory {
+            times: self.times.to_vec(),
+            thetas: self.thetas.to_vec(),
+            omegas: self.omegas.to_vec(),
+            windings: self.windings.to_vec(),
+        }
+    }
+}
+
+impl FromIterator<(f64, State)> for Trajectory {
+    fn from_iter<I: IntoIterator<Item = (f64, State)>>(iter: I) -> Self {
+        let mut traj = Trajectory::new();
+        traj.extend(iter);
+        traj
+    }
+}
+
+impl Extend<(f64, State)> for Trajectory {
+    fn extend<I: IntoIterator<Item = (f64, State)>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        let (lower, _) = iter.size_hint();
+        self.times.reserve(lower);
+        self.thetas.reserve(lower);
+        self.omegas.reserve(lower);
+        self.windings.reserve(lower);
+        for (t, s) in iter {
+            self.push(t, s);
+        }
+    }
+}
+
+impl From<&[(f64, State)]> for Trajectory {
+    fn from(rows: &[(f64, State)]) -> Self {
+        rows.iter().copied().collect()
+    }
+}
+
+impl From<Vec<(f64, State)>> for Trajectory {
+    fn from(rows: Vec<(f64, State)>) -> Self {
+        rows.as_slice().into()
+    }
+}
+
+impl From<Trajectory> for Vec<(f64, State)> {
+    fn from(traj: Trajectory) -> Self {
+        traj.to_rows()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solve_equation::{evaluate_at, solve, solve_trajectory};
+    use crate::spectrum::{power_spectrum_windowed, theta_power_spectrum, Window};
+
+    #[test]
+    fn test_trajectory_columns_match_rows() {
+        let mut params = PendulumParams::new();
+        params.t_end = 20.0;
+        params.dt = 0.05;
+        let rows = solve(&params, 0.2, 0.0).unwrap();
+        let traj = solve_trajectory(&params, 0.2, 0.0).unwrap();
+        assert_eq!(traj.len(), rows.len());
+        assert_eq!(traj.to_rows(), rows);
+        assert_eq!(Trajectory::from(rows.as_slice()), traj);
+        assert_eq!(traj.last(), rows.last().copied());
+
+        // 视图借用原数据，不复制
+        let view = traj.slice(10..20);
+        assert_eq!(view.len(), 10);
+        assert!(std::ptr::eq(view.thetas().as_ptr(), &traj.thetas()[10]));
+        assert_eq!(view.get(0), traj.get(10));
+        let window = traj.window(0.99, 2.01);
+        assert!(window.times().iter().all(|t| (0.99..=2.01).contains(t)));
+        assert_eq!(window.len(), 21);
+
+        for t in [0.0, 0.123, 7.77, 19.99] {
+            assert_eq!(traj.evaluate_at(&params, t), evaluate_at(&rows, &params, t));
+        }
+        assert_eq!(traj.evaluate_at(&params, 25.0), None);
+
+        // 列切片直接做 FFT，与行格式的结果相同
+        let direct = power_spectrum_windowed(traj.thetas(), params.dt, Window::Hann);
+        assert_eq!(direct, theta_power_spectrum(&rows, params.dt, Window::Hann));
+    }
+
+    #[test]
+    fn test_resample_adaptive_trajectory_to_uniform_grid() {
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.q = 0.5;
+        params.f_d = 0.5;
+        params.omega_d = 2.0 / 3.0;
+        params.t_end = 60.0;
+        params.dt = 0.05;
+        params.step_control = crate::model::StepControl::Adaptive;
+        params.atol = 1e-10;
+        params.rtol = 1e-10;
+        let adaptive = solve_trajectory(&params, 0.2, 0.0).unwrap();
+        let steps: Vec<f64> = adaptive.times().windows(2).map(|w| w[1] - w[0]).collect();
+        assert!(steps.iter().any(|h| (h - steps[0]).abs() > 1e-6), "adaptive steps should vary");
+
+        let uniform = adaptive.resample(&params, 0.1).unwrap();
+        assert_eq!(uniform.len(), 601);
+        assert!(uniform.times().iter().enumerate().all(|(k, t)| (t - k as f64 * 0.1).abs() < 1e-9));
+        // 与细步长定步长积分在同一时刻的值比较
+        params.step_control = crate::model::StepControl::FixedDt;
+        params.dt = 0.001;
+        let reference = solve(&params, 0.2, 0.0).unwrap();
+        for (t, s) in uniform.iter().step_by(37) {
+            let r = evaluate_at(&reference, &params, t).unwrap();
+            assert!((s.theta - r.theta).abs() < 1e-5 && (s.omega - r.omega).abs() < 1e-5, "t = {}: {:?} vs {:?}", t, s, r);
+        }
+        assert_eq!(uniform.resample(&params, 0.1).unwrap(), uniform);
+        // 等距序列的频谱峰在驱动频率 Ω/2π 附近（过渡期后）
+        let steady = uniform.window(20.0, 60.0).to_trajectory();
+        let spectrum = power_spectrum_windowed(steady.thetas(), 0.1, Window::Hann);
+        let peak = spectrum.iter().skip(1).max_by(|a, b| a.1.total_cmp(&b.1)).unwrap().0;
+        assert!((peak - params.omega_d / (2.0 * std::f64::consts::PI)).abs() < 0.03, "{}", peak);
+        assert!(matches!(uniform.resample(&params, 0.0), Err(ChaosError::NonPositiveDt(_))));
+        assert!(Trajectory::new().resample(&params, 0.1).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_backward_trajectory_lookup() {
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.q = 0.5;
+        params.f_d = 1.2;
+        params.omega_d = 2.0 / 3.0;
+        params.t_end = 5.0;
+        params.dt = 0.05;
+        params.direction = crate::model::TimeDirection::Backward;
+        let traj = solve_trajectory(&params, 0.2, 0.0).unwrap();
+        assert!(traj.times().windows(2).all(|w| w[1] < w[0]));
+
+        let window = traj.window(-2.01, -0.99);
+        assert_eq!(window.len(), 21);
+        assert!(window.times().iter().all(|t| (-2.01..=-0.99).contains(t)));
+        assert!(window.times()[0] > window.times()[20]);
+
+        // 网格点上取原值，网格之间与步长减半的逆向积分一致
+        let (t20, s20) = traj.get(20).unwrap();
+        assert_eq!(traj.evaluate_at(&params, t20), Some(s20));
+        params.dt = 0.025;
+        let fine = solve_trajectory(&params, 0.2, 0.0).unwrap();
+        let (t, reference) = fine.get(41).unwrap();
+        let s = traj.evaluate_at(&params, t).unwrap();
+        assert!((s.theta - reference.theta).abs() < 1e-5 && (s.omega - reference.omega).abs() < 1e-5, "{:?} vs {:?}", s, reference);
+        assert_eq!(traj.evaluate_at(&params, 0.1), None);
+        assert_eq!(traj.evaluate_at(&params, -5.1), None);
+
+        // 重采样网格沿时间递减方向前进，覆盖整条轨迹
+        let resampled = traj.resample(&params, 0.1).unwrap();
+        assert_eq!(resampled.len(), 51);
+        assert!(resampled.times().windows(2).all(|w| (w[0] - w[1] - 0.1).abs() < 1e-12));
+        for (t, s) in resampled.iter() {
+            assert_eq!(traj.evaluate_at(&params, t), Some(s));
+        }
+    }
+
+    #[test]
+    fn test_lift_survives_wrapping() {
+        // 无阻尼无驱动、初速度超过分界线（2√(g/l)）：一直朝一个方向转动
+        let mut params = PendulumParams::new();
+        params.q = 0.0;
+        params.f_d = 0.0;
+        params.dt = 0.01;
+        params.t_end = 50.0;
+        let traj = solve_trajectory(&params, 0.0, 8.0).unwrap();
+        let turns = *traj.windings().last().unwrap();
+        assert!(turns >= 5, "{}", turns);
+        assert!(traj.windings().windows(2).all(|w| w[1] >= w[0]));
+        let wrapped = traj.wrapped_thetas();
+        for (i, theta) in traj.thetas().iter().enumerate() {
+            assert!((-std::f64::consts::PI..std::f64::consts::PI).contains(&wrapped[i]));
+            assert!((wrapped[i] + 2.0 * std::f64::consts::PI * traj.windings()[i] as f64 - theta).abs() < 1e-9);
+        }
+
+        // 只有折叠角时恢复出原来的 lift 与圈数
+        let mut folded: Trajectory = traj
+            .iter()
+            .map(|(t, s)| (t, State { theta: crate::solve_equation::wrap_angle(s.theta), omega: s.omega }))
+            .collect();
+        assert_eq!(*folded.windings().last().unwrap(), 0);
+        folded.unwrap_thetas();
+        assert_eq!(folded.windings(), traj.windings());
+        assert!(folded.thetas().iter().zip(traj.thetas()).all(|(a, b)| (a - b).abs() < 1e-9));
+    }
+}
@@ -0,0 +1,206 @@
+//transient.rs
+use std::f64::consts::PI;
+use crate::basin::section_distance;
+use crate::error::ChaosError;
+use crate::model::{PendulumParams, StepControl};
+use crate::solve_equation::{steps_per_period, wrap_angle, TrajectoryIter};
+
+// 自动估计过渡期，代替手工猜的 transient_periods：边积分边收集庞加莱点，按 window 个点分块，
+// 用窗口化的最近邻回归判断何时落到了吸引子上。
+// 对候选块 A = [k, k + w)，参考集 R = [k + w, k + 3w)：
+//   drift   = A 中每点到 R 的最近距离（仍在向吸引子漂移的点距离明显偏大）
+//   spacing = R 后半段各点到前半段的最近距离的中位数（吸引子上点集的典型间距，周期轨道约为 0）
+// A 中至少 recurrence_fraction 的点满足 drift <= max(tolerance, spacing_factor · spacing) 时认为 A 已落在吸引子上；
+// 再从前一块开始找第一段连续 window/5 个点都满足阈值的位置，过渡期精确到一个周期。
+// 周期轨道的阈值取 tolerance（与 basin::classify_basin 的周期判定同一量级），混沌吸引子取点集间距的若干倍。
+
+/// 过渡期检测的设置
+#[derive(Debug, Clone)]
+pub struct TransientOptions {
+    /// 每块的庞加莱点数
+    pub window: usize,
+    /// 绝对距离阈值（周期轨道）
+    pub tolerance: f64,
+    /// 相对吸引子点集典型间距的阈值倍数（混沌吸引子）
+    pub spacing_factor: f64,
+    /// 块中至少有这个比例的点回到参考集附近才算落到吸引子上（混沌吸引子上总有少数点离参考集稍远）
+    pub recurrence_fraction: f64,
+    /// 至多检查的过渡周期数，超过时放弃
+    pub max_periods: usize,
+}
+
+impl Default for TransientOptions {
+    fn default() -> Self {
+        TransientOptions {
+            window: 50,
+            tolerance: 1e-3,
+            spacing_factor: 3.0,
+            recurrence_fraction: 0.9,
+            max_periods: 10_000,
+        }
+    }
+}
+
+/// 检测结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransientEstimate {
+    /// 应丢弃的过渡周期数
+    pub transient_periods: usize,
+    /// 是否在 max_periods 内判定为落到吸引子上；为 false 时 transient_periods = max_periods
+    pub settled: bool,
+}
+
+// 点 p 到点集 set 的最近距离
+fn nearest(p: (f64, f64), set: &[(f64, f64)]) -> f64 {
+    set.iter().map(|q| section_distance(p, *q)).fold(f64::INFINITY, f64::min)
+}
+
+// 以 start 开头的块的判定阈值；参考集不足时返回 None
+fn threshold(samples: &[(f64, f64)], start: usize, opts: &TransientOptions) -> Option<f64> {
+    let w = opts.window;
+    let reference = samples.get(start + w..start + 3 * w)?;
+    let (first, second) = reference.split_at(w);
+    let mut gaps: Vec<f64> = second.iter().map(|p| nearest(*p, first)).collect();
+    gaps.sort_by(f64::total_cmp);
+    Some(opts.tolerance.max(opts.spacing_factor * gaps[w / 2]))
+}
+
+/// 在已有的庞加莱点序列上检测过渡期；点数不足以判定时返回 None
+///
+/// 至少需要 3·window 个点；判定为落到吸引子上时返回应丢弃的前导点数。
+pub fn detect_transient(samples: &[(f64, f64)], opts: &TransientOptions) -> Option<usize> {
+    let w = opts.window.max(1);
+    scan_blocks(samples, &mut 0, &TransientOptions { window: w, ..opts.clone() })
+}
+
+// 从 *start 起逐块判定，直到某块落到吸引子上或参考集不足；*start 停在下一个待判定的块，
+// 之后点数变多时可以接着判定，已判定为过渡的块不再重算
+fn scan_blocks(samples: &[(f64, f64)], start: &mut usize, opts: &TransientOptions) -> Option<usize> {
+    let w = opts.window;
+    while let Some(limit) = threshold(samples, *start, opts) {
+        let reference = &samples[*start + w..*start + 3 * w];
+        let recurrent = samples[*start..*start + w].iter().filter(|p| nearest(**p, reference) <= limit).count();
+        let settled = recurrent as f64 >= opts.recurrence_fraction * w as f64;
+        if settled {
+            // 从前一块开始找第一段连续 w/5 个都回到参考集附近的点，其起点之前为过渡期
+            let from = start.saturating_sub(w);
+            let close: Vec<bool> = samples[from..*start + w].iter().map(|p| nearest(*p, reference) <= limit).collect();
+            let run = (w / 5).max(1);
+            let first = close.windows(run).position(|win| win.iter().all(|c| *c)).unwrap_or(close.len());
+            return Some(from + first);
+        }
+        *start += w;
+    }
+    None
+}
+
+// 按每周期 spp 步积分的频闪点迭代器，第 n 个（从 0 起）为 t = (n + 1)T 处的 (θ, ω)，θ 折叠到 [-π, π)，
+// 与 poincare 的约定一致：丢弃前 k 个点即 transient_periods = k
+fn section_points(
+    params: &PendulumParams,
+    theta0: f64,
+    omega0: f64,
+    periods: usize,
+) -> Result<impl Iterator<Item = (f64, f64)>, ChaosError> {
+    params.validate_periodic()?;
+    if params.noise_intensity > 0.0 || params.step_control == StepControl::Adaptive {
+        return Err(ChaosError::InvalidParams(
+            "transient detection needs deterministic fixed-step integration".to_string(),
+        ));
+    }
+    let spp = steps_per_period(params);
+    let mut p = params.clone();
+    p.set_steps_per_period(spp);
+    p.t_end = periods as f64 * 2.0 * PI / p.omega_d + 0.5 * p.dt;
+    Ok(TrajectoryIter::new(&p, theta0, omega0)
+        .step_by(spp)
+        .skip(1)
+        .map(|(_, s)| (wrap_angle(s.theta), s.omega)))
+}
+
+/// 从 (theta0, omega0) 积分，直到检测到落在吸引子上或积分了 max_periods + 3·window 个周期
+pub fn estimate_transient(
+    params: &PendulumParams,
+    theta0: f64,
+    omega0: f64,
+    opts: &TransientOptions,
+) -> Result<TransientEstimate, ChaosError> {
+    Ok(poincare_auto_transient(params, theta0, omega0, 0, opts)?.1)
+}
+
+/// 自动确定过渡期后采样 sample_periods 个庞加莱点，返回（点, 检测结果）
+///
+/// 检测时已经积分过的点直接复用，只补算不足的部分。未落到吸引子上时丢弃 max_periods 个周期后照常采样。
+pub fn poincare_auto_transient(
+    params: &PendulumParams,
+    theta0: f64,
+    omega0: f64,
+    sample_periods: usize,
+    opts: &TransientOptions,
+) -> Result<(Vec<(f64, f64)>, TransientEstimate), ChaosError> {
+    let w = opts.window.max(1);
+    let opts = &TransientOptions { window: w, ..opts.clone() };
+    let total = opts.max_periods + (3 * w).max(sample_periods);
+    let mut points = section_points(params, theta0, omega0, total)?;
+    let mut samples: Vec<(f64, f64)> = Vec::new();
+    let mut estimate = None;
+    // 每攒满一块接着上次停下的块判定，每块只判定一次
+    let mut next_block = 0;
+    while estimate.is_none() && samples.len() < opts.max_periods + 3 * w {
+        samples.extend(points.by_ref().take(w));
+        estimate = scan_blocks(&samples, &mut next_block, opts).filter(|n| *n <= opts.max_periods);
+    }
+    let estimate = match estimate {
+        Some(n) => TransientEstimate { transient_periods: n, settled: true },
+        None => TransientEstimate { transient_periods: opts.max_periods, settled: false },
+    };
+    let needed = estimate.transient_periods + sample_periods;
+    if samples.len() < needed {
+        samples.extend(points.take(needed - samples.len()));
+    }
+    samples.truncate(needed);
+    samples.drain(..estimate.transient_periods);
+    Ok((samples, estimate))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solve_equation::poincare;
+
+    #[test]
+    fn test_detects_transient_for_periodic_and_chaotic_motion() {
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.q = 0.5;
+        params.omega_d = 2.0 / 3.0;
+        params.set_steps_per_period(100);
+        let opts = TransientOptions::default();
+
+        // 周期 1 摆动：从远处出发，过渡期在几十个周期内结束，之后的点收敛到一个不动点
+        params.f_d = 0.5;
+        let (points, estimate) = poincare_auto_transient(&params, 2.5, 1.0, 20, &opts).unwrap();
+        assert!(estimate.settled);
+        assert!(estimate.transient_periods > 0 && estimate.transient_periods < 150);
+        assert_eq!(points.len(), 20);
+        assert!(points.iter().all(|p| section_distance(*p, points[19]) <= opts.tolerance));
+        // 采样与按检测出的过渡期直接调用 poincare 一致
+        params.t_end = (estimate.transient_periods + 21) as f64 * 2.0 * PI / params.omega_d;
+        let direct = poincare(&params, 2.5, 1.0, estimate.transient_periods, 20).unwrap();
+        for (a, b) in points.iter().zip(&direct) {
+            assert!(section_distance(*a, *b) < 1e-9);
+        }
+
+        // 混沌吸引子：也能判定，且不会把全部 max_periods 都丢掉
+        params.f_d = 1.2;
+        let estimate = estimate_transient(&params, 0.2, 0.0, &opts).unwrap();
+        assert!(estimate.settled);
+        assert!(estimate.transient_periods < 500);
+        // 逐块增量判定与一次性在整段点序列上判定结果相同
+        let all: Vec<_> = section_points(&params, 0.2, 0.0, 1000).unwrap().collect();
+        assert_eq!(detect_transient(&all, &opts), Some(estimate.transient_periods));
+
+        // 点数不足时不下结论
+        assert_eq!(detect_transient(&points, &opts), None);
+    }
+}
@@ -0,0 +1,311 @@
+//tui.rs
+use std::time::{Duration, Instant};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::symbols::Marker;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::canvas::{Canvas, Points};
+use ratatui::widgets::{Block, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use crate::model::{PendulumParams, StepControl};
+use crate::solve_equation::{poincare, solve_periods, steps_per_period, wrap_angle};
+
+// 终端里的交互式参数探索：画布用盲文点阵（每个字符 2×4 个点）画庞加莱截面或相图，
+// 方向键改 f_d、q、omega_d 后立即重新积分。每次重算都从同一初值出发、丢弃同样多的过渡周期，
+// 课堂上可以一格一格地把 f_d 从 1.0 调到 1.5，看倍周期级联与混沌的出现。
+// 计算在界面线程里同步完成，默认的采样量下每次重算在几十毫秒内。
+
+/// 画布显示的内容
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExplorerView {
+    /// 频闪采样的庞加莱截面
+    Poincare,
+    /// 过渡期之后若干个驱动周期的 (θ, ω) 轨道
+    PhasePortrait,
+}
+
+impl ExplorerView {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExplorerView::Poincare => "poincare",
+            ExplorerView::PhasePortrait => "phase portrait",
+        }
+    }
+}
+
+/// 可用方向键调节的参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Knob {
+    DriveAmplitude,
+    Damping,
+    DriveFrequency,
+}
+
+impl Knob {
+    const ALL: [Knob; 3] = [Knob::DriveAmplitude, Knob::Damping, Knob::DriveFrequency];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Knob::DriveAmplitude => "f_d",
+            Knob::Damping => "q",
+            Knob::DriveFrequency => "omega_d",
+        }
+    }
+}
+
+/// 按键处理的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyAction {
+    /// 参数或视图变了，需要重算
+    Recompute,
+    /// 只需重画（选中的参数、步长变了）
+    Redraw,
+    Quit,
+    Ignore,
+}
+
+/// 探索器的状态：当前参数、视图与最近一次计算的点
+pub struct Explorer {
+    pub params: PendulumParams,
+    pub theta0: f64,
+    pub omega0: f64,
+    pub view: ExplorerView,
+    pub transient_periods: usize,
+    /// 庞加莱截面的采样周期数
+    pub sample_periods: usize,
+    /// 相图显示的驱动周期数
+    pub portrait_periods: usize,
+    pub selected: Knob,
+    /// ←/→ 每按一次参数的改变量
+    pub step: f64,
+    points: Vec<(f64, f64)>,
+    status: String,
+}
+
+impl Explorer {
+    pub fn new(params: PendulumParams, theta0: f64, omega0: f64) -> Self {
+        Explorer {
+            params,
+            theta0,
+            omega0,
+            view: ExplorerView::Poincare,
+            transient_periods: 100,
+            sample_periods: 1000,
+            portrait_periods: 20,
+            selected: Knob::DriveAmplitude,
+            step: 0.01,
+            points: Vec::new(),
+            status: String::new(),
+        }
+    }
+
+    /// 最近一次计算的点 (θ, ω)，θ 折叠到 [-π, π)
+    pub fn points(&self) -> &[(f64, f64)] {
+        &self.points
+    }
+
+    pub fn value(&self, knob: Knob) -> f64 {
+        match knob {
+            Knob::DriveAmplitude => self.params.f_d,
+            Knob::Damping => self.params.q,
+            Knob::DriveFrequency => self.params.omega_d,
+        }
+    }
+
+    /// 选中的参数改变 direction 个步长；q 不小于 0，omega_d 不小于一个步长
+    pub fn nudge(&mut self, direction: f64) {
+        let delta = direction * self.step;
+        match self.selected {
+            Knob::DriveAmplitude => self.params.f_d += delta,
+            Knob::Damping => self.params.q = (self.params.q + delta).max(0.0),
+            Knob::DriveFrequency => self.params.omega_d = (self.params.omega_d + delta).max(self.step),
+        }
+    }
+
+    /// ↑/↓ 选参数，←/→ 调节，[ / ] 步长缩小 / 放大 10 倍，v 切换视图，q 或 Esc 退出
+    pub fn handle_key(&mut self, key: KeyCode) -> KeyAction {
+        let index = Knob::ALL.iter().position(|k| *k == self.selected).unwrap_or(0);
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => KeyAction::Quit,
+            KeyCode::Up => {
+                self.selected = Knob::ALL[(index + Knob::ALL.len() - 1) % Knob::ALL.len()];
+                KeyAction::Redraw
+            }
+            KeyCode::Down => {
+                self.selected = Knob::ALL[(index + 1) % Knob::ALL.len()];
+                KeyAction::Redraw
+            }
+            KeyCode::Left => {
+                self.nudge(-1.0);
+                KeyAction::Recompute
+            }
+            KeyCode::Right => {
+                self.nudge(1.0);
+                KeyAction::Recompute
+            }
+            KeyCode::Char('[') => {
+                self.step /= 10.0;
+                KeyAction::Redraw
+            }
+            KeyCode::Char(']') => {
+                self.step *= 10.0;
+                KeyAction::Redraw
+            }
+            KeyCode::Char('v') => {
+                self.view = match self.view {
+                    ExplorerView::Poincare => ExplorerView::PhasePortrait,
+                    ExplorerView::PhasePortrait => ExplorerView::Poincare,
+                };
+                KeyAction::Recompute
+            }
+            _ => KeyAction::Ignore,
+        }
+    }
+
+    /// 按当前参数与视图重新积分；参数无效时清空画布，并在状态行显示错误
+    pub fn recompute(&mut self) {
+        let start = Instant::now();
+        // 改了 omega_d 时每周期步数不变，步长跟着新的周期走
+        if let StepControl::StepsPerPeriod { steps } = self.params.step_control {
+            self.params.set_steps_per_period(steps);
+        }
+        let periods = self.transient_periods + self.sample_periods.max(self.portrait_periods);
+        self.params.t_end = (periods + 1) as f64 * 2.0 * std::f64::consts::PI / self.params.omega_d;
+        let result = match self.view {
+            ExplorerView::Poincare => poincare(&self.params, self.theta0, self.omega0, self.transient_periods, self.sample_periods),
+            ExplorerView::PhasePortrait => {
+                let spp = steps_per_period(&self.params);
+                solve_periods(&self.params, self.theta0, self.omega0, self.transient_periods + self.portrait_periods, spp).map(|traj| {
+                    traj[self.transient_periods * spp..]
+                        .iter()
+                        .map(|(_, s)| (wrap_angle(s.theta), s.omega))
+                        .collect()
+                })
+            }
+        };
+        match result {
+            Ok(points) => {
+                self.points = points;
+                self.status = format!("{} points in {} ms", self.points.len(), start.elapsed().as_millis());
+            }
+            Err(e) => {
+                self.points.clear();
+                self.status = format!("error: {}", e);
+            }
+        }
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [canvas_area, status_area] = Layout::vertical([Constraint::Min(5), Constraint::Length(4)]).areas(frame.area());
+        // ω 轴按当前点自动缩放，至少 [-1, 1]
+        let omega_max = self.points.iter().fold(1.0f64, |m, p| m.max(p.1.abs())) * 1.05;
+        let title = format!(" {} · θ ∈ [-π, π), ω ∈ [{:.2}, {:.2}] ", self.view.name(), -omega_max, omega_max);
+        let canvas = Canvas::default()
+            .block(Block::bordered().title(title))
+            .marker(Marker::Braille)
+            .x_bounds([-std::f64::consts::PI, std::f64::consts::PI])
+            .y_bounds([-omega_max, omega_max])
+            .paint(|ctx| {
+                ctx.draw(&Points {
+                    coords: &self.points,
+                    color: Color::Cyan,
+                })
+            });
+        frame.render_widget(canvas, canvas_area);
+
+        let mut knobs = Vec::new();
+        for knob in Knob::ALL {
+            let text = format!(" {} = {:.4} ", knob.name(), self.value(knob));
+            let style = if knob == self.selected {
+                Style::default().add_modifier(Modifier::REVERSED)
+            } else {
+                Style::default()
+            };
+            knobs.push(Span::styled(text, style));
+        }
+        knobs.push(Span::raw(format!("  step {}", self.step)));
+        let lines = vec![
+            Line::from(knobs),
+            Line::from(self.status.clone()),
+            Line::from("↑↓ select  ←→ adjust  [ ] step ÷10 ×10  v poincare/phase  q quit"),
+        ];
+        frame.render_widget(Paragraph::new(lines).block(Block::bordered()), status_area);
+    }
+
+    // 重画、等待按键，直到退出
+    fn event_loop(&mut self, terminal: &mut DefaultTerminal) -> std::io::Result<()> {
+        self.recompute();
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            if !event::poll(Duration::from_millis(250))? {
+                continue;
+            }
+            if let Event::Key(key) = event::read()?
+                && key.kind == KeyEventKind::Press
+            {
+                match self.handle_key(key.code) {
+                    KeyAction::Quit => return Ok(()),
+                    KeyAction::Recompute => self.recompute(),
+                    KeyAction::Redraw | KeyAction::Ignore => {}
+                }
+            }
+        }
+    }
+}
+
+/// 进入全屏界面，退出时恢复终端，返回退出时的探索器（可读取最后的参数）
+pub fn run_explorer(mut explorer: Explorer) -> std::io::Result<Explorer> {
+    ratatui::run(|terminal| explorer.event_loop(terminal))?;
+    Ok(explorer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    #[test]
+    fn test_keys_adjust_parameters_and_redraw() {
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.q = 0.5;
+        params.f_d = 1.2;
+        params.omega_d = 2.0 / 3.0;
+        params.set_steps_per_period(100);
+        let mut explorer = Explorer::new(params, 0.2, 0.0);
+        explorer.transient_periods = 10;
+        explorer.sample_periods = 50;
+        explorer.recompute();
+        assert_eq!(explorer.points().len(), 50);
+
+        assert_eq!(explorer.handle_key(KeyCode::Right), KeyAction::Recompute);
+        assert!((explorer.params.f_d - 1.21).abs() < 1e-12);
+        assert_eq!(explorer.handle_key(KeyCode::Down), KeyAction::Redraw);
+        assert_eq!(explorer.selected, Knob::Damping);
+        explorer.handle_key(KeyCode::Char(']'));
+        for _ in 0..10 {
+            explorer.handle_key(KeyCode::Left);
+        }
+        assert_eq!(explorer.params.q, 0.0);
+        assert_eq!(explorer.handle_key(KeyCode::Up), KeyAction::Redraw);
+        assert_eq!(explorer.handle_key(KeyCode::Up), KeyAction::Redraw);
+        assert_eq!(explorer.selected, Knob::DriveFrequency);
+
+        // 相图的点数为 portrait_periods 个周期的积分步数加一
+        explorer.handle_key(KeyCode::Char('v'));
+        explorer.portrait_periods = 2;
+        explorer.recompute();
+        assert_eq!(explorer.points().len(), 201);
+        assert!(explorer.points().iter().all(|p| (-std::f64::consts::PI..std::f64::consts::PI).contains(&p.0)));
+
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        terminal.draw(|frame| explorer.draw(frame)).unwrap();
+        let screen: String = terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+        assert!(screen.contains("phase portrait") && screen.contains("omega_d = 0.6667"));
+        // 盲文点阵字符在 U+2800..U+28FF
+        assert!(screen.chars().any(|c| ('\u{2801}'..='\u{28ff}').contains(&c)));
+        assert_eq!(explorer.handle_key(KeyCode::Esc), KeyAction::Quit);
+    }
+}
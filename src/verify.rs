@@ -0,0 +1,257 @@
+//verify.rs
+use std::fs::File;
+use std::io::Write;
+use rayon::prelude::*;
+use crate::error::ChaosError;
+use crate::model::{PendulumParams, StepControl, TimeDirection};
+use crate::solve_equation::{step, State};
+
+// 收敛性检验：同一初值问题用一组步长 h_i 积分到同一时刻 t_end，与参考解比较末状态误差 e_i。
+// p 阶方法的误差 e ≈ C h^p，相邻步长给出观测阶 p_i = ln(e_i / e_{i+1}) / ln(h_i / h_{i+1})，
+// 所有点在 log e – log h 上的最小二乘斜率是整体的观测阶。参考解可以是最细步长的结果
+// （这时它本身的误差被忽略，最细的几档步长观测阶会偏高），也可以是小角度线性化的解析解。
+// Richardson 外推 y* = y_h/2 + (y_h/2 - y_h) / (2^p - 1) 用观测阶把最细两档结果外推到 h → 0。
+
+/// 误差的参考解
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConvergenceReference {
+    /// 以最细步长的数值解为参考
+    #[default]
+    Finest,
+    /// 小角度线性化 θ'' + q θ' + (g/l) θ = 0 的解析解；要求无驱动，初值越小线性化误差越小
+    SmallAngle,
+}
+
+impl ConvergenceReference {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ConvergenceReference::Finest => "finest",
+            ConvergenceReference::SmallAngle => "small_angle",
+        }
+    }
+}
+
+/// 某个步长下的末状态与误差
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConvergenceRow {
+    /// 实际使用的步长 t_end / steps（与给定值最多相差取整）
+    pub dt: f64,
+    pub steps: usize,
+    pub theta: f64,
+    pub omega: f64,
+    /// 末状态与参考解之差的最大范数 max(|Δθ|, |Δω|)
+    pub error: f64,
+    /// 与下一档（更细）步长比较得到的观测阶；最后一档或误差为 0 时为 None
+    pub observed_order: Option<f64>,
+}
+
+/// 收敛性检验的结果，rows 按步长从粗到细排列
+#[derive(Debug, Clone)]
+pub struct ConvergenceStudy {
+    pub reference: ConvergenceReference,
+    pub t_end: f64,
+    /// 参考解在 t_end 的状态
+    pub reference_state: State,
+    pub rows: Vec<ConvergenceRow>,
+}
+
+impl ConvergenceStudy {
+    /// log e – log h 的最小二乘斜率；参与拟合的（误差为正的）点少于两个时返回 None
+    pub fn fitted_order(&self) -> Option<f64> {
+        let points: Vec<(f64, f64)> = self
+            .rows
+            .iter()
+            .filter(|r| r.error > 0.0)
+            .map(|r| (r.dt.ln(), r.error.ln()))
+            .collect();
+        if points.len() < 2 {
+            return None;
+        }
+        let n = points.len() as f64;
+        let mx = points.iter().map(|p| p.0).sum::<f64>() / n;
+        let my = points.iter().map(|p| p.1).sum::<f64>() / n;
+        let sxx: f64 = points.iter().map(|p| (p.0 - mx).powi(2)).sum();
+        let sxy: f64 = points.iter().map(|p| (p.0 - mx) * (p.1 - my)).sum();
+        (sxx > 0.0).then(|| sxy / sxx)
+    }
+
+    /// 用最细两档数值解与给定阶数 order 做 Richardson 外推，估计 h → 0 的末状态
+    pub fn richardson_extrapolation(&self, order: f64) -> Option<State> {
+        let [.., coarse, fine] = self.rows.as_slice() else {
+            return None;
+        };
+        let factor = (coarse.dt / fine.dt).powf(order) - 1.0;
+        Some(State {
+            theta: fine.theta + (fine.theta - coarse.theta) / factor,
+            omega: fine.omega + (fine.omega - coarse.omega) / factor,
+        })
+    }
+}
+
+/// 以最细步长为参考的收敛性检验，见 convergence_against
+pub fn convergence(params: &PendulumParams, initial: State, dts: &[f64]) -> Result<ConvergenceStudy, ChaosError> {
+    convergence_against(params, initial, dts, ConvergenceReference::Finest)
+}
+
+/// 用 params.integrator 以 dts 中的每个步长把 initial 积分到 params.t_end，报告误差与观测阶
+///
+/// 每个步长取整为 t_end / round(t_end / dt)，保证末时刻严格相同；各步长并行计算。
+/// 只支持确定性的固定步长正向积分（Adaptive、噪声与反向积分返回 InvalidParams）；
+/// 以最细步长为参考时它自身不出现在 rows 中，因此 dts 至少要有两个值。
+pub fn convergence_against(
+    params: &PendulumParams,
+    initial: State,
+    dts: &[f64],
+    reference: ConvergenceReference,
+) -> Result<ConvergenceStudy, ChaosError> {
+    params.validate()?;
+    if params.step_control == StepControl::Adaptive || params.noise_intensity > 0.0 || params.direction == TimeDirection::Backward {
+        return Err(ChaosError::InvalidParams(
+            "convergence study needs deterministic fixed-step forward integration".to_string(),
+        ));
+    }
+    if !(params.t_end > 0.0 && params.t_end.is_finite()) {
+        return Err(ChaosError::InvalidParams(format!("t_end must be positive, got {}", params.t_end)));
+    }
+    if let Some(&dt) = dts.iter().find(|dt| !(**dt > 0.0 && dt.is_finite())) {
+        return Err(ChaosError::NonPositiveDt(dt));
+    }
+    let mut steps: Vec<usize> = dts.iter().map(|dt| ((params.t_end / dt).round() as usize).max(1)).collect();
+    steps.sort_unstable();
+    steps.dedup();
+    let finals: Vec<State> = steps.par_iter().map(|&n| integrate_steps(params, initial, n)).collect();
+
+    let (reference_state, compared) = match reference {
+        ConvergenceReference::Finest => {
+            if steps.len() < 2 {
+                return Err(ChaosError::InvalidParams("need at least two distinct step sizes".to_string()));
+            }
+            (finals[finals.len() - 1], steps.len() - 1)
+        }
+        ConvergenceReference::SmallAngle => (small_angle_solution(params, initial, params.t_end)?, steps.len()),
+    };
+    let mut rows: Vec<ConvergenceRow> = steps[..compared]
+        .iter()
+        .zip(&finals)
+        .map(|(&n, s)| ConvergenceRow {
+            dt: params.t_end / n as f64,
+            steps: n,
+            theta: s.theta,
+            omega: s.omega,
+            error: (s.theta - reference_state.theta).abs().max((s.omega - reference_state.omega).abs()),
+            observed_order: None,
+        })
+        .collect();
+    for i in 0..rows.len().saturating_sub(1) {
+        let (a, b) = (rows[i], rows[i + 1]);
+        if a.error > 0.0 && b.error > 0.0 {
+            rows[i].observed_order = Some((a.error / b.error).ln() / (a.dt / b.dt).ln());
+        }
+    }
+    Ok(ConvergenceStudy {
+        reference,
+        t_end: params.t_end,
+        reference_state,
+        rows,
+    })
+}
+
+// 以步长 t_end / n 走 n 步
+fn integrate_steps(params: &PendulumParams, initial: State, n: usize) -> State {
+    let mut p = params.clone();
+    p.dt = params.t_end / n as f64;
+    let mut state = initial;
+    for k in 0..n {
+        state = step(&state, k as f64 * p.dt, &p).0;
+    }
+    state
+}
+
+/// 线性阻尼振子 θ'' + q θ' + ω0² θ = 0 在 t 时刻的解析解（欠阻尼、临界阻尼、过阻尼均可）
+///
+/// 只在无驱动（f_d = 0 且无 f_d_schedule）时作为单摆的小角度近似，否则返回 InvalidParams。
+pub fn small_angle_solution(params: &PendulumParams, initial: State, t: f64) -> Result<State, ChaosError> {
+    if params.f_d != 0.0 || params.f_d_schedule.is_some() || params.q_schedule.is_some() {
+        return Err(ChaosError::InvalidParams(
+            "small-angle reference needs an undriven pendulum with constant damping".to_string(),
+        ));
+    }
+    let w2 = params.g / params.l;
+    let gamma = 0.5 * params.q;
+    let (theta0, omega0) = (initial.theta, initial.omega);
+    let decay = (-gamma * t).exp();
+    let disc = w2 - gamma * gamma;
+    // θ(t) = e^{-γt} [θ0 c(t) + (ω0 + γθ0) s(t)]，c、s 为 cos/cosh 与 sin/sinh 型基本解（s 已除以频率）
+    let (c, s, dc, ds) = if disc > 0.0 {
+        let wd = disc.sqrt();
+        ((wd * t).cos(), (wd * t).sin() / wd, -wd * (wd * t).sin(), (wd * t).cos())
+    } else if disc < 0.0 {
+        let k = (-disc).sqrt();
+        ((k * t).cosh(), (k * t).sinh() / k, k * (k * t).sinh(), (k * t).cosh())
+    } else {
+        (1.0, t, 0.0, 1.0)
+    };
+    let a = theta0;
+    let b = omega0 + gamma * theta0;
+    let u = a * c + b * s;
+    let du = a * dc + b * ds;
+    Ok(State {
+        theta: decay * u,
+        omega: decay * (du - gamma * u),
+    })
+}
+
+/// 把收敛性检验写成 CSV（列：dt,steps,theta,omega,error,observed_order），最后一档的 observed_order 为空
+pub fn write_convergence_csv(path: &str, study: &ConvergenceStudy) -> std::io::Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(f, "dt,steps,theta,omega,error,observed_order")?;
+    for r in &study.rows {
+        let order = r.observed_order.map_or(String::new(), |p| format!("{:.12}", p));
+        writeln!(f, "{:.12},{},{:.12},{:.12},{:.12e},{}", r.dt, r.steps, r.theta, r.omega, r.error, order)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Integrator;
+
+    #[test]
+    fn test_rk4_converges_at_fourth_order() {
+        let mut params = PendulumParams::new();
+        params.l = params.g;
+        params.q = 0.5;
+        params.f_d = 0.5;
+        params.omega_d = 2.0 / 3.0;
+        params.t_end = 10.0;
+        let dts = [0.1, 0.05, 0.025, 0.0125, 0.00625, 0.0015625];
+        let initial = State { theta: 0.2, omega: 0.0 };
+
+        let study = convergence(&params, initial, &dts).unwrap();
+        assert_eq!(study.rows.len(), 5);
+        let order = study.fitted_order().unwrap();
+        assert!((order - 4.0).abs() < 0.1, "{}", order);
+        assert!(study.rows[..3].iter().all(|r| (r.observed_order.unwrap() - 4.0).abs() < 0.3));
+        let extrapolated = study.richardson_extrapolation(4.0).unwrap();
+        assert!((extrapolated.theta - study.reference_state.theta).abs() < study.rows[4].error);
+
+        // 速度 Verlet 在无阻尼时是二阶；阻尼项按半步速度显式处理，q > 0 时降为一阶
+        params.integrator = Integrator::VelocityVerlet;
+        let order = convergence(&params, initial, &dts).unwrap().fitted_order().unwrap();
+        assert!((order - 1.0).abs() < 0.2, "{}", order);
+        params.q = 0.0;
+        let order = convergence(&params, initial, &dts).unwrap().fitted_order().unwrap();
+        assert!((order - 2.0).abs() < 0.1, "{}", order);
+        params.q = 0.5;
+
+        // 小角度无驱动：与线性阻尼振子的解析解比较，θ0 = 1e-4 时线性化误差 ~θ0³ 远小于离散误差
+        params.integrator = Integrator::Rk4;
+        params.f_d = 0.0;
+        let tiny = State { theta: 1e-4, omega: 0.0 };
+        let study = convergence_against(&params, tiny, &[0.4, 0.2, 0.1], ConvergenceReference::SmallAngle).unwrap();
+        assert_eq!(study.rows.len(), 3);
+        assert!((study.fitted_order().unwrap() - 4.0).abs() < 0.3);
+        assert!(convergence(&params, tiny, &[0.1]).is_err());
+    }
+}
@@ -0,0 +1,117 @@
+//zero_one.rs
+use std::f64::consts::PI;
+use rayon::prelude::*;
+use crate::random::RandomSource;
+
+// Gottwald–Melbourne 0–1 混沌检验（相关系数法，Gottwald & Melbourne 2009）：
+// 用标量观测序列 φ(j) 驱动平面上的平移变量 p_c(n) = Σ φ(j) cos(jc)、q_c(n) = Σ φ(j) sin(jc)，
+// 规则运动时 (p, q) 有界，混沌时像布朗运动一样扩散。K_c 是均方位移 D_c(n) 与 n 的相关系数，
+// 对若干随机频率 c 取中位数，K ≈ 0 为规则运动，K ≈ 1 为混沌。
+
+/// 0–1 检验的结果
+#[derive(Debug, Clone)]
+pub struct ZeroOneTest {
+    /// 各 K_c 的中位数
+    pub k: f64,
+    /// 每个频率 c 及其 K_c
+    pub k_values: Vec<(f64, f64)>,
+}
+
+/// 对标量序列做 0–1 检验，频率 c 在 (π/5, 4π/5) 上随机取 n_frequencies 个
+///
+/// series 应是频闪采样的观测量（例如庞加莱点的 ω 或 sin θ），长度至少数千；
+/// 均方位移计算到 n_cut = N/10。过采样（一个振荡周期内采很多点）会让规则运动也得到偏大的 K。
+/// 序列少于 20 个点时返回 None。
+pub fn zero_one_test<R: RandomSource>(series: &[f64], n_frequencies: usize, rng: &mut R) -> Option<ZeroOneTest> {
+    let n = series.len();
+    if n < 20 || n_frequencies == 0 {
+        return None;
+    }
+    let frequencies: Vec<f64> = (0..n_frequencies).map(|_| rng.uniform(PI / 5.0, 4.0 * PI / 5.0)).collect();
+    let mut k_values: Vec<(f64, f64)> = frequencies.par_iter().map(|&c| (c, k_statistic(series, c))).collect();
+    let mut sorted: Vec<f64> = k_values.iter().map(|(_, k)| *k).collect();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let mid = sorted.len() / 2;
+    let k = if sorted.len() % 2 == 1 {
+        sorted[mid]
+    } else {
+        0.5 * (sorted[mid - 1] + sorted[mid])
+    };
+    k_values.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+    Some(ZeroOneTest { k, k_values })
+}
+
+// 单个频率 c 的 K_c：修正均方位移 D_c(n) 与 n（n = 1..n_cut）的相关系数
+fn k_statistic(series: &[f64], c: f64) -> f64 {
+    let n = series.len();
+    let n_cut = n / 10;
+    let mut p = Vec::with_capacity(n);
+    let mut q = Vec::with_capacity(n);
+    let (mut sp, mut sq) = (0.0, 0.0);
+    for (j, phi) in series.iter().enumerate() {
+        let jc = (j + 1) as f64 * c;
+        sp += phi * jc.cos();
+        sq += phi * jc.sin();
+        p.push(sp);
+        q.push(sq);
+    }
+    let mean = series.iter().sum::<f64>() / n as f64;
+
+    // 均方位移只平均到 N - n_cut，各 n 使用相同数目的项
+    let span = n - n_cut;
+    let displacement: Vec<f64> = (1..=n_cut)
+        .map(|k| {
+            let m = (0..span)
+                .map(|j| (p[j + k] - p[j]).powi(2) + (q[j + k] - q[j]).powi(2))
+                .sum::<f64>()
+                / span as f64;
+            // 减去振荡项 (Eφ)² (1 - cos kc) / (1 - cos c)
+            m - mean * mean * (1.0 - (k as f64 * c).cos()) / (1.0 - c.cos())
+        })
+        .collect();
+    correlation(&displacement)
+}
+
+// 序列 y 与 1, 2, ..., len 的 Pearson 相关系数
+fn correlation(y: &[f64]) -> f64 {
+    let n = y.len() as f64;
+    let mean_x = (n + 1.0) / 2.0;
+    let mean_y = y.iter().sum::<f64>() / n;
+    let (mut sxy, mut sxx, mut syy) = (0.0, 0.0, 0.0);
+    for (i, v) in y.iter().enumerate() {
+        let dx = (i + 1) as f64 - mean_x;
+        let dy = v - mean_y;
+        sxy += dx * dy;
+        sxx += dx * dx;
+        syy += dy * dy;
+    }
+    if sxx == 0.0 || syy == 0.0 {
+        0.0
+    } else {
+        sxy / (sxx * syy).sqrt()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::seeded_rng;
+
+    #[test]
+    fn test_zero_one_separates_regular_and_chaotic() {
+        let mut rng = seeded_rng(1);
+        // 准周期序列与 r = 3.97 的 logistic 映射
+        let regular: Vec<f64> = (0..3000).map(|j| (0.9 * j as f64).cos() + 0.5 * (2f64.sqrt() * j as f64).sin()).collect();
+        let mut x = 0.3;
+        let chaotic: Vec<f64> = (0..3000)
+            .map(|_| {
+                x = 3.97 * x * (1.0 - x);
+                x
+            })
+            .collect();
+        let k_regular = zero_one_test(&regular, 20, &mut rng).unwrap().k;
+        let k_chaotic = zero_one_test(&chaotic, 20, &mut rng).unwrap().k;
+        assert!(k_regular < 0.2, "{}", k_regular);
+        assert!(k_chaotic > 0.9, "{}", k_chaotic);
+    }
+}
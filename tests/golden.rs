@@ -0,0 +1,65 @@
+//golden.rs
+// 黄金文件回归测试：经典混沌参数（g/l = 1, q = 0.5, f_d = 1.2, Ω = 2/3）下的庞加莱点与 tests/golden/ 中保存的结果比较。
+// 混沌轨道对舍入误差指数敏感（λ ≈ 0.15，每个驱动周期放大约 e^1.4 倍），只比较前若干个周期，
+// 容差留给不同平台 libm 的末位差异。积分器或采样有意改动后，用
+//   UPDATE_GOLDEN=1 cargo test --test golden
+// 重新生成黄金文件并检查差异。
+use std::f64::consts::PI;
+use chaos::solve_equation::{wrap_angle, write_poincare_samples_csv};
+use chaos::{poincare, PendulumParams};
+
+const TOLERANCE: f64 = 1e-7;
+
+fn canonical_params() -> PendulumParams {
+    let mut params = PendulumParams::new();
+    params.l = params.g;
+    params.q = 0.5;
+    params.f_d = 1.2;
+    params.omega_d = 2.0 / 3.0;
+    params.dt = 2.0 * PI / params.omega_d / 200.0;
+    params.t_end = 20.0 * 2.0 * PI / params.omega_d;
+    params
+}
+
+fn golden_path(name: &str) -> String {
+    format!("{}/tests/golden/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+fn read_samples(path: &str) -> Vec<(f64, f64)> {
+    let text = std::fs::read_to_string(path).unwrap_or_else(|e| panic!("cannot read {}: {}", path, e));
+    text.lines()
+        .skip(1)
+        .map(|line| {
+            let (theta, omega) = line.split_once(',').expect("two columns");
+            (theta.parse().unwrap(), omega.parse().unwrap())
+        })
+        .collect()
+}
+
+// θ 按折叠后的差比较，±π 附近的点不会因落在分界两侧而误报
+fn check_golden(name: &str, samples: &[(f64, f64)]) {
+    let path = golden_path(name);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        write_poincare_samples_csv(&path, samples).unwrap();
+        return;
+    }
+    let golden = read_samples(&path);
+    assert_eq!(samples.len(), golden.len(), "{}: number of samples changed", name);
+    for (n, (a, b)) in samples.iter().zip(&golden).enumerate() {
+        assert!(
+            wrap_angle(a.0 - b.0).abs() < TOLERANCE && (a.1 - b.1).abs() < TOLERANCE,
+            "{}: sample {} is {:?}, golden {:?}",
+            name,
+            n,
+            a,
+            b
+        );
+    }
+}
+
+#[test]
+fn poincare_matches_golden_at_canonical_chaotic_parameters() {
+    let params = canonical_params();
+    let samples = poincare(&params, 0.2, 0.0, 0, 12).unwrap();
+    check_golden("poincare_canonical.csv", &samples);
+}
@@ -0,0 +1,72 @@
+//properties.rs
+// 基于 proptest 的不变量检验：随机生成输入，检查对任意参数都应成立的性质
+// 运行：cargo test --test properties（PROPTEST_CASES 可调整每个性质的用例数）
+use std::f64::consts::PI;
+use chaos::separatrix::pendulum_energy;
+use chaos::solve_equation::{interpolate_sample, interpolate_state, wrap_angle, State, TrajectoryIter};
+use chaos::PendulumParams;
+use proptest::prelude::*;
+
+// 任意大小的角度，外加 ±π 前后几个 ulp 内的值（折叠时最容易舍入出界）
+fn angle() -> impl Strategy<Value = f64> {
+    prop_oneof![
+        -1e6..1e6f64,
+        (-2e-15..2e-15f64).prop_map(|eps| -PI + eps),
+        (-2e-15..2e-15f64).prop_map(|eps| PI + eps),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn wrap_angle_stays_in_half_open_interval(theta in angle()) {
+        let w = wrap_angle(theta);
+        prop_assert!((-PI..PI).contains(&w), "wrap_angle({}) = {}", theta, w);
+        // 与原角度相差 2π 的整数倍
+        let turns = (theta - w) / (2.0 * PI);
+        prop_assert!((turns - turns.round()).abs() < 1e-9 * (1.0 + theta.abs()));
+    }
+
+    #[test]
+    fn linear_interpolation_is_bounded_by_endpoints(
+        t1 in -100.0..100.0f64,
+        h in 1e-6..10.0f64,
+        alpha in 0.0..=1.0f64,
+        theta1 in -10.0..10.0f64,
+        theta2 in -10.0..10.0f64,
+        omega1 in -10.0..10.0f64,
+        omega2 in -10.0..10.0f64,
+    ) {
+        let (s1, s2) = (State { theta: theta1, omega: omega1 }, State { theta: theta2, omega: omega2 });
+        let t = t1 + alpha * h;
+        let s = interpolate_state(t1, &s1, t1 + h, &s2, t);
+        let eps = 1e-12;
+        prop_assert!(s.theta >= theta1.min(theta2) - eps && s.theta <= theta1.max(theta2) + eps);
+        prop_assert!(s.omega >= omega1.min(omega2) - eps && s.omega <= omega1.max(omega2) + eps);
+
+        // 折叠角度的插值：ω 同样有界，θ 落在 [-π, π) 内
+        let (theta, omega) = interpolate_sample(t1, &s1, t1 + h, &s2, t);
+        prop_assert!((-PI..PI).contains(&theta));
+        prop_assert!(omega >= omega1.min(omega2) - eps && omega <= omega1.max(omega2) + eps);
+    }
+
+    #[test]
+    fn energy_decays_monotonically_without_drive(
+        q in 0.05..2.0f64,
+        theta0 in -3.0..3.0f64,
+        omega0 in -3.0..3.0f64,
+    ) {
+        let mut params = PendulumParams::new();
+        params.q = q;
+        params.f_d = 0.0;
+        params.dt = 0.01;
+        params.t_end = 20.0;
+        let energies: Vec<f64> = TrajectoryIter::new(&params, theta0, omega0)
+            .map(|(_, s)| pendulum_energy(s.theta, s.omega, &params))
+            .collect();
+        // dE/dt = -q ω² ≤ 0；ω ≈ 0 的转折点附近耗散极小，留出 RK4 截断误差的余量
+        for w in energies.windows(2) {
+            prop_assert!(w[1] <= w[0] + 1e-10, "energy rose from {} to {}", w[0], w[1]);
+        }
+        prop_assert!(energies[energies.len() - 1] < energies[0] || energies[0] == 0.0);
+    }
+}